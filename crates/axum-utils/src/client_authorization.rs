@@ -4,7 +4,7 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Write as _};
 
 use axum::{
     BoxError, Json,
@@ -14,18 +14,25 @@ use axum::{
     },
     response::IntoResponse,
 };
+use base64ct::{Base64UrlUnpadded, Encoding as _};
+use const_oid::ObjectIdentifier;
+use der::{Decode as _, Encode as _, Reader as _, SliceReader, Tag, Tagged as _, asn1::AnyRef};
 use headers::authorization::{Basic, Bearer, Credentials as _};
-use http::{Request, StatusCode};
+use http::{Extensions, Request, StatusCode};
 use mas_data_model::{Client, JwksOrJwksUri};
 use mas_http::RequestBuilderExt;
 use mas_iana::oauth::OAuthClientAuthenticationMethod;
 use mas_jose::{jwk::PublicJsonWebKeySet, jwt::Jwt};
 use mas_keystore::Encrypter;
+use mas_listener::ConnectionInfo;
 use mas_storage::{RepositoryAccess, oauth2::OAuth2ClientRepository};
 use oauth2_types::errors::{ClientError, ClientErrorCode};
+use rustls_pki_types::CertificateDer;
 use serde::{Deserialize, de::DeserializeOwned};
 use serde_json::Value;
+use sha2::{Digest as _, Sha256};
 use thiserror::Error;
+use webpki::EndEntityCert;
 
 use crate::record_error;
 
@@ -62,6 +69,34 @@ pub enum Credentials {
     BearerToken {
         token: String,
     },
+    ClientCertificate {
+        client_id: String,
+        certificate: Box<CertificateDer<'static>>,
+    },
+}
+
+/// Get the certificate presented during the TLS handshake of the connection a
+/// request was received on, if any
+fn peer_certificate(extensions: &Extensions) -> Option<&CertificateDer<'static>> {
+    extensions
+        .get::<ConnectionInfo>()
+        .and_then(ConnectionInfo::get_tls_ref)
+        .and_then(|tls| tls.peer_certificates.as_ref())
+        .and_then(|certificates| certificates.first())
+}
+
+/// Get the SHA-256 thumbprint of a certificate, encoded as in the `x5t#S256`
+/// confirmation method
+fn certificate_thumbprint(certificate: &CertificateDer<'_>) -> String {
+    Base64UrlUnpadded::encode_string(&Sha256::digest(certificate.as_ref()))
+}
+
+/// Get the SHA-256 thumbprint of the certificate presented during the TLS
+/// handshake of the connection a request was received on, if any, encoded as
+/// in the `x5t#S256` confirmation method
+#[must_use]
+pub fn peer_certificate_thumbprint(extensions: &Extensions) -> Option<String> {
+    peer_certificate(extensions).map(certificate_thumbprint)
 }
 
 impl Credentials {
@@ -72,11 +107,24 @@ impl Credentials {
             Credentials::None { client_id }
             | Credentials::ClientSecretBasic { client_id, .. }
             | Credentials::ClientSecretPost { client_id, .. }
-            | Credentials::ClientAssertionJwtBearer { client_id, .. } => Some(client_id),
+            | Credentials::ClientAssertionJwtBearer { client_id, .. }
+            | Credentials::ClientCertificate { client_id, .. } => Some(client_id),
             Credentials::BearerToken { .. } => None,
         }
     }
 
+    /// Get the SHA-256 thumbprint of the TLS client certificate presented by
+    /// the client, encoded as in the `x5t#S256` confirmation method.
+    #[must_use]
+    pub fn certificate_thumbprint(&self) -> Option<String> {
+        match self {
+            Credentials::ClientCertificate { certificate, .. } => {
+                Some(certificate_thumbprint(certificate))
+            }
+            _ => None,
+        }
+    }
+
     /// Get the bearer token from the credentials.
     #[must_use]
     pub fn bearer_token(&self) -> Option<&str> {
@@ -100,7 +148,8 @@ impl Credentials {
             Credentials::None { client_id }
             | Credentials::ClientSecretBasic { client_id, .. }
             | Credentials::ClientSecretPost { client_id, .. }
-            | Credentials::ClientAssertionJwtBearer { client_id, .. } => client_id,
+            | Credentials::ClientAssertionJwtBearer { client_id, .. }
+            | Credentials::ClientCertificate { client_id, .. } => client_id,
             Credentials::BearerToken { .. } => return Ok(None),
         };
 
@@ -121,7 +170,10 @@ impl Credentials {
         client: &Client,
     ) -> Result<(), CredentialsVerificationError> {
         match (self, method) {
-            (Credentials::None { .. }, OAuthClientAuthenticationMethod::None) => {}
+            (
+                Credentials::None { .. } | Credentials::ClientCertificate { .. },
+                OAuthClientAuthenticationMethod::None,
+            ) => {}
 
             (
                 Credentials::ClientSecretPost { client_secret, .. },
@@ -183,6 +235,63 @@ impl Credentials {
                     .map_err(|_| CredentialsVerificationError::InvalidAssertionSignature)?;
             }
 
+            (
+                Credentials::ClientCertificate { certificate, .. },
+                OAuthClientAuthenticationMethod::TlsClientAuth,
+            ) => {
+                let certificate = EndEntityCert::try_from(certificate.as_ref())
+                    .map_err(|_| CredentialsVerificationError::CertificateMismatch)?;
+
+                // The listener accepts self-signed certificates without checking them
+                // against the trusted certificate authorities, so they can't be used here
+                if certificate.issuer() == certificate.subject() {
+                    return Err(CredentialsVerificationError::CertificateMismatch);
+                }
+
+                let matches = match (
+                    &client.tls_client_auth_subject_dn,
+                    &client.tls_client_auth_san_dns,
+                ) {
+                    (Some(subject_dn), None) => format_distinguished_name(certificate.subject())
+                        .is_some_and(|dn| dn == *subject_dn),
+                    (None, Some(san_dns)) => certificate
+                        .valid_dns_names()
+                        .any(|name| name.eq_ignore_ascii_case(san_dns)),
+                    _ => return Err(CredentialsVerificationError::InvalidClientConfig),
+                };
+
+                if !matches {
+                    return Err(CredentialsVerificationError::CertificateMismatch);
+                }
+            }
+
+            (
+                Credentials::ClientCertificate { certificate, .. },
+                OAuthClientAuthenticationMethod::SelfSignedTlsClientAuth,
+            ) => {
+                // Get the client JWKS
+                let jwks = client
+                    .jwks
+                    .as_ref()
+                    .ok_or(CredentialsVerificationError::InvalidClientConfig)?;
+
+                let jwks = fetch_jwks(http_client, jwks)
+                    .await
+                    .map_err(CredentialsVerificationError::JwksFetchFailed)?;
+
+                // The certificate must be the first one of the chain of one of the keys
+                let certificate: &[u8] = certificate;
+                let registered = jwks.iter().any(|key| {
+                    key.x5c()
+                        .and_then(<[_]>::first)
+                        .is_some_and(|registered| registered.as_bytes() == certificate)
+                });
+
+                if !registered {
+                    return Err(CredentialsVerificationError::CertificateMismatch);
+                }
+            }
+
             (_, _) => {
                 return Err(CredentialsVerificationError::AuthenticationMethodMismatch);
             }
@@ -211,6 +320,95 @@ async fn fetch_jwks(
     Ok(response)
 }
 
+/// Format the DER-encoded content of an X.509 distinguished name in the
+/// string representation defined by RFC 4514
+///
+/// Returns `None` if the name could not be decoded.
+fn format_distinguished_name(name: &[u8]) -> Option<String> {
+    const ATTRIBUTE_TYPES: [(ObjectIdentifier, &str); 9] = [
+        (ObjectIdentifier::new_unwrap("2.5.4.3"), "CN"),
+        (ObjectIdentifier::new_unwrap("2.5.4.6"), "C"),
+        (ObjectIdentifier::new_unwrap("2.5.4.7"), "L"),
+        (ObjectIdentifier::new_unwrap("2.5.4.8"), "ST"),
+        (ObjectIdentifier::new_unwrap("2.5.4.9"), "STREET"),
+        (ObjectIdentifier::new_unwrap("2.5.4.10"), "O"),
+        (ObjectIdentifier::new_unwrap("2.5.4.11"), "OU"),
+        (
+            ObjectIdentifier::new_unwrap("0.9.2342.19200300.100.1.1"),
+            "UID",
+        ),
+        (
+            ObjectIdentifier::new_unwrap("0.9.2342.19200300.100.1.25"),
+            "DC",
+        ),
+    ];
+
+    let mut relative_names = Vec::new();
+    let mut reader = SliceReader::new(name).ok()?;
+    while !reader.is_finished() {
+        let relative_name = AnyRef::decode(&mut reader).ok()?;
+        if relative_name.tag() != Tag::Set {
+            return None;
+        }
+
+        let mut attributes = Vec::new();
+        let mut reader = SliceReader::new(relative_name.value()).ok()?;
+        while !reader.is_finished() {
+            let (oid, value) = reader
+                .sequence(|reader| {
+                    let oid = ObjectIdentifier::decode(reader)?;
+                    let value = AnyRef::decode(reader)?;
+                    Ok((oid, value))
+                })
+                .ok()?;
+
+            let mut attribute = ATTRIBUTE_TYPES
+                .iter()
+                .find(|(known, _)| *known == oid)
+                .map_or_else(|| oid.to_string(), |(_, short)| (*short).to_owned());
+            attribute.push('=');
+
+            match value.tag() {
+                Tag::Utf8String | Tag::PrintableString | Tag::Ia5String | Tag::TeletexString => {
+                    let value = std::str::from_utf8(value.value()).ok()?;
+                    let last = value.chars().count().saturating_sub(1);
+                    for (index, c) in value.chars().enumerate() {
+                        match c {
+                            '"' | '+' | ',' | ';' | '<' | '>' | '\\' => {
+                                attribute.push('\\');
+                                attribute.push(c);
+                            }
+                            ' ' | '#' if index == 0 => {
+                                attribute.push('\\');
+                                attribute.push(c);
+                            }
+                            ' ' if index == last => attribute.push_str("\\ "),
+                            '\0' => attribute.push_str("\\00"),
+                            c => attribute.push(c),
+                        }
+                    }
+                }
+
+                // Other string types are represented by their hex-encoded DER encoding
+                _ => {
+                    attribute.push('#');
+                    for byte in value.to_der().ok()? {
+                        write!(attribute, "{byte:02x}").ok()?;
+                    }
+                }
+            }
+
+            attributes.push(attribute);
+        }
+
+        relative_names.push(attributes.join("+"));
+    }
+
+    // The relative names are written starting from the last one
+    relative_names.reverse();
+    Some(relative_names.join(","))
+}
+
 #[derive(Debug, Error)]
 pub enum CredentialsVerificationError {
     #[error("failed to decrypt client credentials")]
@@ -230,6 +428,9 @@ pub enum CredentialsVerificationError {
 
     #[error("failed to fetch jwks")]
     JwksFetchFailed(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("client certificate did not match")]
+    CertificateMismatch,
 }
 
 impl CredentialsVerificationError {
@@ -404,6 +605,9 @@ where
             None
         };
 
+        // Get the certificate the client presented during the TLS handshake, if any
+        let certificate = peer_certificate(req.extensions()).cloned();
+
         // Take the form value
         let (
             client_id_from_form,
@@ -469,8 +673,16 @@ where
             }
 
             (None, Some(client_id), None, None, None) => {
-                // Only got a client_id in the form
-                Credentials::None { client_id }
+                if let Some(certificate) = certificate {
+                    // Got a client_id in the form and a TLS client certificate
+                    Credentials::ClientCertificate {
+                        client_id,
+                        certificate: Box::new(certificate),
+                    }
+                } else {
+                    // Only got a client_id in the form
+                    Credentials::None { client_id }
+                }
             }
 
             (
@@ -737,4 +949,23 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn distinguished_name_test() {
+        // C=FR, O="Example, Inc.", OU=Services + CN=client
+        let name = [
+            0x31, 0x0b, 0x30, 0x09, 0x06, 0x03, 0x55, 0x04, 0x06, 0x13, 0x02, 0x46, 0x52, 0x31,
+            0x16, 0x30, 0x14, 0x06, 0x03, 0x55, 0x04, 0x0a, 0x0c, 0x0d, 0x45, 0x78, 0x61, 0x6d,
+            0x70, 0x6c, 0x65, 0x2c, 0x20, 0x49, 0x6e, 0x63, 0x2e, 0x31, 0x20, 0x30, 0x0f, 0x06,
+            0x03, 0x55, 0x04, 0x0b, 0x0c, 0x08, 0x53, 0x65, 0x72, 0x76, 0x69, 0x63, 0x65, 0x73,
+            0x30, 0x0d, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x06, 0x63, 0x6c, 0x69, 0x65, 0x6e,
+            0x74,
+        ];
+
+        assert_eq!(
+            format_distinguished_name(&name).as_deref(),
+            Some("OU=Services+CN=client,O=Example\\, Inc.,C=FR")
+        );
+        assert_eq!(format_distinguished_name(&name[..10]), None);
+    }
 }
@@ -11,10 +11,20 @@ use ulid::Ulid;
 
 use crate::cookies::CookieJar;
 
+/// The maximum number of sessions which can be signed in on a browser at once
+const MAX_SESSIONS: usize = 5;
+
 /// An encrypted cookie to save the session ID
+///
+/// A browser can be signed in to multiple accounts at once, in which case one
+/// of them is the active one.
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct SessionInfo {
     current: Option<Ulid>,
+
+    /// All the sessions signed in on this browser, most recent first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sessions: Vec<Ulid>,
 }
 
 impl SessionInfo {
@@ -23,13 +33,51 @@ impl SessionInfo {
     pub fn from_session(session: &BrowserSession) -> Self {
         Self {
             current: Some(session.id),
+            sessions: vec![session.id],
+        }
+    }
+
+    /// Add a [`BrowserSession`] to the ones signed in on this browser, and
+    /// make it the active one
+    ///
+    /// Only the most recent sessions are kept, so that the cookie doesn't grow
+    /// without bounds.
+    #[must_use]
+    pub fn with_session(mut self, session: &BrowserSession) -> Self {
+        self.sessions = self.session_ids().filter(|id| *id != session.id).collect();
+        self.sessions.insert(0, session.id);
+        self.sessions.truncate(MAX_SESSIONS);
+        self.current = Some(session.id);
+        self
+    }
+
+    /// Make one of the sessions signed in on this browser the active one
+    ///
+    /// This does nothing if the session isn't signed in on this browser.
+    #[must_use]
+    pub fn select_session(mut self, session_id: Ulid) -> Self {
+        if self.session_ids().any(|id| id == session_id) {
+            self.sessions = self.session_ids().collect();
+            self.current = Some(session_id);
         }
+        self
+    }
+
+    /// Leave the other sessions signed in, but don't have any active one, so
+    /// that the user can sign in to another account
+    #[must_use]
+    pub fn deselect_session(mut self) -> Self {
+        self.sessions = self.session_ids().collect();
+        self.current = None;
+        self
     }
 
     /// Mark the session as ended
     #[must_use]
     pub fn mark_session_ended(mut self) -> Self {
-        self.current = None;
+        if let Some(current) = self.current.take() {
+            self.sessions.retain(|id| *id != current);
+        }
         self
     }
 
@@ -56,11 +104,62 @@ impl SessionInfo {
         Ok(maybe_session)
     }
 
+    /// Load all the active [`BrowserSession`]s signed in on this browser,
+    /// most recent first
+    ///
+    /// Sessions which ended or no longer exist are forgotten, so the updated
+    /// info should be saved back to the cookie.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying repository fails to load the
+    /// sessions.
+    pub async fn load_sessions<E>(
+        &mut self,
+        repo: &mut impl RepositoryAccess<Error = E>,
+    ) -> Result<Vec<BrowserSession>, E> {
+        let session_ids: Vec<Ulid> = self.session_ids().take(MAX_SESSIONS).collect();
+        let mut sessions = Vec::new();
+        for session_id in session_ids {
+            let maybe_session = repo
+                .browser_session()
+                .lookup(session_id)
+                .await?
+                .filter(BrowserSession::active);
+
+            if let Some(session) = maybe_session {
+                sessions.push(session);
+            }
+        }
+
+        self.sessions = sessions.iter().map(|session| session.id).collect();
+        if self
+            .current
+            .is_some_and(|current| !self.sessions.contains(&current))
+        {
+            self.current = None;
+        }
+
+        Ok(sessions)
+    }
+
     /// Get the current session ID, if any
     #[must_use]
     pub fn current_session_id(&self) -> Option<Ulid> {
         self.current
     }
+
+    /// Get the IDs of all the sessions signed in on this browser, most recent
+    /// first
+    ///
+    /// Cookies set before multiple sessions were supported only have the
+    /// current session.
+    pub fn session_ids(&self) -> impl Iterator<Item = Ulid> + '_ {
+        let legacy = self
+            .current
+            .filter(|current| !self.sessions.contains(current));
+        legacy.into_iter().chain(self.sessions.iter().copied())
+    }
 }
 
 pub trait SessionInfoExt {
@@ -70,13 +169,15 @@ pub trait SessionInfoExt {
     #[must_use]
     fn update_session_info(self, info: &SessionInfo) -> Self;
 
+    /// Add the session to the ones signed in, and make it the active one
     #[must_use]
     fn set_session(self, session: &BrowserSession) -> Self
     where
         Self: Sized,
     {
-        let session_info = SessionInfo::from_session(session);
-        self.update_session_info(&session_info)
+        let (session_info, this) = self.session_info();
+        let session_info = session_info.with_session(session);
+        this.update_session_info(&session_info)
     }
 }
 
@@ -4,7 +4,7 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use std::{collections::HashMap, error::Error};
+use std::{collections::HashMap, convert::Infallible, error::Error};
 
 use axum::{
     extract::{
@@ -14,8 +14,11 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use axum_extra::typed_header::{TypedHeader, TypedHeaderRejectionReason};
-use headers::{Authorization, Header, HeaderMapExt, HeaderName, authorization::Bearer};
-use http::{HeaderMap, HeaderValue, Request, StatusCode, header::WWW_AUTHENTICATE};
+use headers::{Header, HeaderMapExt, HeaderName};
+use http::{
+    HeaderMap, HeaderValue, Request, StatusCode,
+    header::{AUTHORIZATION, WWW_AUTHENTICATE},
+};
 use mas_data_model::Session;
 use mas_storage::{
     Clock, RepositoryAccess,
@@ -24,6 +27,8 @@ use mas_storage::{
 use serde::{Deserialize, de::DeserializeOwned};
 use thiserror::Error;
 
+use crate::client_authorization::peer_certificate_thumbprint;
+
 #[derive(Debug, Deserialize)]
 struct AuthorizedForm<F> {
     #[serde(default)]
@@ -37,6 +42,7 @@ struct AuthorizedForm<F> {
 enum AccessToken {
     Form(String),
     Header(String),
+    Dpop(String),
     None,
 }
 
@@ -46,7 +52,7 @@ impl AccessToken {
         repo: &mut impl RepositoryAccess<Error = E>,
     ) -> Result<(mas_data_model::AccessToken, Session), AuthorizationVerificationError<E>> {
         let token = match self {
-            AccessToken::Form(t) | AccessToken::Header(t) => t,
+            AccessToken::Form(t) | AccessToken::Header(t) | AccessToken::Dpop(t) => t,
             AccessToken::None => return Err(AuthorizationVerificationError::MissingToken),
         };
 
@@ -64,27 +70,160 @@ impl AccessToken {
 
         Ok((token, session))
     }
+
+    /// Check that the token is presented the way its session requires.
+    ///
+    /// Tokens presented with the `DPoP` scheme must come with a valid proof,
+    /// and tokens of sessions bound to a `DPoP` key can only be used with a
+    /// proof signed by that key. Tokens of sessions bound to a TLS client
+    /// certificate can only be used over connections presenting that
+    /// certificate.
+    fn check_sender_constraint<E>(
+        &self,
+        session: &Session,
+        dpop_jkt: Option<&str>,
+        x5t_s256: Option<&str>,
+    ) -> Result<(), AuthorizationVerificationError<E>> {
+        if matches!(self, AccessToken::Dpop(_)) != dpop_jkt.is_some()
+            || session.dpop_jkt.as_deref() != dpop_jkt
+        {
+            return Err(AuthorizationVerificationError::InvalidToken);
+        }
+
+        if session.x5t_s256.is_some() && session.x5t_s256.as_deref() != x5t_s256 {
+            return Err(AuthorizationVerificationError::InvalidToken);
+        }
+
+        Ok(())
+    }
+}
+
+/// The `Authorization` header of a request to a protected resource, carrying
+/// an access token either with the `Bearer` scheme or with the `DPoP` scheme
+/// defined in RFC 9449
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessTokenAuthorization {
+    Bearer(String),
+    Dpop(String),
+}
+
+impl AccessTokenAuthorization {
+    /// The access token itself
+    #[must_use]
+    pub fn token(&self) -> &str {
+        match self {
+            Self::Bearer(token) | Self::Dpop(token) => token,
+        }
+    }
+
+    /// The access token, if it was presented with the `DPoP` scheme
+    #[must_use]
+    pub fn dpop_token(&self) -> Option<&str> {
+        match self {
+            Self::Bearer(_) => None,
+            Self::Dpop(token) => Some(token),
+        }
+    }
+}
+
+impl Header for AccessTokenAuthorization {
+    fn name() -> &'static HeaderName {
+        &AUTHORIZATION
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        Self: Sized,
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+        if values.next().is_some() {
+            return Err(headers::Error::invalid());
+        }
+
+        let value = value.to_str().map_err(|_| headers::Error::invalid())?;
+        let (scheme, token) = value.split_once(' ').ok_or_else(headers::Error::invalid)?;
+        let token = token.trim_start();
+        if token.is_empty() || token.contains(char::is_whitespace) {
+            return Err(headers::Error::invalid());
+        }
+
+        if scheme.eq_ignore_ascii_case("Bearer") {
+            Ok(Self::Bearer(token.to_owned()))
+        } else if scheme.eq_ignore_ascii_case("DPoP") {
+            Ok(Self::Dpop(token.to_owned()))
+        } else {
+            Err(headers::Error::invalid())
+        }
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        let value = match self {
+            Self::Bearer(token) => format!("Bearer {token}"),
+            Self::Dpop(token) => format!("DPoP {token}"),
+        };
+
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            values.extend(std::iter::once(value));
+        }
+    }
+}
+
+/// The SHA-256 thumbprint of the certificate presented during the TLS
+/// handshake of the connection the request was received on, if any, encoded as
+/// in the `x5t#S256` confirmation method
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateThumbprint(pub Option<String>);
+
+impl<S> FromRequestParts<S> for CertificateThumbprint
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self(peer_certificate_thumbprint(&parts.extensions)))
+    }
 }
 
 #[derive(Debug)]
 pub struct UserAuthorization<F = ()> {
     access_token: AccessToken,
+    certificate_thumbprint: Option<String>,
     form: Option<F>,
 }
 
 impl<F: Send> UserAuthorization<F> {
+    /// The access token, if it was presented with the `DPoP` scheme, in which
+    /// case the request must carry a `DPoP` proof bound to it
+    #[must_use]
+    pub fn dpop_token(&self) -> Option<&str> {
+        match &self.access_token {
+            AccessToken::Dpop(token) => Some(token),
+            _ => None,
+        }
+    }
+
     // TODO: take scopes to validate as parameter
     /// Verify a user authorization and return the session and the protected
     /// form value
     ///
+    /// `dpop_jkt` is the JWK thumbprint of the key which signed the `DPoP`
+    /// proof sent with the token, which the caller must have verified.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the token is invalid, if the user session ended or
-    /// if the form is missing
+    /// Returns an error if the token is invalid, if the user session ended, if
+    /// the token is not presented with the proof its session requires or if
+    /// the form is missing
     pub async fn protected_form<E>(
         self,
         repo: &mut impl RepositoryAccess<Error = E>,
         clock: &impl Clock,
+        dpop_jkt: Option<&str>,
     ) -> Result<(Session, F), AuthorizationVerificationError<E>> {
         let Some(form) = self.form else {
             return Err(AuthorizationVerificationError::MissingForm);
@@ -96,19 +235,30 @@ impl<F: Send> UserAuthorization<F> {
             return Err(AuthorizationVerificationError::InvalidToken);
         }
 
+        self.access_token.check_sender_constraint(
+            &session,
+            dpop_jkt,
+            self.certificate_thumbprint.as_deref(),
+        )?;
+
         Ok((session, form))
     }
 
     // TODO: take scopes to validate as parameter
     /// Verify a user authorization and return the session
     ///
+    /// `dpop_jkt` is the JWK thumbprint of the key which signed the `DPoP`
+    /// proof sent with the token, which the caller must have verified.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the token is invalid or if the user session ended
+    /// Returns an error if the token is invalid, if the user session ended or
+    /// if the token is not presented with the proof its session requires
     pub async fn protected<E>(
         self,
         repo: &mut impl RepositoryAccess<Error = E>,
         clock: &impl Clock,
+        dpop_jkt: Option<&str>,
     ) -> Result<Session, AuthorizationVerificationError<E>> {
         let (token, session) = self.access_token.fetch(repo).await?;
 
@@ -116,6 +266,12 @@ impl<F: Send> UserAuthorization<F> {
             return Err(AuthorizationVerificationError::InvalidToken);
         }
 
+        self.access_token.check_sender_constraint(
+            &session,
+            dpop_jkt,
+            self.certificate_thumbprint.as_deref(),
+        )?;
+
         if !token.is_used() {
             // Mark the token as used
             repo.oauth2_access_token().mark_used(clock, token).await?;
@@ -296,11 +452,11 @@ where
     ) -> Result<Self, Self::Rejection> {
         let (mut parts, body) = req.into_parts();
         let header =
-            TypedHeader::<Authorization<Bearer>>::from_request_parts(&mut parts, state).await;
+            TypedHeader::<AccessTokenAuthorization>::from_request_parts(&mut parts, state).await;
 
         // Take the Authorization header
         let token_from_header = match header {
-            Ok(header) => Some(header.token().to_owned()),
+            Ok(TypedHeader(header)) => Some(header),
             Err(err) => match err.reason() {
                 // If it's missing it is fine
                 TypedHeaderRejectionReason::Missing => None,
@@ -309,6 +465,8 @@ where
             },
         };
 
+        let certificate_thumbprint = peer_certificate_thumbprint(&parts.extensions);
+
         let req = Request::from_parts(parts, body);
 
         // Take the form value
@@ -328,11 +486,16 @@ where
         let access_token = match (token_from_header, token_from_form) {
             // Ensure the token should not be in both the form and the access token
             (Some(_), Some(_)) => return Err(UserAuthorizationError::TokenInFormAndHeader),
-            (Some(t), None) => AccessToken::Header(t),
+            (Some(AccessTokenAuthorization::Bearer(t)), None) => AccessToken::Header(t),
+            (Some(AccessTokenAuthorization::Dpop(t)), None) => AccessToken::Dpop(t),
             (None, Some(t)) => AccessToken::Form(t),
             (None, None) => AccessToken::None,
         };
 
-        Ok(UserAuthorization { access_token, form })
+        Ok(UserAuthorization {
+            access_token,
+            certificate_thumbprint,
+            form,
+        })
     }
 }
@@ -23,8 +23,8 @@ use mas_storage::{
     compat::{CompatAccessTokenRepository, CompatSessionFilter, CompatSessionRepository},
     oauth2::OAuth2SessionFilter,
     queue::{
-        DeactivateUserJob, ProvisionUserJob, QueueJobRepositoryExt as _, ReactivateUserJob,
-        SyncDevicesJob,
+        BackchannelLogoutJob, DeactivateUserJob, ProvisionUserJob, QueueJobRepositoryExt as _,
+        ReactivateUserJob, SyncDevicesJob,
     },
     user::{BrowserSessionFilter, UserEmailRepository, UserPasswordRepository, UserRepository},
 };
@@ -447,6 +447,17 @@ impl Options {
                     .await?
                     .context("User not found")?;
 
+                // Schedule this before ending the sessions, so that the clients of the
+                // sessions ended below get notified
+                info!(%user.id, "Scheduling back-channel logout of the user");
+                repo.queue_job()
+                    .schedule_job(
+                        &mut rng,
+                        &clock,
+                        BackchannelLogoutJob::for_user(&user, clock.now()),
+                    )
+                    .await?;
+
                 let filter = CompatSessionFilter::new().for_user(&user).active_only();
                 let affected = if dry_run {
                     repo.compat_session().count(filter).await?
@@ -532,6 +543,16 @@ impl Options {
                     repo.queue_job()
                         .schedule_job(&mut rng, &clock, DeactivateUserJob::new(&user, false))
                         .await?;
+                } else {
+                    // The deactivation job takes care of this otherwise
+                    info!(%user.id, "Scheduling back-channel logout of the user");
+                    repo.queue_job()
+                        .schedule_job(
+                            &mut rng,
+                            &clock,
+                            BackchannelLogoutJob::for_user(&user, clock.now()),
+                        )
+                        .await?;
                 }
 
                 repo.into_inner().commit().await?;
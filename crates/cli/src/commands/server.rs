@@ -151,14 +151,7 @@ impl Options {
         );
 
         // Load the site configuration
-        let site_config = site_config_from_config(
-            &config.branding,
-            &config.matrix,
-            &config.experimental,
-            &config.passwords,
-            &config.account,
-            &config.captcha,
-        )?;
+        let site_config = site_config_from_config(&(&config).into())?;
 
         // Load and compile the templates
         let templates =
@@ -182,6 +175,8 @@ impl Options {
                 homeserver_connection.clone(),
                 url_builder.clone(),
                 &site_config,
+                &key_store,
+                http_client.clone(),
                 shutdown.soft_shutdown_token(),
                 shutdown.task_tracker(),
             )
@@ -9,14 +9,16 @@ use std::process::ExitCode;
 use clap::Parser;
 use figment::Figment;
 use mas_config::{
-    AccountConfig, BrandingConfig, CaptchaConfig, ConfigurationSection, ConfigurationSectionExt,
-    ExperimentalConfig, MatrixConfig, PasswordsConfig, TemplatesConfig,
+    AccountConfig, AcrLevelsConfig, AuthorizationDetailsConfig, BrandingConfig, CaptchaConfig,
+    ConfigurationSection, ConfigurationSectionExt, ExperimentalConfig, JwtAccessTokensConfig,
+    JwtBearerConfig, MatrixConfig, PasswordsConfig, ResourceServersConfig,
+    SoftwareStatementsConfig, TemplatesConfig,
 };
 use mas_storage::{Clock, SystemClock};
 use rand::SeedableRng;
 use tracing::info_span;
 
-use crate::util::{site_config_from_config, templates_from_config};
+use crate::util::{SiteConfigSections, site_config_from_config, templates_from_config};
 
 #[derive(Parser, Debug)]
 pub(super) struct Options {
@@ -51,20 +53,41 @@ impl Options {
                     .map_err(anyhow::Error::from_boxed)?;
                 let captcha_config = CaptchaConfig::extract_or_default(figment)
                     .map_err(anyhow::Error::from_boxed)?;
+                let jwt_bearer_config = JwtBearerConfig::extract_or_default(figment)
+                    .map_err(anyhow::Error::from_boxed)?;
+                let software_statements_config =
+                    SoftwareStatementsConfig::extract_or_default(figment)
+                        .map_err(anyhow::Error::from_boxed)?;
+                let jwt_access_tokens_config = JwtAccessTokensConfig::extract_or_default(figment)
+                    .map_err(anyhow::Error::from_boxed)?;
+                let authorization_details_config =
+                    AuthorizationDetailsConfig::extract_or_default(figment)
+                        .map_err(anyhow::Error::from_boxed)?;
+                let resource_servers_config = ResourceServersConfig::extract_or_default(figment)
+                    .map_err(anyhow::Error::from_boxed)?;
+                let acr_levels_config = AcrLevelsConfig::extract_or_default(figment)
+                    .map_err(anyhow::Error::from_boxed)?;
 
                 let clock = SystemClock::default();
                 // XXX: we should disallow SeedableRng::from_entropy
                 let mut rng = rand_chacha::ChaChaRng::from_entropy();
                 let url_builder =
                     mas_router::UrlBuilder::new("https://example.com/".parse()?, None, None);
-                let site_config = site_config_from_config(
-                    &branding_config,
-                    &matrix_config,
-                    &experimental_config,
-                    &password_config,
-                    &account_config,
-                    &captcha_config,
-                )?;
+                let site_config = site_config_from_config(&SiteConfigSections {
+                    branding: &branding_config,
+                    matrix: &matrix_config,
+                    experimental: &experimental_config,
+                    passwords: &password_config,
+                    account: &account_config,
+                    captcha: &captcha_config,
+                    jwt_bearer: &jwt_bearer_config,
+                    software_statements: &software_statements_config,
+                    jwt_access_tokens: &jwt_access_tokens_config,
+                    authorization_details: &authorization_details_config,
+                    resource_servers: &resource_servers_config,
+                    acr_levels: &acr_levels_config,
+                    pairwise_subject_secret: None,
+                })?;
                 let templates =
                     templates_from_config(&template_config, &site_config, &url_builder).await?;
                 templates.check_render(clock.now(), &mut rng)?;
@@ -6,6 +6,7 @@
 
 use std::{process::ExitCode, time::Duration};
 
+use anyhow::Context;
 use clap::Parser;
 use figment::Figment;
 use mas_config::{AppConfig, ConfigurationSection};
@@ -42,14 +43,7 @@ impl Options {
         );
 
         // Load the site configuration
-        let site_config = site_config_from_config(
-            &config.branding,
-            &config.matrix,
-            &config.experimental,
-            &config.passwords,
-            &config.account,
-            &config.captcha,
-        )?;
+        let site_config = site_config_from_config(&(&config).into())?;
 
         // Load and compile the templates
         let templates =
@@ -58,8 +52,15 @@ impl Options {
         let mailer = mailer_from_config(&config.email, &templates)?;
         test_mailer_in_background(&mailer, Duration::from_secs(30));
 
+        // Initialize the key store
+        let key_store = config
+            .secrets
+            .key_store()
+            .await
+            .context("could not import keys from config")?;
+
         let http_client = mas_http::reqwest_client();
-        let conn = homeserver_connection_from_config(&config.matrix, http_client);
+        let conn = homeserver_connection_from_config(&config.matrix, http_client.clone());
 
         drop(config);
 
@@ -71,6 +72,8 @@ impl Options {
             conn,
             url_builder,
             &site_config,
+            &key_store,
+            http_client,
             shutdown.soft_shutdown_token(),
             shutdown.task_tracker(),
         )
@@ -7,6 +7,7 @@
 use std::{
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, ToSocketAddrs},
     os::unix::net::UnixListener,
+    sync::Arc,
     time::Duration,
 };
 
@@ -33,7 +34,16 @@ use opentelemetry_semantic_conventions::trace::{
     HTTP_REQUEST_METHOD, HTTP_RESPONSE_STATUS_CODE, HTTP_ROUTE, NETWORK_PROTOCOL_NAME,
     NETWORK_PROTOCOL_VERSION, URL_PATH, URL_QUERY, URL_SCHEME, USER_AGENT_ORIGINAL,
 };
-use rustls::ServerConfig;
+use rustls::{
+    DigitallySignedStruct, DistinguishedName, RootCertStore, ServerConfig, SignatureScheme,
+    client::danger::HandshakeSignatureValid,
+    crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature},
+    pki_types::{CertificateDer, UnixTime},
+    server::{
+        WebPkiClientVerifier,
+        danger::{ClientCertVerified, ClientCertVerifier},
+    },
+};
 use sentry_tower::{NewSentryLayer, SentryHttpLayer};
 use tower::Layer;
 use tower_http::services::{ServeDir, fs::ServeFileSystemResponseBody};
@@ -341,8 +351,16 @@ pub fn build_router(
 pub fn build_tls_server_config(config: &HttpTlsConfig) -> Result<ServerConfig, anyhow::Error> {
     let (key, chain) = config.load()?;
 
-    let mut config = rustls::ServerConfig::builder()
-        .with_no_client_auth()
+    let builder = rustls::ServerConfig::builder();
+    let builder = if config.request_client_certificate {
+        let authorities = config.load_client_certificate_authorities()?;
+        let verifier = ClientCertificateVerifier::new(builder.crypto_provider(), authorities)?;
+        builder.with_client_cert_verifier(Arc::new(verifier))
+    } else {
+        builder.with_no_client_auth()
+    };
+
+    let mut config = builder
         .with_single_cert(chain, key)
         .context("failed to build TLS server config")?;
     config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
@@ -350,6 +368,116 @@ pub fn build_tls_server_config(config: &HttpTlsConfig) -> Result<ServerConfig, a
     Ok(config)
 }
 
+/// A TLS client certificate verifier which accepts certificates issued by the
+/// configured certificate authorities, as well as self-signed certificates.
+///
+/// Clients are not required to present a certificate. Self-signed
+/// certificates are only checked by the `self_signed_tls_client_auth` client
+/// authentication method, against the certificates registered by the client.
+#[derive(Debug)]
+struct ClientCertificateVerifier {
+    provider: Arc<CryptoProvider>,
+    authorities: Option<Arc<dyn ClientCertVerifier>>,
+}
+
+impl ClientCertificateVerifier {
+    fn new(
+        provider: &Arc<CryptoProvider>,
+        authorities: Vec<CertificateDer<'static>>,
+    ) -> Result<Self, anyhow::Error> {
+        let authorities = if authorities.is_empty() {
+            None
+        } else {
+            let mut roots = RootCertStore::empty();
+            for authority in authorities {
+                roots
+                    .add(authority)
+                    .context("invalid client certificate authority")?;
+            }
+
+            let verifier =
+                WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+                    .allow_unauthenticated()
+                    .build()
+                    .context("failed to build client certificate verifier")?;
+            Some(verifier)
+        };
+
+        Ok(Self {
+            provider: provider.clone(),
+            authorities,
+        })
+    }
+}
+
+impl ClientCertVerifier for ClientCertificateVerifier {
+    fn client_auth_mandatory(&self) -> bool {
+        false
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        self.authorities
+            .as_ref()
+            .map_or(&[], |authorities| authorities.root_hint_subjects())
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        let certificate = webpki::EndEntityCert::try_from(end_entity).map_err(|_| {
+            rustls::Error::InvalidCertificate(rustls::CertificateError::BadEncoding)
+        })?;
+
+        if intermediates.is_empty() && certificate.issuer() == certificate.subject() {
+            return Ok(ClientCertVerified::assertion());
+        }
+
+        match &self.authorities {
+            Some(authorities) => authorities.verify_client_cert(end_entity, intermediates, now),
+            None => Err(rustls::Error::InvalidCertificate(
+                rustls::CertificateError::UnknownIssuer,
+            )),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
 pub fn build_listeners(
     fd_manager: &mut ListenFd,
     configs: &[HttpBindConfig],
@@ -12,6 +12,7 @@ use mas_config::{ClientsConfig, UpstreamOAuth2Config};
 use mas_keystore::Encrypter;
 use mas_storage::{
     Clock, Pagination, RepositoryAccess,
+    oauth2::OAuth2StaticClientParams,
     upstream_oauth2::{UpstreamOAuthProviderFilter, UpstreamOAuthProviderParams},
 };
 use mas_storage_pg::PgRepository;
@@ -396,15 +397,26 @@ pub async fn config_sync(
                 .transpose()?;
 
             repo.oauth2_client()
-                .upsert_static(
-                    client.client_id,
-                    client_name.cloned(),
+                .upsert_static(OAuth2StaticClientParams {
+                    client_id: client.client_id,
+                    client_name: client_name.cloned(),
                     client_auth_method,
                     encrypted_client_secret,
-                    jwks.cloned(),
-                    jwks_uri.cloned(),
-                    client.redirect_uris,
-                )
+                    jwks: jwks.cloned(),
+                    jwks_uri: jwks_uri.cloned(),
+                    redirect_uris: client.redirect_uris,
+                    post_logout_redirect_uris: client.post_logout_redirect_uris,
+                    request_uris: client.request_uris,
+                    backchannel_logout_uri: client.backchannel_logout_uri,
+                    frontchannel_logout_uri: client.frontchannel_logout_uri,
+                    frontchannel_logout_session_required: client
+                        .frontchannel_logout_session_required,
+                    dpop_bound_access_tokens: client.dpop_bound_access_tokens,
+                    tls_client_auth_subject_dn: client.tls_client_auth_subject_dn,
+                    tls_client_auth_san_dns: client.tls_client_auth_san_dns,
+                    require_pushed_authorization_requests: client
+                        .require_pushed_authorization_requests,
+                })
                 .await?;
         }
     }
@@ -8,12 +8,17 @@ use std::{sync::Arc, time::Duration};
 
 use anyhow::Context;
 use mas_config::{
-    AccountConfig, BrandingConfig, CaptchaConfig, DatabaseConfig, EmailConfig, EmailSmtpMode,
-    EmailTransportKind, ExperimentalConfig, HomeserverKind, MatrixConfig, PasswordsConfig,
-    PolicyConfig, TemplatesConfig,
+    AccountConfig, AcrLevelsConfig, AppConfig, AuthorizationDetailsConfig, BrandingConfig,
+    CaptchaConfig, DatabaseConfig, EmailConfig, EmailSmtpMode, EmailTransportKind,
+    ExperimentalConfig, HomeserverKind, JwtAccessTokensConfig, JwtBearerConfig, MatrixConfig,
+    PasswordsConfig, PolicyConfig, ResourceServersConfig, SoftwareStatementsConfig,
+    TemplatesConfig,
 };
 use mas_context::LogContext;
-use mas_data_model::{SessionExpirationConfig, SiteConfig};
+use mas_data_model::{
+    AcrLevel, JwksOrJwksUri, JwtAccessTokenConfig, JwtBearerIssuer, ResourceServer,
+    SessionExpirationConfig, SiteConfig, SoftwareStatementPublisher,
+};
 use mas_email::{MailTransport, Mailer};
 use mas_handlers::passwords::PasswordManager;
 use mas_matrix::{HomeserverConnection, ReadOnlyHomeserverConnection};
@@ -144,6 +149,7 @@ pub async fn policy_factory_from_config(
         register: config.register_entrypoint.clone(),
         client_registration: config.client_registration_entrypoint.clone(),
         authorization_grant: config.authorization_grant_entrypoint.clone(),
+        token_exchange: config.token_exchange_entrypoint.clone(),
         email: config.email_entrypoint.clone(),
     };
 
@@ -183,16 +189,49 @@ pub fn captcha_config_from_config(
     }))
 }
 
+/// The configuration sections used to build the [`SiteConfig`]
+pub struct SiteConfigSections<'a> {
+    pub branding: &'a BrandingConfig,
+    pub matrix: &'a MatrixConfig,
+    pub experimental: &'a ExperimentalConfig,
+    pub passwords: &'a PasswordsConfig,
+    pub account: &'a AccountConfig,
+    pub captcha: &'a CaptchaConfig,
+    pub jwt_bearer: &'a JwtBearerConfig,
+    pub software_statements: &'a SoftwareStatementsConfig,
+    pub jwt_access_tokens: &'a JwtAccessTokensConfig,
+    pub authorization_details: &'a AuthorizationDetailsConfig,
+    pub resource_servers: &'a ResourceServersConfig,
+    pub acr_levels: &'a AcrLevelsConfig,
+    pub pairwise_subject_secret: Option<[u8; 32]>,
+}
+
+impl<'a> From<&'a AppConfig> for SiteConfigSections<'a> {
+    fn from(config: &'a AppConfig) -> Self {
+        Self {
+            branding: &config.branding,
+            matrix: &config.matrix,
+            experimental: &config.experimental,
+            passwords: &config.passwords,
+            account: &config.account,
+            captcha: &config.captcha,
+            jwt_bearer: &config.jwt_bearer,
+            software_statements: &config.software_statements,
+            jwt_access_tokens: &config.jwt_access_tokens,
+            authorization_details: &config.authorization_details,
+            resource_servers: &config.resource_servers,
+            acr_levels: &config.acr_levels,
+            pairwise_subject_secret: config.secrets.pairwise_subject_secret(),
+        }
+    }
+}
+
 pub fn site_config_from_config(
-    branding_config: &BrandingConfig,
-    matrix_config: &MatrixConfig,
-    experimental_config: &ExperimentalConfig,
-    password_config: &PasswordsConfig,
-    account_config: &AccountConfig,
-    captcha_config: &CaptchaConfig,
+    config: &SiteConfigSections<'_>,
 ) -> Result<SiteConfig, anyhow::Error> {
-    let captcha = captcha_config_from_config(captcha_config)?;
-    let session_expiration = experimental_config
+    let captcha = captcha_config_from_config(config.captcha)?;
+    let session_expiration = config
+        .experimental
         .inactive_session_expiration
         .as_ref()
         .map(|c| SessionExpirationConfig {
@@ -201,29 +240,108 @@ pub fn site_config_from_config(
             user_session_inactivity_ttl: c.expire_user_sessions.then_some(c.ttl),
         });
 
+    let jwt_bearer_issuers = config
+        .jwt_bearer
+        .issuers
+        .iter()
+        .map(|issuer| {
+            let jwks = match (&issuer.jwks, &issuer.jwks_uri) {
+                (Some(jwks), None) => JwksOrJwksUri::Jwks(jwks.clone()),
+                (None, Some(jwks_uri)) => JwksOrJwksUri::JwksUri(jwks_uri.clone()),
+                _ => anyhow::bail!(
+                    "exactly one of jwks or jwks_uri must be set for issuer {:?}",
+                    issuer.issuer
+                ),
+            };
+
+            Ok(JwtBearerIssuer {
+                issuer: issuer.issuer.clone(),
+                jwks,
+                upstream_provider_id: issuer.provider,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let software_statement_publishers = config
+        .software_statements
+        .publishers
+        .iter()
+        .map(|publisher| {
+            let jwks = match (&publisher.jwks, &publisher.jwks_uri) {
+                (Some(jwks), None) => JwksOrJwksUri::Jwks(jwks.clone()),
+                (None, Some(jwks_uri)) => JwksOrJwksUri::JwksUri(jwks_uri.clone()),
+                _ => anyhow::bail!(
+                    "exactly one of jwks or jwks_uri must be set for software publisher {:?}",
+                    publisher.issuer
+                ),
+            };
+
+            Ok(SoftwareStatementPublisher {
+                issuer: publisher.issuer.clone(),
+                jwks,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let jwt_access_tokens = (!config.jwt_access_tokens.clients.is_empty()
+        || !config.jwt_access_tokens.scopes.is_empty())
+    .then(|| JwtAccessTokenConfig {
+        clients: config.jwt_access_tokens.clients.clone(),
+        scopes: config.jwt_access_tokens.scopes.clone(),
+        signing_alg: config.jwt_access_tokens.signing_alg.clone(),
+    });
+
+    let resource_servers = config
+        .resource_servers
+        .iter()
+        .map(|server| ResourceServer {
+            resource: server.resource.clone(),
+            client_id: server.client_id,
+        })
+        .collect();
+
     Ok(SiteConfig {
-        access_token_ttl: experimental_config.access_token_ttl,
-        compat_token_ttl: experimental_config.compat_token_ttl,
-        server_name: matrix_config.homeserver.clone(),
-        policy_uri: branding_config.policy_uri.clone(),
-        tos_uri: branding_config.tos_uri.clone(),
-        imprint: branding_config.imprint.clone(),
-        password_login_enabled: password_config.enabled(),
-        password_registration_enabled: password_config.enabled()
-            && account_config.password_registration_enabled,
-        registration_token_required: account_config.registration_token_required,
-        email_change_allowed: account_config.email_change_allowed,
-        displayname_change_allowed: account_config.displayname_change_allowed,
-        password_change_allowed: password_config.enabled()
-            && account_config.password_change_allowed,
-        account_recovery_allowed: password_config.enabled()
-            && account_config.password_recovery_enabled,
-        account_deactivation_allowed: account_config.account_deactivation_allowed,
+        access_token_ttl: config.experimental.access_token_ttl,
+        compat_token_ttl: config.experimental.compat_token_ttl,
+        refresh_token_reuse_grace_period: config.experimental.refresh_token_reuse_grace_period,
+        server_name: config.matrix.homeserver.clone(),
+        policy_uri: config.branding.policy_uri.clone(),
+        tos_uri: config.branding.tos_uri.clone(),
+        imprint: config.branding.imprint.clone(),
+        password_login_enabled: config.passwords.enabled(),
+        password_registration_enabled: config.passwords.enabled()
+            && config.account.password_registration_enabled,
+        registration_token_required: config.account.registration_token_required,
+        email_change_allowed: config.account.email_change_allowed,
+        displayname_change_allowed: config.account.displayname_change_allowed,
+        password_change_allowed: config.passwords.enabled()
+            && config.account.password_change_allowed,
+        account_recovery_allowed: config.passwords.enabled()
+            && config.account.password_recovery_enabled,
+        account_deactivation_allowed: config.account.account_deactivation_allowed,
         captcha,
-        minimum_password_complexity: password_config.minimum_complexity(),
+        minimum_password_complexity: config.passwords.minimum_complexity(),
         session_expiration,
-        login_with_email_allowed: account_config.login_with_email_allowed,
-        plan_management_iframe_uri: experimental_config.plan_management_iframe_uri.clone(),
+        login_with_email_allowed: config.account.login_with_email_allowed,
+        plan_management_iframe_uri: config.experimental.plan_management_iframe_uri.clone(),
+        pairwise_subject_secret: config.pairwise_subject_secret,
+        jwt_bearer_issuers,
+        software_statement_publishers,
+        jwt_access_tokens,
+        authorization_details_types: config.authorization_details.types.clone(),
+        resource_servers,
+        acr_levels: config
+            .acr_levels
+            .iter()
+            .map(|level| AcrLevel {
+                value: level.value.clone(),
+                amr: level
+                    .methods
+                    .iter()
+                    .map(|method| method.amr().to_owned())
+                    .collect(),
+            })
+            .collect(),
     })
 }
 
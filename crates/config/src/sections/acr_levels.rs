@@ -0,0 +1,132 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::{collections::BTreeSet, ops::Deref};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::Error};
+
+use crate::ConfigurationSection;
+
+/// An authentication method which can satisfy an ACR level
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, JsonSchema, Serialize)]
+pub enum AcrMethodConfig {
+    /// Login with a password, reported as the `pwd` authentication method
+    /// reference
+    #[serde(rename = "password")]
+    Password,
+
+    /// Login through an upstream OAuth 2.0 provider, reported as the `fed`
+    /// authentication method reference
+    #[serde(rename = "upstream_oauth2")]
+    UpstreamOAuth2,
+}
+
+impl AcrMethodConfig {
+    /// The authentication method reference (`amr`) value of this method
+    #[must_use]
+    pub const fn amr(self) -> &'static str {
+        match self {
+            Self::Password => "pwd",
+            Self::UpstreamOAuth2 => "fed",
+        }
+    }
+}
+
+/// An authentication context class reference (ACR) level
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct AcrLevelConfig {
+    /// The `acr` value of this level, as requested by clients in the
+    /// `acr_values` parameter and reported in ID tokens
+    pub value: String,
+
+    /// The authentication methods which satisfy this level
+    pub methods: Vec<AcrMethodConfig>,
+}
+
+/// List of ACR levels, from the weakest to the strongest
+///
+/// Clients asking for a level through the `acr_values` parameter make the user
+/// log in again if their last authentication doesn't satisfy it, or any
+/// stronger level.
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema, Serialize)]
+#[serde(transparent)]
+pub struct AcrLevelsConfig(#[schemars(with = "Vec::<AcrLevelConfig>")] Vec<AcrLevelConfig>);
+
+impl AcrLevelsConfig {
+    /// Returns true if the configuration is the default one
+    pub(crate) fn is_default(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Deref for AcrLevelsConfig {
+    type Target = Vec<AcrLevelConfig>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl ConfigurationSection for AcrLevelsConfig {
+    const PATH: Option<&'static str> = Some("acr_levels");
+
+    fn validate(
+        &self,
+        figment: &figment::Figment,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let annotate = |mut error: figment::error::Error, index: usize, field: &'static str| {
+            error.metadata = figment.find_metadata(Self::PATH.unwrap()).cloned();
+            error.profile = Some(figment::Profile::Default);
+            error.path = vec![
+                Self::PATH.unwrap().to_owned(),
+                index.to_string(),
+                field.to_owned(),
+            ];
+            error
+        };
+
+        let mut seen = BTreeSet::new();
+        for (index, level) in self.0.iter().enumerate() {
+            if level.value.is_empty() {
+                return Err(annotate(
+                    figment::error::Error::custom("ACR value can't be empty"),
+                    index,
+                    "value",
+                )
+                .into());
+            }
+
+            if level.value.contains(' ') {
+                return Err(annotate(
+                    figment::error::Error::custom("ACR value can't contain spaces"),
+                    index,
+                    "value",
+                )
+                .into());
+            }
+
+            if !seen.insert(&level.value) {
+                return Err(annotate(
+                    figment::error::Error::custom(format!("duplicate ACR value {:?}", level.value)),
+                    index,
+                    "value",
+                )
+                .into());
+            }
+
+            if level.methods.is_empty() {
+                return Err(annotate(
+                    figment::error::Error::custom("ACR level must have at least one method"),
+                    index,
+                    "methods",
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::collections::BTreeSet;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::Error};
+
+use crate::ConfigurationSection;
+
+/// Configuration of Rich Authorization Requests (RFC 9396)
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema, Serialize)]
+pub struct AuthorizationDetailsConfig {
+    /// List of authorization details types which clients can request through
+    /// the `authorization_details` parameter. Requests using other types are
+    /// rejected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+}
+
+impl AuthorizationDetailsConfig {
+    /// Returns true if the configuration is the default one
+    pub(crate) fn is_default(&self) -> bool {
+        self.types.is_empty()
+    }
+}
+
+impl ConfigurationSection for AuthorizationDetailsConfig {
+    const PATH: Option<&'static str> = Some("authorization_details");
+
+    fn validate(
+        &self,
+        figment: &figment::Figment,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let annotate = |mut error: figment::error::Error, index: usize| {
+            error.metadata = figment.find_metadata(Self::PATH.unwrap()).cloned();
+            error.profile = Some(figment::Profile::Default);
+            error.path = vec![
+                Self::PATH.unwrap().to_owned(),
+                "types".to_owned(),
+                index.to_string(),
+            ];
+            error
+        };
+
+        let mut seen = BTreeSet::new();
+        for (index, kind) in self.types.iter().enumerate() {
+            if kind.is_empty() {
+                return Err(annotate(
+                    figment::error::Error::custom("authorization details type can't be empty"),
+                    index,
+                )
+                .into());
+            }
+
+            if !seen.insert(kind) {
+                return Err(annotate(
+                    figment::error::Error::custom(format!(
+                        "duplicate authorization details type {kind:?}"
+                    )),
+                    index,
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -50,6 +50,14 @@ pub enum ClientAuthMethodConfig {
     /// `client_secret_basic`: a `client_assertion` sent in the request body and
     /// signed by an asymmetric key
     PrivateKeyJwt,
+
+    /// `tls_client_auth`: a client certificate issued by a trusted certificate
+    /// authority, presented during the TLS handshake
+    TlsClientAuth,
+
+    /// `self_signed_tls_client_auth`: a self-signed client certificate,
+    /// registered in the client JWKS, presented during the TLS handshake
+    SelfSignedTlsClientAuth,
 }
 
 impl std::fmt::Display for ClientAuthMethodConfig {
@@ -60,6 +68,10 @@ impl std::fmt::Display for ClientAuthMethodConfig {
             ClientAuthMethodConfig::ClientSecretPost => write!(f, "client_secret_post"),
             ClientAuthMethodConfig::ClientSecretJwt => write!(f, "client_secret_jwt"),
             ClientAuthMethodConfig::PrivateKeyJwt => write!(f, "private_key_jwt"),
+            ClientAuthMethodConfig::TlsClientAuth => write!(f, "tls_client_auth"),
+            ClientAuthMethodConfig::SelfSignedTlsClientAuth => {
+                write!(f, "self_signed_tls_client_auth")
+            }
         }
     }
 }
@@ -87,30 +99,80 @@ pub struct ClientConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_secret: Option<String>,
 
-    /// The JSON Web Key Set (JWKS) used by the `private_key_jwt` authentication
-    /// method. Mutually exclusive with `jwks_uri`
+    /// The JSON Web Key Set (JWKS) used by the `private_key_jwt` and
+    /// `self_signed_tls_client_auth` authentication methods. Mutually exclusive
+    /// with `jwks_uri`
     #[serde(skip_serializing_if = "Option::is_none")]
     pub jwks: Option<PublicJsonWebKeySet>,
 
     /// The URL of the JSON Web Key Set (JWKS) used by the `private_key_jwt`
-    /// authentication method. Mutually exclusive with `jwks`
+    /// and `self_signed_tls_client_auth` authentication methods. Mutually
+    /// exclusive with `jwks`
     #[serde(skip_serializing_if = "Option::is_none")]
     pub jwks_uri: Option<Url>,
 
     /// List of allowed redirect URIs
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub redirect_uris: Vec<Url>,
+
+    /// List of URIs the client may redirect to after logging out through the
+    /// end session endpoint
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_logout_redirect_uris: Vec<Url>,
+
+    /// List of URIs the client may pass request objects by reference from,
+    /// through the `request_uri` parameter
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub request_uris: Vec<Url>,
+
+    /// URI to which logout tokens are sent when the user logs out, as per the
+    /// OpenID Connect Back-Channel Logout specification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backchannel_logout_uri: Option<Url>,
+
+    /// URI which is loaded in an iframe when the user logs out, as per the
+    /// OpenID Connect Front-Channel Logout specification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frontchannel_logout_uri: Option<Url>,
+
+    /// Whether the client requires the `iss` and `sid` query parameters to be
+    /// added to the `frontchannel_logout_uri`. Defaults to `false`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub frontchannel_logout_session_required: bool,
+
+    /// Whether the client must always use DPoP-bound access tokens (RFC 9449).
+    /// Defaults to `false`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dpop_bound_access_tokens: bool,
+
+    /// The expected subject distinguished name of the client certificate, in
+    /// the RFC 4514 format, used by the `tls_client_auth` authentication
+    /// method. Mutually exclusive with `tls_client_auth_san_dns`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_client_auth_subject_dn: Option<String>,
+
+    /// The expected DNS subject alternative name of the client certificate,
+    /// used by the `tls_client_auth` authentication method. Mutually exclusive
+    /// with `tls_client_auth_subject_dn`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_client_auth_san_dns: Option<String>,
+
+    /// Whether the client must use pushed authorization requests (RFC 9126)
+    /// to start an authorization flow. Defaults to `false`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub require_pushed_authorization_requests: bool,
 }
 
 impl ClientConfig {
     fn validate(&self) -> Result<(), Box<figment::error::Error>> {
         let auth_method = self.client_auth_method;
         match self.client_auth_method {
-            ClientAuthMethodConfig::PrivateKeyJwt => {
+            ClientAuthMethodConfig::PrivateKeyJwt
+            | ClientAuthMethodConfig::SelfSignedTlsClientAuth => {
                 if self.jwks.is_none() && self.jwks_uri.is_none() {
-                    let error = figment::error::Error::custom(
-                        "jwks or jwks_uri is required for private_key_jwt",
-                    );
+                    let error = figment::error::Error::custom(format!(
+                        "jwks or jwks_uri is required for {auth_method}"
+                    ));
                     return Err(Box::new(error.with_path("client_auth_method")));
                 }
 
@@ -120,9 +182,27 @@ impl ClientConfig {
                     return Err(Box::new(error.with_path("jwks")));
                 }
 
+                if self.client_secret.is_some() {
+                    let error = figment::error::Error::custom(format!(
+                        "client_secret is not allowed with {auth_method}"
+                    ));
+                    return Err(Box::new(error.with_path("client_secret")));
+                }
+            }
+
+            ClientAuthMethodConfig::TlsClientAuth => {
+                if self.tls_client_auth_subject_dn.is_some()
+                    == self.tls_client_auth_san_dns.is_some()
+                {
+                    let error = figment::error::Error::custom(
+                        "exactly one of tls_client_auth_subject_dn or tls_client_auth_san_dns is required for tls_client_auth",
+                    );
+                    return Err(Box::new(error.with_path("client_auth_method")));
+                }
+
                 if self.client_secret.is_some() {
                     let error = figment::error::Error::custom(
-                        "client_secret is not allowed with private_key_jwt",
+                        "client_secret is not allowed with tls_client_auth",
                     );
                     return Err(Box::new(error.with_path("client_secret")));
                 }
@@ -195,6 +275,10 @@ impl ClientConfig {
                 OAuthClientAuthenticationMethod::ClientSecretJwt
             }
             ClientAuthMethodConfig::PrivateKeyJwt => OAuthClientAuthenticationMethod::PrivateKeyJwt,
+            ClientAuthMethodConfig::TlsClientAuth => OAuthClientAuthenticationMethod::TlsClientAuth,
+            ClientAuthMethodConfig::SelfSignedTlsClientAuth => {
+                OAuthClientAuthenticationMethod::SelfSignedTlsClientAuth
+            }
         }
     }
 }
@@ -302,6 +386,10 @@ mod tests {
                           use: "sig"
                           e: "AQAB"
                           n: "0hukqytPwrj1RbMYhYoepCi3CN5k7DwYkTe_Cmb7cP9_qv4ok78KdvFXt5AnQxCRwBD7-qTNkkfMWO2RxUMBdQD0ED6tsSb1n5dp0XY8dSWiBDCX8f6Hr-KolOpvMLZKRy01HdAWcM6RoL9ikbjYHUEW1C8IJnw3MzVHkpKFDL354aptdNLaAdTCBvKzU9WpXo10g-5ctzSlWWjQuecLMQ4G1mNdsR1LHhUENEnOvgT8cDkX0fJzLbEbyBYkdMgKggyVPEB1bg6evG4fTKawgnf0IDSPxIU-wdS9wdSP9ZCJJPLi5CEp-6t6rE_sb2dGcnzjCGlembC57VwpkUvyMw"
+
+                    - client_id: 01GFWR4QG6RCZ0BYYHNWTJ2V8J
+                      client_auth_method: tls_client_auth
+                      tls_client_auth_subject_dn: CN=service,O=Example
                 "#,
             )?;
 
@@ -309,7 +397,7 @@ mod tests {
                 .merge(Yaml::file("config.yaml"))
                 .extract_inner::<ClientsConfig>("clients")?;
 
-            assert_eq!(config.0.len(), 5);
+            assert_eq!(config.0.len(), 6);
 
             assert_eq!(
                 config.0[0].client_id,
@@ -326,6 +414,15 @@ mod tests {
             );
             assert_eq!(config.0[1].redirect_uris, Vec::new());
 
+            assert_eq!(
+                config.0[5].client_auth_method(),
+                OAuthClientAuthenticationMethod::TlsClientAuth
+            );
+            assert_eq!(
+                config.0[5].tls_client_auth_subject_dn.as_deref(),
+                Some("CN=service,O=Example")
+            );
+
             Ok(())
         });
     }
@@ -23,6 +23,14 @@ fn is_default_token_ttl(value: &Duration) -> bool {
     *value == default_token_ttl()
 }
 
+fn default_refresh_token_reuse_grace_period() -> Duration {
+    Duration::microseconds(30 * 1000 * 1000)
+}
+
+fn is_default_refresh_token_reuse_grace_period(value: &Duration) -> bool {
+    *value == default_refresh_token_reuse_grace_period()
+}
+
 /// Configuration options for the inactive session expiration feature
 #[serde_as]
 #[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
@@ -70,6 +78,19 @@ pub struct ExperimentalConfig {
     #[serde_as(as = "serde_with::DurationSeconds<i64>")]
     pub compat_token_ttl: Duration,
 
+    /// Time in seconds during which a consumed refresh token can be presented
+    /// again without revoking the whole session. Defaults to 30 seconds.
+    ///
+    /// Outside of this window, presenting a consumed refresh token is treated
+    /// as a sign that it leaked: the session and all of its tokens are revoked.
+    #[schemars(with = "u64", range(min = 0, max = 600))]
+    #[serde(
+        default = "default_refresh_token_reuse_grace_period",
+        skip_serializing_if = "is_default_refresh_token_reuse_grace_period"
+    )]
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    pub refresh_token_reuse_grace_period: Duration,
+
     /// Experimetal feature to automatically expire inactive sessions
     ///
     /// Disabled by default
@@ -88,6 +109,7 @@ impl Default for ExperimentalConfig {
         Self {
             access_token_ttl: default_token_ttl(),
             compat_token_ttl: default_token_ttl(),
+            refresh_token_reuse_grace_period: default_refresh_token_reuse_grace_period(),
             inactive_session_expiration: None,
             plan_management_iframe_uri: None,
         }
@@ -98,6 +120,7 @@ impl ExperimentalConfig {
     pub(crate) fn is_default(&self) -> bool {
         is_default_token_ttl(&self.access_token_ttl)
             && is_default_token_ttl(&self.compat_token_ttl)
+            && is_default_refresh_token_reuse_grace_period(&self.refresh_token_reuse_grace_period)
             && self.inactive_session_expiration.is_none()
             && self.plan_management_iframe_uri.is_none()
     }
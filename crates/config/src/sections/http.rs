@@ -188,6 +188,32 @@ pub struct TlsConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schemars(with = "Option<String>")]
     pub password_file: Option<Utf8PathBuf>,
+
+    /// Whether to request a certificate from clients during the TLS handshake.
+    ///
+    /// Clients are not required to present one, but it is needed for the
+    /// `tls_client_auth` and `self_signed_tls_client_auth` client
+    /// authentication methods. Defaults to `false`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub request_client_certificate: bool,
+
+    /// PEM-encoded X509 certificates of the authorities trusted to issue
+    /// client certificates for the `tls_client_auth` authentication method
+    ///
+    /// At most one of `client_certificate_authorities` or
+    /// `client_certificate_authorities_file` can be set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_certificate_authorities: Option<String>,
+
+    /// File containing the PEM-encoded X509 certificates of the authorities
+    /// trusted to issue client certificates for the `tls_client_auth`
+    /// authentication method
+    ///
+    /// At most one of `client_certificate_authorities` or
+    /// `client_certificate_authorities_file` can be set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub client_certificate_authorities_file: Option<Utf8PathBuf>,
 }
 
 impl TlsConfig {
@@ -262,6 +288,38 @@ impl TlsConfig {
 
         Ok((key, certificate_chain))
     }
+
+    /// Load the certificates of the authorities trusted to issue client
+    /// certificates
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an error was encountered either while:
+    ///   - reading the certificates file
+    ///   - decoding the certificates as PEM
+    pub fn load_client_certificate_authorities(
+        &self,
+    ) -> Result<Vec<CertificateDer<'static>>, anyhow::Error> {
+        let certificates_pem = match (
+            &self.client_certificate_authorities,
+            &self.client_certificate_authorities_file,
+        ) {
+            (None, None) => return Ok(Vec::new()),
+            (Some(_), Some(_)) => {
+                bail!(
+                    "Only one of `client_certificate_authorities` or `client_certificate_authorities_file` can be set at a time"
+                )
+            }
+            (Some(certificates), None) => Cow::Borrowed(certificates),
+            (None, Some(path)) => Cow::Owned(std::fs::read_to_string(path)?),
+        };
+
+        let mut certificates_reader = Cursor::new(certificates_pem.as_bytes());
+        let certificates: Result<Vec<_>, _> =
+            rustls_pemfile::certs(&mut certificates_reader).collect();
+
+        Ok(certificates?)
+    }
 }
 
 /// HTTP resources to mount
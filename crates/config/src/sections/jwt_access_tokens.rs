@@ -0,0 +1,78 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use mas_iana::jose::JsonWebSignatureAlg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::Error};
+use ulid::Ulid;
+
+use crate::ConfigurationSection;
+
+fn default_signing_alg() -> JsonWebSignatureAlg {
+    JsonWebSignatureAlg::Rs256
+}
+
+fn is_default_signing_alg(value: &JsonWebSignatureAlg) -> bool {
+    *value == default_signing_alg()
+}
+
+/// Configuration section to issue JWT-formatted access tokens (RFC 9068)
+/// instead of opaque ones, so that resource servers can validate them offline
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct JwtAccessTokensConfig {
+    /// List of IDs of clients which get JWT access tokens
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(with = "Vec<String>")]
+    pub clients: Vec<Ulid>,
+
+    /// List of scopes which, when granted, make the access token a JWT
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+
+    /// The algorithm used to sign the access tokens. Defaults to `RS256`
+    #[serde(
+        default = "default_signing_alg",
+        skip_serializing_if = "is_default_signing_alg"
+    )]
+    pub signing_alg: JsonWebSignatureAlg,
+}
+
+impl Default for JwtAccessTokensConfig {
+    fn default() -> Self {
+        Self {
+            clients: Vec::new(),
+            scopes: Vec::new(),
+            signing_alg: default_signing_alg(),
+        }
+    }
+}
+
+impl JwtAccessTokensConfig {
+    /// Returns true if the configuration is the default one
+    pub(crate) fn is_default(&self) -> bool {
+        self.clients.is_empty()
+            && self.scopes.is_empty()
+            && is_default_signing_alg(&self.signing_alg)
+    }
+}
+
+impl ConfigurationSection for JwtAccessTokensConfig {
+    const PATH: Option<&'static str> = Some("jwt_access_tokens");
+
+    fn validate(
+        &self,
+        figment: &figment::Figment,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        if self.signing_alg == JsonWebSignatureAlg::None {
+            let mut error = figment::error::Error::custom("access tokens must be signed");
+            error.metadata = figment.find_metadata(Self::PATH.unwrap()).cloned();
+            error.profile = Some(figment::Profile::Default);
+            error.path = vec![Self::PATH.unwrap().to_owned(), "signing_alg".to_owned()];
+            return Err(error.into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::collections::BTreeSet;
+
+use mas_jose::jwk::PublicJsonWebKeySet;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::Error};
+use ulid::Ulid;
+use url::Url;
+
+use crate::ConfigurationSection;
+
+/// An issuer of JWTs which clients can exchange for access tokens using the
+/// JWT bearer authorization grant
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct JwtBearerIssuerConfig {
+    /// The expected value of the `iss` claim in the assertions
+    pub issuer: String,
+
+    /// The JSON Web Key Set used to verify the assertions. Mutually exclusive
+    /// with `jwks_uri`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwks: Option<PublicJsonWebKeySet>,
+
+    /// The URL of the JSON Web Key Set used to verify the assertions. Mutually
+    /// exclusive with `jwks`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwks_uri: Option<Url>,
+
+    /// The ID of the upstream OAuth 2.0 provider whose links are used to map
+    /// the `sub` claim of the assertions to a user
+    #[schemars(
+        with = "String",
+        regex(pattern = r"^[0123456789ABCDEFGHJKMNPQRSTVWXYZ]{26}$"),
+        description = "A ULID as per https://github.com/ulid/spec"
+    )]
+    pub provider: Ulid,
+}
+
+/// Configuration of the JWT bearer authorization grant (RFC 7523)
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema, Serialize)]
+pub struct JwtBearerConfig {
+    /// List of issuers trusted to sign assertions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issuers: Vec<JwtBearerIssuerConfig>,
+}
+
+impl JwtBearerConfig {
+    /// Returns true if the configuration is the default one
+    pub(crate) fn is_default(&self) -> bool {
+        self.issuers.is_empty()
+    }
+}
+
+impl ConfigurationSection for JwtBearerConfig {
+    const PATH: Option<&'static str> = Some("jwt_bearer");
+
+    fn validate(
+        &self,
+        figment: &figment::Figment,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let metadata = figment.find_metadata(Self::PATH.unwrap());
+
+        let annotate = |mut error: figment::error::Error, index: usize, field: &'static str| {
+            error.metadata = metadata.cloned();
+            error.profile = Some(figment::Profile::Default);
+            error.path = vec![
+                Self::PATH.unwrap().to_owned(),
+                "issuers".to_owned(),
+                index.to_string(),
+                field.to_owned(),
+            ];
+            error
+        };
+
+        let mut seen = BTreeSet::new();
+        for (index, issuer) in self.issuers.iter().enumerate() {
+            if !seen.insert(&issuer.issuer) {
+                return Err(annotate(
+                    figment::error::Error::custom(format!("duplicate issuer {:?}", issuer.issuer)),
+                    index,
+                    "issuer",
+                )
+                .into());
+            }
+
+            match (&issuer.jwks, &issuer.jwks_uri) {
+                (None, None) => {
+                    return Err(annotate(
+                        figment::error::Error::custom("one of jwks or jwks_uri is required"),
+                        index,
+                        "jwks",
+                    )
+                    .into());
+                }
+                (Some(_), Some(_)) => {
+                    return Err(annotate(
+                        figment::error::Error::custom("jwks and jwks_uri are mutually exclusive"),
+                        index,
+                        "jwks",
+                    )
+                    .into());
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use figment::{
+        Figment, Jail,
+        providers::{Format, Yaml},
+    };
+
+    use super::*;
+
+    #[test]
+    fn load_config() {
+        Jail::expect_with(|jail| {
+            jail.create_file(
+                "config.yaml",
+                r#"
+                    jwt_bearer:
+                      issuers:
+                        - issuer: https://workload.example.com/
+                          jwks_uri: https://workload.example.com/jwks.json
+                          provider: 01GFWR28C4KNE04WG3HKXB7C9R
+                "#,
+            )?;
+
+            let config = Figment::new()
+                .merge(Yaml::file("config.yaml"))
+                .extract_inner::<JwtBearerConfig>("jwt_bearer")?;
+
+            assert_eq!(config.issuers.len(), 1);
+            assert_eq!(config.issuers[0].issuer, "https://workload.example.com/");
+            assert!(config.issuers[0].jwks.is_none());
+
+            Ok(())
+        });
+    }
+}
@@ -9,6 +9,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 mod account;
+mod acr_levels;
+mod authorization_details;
 mod branding;
 mod captcha;
 mod clients;
@@ -16,17 +18,23 @@ mod database;
 mod email;
 mod experimental;
 mod http;
+mod jwt_access_tokens;
+mod jwt_bearer;
 mod matrix;
 mod passwords;
 mod policy;
 mod rate_limiting;
+mod resource_servers;
 mod secrets;
+mod software_statements;
 mod telemetry;
 mod templates;
 mod upstream_oauth2;
 
 pub use self::{
     account::AccountConfig,
+    acr_levels::{AcrLevelConfig, AcrLevelsConfig, AcrMethodConfig},
+    authorization_details::AuthorizationDetailsConfig,
     branding::BrandingConfig,
     captcha::{CaptchaConfig, CaptchaServiceKind},
     clients::{ClientAuthMethodConfig, ClientConfig, ClientsConfig},
@@ -37,13 +45,17 @@ pub use self::{
         BindConfig as HttpBindConfig, HttpConfig, ListenerConfig as HttpListenerConfig,
         Resource as HttpResource, TlsConfig as HttpTlsConfig, UnixOrTcp,
     },
+    jwt_access_tokens::JwtAccessTokensConfig,
+    jwt_bearer::{JwtBearerConfig, JwtBearerIssuerConfig},
     matrix::{HomeserverKind, MatrixConfig},
     passwords::{
         Algorithm as PasswordAlgorithm, HashingScheme as PasswordHashingScheme, PasswordsConfig,
     },
     policy::PolicyConfig,
     rate_limiting::RateLimitingConfig,
+    resource_servers::{ResourceServerConfig, ResourceServersConfig},
     secrets::SecretsConfig,
+    software_statements::{SoftwareStatementPublisherConfig, SoftwareStatementsConfig},
     telemetry::{
         MetricsConfig, MetricsExporterKind, Propagator, TelemetryConfig, TracingConfig,
         TracingExporterKind,
@@ -111,6 +123,36 @@ pub struct RootConfig {
     #[serde(default, skip_serializing_if = "UpstreamOAuth2Config::is_default")]
     pub upstream_oauth2: UpstreamOAuth2Config,
 
+    /// Configuration of the issuers trusted for the JWT bearer authorization
+    /// grant
+    #[serde(default, skip_serializing_if = "JwtBearerConfig::is_default")]
+    pub jwt_bearer: JwtBearerConfig,
+
+    /// Configuration of the software publishers trusted to sign software
+    /// statements in dynamic client registration
+    #[serde(default, skip_serializing_if = "SoftwareStatementsConfig::is_default")]
+    pub software_statements: SoftwareStatementsConfig,
+
+    /// Configuration section to issue JWT-formatted access tokens
+    #[serde(default, skip_serializing_if = "JwtAccessTokensConfig::is_default")]
+    pub jwt_access_tokens: JwtAccessTokensConfig,
+
+    /// Configuration section for Rich Authorization Requests
+    #[serde(
+        default,
+        skip_serializing_if = "AuthorizationDetailsConfig::is_default"
+    )]
+    pub authorization_details: AuthorizationDetailsConfig,
+
+    /// List of resource servers clients can get tokens for
+    #[serde(default, skip_serializing_if = "ResourceServersConfig::is_default")]
+    pub resource_servers: ResourceServersConfig,
+
+    /// List of authentication context class reference (ACR) levels, from the
+    /// weakest to the strongest
+    #[serde(default, skip_serializing_if = "AcrLevelsConfig::is_default")]
+    pub acr_levels: AcrLevelsConfig,
+
     /// Configuration section for tweaking the branding of the service
     #[serde(default, skip_serializing_if = "BrandingConfig::is_default")]
     pub branding: BrandingConfig,
@@ -146,6 +188,12 @@ impl ConfigurationSection for RootConfig {
         self.policy.validate(figment)?;
         self.rate_limiting.validate(figment)?;
         self.upstream_oauth2.validate(figment)?;
+        self.jwt_bearer.validate(figment)?;
+        self.software_statements.validate(figment)?;
+        self.jwt_access_tokens.validate(figment)?;
+        self.authorization_details.validate(figment)?;
+        self.resource_servers.validate(figment)?;
+        self.acr_levels.validate(figment)?;
         self.branding.validate(figment)?;
         self.captcha.validate(figment)?;
         self.account.validate(figment)?;
@@ -178,6 +226,12 @@ impl RootConfig {
             policy: PolicyConfig::default(),
             rate_limiting: RateLimitingConfig::default(),
             upstream_oauth2: UpstreamOAuth2Config::default(),
+            jwt_bearer: JwtBearerConfig::default(),
+            software_statements: SoftwareStatementsConfig::default(),
+            jwt_access_tokens: JwtAccessTokensConfig::default(),
+            authorization_details: AuthorizationDetailsConfig::default(),
+            resource_servers: ResourceServersConfig::default(),
+            acr_levels: AcrLevelsConfig::default(),
             branding: BrandingConfig::default(),
             captcha: CaptchaConfig::default(),
             account: AccountConfig::default(),
@@ -201,6 +255,12 @@ impl RootConfig {
             policy: PolicyConfig::default(),
             rate_limiting: RateLimitingConfig::default(),
             upstream_oauth2: UpstreamOAuth2Config::default(),
+            jwt_bearer: JwtBearerConfig::default(),
+            software_statements: SoftwareStatementsConfig::default(),
+            jwt_access_tokens: JwtAccessTokensConfig::default(),
+            authorization_details: AuthorizationDetailsConfig::default(),
+            resource_servers: ResourceServersConfig::default(),
+            acr_levels: AcrLevelsConfig::default(),
             branding: BrandingConfig::default(),
             captcha: CaptchaConfig::default(),
             account: AccountConfig::default(),
@@ -238,6 +298,24 @@ pub struct AppConfig {
     #[serde(default)]
     pub rate_limiting: RateLimitingConfig,
 
+    #[serde(default)]
+    pub jwt_bearer: JwtBearerConfig,
+
+    #[serde(default)]
+    pub software_statements: SoftwareStatementsConfig,
+
+    #[serde(default)]
+    pub jwt_access_tokens: JwtAccessTokensConfig,
+
+    #[serde(default)]
+    pub authorization_details: AuthorizationDetailsConfig,
+
+    #[serde(default)]
+    pub resource_servers: ResourceServersConfig,
+
+    #[serde(default)]
+    pub acr_levels: AcrLevelsConfig,
+
     #[serde(default)]
     pub branding: BrandingConfig,
 
@@ -265,6 +343,12 @@ impl ConfigurationSection for AppConfig {
         self.matrix.validate(figment)?;
         self.policy.validate(figment)?;
         self.rate_limiting.validate(figment)?;
+        self.jwt_bearer.validate(figment)?;
+        self.software_statements.validate(figment)?;
+        self.jwt_access_tokens.validate(figment)?;
+        self.authorization_details.validate(figment)?;
+        self.resource_servers.validate(figment)?;
+        self.acr_levels.validate(figment)?;
         self.branding.validate(figment)?;
         self.captcha.validate(figment)?;
         self.account.validate(figment)?;
@@ -54,6 +54,14 @@ fn is_default_authorization_grant_entrypoint(value: &String) -> bool {
     *value == default_authorization_grant_entrypoint()
 }
 
+fn default_token_exchange_entrypoint() -> String {
+    "token_exchange/violation".to_owned()
+}
+
+fn is_default_token_exchange_entrypoint(value: &String) -> bool {
+    *value == default_token_exchange_entrypoint()
+}
+
 fn default_password_entrypoint() -> String {
     "password/violation".to_owned()
 }
@@ -111,6 +119,13 @@ pub struct PolicyConfig {
     )]
     pub authorization_grant_entrypoint: String,
 
+    /// Entrypoint to use when evaluating token exchanges
+    #[serde(
+        default = "default_token_exchange_entrypoint",
+        skip_serializing_if = "is_default_token_exchange_entrypoint"
+    )]
+    pub token_exchange_entrypoint: String,
+
     /// Entrypoint to use when changing password
     #[serde(
         default = "default_password_entrypoint",
@@ -137,6 +152,7 @@ impl Default for PolicyConfig {
             client_registration_entrypoint: default_client_registration_entrypoint(),
             register_entrypoint: default_register_entrypoint(),
             authorization_grant_entrypoint: default_authorization_grant_entrypoint(),
+            token_exchange_entrypoint: default_token_exchange_entrypoint(),
             password_entrypoint: default_password_entrypoint(),
             email_entrypoint: default_email_entrypoint(),
             data: default_data(),
@@ -151,6 +167,7 @@ impl PolicyConfig {
             && is_default_client_registration_entrypoint(&self.client_registration_entrypoint)
             && is_default_register_entrypoint(&self.register_entrypoint)
             && is_default_authorization_grant_entrypoint(&self.authorization_grant_entrypoint)
+            && is_default_token_exchange_entrypoint(&self.token_exchange_entrypoint)
             && is_default_password_entrypoint(&self.password_entrypoint)
             && is_default_email_entrypoint(&self.email_entrypoint)
             && is_default_data(&self.data)
@@ -0,0 +1,112 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::{collections::BTreeSet, ops::Deref};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::Error};
+use ulid::Ulid;
+use url::Url;
+
+use crate::ConfigurationSection;
+
+/// A resource server, which clients can get tokens for using resource
+/// indicators
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct ResourceServerConfig {
+    /// The URI identifying the resource server, as passed by clients in the
+    /// `resource` parameter
+    pub resource: Url,
+
+    /// The ID of the client the resource server uses to introspect tokens. It
+    /// can only introspect tokens issued for this resource server
+    #[schemars(
+        with = "String",
+        regex(pattern = r"^[0123456789ABCDEFGHJKMNPQRSTVWXYZ]{26}$"),
+        description = "A ULID as per https://github.com/ulid/spec"
+    )]
+    pub client_id: Ulid,
+}
+
+/// List of resource servers, for resource indicators (RFC 8707)
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema, Serialize)]
+#[serde(transparent)]
+pub struct ResourceServersConfig(
+    #[schemars(with = "Vec::<ResourceServerConfig>")] Vec<ResourceServerConfig>,
+);
+
+impl ResourceServersConfig {
+    /// Returns true if the configuration is the default one
+    pub(crate) fn is_default(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Deref for ResourceServersConfig {
+    type Target = Vec<ResourceServerConfig>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl ConfigurationSection for ResourceServersConfig {
+    const PATH: Option<&'static str> = Some("resource_servers");
+
+    fn validate(
+        &self,
+        figment: &figment::Figment,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let annotate = |mut error: figment::error::Error, index: usize, field: &'static str| {
+            error.metadata = figment.find_metadata(Self::PATH.unwrap()).cloned();
+            error.profile = Some(figment::Profile::Default);
+            error.path = vec![
+                Self::PATH.unwrap().to_owned(),
+                index.to_string(),
+                field.to_owned(),
+            ];
+            error
+        };
+
+        let mut seen_resources = BTreeSet::new();
+        let mut seen_clients = BTreeSet::new();
+        for (index, server) in self.0.iter().enumerate() {
+            if server.resource.fragment().is_some() {
+                return Err(annotate(
+                    figment::error::Error::custom("resource must not have a fragment"),
+                    index,
+                    "resource",
+                )
+                .into());
+            }
+
+            if !seen_resources.insert(server.resource.as_str()) {
+                return Err(annotate(
+                    figment::error::Error::custom(format!(
+                        "duplicate resource {:?}",
+                        server.resource.as_str()
+                    )),
+                    index,
+                    "resource",
+                )
+                .into());
+            }
+
+            if !seen_clients.insert(server.client_id) {
+                return Err(annotate(
+                    figment::error::Error::custom(format!(
+                        "client {} is already used by another resource server",
+                        server.client_id
+                    )),
+                    index,
+                    "client_id",
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
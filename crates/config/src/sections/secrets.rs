@@ -252,6 +252,20 @@ pub struct SecretsConfig {
     /// List of private keys to use for signing and encrypting payloads
     #[serde(default)]
     keys: Vec<KeyConfig>,
+
+    /// Secret used to derive pairwise subject identifiers, for clients which
+    /// registered with the `pairwise` subject type.
+    ///
+    /// Changing it changes the subject identifiers those clients see. If not
+    /// set, clients can't register with the `pairwise` subject type.
+    #[schemars(
+        with = "Option<String>",
+        regex(pattern = r"[0-9a-fA-F]{64}"),
+        example = "example_secret"
+    )]
+    #[serde_as(as = "Option<serde_with::hex::Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pairwise_subject_secret: Option<[u8; 32]>,
 }
 
 impl SecretsConfig {
@@ -276,6 +290,13 @@ impl SecretsConfig {
         Ok(Encrypter::new(&self.encryption().await?))
     }
 
+    /// Returns the secret used to derive pairwise subject identifiers, if
+    /// configured.
+    #[must_use]
+    pub fn pairwise_subject_secret(&self) -> Option<[u8; 32]> {
+        self.pairwise_subject_secret
+    }
+
     /// Returns the encryption secret.
     ///
     /// # Errors
@@ -377,6 +398,7 @@ impl SecretsConfig {
         Ok(Self {
             encryption: Encryption::Value(Standard.sample(&mut rng)),
             keys: vec![rsa_key, ec_p256_key, ec_p384_key, ec_k256_key],
+            pairwise_subject_secret: Some(Standard.sample(&mut rng)),
         })
     }
 
@@ -418,6 +440,7 @@ impl SecretsConfig {
         Self {
             encryption: Encryption::Value([0xEA; 32]),
             keys: vec![rsa_key, ecdsa_key],
+            pairwise_subject_secret: None,
         }
     }
 }
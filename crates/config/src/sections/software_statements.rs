@@ -0,0 +1,144 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::collections::BTreeSet;
+
+use mas_jose::jwk::PublicJsonWebKeySet;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::Error};
+use url::Url;
+
+use crate::ConfigurationSection;
+
+/// A software publisher trusted to sign software statements
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct SoftwareStatementPublisherConfig {
+    /// The expected value of the `iss` claim in the software statements
+    pub issuer: String,
+
+    /// The JSON Web Key Set used to verify the software statements. Mutually
+    /// exclusive with `jwks_uri`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwks: Option<PublicJsonWebKeySet>,
+
+    /// The URL of the JSON Web Key Set used to verify the software statements.
+    /// Mutually exclusive with `jwks`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwks_uri: Option<Url>,
+}
+
+/// Configuration of the software statements accepted during dynamic client
+/// registration (RFC 7591)
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema, Serialize)]
+pub struct SoftwareStatementsConfig {
+    /// List of software publishers trusted to sign software statements
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub publishers: Vec<SoftwareStatementPublisherConfig>,
+}
+
+impl SoftwareStatementsConfig {
+    /// Returns true if the configuration is the default one
+    pub(crate) fn is_default(&self) -> bool {
+        self.publishers.is_empty()
+    }
+}
+
+impl ConfigurationSection for SoftwareStatementsConfig {
+    const PATH: Option<&'static str> = Some("software_statements");
+
+    fn validate(
+        &self,
+        figment: &figment::Figment,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let metadata = figment.find_metadata(Self::PATH.unwrap());
+
+        let annotate = |mut error: figment::error::Error, index: usize, field: &'static str| {
+            error.metadata = metadata.cloned();
+            error.profile = Some(figment::Profile::Default);
+            error.path = vec![
+                Self::PATH.unwrap().to_owned(),
+                "publishers".to_owned(),
+                index.to_string(),
+                field.to_owned(),
+            ];
+            error
+        };
+
+        let mut seen = BTreeSet::new();
+        for (index, publisher) in self.publishers.iter().enumerate() {
+            if !seen.insert(&publisher.issuer) {
+                return Err(annotate(
+                    figment::error::Error::custom(format!(
+                        "duplicate issuer {:?}",
+                        publisher.issuer
+                    )),
+                    index,
+                    "issuer",
+                )
+                .into());
+            }
+
+            match (&publisher.jwks, &publisher.jwks_uri) {
+                (None, None) => {
+                    return Err(annotate(
+                        figment::error::Error::custom("one of jwks or jwks_uri is required"),
+                        index,
+                        "jwks",
+                    )
+                    .into());
+                }
+                (Some(_), Some(_)) => {
+                    return Err(annotate(
+                        figment::error::Error::custom("jwks and jwks_uri are mutually exclusive"),
+                        index,
+                        "jwks",
+                    )
+                    .into());
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use figment::{
+        Figment, Jail,
+        providers::{Format, Yaml},
+    };
+
+    use super::*;
+
+    #[test]
+    fn load_config() {
+        Jail::expect_with(|jail| {
+            jail.create_file(
+                "config.yaml",
+                r#"
+                    software_statements:
+                      publishers:
+                        - issuer: https://publisher.example.com/
+                          jwks_uri: https://publisher.example.com/jwks.json
+                "#,
+            )?;
+
+            let config = Figment::new()
+                .merge(Yaml::file("config.yaml"))
+                .extract_inner::<SoftwareStatementsConfig>("software_statements")?;
+
+            assert_eq!(config.publishers.len(), 1);
+            assert_eq!(
+                config.publishers[0].issuer,
+                "https://publisher.example.com/"
+            );
+            assert!(config.publishers[0].jwks.is_none());
+
+            Ok(())
+        });
+    }
+}
@@ -30,13 +30,19 @@ pub use self::{
         CompatSessionState, CompatSsoLogin, CompatSsoLoginState, Device, ToScopeTokenError,
     },
     oauth2::{
-        AuthorizationCode, AuthorizationGrant, AuthorizationGrantStage, Client, DeviceCodeGrant,
-        DeviceCodeGrantState, InvalidRedirectUriError, JwksOrJwksUri, Pkce, Session, SessionState,
+        AuthenticationCheck, AuthorizationCode, AuthorizationGrant, AuthorizationGrantStage,
+        Client, Consent, DeviceCodeGrant, DeviceCodeGrantState, InvalidRedirectUriError,
+        JwksOrJwksUri, MissingPairwiseSubjectSecretError, PUSHED_AUTHORIZATION_REQUEST_URI_PREFIX,
+        Pkce, PushedAuthorizationRequest, Session, SessionState,
     },
     policy_data::PolicyData,
-    site_config::{CaptchaConfig, CaptchaService, SessionExpirationConfig, SiteConfig},
+    site_config::{
+        AcrLevel, CaptchaConfig, CaptchaService, JwtAccessTokenConfig, JwtBearerIssuer,
+        ResourceServer, SessionExpirationConfig, SiteConfig, SoftwareStatementPublisher,
+    },
     tokens::{
-        AccessToken, AccessTokenState, RefreshToken, RefreshTokenState, TokenFormatError, TokenType,
+        AccessToken, AccessTokenState, RefreshToken, RefreshTokenReuse, RefreshTokenState,
+        TokenFormatError, TokenType,
     },
     upstream_oauth2::{
         UpstreamOAuthAuthorizationSession, UpstreamOAuthAuthorizationSessionState,
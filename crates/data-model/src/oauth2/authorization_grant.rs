@@ -4,11 +4,11 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use mas_iana::oauth::PkceCodeChallengeMethod;
 use oauth2_types::{
     pkce::{CodeChallengeError, CodeChallengeMethodExt},
-    requests::ResponseMode,
+    requests::{AuthorizationDetails, ClaimsParameter, ResponseMode},
     scope::{OPENID, PROFILE, Scope},
 };
 use rand::{
@@ -21,7 +21,7 @@ use ulid::Ulid;
 use url::Url;
 
 use super::session::Session;
-use crate::InvalidTransitionError;
+use crate::{AcrLevel, Authentication, InvalidTransitionError};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Pkce {
@@ -145,6 +145,21 @@ pub enum LoginHint<'a> {
     None,
 }
 
+/// The outcome of checking the last authentication of a user against the
+/// requirements of an [`AuthorizationGrant`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthenticationCheck {
+    /// The last authentication is recent and strong enough
+    Satisfied,
+
+    /// The user has to log in again
+    Reauthenticate,
+
+    /// The user logged in again for this grant, but still doesn't satisfy the
+    /// requested ACR levels
+    Unmet,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct AuthorizationGrant {
     pub id: Ulid,
@@ -161,6 +176,20 @@ pub struct AuthorizationGrant {
     pub created_at: DateTime<Utc>,
     pub login_hint: Option<String>,
     pub locale: Option<String>,
+    pub requested_claims: Option<ClaimsParameter>,
+    pub authorization_details: Option<AuthorizationDetails>,
+    pub resource: Option<Url>,
+
+    /// The maximum age of the user authentication, in seconds. A value of `0`
+    /// is how `prompt=login` is recorded
+    pub max_age: Option<u32>,
+
+    /// The ACR values the client requested
+    pub acr_values: Vec<String>,
+
+    /// Whether the client asked for `prompt=consent`, in which case the user
+    /// is asked for their consent even if they already gave it before
+    pub requires_consent: bool,
 }
 
 impl std::ops::Deref for AuthorizationGrant {
@@ -202,6 +231,59 @@ impl AuthorizationGrant {
         }
     }
 
+    /// Check whether the last authentication of the user is recent and strong
+    /// enough for this grant to be fulfilled
+    ///
+    /// The user has to log in again if their last authentication is older
+    /// than the requested `max_age`, or doesn't satisfy any of the requested
+    /// ACR levels. Authentications which happened after the grant was created
+    /// are always recent enough, but they still need to satisfy the requested
+    /// ACR levels: if they don't, logging in again won't help.
+    #[must_use]
+    pub fn check_authentication(
+        &self,
+        last_authentication: Option<&Authentication>,
+        acr_levels: &[AcrLevel],
+        now: DateTime<Utc>,
+    ) -> AuthenticationCheck {
+        // Only consider the ACR values we know about, the weakest of them being
+        // the minimum level we expect
+        let requested_level = acr_levels
+            .iter()
+            .position(|level| self.acr_values.contains(&level.value));
+
+        if self.max_age.is_none() && requested_level.is_none() {
+            return AuthenticationCheck::Satisfied;
+        }
+
+        let Some(authentication) = last_authentication else {
+            return AuthenticationCheck::Reauthenticate;
+        };
+
+        let fresh = authentication.created_at >= self.created_at;
+
+        if let Some(max_age) = self.max_age
+            && !fresh
+            && now - authentication.created_at > Duration::seconds(max_age.into())
+        {
+            return AuthenticationCheck::Reauthenticate;
+        }
+
+        if let Some(requested_level) = requested_level
+            && !acr_levels[requested_level..]
+                .iter()
+                .any(|level| level.is_satisfied_by(authentication))
+        {
+            return if fresh {
+                AuthenticationCheck::Unmet
+            } else {
+                AuthenticationCheck::Reauthenticate
+            };
+        }
+
+        AuthenticationCheck::Satisfied
+    }
+
     /// Mark the authorization grant as exchanged.
     ///
     /// # Errors
@@ -265,6 +347,12 @@ impl AuthorizationGrant {
             created_at: now,
             login_hint: Some(String::from("mxid:@example-user:example.com")),
             locale: Some(String::from("fr")),
+            requested_claims: None,
+            authorization_details: None,
+            resource: None,
+            max_age: None,
+            acr_values: Vec::new(),
+            requires_consent: false,
         }
     }
 }
@@ -364,4 +452,118 @@ mod tests {
 
         assert!(matches!(hint, LoginHint::None));
     }
+
+    #[test]
+    fn reauthentication() {
+        #[allow(clippy::disallowed_methods)]
+        let mut rng = thread_rng();
+
+        #[allow(clippy::disallowed_methods)]
+        let now = Utc::now();
+
+        let levels = [
+            AcrLevel {
+                value: "low".to_owned(),
+                amr: vec!["pwd".to_owned(), "fed".to_owned()],
+            },
+            AcrLevel {
+                value: "high".to_owned(),
+                amr: vec!["fed".to_owned()],
+            },
+        ];
+
+        let password = Authentication {
+            id: Ulid::from_datetime_with_source(now.into(), &mut rng),
+            created_at: now - Duration::minutes(10),
+            authentication_method: crate::AuthenticationMethod::Password {
+                user_password_id: Ulid::nil(),
+            },
+        };
+
+        // Nothing requested
+        let grant = AuthorizationGrant::sample(now, &mut rng);
+        assert_eq!(
+            grant.check_authentication(Some(&password), &levels, now),
+            AuthenticationCheck::Satisfied
+        );
+        assert_eq!(
+            grant.check_authentication(None, &levels, now),
+            AuthenticationCheck::Satisfied
+        );
+
+        // max_age, with prompt=login recorded as 0
+        let grant = AuthorizationGrant {
+            max_age: Some(0),
+            ..AuthorizationGrant::sample(now, &mut rng)
+        };
+        assert_eq!(
+            grant.check_authentication(Some(&password), &levels, now),
+            AuthenticationCheck::Reauthenticate
+        );
+        assert_eq!(
+            grant.check_authentication(None, &levels, now),
+            AuthenticationCheck::Reauthenticate
+        );
+
+        let grant = AuthorizationGrant {
+            max_age: Some(3600),
+            ..AuthorizationGrant::sample(now, &mut rng)
+        };
+        assert_eq!(
+            grant.check_authentication(Some(&password), &levels, now),
+            AuthenticationCheck::Satisfied
+        );
+
+        // ACR levels; unknown values are ignored
+        let grant = AuthorizationGrant {
+            acr_values: vec!["low".to_owned(), "unknown".to_owned()],
+            ..AuthorizationGrant::sample(now, &mut rng)
+        };
+        assert_eq!(
+            grant.check_authentication(Some(&password), &levels, now),
+            AuthenticationCheck::Satisfied
+        );
+
+        let grant = AuthorizationGrant {
+            acr_values: vec!["high".to_owned()],
+            ..AuthorizationGrant::sample(now, &mut rng)
+        };
+        assert_eq!(
+            grant.check_authentication(Some(&password), &levels, now),
+            AuthenticationCheck::Reauthenticate
+        );
+
+        let grant = AuthorizationGrant {
+            acr_values: vec!["unknown".to_owned()],
+            ..AuthorizationGrant::sample(now, &mut rng)
+        };
+        assert_eq!(
+            grant.check_authentication(Some(&password), &levels, now),
+            AuthenticationCheck::Satisfied
+        );
+
+        // Authentications done after the grant was created are always recent
+        // enough
+        let grant = AuthorizationGrant {
+            max_age: Some(0),
+            acr_values: vec!["low".to_owned()],
+            ..AuthorizationGrant::sample(now - Duration::minutes(20), &mut rng)
+        };
+        assert_eq!(
+            grant.check_authentication(Some(&password), &levels, now),
+            AuthenticationCheck::Satisfied
+        );
+
+        // ...but they still have to be strong enough, and logging in again with
+        // the same method won't help
+        let grant = AuthorizationGrant {
+            max_age: Some(0),
+            acr_values: vec!["high".to_owned()],
+            ..AuthorizationGrant::sample(now - Duration::minutes(20), &mut rng)
+        };
+        assert_eq!(
+            grant.check_authentication(Some(&password), &levels, now),
+            AuthenticationCheck::Unmet
+        );
+    }
 }
@@ -4,20 +4,27 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
+use base64ct::{Base64UrlUnpadded, Encoding};
 use chrono::{DateTime, Utc};
-use mas_iana::{jose::JsonWebSignatureAlg, oauth::OAuthClientAuthenticationMethod};
+use mas_iana::{
+    jose::{JsonWebEncryptionAlg, JsonWebEncryptionEnc, JsonWebSignatureAlg},
+    oauth::OAuthClientAuthenticationMethod,
+};
 use mas_jose::jwk::PublicJsonWebKeySet;
 use oauth2_types::{
-    oidc::ApplicationType,
-    registration::{ClientMetadata, Localized},
+    oidc::{ApplicationType, BackchannelTokenDeliveryMode, SubjectType},
+    registration::{ClientMetadata, DEFAULT_ENCRYPTION_ENC_ALGORITHM, Localized},
     requests::GrantType,
 };
 use rand::RngCore;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 use ulid::Ulid;
 use url::Url;
 
+use crate::User;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum JwksOrJwksUri {
@@ -75,6 +82,43 @@ pub struct Client {
     /// JWS alg algorithm REQUIRED for signing `UserInfo` Responses.
     pub userinfo_signed_response_alg: Option<JsonWebSignatureAlg>,
 
+    /// JWS alg algorithm used for signing JWT introspection responses, as per
+    /// RFC 9701
+    pub introspection_signed_response_alg: Option<JsonWebSignatureAlg>,
+
+    /// JWE alg algorithm REQUIRED for encrypting the ID Token issued to this
+    /// Client
+    pub id_token_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
+
+    /// JWE enc algorithm REQUIRED for encrypting the ID Token issued to this
+    /// Client
+    pub id_token_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
+
+    /// JWE alg algorithm REQUIRED for encrypting `UserInfo` Responses
+    pub userinfo_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
+
+    /// JWE enc algorithm REQUIRED for encrypting `UserInfo` Responses
+    pub userinfo_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
+
+    /// JWE alg algorithm used for encrypting JWT introspection responses, as
+    /// per RFC 9701
+    pub introspection_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
+
+    /// JWE enc algorithm used for encrypting JWT introspection responses, as
+    /// per RFC 9701
+    pub introspection_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
+
+    /// JWS alg algorithm used for signing JWT-secured authorization responses
+    pub authorization_signed_response_alg: Option<JsonWebSignatureAlg>,
+
+    /// JWE alg algorithm used for encrypting JWT-secured authorization
+    /// responses
+    pub authorization_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
+
+    /// JWE enc algorithm used for encrypting JWT-secured authorization
+    /// responses
+    pub authorization_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
+
     /// Requested authentication method for the token endpoint
     pub token_endpoint_auth_method: Option<OAuthClientAuthenticationMethod>,
 
@@ -86,8 +130,67 @@ pub struct Client {
     /// URI using the https scheme that a third party can use to initiate a
     /// login by the RP
     pub initiate_login_uri: Option<Url>,
+
+    /// Whether the client must use pushed authorization requests to initiate
+    /// authorization grants
+    pub require_pushed_authorization_requests: bool,
+
+    /// List of URIs the client pre-registered to pass request objects by
+    /// reference through the `request_uri` parameter
+    pub request_uris: Vec<Url>,
+
+    /// The kind of subject identifiers this client gets
+    pub subject_type: Option<SubjectType>,
+
+    /// URL of a JSON document listing the redirect URIs of the sector this
+    /// client belongs to, used to compute pairwise subject identifiers
+    pub sector_identifier_uri: Option<Url>,
+
+    /// List of URIs the client may redirect to after logging out through the
+    /// end session endpoint
+    pub post_logout_redirect_uris: Vec<Url>,
+
+    /// URI to which logout tokens are sent when the user logs out, as per the
+    /// OpenID Connect Back-Channel Logout specification
+    pub backchannel_logout_uri: Option<Url>,
+
+    /// URI which is loaded in an iframe when the user logs out, as per the
+    /// OpenID Connect Front-Channel Logout specification
+    pub frontchannel_logout_uri: Option<Url>,
+
+    /// Whether the client requires the `iss` and `sid` query parameters to be
+    /// added to the `frontchannel_logout_uri`
+    pub frontchannel_logout_session_required: bool,
+
+    /// Whether the client must always use DPoP-bound access tokens, as per
+    /// RFC 9449
+    pub dpop_bound_access_tokens: bool,
+
+    /// The expected subject distinguished name of the certificate used for
+    /// `tls_client_auth` authentication, as per RFC 8705
+    pub tls_client_auth_subject_dn: Option<String>,
+
+    /// The expected DNS subject alternative name of the certificate used for
+    /// `tls_client_auth` authentication, as per RFC 8705
+    pub tls_client_auth_san_dns: Option<String>,
+
+    /// The mode used to deliver the result of client-initiated backchannel
+    /// authentication requests
+    pub backchannel_token_delivery_mode: Option<BackchannelTokenDeliveryMode>,
+
+    /// The URL where the client receives notifications about client-initiated
+    /// backchannel authentication requests
+    pub backchannel_client_notification_endpoint: Option<Url>,
+
+    /// Hash of the access token the client can use to manage its registration,
+    /// as per RFC 7592
+    pub registration_access_token_hash: Option<String>,
 }
 
+#[derive(Debug, Error)]
+#[error("client {0} uses pairwise subject identifiers, but no secret is configured to derive them")]
+pub struct MissingPairwiseSubjectSecretError(pub Ulid);
+
 #[derive(Debug, Error)]
 pub enum InvalidRedirectUriError {
     #[error("redirect_uri is not allowed for this client")]
@@ -123,6 +226,183 @@ impl Client {
         }
     }
 
+    /// Whether the given URI was registered as a post-logout redirect URI by
+    /// this client.
+    ///
+    /// Unlike redirect URIs, those are compared as plain strings.
+    #[must_use]
+    pub fn has_post_logout_redirect_uri(&self, uri: &Url) -> bool {
+        self.post_logout_redirect_uris.contains(uri)
+    }
+
+    /// Whether the given URI was pre-registered as a request URI by this
+    /// client.
+    ///
+    /// The fragment, which clients may use to distinguish versions of the
+    /// request object, is ignored on both sides of the comparison.
+    #[must_use]
+    pub fn has_request_uri(&self, uri: &Url) -> bool {
+        let mut uri = uri.clone();
+        uri.set_fragment(None);
+        self.request_uris.iter().any(|registered| {
+            let mut registered = registered.clone();
+            registered.set_fragment(None);
+            registered == uri
+        })
+    }
+
+    /// The URI to load in an iframe to log the user out of this client, if it
+    /// registered a front-channel logout URI.
+    ///
+    /// The `iss` and `sid` query parameters are only added if the client
+    /// requires them through `frontchannel_logout_session_required`.
+    #[must_use]
+    pub fn frontchannel_logout_uri_for(&self, issuer: &Url, session_id: Ulid) -> Option<Url> {
+        let mut uri = self.frontchannel_logout_uri.clone()?;
+        if self.frontchannel_logout_session_required {
+            uri.query_pairs_mut()
+                .append_pair("iss", issuer.as_str())
+                .append_pair("sid", &session_id.to_string());
+        }
+        Some(uri)
+    }
+
+    /// The sector identifier used to compute pairwise subject identifiers for
+    /// this client.
+    ///
+    /// This is the host of the `sector_identifier_uri` if set, else the host
+    /// of the redirect URIs. Clients without any redirect URI are their own
+    /// sector.
+    ///
+    /// Returns `None` if the client gets public subject identifiers.
+    #[must_use]
+    pub fn pairwise_sector_identifier(&self) -> Option<&str> {
+        if self.subject_type != Some(SubjectType::Pairwise) {
+            return None;
+        }
+
+        let sector = self
+            .sector_identifier_uri
+            .as_ref()
+            .or_else(|| self.redirect_uris.first())
+            .and_then(Url::host_str)
+            .unwrap_or(&self.client_id);
+
+        Some(sector)
+    }
+
+    /// The subject identifier of the given user, as seen by this client.
+    ///
+    /// For clients using pairwise subject identifiers, it is derived from the
+    /// sector of the client, the public subject identifier of the user and
+    /// the given secret, so that clients from different sectors can't
+    /// correlate users.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client uses pairwise subject identifiers, but
+    /// no secret was given
+    pub fn subject_for(
+        &self,
+        user: &User,
+        pairwise_subject_secret: Option<&[u8; 32]>,
+    ) -> Result<String, MissingPairwiseSubjectSecretError> {
+        let Some(sector) = self.pairwise_sector_identifier() else {
+            return Ok(user.sub.clone());
+        };
+
+        let secret = pairwise_subject_secret.ok_or(MissingPairwiseSubjectSecretError(self.id))?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(sector.as_bytes());
+        hasher.update([0]);
+        hasher.update(user.sub.as_bytes());
+        hasher.update([0]);
+        hasher.update(secret);
+
+        Ok(Base64UrlUnpadded::encode_string(&hasher.finalize()))
+    }
+
+    /// The JWE `alg` and `enc` algorithms used to encrypt the ID tokens issued to this
+    /// client, if it registered any.
+    ///
+    /// The `enc` algorithm defaults to [`DEFAULT_ENCRYPTION_ENC_ALGORITHM`] if
+    /// only the `alg` one is set.
+    #[must_use]
+    pub fn id_token_encrypted_response(
+        &self,
+    ) -> Option<(&JsonWebEncryptionAlg, &JsonWebEncryptionEnc)> {
+        self.id_token_encrypted_response_alg.as_ref().map(|alg| {
+            (
+                alg,
+                self.id_token_encrypted_response_enc
+                    .as_ref()
+                    .unwrap_or(DEFAULT_ENCRYPTION_ENC_ALGORITHM),
+            )
+        })
+    }
+
+    /// The JWE `alg` and `enc` algorithms used to encrypt the `UserInfo` responses sent to this
+    /// client, if it registered any.
+    ///
+    /// The `enc` algorithm defaults to [`DEFAULT_ENCRYPTION_ENC_ALGORITHM`] if
+    /// only the `alg` one is set.
+    #[must_use]
+    pub fn userinfo_encrypted_response(
+        &self,
+    ) -> Option<(&JsonWebEncryptionAlg, &JsonWebEncryptionEnc)> {
+        self.userinfo_encrypted_response_alg.as_ref().map(|alg| {
+            (
+                alg,
+                self.userinfo_encrypted_response_enc
+                    .as_ref()
+                    .unwrap_or(DEFAULT_ENCRYPTION_ENC_ALGORITHM),
+            )
+        })
+    }
+
+    /// The JWE `alg` and `enc` algorithms used to encrypt the JWT
+    /// introspection responses sent to this client, if it registered any.
+    ///
+    /// The `enc` algorithm defaults to [`DEFAULT_ENCRYPTION_ENC_ALGORITHM`] if
+    /// only the `alg` one is set.
+    #[must_use]
+    pub fn introspection_encrypted_response(
+        &self,
+    ) -> Option<(&JsonWebEncryptionAlg, &JsonWebEncryptionEnc)> {
+        self.introspection_encrypted_response_alg
+            .as_ref()
+            .map(|alg| {
+                (
+                    alg,
+                    self.introspection_encrypted_response_enc
+                        .as_ref()
+                        .unwrap_or(DEFAULT_ENCRYPTION_ENC_ALGORITHM),
+                )
+            })
+    }
+
+    /// The JWE `alg` and `enc` algorithms used to encrypt the JWT-secured
+    /// authorization responses sent to this client, if it registered any.
+    ///
+    /// The `enc` algorithm defaults to [`DEFAULT_ENCRYPTION_ENC_ALGORITHM`] if
+    /// only the `alg` one is set.
+    #[must_use]
+    pub fn authorization_encrypted_response(
+        &self,
+    ) -> Option<(&JsonWebEncryptionAlg, &JsonWebEncryptionEnc)> {
+        self.authorization_encrypted_response_alg
+            .as_ref()
+            .map(|alg| {
+                (
+                    alg,
+                    self.authorization_encrypted_response_enc
+                        .as_ref()
+                        .unwrap_or(DEFAULT_ENCRYPTION_ENC_ALGORITHM),
+                )
+            })
+    }
+
     /// Create a client metadata object for this client
     #[must_use]
     pub fn into_metadata(self) -> ClientMetadata {
@@ -151,29 +431,47 @@ impl Client {
             contacts: None,
             software_id: None,
             software_version: None,
-            sector_identifier_uri: None,
-            subject_type: None,
-            id_token_encrypted_response_alg: None,
-            id_token_encrypted_response_enc: None,
-            userinfo_encrypted_response_alg: None,
-            userinfo_encrypted_response_enc: None,
+            software_statement: None,
+            sector_identifier_uri: self.sector_identifier_uri,
+            subject_type: self.subject_type,
+            id_token_encrypted_response_alg: self.id_token_encrypted_response_alg,
+            id_token_encrypted_response_enc: self.id_token_encrypted_response_enc,
+            userinfo_encrypted_response_alg: self.userinfo_encrypted_response_alg,
+            userinfo_encrypted_response_enc: self.userinfo_encrypted_response_enc,
             request_object_signing_alg: None,
             request_object_encryption_alg: None,
             request_object_encryption_enc: None,
             default_max_age: None,
             require_auth_time: None,
             default_acr_values: None,
-            request_uris: None,
+            request_uris: Some(self.request_uris).filter(|uris| !uris.is_empty()),
             require_signed_request_object: None,
-            require_pushed_authorization_requests: None,
-            introspection_signed_response_alg: None,
-            introspection_encrypted_response_alg: None,
-            introspection_encrypted_response_enc: None,
-            post_logout_redirect_uris: None,
+            require_pushed_authorization_requests: self
+                .require_pushed_authorization_requests
+                .then_some(true),
+            introspection_signed_response_alg: self.introspection_signed_response_alg,
+            introspection_encrypted_response_alg: self.introspection_encrypted_response_alg,
+            introspection_encrypted_response_enc: self.introspection_encrypted_response_enc,
+            authorization_signed_response_alg: self.authorization_signed_response_alg,
+            authorization_encrypted_response_alg: self.authorization_encrypted_response_alg,
+            authorization_encrypted_response_enc: self.authorization_encrypted_response_enc,
+            post_logout_redirect_uris: Some(self.post_logout_redirect_uris)
+                .filter(|uris| !uris.is_empty()),
+            backchannel_logout_uri: self.backchannel_logout_uri,
+            frontchannel_logout_uri: self.frontchannel_logout_uri,
+            frontchannel_logout_session_required: self
+                .frontchannel_logout_session_required
+                .then_some(true),
+            dpop_bound_access_tokens: self.dpop_bound_access_tokens.then_some(true),
+            tls_client_auth_subject_dn: self.tls_client_auth_subject_dn,
+            tls_client_auth_san_dns: self.tls_client_auth_san_dns,
+            backchannel_token_delivery_mode: self.backchannel_token_delivery_mode,
+            backchannel_client_notification_endpoint: self.backchannel_client_notification_endpoint,
         }
     }
 
     #[doc(hidden)]
+    #[allow(clippy::too_many_lines)]
     pub fn samples(now: DateTime<Utc>, rng: &mut impl RngCore) -> Vec<Client> {
         vec![
             // A client with all the URIs set
@@ -200,7 +498,37 @@ impl Client {
                 token_endpoint_auth_signing_alg: None,
                 id_token_signed_response_alg: None,
                 userinfo_signed_response_alg: None,
+                introspection_signed_response_alg: None,
+                id_token_encrypted_response_alg: None,
+                id_token_encrypted_response_enc: None,
+                userinfo_encrypted_response_alg: None,
+                userinfo_encrypted_response_enc: None,
+                introspection_encrypted_response_alg: None,
+                introspection_encrypted_response_enc: None,
+                authorization_signed_response_alg: None,
+                authorization_encrypted_response_alg: None,
+                authorization_encrypted_response_enc: None,
                 jwks: None,
+                require_pushed_authorization_requests: false,
+                request_uris: Vec::new(),
+                subject_type: None,
+                sector_identifier_uri: None,
+                post_logout_redirect_uris: vec![
+                    Url::parse("https://client1.example.com/logged-out").unwrap(),
+                ],
+                backchannel_logout_uri: Some(
+                    Url::parse("https://client1.example.com/backchannel-logout").unwrap(),
+                ),
+                frontchannel_logout_uri: Some(
+                    Url::parse("https://client1.example.com/frontchannel-logout").unwrap(),
+                ),
+                frontchannel_logout_session_required: true,
+                dpop_bound_access_tokens: false,
+                tls_client_auth_subject_dn: None,
+                tls_client_auth_san_dns: None,
+                backchannel_token_delivery_mode: None,
+                backchannel_client_notification_endpoint: None,
+                registration_access_token_hash: None,
             },
             // Another client without any URIs set
             Self {
@@ -221,7 +549,31 @@ impl Client {
                 token_endpoint_auth_signing_alg: None,
                 id_token_signed_response_alg: None,
                 userinfo_signed_response_alg: None,
+                introspection_signed_response_alg: None,
+                id_token_encrypted_response_alg: None,
+                id_token_encrypted_response_enc: None,
+                userinfo_encrypted_response_alg: None,
+                userinfo_encrypted_response_enc: None,
+                introspection_encrypted_response_alg: None,
+                introspection_encrypted_response_enc: None,
+                authorization_signed_response_alg: None,
+                authorization_encrypted_response_alg: None,
+                authorization_encrypted_response_enc: None,
                 jwks: None,
+                require_pushed_authorization_requests: false,
+                request_uris: Vec::new(),
+                subject_type: None,
+                sector_identifier_uri: None,
+                post_logout_redirect_uris: Vec::new(),
+                backchannel_logout_uri: None,
+                frontchannel_logout_uri: None,
+                frontchannel_logout_session_required: false,
+                dpop_bound_access_tokens: false,
+                tls_client_auth_subject_dn: None,
+                tls_client_auth_san_dns: None,
+                backchannel_token_delivery_mode: None,
+                backchannel_client_notification_endpoint: None,
+                registration_access_token_hash: None,
             },
         ]
     }
@@ -248,6 +600,8 @@ fn uri_matches_one_of(uri: &Url, registered_uris: &[Url]) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
     use url::Url;
 
     use super::*;
@@ -283,4 +637,50 @@ mod tests {
             registered_uris
         ));
     }
+
+    #[test]
+    fn test_pairwise_subject() {
+        let now = DateTime::UNIX_EPOCH;
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let user = &crate::User::samples(now, &mut rng)[0];
+        let secret = [0x42; 32];
+
+        let mut clients = Client::samples(now, &mut rng);
+        let mut other_client = clients.pop().unwrap();
+        let mut client = clients.pop().unwrap();
+
+        // Clients get the public subject identifier by default
+        assert_eq!(client.pairwise_sector_identifier(), None);
+        assert_eq!(client.subject_for(user, None).unwrap(), user.sub);
+
+        client.subject_type = Some(SubjectType::Pairwise);
+        other_client.subject_type = Some(SubjectType::Pairwise);
+        assert_eq!(
+            client.pairwise_sector_identifier(),
+            Some("client1.example.com")
+        );
+
+        // A secret is required to derive pairwise subject identifiers
+        assert!(client.subject_for(user, None).is_err());
+
+        let sub = client.subject_for(user, Some(&secret)).unwrap();
+        let other_sub = other_client.subject_for(user, Some(&secret)).unwrap();
+        assert_ne!(sub, user.sub);
+        assert_ne!(sub, other_sub);
+
+        // Clients in the same sector get the same subject identifier
+        other_client.sector_identifier_uri =
+            Some(Url::parse("https://client1.example.com/sector.json").unwrap());
+        assert_eq!(
+            other_client.pairwise_sector_identifier(),
+            Some("client1.example.com")
+        );
+        assert_eq!(other_client.subject_for(user, Some(&secret)).unwrap(), sub);
+
+        // ...but only with the same secret
+        assert_ne!(
+            other_client.subject_for(user, Some(&[0x43; 32])).unwrap(),
+            sub
+        );
+    }
 }
@@ -0,0 +1,111 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use chrono::{DateTime, Utc};
+use oauth2_types::scope::Scope;
+use serde::Serialize;
+use ulid::Ulid;
+
+use crate::Device;
+
+/// The scope a user agreed to grant to a client, remembered so that they
+/// don't get asked again on subsequent authorizations
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Consent {
+    pub id: Ulid,
+
+    /// The user who gave their consent
+    pub user_id: Ulid,
+
+    /// The client the consent was given to
+    pub client_id: Ulid,
+
+    /// All the scope tokens the user consented to
+    pub scope: Scope,
+
+    /// When the user first consented
+    pub created_at: DateTime<Utc>,
+
+    /// When the user last consented again, possibly to more scope tokens
+    pub refreshed_at: Option<DateTime<Utc>>,
+}
+
+impl Consent {
+    /// The part of a scope which is remembered in a consent
+    ///
+    /// Matrix clients ask for a new device scope token every time they log in,
+    /// so those are left out: they only identify the session being created.
+    #[must_use]
+    pub fn remembered_scope(scope: &Scope) -> Scope {
+        scope
+            .iter()
+            .filter(|token| Device::from_scope_token(token).is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// Whether the user already consented to all the tokens of the given
+    /// scope
+    #[must_use]
+    pub fn covers(&self, scope: &Scope) -> bool {
+        Self::remembered_scope(scope).is_subset(&self.scope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2_types::scope::{EMAIL, OPENID, PROFILE, ScopeToken};
+
+    use super::*;
+
+    #[test]
+    fn covers() {
+        #[allow(clippy::disallowed_methods)]
+        let now = Utc::now();
+        let consent = Consent {
+            id: Ulid::nil(),
+            user_id: Ulid::nil(),
+            client_id: Ulid::nil(),
+            scope: [OPENID, EMAIL].into_iter().collect(),
+            created_at: now,
+            refreshed_at: None,
+        };
+
+        assert!(consent.covers(&[OPENID].into_iter().collect()));
+        assert!(consent.covers(&[OPENID, EMAIL].into_iter().collect()));
+        assert!(!consent.covers(&[OPENID, PROFILE].into_iter().collect()));
+    }
+
+    #[test]
+    fn covers_device_scopes() {
+        #[allow(clippy::disallowed_methods)]
+        let now = Utc::now();
+        let api: ScopeToken = "urn:matrix:org.matrix.msc2967.client:api:*"
+            .parse()
+            .unwrap();
+        let device = Device::from("ABCDEFGHIJ".to_owned())
+            .to_scope_token()
+            .unwrap();
+
+        let scope: Scope = [OPENID, api.clone(), device].into_iter().collect();
+        let remembered = Consent::remembered_scope(&scope);
+        assert_eq!(remembered, [OPENID, api.clone()].into_iter().collect());
+
+        let consent = Consent {
+            id: Ulid::nil(),
+            user_id: Ulid::nil(),
+            client_id: Ulid::nil(),
+            scope: remembered,
+            created_at: now,
+            refreshed_at: None,
+        };
+
+        // A new login, with another device
+        let other_device = Device::from("KLMNOPQRST".to_owned())
+            .to_scope_token()
+            .unwrap();
+        assert!(consent.covers(&[OPENID, api, other_device].into_iter().collect()));
+    }
+}
@@ -193,6 +193,18 @@ pub struct DeviceCodeGrant {
 
     /// The user agent used to request this device code grant.
     pub user_agent: Option<String>,
+
+    /// The user for whom authentication was requested, if this grant was
+    /// started through a client-initiated backchannel authentication request.
+    pub user_id: Option<Ulid>,
+
+    /// The message to display to the user to tie the request to the device
+    /// which started it.
+    pub binding_message: Option<String>,
+
+    /// The token used to authenticate the notification sent to the client,
+    /// in the `ping` token delivery mode.
+    pub client_notification_token: Option<String>,
 }
 
 impl std::ops::Deref for DeviceCodeGrant {
@@ -204,6 +216,13 @@ impl std::ops::Deref for DeviceCodeGrant {
 }
 
 impl DeviceCodeGrant {
+    /// Whether this grant was started through a client-initiated backchannel
+    /// authentication request, rather than the device authorization flow.
+    #[must_use]
+    pub fn is_backchannel_authentication(&self) -> bool {
+        self.user_id.is_some()
+    }
+
     /// Mark this device code grant as fulfilled, returning the updated grant.
     ///
     /// # Errors
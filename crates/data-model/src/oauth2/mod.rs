@@ -6,14 +6,21 @@
 
 mod authorization_grant;
 mod client;
+mod consent;
 mod device_code_grant;
+mod pushed_authorization_request;
 mod session;
 
 pub use self::{
     authorization_grant::{
-        AuthorizationCode, AuthorizationGrant, AuthorizationGrantStage, LoginHint, Pkce,
+        AuthenticationCheck, AuthorizationCode, AuthorizationGrant, AuthorizationGrantStage,
+        LoginHint, Pkce,
     },
-    client::{Client, InvalidRedirectUriError, JwksOrJwksUri},
+    client::{Client, InvalidRedirectUriError, JwksOrJwksUri, MissingPairwiseSubjectSecretError},
+    consent::Consent,
     device_code_grant::{DeviceCodeGrant, DeviceCodeGrantState},
+    pushed_authorization_request::{
+        PUSHED_AUTHORIZATION_REQUEST_URI_PREFIX, PushedAuthorizationRequest,
+    },
     session::{Session, SessionState},
 };
@@ -0,0 +1,84 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use ulid::Ulid;
+
+use crate::InvalidTransitionError;
+
+/// The prefix of the `request_uri` values handed out by the pushed
+/// authorization request endpoint, as recommended by RFC 9126.
+pub const PUSHED_AUTHORIZATION_REQUEST_URI_PREFIX: &str = "urn:ietf:params:oauth:request_uri:";
+
+/// An authorization request pushed by a client through the pushed
+/// authorization request endpoint (RFC 9126)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PushedAuthorizationRequest {
+    pub id: Ulid,
+
+    /// The client which pushed this authorization request.
+    pub client_id: Ulid,
+
+    /// The random reference which is part of the `request_uri`.
+    pub reference: String,
+
+    /// The authorization request parameters, as sent by the client.
+    pub parameters: BTreeMap<String, String>,
+
+    /// The time at which this request was pushed.
+    pub created_at: DateTime<Utc>,
+
+    /// The time at which the `request_uri` expires.
+    pub expires_at: DateTime<Utc>,
+
+    /// The time at which the `request_uri` was used at the authorization
+    /// endpoint.
+    pub consumed_at: Option<DateTime<Utc>>,
+}
+
+impl PushedAuthorizationRequest {
+    /// The `request_uri` the client should use to reference this request.
+    #[must_use]
+    pub fn request_uri(&self) -> String {
+        format!(
+            "{PUSHED_AUTHORIZATION_REQUEST_URI_PREFIX}{}",
+            self.reference
+        )
+    }
+
+    /// Extract the reference from a `request_uri` value.
+    ///
+    /// Returns `None` if the `request_uri` was not issued by the pushed
+    /// authorization request endpoint.
+    #[must_use]
+    pub fn reference_from_request_uri(request_uri: &str) -> Option<&str> {
+        request_uri
+            .strip_prefix(PUSHED_AUTHORIZATION_REQUEST_URI_PREFIX)
+            .filter(|reference| !reference.is_empty())
+    }
+
+    /// Whether this request can still be used at the authorization endpoint.
+    #[must_use]
+    pub fn is_valid(&self, now: DateTime<Utc>) -> bool {
+        self.consumed_at.is_none() && now < self.expires_at
+    }
+
+    /// Mark this request as consumed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request was already consumed.
+    pub fn consume(mut self, consumed_at: DateTime<Utc>) -> Result<Self, InvalidTransitionError> {
+        if self.consumed_at.is_some() {
+            return Err(InvalidTransitionError);
+        }
+
+        self.consumed_at = Some(consumed_at);
+        Ok(self)
+    }
+}
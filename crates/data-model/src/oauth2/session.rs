@@ -7,7 +7,10 @@
 use std::net::IpAddr;
 
 use chrono::{DateTime, Utc};
-use oauth2_types::scope::Scope;
+use oauth2_types::{
+    requests::{ActorClaim, AuthorizationDetails, ClaimsParameter},
+    scope::Scope,
+};
 use serde::Serialize;
 use ulid::Ulid;
 
@@ -84,6 +87,12 @@ pub struct Session {
     pub last_active_at: Option<DateTime<Utc>>,
     pub last_active_ip: Option<IpAddr>,
     pub human_name: Option<String>,
+    pub requested_claims: Option<ClaimsParameter>,
+    pub authorization_details: Option<AuthorizationDetails>,
+    pub dpop_jkt: Option<String>,
+    pub x5t_s256: Option<String>,
+    pub audience: Vec<String>,
+    pub act: Option<ActorClaim>,
 }
 
 impl std::ops::Deref for Session {
@@ -5,8 +5,13 @@
 // Please see LICENSE files in the repository root for full details.
 
 use chrono::Duration;
+use mas_iana::jose::JsonWebSignatureAlg;
+use oauth2_types::scope::Scope;
+use ulid::Ulid;
 use url::Url;
 
+use crate::{Authentication, JwksOrJwksUri};
+
 /// Which Captcha service is being used
 #[derive(Debug, Clone, Copy)]
 pub enum CaptchaService {
@@ -28,6 +33,66 @@ pub struct CaptchaConfig {
     pub secret_key: String,
 }
 
+/// An issuer of JWTs which clients can present as authorization grants
+#[derive(Debug, Clone)]
+pub struct JwtBearerIssuer {
+    /// The expected `iss` claim of the assertions
+    pub issuer: String,
+
+    /// The keys used to verify the assertions
+    pub jwks: JwksOrJwksUri,
+
+    /// The upstream OAuth 2.0 provider whose links map the subject of the
+    /// assertions to users
+    pub upstream_provider_id: Ulid,
+}
+
+/// A software publisher trusted to sign software statements used in dynamic
+/// client registration
+#[derive(Debug, Clone)]
+pub struct SoftwareStatementPublisher {
+    /// The expected `iss` claim of the software statements
+    pub issuer: String,
+
+    /// The keys used to verify the software statements
+    pub jwks: JwksOrJwksUri,
+}
+
+/// Which access tokens are issued as JWTs, as per RFC 9068
+#[derive(Debug, Clone)]
+pub struct JwtAccessTokenConfig {
+    /// The IDs of the clients which get JWT access tokens
+    pub clients: Vec<Ulid>,
+
+    /// The scopes which, when granted, make the access tokens JWTs
+    pub scopes: Vec<String>,
+
+    /// The algorithm used to sign the access tokens
+    pub signing_alg: JsonWebSignatureAlg,
+}
+
+impl JwtAccessTokenConfig {
+    /// Whether access tokens issued to the given client, with the given scope,
+    /// should be JWTs
+    #[must_use]
+    pub fn applies_to(&self, client_id: Ulid, scope: &Scope) -> bool {
+        self.clients.contains(&client_id)
+            || scope
+                .iter()
+                .any(|token| self.scopes.iter().any(|s| s == token.as_str()))
+    }
+}
+
+/// A resource server clients can get tokens for, as per RFC 8707
+#[derive(Debug, Clone)]
+pub struct ResourceServer {
+    /// The URI identifying the resource server
+    pub resource: Url,
+
+    /// The ID of the client the resource server uses to introspect tokens
+    pub client_id: Ulid,
+}
+
 /// Automatic session expiration configuration
 #[derive(Debug, Clone)]
 pub struct SessionExpirationConfig {
@@ -36,6 +101,27 @@ pub struct SessionExpirationConfig {
     pub compat_session_inactivity_ttl: Option<Duration>,
 }
 
+/// An authentication context class reference (ACR) level
+#[derive(Debug, Clone)]
+pub struct AcrLevel {
+    /// The `acr` value of this level
+    pub value: String,
+
+    /// The authentication method references (`amr`) which satisfy this level
+    pub amr: Vec<String>,
+}
+
+impl AcrLevel {
+    /// Returns true if the given authentication satisfies this level
+    #[must_use]
+    pub fn is_satisfied_by(&self, authentication: &Authentication) -> bool {
+        authentication
+            .authentication_method
+            .amr()
+            .is_some_and(|amr| self.amr.iter().any(|value| value == amr))
+    }
+}
+
 /// Random site configuration we want accessible in various places.
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone)]
@@ -46,6 +132,10 @@ pub struct SiteConfig {
     /// Time-to-live of compatibility access tokens.
     pub compat_token_ttl: Duration,
 
+    /// Time during which a consumed refresh token can be presented again
+    /// without revoking the whole session.
+    pub refresh_token_reuse_grace_period: Duration,
+
     /// The server name, e.g. "matrix.org".
     pub server_name: String,
 
@@ -96,4 +186,36 @@ pub struct SiteConfig {
 
     /// The iframe URL to show in the plan tab of the UI
     pub plan_management_iframe_uri: Option<String>,
+
+    /// The secret used to derive pairwise subject identifiers, if configured
+    pub pairwise_subject_secret: Option<[u8; 32]>,
+
+    /// The issuers trusted for the JWT bearer authorization grant
+    pub jwt_bearer_issuers: Vec<JwtBearerIssuer>,
+
+    /// The software publishers trusted to sign software statements
+    pub software_statement_publishers: Vec<SoftwareStatementPublisher>,
+
+    /// Issue JWT access tokens to some clients or scopes, if configured
+    pub jwt_access_tokens: Option<JwtAccessTokenConfig>,
+
+    /// The authorization details types clients can request, as per RFC 9396
+    pub authorization_details_types: Vec<String>,
+
+    /// The resource servers clients can get tokens for, as per RFC 8707
+    pub resource_servers: Vec<ResourceServer>,
+
+    /// The ACR levels, from the weakest to the strongest
+    pub acr_levels: Vec<AcrLevel>,
+}
+
+impl SiteConfig {
+    /// The strongest ACR level satisfied by the given authentication, if any
+    #[must_use]
+    pub fn acr_for(&self, authentication: &Authentication) -> Option<&AcrLevel> {
+        self.acr_levels
+            .iter()
+            .rev()
+            .find(|level| level.is_satisfied_by(authentication))
+    }
 }
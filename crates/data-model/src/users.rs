@@ -70,6 +70,22 @@ pub enum AuthenticationMethod {
     Unknown,
 }
 
+impl AuthenticationMethod {
+    /// The authentication method reference (`amr`) value for this method, as
+    /// per RFC 8176
+    ///
+    /// Logins through an upstream provider are reported as `fed`, as we don't
+    /// know how the user authenticated with the provider.
+    #[must_use]
+    pub const fn amr(&self) -> Option<&'static str> {
+        match self {
+            Self::Password { .. } => Some("pwd"),
+            Self::UpstreamOAuth2 { .. } => Some("fed"),
+            Self::Unknown => None,
+        }
+    }
+}
+
 /// A session to recover a user if they have lost their credentials
 ///
 /// For each session intiated, there may be multiple [`UserRecoveryTicket`]s
@@ -9,19 +9,27 @@ use std::convert::Infallible;
 use aide::OperationIo;
 use axum::{
     Json,
-    extract::FromRequestParts,
+    extract::{FromRef, FromRequestParts, OriginalUri},
+    http::request::Parts,
     response::{IntoResponse, Response},
 };
 use axum_extra::TypedHeader;
-use headers::{Authorization, authorization::Bearer};
 use hyper::StatusCode;
-use mas_axum_utils::record_error;
+use mas_axum_utils::{
+    client_authorization::peer_certificate_thumbprint, record_error,
+    user_authorization::AccessTokenAuthorization,
+};
 use mas_data_model::{Session, User};
+use mas_router::UrlBuilder;
 use mas_storage::{BoxClock, BoxRepository, RepositoryError};
 use ulid::Ulid;
+use url::Url;
 
 use super::response::ErrorResponse;
-use crate::BoundActivityTracker;
+use crate::{
+    BoundActivityTracker,
+    oauth2::dpop::{self, DpopError},
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Rejection {
@@ -49,6 +57,19 @@ pub enum Rejection {
     #[error("Access token expired")]
     TokenExpired,
 
+    /// The `DPoP` proof sent with the access token is invalid
+    #[error("Invalid DPoP proof")]
+    InvalidDpopProof(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    /// Failed to verify the `DPoP` proof sent with the access token
+    #[error("Failed to verify the DPoP proof")]
+    DpopVerification(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    /// The access token was not presented with the `DPoP` proof or the TLS
+    /// client certificate its session requires
+    #[error("Access token not presented with the proof its session requires")]
+    SenderConstraintMismatch,
+
     /// The session associated with the access token was revoked
     #[error("Access token revoked")]
     SessionRevoked,
@@ -77,6 +98,7 @@ impl IntoResponse for Rejection {
             self,
             Self::RepositorySetup(_)
                 | Self::Repository(_)
+                | Self::DpopVerification(_)
                 | Self::LoadSession(_)
                 | Self::LoadUser(_)
         );
@@ -88,12 +110,15 @@ impl IntoResponse for Rejection {
 
             Rejection::UnknownAccessToken
             | Rejection::TokenExpired
+            | Rejection::InvalidDpopProof(_)
+            | Rejection::SenderConstraintMismatch
             | Rejection::SessionRevoked
             | Rejection::UserLocked
             | Rejection::MissingScope => StatusCode::UNAUTHORIZED,
 
             Rejection::RepositorySetup(_)
             | Rejection::Repository(_)
+            | Rejection::DpopVerification(_)
             | Rejection::LoadSession(_)
             | Rejection::LoadUser(_) => StatusCode::INTERNAL_SERVER_ERROR,
         };
@@ -116,9 +141,39 @@ pub struct CallContext {
     pub session: Session,
 }
 
+impl From<DpopError> for Rejection {
+    fn from(e: DpopError) -> Self {
+        if e.is_internal() {
+            Self::DpopVerification(Box::new(e))
+        } else {
+            Self::InvalidDpopProof(Box::new(e))
+        }
+    }
+}
+
+/// The URL the request was sent to, as seen by the client
+fn request_url(url_builder: &UrlBuilder, parts: &Parts) -> Result<Url, DpopError> {
+    let uri = parts
+        .extensions
+        .get::<OriginalUri>()
+        .map_or(&parts.uri, |OriginalUri(uri)| uri);
+
+    let path = uri.path();
+    let path = url_builder
+        .prefix()
+        .and_then(|prefix| path.strip_prefix(prefix))
+        .unwrap_or(path);
+
+    url_builder
+        .http_base()
+        .join(path.trim_start_matches('/'))
+        .map_err(|_| DpopError::WrongEndpoint)
+}
+
 impl<S> FromRequestParts<S> for CallContext
 where
     S: Send + Sync,
+    UrlBuilder: FromRef<S>,
     BoundActivityTracker: FromRequestParts<S, Rejection = Infallible>,
     BoxRepository: FromRequestParts<S>,
     BoxClock: FromRequestParts<S, Rejection = Infallible>,
@@ -141,24 +196,45 @@ where
             .map_err(Rejection::RepositorySetup)?;
 
         // Extract the access token from the authorization header
-        let token = TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
-            .await
-            .map_err(|e| {
-                // We map to two differentsson of errors depending on whether the header is
-                // missing or invalid
-                if e.is_missing() {
-                    Rejection::MissingAuthorizationHeader
-                } else {
-                    Rejection::InvalidAuthorizationHeader
-                }
-            })?;
-
-        let token = token.token();
+        let TypedHeader(authorization) =
+            TypedHeader::<AccessTokenAuthorization>::from_request_parts(parts, state)
+                .await
+                .map_err(|e| {
+                    // We map to two differentsson of errors depending on whether the header is
+                    // missing or invalid
+                    if e.is_missing() {
+                        Rejection::MissingAuthorizationHeader
+                    } else {
+                        Rejection::InvalidAuthorizationHeader
+                    }
+                })?;
+
+        // Tokens presented with the DPoP scheme must come with a proof for this request
+        let url_builder = UrlBuilder::from_ref(state);
+        let dpop_jkt = dpop::verify_resource_proof(
+            &clock,
+            &mut repo,
+            &parts.headers,
+            &parts.method,
+            &request_url(&url_builder, parts)?,
+            authorization.dpop_token(),
+        )
+        .await?;
+
+        if dpop_jkt.is_some() {
+            // Routes which only read don't save the repository, so record the JWT ID
+            // of the proof right away to prevent it from being replayed
+            repo.save().await?;
+            repo = BoxRepository::from_request_parts(parts, state)
+                .await
+                .map_err(Into::into)
+                .map_err(Rejection::RepositorySetup)?;
+        }
 
         // Look for the access token in the database
         let token = repo
             .oauth2_access_token()
-            .find_by_token(token)
+            .find_by_token(authorization.token())
             .await?
             .ok_or(Rejection::UnknownAccessToken)?;
 
@@ -197,6 +273,20 @@ where
             return Err(Rejection::SessionRevoked);
         }
 
+        // Tokens of sessions bound to a DPoP key can only be used with a proof
+        // signed by that key
+        if session.dpop_jkt.as_deref() != dpop_jkt.as_deref() {
+            return Err(Rejection::SenderConstraintMismatch);
+        }
+
+        // Tokens of sessions bound to a TLS client certificate can only be used over
+        // connections presenting that certificate
+        if session.x5t_s256.is_some()
+            && session.x5t_s256 != peer_certificate_thumbprint(&parts.extensions)
+        {
+            return Err(Rejection::SenderConstraintMismatch);
+        }
+
         if !token.is_valid(clock.now()) {
             return Err(Rejection::TokenExpired);
         }
@@ -53,6 +53,11 @@ fn finish(t: TransformOpenApi) -> TransformOpenApi {
             description: Some("Manage the dynamic policy data".to_owned()),
             ..Tag::default()
         })
+        .tag(Tag {
+            name: "oauth2-consent".to_owned(),
+            description: Some("Manage consents users gave to OAuth2 clients".to_owned()),
+            ..Tag::default()
+        })
         .tag(Tag {
             name: "oauth2-session".to_owned(),
             description: Some("Manage OAuth2 sessions".to_owned()),
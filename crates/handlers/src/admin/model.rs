@@ -280,6 +280,76 @@ impl CompatSession {
     }
 }
 
+/// The consent a user gave to an OAuth 2.0 client
+#[derive(Serialize, JsonSchema)]
+pub struct OAuth2Consent {
+    #[serde(skip)]
+    id: Ulid,
+
+    /// When the user first consented to the client
+    created_at: DateTime<Utc>,
+
+    /// When the user last consented to the client, if more than once
+    refreshed_at: Option<DateTime<Utc>>,
+
+    /// The ID of the user who gave this consent
+    #[schemars(with = "super::schema::Ulid")]
+    user_id: Ulid,
+
+    /// The ID of the client this consent was given to
+    #[schemars(with = "super::schema::Ulid")]
+    client_id: Ulid,
+
+    /// The scope the user consented to
+    scope: String,
+}
+
+impl Resource for OAuth2Consent {
+    const KIND: &'static str = "oauth2-consent";
+    const PATH: &'static str = "/api/admin/v1/oauth2-consents";
+
+    fn id(&self) -> Ulid {
+        self.id
+    }
+}
+
+impl From<mas_data_model::Consent> for OAuth2Consent {
+    fn from(consent: mas_data_model::Consent) -> Self {
+        Self {
+            id: consent.id,
+            created_at: consent.created_at,
+            refreshed_at: consent.refreshed_at,
+            user_id: consent.user_id,
+            client_id: consent.client_id,
+            scope: consent.scope.to_string(),
+        }
+    }
+}
+
+impl OAuth2Consent {
+    /// Samples of OAuth 2.0 consents
+    pub fn samples() -> [Self; 2] {
+        [
+            Self {
+                id: Ulid::from_bytes([0x01; 16]),
+                created_at: DateTime::default(),
+                refreshed_at: None,
+                user_id: Ulid::from_bytes([0x02; 16]),
+                client_id: Ulid::from_bytes([0x03; 16]),
+                scope: "openid".to_owned(),
+            },
+            Self {
+                id: Ulid::from_bytes([0x02; 16]),
+                created_at: DateTime::default(),
+                refreshed_at: Some(DateTime::default()),
+                user_id: Ulid::from_bytes([0x02; 16]),
+                client_id: Ulid::from_bytes([0x04; 16]),
+                scope: "email openid urn:matrix:org.matrix.msc2967.client:api:*".to_owned(),
+            },
+        ]
+    }
+}
+
 /// A OAuth 2.0 session
 #[derive(Serialize, JsonSchema)]
 pub struct OAuth2Session {
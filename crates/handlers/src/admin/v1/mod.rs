@@ -19,6 +19,7 @@ use super::call_context::CallContext;
 use crate::passwords::PasswordManager;
 
 mod compat_sessions;
+mod oauth2_consents;
 mod oauth2_sessions;
 mod policy_data;
 mod upstream_oauth_links;
@@ -46,6 +47,17 @@ where
             "/compat-sessions/{id}",
             get_with(self::compat_sessions::get, self::compat_sessions::get_doc),
         )
+        .api_route(
+            "/oauth2-consents",
+            get_with(self::oauth2_consents::list, self::oauth2_consents::list_doc),
+        )
+        .api_route(
+            "/oauth2-consents/{id}",
+            get_with(self::oauth2_consents::get, self::oauth2_consents::get_doc).delete_with(
+                self::oauth2_consents::delete,
+                self::oauth2_consents::delete_doc,
+            ),
+        )
         .api_route(
             "/oauth2-sessions",
             get_with(self::oauth2_sessions::list, self::oauth2_sessions::list_doc),
@@ -4,10 +4,14 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use aide::{OperationIo, transform::TransformOperation};
+use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
+use mas_storage::{
+    BoxRng, Clock,
+    queue::{BackchannelLogoutJob, QueueJobRepositoryExt as _},
+};
 use ulid::Ulid;
 
 use crate::{
@@ -49,7 +53,8 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
         .id("lockUser")
         .summary("Lock a user")
         .description("Calling this endpoint will lock the user, preventing them from doing any action.
-This DOES NOT invalidate any existing session, meaning that all their existing sessions will work again as soon as they get unlocked.")
+This DOES NOT invalidate any existing session, meaning that all their existing sessions will work again as soon as they get unlocked.
+Clients which registered a back-channel logout URI are still asked to log the user out.")
         .tag("user")
         .response_with::<200, Json<SingleResponse<User>>, _>(|t| {
             // In the samples, the third user is the one locked
@@ -69,6 +74,7 @@ pub async fn handler(
     CallContext {
         mut repo, clock, ..
     }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
     id: UlidPathParam,
 ) -> Result<Json<SingleResponse<User>>, RouteError> {
     let id = *id;
@@ -80,6 +86,16 @@ pub async fn handler(
 
     let user = repo.user().lock(&clock, user).await?;
 
+    // Even though the sessions stay valid, let the clients know that they
+    // should log the user out
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            BackchannelLogoutJob::for_user(&user, clock.now()),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok(Json(SingleResponse::new(
@@ -90,10 +106,24 @@ pub async fn handler(
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use chrono::Duration;
     use hyper::{Request, StatusCode};
-    use mas_storage::{Clock, RepositoryAccess, user::UserRepository};
-    use sqlx::PgPool;
+    use mas_iana::oauth::OAuthClientAuthenticationMethod;
+    use mas_jose::jwt::Jwt;
+    use mas_storage::{
+        Clock, RepositoryAccess,
+        oauth2::{OAuth2ClientRepository, OAuth2SessionRepository, OAuth2StaticClientParams},
+        user::{BrowserSessionRepository, UserRepository},
+    };
+    use oauth2_types::scope::{OPENID, Scope};
+    use sqlx::{PgPool, types::Json};
+    use ulid::Ulid;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path},
+    };
 
     use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
 
@@ -125,6 +155,118 @@ mod tests {
         );
     }
 
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_lock_user_backchannel_logout(pool: PgPool) {
+        setup();
+        let mock_server = MockServer::start().await;
+        let _mock_guard = Mock::given(method("POST"))
+            .and(path("/logout"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount_as_scoped(&mock_server)
+            .await;
+
+        let mut state = TestState::from_pool(pool.clone()).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+        let mut rng = state.rng();
+
+        // Provision a client with a back-channel logout URI, and a session of
+        // the user on it
+        let mut repo = state.repository().await.unwrap();
+        let client = repo
+            .oauth2_client()
+            .upsert_static(OAuth2StaticClientParams {
+                client_id: Ulid::from_datetime_with_source(state.clock.now().into(), &mut rng),
+                client_name: None,
+                client_auth_method: OAuthClientAuthenticationMethod::None,
+                encrypted_client_secret: None,
+                jwks: None,
+                jwks_uri: None,
+                redirect_uris: vec!["https://example.com/callback".parse().unwrap()],
+                post_logout_redirect_uris: Vec::new(),
+                request_uris: Vec::new(),
+                backchannel_logout_uri: Some(
+                    format!("{}/logout", mock_server.uri()).parse().unwrap(),
+                ),
+                frontchannel_logout_uri: None,
+                frontchannel_logout_session_required: false,
+                dpop_bound_access_tokens: false,
+                tls_client_auth_subject_dn: None,
+                tls_client_auth_san_dns: None,
+                require_pushed_authorization_requests: false,
+            })
+            .await
+            .unwrap();
+        let user = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let browser_session = repo
+            .browser_session()
+            .add(&mut rng, &state.clock, &user, None)
+            .await
+            .unwrap();
+        repo.oauth2_session()
+            .add_from_browser_session(
+                &mut rng,
+                &state.clock,
+                &client,
+                &browser_session,
+                Scope::from_iter([OPENID]),
+            )
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        let request = Request::post(format!("/api/admin/v1/users/{}/lock", user.id))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        // It should have scheduled a back-channel logout job for the user
+        let job: Json<serde_json::Value> = sqlx::query_scalar(
+            "SELECT payload FROM queue_jobs WHERE queue_name = 'backchannel-logout'",
+        )
+        .fetch_one(&pool)
+        .await
+        .expect("Back-channel logout job to be scheduled");
+        assert_eq!(job["target"]["kind"], "user");
+        assert_eq!(job["target"]["user_id"], serde_json::json!(user.id));
+
+        // The first run schedules a job for each session, the second one sends
+        // the logout tokens
+        state.run_jobs_in_queue().await;
+        state.run_jobs_in_queue().await;
+
+        let requests = mock_server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let form: HashMap<String, String> =
+            serde_urlencoded::from_bytes(&requests[0].body).unwrap();
+        let logout_token = form.get("logout_token").unwrap();
+
+        let jwt: Jwt<'_, HashMap<String, serde_json::Value>> =
+            Jwt::try_from(logout_token.as_str()).unwrap();
+        jwt.verify_with_jwks(&state.key_store.public_jwks())
+            .unwrap();
+        assert_eq!(jwt.header().typ(), Some("logout+jwt"));
+
+        let claims = jwt.payload();
+        assert_eq!(claims["iss"], state.url_builder.oidc_issuer().as_str());
+        assert_eq!(claims["aud"], client.client_id);
+        assert_eq!(claims["iat"], state.clock.now().timestamp());
+        assert!(claims["jti"].is_string());
+        assert_eq!(claims["sub"], user.sub);
+        assert_eq!(claims["sid"], browser_session.id.to_string());
+        assert_eq!(
+            claims["events"],
+            serde_json::json!({ "http://schemas.openid.net/event/backchannel-logout": {} })
+        );
+        // Logout tokens must never carry a nonce
+        assert!(!claims.contains_key("nonce"));
+    }
+
     #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
     async fn test_lock_user_twice(pool: PgPool) {
         setup();
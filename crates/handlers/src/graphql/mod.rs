@@ -17,16 +17,19 @@ use axum::{
     Extension, Json,
     body::Body,
     extract::{RawQuery, State as AxumState},
-    http::StatusCode,
+    http::{HeaderMap, Method, StatusCode},
     response::{Html, IntoResponse, Response},
 };
 use axum_extra::typed_header::TypedHeader;
 use chrono::{DateTime, Utc};
 use futures_util::TryStreamExt;
-use headers::{Authorization, ContentType, HeaderValue, authorization::Bearer};
+use headers::{ContentType, HeaderValue};
 use hyper::header::CACHE_CONTROL;
 use mas_axum_utils::{
-    InternalError, SessionInfo, SessionInfoExt, cookies::CookieJar, sentry::SentryEventID,
+    InternalError, SessionInfo, SessionInfoExt,
+    cookies::CookieJar,
+    sentry::SentryEventID,
+    user_authorization::{AccessTokenAuthorization, CertificateThumbprint},
 };
 use mas_data_model::{BrowserSession, Session, SiteConfig, User};
 use mas_matrix::HomeserverConnection;
@@ -55,6 +58,7 @@ use self::{
 };
 use crate::{
     BoundActivityTracker, Limiter, RequesterFingerprint, impl_from_error_for_route,
+    oauth2::dpop::{self, DpopError},
     passwords::PasswordManager,
 };
 
@@ -174,6 +178,9 @@ pub enum RouteError {
     #[error("Invalid access token")]
     InvalidToken,
 
+    #[error("Invalid DPoP proof")]
+    InvalidDpopProof(#[source] DpopError),
+
     #[error("Missing scope")]
     MissingScope,
 
@@ -181,6 +188,16 @@ pub enum RouteError {
     ParseRequest(#[from] async_graphql::ParseRequestError),
 }
 
+impl From<DpopError> for RouteError {
+    fn from(e: DpopError) -> Self {
+        if e.is_internal() {
+            Self::Internal(Box::new(e))
+        } else {
+            Self::InvalidDpopProof(e)
+        }
+    }
+}
+
 impl_from_error_for_route!(mas_storage::RepositoryError);
 
 impl IntoResponse for RouteError {
@@ -206,6 +223,15 @@ impl IntoResponse for RouteError {
                     .into_response()
             }
 
+            Self::InvalidDpopProof(e) => {
+                let error = async_graphql::Error::new_with_source(e);
+                (
+                    StatusCode::UNAUTHORIZED,
+                    Json(serde_json::json!({"errors": [error]})),
+                )
+                    .into_response()
+            }
+
             Self::MissingScope => {
                 let error = async_graphql::Error::new("Missing urn:mas:graphql:* scope");
                 (
@@ -229,6 +255,7 @@ impl IntoResponse for RouteError {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn get_requester(
     undocumented_oauth2_access: bool,
     clock: &impl Clock,
@@ -236,17 +263,32 @@ async fn get_requester(
     mut repo: BoxRepository,
     session_info: &SessionInfo,
     user_agent: Option<String>,
-    token: Option<&str>,
+    url_builder: &UrlBuilder,
+    method: &Method,
+    headers: &HeaderMap,
+    authorization: Option<&AccessTokenAuthorization>,
+    certificate_thumbprint: Option<&str>,
 ) -> Result<Requester, RouteError> {
-    let entity = if let Some(token) = token {
+    let entity = if let Some(authorization) = authorization {
         // If we haven't enabled undocumented_oauth2_access on the listener, we bail out
         if !undocumented_oauth2_access {
             return Err(RouteError::InvalidToken);
         }
 
+        // Tokens presented with the DPoP scheme must come with a proof for this request
+        let dpop_jkt = dpop::verify_resource_proof(
+            clock,
+            &mut repo,
+            headers,
+            method,
+            &url_builder.graphql_endpoint(),
+            authorization.dpop_token(),
+        )
+        .await?;
+
         let token = repo
             .oauth2_access_token()
-            .find_by_token(token)
+            .find_by_token(authorization.token())
             .await?
             .ok_or(RouteError::InvalidToken)?;
 
@@ -279,6 +321,18 @@ async fn get_requester(
             return Err(RouteError::InvalidToken);
         }
 
+        // Tokens of sessions bound to a DPoP key can only be used with a proof
+        // signed by that key
+        if session.dpop_jkt.as_deref() != dpop_jkt.as_deref() {
+            return Err(RouteError::InvalidToken);
+        }
+
+        // Tokens of sessions bound to a TLS client certificate can only be used over
+        // connections presenting that certificate
+        if session.x5t_s256.is_some() && session.x5t_s256.as_deref() != certificate_thumbprint {
+            return Err(RouteError::InvalidToken);
+        }
+
         if !session.scope.contains("urn:mas:graphql:*") {
             return Err(RouteError::MissingScope);
         }
@@ -302,7 +356,8 @@ async fn get_requester(
         user_agent,
     };
 
-    repo.cancel().await?;
+    // Save the repository to record the JWT ID of the DPoP proof, if any
+    repo.save().await?;
     Ok(requester)
 }
 
@@ -315,15 +370,16 @@ pub async fn post(
     repo: BoxRepository,
     activity_tracker: BoundActivityTracker,
     cookie_jar: CookieJar,
+    AxumState(url_builder): AxumState<UrlBuilder>,
+    headers: HeaderMap,
+    CertificateThumbprint(certificate_thumbprint): CertificateThumbprint,
     content_type: Option<TypedHeader<ContentType>>,
-    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    authorization: Option<TypedHeader<AccessTokenAuthorization>>,
     user_agent: Option<TypedHeader<headers::UserAgent>>,
     body: Body,
 ) -> Result<impl IntoResponse, RouteError> {
     let body = body.into_data_stream();
-    let token = authorization
-        .as_ref()
-        .map(|TypedHeader(Authorization(bearer))| bearer.token());
+    let authorization = authorization.map(|TypedHeader(h)| h);
     let user_agent = user_agent.map(|TypedHeader(h)| h.to_string());
     let (session_info, mut cookie_jar) = cookie_jar.session_info();
     let requester = get_requester(
@@ -333,7 +389,11 @@ pub async fn post(
         repo,
         &session_info,
         user_agent,
-        token,
+        &url_builder,
+        &Method::POST,
+        &headers,
+        authorization.as_ref(),
+        certificate_thumbprint.as_deref(),
     )
     .await?;
 
@@ -375,13 +435,14 @@ pub async fn get(
     repo: BoxRepository,
     activity_tracker: BoundActivityTracker,
     cookie_jar: CookieJar,
-    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    AxumState(url_builder): AxumState<UrlBuilder>,
+    headers: HeaderMap,
+    CertificateThumbprint(certificate_thumbprint): CertificateThumbprint,
+    authorization: Option<TypedHeader<AccessTokenAuthorization>>,
     user_agent: Option<TypedHeader<headers::UserAgent>>,
     RawQuery(query): RawQuery,
 ) -> Result<impl IntoResponse, InternalError> {
-    let token = authorization
-        .as_ref()
-        .map(|TypedHeader(Authorization(bearer))| bearer.token());
+    let authorization = authorization.map(|TypedHeader(h)| h);
     let user_agent = user_agent.map(|TypedHeader(h)| h.to_string());
     let (session_info, mut cookie_jar) = cookie_jar.session_info();
     let requester = get_requester(
@@ -391,7 +452,11 @@ pub async fn get(
         repo,
         &session_info,
         user_agent,
-        token,
+        &url_builder,
+        &Method::GET,
+        &headers,
+        authorization.as_ref(),
+        certificate_thumbprint.as_deref(),
     )
     .await?;
 
@@ -512,6 +577,18 @@ impl OwnerId for mas_data_model::CompatSession {
     }
 }
 
+impl OwnerId for mas_data_model::DeviceCodeGrant {
+    fn owner_id(&self) -> Option<Ulid> {
+        self.user_id
+    }
+}
+
+impl OwnerId for mas_data_model::Consent {
+    fn owner_id(&self) -> Option<Ulid> {
+        Some(self.user_id)
+    }
+}
+
 impl OwnerId for mas_data_model::UpstreamOAuthLink {
     fn owner_id(&self) -> Option<Ulid> {
         self.user_id
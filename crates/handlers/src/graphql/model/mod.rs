@@ -23,7 +23,7 @@ pub use self::{
     compat_sessions::{CompatSession, CompatSsoLogin},
     cursor::{Cursor, NodeCursor},
     node::{Node, NodeType},
-    oauth::{OAuth2Client, OAuth2Session},
+    oauth::{BackchannelAuthenticationRequest, OAuth2Client, OAuth2Consent, OAuth2Session},
     site_config::{SITE_CONFIG_ID, SiteConfig},
     upstream_oauth::{UpstreamOAuth2Link, UpstreamOAuth2Provider},
     users::{AppSession, User, UserEmail, UserEmailAuthentication, UserRecoveryTicket},
@@ -10,18 +10,20 @@ use thiserror::Error;
 use ulid::Ulid;
 
 use super::{
-    Anonymous, Authentication, BrowserSession, CompatSession, CompatSsoLogin, OAuth2Client,
-    OAuth2Session, SiteConfig, UpstreamOAuth2Link, UpstreamOAuth2Provider, User, UserEmail,
-    UserEmailAuthentication, UserRecoveryTicket,
+    Anonymous, Authentication, BackchannelAuthenticationRequest, BrowserSession, CompatSession,
+    CompatSsoLogin, OAuth2Client, OAuth2Consent, OAuth2Session, SiteConfig, UpstreamOAuth2Link,
+    UpstreamOAuth2Provider, User, UserEmail, UserEmailAuthentication, UserRecoveryTicket,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum NodeType {
     Authentication,
+    BackchannelAuthenticationRequest,
     BrowserSession,
     CompatSession,
     CompatSsoLogin,
     OAuth2Client,
+    OAuth2Consent,
     OAuth2Session,
     UpstreamOAuth2Provider,
     UpstreamOAuth2Link,
@@ -44,10 +46,12 @@ impl NodeType {
     fn to_prefix(self) -> &'static str {
         match self {
             NodeType::Authentication => "authentication",
+            NodeType::BackchannelAuthenticationRequest => "backchannel_authentication_request",
             NodeType::BrowserSession => "browser_session",
             NodeType::CompatSession => "compat_session",
             NodeType::CompatSsoLogin => "compat_sso_login",
             NodeType::OAuth2Client => "oauth2_client",
+            NodeType::OAuth2Consent => "oauth2_consent",
             NodeType::OAuth2Session => "oauth2_session",
             NodeType::UpstreamOAuth2Provider => "upstream_oauth2_provider",
             NodeType::UpstreamOAuth2Link => "upstream_oauth2_link",
@@ -61,10 +65,14 @@ impl NodeType {
     fn from_prefix(prefix: &str) -> Option<Self> {
         match prefix {
             "authentication" => Some(NodeType::Authentication),
+            "backchannel_authentication_request" => {
+                Some(NodeType::BackchannelAuthenticationRequest)
+            }
             "browser_session" => Some(NodeType::BrowserSession),
             "compat_session" => Some(NodeType::CompatSession),
             "compat_sso_login" => Some(NodeType::CompatSsoLogin),
             "oauth2_client" => Some(NodeType::OAuth2Client),
+            "oauth2_consent" => Some(NodeType::OAuth2Consent),
             "oauth2_session" => Some(NodeType::OAuth2Session),
             "upstream_oauth2_provider" => Some(NodeType::UpstreamOAuth2Provider),
             "upstream_oauth2_link" => Some(NodeType::UpstreamOAuth2Link),
@@ -117,10 +125,12 @@ impl NodeType {
 pub enum Node {
     Anonymous(Box<Anonymous>),
     Authentication(Box<Authentication>),
+    BackchannelAuthenticationRequest(Box<BackchannelAuthenticationRequest>),
     BrowserSession(Box<BrowserSession>),
     CompatSession(Box<CompatSession>),
     CompatSsoLogin(Box<CompatSsoLogin>),
     OAuth2Client(Box<OAuth2Client>),
+    OAuth2Consent(Box<OAuth2Consent>),
     OAuth2Session(Box<OAuth2Session>),
     SiteConfig(Box<SiteConfig>),
     UpstreamOAuth2Provider(Box<UpstreamOAuth2Provider>),
@@ -8,8 +8,7 @@ use anyhow::Context as _;
 use async_graphql::{Context, Description, Enum, ID, Object};
 use chrono::{DateTime, Utc};
 use mas_storage::{oauth2::OAuth2ClientRepository, user::BrowserSessionRepository};
-use oauth2_types::{oidc::ApplicationType, scope::Scope};
-use ulid::Ulid;
+use oauth2_types::oidc::ApplicationType;
 use url::Url;
 
 use super::{BrowserSession, NodeType, SessionState, User, UserAgent};
@@ -204,16 +203,28 @@ impl OAuth2Client {
 /// An OAuth 2.0 consent represents the scope a user consented to grant to a
 /// client.
 #[derive(Description)]
-pub struct OAuth2Consent {
-    scope: Scope,
-    client_id: Ulid,
-}
+pub struct OAuth2Consent(pub mas_data_model::Consent);
 
 #[Object(use_type_description)]
 impl OAuth2Consent {
+    /// ID of the object.
+    pub async fn id(&self) -> ID {
+        NodeType::OAuth2Consent.id(self.0.id)
+    }
+
+    /// When the user first consented to this client.
+    pub async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    /// When the user last consented to this client, if more than once.
+    pub async fn refreshed_at(&self) -> Option<DateTime<Utc>> {
+        self.0.refreshed_at
+    }
+
     /// Scope consented by the user for this client.
     pub async fn scope(&self) -> String {
-        self.scope.to_string()
+        self.0.scope.to_string()
     }
 
     /// OAuth 2.0 client for which the user granted access.
@@ -222,7 +233,7 @@ impl OAuth2Consent {
         let mut repo = state.repository().await?;
         let client = repo
             .oauth2_client()
-            .lookup(self.client_id)
+            .lookup(self.0.client_id)
             .await?
             .context("Could not load client")?;
         repo.cancel().await?;
@@ -230,3 +241,51 @@ impl OAuth2Consent {
         Ok(OAuth2Client(client))
     }
 }
+
+/// A pending client-initiated backchannel authentication request, which the
+/// user can approve or reject.
+#[derive(Description)]
+pub struct BackchannelAuthenticationRequest(pub mas_data_model::DeviceCodeGrant);
+
+#[Object(use_type_description)]
+impl BackchannelAuthenticationRequest {
+    /// ID of the object.
+    pub async fn id(&self) -> ID {
+        NodeType::BackchannelAuthenticationRequest.id(self.0.id)
+    }
+
+    /// OAuth 2.0 client which started the request.
+    pub async fn client(&self, ctx: &Context<'_>) -> Result<OAuth2Client, async_graphql::Error> {
+        let state = ctx.state();
+        let mut repo = state.repository().await?;
+        let client = repo
+            .oauth2_client()
+            .lookup(self.0.client_id)
+            .await?
+            .context("Could not load client")?;
+        repo.cancel().await?;
+
+        Ok(OAuth2Client(client))
+    }
+
+    /// Scope requested by the client.
+    pub async fn scope(&self) -> String {
+        self.0.scope.to_string()
+    }
+
+    /// Message to display to the user, which should match the one shown on
+    /// the device which started the request.
+    pub async fn binding_message(&self) -> Option<&str> {
+        self.0.binding_message.as_deref()
+    }
+
+    /// When the object was created.
+    pub async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    /// When the request expires.
+    pub async fn expires_at(&self) -> DateTime<Utc> {
+        self.0.expires_at
+    }
+}
@@ -15,14 +15,16 @@ use mas_storage::{
     Pagination, RepositoryAccess,
     app_session::AppSessionFilter,
     compat::{CompatSessionFilter, CompatSsoLoginFilter, CompatSsoLoginRepository},
-    oauth2::{OAuth2SessionFilter, OAuth2SessionRepository},
+    oauth2::{
+        OAuth2ConsentFilter, OAuth2ConsentRepository, OAuth2SessionFilter, OAuth2SessionRepository,
+    },
     upstream_oauth2::{UpstreamOAuthLinkFilter, UpstreamOAuthLinkRepository},
     user::{BrowserSessionFilter, BrowserSessionRepository, UserEmailFilter, UserEmailRepository},
 };
 
 use super::{
-    BrowserSession, CompatSession, Cursor, NodeCursor, NodeType, OAuth2Session,
-    PreloadedTotalCount, SessionState, UpstreamOAuth2Link,
+    BackchannelAuthenticationRequest, BrowserSession, CompatSession, Cursor, NodeCursor, NodeType,
+    OAuth2Consent, OAuth2Session, PreloadedTotalCount, SessionState, UpstreamOAuth2Link,
     compat_sessions::{CompatSessionType, CompatSsoLogin},
     matrix::MatrixUser,
 };
@@ -706,6 +708,110 @@ impl User {
         .await
     }
 
+    /// Get the list of consents the user gave to OAuth 2.0 clients,
+    /// chronologically sorted
+    async fn oauth2_consents(
+        &self,
+        ctx: &Context<'_>,
+
+        #[graphql(desc = "List only the consent given to the given client.")] client: Option<ID>,
+
+        #[graphql(desc = "Returns the elements in the list that come after the cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the elements in the list that come before the cursor.")]
+        before: Option<String>,
+        #[graphql(desc = "Returns the first *n* elements from the list.")] first: Option<i32>,
+        #[graphql(desc = "Returns the last *n* elements from the list.")] last: Option<i32>,
+    ) -> Result<Connection<Cursor, OAuth2Consent, PreloadedTotalCount>, async_graphql::Error> {
+        let state = ctx.state();
+        let mut repo = state.repository().await?;
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            async |after, before, first, last| {
+                let after_id = after
+                    .map(|x: OpaqueCursor<NodeCursor>| x.extract_for_type(NodeType::OAuth2Consent))
+                    .transpose()?;
+                let before_id = before
+                    .map(|x: OpaqueCursor<NodeCursor>| x.extract_for_type(NodeType::OAuth2Consent))
+                    .transpose()?;
+                let pagination = Pagination::try_new(before_id, after_id, first, last)?;
+
+                let client = if let Some(id) = client {
+                    // Load the client if we're filtering by it
+                    let id = NodeType::OAuth2Client.extract_ulid(&id)?;
+                    let client = repo
+                        .oauth2_client()
+                        .lookup(id)
+                        .await?
+                        .ok_or(async_graphql::Error::new("Unknown client ID"))?;
+
+                    Some(client)
+                } else {
+                    None
+                };
+
+                let filter = OAuth2ConsentFilter::new().for_user(&self.0);
+
+                let filter = match client.as_ref() {
+                    Some(client) => filter.for_client(client),
+                    None => filter,
+                };
+
+                let page = repo.oauth2_consent().list(filter, pagination).await?;
+
+                // Preload the total count if requested
+                let count = if ctx.look_ahead().field("totalCount").exists() {
+                    Some(repo.oauth2_consent().count(filter).await?)
+                } else {
+                    None
+                };
+
+                repo.cancel().await?;
+
+                let mut connection = Connection::with_additional_fields(
+                    page.has_previous_page,
+                    page.has_next_page,
+                    PreloadedTotalCount(count),
+                );
+                connection.edges.extend(page.edges.into_iter().map(|c| {
+                    Edge::new(
+                        OpaqueCursor(NodeCursor(NodeType::OAuth2Consent, c.id)),
+                        OAuth2Consent(c),
+                    )
+                }));
+
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Get the pending client-initiated backchannel authentication requests
+    /// for this user, oldest first.
+    async fn pending_backchannel_authentication_requests(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<BackchannelAuthenticationRequest>, async_graphql::Error> {
+        let state = ctx.state();
+        let clock = state.clock();
+        let mut repo = state.repository().await?;
+
+        let grants = repo
+            .oauth2_device_code_grant()
+            .list_pending_for_user(&clock, &self.0)
+            .await?;
+        repo.cancel().await?;
+
+        Ok(grants
+            .into_iter()
+            .map(BackchannelAuthenticationRequest)
+            .collect())
+    }
+
     /// Check if the user has a password set.
     async fn has_password(&self, ctx: &Context<'_>) -> Result<bool, async_graphql::Error> {
         let state = ctx.state();
@@ -0,0 +1,216 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use anyhow::Context as _;
+use async_graphql::{Context, Description, Enum, ID, InputObject, Object};
+use mas_storage::{
+    RepositoryAccess,
+    oauth2::{OAuth2ClientRepository, OAuth2DeviceCodeGrantRepository},
+    queue::{QueueJobRepositoryExt as _, SendBackchannelAuthenticationNotificationJob},
+};
+use oauth2_types::oidc::BackchannelTokenDeliveryMode;
+
+use crate::graphql::{
+    model::{BackchannelAuthenticationRequest, NodeType},
+    state::ContextExt,
+};
+
+#[derive(Default)]
+pub struct BackchannelAuthenticationMutations {
+    _private: (),
+}
+
+/// The input of the `approveBackchannelAuthenticationRequest` and
+/// `rejectBackchannelAuthenticationRequest` mutations.
+#[derive(InputObject)]
+pub struct BackchannelAuthenticationRequestInput {
+    /// The ID of the backchannel authentication request.
+    backchannel_authentication_request_id: ID,
+}
+
+/// The status of the `approveBackchannelAuthenticationRequest` and
+/// `rejectBackchannelAuthenticationRequest` mutations.
+#[derive(Enum, Copy, Clone, PartialEq, Eq, Debug)]
+enum BackchannelAuthenticationRequestStatus {
+    /// The request was approved.
+    Approved,
+
+    /// The request was rejected.
+    Rejected,
+
+    /// The request was not found, is expired, or was already handled.
+    NotFound,
+
+    /// The request was denied by the policy.
+    Denied,
+}
+
+/// The payload of the `approveBackchannelAuthenticationRequest` and
+/// `rejectBackchannelAuthenticationRequest` mutations.
+#[derive(Description)]
+pub enum BackchannelAuthenticationRequestPayload {
+    Approved(Box<mas_data_model::DeviceCodeGrant>),
+    Rejected(Box<mas_data_model::DeviceCodeGrant>),
+    NotFound,
+    Denied {
+        violations: Vec<mas_policy::Violation>,
+    },
+}
+
+#[Object(use_type_description)]
+impl BackchannelAuthenticationRequestPayload {
+    /// The status of the mutation.
+    async fn status(&self) -> BackchannelAuthenticationRequestStatus {
+        match self {
+            Self::Approved(_) => BackchannelAuthenticationRequestStatus::Approved,
+            Self::Rejected(_) => BackchannelAuthenticationRequestStatus::Rejected,
+            Self::NotFound => BackchannelAuthenticationRequestStatus::NotFound,
+            Self::Denied { .. } => BackchannelAuthenticationRequestStatus::Denied,
+        }
+    }
+
+    /// Returns the request which was approved or rejected.
+    async fn backchannel_authentication_request(&self) -> Option<BackchannelAuthenticationRequest> {
+        match self {
+            Self::Approved(grant) | Self::Rejected(grant) => {
+                Some(BackchannelAuthenticationRequest(*grant.clone()))
+            }
+            Self::NotFound | Self::Denied { .. } => None,
+        }
+    }
+
+    /// The list of policy violations if the request was denied.
+    async fn violations(&self) -> Option<Vec<String>> {
+        let Self::Denied { violations } = self else {
+            return None;
+        };
+
+        let messages = violations.iter().map(|v| v.msg.clone()).collect();
+        Some(messages)
+    }
+}
+
+#[Object]
+impl BackchannelAuthenticationMutations {
+    /// Approve a pending client-initiated backchannel authentication request,
+    /// using the current browser session.
+    async fn approve_backchannel_authentication_request(
+        &self,
+        ctx: &Context<'_>,
+        input: BackchannelAuthenticationRequestInput,
+    ) -> Result<BackchannelAuthenticationRequestPayload, async_graphql::Error> {
+        handle_request(ctx, input, true).await
+    }
+
+    /// Reject a pending client-initiated backchannel authentication request,
+    /// using the current browser session.
+    async fn reject_backchannel_authentication_request(
+        &self,
+        ctx: &Context<'_>,
+        input: BackchannelAuthenticationRequestInput,
+    ) -> Result<BackchannelAuthenticationRequestPayload, async_graphql::Error> {
+        handle_request(ctx, input, false).await
+    }
+}
+
+/// Approve or reject a backchannel authentication request on behalf of the
+/// user of the current browser session.
+async fn handle_request(
+    ctx: &Context<'_>,
+    input: BackchannelAuthenticationRequestInput,
+    approve: bool,
+) -> Result<BackchannelAuthenticationRequestPayload, async_graphql::Error> {
+    let state = ctx.state();
+    let id = NodeType::BackchannelAuthenticationRequest
+        .extract_ulid(&input.backchannel_authentication_request_id)?;
+    let requester = ctx.requester();
+
+    // Only the user themselves can handle the request, from a browser session
+    let Some(browser_session) = requester.browser_session() else {
+        return Err(async_graphql::Error::new("Unauthorized"));
+    };
+
+    let mut repo = state.repository().await?;
+    let clock = state.clock();
+    let mut rng = state.rng();
+
+    let grant = repo
+        .oauth2_device_code_grant()
+        .lookup(id)
+        .await?
+        .filter(|grant| grant.user_id == Some(browser_session.user.id))
+        .filter(|grant| grant.is_pending())
+        .filter(|grant| grant.expires_at > clock.now());
+
+    let Some(grant) = grant else {
+        return Ok(BackchannelAuthenticationRequestPayload::NotFound);
+    };
+
+    let client = repo
+        .oauth2_client()
+        .lookup(grant.client_id)
+        .await?
+        .context("Client not found")?;
+
+    let grant = if approve {
+        let last_authentication = repo
+            .browser_session()
+            .get_last_authentication(browser_session)
+            .await?;
+        let acr = last_authentication
+            .as_ref()
+            .and_then(|authentication| state.site_config().acr_for(authentication))
+            .map(|level| level.value.as_str());
+
+        let mut policy = state.policy().await?;
+        let res = policy
+            .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
+                grant_type: mas_policy::GrantType::ClientInitiatedBackchannelAuthentication,
+                authorization_details: &[],
+                client: &client,
+                scope: &grant.scope,
+                user: Some(&browser_session.user),
+                acr,
+                requester: requester.for_policy(),
+            })
+            .await?;
+        if !res.valid() {
+            return Ok(BackchannelAuthenticationRequestPayload::Denied {
+                violations: res.violations,
+            });
+        }
+
+        repo.oauth2_device_code_grant()
+            .fulfill(&clock, grant, browser_session)
+            .await?
+    } else {
+        repo.oauth2_device_code_grant()
+            .reject(&clock, grant, browser_session)
+            .await?
+    };
+
+    // In the ping mode, let the client know that it can fetch the result
+    if client.backchannel_token_delivery_mode == Some(BackchannelTokenDeliveryMode::Ping) {
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                &clock,
+                SendBackchannelAuthenticationNotificationJob::new(&grant),
+            )
+            .await?;
+    }
+
+    repo.save().await?;
+
+    if approve {
+        Ok(BackchannelAuthenticationRequestPayload::Approved(Box::new(
+            grant,
+        )))
+    } else {
+        Ok(BackchannelAuthenticationRequestPayload::Rejected(Box::new(
+            grant,
+        )))
+    }
+}
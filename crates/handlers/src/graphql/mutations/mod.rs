@@ -4,9 +4,11 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
+mod backchannel_authentication;
 mod browser_session;
 mod compat_session;
 mod matrix;
+mod oauth2_consent;
 mod oauth2_session;
 mod user;
 mod user_email;
@@ -26,9 +28,11 @@ pub struct Mutation(
     user_email::UserEmailMutations,
     user::UserMutations,
     oauth2_session::OAuth2SessionMutations,
+    oauth2_consent::OAuth2ConsentMutations,
     compat_session::CompatSessionMutations,
     browser_session::BrowserSessionMutations,
     matrix::MatrixMutations,
+    backchannel_authentication::BackchannelAuthenticationMutations,
 );
 
 impl Mutation {
@@ -0,0 +1,93 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_graphql::{Context, Enum, ID, InputObject, Object};
+use mas_storage::{RepositoryAccess, oauth2::OAuth2ConsentRepository};
+
+use crate::graphql::{
+    model::{NodeType, OAuth2Consent},
+    state::ContextExt,
+};
+
+#[derive(Default)]
+pub struct OAuth2ConsentMutations {
+    _private: (),
+}
+
+/// The input of the `revokeOauth2Consent` mutation.
+#[derive(InputObject)]
+pub struct RevokeOAuth2ConsentInput {
+    /// The ID of the consent to revoke.
+    oauth2_consent_id: ID,
+}
+
+/// The payload of the `revokeOauth2Consent` mutation.
+pub enum RevokeOAuth2ConsentPayload {
+    NotFound,
+    Revoked(Box<mas_data_model::Consent>),
+}
+
+/// The status of the `revokeOauth2Consent` mutation.
+#[derive(Enum, Copy, Clone, PartialEq, Eq, Debug)]
+enum RevokeOAuth2ConsentStatus {
+    /// The consent was revoked.
+    Revoked,
+
+    /// The consent was not found.
+    NotFound,
+}
+
+#[Object]
+impl RevokeOAuth2ConsentPayload {
+    /// The status of the mutation.
+    async fn status(&self) -> RevokeOAuth2ConsentStatus {
+        match self {
+            Self::Revoked(_) => RevokeOAuth2ConsentStatus::Revoked,
+            Self::NotFound => RevokeOAuth2ConsentStatus::NotFound,
+        }
+    }
+
+    /// Returns the revoked consent.
+    async fn oauth2_consent(&self) -> Option<OAuth2Consent> {
+        match self {
+            Self::Revoked(consent) => Some(OAuth2Consent(*consent.clone())),
+            Self::NotFound => None,
+        }
+    }
+}
+
+#[Object]
+impl OAuth2ConsentMutations {
+    /// Revoke the consent a user gave to an OAuth 2.0 client, so that they
+    /// get asked again the next time the client wants access.
+    ///
+    /// This doesn't end the sessions the client already has.
+    async fn revoke_oauth2_consent(
+        &self,
+        ctx: &Context<'_>,
+        input: RevokeOAuth2ConsentInput,
+    ) -> Result<RevokeOAuth2ConsentPayload, async_graphql::Error> {
+        let state = ctx.state();
+        let oauth2_consent_id = NodeType::OAuth2Consent.extract_ulid(&input.oauth2_consent_id)?;
+        let requester = ctx.requester();
+
+        let mut repo = state.repository().await?;
+
+        let consent = repo.oauth2_consent().lookup(oauth2_consent_id).await?;
+        let Some(consent) = consent else {
+            return Ok(RevokeOAuth2ConsentPayload::NotFound);
+        };
+
+        if !requester.is_owner_or_admin(&consent) {
+            return Ok(RevokeOAuth2ConsentPayload::NotFound);
+        }
+
+        repo.oauth2_consent().revoke(consent.clone()).await?;
+
+        repo.save().await?;
+
+        Ok(RevokeOAuth2ConsentPayload::Revoked(Box::new(consent)))
+    }
+}
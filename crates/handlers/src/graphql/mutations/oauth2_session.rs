@@ -14,7 +14,7 @@ use mas_storage::{
         OAuth2AccessTokenRepository, OAuth2ClientRepository, OAuth2RefreshTokenRepository,
         OAuth2SessionRepository,
     },
-    queue::{QueueJobRepositoryExt as _, SyncDevicesJob},
+    queue::{QueueJobRepositoryExt as _, SendBackchannelLogoutJob, SyncDevicesJob},
     user::UserRepository,
 };
 use oauth2_types::scope::Scope;
@@ -288,6 +288,11 @@ impl OAuth2SessionMutations {
                 .await?;
         }
 
+        // Let the client know that the session ended
+        repo.queue_job()
+            .schedule_job(&mut rng, &clock, SendBackchannelLogoutJob::new(&session))
+            .await?;
+
         let session = repo.oauth2_session().finish(&clock, session).await?;
 
         repo.save().await?;
@@ -7,8 +7,9 @@
 use anyhow::Context as _;
 use async_graphql::{Context, Description, Enum, ID, InputObject, Object};
 use mas_storage::{
+    Clock,
     queue::{
-        DeactivateUserJob, ProvisionUserJob, QueueJobRepositoryExt as _,
+        BackchannelLogoutJob, DeactivateUserJob, ProvisionUserJob, QueueJobRepositoryExt as _,
         SendAccountRecoveryEmailsJob,
     },
     user::UserRepository,
@@ -556,6 +557,16 @@ impl UserMutations {
             repo.queue_job()
                 .schedule_job(&mut rng, &clock, DeactivateUserJob::new(&user, deactivate))
                 .await?;
+        } else {
+            // The deactivation job takes care of this otherwise
+            info!(%user.id, "Scheduling back-channel logout of user");
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    &clock,
+                    BackchannelLogoutJob::for_user(&user, clock.now()),
+                )
+                .await?;
         }
 
         repo.save().await?;
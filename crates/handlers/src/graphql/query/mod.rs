@@ -8,8 +8,9 @@ use async_graphql::{Context, ID, MergedObject, Object};
 
 use crate::graphql::{
     model::{
-        Anonymous, BrowserSession, CompatSession, Node, NodeType, OAuth2Client, OAuth2Session,
-        SiteConfig, User, UserEmail, UserRecoveryTicket,
+        Anonymous, BackchannelAuthenticationRequest, BrowserSession, CompatSession, Node, NodeType,
+        OAuth2Client, OAuth2Consent, OAuth2Session, SiteConfig, User, UserEmail,
+        UserRecoveryTicket,
     },
     state::ContextExt,
 };
@@ -158,6 +159,33 @@ impl BaseQuery {
         Ok(Some(OAuth2Session(oauth2_session)))
     }
 
+    /// Fetch a client-initiated backchannel authentication request by its ID.
+    async fn backchannel_authentication_request(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+    ) -> Result<Option<BackchannelAuthenticationRequest>, async_graphql::Error> {
+        let state = ctx.state();
+        let id = NodeType::BackchannelAuthenticationRequest.extract_ulid(&id)?;
+        let requester = ctx.requester();
+
+        let mut repo = state.repository().await?;
+        let grant = repo.oauth2_device_code_grant().lookup(id).await?;
+        repo.cancel().await?;
+
+        let Some(grant) =
+            grant.filter(mas_data_model::DeviceCodeGrant::is_backchannel_authentication)
+        else {
+            return Ok(None);
+        };
+
+        if !requester.is_owner_or_admin(&grant) {
+            return Ok(None);
+        }
+
+        Ok(Some(BackchannelAuthenticationRequest(grant)))
+    }
+
     /// Fetch a user email by its ID.
     async fn user_email(
         &self,
@@ -183,6 +211,31 @@ impl BaseQuery {
         Ok(Some(UserEmail(user_email)))
     }
 
+    /// Fetch an OAuth 2.0 consent by its ID.
+    async fn oauth2_consent(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+    ) -> Result<Option<OAuth2Consent>, async_graphql::Error> {
+        let state = ctx.state();
+        let id = NodeType::OAuth2Consent.extract_ulid(&id)?;
+        let requester = ctx.requester();
+
+        let mut repo = state.repository().await?;
+        let consent = repo.oauth2_consent().lookup(id).await?;
+        repo.cancel().await?;
+
+        let Some(consent) = consent else {
+            return Ok(None);
+        };
+
+        if !requester.is_owner_or_admin(&consent) {
+            return Ok(None);
+        }
+
+        Ok(Some(OAuth2Consent(consent)))
+    }
+
     /// Fetch a user recovery ticket.
     async fn user_recovery_ticket(
         &self,
@@ -259,6 +312,11 @@ impl BaseQuery {
                 .await?
                 .map(|c| Node::OAuth2Client(Box::new(c))),
 
+            NodeType::OAuth2Consent => self
+                .oauth2_consent(ctx, id)
+                .await?
+                .map(|c| Node::OAuth2Consent(Box::new(c))),
+
             NodeType::UserEmail => self
                 .user_email(ctx, id)
                 .await?
@@ -279,6 +337,11 @@ impl BaseQuery {
                 .await?
                 .map(|s| Node::OAuth2Session(Box::new(s))),
 
+            NodeType::BackchannelAuthenticationRequest => self
+                .backchannel_authentication_request(ctx, id)
+                .await?
+                .map(|r| Node::BackchannelAuthenticationRequest(Box::new(r))),
+
             NodeType::BrowserSession => self
                 .browser_session(ctx, id)
                 .await?
@@ -11,7 +11,7 @@ use mas_matrix::{HomeserverConnection, ProvisionRequest};
 use mas_router::SimpleRoute;
 use mas_storage::{
     RepositoryAccess,
-    oauth2::{OAuth2AccessTokenRepository, OAuth2ClientRepository},
+    oauth2::{OAuth2AccessTokenRepository, OAuth2ClientParams, OAuth2ClientRepository},
 };
 use oauth2_types::{
     registration::ClientRegistrationResponse,
@@ -31,27 +31,7 @@ async fn create_test_client(state: &TestState) -> Client {
 
     let client = repo
         .oauth2_client()
-        .add(
-            &mut rng,
-            &state.clock,
-            vec![],
-            None,
-            None,
-            None,
-            vec![],
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-        )
+        .add(&mut rng, &state.clock, OAuth2ClientParams::default())
         .await
         .unwrap();
 
@@ -118,6 +118,7 @@ pub fn graphql_router<S>(playground: bool, undocumented_oauth2_access: bool) ->
 where
     S: Clone + Send + Sync + 'static,
     graphql::Schema: FromRef<S>,
+    UrlBuilder: FromRef<S>,
     BoundActivityTracker: FromRequestParts<S>,
     BoxRepository: FromRequestParts<S>,
     BoxClock: FromRequestParts<S>,
@@ -146,6 +147,8 @@ where
                     ACCEPT_LANGUAGE,
                     CONTENT_LANGUAGE,
                     CONTENT_TYPE,
+                    // Access tokens presented with the DPoP scheme come with a proof
+                    HeaderName::from_static("dpop"),
                 ]),
         );
 
@@ -235,10 +238,24 @@ where
             mas_router::OAuth2RegistrationEndpoint::route(),
             post(self::oauth2::registration::post),
         )
+        .route(
+            mas_router::OAuth2ClientConfiguration::route(),
+            get(self::oauth2::registration::get)
+                .put(self::oauth2::registration::put)
+                .delete(self::oauth2::registration::delete),
+        )
         .route(
             mas_router::OAuth2DeviceAuthorizationEndpoint::route(),
             post(self::oauth2::device::authorize::post),
         )
+        .route(
+            mas_router::OAuth2PushedAuthorizationRequestEndpoint::route(),
+            post(self::oauth2::pushed_authorization_request::post),
+        )
+        .route(
+            mas_router::OAuth2BackchannelAuthenticationEndpoint::route(),
+            post(self::oauth2::backchannel_authentication::post),
+        )
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
@@ -251,6 +268,8 @@ where
                     CONTENT_TYPE,
                     // Swagger will send this header, so we have to allow it to avoid CORS errors
                     HeaderName::from_static("x-requested-with"),
+                    // Browser-based clients send their DPoP proofs to the token endpoint
+                    HeaderName::from_static("dpop"),
                 ])
                 .max_age(Duration::from_secs(60 * 60)),
         )
@@ -429,11 +448,20 @@ where
             mas_router::OAuth2AuthorizationEndpoint::route(),
             get(self::oauth2::authorization::get),
         )
+        .route(
+            mas_router::OidcEndSession::route(),
+            get(self::oauth2::end_session::get).post(self::oauth2::end_session::post),
+        )
         .route(
             mas_router::Consent::route(),
             get(self::oauth2::authorization::consent::get)
                 .post(self::oauth2::authorization::consent::post),
         )
+        .route(
+            mas_router::SelectAccount::route(),
+            get(self::oauth2::authorization::select_account::get)
+                .post(self::oauth2::authorization::select_account::post),
+        )
         .route(
             mas_router::CompatLoginSsoComplete::route(),
             get(self::compat::login_sso_complete::get).post(self::compat::login_sso_complete::post),
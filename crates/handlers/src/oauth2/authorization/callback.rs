@@ -9,14 +9,30 @@
 use std::collections::HashMap;
 
 use axum::response::{Html, IntoResponse, Redirect, Response};
-use mas_data_model::AuthorizationGrant;
+use chrono::{DateTime, Duration, Utc};
+use mas_data_model::{AuthorizationGrant, Client};
 use mas_i18n::DataLocale;
+use mas_iana::jose::JsonWebSignatureAlg;
+use mas_jose::{
+    claims,
+    constraints::Constrainable,
+    jwe::JsonWebEncryptionHeader,
+    jwt::{JsonWebSignatureHeader, Jwt},
+};
+use mas_keystore::{Encrypter, Keystore};
+use mas_router::UrlBuilder;
+use mas_storage::Clock;
 use mas_templates::{FormPostContext, Templates};
 use oauth2_types::requests::ResponseMode;
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
 use serde::Serialize;
+use serde_json::Value;
 use thiserror::Error;
 use url::Url;
 
+use crate::oauth2::response_encryption::{ResponseEncryptionError, encrypt_for_client};
+
 #[derive(Debug, Clone)]
 enum CallbackDestinationMode {
     Query {
@@ -29,8 +45,32 @@ enum CallbackDestinationMode {
 #[derive(Debug, Clone)]
 pub struct CallbackDestination {
     mode: CallbackDestinationMode,
+    jwt: bool,
     safe_redirect_uri: Url,
     state: Option<String>,
+    signer: Option<ResponseSigner>,
+}
+
+/// Signs, and optionally encrypts, the authorization responses sent with one of
+/// the JWT response modes, as per JARM
+#[derive(Clone)]
+pub struct ResponseSigner {
+    rng: ChaChaRng,
+    now: DateTime<Utc>,
+    issuer: String,
+    key_store: Keystore,
+    encrypter: Encrypter,
+    http_client: reqwest::Client,
+    client: Client,
+}
+
+impl std::fmt::Debug for ResponseSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseSigner")
+            .field("issuer", &self.issuer)
+            .field("client", &self.client.id)
+            .finish_non_exhaustive()
+    }
 }
 
 #[derive(Debug, Error)]
@@ -52,6 +92,107 @@ pub enum CallbackDestinationError {
 
     #[error("Failed to serialize parameters query string")]
     ParamsSerialization(#[from] serde_urlencoded::ser::Error),
+
+    #[error("No response signer was set for a JWT response mode")]
+    MissingResponseSigner,
+
+    #[error("Failed to serialize the response parameters")]
+    ResponseSerialization(#[from] serde_json::Error),
+
+    #[error("Response parameters must serialize to an object")]
+    InvalidResponseParams,
+
+    #[error("The signing key is invalid")]
+    InvalidSigningKey,
+
+    #[error(transparent)]
+    Claim(#[from] mas_jose::claims::ClaimError),
+
+    #[error(transparent)]
+    JwtSignature(#[from] mas_jose::jwt::JwtSignatureError),
+
+    #[error(transparent)]
+    WrongAlgorithm(#[from] mas_keystore::WrongAlgorithmError),
+
+    #[error(transparent)]
+    ResponseEncryption(#[from] ResponseEncryptionError),
+}
+
+impl ResponseSigner {
+    pub fn new(
+        rng: &mut (impl rand::RngCore + rand::CryptoRng),
+        clock: &impl Clock,
+        url_builder: &UrlBuilder,
+        key_store: &Keystore,
+        encrypter: &Encrypter,
+        http_client: &reqwest::Client,
+        client: &Client,
+    ) -> Self {
+        Self {
+            rng: ChaChaRng::from_rng(rng).expect("Failed to seed rng"),
+            now: clock.now(),
+            issuer: url_builder.oidc_issuer().to_string(),
+            key_store: key_store.clone(),
+            encrypter: encrypter.clone(),
+            http_client: http_client.clone(),
+            client: client.clone(),
+        }
+    }
+
+    /// Wrap the response parameters in a signed JWT, encrypted if the client
+    /// registered an algorithm for it
+    async fn sign<T: Serialize>(
+        mut self,
+        state: Option<String>,
+        params: T,
+    ) -> Result<String, CallbackDestinationError> {
+        let Value::Object(params) = serde_json::to_value(params)? else {
+            return Err(CallbackDestinationError::InvalidResponseParams);
+        };
+
+        let mut claims: HashMap<String, Value> = params.into_iter().collect();
+        claims::ISS.insert(&mut claims, self.issuer)?;
+        claims::AUD.insert(&mut claims, self.client.client_id.clone())?;
+        // JARM recommends a short lifetime, of about 10 minutes
+        claims::EXP.insert(&mut claims, self.now + Duration::try_minutes(10).unwrap())?;
+        if let Some(state) = state {
+            claims.insert("state".to_owned(), Value::String(state));
+        }
+
+        let alg = self
+            .client
+            .authorization_signed_response_alg
+            .clone()
+            .unwrap_or(JsonWebSignatureAlg::Rs256);
+        let key = self
+            .key_store
+            .signing_key_for_algorithm(&alg)
+            .ok_or(CallbackDestinationError::InvalidSigningKey)?;
+        let signer = key.params().signing_key_for_alg(&alg)?;
+        let header = JsonWebSignatureHeader::new(alg).with_kid(
+            key.kid()
+                .ok_or(CallbackDestinationError::InvalidSigningKey)?,
+        );
+        let response = Jwt::sign_with_rng(&mut self.rng, header, claims, &signer)?.into_string();
+
+        let Some((alg, enc)) = self.client.authorization_encrypted_response() else {
+            return Ok(response);
+        };
+
+        let header =
+            JsonWebEncryptionHeader::new(alg.clone(), enc.clone()).with_cty("JWT".to_owned());
+        let response = encrypt_for_client(
+            &mut self.rng,
+            &self.http_client,
+            &self.encrypter,
+            &self.client,
+            header,
+            response.as_bytes(),
+        )
+        .await?;
+
+        Ok(response)
+    }
 }
 
 impl TryFrom<&AuthorizationGrant> for CallbackDestination {
@@ -76,8 +217,13 @@ impl CallbackDestination {
             return Err(IntoCallbackDestinationError::RedirectUriFragmentNotAllowed);
         }
 
+        let jwt = matches!(
+            mode,
+            ResponseMode::QueryJwt | ResponseMode::FragmentJwt | ResponseMode::FormPostJwt
+        );
+
         let mode = match mode {
-            ResponseMode::Query => {
+            ResponseMode::Query | ResponseMode::QueryJwt => {
                 let existing_params = redirect_uri
                     .query()
                     .map(serde_urlencoded::from_str)
@@ -89,19 +235,52 @@ impl CallbackDestination {
 
                 CallbackDestinationMode::Query { existing_params }
             }
-            ResponseMode::Fragment => CallbackDestinationMode::Fragment,
-            ResponseMode::FormPost => CallbackDestinationMode::FormPost,
+            ResponseMode::Fragment | ResponseMode::FragmentJwt => CallbackDestinationMode::Fragment,
+            ResponseMode::FormPost | ResponseMode::FormPostJwt => CallbackDestinationMode::FormPost,
             _ => return Err(IntoCallbackDestinationError::UnsupportedResponseMode),
         };
 
         Ok(Self {
             mode,
+            jwt,
             safe_redirect_uri: redirect_uri,
             state,
+            signer: None,
         })
     }
 
-    pub fn go<T: Serialize + Send + Sync>(
+    /// Set the signer used for the JWT response modes
+    #[must_use]
+    pub fn with_response_signer(mut self, signer: ResponseSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    pub async fn go<T: Serialize + Send + Sync>(
+        mut self,
+        templates: &Templates,
+        locale: &DataLocale,
+        params: T,
+    ) -> Result<Response, CallbackDestinationError> {
+        #[derive(Serialize)]
+        struct JwtResponse {
+            response: String,
+        }
+
+        if !self.jwt {
+            return self.deliver(templates, locale, params);
+        }
+
+        // With the JWT response modes, the state is part of the response JWT
+        let signer = self
+            .signer
+            .take()
+            .ok_or(CallbackDestinationError::MissingResponseSigner)?;
+        let response = signer.sign(self.state.take(), params).await?;
+        self.deliver(templates, locale, JwtResponse { response })
+    }
+
+    fn deliver<T: Serialize>(
         self,
         templates: &Templates,
         locale: &DataLocale,
@@ -15,23 +15,30 @@ use mas_axum_utils::{
     cookies::CookieJar,
     csrf::{CsrfExt, ProtectedForm},
 };
-use mas_data_model::AuthorizationGrantStage;
-use mas_keystore::Keystore;
+use mas_data_model::{
+    Authentication, AuthenticationCheck, AuthorizationGrant, AuthorizationGrantStage,
+    BrowserSession, Client, SiteConfig,
+};
+use mas_i18n::DataLocale;
+use mas_keystore::{Encrypter, Keystore};
 use mas_policy::Policy;
 use mas_router::{PostAuthAction, UrlBuilder};
 use mas_storage::{
     BoxClock, BoxRepository, BoxRng,
-    oauth2::{OAuth2AuthorizationGrantRepository, OAuth2ClientRepository},
+    oauth2::{OAuth2AuthorizationGrantRepository, OAuth2ClientRepository, OAuth2ConsentRepository},
 };
 use mas_templates::{ConsentContext, PolicyViolationContext, TemplateContext, Templates};
-use oauth2_types::requests::AuthorizationResponse;
+use oauth2_types::{
+    errors::{ClientError, ClientErrorCode},
+    requests::AuthorizationResponse,
+};
 use thiserror::Error;
 use ulid::Ulid;
 
-use super::callback::CallbackDestination;
+use super::callback::{CallbackDestination, ResponseSigner};
 use crate::{
     BoundActivityTracker, PreferredLanguage, impl_from_error_for_route,
-    oauth2::generate_id_token,
+    oauth2::{encrypt_id_token, generate_id_token, load_requested_claims},
     session::{SessionOrFallback, load_session_or_fallback},
 };
 
@@ -59,6 +66,7 @@ impl_from_error_for_route!(mas_policy::LoadError);
 impl_from_error_for_route!(mas_policy::EvaluationError);
 impl_from_error_for_route!(crate::session::SessionLoadError);
 impl_from_error_for_route!(crate::oauth2::IdTokenSignatureError);
+impl_from_error_for_route!(crate::oauth2::ResponseEncryptionError);
 impl_from_error_for_route!(super::callback::IntoCallbackDestinationError);
 impl_from_error_for_route!(super::callback::CallbackDestinationError);
 
@@ -87,6 +95,10 @@ pub(crate) async fn get(
     PreferredLanguage(locale): PreferredLanguage,
     State(templates): State<Templates>,
     State(url_builder): State<UrlBuilder>,
+    State(key_store): State<Keystore>,
+    State(encrypter): State<Encrypter>,
+    State(http_client): State<reqwest::Client>,
+    State(site_config): State<SiteConfig>,
     mut policy: Policy,
     mut repo: BoxRepository,
     activity_tracker: BoundActivityTracker,
@@ -130,6 +142,49 @@ pub(crate) async fn get(
         return Ok((cookie_jar, url_builder.redirect(&login)).into_response());
     };
 
+    // The client may have asked for a recent or stronger authentication
+    let last_authentication = repo
+        .browser_session()
+        .get_last_authentication(&session)
+        .await?;
+    match grant.check_authentication(
+        last_authentication.as_ref(),
+        &site_config.acr_levels,
+        clock.now(),
+    ) {
+        AuthenticationCheck::Satisfied => {}
+        AuthenticationCheck::Reauthenticate => {
+            let login = mas_router::Login::and_continue_grant(grant_id);
+            return Ok((cookie_jar, url_builder.redirect(&login)).into_response());
+        }
+        AuthenticationCheck::Unmet => {
+            // The user just logged in again, and still can't satisfy the
+            // requested ACR levels
+            let callback_destination =
+                CallbackDestination::try_from(&grant)?.with_response_signer(ResponseSigner::new(
+                    &mut rng,
+                    &clock,
+                    &url_builder,
+                    &key_store,
+                    &encrypter,
+                    &http_client,
+                    &client,
+                ));
+            let response = callback_destination
+                .go(
+                    &templates,
+                    &locale,
+                    ClientError::from(ClientErrorCode::UnmetAuthenticationRequirements),
+                )
+                .await?;
+            return Ok((cookie_jar, response).into_response());
+        }
+    }
+    let acr = last_authentication
+        .as_ref()
+        .and_then(|authentication| site_config.acr_for(authentication))
+        .map(|level| level.value.as_str());
+
     activity_tracker
         .record_browser_session(&clock, &session)
         .await;
@@ -142,6 +197,8 @@ pub(crate) async fn get(
             client: &client,
             scope: &grant.scope,
             grant_type: mas_policy::GrantType::AuthorizationCode,
+            authorization_details: grant.authorization_details.as_deref().unwrap_or_default(),
+            acr,
             requester: mas_policy::Requester {
                 ip_address: activity_tracker.ip(),
                 user_agent,
@@ -159,6 +216,51 @@ pub(crate) async fn get(
         return Ok((cookie_jar, Html(content)).into_response());
     }
 
+    // Skip the consent screen if the user already consented to everything the
+    // client asks for, unless the client explicitly asked to prompt them again.
+    // Authorization details and claims requested through the `claims`
+    // parameter are never remembered, so they always need consent.
+    if !grant.requires_consent
+        && grant.authorization_details.is_none()
+        && grant.requested_claims.is_none()
+    {
+        let consent = repo.oauth2_consent().find(&session.user, &client).await?;
+        if consent.is_some_and(|consent| consent.covers(&grant.scope)) {
+            let callback_destination =
+                CallbackDestination::try_from(&grant)?.with_response_signer(ResponseSigner::new(
+                    &mut rng,
+                    &clock,
+                    &url_builder,
+                    &key_store,
+                    &encrypter,
+                    &http_client,
+                    &client,
+                ));
+
+            let response = Box::pin(complete_grant(
+                &mut rng,
+                &clock,
+                &templates,
+                &locale,
+                &url_builder,
+                &key_store,
+                &encrypter,
+                &http_client,
+                &site_config,
+                repo,
+                &activity_tracker,
+                callback_destination,
+                &client,
+                grant,
+                &session,
+                last_authentication.as_ref(),
+            ))
+            .await?;
+
+            return Ok((cookie_jar, response).into_response());
+        }
+    }
+
     let ctx = ConsentContext::new(grant, client)
         .with_session(session)
         .with_csrf(csrf_token.form_value())
@@ -180,6 +282,9 @@ pub(crate) async fn post(
     PreferredLanguage(locale): PreferredLanguage,
     State(templates): State<Templates>,
     State(key_store): State<Keystore>,
+    State(encrypter): State<Encrypter>,
+    State(http_client): State<reqwest::Client>,
+    State(site_config): State<SiteConfig>,
     mut policy: Policy,
     mut repo: BoxRepository,
     activity_tracker: BoundActivityTracker,
@@ -231,16 +336,60 @@ pub(crate) async fn post(
         .await?
         .ok_or(RouteError::NoSuchClient(grant.client_id))?;
 
+    let callback_destination = callback_destination.with_response_signer(ResponseSigner::new(
+        &mut rng,
+        &clock,
+        &url_builder,
+        &key_store,
+        &encrypter,
+        &http_client,
+        &client,
+    ));
+
     if !matches!(grant.stage, AuthorizationGrantStage::Pending) {
         return Err(RouteError::GrantNotPending(grant.id));
     }
 
+    let last_authentication = repo
+        .browser_session()
+        .get_last_authentication(&browser_session)
+        .await?;
+    match grant.check_authentication(
+        last_authentication.as_ref(),
+        &site_config.acr_levels,
+        clock.now(),
+    ) {
+        AuthenticationCheck::Satisfied => {}
+        AuthenticationCheck::Reauthenticate => {
+            let login = mas_router::Login::and_continue_grant(grant_id);
+            return Ok((cookie_jar, url_builder.redirect(&login)).into_response());
+        }
+        AuthenticationCheck::Unmet => {
+            // The user just logged in again, and still can't satisfy the
+            // requested ACR levels
+            let response = callback_destination
+                .go(
+                    &templates,
+                    &locale,
+                    ClientError::from(ClientErrorCode::UnmetAuthenticationRequirements),
+                )
+                .await?;
+            return Ok((cookie_jar, response).into_response());
+        }
+    }
+    let acr = last_authentication
+        .as_ref()
+        .and_then(|authentication| site_config.acr_for(authentication))
+        .map(|level| level.value.as_str());
+
     let res = policy
         .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
             user: Some(&browser_session.user),
             client: &client,
             scope: &grant.scope,
             grant_type: mas_policy::GrantType::AuthorizationCode,
+            authorization_details: grant.authorization_details.as_deref().unwrap_or_default(),
+            acr,
             requester: mas_policy::Requester {
                 ip_address: activity_tracker.ip(),
                 user_agent,
@@ -259,44 +408,131 @@ pub(crate) async fn post(
         return Ok((cookie_jar, Html(content)).into_response());
     }
 
-    // All good, let's start the session
-    let session = repo
-        .oauth2_session()
-        .add_from_browser_session(
+    // Remember the consent, so that the user doesn't get asked again
+    repo.oauth2_consent()
+        .give(
             &mut rng,
             &clock,
+            &browser_session.user,
             &client,
-            &browser_session,
-            grant.scope.clone(),
+            &grant.scope,
         )
         .await?;
 
+    let response = Box::pin(complete_grant(
+        &mut rng,
+        &clock,
+        &templates,
+        &locale,
+        &url_builder,
+        &key_store,
+        &encrypter,
+        &http_client,
+        &site_config,
+        repo,
+        &activity_tracker,
+        callback_destination,
+        &client,
+        grant,
+        &browser_session,
+        last_authentication.as_ref(),
+    ))
+    .await?;
+
+    Ok((cookie_jar, response).into_response())
+}
+
+/// Start the OAuth 2.0 session of an authorization grant the user consented
+/// to, and send the response back to the client
+#[allow(clippy::too_many_arguments)]
+async fn complete_grant(
+    rng: &mut BoxRng,
+    clock: &BoxClock,
+    templates: &Templates,
+    locale: &DataLocale,
+    url_builder: &UrlBuilder,
+    key_store: &Keystore,
+    encrypter: &Encrypter,
+    http_client: &reqwest::Client,
+    site_config: &SiteConfig,
+    mut repo: BoxRepository,
+    activity_tracker: &BoundActivityTracker,
+    callback_destination: CallbackDestination,
+    client: &Client,
+    grant: AuthorizationGrant,
+    browser_session: &BrowserSession,
+    last_authentication: Option<&Authentication>,
+) -> Result<Response, RouteError> {
+    // All good, let's start the session
+    let session = repo
+        .oauth2_session()
+        .add_from_browser_session(rng, clock, client, browser_session, grant.scope.clone())
+        .await?;
+
+    // Remember the claims requested for the UserInfo endpoint
+    let session = if grant.requested_claims.is_some() {
+        repo.oauth2_session()
+            .set_requested_claims(session, grant.requested_claims.clone())
+            .await?
+    } else {
+        session
+    };
+
+    // Remember the authorization details the user consented to
+    let session = if grant.authorization_details.is_some() {
+        repo.oauth2_session()
+            .set_authorization_details(session, grant.authorization_details.clone())
+            .await?
+    } else {
+        session
+    };
+
+    // Restrict the tokens to the resource server the client asked for
+    let session = if let Some(resource) = &grant.resource {
+        repo.oauth2_session()
+            .set_audience(session, vec![resource.to_string()])
+            .await?
+    } else {
+        session
+    };
+
     let grant = repo
         .oauth2_authorization_grant()
-        .fulfill(&clock, &session, grant)
+        .fulfill(clock, &session, grant)
         .await?;
 
     let mut params = AuthorizationResponse::default();
 
     // Did they request an ID token?
     if grant.response_type_id_token {
-        // Fetch the last authentication
-        let last_authentication = repo
-            .browser_session()
-            .get_last_authentication(&browser_session)
-            .await?;
+        let user_claims = load_requested_claims(
+            &mut repo,
+            client,
+            &browser_session.user,
+            &grant.scope,
+            grant
+                .requested_claims
+                .as_ref()
+                .and_then(|claims| claims.id_token.as_ref()),
+        )
+        .await?;
 
-        params.id_token = Some(generate_id_token(
-            &mut rng,
-            &clock,
-            &url_builder,
-            &key_store,
-            &client,
+        let id_token = generate_id_token(
+            rng,
+            clock,
+            url_builder,
+            key_store,
+            site_config,
+            client,
             Some(&grant),
-            &browser_session,
+            browser_session,
             None,
-            last_authentication.as_ref(),
-        )?);
+            last_authentication,
+            user_claims,
+        )?;
+
+        params.id_token =
+            Some(encrypt_id_token(rng, http_client, encrypter, client, id_token).await?);
     }
 
     // Did they request an auth code?
@@ -307,12 +543,8 @@ pub(crate) async fn post(
     repo.save().await?;
 
     activity_tracker
-        .record_oauth2_session(&clock, &session)
+        .record_oauth2_session(clock, &session)
         .await;
 
-    Ok((
-        cookie_jar,
-        callback_destination.go(&templates, &locale, params)?,
-    )
-        .into_response())
+    Ok(callback_destination.go(templates, locale, params).await?)
 }
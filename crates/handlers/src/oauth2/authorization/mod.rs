@@ -4,17 +4,35 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
+use std::{
+    collections::{BTreeMap, HashMap},
+    num::NonZeroU32,
+    sync::Arc,
+};
+
 use axum::{
     extract::{Form, State},
     response::{IntoResponse, Response},
 };
 use hyper::StatusCode;
-use mas_axum_utils::{GenericError, InternalError, SessionInfoExt, cookies::CookieJar};
-use mas_data_model::{AuthorizationCode, Pkce};
+use mas_axum_utils::{
+    GenericError, InternalError, SessionInfo, SessionInfoExt, cookies::CookieJar,
+};
+use mas_data_model::{
+    AuthenticationCheck, AuthorizationCode, BrowserSession, Client,
+    MissingPairwiseSubjectSecretError, Pkce, PushedAuthorizationRequest, SiteConfig,
+    oauth2::LoginHint,
+};
+use mas_jose::{claims, jwt::Jwt};
+use mas_keystore::{Encrypter, Keystore};
+use mas_matrix::HomeserverConnection;
 use mas_router::{PostAuthAction, UrlBuilder};
 use mas_storage::{
-    BoxClock, BoxRepository, BoxRng,
-    oauth2::{OAuth2AuthorizationGrantRepository, OAuth2ClientRepository},
+    BoxClock, BoxRepository, BoxRng, Clock,
+    oauth2::{
+        OAuth2AuthorizationGrantParams, OAuth2AuthorizationGrantRepository, OAuth2ClientRepository,
+        OAuth2PushedAuthorizationRequestRepository,
+    },
 };
 use mas_templates::Templates;
 use oauth2_types::{
@@ -25,13 +43,21 @@ use oauth2_types::{
 };
 use rand::{Rng, distributions::Alphanumeric};
 use serde::Deserialize;
+use serde_json::Value;
 use thiserror::Error;
 
-use self::callback::CallbackDestination;
+use self::callback::{CallbackDestination, ResponseSigner};
+use super::{
+    is_known_resource,
+    pushed_authorization_request::UniqueParameters,
+    request_object::{RequestObjectError, resolve_parameters},
+    unsupported_authorization_details_type,
+};
 use crate::{BoundActivityTracker, PreferredLanguage, impl_from_error_for_route};
 
 mod callback;
 pub(crate) mod consent;
+pub(crate) mod select_account;
 
 #[derive(Debug, Error)]
 pub enum RouteError {
@@ -41,6 +67,15 @@ pub enum RouteError {
     #[error("could not find client")]
     ClientNotFound,
 
+    #[error("invalid authorization request parameters")]
+    InvalidParameters(#[source] serde_urlencoded::de::Error),
+
+    #[error("invalid, expired or already used request_uri")]
+    InvalidRequestUri,
+
+    #[error("invalid request object")]
+    InvalidRequestObject(#[from] RequestObjectError),
+
     #[error("invalid response mode")]
     InvalidResponseMode,
 
@@ -55,7 +90,13 @@ impl IntoResponse for RouteError {
     fn into_response(self) -> axum::response::Response {
         match self {
             Self::Internal(e) => InternalError::new(e).into_response(),
+            Self::InvalidRequestObject(e) if e.is_internal() => {
+                InternalError::new(Box::new(e)).into_response()
+            }
             e @ (Self::ClientNotFound
+            | Self::InvalidParameters(_)
+            | Self::InvalidRequestUri
+            | Self::InvalidRequestObject(_)
             | Self::InvalidResponseMode
             | Self::IntoCallbackDestination(_)
             | Self::UnknownRedirectUri(_)) => {
@@ -70,14 +111,100 @@ impl_from_error_for_route!(mas_templates::TemplateError);
 impl_from_error_for_route!(self::callback::CallbackDestinationError);
 impl_from_error_for_route!(mas_policy::LoadError);
 impl_from_error_for_route!(mas_policy::EvaluationError);
+impl_from_error_for_route!(MissingPairwiseSubjectSecretError);
 
 #[derive(Deserialize)]
 pub(crate) struct Params {
     #[serde(flatten)]
-    auth: AuthorizationRequest,
+    pub(crate) auth: AuthorizationRequest,
 
     #[serde(flatten)]
-    pkce: Option<pkce::AuthorizationRequest>,
+    pub(crate) pkce: Option<pkce::AuthorizationRequest>,
+}
+
+impl Params {
+    /// Parse the authorization request parameters from a map of raw
+    /// parameters, the same way they are parsed from the query string
+    pub(crate) fn from_raw(
+        raw: &BTreeMap<String, String>,
+    ) -> Result<Self, serde_urlencoded::de::Error> {
+        let query = serde_urlencoded::to_string(raw).map_err(serde::de::Error::custom)?;
+        serde_urlencoded::from_str(&query)
+    }
+}
+
+/// The claims of a valid `id_token_hint`, used to pick which of the accounts
+/// signed in on the browser to use
+struct IdTokenHint {
+    /// The browser session the ID token was issued for
+    session_id: Option<String>,
+
+    /// The subject identifier of the user, as known by the client
+    subject: String,
+}
+
+impl IdTokenHint {
+    /// Parse an `id_token_hint`, which has to be an ID token we issued to the
+    /// given client. Returns `None` if it isn't.
+    ///
+    /// The ID token may have expired since it was issued, which is fine here,
+    /// so its expiration isn't checked.
+    fn parse(
+        id_token_hint: &str,
+        key_store: &Keystore,
+        url_builder: &UrlBuilder,
+        client: &Client,
+    ) -> Option<Self> {
+        let jwt: Jwt<'_, HashMap<String, Value>> = Jwt::try_from(id_token_hint).ok()?;
+        jwt.verify_with_jwks(&key_store.public_jwks()).ok()?;
+        let (_header, mut claims) = jwt.into_parts();
+
+        let issuer = url_builder.oidc_issuer();
+        claims::ISS
+            .extract_required_with_options(&mut claims, issuer.as_str())
+            .ok()?;
+        let subject = claims::SUB.extract_required(&mut claims).ok()?;
+        let session_id = claims::SID.extract_optional(&mut claims).ok()?;
+
+        // We only ever issue ID tokens with a single audience, the client
+        let audience = claims.get("aud").and_then(Value::as_str)?;
+        if audience != client.client_id {
+            return None;
+        }
+
+        Some(Self {
+            session_id,
+            subject,
+        })
+    }
+
+    /// Find the browser session this hint refers to, among the ones signed in
+    /// on the browser
+    fn find_session(
+        &self,
+        site_config: &SiteConfig,
+        client: &Client,
+        sessions: &[BrowserSession],
+    ) -> Result<Option<BrowserSession>, MissingPairwiseSubjectSecretError> {
+        // Prefer the exact session the ID token was issued for
+        if let Some(session) = sessions
+            .iter()
+            .find(|session| self.session_id.as_deref() == Some(&session.id.to_string()))
+        {
+            return Ok(Some(session.clone()));
+        }
+
+        // Else any session of the same user
+        for session in sessions {
+            let subject =
+                client.subject_for(&session.user, site_config.pairwise_subject_secret.as_ref())?;
+            if subject == self.subject {
+                return Ok(Some(session.clone()));
+            }
+        }
+
+        Ok(None)
+    }
 }
 
 /// Given a list of response types and an optional user-defined response mode,
@@ -90,23 +217,29 @@ fn resolve_response_mode(
     use ResponseMode as M;
 
     // If the response type includes either "token" or "id_token", the default
-    // response mode is "fragment" and the response mode "query" must not be
-    // used
+    // response mode is "fragment" and the response modes "query" and
+    // "query.jwt" must not be used. The generic "jwt" response mode follows
+    // the same defaults.
     if response_type.has_token() || response_type.has_id_token() {
         match suggested_response_mode {
             None => Ok(M::Fragment),
-            Some(M::Query) => Err(RouteError::InvalidResponseMode),
+            Some(M::Jwt) => Ok(M::FragmentJwt),
+            Some(M::Query | M::QueryJwt) => Err(RouteError::InvalidResponseMode),
             Some(mode) => Ok(mode),
         }
     } else {
         // In other cases, all response modes are allowed, defaulting to "query"
-        Ok(suggested_response_mode.unwrap_or(M::Query))
+        match suggested_response_mode {
+            None => Ok(M::Query),
+            Some(M::Jwt) => Ok(M::QueryJwt),
+            Some(mode) => Ok(mode),
+        }
     }
 }
 
 #[tracing::instrument(
     name = "handlers.oauth2.authorization.get",
-    fields(client.id = %params.auth.client_id),
+    fields(client.id = raw_params.get("client_id").map(String::as_str)),
     skip_all,
 )]
 #[allow(clippy::too_many_lines)]
@@ -116,17 +249,82 @@ pub(crate) async fn get(
     PreferredLanguage(locale): PreferredLanguage,
     State(templates): State<Templates>,
     State(url_builder): State<UrlBuilder>,
+    State(http_client): State<reqwest::Client>,
+    State(key_store): State<Keystore>,
+    State(encrypter): State<Encrypter>,
+    State(site_config): State<SiteConfig>,
+    State(homeserver): State<Arc<dyn HomeserverConnection>>,
     activity_tracker: BoundActivityTracker,
     mut repo: BoxRepository,
     cookie_jar: CookieJar,
-    Form(params): Form<Params>,
+    Form(UniqueParameters(raw_params)): Form<UniqueParameters>,
 ) -> Result<Response, RouteError> {
-    // First, figure out what client it is
-    let client = repo
-        .oauth2_client()
-        .find_by_client_id(&params.auth.client_id)
-        .await?
-        .ok_or(RouteError::ClientNotFound)?;
+    // First, figure out what client it is, and what the parameters are. If the
+    // client pushed its authorization request beforehand, the parameters are
+    // loaded from the database, and everything but the `client_id` and
+    // `request_uri` in the query is ignored. Otherwise, the parameters may be
+    // partly or entirely passed as a signed request object.
+    let pushed_request_reference = raw_params.get("request_uri").and_then(|request_uri| {
+        PushedAuthorizationRequest::reference_from_request_uri(request_uri)
+    });
+
+    let used_pushed_request = pushed_request_reference.is_some();
+
+    let (client, params) = if let Some(reference) = pushed_request_reference {
+        let pushed_request = repo
+            .oauth2_pushed_authorization_request()
+            .find_by_reference(reference)
+            .await?
+            .filter(|pushed_request| pushed_request.is_valid(clock.now()))
+            .ok_or(RouteError::InvalidRequestUri)?;
+
+        let client = repo
+            .oauth2_client()
+            .lookup(pushed_request.client_id)
+            .await?
+            .ok_or(RouteError::ClientNotFound)?;
+
+        // The request_uri is bound to the client which pushed it
+        if raw_params.get("client_id") != Some(&client.client_id) {
+            return Err(RouteError::InvalidRequestUri);
+        }
+
+        // The request_uri is for one-time use only. This only gets persisted if
+        // we end up starting the authorization grant
+        let pushed_request = repo
+            .oauth2_pushed_authorization_request()
+            .consume(&clock, pushed_request)
+            .await?;
+
+        let params =
+            Params::from_raw(&pushed_request.parameters).map_err(RouteError::InvalidParameters)?;
+
+        (client, params)
+    } else {
+        let client_id = raw_params
+            .get("client_id")
+            .ok_or(RouteError::ClientNotFound)?;
+
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(client_id)
+            .await?
+            .ok_or(RouteError::ClientNotFound)?;
+
+        let raw_params = resolve_parameters(
+            &http_client,
+            &url_builder,
+            &clock,
+            &mut repo,
+            &client,
+            raw_params,
+        )
+        .await?;
+
+        let params = Params::from_raw(&raw_params).map_err(RouteError::InvalidParameters)?;
+
+        (client, params)
+    };
 
     // And resolve the redirect_uri and response_mode
     let redirect_uri = client
@@ -140,83 +338,178 @@ pub(crate) async fn get(
         &response_mode,
         redirect_uri.clone(),
         params.auth.state.clone(),
-    )?;
+    )?
+    .with_response_signer(ResponseSigner::new(
+        &mut rng,
+        &clock,
+        &url_builder,
+        &key_store,
+        &encrypter,
+        &http_client,
+        &client,
+    ));
 
     // Get the session info from the cookie
     let (session_info, cookie_jar) = cookie_jar.session_info();
 
     // One day, we will have try blocks
-    let res: Result<Response, RouteError> = ({
+    let res: Result<(SessionInfo, Response), RouteError> = Box::pin({
         let templates = templates.clone();
         let callback_destination = callback_destination.clone();
         let locale = locale.clone();
+        let mut session_info = session_info.clone();
         async move {
-            let maybe_session = session_info.load_active_session(&mut repo).await?;
             let prompt = params.auth.prompt.as_deref().unwrap_or_default();
 
-            // Check if the request/request_uri/registration params are used. If so, reply
-            // with the right error since we don't support them.
-            if params.auth.request.is_some() {
-                return Ok(callback_destination.go(
-                    &templates,
-                    &locale,
-                    ClientError::from(ClientErrorCode::RequestNotSupported),
-                )?);
-            }
-
-            if params.auth.request_uri.is_some() {
-                return Ok(callback_destination.go(
-                    &templates,
-                    &locale,
-                    ClientError::from(ClientErrorCode::RequestUriNotSupported),
-                )?);
+            // Some clients are required to push their authorization requests first
+            if client.require_pushed_authorization_requests && !used_pushed_request {
+                return Ok((
+                    session_info,
+                    callback_destination
+                        .go(
+                            &templates,
+                            &locale,
+                            ClientError::from(ClientErrorCode::InvalidRequest).with_description(
+                                "This client must use pushed authorization requests".to_owned(),
+                            ),
+                        )
+                        .await?,
+                ));
             }
 
             // Check if the client asked for a `token` response type, and bail out if it's
             // the case, since we don't support them
             if response_type.has_token() {
-                return Ok(callback_destination.go(
-                    &templates,
-                    &locale,
-                    ClientError::from(ClientErrorCode::UnsupportedResponseType),
-                )?);
+                return Ok((
+                    session_info,
+                    callback_destination
+                        .go(
+                            &templates,
+                            &locale,
+                            ClientError::from(ClientErrorCode::UnsupportedResponseType),
+                        )
+                        .await?,
+                ));
             }
 
             // If the client asked for a `id_token` response type, we must check if it can
             // use the `implicit` grant type
             if response_type.has_id_token() && !client.grant_types.contains(&GrantType::Implicit) {
-                return Ok(callback_destination.go(
-                    &templates,
-                    &locale,
-                    ClientError::from(ClientErrorCode::UnauthorizedClient),
-                )?);
+                return Ok((
+                    session_info,
+                    callback_destination
+                        .go(
+                            &templates,
+                            &locale,
+                            ClientError::from(ClientErrorCode::UnauthorizedClient),
+                        )
+                        .await?,
+                ));
             }
 
             if params.auth.registration.is_some() {
-                return Ok(callback_destination.go(
-                    &templates,
-                    &locale,
-                    ClientError::from(ClientErrorCode::RegistrationNotSupported),
-                )?);
+                return Ok((
+                    session_info,
+                    callback_destination
+                        .go(
+                            &templates,
+                            &locale,
+                            ClientError::from(ClientErrorCode::RegistrationNotSupported),
+                        )
+                        .await?,
+                ));
+            }
+
+            // Check that we know about all the requested types of authorization details
+            if let Some(unsupported) = params
+                .auth
+                .authorization_details
+                .as_ref()
+                .and_then(|details| unsupported_authorization_details_type(&site_config, details))
+            {
+                return Ok((
+                    session_info,
+                    callback_destination
+                        .go(
+                            &templates,
+                            &locale,
+                            ClientError::from(ClientErrorCode::InvalidAuthorizationDetails)
+                                .with_description(format!(
+                                    "Unsupported authorization details type {unsupported:?}"
+                                )),
+                        )
+                        .await?,
+                ));
+            }
+
+            // Check that the requested resource server is one we know about
+            if let Some(resource) = &params.auth.resource
+                && !is_known_resource(&site_config, resource)
+            {
+                return Ok((
+                    session_info,
+                    callback_destination
+                        .go(
+                            &templates,
+                            &locale,
+                            ClientError::from(ClientErrorCode::InvalidTarget)
+                                .with_description(format!("Unknown resource {resource}")),
+                        )
+                        .await?,
+                ));
             }
 
+            // The client may hint at which account to use with an ID token it got
+            // before
+            let id_token_hint = match params.auth.id_token_hint.as_deref() {
+                Some(id_token_hint) => {
+                    let Some(hint) =
+                        IdTokenHint::parse(id_token_hint, &key_store, &url_builder, &client)
+                    else {
+                        return Ok((
+                            session_info,
+                            callback_destination
+                                .go(
+                                    &templates,
+                                    &locale,
+                                    ClientError::from(ClientErrorCode::InvalidRequest)
+                                        .with_description("Invalid id_token_hint".to_owned()),
+                                )
+                                .await?,
+                        ));
+                    };
+                    Some(hint)
+                }
+                None => None,
+            };
+
             // Fail early if prompt=none; we never let it go through
             if prompt.contains(&Prompt::None) {
-                return Ok(callback_destination.go(
-                    &templates,
-                    &locale,
-                    ClientError::from(ClientErrorCode::LoginRequired),
-                )?);
+                return Ok((
+                    session_info,
+                    callback_destination
+                        .go(
+                            &templates,
+                            &locale,
+                            ClientError::from(ClientErrorCode::LoginRequired),
+                        )
+                        .await?,
+                ));
             }
 
             let code: Option<AuthorizationCode> = if response_type.has_code() {
                 // Check if it is allowed to use this grant type
                 if !client.grant_types.contains(&GrantType::AuthorizationCode) {
-                    return Ok(callback_destination.go(
-                        &templates,
-                        &locale,
-                        ClientError::from(ClientErrorCode::UnauthorizedClient),
-                    )?);
+                    return Ok((
+                        session_info,
+                        callback_destination
+                            .go(
+                                &templates,
+                                &locale,
+                                ClientError::from(ClientErrorCode::UnauthorizedClient),
+                            )
+                            .await?,
+                    ));
                 }
 
                 // 32 random alphanumeric characters, about 190bit of entropy
@@ -236,36 +529,102 @@ pub(crate) async fn get(
                 // If the request had PKCE params but no code asked, it should get back with an
                 // error
                 if params.pkce.is_some() {
-                    return Ok(callback_destination.go(
-                        &templates,
-                        &locale,
-                        ClientError::from(ClientErrorCode::InvalidRequest),
-                    )?);
+                    return Ok((
+                        session_info,
+                        callback_destination
+                            .go(
+                                &templates,
+                                &locale,
+                                ClientError::from(ClientErrorCode::InvalidRequest),
+                            )
+                            .await?,
+                    ));
                 }
 
                 None
             };
 
+            // prompt=login is the same as asking for an authentication which
+            // happened less than 0 seconds ago
+            let max_age = if prompt.contains(&Prompt::Login) {
+                Some(0)
+            } else {
+                params.auth.max_age.map(NonZeroU32::get)
+            };
+
+            let mut acr_values: Vec<String> = params
+                .auth
+                .acr_values
+                .map(|values| values.into_iter().collect())
+                .unwrap_or_default();
+            acr_values.sort();
+
             let grant = repo
                 .oauth2_authorization_grant()
                 .add(
                     &mut rng,
                     &clock,
-                    &client,
-                    redirect_uri.clone(),
-                    params.auth.scope,
-                    code,
-                    params.auth.state.clone(),
-                    params.auth.nonce,
-                    response_mode,
-                    response_type.has_id_token(),
-                    params.auth.login_hint,
-                    Some(locale.to_string()),
+                    OAuth2AuthorizationGrantParams {
+                        client: &client,
+                        redirect_uri: redirect_uri.clone(),
+                        scope: params.auth.scope,
+                        code,
+                        state: params.auth.state.clone(),
+                        nonce: params.auth.nonce,
+                        response_mode,
+                        response_type_id_token: response_type.has_id_token(),
+                        login_hint: params.auth.login_hint,
+                        locale: Some(locale.to_string()),
+                        requested_claims: params.auth.claims,
+                        authorization_details: params.auth.authorization_details,
+                        resource: params.auth.resource,
+                        max_age,
+                        acr_values,
+                        requires_consent: prompt.contains(&Prompt::Consent),
+                    },
                 )
                 .await?;
             let continue_grant = PostAuthAction::continue_grant(grant.id);
 
+            // The browser may be signed in to multiple accounts. The hints pick
+            // one of them, or ask for a login if the hinted user isn't one of
+            // them.
+            let sessions = session_info.load_sessions(&mut repo).await?;
+            let hinted_session = if let Some(hint) = &id_token_hint {
+                Some(hint.find_session(&site_config, &client, &sessions)?)
+            } else if let LoginHint::MXID(mxid) = grant.parse_login_hint(homeserver.homeserver()) {
+                Some(
+                    sessions
+                        .iter()
+                        .find(|session| session.user.username == mxid.localpart())
+                        .cloned(),
+                )
+            } else {
+                None
+            };
+
+            // Let the user choose an account, unless the client told us which one
+            let select_account =
+                prompt.contains(&Prompt::SelectAccount) && hinted_session.is_none();
+
+            let (session_info, maybe_session) = match hinted_session {
+                Some(Some(session)) => (session_info.select_session(session.id), Some(session)),
+                Some(None) => (session_info.deselect_session(), None),
+                None => {
+                    let maybe_session = session_info.load_active_session(&mut repo).await?;
+                    (session_info, maybe_session)
+                }
+            };
+
             let res = match maybe_session {
+                _ if select_account && !sessions.is_empty() => {
+                    repo.save().await?;
+
+                    url_builder
+                        .redirect(&mas_router::SelectAccount(grant.id))
+                        .into_response()
+                }
+
                 None if prompt.contains(&Prompt::Create) => {
                     // Client asked for a registration, show the registration prompt
                     repo.save().await?;
@@ -285,34 +644,516 @@ pub(crate) async fn get(
                 }
 
                 Some(user_session) => {
+                    // The client may want a more recent or a stronger authentication
+                    // than the one of the current session
+                    let last_authentication = repo
+                        .browser_session()
+                        .get_last_authentication(&user_session)
+                        .await?;
+                    let check = grant.check_authentication(
+                        last_authentication.as_ref(),
+                        &site_config.acr_levels,
+                        clock.now(),
+                    );
+
                     // TODO: better support for prompt=create when we have a session
                     repo.save().await?;
 
                     activity_tracker
                         .record_browser_session(&clock, &user_session)
                         .await;
-                    url_builder
-                        .redirect(&mas_router::Consent(grant.id))
-                        .into_response()
+
+                    // The grant was just created, so the check can only ask
+                    // for a new login, and the consent page handles the
+                    // requirements which still can't be met after it
+                    if check == AuthenticationCheck::Satisfied {
+                        url_builder
+                            .redirect(&mas_router::Consent(grant.id))
+                            .into_response()
+                    } else {
+                        url_builder
+                            .redirect(&mas_router::Login::and_then(continue_grant))
+                            .into_response()
+                    }
                 }
             };
 
-            Ok(res)
+            Ok((session_info, res))
         }
     })
     .await;
 
-    let response = match res {
-        Ok(r) => r,
+    let (cookie_jar, response) = match res {
+        Ok((session_info, response)) => (cookie_jar.update_session_info(&session_info), response),
         Err(err) => {
             tracing::error!(message = &err as &dyn std::error::Error);
-            callback_destination.go(
-                &templates,
-                &locale,
-                ClientError::from(ClientErrorCode::ServerError),
-            )?
+            let response = callback_destination
+                .go(
+                    &templates,
+                    &locale,
+                    ClientError::from(ClientErrorCode::ServerError),
+                )
+                .await?;
+            (cookie_jar, response)
         }
     };
 
     Ok((cookie_jar, response).into_response())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::Duration;
+    use hyper::{Request, StatusCode, header::LOCATION};
+    use mas_jose::jwt::Jwt;
+    use mas_router::SimpleRoute;
+    use mas_storage::{
+        Pagination, RepositoryAccess, oauth2::OAuth2ConsentFilter, user::UserPasswordRepository,
+    };
+    use oauth2_types::registration::ClientRegistrationResponse;
+    use sqlx::PgPool;
+    use url::Url;
+    use zeroize::Zeroizing;
+
+    use crate::test_utils::{CookieHelper, RequestBuilderExt, ResponseExt, TestState, setup};
+
+    async fn register_client(state: &TestState) -> String {
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "token_endpoint_auth_method": "none",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let response: ClientRegistrationResponse = response.json();
+        response.client_id
+    }
+
+    /// Send an authorization request with the given extra parameters, and
+    /// return where it redirects to
+    async fn authorize(
+        state: &TestState,
+        cookies: &CookieHelper,
+        client_id: &str,
+        extra: &[(&str, &str)],
+    ) -> String {
+        let mut params = vec![
+            ("client_id", client_id),
+            ("response_type", "code"),
+            ("scope", "openid"),
+        ];
+        params.extend_from_slice(extra);
+        let query = serde_urlencoded::to_string(params).unwrap();
+        let request = Request::get(format!(
+            "{}?{query}",
+            mas_router::OAuth2AuthorizationEndpoint::PATH
+        ))
+        .empty();
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::SEE_OTHER);
+        response
+            .headers()
+            .get(LOCATION)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    /// Provision a user with the `hunter2` password
+    async fn add_user(state: &TestState, username: &str) {
+        let mut rng = state.rng();
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut rng, &state.clock, username.to_owned())
+            .await
+            .unwrap();
+        let (version, hash) = state
+            .password_manager
+            .hash(&mut rng, Zeroizing::new("hunter2".to_owned()))
+            .await
+            .unwrap();
+        repo.user_password()
+            .add(&mut rng, &state.clock, &user, version, hash, None)
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+    }
+
+    /// Fill the login form at the given location, and return where it
+    /// redirects to
+    async fn login(
+        state: &TestState,
+        cookies: &CookieHelper,
+        location: &str,
+        username: &str,
+    ) -> String {
+        let request = cookies.with_cookies(Request::get(location).empty());
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        let csrf_token = response
+            .body()
+            .split("name=\"csrf\" value=\"")
+            .nth(1)
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap();
+        let request = Request::post(location).form(serde_json::json!({
+            "csrf": csrf_token,
+            "username": username,
+            "password": "hunter2",
+        }));
+        let response = state.request(cookies.with_cookies(request)).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::SEE_OTHER);
+        response
+            .headers()
+            .get(LOCATION)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    /// Get the page at the given location, and check who it is shown to
+    async fn assert_signed_in_as(
+        state: &TestState,
+        cookies: &CookieHelper,
+        location: &str,
+        username: &str,
+    ) {
+        let request = cookies.with_cookies(Request::get(location).empty());
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        assert!(
+            response.body().contains(&format!("Not {username}?")),
+            "{}",
+            response.body()
+        );
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_jwt_response_mode(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        let client_id = register_client(&state).await;
+
+        // Without a session, prompt=none fails, and the error is sent back
+        // wrapped in a signed JWT
+        let query = serde_urlencoded::to_string([
+            ("client_id", client_id.as_str()),
+            ("response_type", "code"),
+            ("response_mode", "jwt"),
+            ("scope", "openid"),
+            ("prompt", "none"),
+            ("state", "some-state"),
+        ])
+        .unwrap();
+        let request = Request::get(format!(
+            "{}?{query}",
+            mas_router::OAuth2AuthorizationEndpoint::PATH
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        let location = response.headers().get(LOCATION).unwrap().to_str().unwrap();
+        let location = Url::parse(location).unwrap();
+        assert_eq!(location.path(), "/callback");
+
+        let params: HashMap<String, String> = location.query_pairs().into_owned().collect();
+        assert!(!params.contains_key("state"));
+        assert!(!params.contains_key("error"));
+
+        let jwt: Jwt<'_, HashMap<String, serde_json::Value>> =
+            Jwt::try_from(params["response"].as_str()).unwrap();
+        jwt.verify_with_jwks(&state.key_store.public_jwks())
+            .unwrap();
+        let claims = jwt.payload();
+        assert_eq!(claims["iss"], state.url_builder.oidc_issuer().as_str());
+        assert_eq!(claims["aud"], client_id);
+        assert_eq!(claims["state"], "some-state");
+        assert_eq!(claims["error"], "login_required");
+        assert!(claims.contains_key("exp"));
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_repeated_parameters(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        let client_id = register_client(&state).await;
+
+        // Parameters must not be included more than once
+        let query = serde_urlencoded::to_string([
+            ("client_id", client_id.as_str()),
+            ("response_type", "code"),
+            ("scope", "openid"),
+            ("scope", "openid email"),
+        ])
+        .unwrap();
+        let request = Request::get(format!(
+            "{}?{query}",
+            mas_router::OAuth2AuthorizationEndpoint::PATH
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_reauthentication(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let cookies = CookieHelper::new();
+        let client_id = register_client(&state).await;
+
+        // Log in to get a browser session
+        add_user(&state, "john").await;
+        login(&state, &cookies, "/login", "john").await;
+
+        // A plain request goes straight to the consent screen
+        let location = authorize(&state, &cookies, &client_id, &[]).await;
+        assert!(location.contains("/consent/"), "{location}");
+
+        // prompt=login asks for a new login
+        let location = authorize(&state, &cookies, &client_id, &[("prompt", "login")]).await;
+        assert!(location.contains("/login"), "{location}");
+
+        // The login page doesn't skip the form, even though there is a session
+        let request = cookies.with_cookies(Request::get(location).empty());
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        // A recent enough authentication is fine
+        let location = authorize(&state, &cookies, &client_id, &[("max_age", "3600")]).await;
+        assert!(location.contains("/consent/"), "{location}");
+
+        // But not once it gets too old
+        state.clock.advance(Duration::try_hours(2).unwrap());
+        let location = authorize(&state, &cookies, &client_id, &[("max_age", "3600")]).await;
+        assert!(location.contains("/login"), "{location}");
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_select_account(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let cookies = CookieHelper::new();
+        let client_id = register_client(&state).await;
+
+        add_user(&state, "alice").await;
+        add_user(&state, "bob").await;
+        login(&state, &cookies, "/login", "alice").await;
+
+        // With a single account, the chooser still lets the user sign in to
+        // another one
+        let location = authorize(
+            &state,
+            &cookies,
+            &client_id,
+            &[("prompt", "select_account")],
+        )
+        .await;
+        assert!(location.starts_with("/select_account/"), "{location}");
+
+        let request = cookies.with_cookies(Request::get(&location).empty());
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        let body = response.body();
+        assert!(body.contains("alice"), "{body}");
+        let csrf_token = body
+            .split("name=\"csrf\" value=\"")
+            .nth(1)
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap();
+
+        let request = Request::post(&location).form(serde_json::json!({ "csrf": csrf_token }));
+        let response = state.request(cookies.with_cookies(request)).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::SEE_OTHER);
+        let location = response.headers().get(LOCATION).unwrap().to_str().unwrap();
+        assert!(location.starts_with("/login"), "{location}");
+
+        // Signing in to another account keeps the first one signed in
+        let location = login(&state, &cookies, location, "bob").await;
+        assert!(location.starts_with("/consent/"), "{location}");
+        assert_signed_in_as(&state, &cookies, &location, "bob").await;
+
+        // Both accounts can now be chosen from
+        let location = authorize(
+            &state,
+            &cookies,
+            &client_id,
+            &[("prompt", "select_account")],
+        )
+        .await;
+        let request = cookies.with_cookies(Request::get(&location).empty());
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        let body = response.body();
+        assert!(body.contains("alice"), "{body}");
+        assert!(body.contains("bob"), "{body}");
+        let csrf_token = body
+            .split("name=\"csrf\" value=\"")
+            .nth(1)
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap();
+
+        // Sessions are listed most recent first, so alice is the second one
+        let alice_session_id = body
+            .split("name=\"session_id\"")
+            .nth(2)
+            .unwrap()
+            .split("value=\"")
+            .nth(1)
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap();
+
+        let request = Request::post(&location).form(serde_json::json!({
+            "csrf": csrf_token,
+            "session_id": alice_session_id,
+        }));
+        let response = state.request(cookies.with_cookies(request)).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::SEE_OTHER);
+        let location = response.headers().get(LOCATION).unwrap().to_str().unwrap();
+        assert!(location.starts_with("/consent/"), "{location}");
+        assert_signed_in_as(&state, &cookies, location, "alice").await;
+
+        // A login_hint picks the account, and skips the chooser
+        let location = authorize(
+            &state,
+            &cookies,
+            &client_id,
+            &[
+                ("prompt", "select_account"),
+                ("login_hint", "mxid:@bob:example.com"),
+            ],
+        )
+        .await;
+        assert!(location.starts_with("/consent/"), "{location}");
+        assert_signed_in_as(&state, &cookies, &location, "bob").await;
+
+        // An account which isn't signed in has to log in
+        let location = authorize(
+            &state,
+            &cookies,
+            &client_id,
+            &[("login_hint", "mxid:@carol:example.com")],
+        )
+        .await;
+        assert!(location.starts_with("/login"), "{location}");
+
+        // An invalid id_token_hint is rejected
+        let location = authorize(
+            &state,
+            &cookies,
+            &client_id,
+            &[("id_token_hint", "not-a-jwt")],
+        )
+        .await;
+        let location = Url::parse(&location).unwrap();
+        let params: HashMap<String, String> = location.query_pairs().into_owned().collect();
+        assert_eq!(params["error"], "invalid_request");
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_remembered_consent(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let cookies = CookieHelper::new();
+        let client_id = register_client(&state).await;
+
+        add_user(&state, "john").await;
+        login(&state, &cookies, "/login", "john").await;
+
+        // The first time, the user has to consent
+        let location = authorize(&state, &cookies, &client_id, &[]).await;
+        assert!(location.starts_with("/consent/"), "{location}");
+        let request = cookies.with_cookies(Request::get(&location).empty());
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        let csrf_token = response
+            .body()
+            .split("name=\"csrf\" value=\"")
+            .nth(1)
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap();
+
+        let request = Request::post(&location).form(serde_json::json!({ "csrf": csrf_token }));
+        let response = state.request(cookies.with_cookies(request)).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::SEE_OTHER);
+        let callback = response.headers().get(LOCATION).unwrap().to_str().unwrap();
+        assert!(
+            callback.starts_with("https://example.com/callback"),
+            "{callback}"
+        );
+
+        // The next time, the consent screen is skipped
+        let location = authorize(&state, &cookies, &client_id, &[]).await;
+        let request = cookies.with_cookies(Request::get(&location).empty());
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        let callback = response.headers().get(LOCATION).unwrap().to_str().unwrap();
+        assert!(callback.contains("code="), "{callback}");
+
+        // Unless the client explicitly asks to prompt the user again
+        let location = authorize(&state, &cookies, &client_id, &[("prompt", "consent")]).await;
+        let request = cookies.with_cookies(Request::get(&location).empty());
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        // Or asks for claims through the claims parameter, which are not
+        // remembered
+        let location = authorize(
+            &state,
+            &cookies,
+            &client_id,
+            &[("claims", r#"{"userinfo":{"preferred_username":null}}"#)],
+        )
+        .await;
+        let request = cookies.with_cookies(Request::get(&location).empty());
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        // Once revoked, the user has to consent again
+        let mut repo = state.repository().await.unwrap();
+        let page = repo
+            .oauth2_consent()
+            .list(OAuth2ConsentFilter::new(), Pagination::first(10))
+            .await
+            .unwrap();
+        assert_eq!(page.edges.len(), 1);
+        let consent = page.edges.into_iter().next().unwrap();
+        repo.oauth2_consent().revoke(consent).await.unwrap();
+        repo.save().await.unwrap();
+
+        let location = authorize(&state, &cookies, &client_id, &[]).await;
+        let request = cookies.with_cookies(Request::get(&location).empty());
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! The account chooser page, shown when the client asked for
+//! `prompt=select_account`
+//!
+//! It lists the accounts signed in on this browser, and lets the user either
+//! continue with one of them, or sign in to another one.
+
+use axum::{
+    extract::{Form, Path, State},
+    response::{Html, IntoResponse, Response},
+};
+use hyper::StatusCode;
+use mas_axum_utils::{
+    GenericError, InternalError, SessionInfoExt,
+    cookies::CookieJar,
+    csrf::{CsrfExt, ProtectedForm},
+};
+use mas_data_model::AuthorizationGrantStage;
+use mas_router::UrlBuilder;
+use mas_storage::{
+    BoxClock, BoxRepository, BoxRng,
+    oauth2::{OAuth2AuthorizationGrantRepository, OAuth2ClientRepository},
+};
+use mas_templates::{SelectAccountContext, TemplateContext, Templates};
+use serde::Deserialize;
+use thiserror::Error;
+use ulid::Ulid;
+
+use crate::{PreferredLanguage, impl_from_error_for_route};
+
+#[derive(Debug, Error)]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync>),
+
+    #[error(transparent)]
+    Csrf(#[from] mas_axum_utils::csrf::CsrfError),
+
+    #[error("Authorization grant not found")]
+    GrantNotFound,
+
+    #[error("Authorization grant {0} already used")]
+    GrantNotPending(Ulid),
+
+    #[error("Failed to load client {0}")]
+    NoSuchClient(Ulid),
+}
+
+impl_from_error_for_route!(mas_templates::TemplateError);
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::Internal(e) => InternalError::new(e).into_response(),
+            e @ Self::NoSuchClient(_) => InternalError::new(Box::new(e)).into_response(),
+            e @ Self::GrantNotFound => GenericError::new(StatusCode::NOT_FOUND, e).into_response(),
+            e @ Self::GrantNotPending(_) => {
+                GenericError::new(StatusCode::CONFLICT, e).into_response()
+            }
+            e @ Self::Csrf(_) => GenericError::new(StatusCode::BAD_REQUEST, e).into_response(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SelectAccountForm {
+    /// The browser session to continue with. Signing in to another account if
+    /// missing.
+    #[serde(default)]
+    session_id: Option<Ulid>,
+}
+
+#[tracing::instrument(
+    name = "handlers.oauth2.authorization.select_account.get",
+    fields(grant.id = %grant_id),
+    skip_all,
+)]
+pub(crate) async fn get(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    PreferredLanguage(locale): PreferredLanguage,
+    State(templates): State<Templates>,
+    State(url_builder): State<UrlBuilder>,
+    mut repo: BoxRepository,
+    cookie_jar: CookieJar,
+    Path(grant_id): Path<Ulid>,
+) -> Result<Response, RouteError> {
+    let (mut session_info, cookie_jar) = cookie_jar.session_info();
+
+    let grant = repo
+        .oauth2_authorization_grant()
+        .lookup(grant_id)
+        .await?
+        .ok_or(RouteError::GrantNotFound)?;
+
+    let client = repo
+        .oauth2_client()
+        .lookup(grant.client_id)
+        .await?
+        .ok_or(RouteError::NoSuchClient(grant.client_id))?;
+
+    if !matches!(grant.stage, AuthorizationGrantStage::Pending) {
+        return Err(RouteError::GrantNotPending(grant.id));
+    }
+
+    // Forget the sessions which are no longer signed in
+    let sessions = session_info.load_sessions(&mut repo).await?;
+    let cookie_jar = cookie_jar.update_session_info(&session_info);
+    if sessions.is_empty() {
+        // There is nothing to choose from, ask for a login
+        let login = mas_router::Login::and_continue_grant(grant_id);
+        return Ok((cookie_jar, url_builder.redirect(&login)).into_response());
+    }
+
+    let maybe_session = session_info.load_active_session(&mut repo).await?;
+
+    let (csrf_token, cookie_jar) = cookie_jar.csrf_token(&clock, &mut rng);
+
+    let ctx = SelectAccountContext::new(grant, client, sessions)
+        .maybe_with_session(maybe_session)
+        .with_csrf(csrf_token.form_value())
+        .with_language(locale);
+
+    let content = templates.render_select_account(&ctx)?;
+
+    Ok((cookie_jar, Html(content)).into_response())
+}
+
+#[tracing::instrument(
+    name = "handlers.oauth2.authorization.select_account.post",
+    fields(grant.id = %grant_id),
+    skip_all,
+)]
+pub(crate) async fn post(
+    clock: BoxClock,
+    State(url_builder): State<UrlBuilder>,
+    mut repo: BoxRepository,
+    cookie_jar: CookieJar,
+    Path(grant_id): Path<Ulid>,
+    Form(form): Form<ProtectedForm<SelectAccountForm>>,
+) -> Result<Response, RouteError> {
+    let form = cookie_jar.verify_form(&clock, form)?;
+    let (mut session_info, cookie_jar) = cookie_jar.session_info();
+
+    let grant = repo
+        .oauth2_authorization_grant()
+        .lookup(grant_id)
+        .await?
+        .ok_or(RouteError::GrantNotFound)?;
+
+    if !matches!(grant.stage, AuthorizationGrantStage::Pending) {
+        return Err(RouteError::GrantNotPending(grant.id));
+    }
+
+    // Only sessions which are still signed in on this browser can be chosen
+    let sessions = session_info.load_sessions(&mut repo).await?;
+    let chosen = form
+        .session_id
+        .filter(|session_id| sessions.iter().any(|session| session.id == *session_id));
+
+    let Some(session_id) = chosen else {
+        // Keep the other accounts signed in, but let the user sign in to
+        // another one
+        let session_info = session_info.deselect_session();
+        let cookie_jar = cookie_jar.update_session_info(&session_info);
+        let login = mas_router::Login::and_continue_grant(grant_id);
+        return Ok((cookie_jar, url_builder.redirect(&login)).into_response());
+    };
+
+    let session_info = session_info.select_session(session_id);
+    let cookie_jar = cookie_jar.update_session_info(&session_info);
+
+    Ok((
+        cookie_jar,
+        url_builder.redirect(&mas_router::Consent(grant_id)),
+    )
+        .into_response())
+}
@@ -0,0 +1,476 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Handler for the [OpenID Connect Client-Initiated Backchannel
+//! Authentication] endpoint.
+//!
+//! Requests are stored as device code grants bound to a user. The user
+//! approves or rejects them from an existing session, and the client then
+//! polls the token endpoint with the `auth_req_id`, which is the device code.
+//!
+//! [OpenID Connect Client-Initiated Backchannel Authentication]: https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html
+
+use std::sync::Arc;
+
+use axum::{Json, extract::State, response::IntoResponse};
+use axum_extra::typed_header::TypedHeader;
+use chrono::Duration;
+use headers::{CacheControl, Pragma};
+use hyper::StatusCode;
+use mas_axum_utils::{
+    client_authorization::{ClientAuthorization, CredentialsVerificationError},
+    record_error,
+};
+use mas_iana::oauth::OAuthClientAuthenticationMethod;
+use mas_keystore::Encrypter;
+use mas_matrix::HomeserverConnection;
+use mas_storage::{BoxClock, BoxRepository, BoxRng, oauth2::OAuth2DeviceCodeGrantParams};
+use oauth2_types::{
+    errors::{ClientError, ClientErrorCode},
+    oidc::BackchannelTokenDeliveryMode,
+    requests::{BackchannelAuthenticationRequest, BackchannelAuthenticationResponse, GrantType},
+    scope::OPENID,
+};
+use rand::distributions::{Alphanumeric, DistString};
+use thiserror::Error;
+use ulid::Ulid;
+
+use crate::{BoundActivityTracker, impl_from_error_for_route};
+
+/// The lifetime of a request if the client doesn't ask for a specific one
+const DEFAULT_EXPIRES_IN: Duration = Duration::minutes(10);
+
+/// The maximum lifetime of a request
+const MAX_EXPIRES_IN: Duration = Duration::minutes(30);
+
+/// The minimum amount of time the client should wait between polling requests
+const INTERVAL: Duration = Duration::seconds(5);
+
+/// The maximum length of the `binding_message`, in characters
+const MAX_BINDING_MESSAGE_LENGTH: usize = 100;
+
+/// The maximum length of the `client_notification_token`, in characters
+const MAX_CLIENT_NOTIFICATION_TOKEN_LENGTH: usize = 1024;
+
+#[derive(Debug, Error)]
+pub(crate) enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("client not found")]
+    ClientNotFound,
+
+    #[error("client {0} is not allowed to use the backchannel authentication grant")]
+    ClientNotAllowed(Ulid),
+
+    #[error("invalid client credentials for client {client_id}")]
+    InvalidClientCredentials {
+        client_id: Ulid,
+        #[source]
+        source: CredentialsVerificationError,
+    },
+
+    #[error("could not verify client credentials for client {client_id}")]
+    ClientCredentialsVerification {
+        client_id: Ulid,
+        #[source]
+        source: CredentialsVerificationError,
+    },
+
+    #[error("missing request parameters")]
+    MissingParameters,
+
+    #[error("the openid scope is required")]
+    MissingOpenIdScope,
+
+    #[error("exactly one of login_hint, login_hint_token or id_token_hint is required")]
+    InvalidHint,
+
+    #[error("only the login_hint is supported")]
+    UnsupportedHint,
+
+    #[error("could not find a user matching the login_hint")]
+    UnknownUser,
+
+    #[error("the client_notification_token is required in the ping mode")]
+    MissingClientNotificationToken,
+
+    #[error("the client_notification_token is too long")]
+    ClientNotificationTokenTooLong,
+
+    #[error("the binding_message is too long")]
+    InvalidBindingMessage,
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+
+        let response = match self {
+            Self::Internal(_) | Self::ClientCredentialsVerification { .. } => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ClientError::from(ClientErrorCode::ServerError)),
+            ),
+            Self::ClientNotFound | Self::InvalidClientCredentials { .. } => (
+                StatusCode::UNAUTHORIZED,
+                Json(ClientError::from(ClientErrorCode::InvalidClient)),
+            ),
+            Self::ClientNotAllowed(_) => (
+                StatusCode::UNAUTHORIZED,
+                Json(ClientError::from(ClientErrorCode::UnauthorizedClient)),
+            ),
+            Self::MissingOpenIdScope => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidScope)
+                        .with_description(self.to_string()),
+                ),
+            ),
+            Self::MissingParameters
+            | Self::InvalidHint
+            | Self::UnsupportedHint
+            | Self::MissingClientNotificationToken
+            | Self::ClientNotificationTokenTooLong => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidRequest)
+                        .with_description(self.to_string()),
+                ),
+            ),
+            Self::UnknownUser => (
+                StatusCode::BAD_REQUEST,
+                Json(ClientError::from(ClientErrorCode::UnknownUserId)),
+            ),
+            Self::InvalidBindingMessage => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidBindingMessage)
+                        .with_description(self.to_string()),
+                ),
+            ),
+        };
+
+        (sentry_event_id, response).into_response()
+    }
+}
+
+#[tracing::instrument(
+    name = "handlers.oauth2.backchannel_authentication.post",
+    fields(client.id = client_authorization.client_id()),
+    skip_all,
+)]
+pub(crate) async fn post(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    mut repo: BoxRepository,
+    user_agent: Option<TypedHeader<headers::UserAgent>>,
+    activity_tracker: BoundActivityTracker,
+    State(http_client): State<reqwest::Client>,
+    State(encrypter): State<Encrypter>,
+    State(homeserver): State<Arc<dyn HomeserverConnection>>,
+    client_authorization: ClientAuthorization<BackchannelAuthenticationRequest>,
+) -> Result<impl IntoResponse, RouteError> {
+    let client = client_authorization
+        .credentials
+        .fetch(&mut repo)
+        .await?
+        .ok_or(RouteError::ClientNotFound)?;
+
+    // Reuse the token endpoint auth method to verify the client. Only
+    // confidential clients can start backchannel authentication requests.
+    let method = client
+        .token_endpoint_auth_method
+        .as_ref()
+        .filter(|method| **method != OAuthClientAuthenticationMethod::None)
+        .ok_or(RouteError::ClientNotAllowed(client.id))?;
+
+    client_authorization
+        .credentials
+        .verify(&http_client, &encrypter, method, &client)
+        .await
+        .map_err(|err| {
+            if err.is_internal() {
+                RouteError::ClientCredentialsVerification {
+                    client_id: client.id,
+                    source: err,
+                }
+            } else {
+                RouteError::InvalidClientCredentials {
+                    client_id: client.id,
+                    source: err,
+                }
+            }
+        })?;
+
+    if !client
+        .grant_types
+        .contains(&GrantType::ClientInitiatedBackchannelAuthentication)
+    {
+        return Err(RouteError::ClientNotAllowed(client.id));
+    }
+
+    let form = client_authorization
+        .form
+        .ok_or(RouteError::MissingParameters)?;
+
+    if !form.scope.contains(&OPENID) {
+        return Err(RouteError::MissingOpenIdScope);
+    }
+
+    // Exactly one hint must be present, and we only support the `login_hint`
+    let hints = [
+        form.login_hint.is_some(),
+        form.login_hint_token.is_some(),
+        form.id_token_hint.is_some(),
+    ];
+    if hints.into_iter().filter(|present| *present).count() != 1 {
+        return Err(RouteError::InvalidHint);
+    }
+
+    let Some(login_hint) = form.login_hint else {
+        return Err(RouteError::UnsupportedHint);
+    };
+
+    // The hint is either a full MXID prefixed with `mxid:`, like in authorization
+    // requests, or a plain username
+    let username = if let Some(mxid) = login_hint.strip_prefix("mxid:") {
+        homeserver.localpart(mxid).ok_or(RouteError::UnknownUser)?
+    } else {
+        login_hint.as_str()
+    };
+
+    let user = repo
+        .user()
+        .find_by_username(username)
+        .await?
+        .filter(mas_data_model::User::is_valid)
+        .ok_or(RouteError::UnknownUser)?;
+
+    // Clients without a delivery mode are static clients, which use the poll mode
+    let mode = client
+        .backchannel_token_delivery_mode
+        .clone()
+        .unwrap_or(BackchannelTokenDeliveryMode::Poll);
+
+    if mode == BackchannelTokenDeliveryMode::Ping && form.client_notification_token.is_none() {
+        return Err(RouteError::MissingClientNotificationToken);
+    }
+
+    if form
+        .client_notification_token
+        .as_ref()
+        .is_some_and(|token| token.len() > MAX_CLIENT_NOTIFICATION_TOKEN_LENGTH)
+    {
+        return Err(RouteError::ClientNotificationTokenTooLong);
+    }
+
+    if form
+        .binding_message
+        .as_ref()
+        .is_some_and(|message| message.chars().count() > MAX_BINDING_MESSAGE_LENGTH)
+    {
+        return Err(RouteError::InvalidBindingMessage);
+    }
+
+    let expires_in = form
+        .requested_expiry
+        .map_or(DEFAULT_EXPIRES_IN, |expiry| {
+            Duration::seconds(expiry.get().into())
+        })
+        .min(MAX_EXPIRES_IN);
+
+    let user_agent = user_agent.map(|ua| ua.as_str().to_owned());
+    let ip_address = activity_tracker.ip();
+
+    // The `auth_req_id` is stored as the device code. The user code is never
+    // shown, but must be unique, so it is made long enough to not collide with
+    // the ones generated for the device authorization grant.
+    let device_code = Alphanumeric.sample_string(&mut rng, 32);
+    let user_code = Alphanumeric.sample_string(&mut rng, 32);
+
+    let grant = repo
+        .oauth2_device_code_grant()
+        .add(
+            &mut rng,
+            &clock,
+            OAuth2DeviceCodeGrantParams {
+                client: &client,
+                scope: form.scope,
+                device_code,
+                user_code,
+                expires_in,
+                user_agent,
+                ip_address,
+                user: Some(&user),
+                binding_message: form.binding_message,
+                client_notification_token: form.client_notification_token,
+            },
+        )
+        .await?;
+
+    repo.save().await?;
+
+    let response = BackchannelAuthenticationResponse {
+        auth_req_id: grant.device_code,
+        expires_in,
+        interval: Some(INTERVAL),
+    };
+
+    Ok((
+        StatusCode::OK,
+        TypedHeader(CacheControl::new().with_no_store()),
+        TypedHeader(Pragma::no_cache()),
+        Json(response),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use mas_router::SimpleRoute;
+    use oauth2_types::{
+        errors::{ClientError, ClientErrorCode},
+        registration::ClientRegistrationResponse,
+        requests::{AccessTokenResponse, BackchannelAuthenticationResponse},
+    };
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_backchannel_authentication_poll(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision a client
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "token_endpoint_auth_method": "client_secret_post",
+                "grant_types": ["urn:openid:params:grant-type:ciba"],
+                "response_types": [],
+                "backchannel_token_delivery_mode": "poll",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+        let client_secret = response.client_secret.expect("to have a client secret");
+
+        // Provision a user and a browser session for them
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let browser_session = repo
+            .browser_session()
+            .add(&mut state.rng(), &state.clock, &user, None)
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // Unknown users are rejected
+        let request = Request::post(mas_router::OAuth2BackchannelAuthenticationEndpoint::PATH)
+            .form(serde_json::json!({
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "scope": "openid",
+                "login_hint": "bob",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::UnknownUserId);
+
+        // The openid scope is required
+        let request = Request::post(mas_router::OAuth2BackchannelAuthenticationEndpoint::PATH)
+            .form(serde_json::json!({
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "scope": "urn:matrix:org.matrix.msc2967.client:api:*",
+                "login_hint": "alice",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidScope);
+
+        // Start a request for alice
+        let request = Request::post(mas_router::OAuth2BackchannelAuthenticationEndpoint::PATH)
+            .form(serde_json::json!({
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "scope": "openid",
+                "login_hint": "mxid:@alice:example.com",
+                "binding_message": "W4SCT",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let BackchannelAuthenticationResponse { auth_req_id, .. } = response.json();
+
+        // Poll the token endpoint, it should be pending
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:openid:params:grant-type:ciba",
+                "auth_req_id": auth_req_id,
+                "client_id": client_id,
+                "client_secret": client_secret,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::AuthorizationPending);
+
+        // The request shows up for the user, and they approve it
+        let mut repo = state.repository().await.unwrap();
+        let mut grants = repo
+            .oauth2_device_code_grant()
+            .list_pending_for_user(&state.clock, &user)
+            .await
+            .unwrap();
+        assert_eq!(grants.len(), 1);
+        let grant = grants.pop().unwrap();
+        assert_eq!(grant.binding_message.as_deref(), Some("W4SCT"));
+
+        repo.oauth2_device_code_grant()
+            .fulfill(&state.clock, grant, &browser_session)
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // Now the token endpoint gives us tokens
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:openid:params:grant-type:ciba",
+                "auth_req_id": auth_req_id,
+                "client_id": client_id,
+                "client_secret": client_secret,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: AccessTokenResponse = response.json();
+        assert!(state.is_access_token_valid(&response.access_token).await);
+        assert!(response.id_token.is_some());
+
+        // Calling it again should fail
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:openid:params:grant-type:ciba",
+                "auth_req_id": auth_req_id,
+                "client_id": client_id,
+                "client_secret": client_secret,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidGrant);
+    }
+}
@@ -4,6 +4,8 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
+use std::collections::BTreeMap;
+
 use axum::{Json, extract::State, response::IntoResponse};
 use axum_extra::typed_header::TypedHeader;
 use chrono::Duration;
@@ -25,7 +27,10 @@ use rand::distributions::{Alphanumeric, DistString};
 use thiserror::Error;
 use ulid::Ulid;
 
-use crate::{BoundActivityTracker, impl_from_error_for_route};
+use crate::{
+    BoundActivityTracker, impl_from_error_for_route,
+    oauth2::request_object::{RequestObjectError, resolve_parameters},
+};
 
 #[derive(Debug, Error)]
 pub(crate) enum RouteError {
@@ -51,10 +56,26 @@ pub(crate) enum RouteError {
         #[source]
         source: CredentialsVerificationError,
     },
+
+    #[error("invalid request object")]
+    InvalidRequestObject(#[source] RequestObjectError),
+
+    #[error("invalid scope")]
+    InvalidScope(#[source] oauth2_types::scope::InvalidScope),
 }
 
 impl_from_error_for_route!(mas_storage::RepositoryError);
 
+impl From<RequestObjectError> for RouteError {
+    fn from(e: RequestObjectError) -> Self {
+        if e.is_internal() {
+            Self::Internal(Box::new(e))
+        } else {
+            Self::InvalidRequestObject(e)
+        }
+    }
+}
+
 impl IntoResponse for RouteError {
     fn into_response(self) -> axum::response::Response {
         let sentry_event_id = record_error!(self, Self::Internal(_));
@@ -72,6 +93,22 @@ impl IntoResponse for RouteError {
                 StatusCode::UNAUTHORIZED,
                 Json(ClientError::from(ClientErrorCode::UnauthorizedClient)),
             ),
+            Self::InvalidRequestObject(ref e) => {
+                let code = if e.is_request_uri_error() {
+                    ClientErrorCode::InvalidRequestUri
+                } else {
+                    ClientErrorCode::InvalidRequestObject
+                };
+
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ClientError::from(code).with_description(e.to_string())),
+                )
+            }
+            Self::InvalidScope(_) => (
+                StatusCode::BAD_REQUEST,
+                Json(ClientError::from(ClientErrorCode::InvalidScope)),
+            ),
         };
 
         (sentry_event_id, response).into_response()
@@ -128,11 +165,46 @@ pub(crate) async fn post(
         return Err(RouteError::ClientNotAllowed(client.id));
     }
 
-    let scope = client_authorization
+    let form = client_authorization
         .form
-        .and_then(|f| f.scope)
-        // XXX: Is this really how we do empty scopes?
-        .unwrap_or(std::iter::empty::<ScopeToken>().collect());
+        .unwrap_or(DeviceAuthorizationRequest {
+            scope: None,
+            request: None,
+            request_uri: None,
+        });
+
+    let scope = if form.request.is_some() || form.request_uri.is_some() {
+        // The scope may be passed in a signed request object, in which case the
+        // one passed as a plain parameter is ignored
+        let mut parameters = BTreeMap::new();
+        if let Some(request) = form.request {
+            parameters.insert("request".to_owned(), request);
+        }
+        if let Some(request_uri) = form.request_uri {
+            parameters.insert("request_uri".to_owned(), request_uri.to_string());
+        }
+
+        let parameters = resolve_parameters(
+            &http_client,
+            &url_builder,
+            &clock,
+            &mut repo,
+            &client,
+            parameters,
+        )
+        .await?;
+
+        parameters
+            .get("scope")
+            .map(|scope| scope.parse())
+            .transpose()
+            .map_err(RouteError::InvalidScope)?
+    } else {
+        form.scope
+    };
+
+    // XXX: Is this really how we do empty scopes?
+    let scope = scope.unwrap_or(std::iter::empty::<ScopeToken>().collect());
 
     let expires_in = Duration::microseconds(20 * 60 * 1000 * 1000);
 
@@ -155,6 +227,9 @@ pub(crate) async fn post(
                 expires_in,
                 user_agent,
                 ip_address,
+                user: None,
+                binding_message: None,
+                client_notification_token: None,
             },
         )
         .await?;
@@ -180,10 +255,23 @@ pub(crate) async fn post(
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use base64ct::{Base64UrlUnpadded, Encoding};
+    use chrono::Duration;
     use hyper::{Request, StatusCode};
+    use mas_iana::jose::JsonWebSignatureAlg;
+    use mas_jose::{
+        claims,
+        jwt::{JsonWebSignatureHeader, Jwt},
+    };
+    use mas_keystore::{JsonWebKey, JsonWebKeySet, Keystore, PrivateKey};
     use mas_router::SimpleRoute;
+    use mas_storage::Clock;
     use oauth2_types::{
-        registration::ClientRegistrationResponse, requests::DeviceAuthorizationResponse,
+        errors::{ClientError, ClientErrorCode},
+        registration::ClientRegistrationResponse,
+        requests::DeviceAuthorizationResponse,
     };
     use sqlx::PgPool;
 
@@ -223,4 +311,145 @@ mod tests {
         assert_eq!(response.device_code.len(), 32);
         assert_eq!(response.user_code.len(), 6);
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_device_code_request_object(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        let key = PrivateKey::load_pem(include_str!(
+            "../../../../keystore/tests/keys/ec-p256.pkcs8.pem"
+        ))
+        .unwrap();
+        let client_keys = Keystore::new(JsonWebKeySet::new(vec![
+            JsonWebKey::new(key).with_kid("client-key"),
+        ]));
+
+        // Provision a client with a JWKS
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "token_endpoint_auth_method": "none",
+                "grant_types": ["urn:ietf:params:oauth:grant-type:device_code"],
+                "response_types": [],
+                "jwks": client_keys.public_jwks(),
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+
+        let sign = |claims: HashMap<String, serde_json::Value>| {
+            let alg = JsonWebSignatureAlg::Es256;
+            let key = client_keys.signing_key_for_algorithm(&alg).unwrap();
+            let signer = key.params().signing_key_for_alg(&alg).unwrap();
+            let header = JsonWebSignatureHeader::new(alg).with_kid("client-key");
+            Jwt::sign(header, claims, &signer).unwrap().into_string()
+        };
+
+        let mut claims = HashMap::new();
+        claims::ISS.insert(&mut claims, client_id.clone()).unwrap();
+        claims::AUD
+            .insert(&mut claims, state.url_builder.oidc_issuer().to_string())
+            .unwrap();
+        claims::EXP
+            .insert(&mut claims, state.clock.now() + Duration::minutes(5))
+            .unwrap();
+        claims::JTI
+            .insert(&mut claims, "request-object".to_owned())
+            .unwrap();
+        claims.insert("scope".to_owned(), "openid".into());
+        let request_object = sign(claims.clone());
+
+        // The signed request object is accepted
+        let request = Request::post(mas_router::OAuth2DeviceAuthorizationEndpoint::PATH).form(
+            serde_json::json!({
+                "client_id": client_id,
+                "request": request_object,
+            }),
+        );
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        // The scope from the request object is used
+        let response: DeviceAuthorizationResponse = response.json();
+        let mut repo = state.repository().await.unwrap();
+        let grant = repo
+            .oauth2_device_code_grant()
+            .find_by_device_code(&response.device_code)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(grant.scope.to_string(), "openid");
+        repo.save().await.unwrap();
+
+        // It can't be replayed
+        let request = Request::post(mas_router::OAuth2DeviceAuthorizationEndpoint::PATH).form(
+            serde_json::json!({
+                "client_id": client_id,
+                "request": request_object,
+            }),
+        );
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let error: ClientError = response.json();
+        assert_eq!(error.error, ClientErrorCode::InvalidRequestObject);
+
+        // Unsigned request objects are rejected
+        claims::JTI
+            .insert(&mut claims, "unsigned-request-object".to_owned())
+            .unwrap();
+        let header = Base64UrlUnpadded::encode_string(br#"{"alg":"none"}"#);
+        let payload = Base64UrlUnpadded::encode_string(&serde_json::to_vec(&claims).unwrap());
+        let request = Request::post(mas_router::OAuth2DeviceAuthorizationEndpoint::PATH).form(
+            serde_json::json!({
+                "client_id": client_id,
+                "request": format!("{header}.{payload}."),
+            }),
+        );
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let error: ClientError = response.json();
+        assert_eq!(error.error, ClientErrorCode::InvalidRequestObject);
+
+        // Parameters passed outside of the request object are ignored
+        claims::JTI
+            .insert(&mut claims, "request-object-without-scope".to_owned())
+            .unwrap();
+        claims.remove("scope");
+        let request = Request::post(mas_router::OAuth2DeviceAuthorizationEndpoint::PATH).form(
+            serde_json::json!({
+                "client_id": client_id,
+                "scope": "openid",
+                "request": sign(claims),
+            }),
+        );
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let response: DeviceAuthorizationResponse = response.json();
+        let mut repo = state.repository().await.unwrap();
+        let grant = repo
+            .oauth2_device_code_grant()
+            .find_by_device_code(&response.device_code)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(grant.scope.is_empty());
+        repo.save().await.unwrap();
+
+        // Request objects are not fetched from URIs the client did not register
+        let request = Request::post(mas_router::OAuth2DeviceAuthorizationEndpoint::PATH).form(
+            serde_json::json!({
+                "client_id": client_id,
+                "request_uri": "https://example.com/request.jwt",
+            }),
+        );
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let error: ClientError = response.json();
+        assert_eq!(error.error, ClientErrorCode::InvalidRequestUri);
+    }
 }
@@ -16,6 +16,7 @@ use mas_axum_utils::{
     cookies::CookieJar,
     csrf::{CsrfExt, ProtectedForm},
 };
+use mas_data_model::SiteConfig;
 use mas_policy::Policy;
 use mas_router::UrlBuilder;
 use mas_storage::{BoxClock, BoxRepository, BoxRng};
@@ -48,6 +49,7 @@ pub(crate) async fn get(
     PreferredLanguage(locale): PreferredLanguage,
     State(templates): State<Templates>,
     State(url_builder): State<UrlBuilder>,
+    State(site_config): State<SiteConfig>,
     mut repo: BoxRepository,
     mut policy: Policy,
     activity_tracker: BoundActivityTracker,
@@ -86,6 +88,7 @@ pub(crate) async fn get(
         .oauth2_device_code_grant()
         .lookup(grant_id)
         .await?
+        .filter(|grant| !grant.is_backchannel_authentication())
         .context("Device grant not found")
         .map_err(InternalError::from_anyhow)?;
 
@@ -102,13 +105,24 @@ pub(crate) async fn get(
         .context("Client not found")
         .map_err(InternalError::from_anyhow)?;
 
+    let last_authentication = repo
+        .browser_session()
+        .get_last_authentication(&session)
+        .await?;
+    let acr = last_authentication
+        .as_ref()
+        .and_then(|authentication| site_config.acr_for(authentication))
+        .map(|level| level.value.as_str());
+
     // Evaluate the policy
     let res = policy
         .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
             grant_type: mas_policy::GrantType::DeviceCode,
+            authorization_details: &[],
             client: &client,
             scope: &grant.scope,
             user: Some(&session.user),
+            acr,
             requester: mas_policy::Requester {
                 ip_address: activity_tracker.ip(),
                 user_agent,
@@ -149,6 +163,7 @@ pub(crate) async fn post(
     PreferredLanguage(locale): PreferredLanguage,
     State(templates): State<Templates>,
     State(url_builder): State<UrlBuilder>,
+    State(site_config): State<SiteConfig>,
     mut repo: BoxRepository,
     mut policy: Policy,
     activity_tracker: BoundActivityTracker,
@@ -188,6 +203,7 @@ pub(crate) async fn post(
         .oauth2_device_code_grant()
         .lookup(grant_id)
         .await?
+        .filter(|grant| !grant.is_backchannel_authentication())
         .context("Device grant not found")
         .map_err(InternalError::from_anyhow)?;
 
@@ -204,13 +220,24 @@ pub(crate) async fn post(
         .context("Client not found")
         .map_err(InternalError::from_anyhow)?;
 
+    let last_authentication = repo
+        .browser_session()
+        .get_last_authentication(&session)
+        .await?;
+    let acr = last_authentication
+        .as_ref()
+        .and_then(|authentication| site_config.acr_for(authentication))
+        .map(|level| level.value.as_str());
+
     // Evaluate the policy
     let res = policy
         .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
             grant_type: mas_policy::GrantType::DeviceCode,
+            authorization_details: &[],
             client: &client,
             scope: &grant.scope,
             user: Some(&session.user),
+            acr,
             requester: mas_policy::Requester {
                 ip_address: activity_tracker.ip(),
                 user_agent,
@@ -46,6 +46,9 @@ pub(crate) async fn get(
             .await?
             // XXX: We should have different error messages for already exchanged and expired
             .filter(|grant| grant.is_pending())
+            // Backchannel authentication requests are approved from the account UI, not
+            // through the device link
+            .filter(|grant| !grant.is_backchannel_authentication())
             .filter(|grant| grant.expires_at > clock.now());
 
         if let Some(grant) = grant {
@@ -5,20 +5,27 @@
 // Please see LICENSE files in the repository root for full details.
 
 use axum::{Json, extract::State, response::IntoResponse};
-use mas_iana::oauth::{
-    OAuthAuthorizationEndpointResponseType, OAuthClientAuthenticationMethod,
-    PkceCodeChallengeMethod,
+use mas_iana::{
+    jose::JsonWebSignatureAlg,
+    oauth::{
+        OAuthAuthorizationEndpointResponseType, OAuthClientAuthenticationMethod,
+        PkceCodeChallengeMethod,
+    },
+};
+use mas_jose::jwa::{
+    SUPPORTED_CONTENT_ENCRYPTION_ALGORITHMS, SUPPORTED_KEY_MANAGEMENT_ALGORITHMS,
+    SUPPORTED_SIGNING_ALGORITHMS,
 };
-use mas_jose::jwa::SUPPORTED_SIGNING_ALGORITHMS;
 use mas_keystore::Keystore;
 use mas_router::UrlBuilder;
 use oauth2_types::{
-    oidc::{ClaimType, ProviderMetadata, SubjectType},
+    oidc::{BackchannelTokenDeliveryMode, ClaimType, ProviderMetadata, SubjectType},
     requests::{Display, GrantType, Prompt, ResponseMode},
     scope,
 };
 use serde::Serialize;
 
+use super::dpop;
 use crate::SiteConfig;
 
 #[derive(Debug, Serialize)]
@@ -47,6 +54,8 @@ pub(crate) async fn get(
         OAuthClientAuthenticationMethod::ClientSecretPost,
         OAuthClientAuthenticationMethod::ClientSecretJwt,
         OAuthClientAuthenticationMethod::PrivateKeyJwt,
+        OAuthClientAuthenticationMethod::TlsClientAuth,
+        OAuthClientAuthenticationMethod::SelfSignedTlsClientAuth,
         OAuthClientAuthenticationMethod::None,
     ]);
 
@@ -61,11 +70,22 @@ pub(crate) async fn get(
     let authorization_endpoint = Some(url_builder.oauth_authorization_endpoint());
     let token_endpoint = Some(url_builder.oauth_token_endpoint());
     let device_authorization_endpoint = Some(url_builder.oauth_device_authorization_endpoint());
+    let backchannel_authentication_endpoint =
+        Some(url_builder.oauth_backchannel_authentication_endpoint());
     let jwks_uri = Some(url_builder.jwks_uri());
     let introspection_endpoint = Some(url_builder.oauth_introspection_endpoint());
     let revocation_endpoint = Some(url_builder.oauth_revocation_endpoint());
     let userinfo_endpoint = Some(url_builder.oidc_userinfo_endpoint());
     let registration_endpoint = Some(url_builder.oauth_registration_endpoint());
+    let end_session_endpoint = Some(url_builder.oidc_end_session_endpoint());
+    let backchannel_logout_supported = Some(true);
+    let backchannel_logout_session_supported = Some(true);
+    let frontchannel_logout_supported = Some(true);
+    let frontchannel_logout_session_supported = Some(true);
+    let pushed_authorization_request_endpoint =
+        Some(url_builder.oauth_pushed_authorization_request_endpoint());
+    let dpop_signing_alg_values_supported = Some(dpop::SUPPORTED_ALGORITHMS.to_vec());
+    let tls_client_certificate_bound_access_tokens = Some(true);
 
     let scopes_supported = Some(vec![scope::OPENID.to_string(), scope::EMAIL.to_string()]);
 
@@ -79,6 +99,10 @@ pub(crate) async fn get(
         ResponseMode::FormPost,
         ResponseMode::Query,
         ResponseMode::Fragment,
+        ResponseMode::Jwt,
+        ResponseMode::FormPostJwt,
+        ResponseMode::QueryJwt,
+        ResponseMode::FragmentJwt,
     ]);
 
     let grant_types_supported = Some(vec![
@@ -86,6 +110,9 @@ pub(crate) async fn get(
         GrantType::RefreshToken,
         GrantType::ClientCredentials,
         GrantType::DeviceCode,
+        GrantType::TokenExchange,
+        GrantType::JwtBearer,
+        GrantType::ClientInitiatedBackchannelAuthentication,
     ]);
 
     let token_endpoint_auth_methods_supported = client_auth_methods_supported.clone();
@@ -106,10 +133,31 @@ pub(crate) async fn get(
         PkceCodeChallengeMethod::S256,
     ]);
 
-    let subject_types_supported = Some(vec![SubjectType::Public]);
+    // Pairwise subject identifiers are derived from a secret, so they are only
+    // supported if one is configured
+    let mut subject_types_supported = vec![SubjectType::Public];
+    if site_config.pairwise_subject_secret.is_some() {
+        subject_types_supported.push(SubjectType::Pairwise);
+    }
+    let subject_types_supported = Some(subject_types_supported);
 
+    let introspection_signing_alg_values_supported = jwt_signing_alg_values_supported.clone();
     let id_token_signing_alg_values_supported = jwt_signing_alg_values_supported.clone();
-    let userinfo_signing_alg_values_supported = jwt_signing_alg_values_supported;
+    let userinfo_signing_alg_values_supported = jwt_signing_alg_values_supported.clone();
+    let authorization_signing_alg_values_supported = jwt_signing_alg_values_supported;
+
+    // Responses are encrypted with the client's keys, so the supported
+    // algorithms don't depend on the configured keys
+    let encryption_alg_values_supported = Some(SUPPORTED_KEY_MANAGEMENT_ALGORITHMS.to_vec());
+    let encryption_enc_values_supported = Some(SUPPORTED_CONTENT_ENCRYPTION_ALGORITHMS.to_vec());
+    let introspection_encryption_alg_values_supported = encryption_alg_values_supported.clone();
+    let introspection_encryption_enc_values_supported = encryption_enc_values_supported.clone();
+    let id_token_encryption_alg_values_supported = encryption_alg_values_supported.clone();
+    let id_token_encryption_enc_values_supported = encryption_enc_values_supported.clone();
+    let userinfo_encryption_alg_values_supported = encryption_alg_values_supported.clone();
+    let userinfo_encryption_enc_values_supported = encryption_enc_values_supported.clone();
+    let authorization_encryption_alg_values_supported = encryption_alg_values_supported;
+    let authorization_encryption_enc_values_supported = encryption_enc_values_supported;
 
     let display_values_supported = Some(vec![Display::Page]);
 
@@ -123,16 +171,52 @@ pub(crate) async fn get(
         "exp".to_owned(),
         "nonce".to_owned(),
         "auth_time".to_owned(),
+        "acr".to_owned(),
+        "amr".to_owned(),
         "at_hash".to_owned(),
         "c_hash".to_owned(),
+        "sid".to_owned(),
+        "preferred_username".to_owned(),
+        "email".to_owned(),
+        "email_verified".to_owned(),
     ]);
 
-    let claims_parameter_supported = Some(false);
-    let request_parameter_supported = Some(false);
-    let request_uri_parameter_supported = Some(false);
+    let claims_parameter_supported = Some(true);
+    let request_parameter_supported = Some(true);
+    let request_uri_parameter_supported = Some(true);
+
+    // Request objects are verified against the client's JWKS, so only the
+    // asymmetric algorithms are supported
+    let request_object_signing_alg_values_supported = Some(
+        SUPPORTED_SIGNING_ALGORITHMS
+            .into_iter()
+            .filter(|alg| {
+                !matches!(
+                    alg,
+                    JsonWebSignatureAlg::Hs256
+                        | JsonWebSignatureAlg::Hs384
+                        | JsonWebSignatureAlg::Hs512
+                )
+            })
+            .collect(),
+    );
+
+    // Only advertise rich authorization requests if some types are configured
+    let authorization_details_types_supported =
+        (!site_config.authorization_details_types.is_empty())
+            .then(|| site_config.authorization_details_types.clone());
+
+    // Only advertise ACR values if some levels are configured
+    let acr_values_supported = (!site_config.acr_levels.is_empty()).then(|| {
+        site_config
+            .acr_levels
+            .iter()
+            .map(|level| level.value.clone())
+            .collect()
+    });
 
     let prompt_values_supported = Some({
-        let mut v = vec![Prompt::Login];
+        let mut v = vec![Prompt::Login, Prompt::SelectAccount];
         // Advertise for prompt=create if password registration is enabled
         // TODO: we may want to be able to forward that to upstream providers if they
         // support it
@@ -142,6 +226,14 @@ pub(crate) async fn get(
         v
     });
 
+    // Client-initiated backchannel authentication requests are approved from the
+    // account UI, and the client either polls the token endpoint or gets pinged
+    let backchannel_token_delivery_modes_supported = Some(vec![
+        BackchannelTokenDeliveryMode::Poll,
+        BackchannelTokenDeliveryMode::Ping,
+    ]);
+    let backchannel_user_code_parameter_supported = Some(false);
+
     let standard = ProviderMetadata {
         issuer,
         authorization_endpoint,
@@ -160,19 +252,43 @@ pub(crate) async fn get(
         introspection_endpoint,
         introspection_endpoint_auth_methods_supported,
         introspection_endpoint_auth_signing_alg_values_supported,
+        introspection_signing_alg_values_supported,
+        introspection_encryption_alg_values_supported,
+        introspection_encryption_enc_values_supported,
+        authorization_signing_alg_values_supported,
+        authorization_encryption_alg_values_supported,
+        authorization_encryption_enc_values_supported,
         code_challenge_methods_supported,
         userinfo_endpoint,
+        acr_values_supported,
         subject_types_supported,
         id_token_signing_alg_values_supported,
+        id_token_encryption_alg_values_supported,
+        id_token_encryption_enc_values_supported,
         userinfo_signing_alg_values_supported,
+        userinfo_encryption_alg_values_supported,
+        userinfo_encryption_enc_values_supported,
         display_values_supported,
         claim_types_supported,
         claims_supported,
         claims_parameter_supported,
         request_parameter_supported,
         request_uri_parameter_supported,
+        request_object_signing_alg_values_supported,
         prompt_values_supported,
         device_authorization_endpoint,
+        backchannel_authentication_endpoint,
+        backchannel_token_delivery_modes_supported,
+        backchannel_user_code_parameter_supported,
+        pushed_authorization_request_endpoint,
+        end_session_endpoint,
+        backchannel_logout_supported,
+        backchannel_logout_session_supported,
+        frontchannel_logout_supported,
+        frontchannel_logout_session_supported,
+        dpop_signing_alg_values_supported,
+        tls_client_certificate_bound_access_tokens,
+        authorization_details_types_supported,
         ..ProviderMetadata::default()
     };
 
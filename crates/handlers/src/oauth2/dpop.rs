@@ -0,0 +1,275 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Support for sender-constraining tokens with `DPoP` proofs, as defined in
+//! RFC 9449
+
+use std::collections::HashMap;
+
+use axum::http::{HeaderMap, Method};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use chrono::Duration;
+use mas_data_model::Session;
+use mas_iana::jose::JsonWebSignatureAlg;
+use mas_jose::{
+    claims::{self, Claim, ClaimError, Equality, TimeOptions},
+    jwa::AsymmetricVerifyingKey,
+    jwt::{Jwt, JwtDecodeError},
+};
+use mas_storage::{BoxRepository, Clock};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use url::Url;
+
+use crate::impl_from_error_for_route;
+
+/// The name of the header carrying the `DPoP` proof
+pub(crate) const DPOP_HEADER: &str = "dpop";
+
+/// The JWS algorithms we accept for `DPoP` proofs
+pub(crate) const SUPPORTED_ALGORITHMS: [JsonWebSignatureAlg; 9] = [
+    JsonWebSignatureAlg::Rs256,
+    JsonWebSignatureAlg::Rs384,
+    JsonWebSignatureAlg::Rs512,
+    JsonWebSignatureAlg::Ps256,
+    JsonWebSignatureAlg::Ps384,
+    JsonWebSignatureAlg::Ps512,
+    JsonWebSignatureAlg::Es256,
+    JsonWebSignatureAlg::Es384,
+    JsonWebSignatureAlg::Es256K,
+];
+
+/// How long after being issued a proof is still accepted
+const PROOF_MAX_AGE: Duration = Duration::minutes(5);
+
+const HTM: Claim<String, Equality<str>> = Claim::new("htm");
+const HTU: Claim<String> = Claim::new("htu");
+const ATH: Claim<String> = Claim::new("ath");
+
+#[derive(Debug, Error)]
+pub(crate) enum DpopError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("the DPoP header is invalid")]
+    InvalidHeader,
+
+    #[error("failed to decode the DPoP proof")]
+    Decode(#[from] JwtDecodeError),
+
+    #[error("the DPoP proof has an invalid type")]
+    InvalidType,
+
+    #[error("the DPoP proof uses an unsupported algorithm")]
+    UnsupportedAlgorithm,
+
+    #[error("the DPoP proof does not embed its public key")]
+    MissingKey,
+
+    #[error("invalid DPoP proof signature")]
+    InvalidSignature,
+
+    #[error("invalid claims in DPoP proof")]
+    InvalidClaims(#[from] ClaimError),
+
+    #[error("the DPoP proof is not for this endpoint")]
+    WrongEndpoint,
+
+    #[error("the DPoP proof is not bound to this access token")]
+    WrongAccessToken,
+
+    #[error("the DPoP proof is too old")]
+    Expired,
+
+    #[error("the DPoP proof was already used")]
+    Replayed,
+
+    #[error("a DPoP proof is required")]
+    Required,
+
+    #[error("the DPoP proof does not use the key the session is bound to")]
+    KeyMismatch,
+
+    #[error("the session is not bound to a DPoP key")]
+    Unbound,
+}
+
+impl_from_error_for_route!(DpopError: mas_storage::RepositoryError);
+
+impl DpopError {
+    /// Returns true if the error is an internal error, not caused by the client
+    pub(crate) fn is_internal(&self) -> bool {
+        matches!(self, Self::Internal(_))
+    }
+}
+
+/// Verify the `DPoP` proof sent in the headers of a request, if any.
+///
+/// Returns the JWK SHA-256 thumbprint of the key which signed the proof, or
+/// `None` if no proof was sent.
+///
+/// # Errors
+///
+/// Returns an error if the proof is invalid, was not made for this request or
+/// was already used.
+pub(crate) async fn verify_proof(
+    clock: &dyn Clock,
+    repo: &mut BoxRepository,
+    headers: &HeaderMap,
+    method: &Method,
+    uri: &Url,
+) -> Result<Option<String>, DpopError> {
+    verify(clock, repo, headers, method, uri, None).await
+}
+
+/// Verify the `DPoP` proof sent to a protected resource along with an access
+/// token presented with the `DPoP` scheme, if any.
+///
+/// Returns the JWK SHA-256 thumbprint of the key which signed the proof, or
+/// `None` if the token was presented as a bearer token.
+///
+/// # Errors
+///
+/// Returns an error if the proof is missing or invalid, was not made for this
+/// request and access token or was already used.
+pub(crate) async fn verify_resource_proof(
+    clock: &dyn Clock,
+    repo: &mut BoxRepository,
+    headers: &HeaderMap,
+    method: &Method,
+    uri: &Url,
+    dpop_token: Option<&str>,
+) -> Result<Option<String>, DpopError> {
+    let Some(access_token) = dpop_token else {
+        return Ok(None);
+    };
+
+    let jkt = verify(clock, repo, headers, method, uri, Some(access_token))
+        .await?
+        .ok_or(DpopError::Required)?;
+
+    Ok(Some(jkt))
+}
+
+async fn verify(
+    clock: &dyn Clock,
+    repo: &mut BoxRepository,
+    headers: &HeaderMap,
+    method: &Method,
+    uri: &Url,
+    access_token: Option<&str>,
+) -> Result<Option<String>, DpopError> {
+    let mut proofs = headers.get_all(DPOP_HEADER).iter();
+    let Some(proof) = proofs.next() else {
+        return Ok(None);
+    };
+
+    if proofs.next().is_some() {
+        return Err(DpopError::InvalidHeader);
+    }
+
+    let proof = proof.to_str().map_err(|_| DpopError::InvalidHeader)?;
+    let jwt: Jwt<'_, HashMap<String, Value>> = Jwt::try_from(proof)?;
+
+    if jwt.header().typ() != Some("dpop+jwt") {
+        return Err(DpopError::InvalidType);
+    }
+
+    if !SUPPORTED_ALGORITHMS.contains(jwt.header().alg()) {
+        return Err(DpopError::UnsupportedAlgorithm);
+    }
+
+    let jwk = jwt.header().jwk().ok_or(DpopError::MissingKey)?;
+    let key = AsymmetricVerifyingKey::from_jwk_and_alg(jwk.params(), jwt.header().alg())
+        .map_err(|_| DpopError::InvalidSignature)?;
+    jwt.verify(&key).map_err(|_| DpopError::InvalidSignature)?;
+
+    let jkt = jwk.params().thumbprint_sha256();
+    let (_header, mut claims) = jwt.into_parts();
+
+    let now = clock.now();
+    let time_options = TimeOptions::new(now);
+    let iat = claims::IAT.extract_required_with_options(&mut claims, &time_options)?;
+    if now - *iat > PROOF_MAX_AGE {
+        return Err(DpopError::Expired);
+    }
+
+    let jti = claims::JTI.extract_required(&mut claims)?;
+    HTM.extract_required_with_options(&mut claims, method.as_str())?;
+
+    // The query and fragment parts are ignored when comparing the URIs
+    let htu = HTU.extract_required(&mut claims)?;
+    let mut htu = Url::parse(&htu).map_err(|_| DpopError::WrongEndpoint)?;
+    htu.set_query(None);
+    htu.set_fragment(None);
+    if &htu != uri {
+        return Err(DpopError::WrongEndpoint);
+    }
+
+    // Proofs sent to protected resources carry the hash of the access token
+    if let Some(access_token) = access_token {
+        let ath = ATH.extract_required(&mut claims)?;
+        let expected = Base64UrlUnpadded::encode_string(&Sha256::digest(access_token));
+        if ath != expected {
+            return Err(DpopError::WrongAccessToken);
+        }
+    }
+
+    // JWT IDs are scoped to the key which signed the proof
+    let first_use = repo
+        .oauth2_used_jwt_id()
+        .mark_used(clock, &jkt, &jti, *iat + PROOF_MAX_AGE)
+        .await?;
+    if !first_use {
+        return Err(DpopError::Replayed);
+    }
+
+    Ok(Some(jkt))
+}
+
+/// Check that the key used for a `DPoP` proof matches the one a newly started
+/// session is bound to, binding the session to it if it was not bound yet.
+///
+/// # Errors
+///
+/// Returns an error if the session is bound to a key and the proof is missing
+/// or uses another key.
+pub(crate) async fn bind_session(
+    repo: &mut BoxRepository,
+    session: Session,
+    dpop_jkt: Option<&str>,
+) -> Result<Session, DpopError> {
+    match (session.dpop_jkt.as_deref(), dpop_jkt) {
+        (None, None) => Ok(session),
+        (Some(bound), Some(jkt)) if bound == jkt => Ok(session),
+        (Some(_), None) => Err(DpopError::Required),
+        (Some(_), Some(_)) => Err(DpopError::KeyMismatch),
+        (None, Some(jkt)) => Ok(repo
+            .oauth2_session()
+            .set_dpop_jkt(session, jkt.to_owned())
+            .await?),
+    }
+}
+
+/// Check that the key used for a `DPoP` proof matches the one an existing
+/// session is bound to.
+///
+/// Sessions are only bound when they start, so a proof sent for a session
+/// issued as bearer is rejected instead of upgrading it.
+///
+/// # Errors
+///
+/// Returns an error if the session is bound to a key and the proof is missing
+/// or uses another key, or if a proof is sent for a session which is not bound.
+pub(crate) fn check_session(session: &Session, dpop_jkt: Option<&str>) -> Result<(), DpopError> {
+    match (session.dpop_jkt.as_deref(), dpop_jkt) {
+        (None, None) => Ok(()),
+        (Some(bound), Some(jkt)) if bound == jkt => Ok(()),
+        (Some(_), None) => Err(DpopError::Required),
+        (Some(_), Some(_)) => Err(DpopError::KeyMismatch),
+        (None, Some(_)) => Err(DpopError::Unbound),
+    }
+}
@@ -0,0 +1,590 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Support for OpenID Connect RP-Initiated Logout 1.0
+//!
+//! The relying party sends the user to the end session endpoint with a `GET`
+//! request, which shows a confirmation page. The user then confirms the logout
+//! by submitting the form on that page, which `POST`s the same parameters back
+//! to the endpoint.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Form, Query, State},
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use hyper::StatusCode;
+use mas_axum_utils::{
+    GenericError, InternalError, SessionInfoExt,
+    cookies::CookieJar,
+    csrf::{CsrfExt, ProtectedForm},
+};
+use mas_data_model::{BrowserSession, Client, SiteConfig};
+use mas_jose::{
+    claims::{self, ClaimError},
+    jwt::Jwt,
+};
+use mas_keystore::Keystore;
+use mas_router::UrlBuilder;
+use mas_storage::{
+    BoxClock, BoxRepository, BoxRng, Clock, RepositoryAccess,
+    oauth2::{OAuth2ClientRepository, OAuth2SessionFilter, OAuth2SessionRepository},
+    queue::{BackchannelLogoutJob, QueueJobRepositoryExt as _, SyncDevicesJob},
+    user::BrowserSessionRepository,
+};
+use mas_templates::{EndSessionContext, FrontchannelLogoutContext, TemplateContext, Templates};
+use oauth2_types::oidc::RpInitiatedLogoutRequest;
+use rand::RngCore;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    BoundActivityTracker, PreferredLanguage, impl_from_error_for_route,
+    views::logout::frontchannel_logout_uris,
+};
+
+#[derive(Debug, Error)]
+pub(crate) enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("invalid id_token_hint")]
+    InvalidIdTokenHint,
+
+    #[error("the id_token_hint was not issued to the given client")]
+    ClientIdMismatch,
+
+    #[error("could not find client")]
+    ClientNotFound,
+
+    #[error("a client_id or an id_token_hint is required to use a post_logout_redirect_uri")]
+    MissingClient,
+
+    #[error("the post_logout_redirect_uri is not registered for this client")]
+    UnknownPostLogoutRedirectUri,
+
+    #[error("the id_token_hint was issued to another user")]
+    UserMismatch,
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+impl_from_error_for_route!(mas_templates::TemplateError);
+impl_from_error_for_route!(mas_axum_utils::csrf::CsrfError);
+impl_from_error_for_route!(mas_data_model::MissingPairwiseSubjectSecretError);
+
+impl From<ClaimError> for RouteError {
+    fn from(_: ClaimError) -> Self {
+        Self::InvalidIdTokenHint
+    }
+}
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Internal(e) => InternalError::new(e).into_response(),
+            e @ (Self::InvalidIdTokenHint
+            | Self::ClientIdMismatch
+            | Self::ClientNotFound
+            | Self::MissingClient
+            | Self::UnknownPostLogoutRedirectUri
+            | Self::UserMismatch) => GenericError::new(StatusCode::BAD_REQUEST, e).into_response(),
+        }
+    }
+}
+
+/// A logout request, once its parameters were checked
+struct ValidatedRequest {
+    /// The client which sent the user here, if it could be identified
+    client: Option<Client>,
+
+    /// The subject of the `id_token_hint`, if one was given
+    hint_subject: Option<String>,
+}
+
+impl ValidatedRequest {
+    /// Check that the given browser session belongs to the user the
+    /// `id_token_hint` was issued for.
+    ///
+    /// The subject identifier may be pairwise, so it is compared to the one
+    /// the client knows the user by.
+    fn check_user(
+        &self,
+        site_config: &SiteConfig,
+        session: &BrowserSession,
+    ) -> Result<(), RouteError> {
+        let (Some(client), Some(hint_subject)) = (&self.client, &self.hint_subject) else {
+            return Ok(());
+        };
+
+        let subject =
+            client.subject_for(&session.user, site_config.pairwise_subject_secret.as_ref())?;
+        if &subject != hint_subject {
+            return Err(RouteError::UserMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+/// Check the parameters of a logout request, and figure out which client sent
+/// it
+async fn validate_request(
+    key_store: &Keystore,
+    url_builder: &UrlBuilder,
+    repo: &mut BoxRepository,
+    params: &RpInitiatedLogoutRequest,
+) -> Result<ValidatedRequest, RouteError> {
+    let mut client_id = params.client_id.clone();
+    let mut hint_subject = None;
+
+    if let Some(id_token_hint) = &params.id_token_hint {
+        let jwt: Jwt<'_, HashMap<String, Value>> =
+            Jwt::try_from(id_token_hint.as_str()).map_err(|_| RouteError::InvalidIdTokenHint)?;
+
+        jwt.verify_with_jwks(&key_store.public_jwks())
+            .map_err(|_| RouteError::InvalidIdTokenHint)?;
+
+        let (_header, mut claims) = jwt.into_parts();
+
+        // The ID token may have expired since it was issued, which is fine here,
+        // so we don't check its expiration
+        let issuer = url_builder.oidc_issuer();
+        claims::ISS.extract_required_with_options(&mut claims, issuer.as_str())?;
+        let subject = claims::SUB.extract_required(&mut claims)?;
+
+        // We only ever issue ID tokens with a single audience, the client
+        let audience = claims
+            .get("aud")
+            .and_then(Value::as_str)
+            .ok_or(RouteError::InvalidIdTokenHint)?;
+
+        if client_id.as_deref().is_some_and(|id| id != audience) {
+            return Err(RouteError::ClientIdMismatch);
+        }
+
+        client_id = Some(audience.to_owned());
+        hint_subject = Some(subject);
+    }
+
+    let client = if let Some(client_id) = client_id {
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await?
+            .ok_or(RouteError::ClientNotFound)?;
+        Some(client)
+    } else {
+        None
+    };
+
+    if let Some(post_logout_redirect_uri) = &params.post_logout_redirect_uri {
+        let client = client.as_ref().ok_or(RouteError::MissingClient)?;
+        if !client.has_post_logout_redirect_uri(post_logout_redirect_uri) {
+            return Err(RouteError::UnknownPostLogoutRedirectUri);
+        }
+    }
+
+    Ok(ValidatedRequest {
+        client,
+        hint_subject,
+    })
+}
+
+/// Where to send the user once they are logged out
+fn destination(url_builder: &UrlBuilder, params: RpInitiatedLogoutRequest) -> String {
+    let Some(mut post_logout_redirect_uri) = params.post_logout_redirect_uri else {
+        return url_builder.relative_url_for(&mas_router::Login::default());
+    };
+
+    if let Some(state) = &params.state {
+        post_logout_redirect_uri
+            .query_pairs_mut()
+            .append_pair("state", state);
+    }
+
+    post_logout_redirect_uri.into()
+}
+
+#[tracing::instrument(name = "handlers.oauth2.end_session.get", skip_all)]
+pub(crate) async fn get(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    PreferredLanguage(locale): PreferredLanguage,
+    State(templates): State<Templates>,
+    State(key_store): State<Keystore>,
+    State(url_builder): State<UrlBuilder>,
+    State(site_config): State<SiteConfig>,
+    mut repo: BoxRepository,
+    activity_tracker: BoundActivityTracker,
+    cookie_jar: CookieJar,
+    Query(params): Query<RpInitiatedLogoutRequest>,
+) -> Result<Response, RouteError> {
+    let request = validate_request(&key_store, &url_builder, &mut repo, &params).await?;
+
+    let (session_info, cookie_jar) = cookie_jar.session_info();
+    let maybe_session = session_info.load_active_session(&mut repo).await?;
+
+    // If the user is not logged in, there is nothing to confirm
+    let Some(session) = maybe_session else {
+        let destination = destination(&url_builder, params);
+        return Ok((cookie_jar, Redirect::to(&destination)).into_response());
+    };
+
+    activity_tracker
+        .record_browser_session(&clock, &session)
+        .await;
+
+    request.check_user(&site_config, &session)?;
+
+    let (csrf_token, cookie_jar) = cookie_jar.csrf_token(&clock, &mut rng);
+    let ctx = EndSessionContext::new(request.client, params)
+        .with_session(session)
+        .with_csrf(csrf_token.form_value())
+        .with_language(locale);
+
+    let content = templates.render_end_session(&ctx)?;
+
+    Ok((cookie_jar, Html(content)).into_response())
+}
+
+#[tracing::instrument(name = "handlers.oauth2.end_session.post", skip_all)]
+pub(crate) async fn post(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    PreferredLanguage(locale): PreferredLanguage,
+    State(templates): State<Templates>,
+    State(key_store): State<Keystore>,
+    State(url_builder): State<UrlBuilder>,
+    State(site_config): State<SiteConfig>,
+    mut repo: BoxRepository,
+    activity_tracker: BoundActivityTracker,
+    cookie_jar: CookieJar,
+    Form(form): Form<ProtectedForm<RpInitiatedLogoutRequest>>,
+) -> Result<Response, RouteError> {
+    let params = cookie_jar.verify_form(&clock, form)?;
+
+    let request = validate_request(&key_store, &url_builder, &mut repo, &params).await?;
+
+    let (session_info, cookie_jar) = cookie_jar.session_info();
+    let maybe_session = session_info.load_active_session(&mut repo).await?;
+
+    let mut logout_uris = Vec::new();
+    if let Some(session) = maybe_session {
+        request.check_user(&site_config, &session)?;
+
+        activity_tracker
+            .record_browser_session(&clock, &session)
+            .await;
+
+        // Let the clients know that the user logged out
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                &clock,
+                BackchannelLogoutJob::for_browser_session(&session, clock.now()),
+            )
+            .await?;
+
+        logout_uris = frontchannel_logout_uris(&url_builder, &mut repo, &session).await?;
+
+        // Only end the sessions of the client if it proved that it started the
+        // logout, by sending an ID token it got. Anyone can craft a link with a
+        // `client_id`, and ending the sessions deletes the devices of the user
+        if let Some(client) = &request.client
+            && request.hint_subject.is_some()
+        {
+            end_client_sessions(&mut rng, &clock, &mut repo, client, &session).await?;
+        }
+
+        repo.browser_session().finish(&clock, session).await?;
+    }
+
+    repo.save().await?;
+
+    // We always want to clear out the session cookie, even if the session was
+    // invalid
+    let cookie_jar = cookie_jar.update_session_info(&session_info.mark_session_ended());
+
+    let destination = destination(&url_builder, params);
+    if logout_uris.is_empty() {
+        return Ok((cookie_jar, Redirect::to(&destination)).into_response());
+    }
+
+    let ctx = FrontchannelLogoutContext::new(logout_uris, destination).with_language(locale);
+    let content = templates.render_frontchannel_logout(&ctx)?;
+
+    Ok((cookie_jar, Html(content)).into_response())
+}
+
+/// End the sessions of the client which were started from the given browser
+/// session.
+///
+/// This is only done when the client identified itself with an
+/// `id_token_hint`, as the other sessions of the user are left alone by a
+/// logout.
+async fn end_client_sessions<E>(
+    rng: &mut (dyn RngCore + Send),
+    clock: &dyn Clock,
+    repo: &mut impl RepositoryAccess<Error = E>,
+    client: &Client,
+    browser_session: &BrowserSession,
+) -> Result<(), E> {
+    let filter = OAuth2SessionFilter::new()
+        .for_browser_session(browser_session)
+        .for_client(client)
+        .active_only();
+
+    let affected = repo.oauth2_session().finish_bulk(clock, filter).await?;
+
+    // The sessions may have devices on the homeserver, so make sure they get
+    // deleted
+    if affected > 0 {
+        repo.queue_job()
+            .schedule_job(rng, clock, SyncDevicesJob::new(&browser_session.user))
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use hyper::{Request, StatusCode, header::LOCATION};
+    use mas_axum_utils::SessionInfoExt;
+    use mas_data_model::{BrowserSession, Client, Session};
+    use mas_iana::{jose::JsonWebSignatureAlg, oauth::OAuthClientAuthenticationMethod};
+    use mas_jose::{
+        constraints::Constrainable,
+        jwt::{JsonWebSignatureHeader, Jwt},
+    };
+    use mas_router::SimpleRoute;
+    use mas_storage::{
+        Clock, RepositoryAccess,
+        oauth2::{OAuth2ClientRepository, OAuth2SessionRepository, OAuth2StaticClientParams},
+        user::{BrowserSessionRepository, UserRepository},
+    };
+    use oauth2_types::scope::{OPENID, Scope};
+    use sqlx::PgPool;
+    use ulid::Ulid;
+
+    use crate::test_utils::{CookieHelper, RequestBuilderExt, ResponseExt, TestState, setup};
+
+    /// Provision a client with a post logout redirect URI, and a user logged
+    /// in on it
+    async fn logged_in_session(state: &TestState) -> (Client, BrowserSession, Session) {
+        let mut rng = state.rng();
+        let mut repo = state.repository().await.unwrap();
+
+        let client = repo
+            .oauth2_client()
+            .upsert_static(OAuth2StaticClientParams {
+                client_id: Ulid::from_datetime_with_source(state.clock.now().into(), &mut rng),
+                client_name: None,
+                client_auth_method: OAuthClientAuthenticationMethod::None,
+                encrypted_client_secret: None,
+                jwks: None,
+                jwks_uri: None,
+                redirect_uris: vec!["https://example.com/callback".parse().unwrap()],
+                post_logout_redirect_uris: vec!["https://example.com/logged-out".parse().unwrap()],
+                request_uris: Vec::new(),
+                backchannel_logout_uri: None,
+                frontchannel_logout_uri: None,
+                frontchannel_logout_session_required: false,
+                dpop_bound_access_tokens: false,
+                tls_client_auth_subject_dn: None,
+                tls_client_auth_san_dns: None,
+                require_pushed_authorization_requests: false,
+            })
+            .await
+            .unwrap();
+        let user = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let browser_session = repo
+            .browser_session()
+            .add(&mut rng, &state.clock, &user, None)
+            .await
+            .unwrap();
+        let session = repo
+            .oauth2_session()
+            .add_from_browser_session(
+                &mut rng,
+                &state.clock,
+                &client,
+                &browser_session,
+                Scope::from_iter([OPENID]),
+            )
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        (client, browser_session, session)
+    }
+
+    /// Sign an ID token for the given client and browser session
+    fn id_token(state: &TestState, client: &Client, browser_session: &BrowserSession) -> String {
+        let alg = JsonWebSignatureAlg::Rs256;
+        let key = state.key_store.signing_key_for_algorithm(&alg).unwrap();
+        let signer = key.params().signing_key_for_alg(&alg).unwrap();
+        let header = JsonWebSignatureHeader::new(alg).with_kid(key.kid().unwrap());
+
+        let claims: HashMap<String, serde_json::Value> =
+            serde_json::from_value(serde_json::json!({
+                "iss": state.url_builder.oidc_issuer(),
+                "sub": browser_session.user.sub,
+                "aud": client.client_id,
+                "sid": browser_session.id.to_string(),
+            }))
+            .unwrap();
+
+        Jwt::sign(header, claims, &signer).unwrap().into_string()
+    }
+
+    /// Render the confirmation page and get the CSRF token out of it
+    async fn csrf_token(state: &TestState, cookies: &CookieHelper, query: &str) -> String {
+        let request = Request::get(format!("{}?{query}", mas_router::OidcEndSession::PATH)).empty();
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+
+        response
+            .body()
+            .split("name=\"csrf\" value=\"")
+            .nth(1)
+            .unwrap()
+            .split('\"')
+            .next()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_end_session_post(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let (client, browser_session, session) = logged_in_session(&state).await;
+        let id_token_hint = id_token(&state, &client, &browser_session);
+
+        let cookies = CookieHelper::new();
+        cookies.import(state.cookie_jar().set_session(&browser_session));
+
+        let params = serde_json::json!({
+            "id_token_hint": id_token_hint,
+            "post_logout_redirect_uri": "https://example.com/logged-out",
+            "state": "some-state",
+        });
+        let query = serde_urlencoded::to_string(&params).unwrap();
+        let csrf = csrf_token(&state, &cookies, &query).await;
+
+        let mut form = params;
+        form["csrf"] = csrf.into();
+        let request = Request::post(mas_router::OidcEndSession::PATH).form(form);
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+
+        // The user is sent back to the client, with the state it gave
+        response.assert_status(StatusCode::SEE_OTHER);
+        response.assert_header_value(LOCATION, "https://example.com/logged-out?state=some-state");
+
+        // Both the browser session and the session of the client ended
+        let mut repo = state.repository().await.unwrap();
+        let browser_session = repo
+            .browser_session()
+            .lookup(browser_session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(browser_session.finished_at.is_some());
+        let session = repo
+            .oauth2_session()
+            .lookup(session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(session.is_finished());
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_end_session_post_without_id_token_hint(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let (client, browser_session, session) = logged_in_session(&state).await;
+
+        let cookies = CookieHelper::new();
+        cookies.import(state.cookie_jar().set_session(&browser_session));
+
+        let params = serde_json::json!({
+            "client_id": client.client_id,
+            "post_logout_redirect_uri": "https://example.com/logged-out",
+        });
+        let query = serde_urlencoded::to_string(&params).unwrap();
+        let csrf = csrf_token(&state, &cookies, &query).await;
+
+        let mut form = params;
+        form["csrf"] = csrf.into();
+        let request = Request::post(mas_router::OidcEndSession::PATH).form(form);
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+
+        response.assert_status(StatusCode::SEE_OTHER);
+        response.assert_header_value(LOCATION, "https://example.com/logged-out");
+
+        // The browser session ended, but as the client didn't prove that it
+        // started the logout, its session is left alone
+        let mut repo = state.repository().await.unwrap();
+        let browser_session = repo
+            .browser_session()
+            .lookup(browser_session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(browser_session.finished_at.is_some());
+        let session = repo
+            .oauth2_session()
+            .lookup(session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!session.is_finished());
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_end_session_validation(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // An invalid id_token_hint is rejected
+        let request = Request::get(format!(
+            "{}?id_token_hint=not-a-jwt",
+            mas_router::OidcEndSession::PATH
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // A post_logout_redirect_uri can't be used without identifying the
+        // client
+        let request = Request::get(format!(
+            "{}?post_logout_redirect_uri=https%3A%2F%2Fexample.com%2F",
+            mas_router::OidcEndSession::PATH
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // Without a session, the user is sent straight to the login page
+        let request = Request::get(mas_router::OidcEndSession::PATH).empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+    }
+}
@@ -7,30 +7,47 @@
 use std::sync::{Arc, LazyLock};
 
 use axum::{Json, extract::State, http::HeaderValue, response::IntoResponse};
-use hyper::{HeaderMap, StatusCode};
+use axum_extra::typed_header::TypedHeader;
+use chrono::{DateTime, Utc};
+use headers::ContentType;
+use hyper::{HeaderMap, StatusCode, header::ACCEPT};
 use mas_axum_utils::{
     client_authorization::{ClientAuthorization, CredentialsVerificationError},
     record_error,
 };
-use mas_data_model::{Device, TokenFormatError, TokenType};
-use mas_iana::oauth::{OAuthClientAuthenticationMethod, OAuthTokenTypeHint};
-use mas_keystore::Encrypter;
+use mas_data_model::{Client, Device, Session, SiteConfig, TokenFormatError, TokenType};
+use mas_iana::{
+    jose::JsonWebSignatureAlg,
+    oauth::{OAuthClientAuthenticationMethod, OAuthTokenTypeHint},
+};
+use mas_jose::{
+    constraints::Constrainable,
+    jwe::JsonWebEncryptionHeader,
+    jwt::{JsonWebSignatureHeader, Jwt},
+};
+use mas_keystore::{Encrypter, Keystore};
 use mas_matrix::HomeserverConnection;
+use mas_router::UrlBuilder;
 use mas_storage::{
-    BoxClock, BoxRepository, Clock,
+    BoxClock, BoxRepository, BoxRng, Clock,
     compat::{CompatAccessTokenRepository, CompatRefreshTokenRepository, CompatSessionRepository},
     oauth2::{OAuth2AccessTokenRepository, OAuth2RefreshTokenRepository, OAuth2SessionRepository},
-    user::UserRepository,
+    user::{BrowserSessionRepository, UserRepository},
 };
+use mime::Mime;
 use oauth2_types::{
     errors::{ClientError, ClientErrorCode},
-    requests::{IntrospectionRequest, IntrospectionResponse},
+    requests::{Confirmation, IntrospectionRequest, IntrospectionResponse},
     scope::ScopeToken,
 };
 use opentelemetry::{Key, KeyValue, metrics::Counter};
+use serde::Serialize;
+use serde_with::{TimestampSeconds, serde_as};
 use thiserror::Error;
 use ulid::Ulid;
+use url::Url;
 
+use super::response_encryption::{ResponseEncryptionError, encrypt_for_client};
 use crate::{ActivityTracker, METER, impl_from_error_for_route};
 
 static INTROSPECTION_COUNTER: LazyLock<Counter<u64>> = LazyLock::new(|| {
@@ -44,6 +61,26 @@ static INTROSPECTION_COUNTER: LazyLock<Counter<u64>> = LazyLock::new(|| {
 const KIND: Key = Key::from_static_str("kind");
 const ACTIVE: Key = Key::from_static_str("active");
 
+/// The `typ` header of JWT introspection responses, as per RFC 9701
+const TOKEN_INTROSPECTION_JWT_TYPE: &str = "token-introspection+jwt";
+
+static TOKEN_INTROSPECTION_JWT_MIME: LazyLock<Mime> = LazyLock::new(|| {
+    "application/token-introspection+jwt"
+        .parse()
+        .expect("valid media type")
+});
+
+/// The claims of a JWT introspection response, as per RFC 9701
+#[serde_as]
+#[derive(Serialize)]
+struct IntrospectionJwtClaims {
+    iss: String,
+    aud: String,
+    #[serde_as(as = "TimestampSeconds<i64>")]
+    iat: DateTime<Utc>,
+    token_introspection: IntrospectionResponse,
+}
+
 #[derive(Debug, Error)]
 pub enum RouteError {
     /// An internal error occurred.
@@ -111,6 +148,38 @@ pub enum RouteError {
 
     #[error("bearer token presented is invalid")]
     InvalidBearerToken,
+
+    #[error("no suitable key found for signing")]
+    InvalidSigningKey,
+
+    /// The token is not intended for the client or resource server
+    /// introspecting it.
+    #[error("token is not intended for the introspecting party")]
+    AudienceMismatch,
+}
+
+impl_from_error_for_route!(mas_data_model::MissingPairwiseSubjectSecretError);
+impl_from_error_for_route!(mas_keystore::WrongAlgorithmError);
+impl_from_error_for_route!(mas_jose::jwt::JwtSignatureError);
+impl_from_error_for_route!(ResponseEncryptionError);
+
+impl RouteError {
+    /// Returns true if the error means the token should be reported as
+    /// inactive, rather than failing the request
+    fn is_inactive(&self) -> bool {
+        matches!(
+            self,
+            Self::UnknownToken(_)
+                | Self::UnexpectedTokenType
+                | Self::InvalidToken(_)
+                | Self::InvalidUser(_)
+                | Self::InvalidCompatSession(_)
+                | Self::InvalidOAuthSession(_)
+                | Self::InvalidTokenFormat(_)
+                | Self::CantEncodeDeviceID(_)
+                | Self::AudienceMismatch
+        )
+    }
 }
 
 impl IntoResponse for RouteError {
@@ -122,6 +191,7 @@ impl IntoResponse for RouteError {
                 | Self::CantLoadOAuthSession(_)
                 | Self::CantLoadUser(_)
                 | Self::FailedToVerifyToken(_)
+                | Self::InvalidSigningKey
         );
 
         let response = match self {
@@ -129,7 +199,8 @@ impl IntoResponse for RouteError {
             | Self::CantLoadCompatSession(_)
             | Self::CantLoadOAuthSession(_)
             | Self::CantLoadUser(_)
-            | Self::FailedToVerifyToken(_)) => (
+            | Self::FailedToVerifyToken(_)
+            | Self::InvalidSigningKey) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(
                     ClientError::from(ClientErrorCode::ServerError).with_description(e.to_string()),
@@ -165,7 +236,8 @@ impl IntoResponse for RouteError {
             | Self::InvalidCompatSession(_)
             | Self::InvalidOAuthSession(_)
             | Self::InvalidTokenFormat(_)
-            | Self::CantEncodeDeviceID(_) => {
+            | Self::CantEncodeDeviceID(_)
+            | Self::AudienceMismatch => {
                 INTROSPECTION_COUNTER.add(1, &[KeyValue::new(ACTIVE.clone(), false)]);
 
                 Json(INACTIVE).into_response()
@@ -205,6 +277,11 @@ const INACTIVE: IntrospectionResponse = IntrospectionResponse {
     iss: None,
     jti: None,
     device_id: None,
+    cnf: None,
+    act: None,
+    authorization_details: None,
+    acr: None,
+    auth_time: None,
 };
 
 const API_SCOPE: ScopeToken = ScopeToken::from_static("urn:matrix:org.matrix.msc2967.client:api:*");
@@ -217,16 +294,20 @@ const SYNAPSE_ADMIN_SCOPE: ScopeToken = ScopeToken::from_static("urn:synapse:adm
 )]
 #[allow(clippy::too_many_lines)]
 pub(crate) async fn post(
+    mut rng: BoxRng,
     clock: BoxClock,
     State(http_client): State<reqwest::Client>,
     mut repo: BoxRepository,
     activity_tracker: ActivityTracker,
     State(encrypter): State<Encrypter>,
+    State(key_store): State<Keystore>,
+    State(url_builder): State<UrlBuilder>,
     State(homeserver): State<Arc<dyn HomeserverConnection>>,
+    State(site_config): State<SiteConfig>,
     headers: HeaderMap,
     ClientAuthorization { credentials, form }: ClientAuthorization<IntrospectionRequest>,
 ) -> Result<impl IntoResponse, RouteError> {
-    if let Some(token) = credentials.bearer_token() {
+    let introspecting_client = if let Some(token) = credentials.bearer_token() {
         // If the client presented a bearer token, we check with the homeserver
         // configuration if it is allowed to use the introspection endpoint
         if !homeserver
@@ -236,6 +317,8 @@ pub(crate) async fn post(
         {
             return Err(RouteError::InvalidBearerToken);
         }
+
+        None
     } else {
         // Otherwise, it presented regular client credentials, so we verify them
         let client = credentials
@@ -254,20 +337,14 @@ pub(crate) async fn post(
         credentials
             .verify(&http_client, &encrypter, method, &client)
             .await?;
-    }
+
+        Some(client)
+    };
 
     let Some(form) = form else {
         return Err(RouteError::BadRequest);
     };
 
-    let token = &form.token;
-    let token_type = TokenType::check(token)?;
-    if let Some(hint) = form.token_type_hint {
-        if token_type != hint {
-            return Err(RouteError::UnexpectedTokenType);
-        }
-    }
-
     // Not all device IDs can be encoded as scope. On OAuth 2.0 sessions, we
     // don't have this problem, as the device ID *is* already encoded as a scope.
     // But on compatibility sessions, it's possible to have device IDs with
@@ -278,9 +355,197 @@ pub(crate) async fn post(
     let supports_explicit_device_id =
         headers.get("X-MAS-Supports-Device-Id") == Some(&HeaderValue::from_static("1"));
 
+    let reply = introspect(
+        &clock,
+        &mut repo,
+        &activity_tracker,
+        &site_config,
+        introspecting_client.as_ref(),
+        &form,
+        supports_explicit_device_id,
+    )
+    .await;
+
+    // Resource servers authenticating as a client can ask for the response as
+    // a signed JWT, as per RFC 9701
+    let Some(client) = introspecting_client.filter(|_| accepts_jwt_response(&headers)) else {
+        let reply = reply?;
+        repo.save().await?;
+        return Ok(Json(reply).into_response());
+    };
+
+    // In that case, inactive tokens also get a signed response
+    let reply = match reply {
+        Ok(reply) => {
+            repo.save().await?;
+            reply
+        }
+        Err(e) if e.is_inactive() => {
+            INTROSPECTION_COUNTER.add(1, &[KeyValue::new(ACTIVE, false)]);
+            INACTIVE
+        }
+        Err(e) => return Err(e),
+    };
+
+    let alg = client
+        .introspection_signed_response_alg
+        .clone()
+        .unwrap_or(JsonWebSignatureAlg::Rs256);
+    let key = key_store
+        .signing_key_for_algorithm(&alg)
+        .ok_or(RouteError::InvalidSigningKey)?;
+
+    let signer = key.params().signing_key_for_alg(&alg)?;
+    let header = JsonWebSignatureHeader::new(alg)
+        .with_typ(TOKEN_INTROSPECTION_JWT_TYPE.to_owned())
+        .with_kid(key.kid().ok_or(RouteError::InvalidSigningKey)?);
+
+    let claims = IntrospectionJwtClaims {
+        iss: url_builder.oidc_issuer().to_string(),
+        aud: client.client_id.clone(),
+        iat: clock.now(),
+        token_introspection: reply,
+    };
+
+    let jwt = Jwt::sign_with_rng(&mut rng, header, claims, &signer)?.into_string();
+
+    // The signed response is then encrypted if the client registered an
+    // encryption algorithm for it, making it a nested JWT
+    let jwt = if let Some((alg, enc)) = client.introspection_encrypted_response() {
+        let header = JsonWebEncryptionHeader::new(alg.clone(), enc.clone())
+            .with_typ(TOKEN_INTROSPECTION_JWT_TYPE.to_owned())
+            .with_cty("JWT".to_owned());
+        encrypt_for_client(
+            &mut rng,
+            &http_client,
+            &encrypter,
+            &client,
+            header,
+            jwt.as_bytes(),
+        )
+        .await?
+    } else {
+        jwt
+    };
+
+    let content_type = ContentType::from(TOKEN_INTROSPECTION_JWT_MIME.clone());
+    Ok((TypedHeader(content_type), jwt).into_response())
+}
+
+/// Whether the `Accept` header of the request asks for a JWT introspection
+/// response
+fn accepts_jwt_response(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media_range| media_range.split(';').next())
+        .any(|media_type| {
+            media_type
+                .trim()
+                .eq_ignore_ascii_case(TOKEN_INTROSPECTION_JWT_MIME.essence_str())
+        })
+}
+
+/// Check that the tokens of a session are intended for the party introspecting
+/// them.
+///
+/// Tokens restricted to an audience are only intended for the clients and
+/// resource servers in it, and never for the homeserver. Resource servers only
+/// see the tokens restricted to them.
+fn check_audience(
+    session: &Session,
+    introspecting_client: Option<&Client>,
+    resource: Option<&Url>,
+) -> Result<(), RouteError> {
+    let is_audience = |target: &str| session.audience.iter().any(|audience| audience == target);
+
+    let intended = if session.audience.is_empty() {
+        resource.is_none()
+    } else {
+        resource.is_some_and(|resource| is_audience(resource.as_str()))
+            || introspecting_client.is_some_and(|client| is_audience(&client.client_id))
+    };
+
+    if intended {
+        Ok(())
+    } else {
+        Err(RouteError::AudienceMismatch)
+    }
+}
+
+/// Load the authentication context of the browser session an OAuth 2.0 session
+/// was started from, as its ACR value and authentication time
+async fn authentication_context(
+    repo: &mut BoxRepository,
+    site_config: &SiteConfig,
+    session: &Session,
+) -> Result<(Option<String>, Option<DateTime<Utc>>), RouteError> {
+    let Some(user_session_id) = session.user_session_id else {
+        return Ok((None, None));
+    };
+
+    let Some(browser_session) = repo.browser_session().lookup(user_session_id).await? else {
+        return Ok((None, None));
+    };
+
+    let Some(authentication) = repo
+        .browser_session()
+        .get_last_authentication(&browser_session)
+        .await?
+    else {
+        return Ok((None, None));
+    };
+
+    let acr = site_config
+        .acr_for(&authentication)
+        .map(|level| level.value.clone());
+
+    Ok((acr, Some(authentication.created_at)))
+}
+
+/// Look up the token being introspected and build the introspection response
+/// for it
+#[allow(clippy::too_many_lines)]
+async fn introspect(
+    clock: &dyn Clock,
+    repo: &mut BoxRepository,
+    activity_tracker: &ActivityTracker,
+    site_config: &SiteConfig,
+    introspecting_client: Option<&Client>,
+    form: &IntrospectionRequest,
+    supports_explicit_device_id: bool,
+) -> Result<IntrospectionResponse, RouteError> {
+    let token = &form.token;
+    let token_type = TokenType::check(token)?;
+    if let Some(hint) = &form.token_type_hint
+        && token_type != *hint
+    {
+        return Err(RouteError::UnexpectedTokenType);
+    }
+
     // XXX: we should get the IP from the client introspecting the token
     let ip = None;
 
+    // Resource servers can only introspect tokens intended for them, which
+    // compatibility tokens never are
+    let resource = introspecting_client.and_then(|client| {
+        site_config
+            .resource_servers
+            .iter()
+            .find(|server| server.client_id == client.id)
+            .map(|server| &server.resource)
+    });
+    if resource.is_some()
+        && matches!(
+            token_type,
+            TokenType::CompatAccessToken | TokenType::CompatRefreshToken
+        )
+    {
+        return Err(RouteError::AudienceMismatch);
+    }
+
     let reply = match token_type {
         TokenType::AccessToken => {
             let mut access_token = repo
@@ -303,11 +568,13 @@ pub(crate) async fn post(
                 return Err(RouteError::InvalidOAuthSession(session.id));
             }
 
+            check_audience(&session, introspecting_client, resource)?;
+
             // If this is the first time we're using this token, mark it as used
             if !access_token.is_used() {
                 access_token = repo
                     .oauth2_access_token()
-                    .mark_used(&clock, access_token)
+                    .mark_used(clock, access_token)
                     .await?;
             }
 
@@ -324,15 +591,39 @@ pub(crate) async fn post(
                     return Err(RouteError::InvalidUser(user.id));
                 }
 
-                (Some(user.sub), Some(user.username))
+                // Clients introspecting their own tokens get the subject
+                // identifier they know the user by, which may be pairwise.
+                // Other resource servers, like the homeserver, get the
+                // public one.
+                let sub = match &introspecting_client {
+                    Some(client) if client.id == session.client_id => {
+                        client.subject_for(&user, site_config.pairwise_subject_secret.as_ref())?
+                    }
+                    _ => user.sub,
+                };
+
+                (Some(sub), Some(user.username))
             } else {
                 (None, None)
             };
 
             activity_tracker
-                .record_oauth2_session(&clock, &session, ip)
+                .record_oauth2_session(clock, &session, ip)
                 .await;
 
+            let (acr, auth_time) = authentication_context(repo, site_config, &session).await?;
+
+            // The token may be bound to a DPoP key and/or a client certificate
+            let cnf = (session.dpop_jkt.is_some() || session.x5t_s256.is_some()).then_some(
+                Confirmation {
+                    jkt: session.dpop_jkt,
+                    x5t_s256: session.x5t_s256,
+                },
+            );
+
+            // Tokens obtained through a token exchange may be restricted to an audience
+            let aud = (!session.audience.is_empty()).then_some(session.audience);
+
             INTROSPECTION_COUNTER.add(
                 1,
                 &[
@@ -354,10 +645,15 @@ pub(crate) async fn post(
                 iat: Some(access_token.created_at),
                 nbf: Some(access_token.created_at),
                 sub,
-                aud: None,
+                aud,
                 iss: None,
                 jti: Some(access_token.jti()),
                 device_id: None,
+                cnf,
+                act: session.act,
+                authorization_details: session.authorization_details,
+                acr,
+                auth_time,
             }
         }
 
@@ -382,6 +678,8 @@ pub(crate) async fn post(
                 return Err(RouteError::InvalidOAuthSession(session.id));
             }
 
+            check_audience(&session, introspecting_client, resource)?;
+
             // The session might not have a user on it (for Client Credentials grants for
             // example), so we're optionally fetching the user
             let (sub, username) = if let Some(user_id) = session.user_id {
@@ -395,15 +693,28 @@ pub(crate) async fn post(
                     return Err(RouteError::InvalidUser(user.id));
                 }
 
-                (Some(user.sub), Some(user.username))
+                // Clients introspecting their own tokens get the subject
+                // identifier they know the user by, which may be pairwise.
+                // Other resource servers, like the homeserver, get the
+                // public one.
+                let sub = match &introspecting_client {
+                    Some(client) if client.id == session.client_id => {
+                        client.subject_for(&user, site_config.pairwise_subject_secret.as_ref())?
+                    }
+                    _ => user.sub,
+                };
+
+                (Some(sub), Some(user.username))
             } else {
                 (None, None)
             };
 
             activity_tracker
-                .record_oauth2_session(&clock, &session, ip)
+                .record_oauth2_session(clock, &session, ip)
                 .await;
 
+            let (acr, auth_time) = authentication_context(repo, site_config, &session).await?;
+
             INTROSPECTION_COUNTER.add(
                 1,
                 &[
@@ -427,6 +738,11 @@ pub(crate) async fn post(
                 iss: None,
                 jti: Some(refresh_token.jti()),
                 device_id: None,
+                cnf: None,
+                act: None,
+                authorization_details: session.authorization_details,
+                acr,
+                auth_time,
             }
         }
 
@@ -483,7 +799,7 @@ pub(crate) async fn post(
                 .collect();
 
             activity_tracker
-                .record_compat_session(&clock, &session, ip)
+                .record_compat_session(clock, &session, ip)
                 .await;
 
             INTROSPECTION_COUNTER.add(
@@ -511,6 +827,11 @@ pub(crate) async fn post(
                 iss: None,
                 jti: None,
                 device_id: session.device.map(Device::into),
+                cnf: None,
+                act: None,
+                authorization_details: None,
+                acr: None,
+                auth_time: None,
             }
         }
 
@@ -567,7 +888,7 @@ pub(crate) async fn post(
                 .collect();
 
             activity_tracker
-                .record_compat_session(&clock, &session, ip)
+                .record_compat_session(clock, &session, ip)
                 .await;
 
             INTROSPECTION_COUNTER.add(
@@ -593,36 +914,49 @@ pub(crate) async fn post(
                 iss: None,
                 jti: None,
                 device_id: session.device.map(Device::into),
+                cnf: None,
+                act: None,
+                authorization_details: None,
+                acr: None,
+                auth_time: None,
             }
         }
     };
 
-    repo.save().await?;
-
-    Ok(Json(reply))
+    Ok(reply)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use chrono::Duration;
-    use hyper::{Request, StatusCode};
-    use mas_data_model::{AccessToken, RefreshToken};
+    use hyper::{
+        Request, StatusCode,
+        header::{ACCEPT, CONTENT_TYPE},
+    };
+    use mas_data_model::{AccessToken, RefreshToken, ResourceServer};
     use mas_iana::oauth::OAuthTokenTypeHint;
+    use mas_jose::{jwe::Jwe, jwt::Jwt};
     use mas_matrix::{HomeserverConnection, MockHomeserverConnection, ProvisionRequest};
-    use mas_router::{OAuth2Introspection, OAuth2RegistrationEndpoint, SimpleRoute};
+    use mas_router::{
+        OAuth2Introspection, OAuth2RegistrationEndpoint, OAuth2TokenEndpoint, SimpleRoute,
+    };
     use mas_storage::Clock;
     use oauth2_types::{
         errors::{ClientError, ClientErrorCode},
         registration::ClientRegistrationResponse,
-        requests::IntrospectionResponse,
+        requests::{AccessTokenResponse, IntrospectionResponse},
         scope::{OPENID, Scope},
     };
     use serde_json::json;
+    use sha2::{Digest as _, Sha256};
     use sqlx::PgPool;
+    use url::Url;
     use zeroize::Zeroizing;
 
     use crate::{
-        oauth2::generate_token_pair,
+        oauth2::{GeneratedAccessToken, generate_token_pair},
         test_utils::{RequestBuilderExt, ResponseExt, TestState, setup},
     };
 
@@ -702,6 +1036,7 @@ mod tests {
                 &state.clock,
                 &mut repo,
                 &session,
+                GeneratedAccessToken::opaque(&mut state.rng(), &state.clock),
                 Duration::microseconds(5 * 60 * 1000 * 1000),
             )
             .await
@@ -1040,4 +1375,302 @@ mod tests {
         let response: ClientError = response.json();
         assert_eq!(response.error, ClientErrorCode::AccessDenied);
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_introspect_jwt_response(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Encrypting introspection responses with an asymmetric algorithm needs the
+        // client keys
+        let request = Request::post(OAuth2RegistrationEndpoint::PATH).json(json!({
+            "client_uri": "https://introspecting.com/",
+            "grant_types": [],
+            "token_endpoint_auth_method": "client_secret_basic",
+            "introspection_encrypted_response_alg": "RSA-OAEP-256",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidClientMetadata);
+
+        // Provision a client which will be used to do introspection requests
+        let request = Request::post(OAuth2RegistrationEndpoint::PATH).json(json!({
+            "client_uri": "https://introspecting.com/",
+            "grant_types": [],
+            "token_endpoint_auth_method": "client_secret_basic",
+            "introspection_signed_response_alg": "RS256",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let client: ClientRegistrationResponse = response.json();
+        let introspecting_client_id = client.client_id;
+        let introspecting_client_secret = client.client_secret.unwrap();
+
+        // Provision a client which will be used to generate tokens
+        let request = Request::post(OAuth2RegistrationEndpoint::PATH).json(json!({
+            "client_uri": "https://client.com/",
+            "redirect_uris": ["https://client.com/"],
+            "response_types": ["code"],
+            "grant_types": ["authorization_code", "refresh_token"],
+            "token_endpoint_auth_method": "none",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let ClientRegistrationResponse { client_id, .. } = response.json();
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let browser_session = repo
+            .browser_session()
+            .add(&mut state.rng(), &state.clock, &user, None)
+            .await
+            .unwrap();
+
+        let session = repo
+            .oauth2_session()
+            .add_from_browser_session(
+                &mut state.rng(),
+                &state.clock,
+                &client,
+                &browser_session,
+                Scope::from_iter([OPENID]),
+            )
+            .await
+            .unwrap();
+
+        let (AccessToken { access_token, .. }, _) = generate_token_pair(
+            &mut state.rng(),
+            &state.clock,
+            &mut repo,
+            &session,
+            GeneratedAccessToken::opaque(&mut state.rng(), &state.clock),
+            Duration::microseconds(5 * 60 * 1000 * 1000),
+        )
+        .await
+        .unwrap();
+
+        repo.save().await.unwrap();
+
+        // Ask for a JWT introspection response
+        let request = Request::post(OAuth2Introspection::PATH)
+            .header(ACCEPT, "application/token-introspection+jwt")
+            .basic_auth(&introspecting_client_id, &introspecting_client_secret)
+            .form(json!({ "token": access_token }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        response.assert_header_value(CONTENT_TYPE, "application/token-introspection+jwt");
+
+        let jwt: Jwt<'_, HashMap<String, serde_json::Value>> =
+            Jwt::try_from(response.body().as_str()).unwrap();
+        jwt.verify_with_jwks(&state.key_store.public_jwks())
+            .unwrap();
+        assert_eq!(jwt.header().typ(), Some("token-introspection+jwt"));
+        let claims = jwt.payload();
+        assert_eq!(claims["aud"], introspecting_client_id);
+        assert_eq!(claims["token_introspection"]["active"], true);
+        assert_eq!(claims["token_introspection"]["username"], "alice");
+        assert_eq!(claims["token_introspection"]["client_id"], client_id);
+
+        // Inactive tokens also get a signed response
+        let request = Request::post(OAuth2Introspection::PATH)
+            .header(ACCEPT, "application/token-introspection+jwt")
+            .basic_auth(&introspecting_client_id, &introspecting_client_secret)
+            .form(json!({ "token": "mat_unknown" }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        response.assert_header_value(CONTENT_TYPE, "application/token-introspection+jwt");
+
+        let jwt: Jwt<'_, HashMap<String, serde_json::Value>> =
+            Jwt::try_from(response.body().as_str()).unwrap();
+        assert_eq!(
+            jwt.payload()["token_introspection"],
+            json!({ "active": false })
+        );
+
+        // Without the Accept header, the response is plain JSON
+        let request = Request::post(OAuth2Introspection::PATH)
+            .basic_auth(&introspecting_client_id, &introspecting_client_secret)
+            .form(json!({ "token": access_token }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: IntrospectionResponse = response.json();
+        assert!(response.active);
+
+        // Provision a client which gets encrypted introspection responses, using a key
+        // derived from its secret
+        let request = Request::post(OAuth2RegistrationEndpoint::PATH).json(json!({
+            "client_uri": "https://introspecting.com/",
+            "grant_types": [],
+            "token_endpoint_auth_method": "client_secret_basic",
+            "introspection_encrypted_response_alg": "A256KW",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let client: ClientRegistrationResponse = response.json();
+        let introspecting_client_id = client.client_id;
+        let introspecting_client_secret = client.client_secret.unwrap();
+
+        let request = Request::post(OAuth2Introspection::PATH)
+            .header(ACCEPT, "application/token-introspection+jwt")
+            .basic_auth(&introspecting_client_id, &introspecting_client_secret)
+            .form(json!({ "token": access_token }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        response.assert_header_value(CONTENT_TYPE, "application/token-introspection+jwt");
+
+        // The response is the signed JWT, encrypted
+        let jwe = Jwe::try_from(response.body().as_str()).unwrap();
+        assert_eq!(jwe.header().typ(), Some("token-introspection+jwt"));
+        assert_eq!(jwe.header().cty(), Some("JWT"));
+        let jwt = jwe
+            .decrypt_with_shared_secret(&Sha256::digest(&introspecting_client_secret))
+            .unwrap();
+
+        let jwt: Jwt<'_, HashMap<String, serde_json::Value>> =
+            Jwt::try_from(std::str::from_utf8(&jwt).unwrap()).unwrap();
+        jwt.verify_with_jwks(&state.key_store.public_jwks())
+            .unwrap();
+        assert_eq!(jwt.payload()["aud"], introspecting_client_id);
+        assert_eq!(jwt.payload()["token_introspection"]["active"], true);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_introspect_resource_server(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision two resource servers, which introspect tokens
+        let mut resource_servers = Vec::new();
+        for client_uri in ["https://api.example.com/", "https://files.example.com/"] {
+            let request = Request::post(OAuth2RegistrationEndpoint::PATH).json(json!({
+                "client_uri": client_uri,
+                "grant_types": [],
+                "token_endpoint_auth_method": "client_secret_basic",
+            }));
+            let response = state.request(request).await;
+            response.assert_status(StatusCode::CREATED);
+            let client: ClientRegistrationResponse = response.json();
+            resource_servers.push((
+                client_uri.parse::<Url>().unwrap(),
+                client.client_id,
+                client.client_secret.unwrap(),
+            ));
+        }
+
+        state.site_config.resource_servers = resource_servers
+            .iter()
+            .map(|(resource, client_id, _)| ResourceServer {
+                resource: resource.clone(),
+                client_id: client_id.parse().unwrap(),
+            })
+            .collect();
+
+        let (api, api_client_id, api_client_secret) = &resource_servers[0];
+        let (_, files_client_id, files_client_secret) = &resource_servers[1];
+
+        // Provision a client which will be used to generate tokens
+        let request = Request::post(OAuth2RegistrationEndpoint::PATH).json(json!({
+            "client_uri": "https://client.com/",
+            "grant_types": ["client_credentials"],
+            "token_endpoint_auth_method": "client_secret_post",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let client: ClientRegistrationResponse = response.json();
+        let client_id = client.client_id;
+        let client_secret = client.client_secret.unwrap();
+
+        // Unknown resource servers are rejected
+        let request = Request::post(OAuth2TokenEndpoint::PATH).form(json!({
+            "grant_type": "client_credentials",
+            "client_id": client_id,
+            "client_secret": client_secret,
+            "resource": "https://unknown.example.com/",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidTarget);
+
+        // Get a token for the first resource server
+        let request = Request::post(OAuth2TokenEndpoint::PATH).form(json!({
+            "grant_type": "client_credentials",
+            "client_id": client_id,
+            "client_secret": client_secret,
+            "resource": api.as_str(),
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let AccessTokenResponse { access_token, .. } = response.json();
+
+        // The resource server it is intended for sees it as active
+        let request = Request::post(OAuth2Introspection::PATH)
+            .basic_auth(api_client_id, api_client_secret)
+            .form(json!({ "token": access_token }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: IntrospectionResponse = response.json();
+        assert!(response.active);
+        assert_eq!(response.aud, Some(vec![api.to_string()]));
+
+        // But not the other one
+        let request = Request::post(OAuth2Introspection::PATH)
+            .basic_auth(files_client_id, files_client_secret)
+            .form(json!({ "token": access_token }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: IntrospectionResponse = response.json();
+        assert!(!response.active);
+
+        // Nor the homeserver
+        let request = Request::post(OAuth2Introspection::PATH)
+            .bearer(MockHomeserverConnection::VALID_BEARER_TOKEN)
+            .form(json!({ "token": access_token }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: IntrospectionResponse = response.json();
+        assert!(!response.active);
+
+        // Nor the client it was issued to
+        let request = Request::post(OAuth2Introspection::PATH).form(json!({
+            "client_id": client_id,
+            "client_secret": client_secret,
+            "token": access_token,
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: IntrospectionResponse = response.json();
+        assert!(!response.active);
+
+        // Tokens not intended for any resource server are inactive for them
+        let request = Request::post(OAuth2TokenEndpoint::PATH).form(json!({
+            "grant_type": "client_credentials",
+            "client_id": client_id,
+            "client_secret": client_secret,
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let AccessTokenResponse { access_token, .. } = response.json();
+
+        let request = Request::post(OAuth2Introspection::PATH)
+            .basic_auth(api_client_id, api_client_secret)
+            .form(json!({ "token": access_token }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: IntrospectionResponse = response.json();
+        assert!(!response.active);
+    }
 }
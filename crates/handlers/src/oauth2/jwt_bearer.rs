@@ -0,0 +1,182 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Support for JWTs used as authorization grants, as defined in RFC 7523
+
+use std::collections::HashMap;
+
+use mas_data_model::{JwksOrJwksUri, SiteConfig, User};
+use mas_iana::jose::JsonWebSignatureAlg;
+use mas_jose::{
+    claims::{self, Claim, ClaimError, OneOrMany, TimeOptions},
+    jwt::{Jwt, JwtDecodeError},
+};
+use mas_router::UrlBuilder;
+use mas_storage::{BoxRepository, Clock};
+use serde_json::Value;
+use thiserror::Error;
+use ulid::Ulid;
+
+use crate::impl_from_error_for_route;
+
+/// The `aud` claim, validated separately as we accept more than one value
+const AUD: Claim<OneOrMany<String>> = Claim::new("aud");
+
+#[derive(Debug, Error)]
+pub(crate) enum JwtBearerError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("failed to decode the assertion")]
+    Decode(#[from] JwtDecodeError),
+
+    #[error("the assertion is not signed")]
+    Unsigned,
+
+    #[error("the assertion was issued by an untrusted issuer {0:?}")]
+    UntrustedIssuer(Option<String>),
+
+    #[error("failed to fetch the JWKS of issuer {issuer:?}")]
+    FetchJwks {
+        issuer: String,
+        #[source]
+        source: mas_oidc_client::error::JwksError,
+    },
+
+    #[error("invalid assertion signature")]
+    InvalidSignature,
+
+    #[error("invalid claims in assertion")]
+    InvalidClaims(#[from] ClaimError),
+
+    #[error("the assertion is not intended for this server")]
+    AudienceMismatch,
+
+    #[error("the assertion was already used")]
+    Replayed,
+
+    #[error("upstream provider {0} referenced by the JWT bearer configuration does not exist")]
+    NoSuchProvider(Ulid),
+
+    #[error("the subject of the assertion is not linked to a valid user")]
+    UnknownSubject,
+}
+
+impl_from_error_for_route!(JwtBearerError: mas_storage::RepositoryError);
+
+impl JwtBearerError {
+    /// Returns true if the error is an internal error, not caused by the client
+    pub(crate) fn is_internal(&self) -> bool {
+        matches!(
+            self,
+            Self::Internal(_) | Self::FetchJwks { .. } | Self::NoSuchProvider(_)
+        )
+    }
+}
+
+/// Verify a JWT presented as an authorization grant, and find the user it
+/// was issued for.
+///
+/// The assertion must be signed by one of the issuers trusted in the site
+/// configuration, and its subject must be linked to a user through the
+/// upstream OAuth 2.0 provider associated with that issuer.
+///
+/// # Errors
+///
+/// Returns an error if the assertion is invalid, was already used, or if its
+/// subject could not be mapped to a valid user.
+pub(crate) async fn verify_assertion(
+    http_client: &reqwest::Client,
+    url_builder: &UrlBuilder,
+    site_config: &SiteConfig,
+    clock: &dyn Clock,
+    repo: &mut BoxRepository,
+    assertion: &str,
+) -> Result<User, JwtBearerError> {
+    let jwt: Jwt<'_, HashMap<String, Value>> = Jwt::try_from(assertion)?;
+
+    if jwt.header().alg() == &JsonWebSignatureAlg::None {
+        return Err(JwtBearerError::Unsigned);
+    }
+
+    // Find out which keys to use from the (not yet verified) issuer
+    let issuer = jwt.payload().get("iss").and_then(Value::as_str);
+    let trusted_issuer = site_config
+        .jwt_bearer_issuers
+        .iter()
+        .find(|trusted| Some(trusted.issuer.as_str()) == issuer)
+        .ok_or_else(|| JwtBearerError::UntrustedIssuer(issuer.map(ToOwned::to_owned)))?;
+
+    let jwks = match &trusted_issuer.jwks {
+        JwksOrJwksUri::Jwks(jwks) => jwks.clone(),
+        JwksOrJwksUri::JwksUri(jwks_uri) => {
+            mas_oidc_client::requests::jose::fetch_jwks(http_client, jwks_uri)
+                .await
+                .map_err(|source| JwtBearerError::FetchJwks {
+                    issuer: trusted_issuer.issuer.clone(),
+                    source,
+                })?
+        }
+    };
+
+    jwt.verify_with_jwks(&jwks)
+        .map_err(|_| JwtBearerError::InvalidSignature)?;
+
+    let (_header, mut claims) = jwt.into_parts();
+
+    let time_options = TimeOptions::new(clock.now());
+    claims::ISS.extract_required_with_options(&mut claims, trusted_issuer.issuer.as_str())?;
+    let subject = claims::SUB.extract_required(&mut claims)?;
+    let exp = claims::EXP.extract_required_with_options(&mut claims, &time_options)?;
+    claims::NBF.extract_optional_with_options(&mut claims, &time_options)?;
+
+    // The assertion must be intended for us, either through our issuer
+    // identifier or the URL of the token endpoint
+    let audience = AUD.extract_required(&mut claims)?;
+    let issuer_url = url_builder.oidc_issuer().to_string();
+    let token_endpoint = url_builder.oauth_token_endpoint().to_string();
+    if !audience
+        .iter()
+        .any(|aud| *aud == issuer_url || *aud == token_endpoint)
+    {
+        return Err(JwtBearerError::AudienceMismatch);
+    }
+
+    // The JWT ID is optional, but if present we use it to detect replays
+    if let Some(jti) = claims::JTI.extract_optional(&mut claims)? {
+        let first_use = repo
+            .oauth2_used_jwt_id()
+            .mark_used(clock, &trusted_issuer.issuer, &jti, *exp)
+            .await?;
+        if !first_use {
+            return Err(JwtBearerError::Replayed);
+        }
+    }
+
+    let provider = repo
+        .upstream_oauth_provider()
+        .lookup(trusted_issuer.upstream_provider_id)
+        .await?
+        .ok_or(JwtBearerError::NoSuchProvider(
+            trusted_issuer.upstream_provider_id,
+        ))?;
+
+    let link = repo
+        .upstream_oauth_link()
+        .find_by_subject(&provider, &subject)
+        .await?
+        .ok_or(JwtBearerError::UnknownSubject)?;
+
+    let user_id = link.user_id.ok_or(JwtBearerError::UnknownSubject)?;
+
+    let user = repo
+        .user()
+        .lookup(user_id)
+        .await?
+        .filter(User::is_valid)
+        .ok_or(JwtBearerError::UnknownSubject)?;
+
+    Ok(user)
+}
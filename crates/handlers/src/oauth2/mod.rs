@@ -8,31 +8,49 @@ use std::collections::HashMap;
 
 use chrono::Duration;
 use mas_data_model::{
-    AccessToken, Authentication, AuthorizationGrant, BrowserSession, Client, RefreshToken, Session,
-    TokenType,
+    AccessToken, Authentication, AuthorizationGrant, BrowserSession, Client,
+    MissingPairwiseSubjectSecretError, RefreshToken, Session, SiteConfig, TokenType, User,
 };
 use mas_iana::jose::JsonWebSignatureAlg;
 use mas_jose::{
     claims::{self, hash_token},
     constraints::Constrainable,
+    jwe::JsonWebEncryptionHeader,
     jwt::{JsonWebSignatureHeader, Jwt},
 };
-use mas_keystore::Keystore;
+use mas_keystore::{Encrypter, Keystore};
 use mas_router::UrlBuilder;
-use mas_storage::{Clock, RepositoryAccess};
+use mas_storage::{BoxRepository, Clock, RepositoryAccess, RepositoryError};
+use oauth2_types::{
+    requests::{AuthorizationDetails, RequestedClaims},
+    scope::{PROFILE, Scope},
+};
+use serde_json::Value;
 use thiserror::Error;
+use ulid::Ulid;
+use url::Url;
 
 pub mod authorization;
+pub mod backchannel_authentication;
 pub mod device;
 pub mod discovery;
+pub(crate) mod dpop;
+pub mod end_session;
 pub mod introspection;
+mod jwt_bearer;
 pub mod keys;
+pub mod pushed_authorization_request;
 pub mod registration;
+mod request_object;
+mod response_encryption;
 pub mod revoke;
+mod software_statement;
 pub mod token;
 pub mod userinfo;
 pub mod webfinger;
 
+pub(crate) use self::response_encryption::ResponseEncryptionError;
+
 #[derive(Debug, Error)]
 #[error(transparent)]
 pub(crate) enum IdTokenSignatureError {
@@ -42,6 +60,7 @@ pub(crate) enum IdTokenSignatureError {
     JwtSignature(#[from] mas_jose::jwt::JwtSignatureError),
     WrongAlgorithm(#[from] mas_keystore::WrongAlgorithmError),
     TokenHash(#[from] mas_jose::claims::TokenHashError),
+    PairwiseSubject(#[from] MissingPairwiseSubjectSecretError),
 }
 
 pub(crate) fn generate_id_token(
@@ -49,26 +68,47 @@ pub(crate) fn generate_id_token(
     clock: &impl Clock,
     url_builder: &UrlBuilder,
     key_store: &Keystore,
+    site_config: &SiteConfig,
     client: &Client,
     grant: Option<&AuthorizationGrant>,
     browser_session: &BrowserSession,
     access_token: Option<&AccessToken>,
     last_authentication: Option<&Authentication>,
+    user_claims: HashMap<String, Value>,
 ) -> Result<String, IdTokenSignatureError> {
-    let mut claims = HashMap::new();
+    // Start with the user claims, so that they can't override the ones set below
+    let mut claims = user_claims;
     let now = clock.now();
     claims::ISS.insert(&mut claims, url_builder.oidc_issuer().to_string())?;
-    claims::SUB.insert(&mut claims, &browser_session.user.sub)?;
+    claims::SUB.insert(
+        &mut claims,
+        client.subject_for(
+            &browser_session.user,
+            site_config.pairwise_subject_secret.as_ref(),
+        )?,
+    )?;
     claims::AUD.insert(&mut claims, client.client_id.clone())?;
     claims::IAT.insert(&mut claims, now)?;
     claims::EXP.insert(&mut claims, now + Duration::try_hours(1).unwrap())?;
 
+    // The browser session is what gets logged out, so it identifies the session
+    // in front-channel and back-channel logout requests
+    claims::SID.insert(&mut claims, browser_session.id.to_string())?;
+
     if let Some(nonce) = grant.and_then(|grant| grant.nonce.as_ref()) {
         claims::NONCE.insert(&mut claims, nonce)?;
     }
 
     if let Some(last_authentication) = last_authentication {
         claims::AUTH_TIME.insert(&mut claims, last_authentication.created_at)?;
+
+        if let Some(amr) = last_authentication.authentication_method.amr() {
+            claims::AMR.insert(&mut claims, vec![amr.to_owned()])?;
+        }
+
+        if let Some(level) = site_config.acr_for(last_authentication) {
+            claims::ACR.insert(&mut claims, level.value.clone())?;
+        }
     }
 
     let alg = client
@@ -95,19 +135,282 @@ pub(crate) fn generate_id_token(
     Ok(id_token.into_string())
 }
 
+/// Encrypt an ID token generated by [`generate_id_token`] for the client, if
+/// it registered an encryption algorithm for them
+pub(crate) async fn encrypt_id_token(
+    rng: &mut (impl rand::RngCore + rand::CryptoRng),
+    http_client: &reqwest::Client,
+    encrypter: &Encrypter,
+    client: &Client,
+    id_token: String,
+) -> Result<String, ResponseEncryptionError> {
+    let Some((alg, enc)) = client.id_token_encrypted_response() else {
+        return Ok(id_token);
+    };
+
+    // The ID token is signed then encrypted, making it a nested JWT
+    let header = JsonWebEncryptionHeader::new(alg.clone(), enc.clone()).with_cty("JWT".to_owned());
+    response_encryption::encrypt_for_client(
+        rng,
+        http_client,
+        encrypter,
+        client,
+        header,
+        id_token.as_bytes(),
+    )
+    .await
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum AccessTokenGenerationError {
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+
+    #[error("user {0} not found")]
+    NoSuchUser(Ulid),
+
+    #[error("The signing key is invalid")]
+    InvalidSigningKey,
+
+    #[error(transparent)]
+    Claim(#[from] mas_jose::claims::ClaimError),
+
+    #[error(transparent)]
+    Serialization(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    JwtSignature(#[from] mas_jose::jwt::JwtSignatureError),
+
+    #[error(transparent)]
+    WrongAlgorithm(#[from] mas_keystore::WrongAlgorithmError),
+
+    #[error(transparent)]
+    PairwiseSubject(#[from] MissingPairwiseSubjectSecretError),
+}
+
+/// A newly generated access token, along with the ID it must be stored under
+pub(crate) struct GeneratedAccessToken {
+    /// The ID of the access token, which is also the `jti` claim of
+    /// JWT-formatted access tokens
+    pub id: Ulid,
+
+    /// The value of the access token
+    pub token: String,
+}
+
+impl GeneratedAccessToken {
+    /// Generate a new opaque access token
+    pub(crate) fn opaque(
+        rng: &mut (impl rand::RngCore + rand::CryptoRng + Send),
+        clock: &impl Clock,
+    ) -> Self {
+        Self {
+            id: Ulid::from_datetime_with_source(clock.now().into(), rng),
+            token: TokenType::AccessToken.generate(rng),
+        }
+    }
+}
+
+/// Generate the value of a new access token for the session.
+///
+/// Access tokens are opaque, unless the site is configured to issue
+/// JWT-formatted access tokens, as per RFC 9068, to the client or for one of
+/// the scopes of the session. Those are stored by their hash, so that they can
+/// still be introspected and revoked.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn generate_access_token(
+    rng: &mut (impl rand::RngCore + rand::CryptoRng + Send),
+    clock: &impl Clock,
+    repo: &mut BoxRepository,
+    url_builder: &UrlBuilder,
+    key_store: &Keystore,
+    site_config: &SiteConfig,
+    client: &Client,
+    session: &Session,
+    ttl: Duration,
+) -> Result<GeneratedAccessToken, AccessTokenGenerationError> {
+    let Some(config) = site_config
+        .jwt_access_tokens
+        .as_ref()
+        .filter(|config| config.applies_to(client.id, &session.scope))
+    else {
+        return Ok(GeneratedAccessToken::opaque(rng, clock));
+    };
+
+    let sub = if let Some(user_id) = session.user_id {
+        let user = repo
+            .user()
+            .lookup(user_id)
+            .await?
+            .ok_or(AccessTokenGenerationError::NoSuchUser(user_id))?;
+        client.subject_for(&user, site_config.pairwise_subject_secret.as_ref())?
+    } else {
+        // Tokens which are not issued on behalf of a user are about the client
+        client.client_id.clone()
+    };
+
+    // Tokens are meant for our own APIs, unless the session was restricted to
+    // another audience
+    let audience = if session.audience.is_empty() {
+        vec![url_builder.oidc_issuer().to_string()]
+    } else {
+        session.audience.clone()
+    };
+
+    // The ID of the token is used as its `jti`, so that introspection reports
+    // the same one
+    let now = clock.now();
+    let id = Ulid::from_datetime_with_source(now.into(), rng);
+    let mut claims = HashMap::new();
+    claims::ISS.insert(&mut claims, url_builder.oidc_issuer().to_string())?;
+    claims::SUB.insert(&mut claims, sub)?;
+    claims::AUD.insert(&mut claims, audience)?;
+    claims::IAT.insert(&mut claims, now)?;
+    claims::EXP.insert(&mut claims, now + ttl)?;
+    claims::JTI.insert(&mut claims, id.to_string())?;
+    claims.insert(
+        "client_id".to_owned(),
+        Value::String(client.client_id.clone()),
+    );
+    claims.insert("scope".to_owned(), Value::String(session.scope.to_string()));
+
+    if let Some(user_session_id) = session.user_session_id {
+        claims::SID.insert(&mut claims, user_session_id.to_string())?;
+    }
+
+    // Let resource servers check the proof of possession on bound tokens
+    let mut cnf = serde_json::Map::new();
+    if let Some(jkt) = &session.dpop_jkt {
+        cnf.insert("jkt".to_owned(), Value::String(jkt.clone()));
+    }
+    if let Some(x5t_s256) = &session.x5t_s256 {
+        cnf.insert("x5t#S256".to_owned(), Value::String(x5t_s256.clone()));
+    }
+    if !cnf.is_empty() {
+        claims.insert("cnf".to_owned(), Value::Object(cnf));
+    }
+
+    if let Some(act) = &session.act {
+        claims.insert("act".to_owned(), serde_json::to_value(act)?);
+    }
+
+    if let Some(authorization_details) = &session.authorization_details {
+        claims.insert(
+            "authorization_details".to_owned(),
+            serde_json::to_value(authorization_details)?,
+        );
+    }
+
+    let alg = config.signing_alg.clone();
+    let key = key_store
+        .signing_key_for_algorithm(&alg)
+        .ok_or(AccessTokenGenerationError::InvalidSigningKey)?;
+    let signer = key.params().signing_key_for_alg(&alg)?;
+    let header = JsonWebSignatureHeader::new(alg)
+        .with_typ("at+jwt".to_owned())
+        .with_kid(
+            key.kid()
+                .ok_or(AccessTokenGenerationError::InvalidSigningKey)?,
+        );
+    let access_token = Jwt::sign_with_rng(rng, header, claims, &signer)?;
+
+    Ok(GeneratedAccessToken {
+        id,
+        token: access_token.into_string(),
+    })
+}
+
+/// Find the first type of authorization details which is not supported by
+/// this server, if any.
+pub(crate) fn unsupported_authorization_details_type<'a>(
+    site_config: &SiteConfig,
+    authorization_details: &'a AuthorizationDetails,
+) -> Option<&'a str> {
+    authorization_details
+        .iter()
+        .map(|detail| detail.r#type.as_str())
+        .find(|r#type| {
+            !site_config
+                .authorization_details_types
+                .iter()
+                .any(|supported| supported == r#type)
+        })
+}
+
+/// Check whether the resource server a client asked a token for through the
+/// `resource` parameter is one of the configured resource servers.
+pub(crate) fn is_known_resource(site_config: &SiteConfig, resource: &Url) -> bool {
+    site_config
+        .resource_servers
+        .iter()
+        .any(|server| server.resource == *resource)
+}
+
+/// Load the values of the user claims requested through the `claims`
+/// authorization request parameter.
+///
+/// Claims which are not supported, or for which the user has no value, are
+/// omitted, as allowed by the specification.
+///
+/// The username is the same for every client, so clients with pairwise
+/// subject identifiers only get it if the user consented to the `profile`
+/// scope.
+pub(crate) async fn load_requested_claims<R: RepositoryAccess>(
+    repo: &mut R,
+    client: &Client,
+    user: &User,
+    scope: &Scope,
+    requested: Option<&RequestedClaims>,
+) -> Result<HashMap<String, Value>, R::Error> {
+    let mut claims = HashMap::new();
+    let Some(requested) = requested else {
+        return Ok(claims);
+    };
+
+    let pairwise = client.pairwise_sector_identifier().is_some();
+    if requested.contains_key("preferred_username") && (!pairwise || scope.contains(&PROFILE)) {
+        claims.insert(
+            "preferred_username".to_owned(),
+            Value::String(user.username.clone()),
+        );
+    }
+
+    if requested.contains_key("email") || requested.contains_key("email_verified") {
+        // Emails are only ever added to an account once they are verified
+        let email = repo.user_email().all(user).await?.into_iter().next();
+        if let Some(email) = email {
+            if requested.contains_key("email") {
+                claims.insert("email".to_owned(), Value::String(email.email));
+            }
+
+            if requested.contains_key("email_verified") {
+                claims.insert("email_verified".to_owned(), Value::Bool(true));
+            }
+        }
+    }
+
+    Ok(claims)
+}
+
 pub(crate) async fn generate_token_pair<R: RepositoryAccess>(
     rng: &mut (impl rand::RngCore + Send),
     clock: &impl Clock,
     repo: &mut R,
     session: &Session,
+    access_token: GeneratedAccessToken,
     ttl: Duration,
 ) -> Result<(AccessToken, RefreshToken), R::Error> {
-    let access_token_str = TokenType::AccessToken.generate(rng);
     let refresh_token_str = TokenType::RefreshToken.generate(rng);
 
     let access_token = repo
         .oauth2_access_token()
-        .add(rng, clock, session, access_token_str, Some(ttl))
+        .add_with_id(
+            clock,
+            access_token.id,
+            session,
+            access_token.token,
+            Some(ttl),
+        )
         .await?;
 
     let refresh_token = repo
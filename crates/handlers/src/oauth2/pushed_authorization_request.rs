@@ -0,0 +1,498 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::collections::{BTreeMap, btree_map::Entry};
+
+use axum::{Json, extract::State, response::IntoResponse};
+use axum_extra::typed_header::TypedHeader;
+use chrono::Duration;
+use headers::{CacheControl, Pragma};
+use hyper::StatusCode;
+use mas_axum_utils::{
+    client_authorization::{ClientAuthorization, CredentialsVerificationError},
+    record_error,
+};
+use mas_data_model::SiteConfig;
+use mas_keystore::Encrypter;
+use mas_router::UrlBuilder;
+use mas_storage::{BoxClock, BoxRepository, BoxRng};
+use oauth2_types::{
+    errors::{ClientError, ClientErrorCode},
+    requests::PushedAuthorizationResponse,
+};
+use rand::distributions::{Alphanumeric, DistString};
+use serde::{Deserialize, Deserializer, de::MapAccess};
+use thiserror::Error;
+use ulid::Ulid;
+
+use super::{
+    authorization::Params,
+    is_known_resource,
+    request_object::{RequestObjectError, resolve_parameters},
+    unsupported_authorization_details_type,
+};
+use crate::impl_from_error_for_route;
+
+/// How long a pushed authorization request can be used for
+const EXPIRES_IN: Duration = Duration::seconds(60);
+
+/// The pushed authorization request parameters, also used for plain
+/// authorization requests.
+///
+/// Request parameters must not be included more than once (RFC 6749, section
+/// 3.1), so unlike a plain map, this fails to deserialize on duplicates
+/// instead of keeping the last value.
+pub(crate) struct UniqueParameters(pub(crate) BTreeMap<String, String>);
+
+impl<'de> Deserialize<'de> for UniqueParameters {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = UniqueParameters;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of unique parameters")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut parameters = BTreeMap::new();
+                while let Some((key, value)) = map.next_entry::<String, String>()? {
+                    match parameters.entry(key) {
+                        Entry::Occupied(entry) => {
+                            return Err(serde::de::Error::custom(format!(
+                                "parameter {:?} is included more than once",
+                                entry.key()
+                            )));
+                        }
+                        Entry::Vacant(entry) => {
+                            entry.insert(value);
+                        }
+                    }
+                }
+
+                Ok(UniqueParameters(parameters))
+            }
+        }
+
+        deserializer.deserialize_map(Visitor)
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("bad request")]
+    BadRequest,
+
+    #[error("client not found")]
+    ClientNotFound,
+
+    #[error("client {0} is not allowed to push authorization requests")]
+    ClientNotAllowed(Ulid),
+
+    #[error("invalid client credentials for client {client_id}")]
+    InvalidClientCredentials {
+        client_id: Ulid,
+        #[source]
+        source: CredentialsVerificationError,
+    },
+
+    #[error("could not verify client credentials for client {client_id}")]
+    ClientCredentialsVerification {
+        client_id: Ulid,
+        #[source]
+        source: CredentialsVerificationError,
+    },
+
+    #[error("the request_uri parameter is not allowed in pushed authorization requests")]
+    RequestUriNotAllowed,
+
+    #[error("invalid request object")]
+    InvalidRequestObject(#[source] RequestObjectError),
+
+    #[error("invalid authorization request parameters")]
+    InvalidParameters(#[source] serde_urlencoded::de::Error),
+
+    #[error("invalid redirect uri")]
+    InvalidRedirectUri(#[from] mas_data_model::InvalidRedirectUriError),
+
+    #[error("unsupported authorization details type {0:?}")]
+    UnsupportedAuthorizationDetailsType(String),
+
+    #[error("unknown resource {0}")]
+    UnknownResource(url::Url),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl From<RequestObjectError> for RouteError {
+    fn from(e: RequestObjectError) -> Self {
+        if e.is_internal() {
+            Self::Internal(Box::new(e))
+        } else {
+            Self::InvalidRequestObject(e)
+        }
+    }
+}
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+
+        let response = match self {
+            Self::Internal(_) | Self::ClientCredentialsVerification { .. } => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ClientError::from(ClientErrorCode::ServerError)),
+            ),
+            Self::BadRequest => (
+                StatusCode::BAD_REQUEST,
+                Json(ClientError::from(ClientErrorCode::InvalidRequest)),
+            ),
+            Self::ClientNotFound | Self::InvalidClientCredentials { .. } => (
+                StatusCode::UNAUTHORIZED,
+                Json(ClientError::from(ClientErrorCode::InvalidClient)),
+            ),
+            Self::ClientNotAllowed(_) => (
+                StatusCode::UNAUTHORIZED,
+                Json(ClientError::from(ClientErrorCode::UnauthorizedClient)),
+            ),
+            Self::RequestUriNotAllowed => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidRequest).with_description(
+                        "The request_uri parameter is not allowed in pushed authorization requests"
+                            .to_owned(),
+                    ),
+                ),
+            ),
+            Self::InvalidRequestObject(ref e) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidRequestObject)
+                        .with_description(e.to_string()),
+                ),
+            ),
+            Self::InvalidParameters(ref e) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidRequest)
+                        .with_description(format!("Invalid authorization request: {e}")),
+                ),
+            ),
+            Self::InvalidRedirectUri(ref e) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidRequest)
+                        .with_description(format!("Invalid redirect_uri: {e}")),
+                ),
+            ),
+            Self::UnsupportedAuthorizationDetailsType(ref kind) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidAuthorizationDetails)
+                        .with_description(format!(
+                            "Unsupported authorization details type {kind:?}"
+                        )),
+                ),
+            ),
+            Self::UnknownResource(ref resource) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidTarget)
+                        .with_description(format!("Unknown resource {resource}")),
+                ),
+            ),
+        };
+
+        (sentry_event_id, response).into_response()
+    }
+}
+
+#[tracing::instrument(
+    name = "handlers.oauth2.pushed_authorization_request.post",
+    fields(client.id = client_authorization.client_id()),
+    skip_all,
+)]
+pub(crate) async fn post(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    mut repo: BoxRepository,
+    State(url_builder): State<UrlBuilder>,
+    State(http_client): State<reqwest::Client>,
+    State(encrypter): State<Encrypter>,
+    State(site_config): State<SiteConfig>,
+    client_authorization: ClientAuthorization<UniqueParameters>,
+) -> Result<impl IntoResponse, RouteError> {
+    let client = client_authorization
+        .credentials
+        .fetch(&mut repo)
+        .await?
+        .ok_or(RouteError::ClientNotFound)?;
+
+    // Reuse the token endpoint auth method to verify the client
+    let method = client
+        .token_endpoint_auth_method
+        .as_ref()
+        .ok_or(RouteError::ClientNotAllowed(client.id))?;
+
+    client_authorization
+        .credentials
+        .verify(&http_client, &encrypter, method, &client)
+        .await
+        .map_err(|err| {
+            if err.is_internal() {
+                RouteError::ClientCredentialsVerification {
+                    client_id: client.id,
+                    source: err,
+                }
+            } else {
+                RouteError::InvalidClientCredentials {
+                    client_id: client.id,
+                    source: err,
+                }
+            }
+        })?;
+
+    let UniqueParameters(mut parameters) =
+        client_authorization.form.ok_or(RouteError::BadRequest)?;
+
+    // Pushing a request which references another one doesn't make sense
+    if parameters.contains_key("request_uri") {
+        return Err(RouteError::RequestUriNotAllowed);
+    }
+
+    // The client credentials are not part of the authorization request, but the
+    // client_id is required when parsing it at the authorization endpoint
+    parameters.insert("client_id".to_owned(), client.client_id.clone());
+
+    // The request may be passed as a signed request object, which is verified
+    // now so that we only store the resulting parameters
+    let parameters = resolve_parameters(
+        &http_client,
+        &url_builder,
+        &clock,
+        &mut repo,
+        &client,
+        parameters,
+    )
+    .await?;
+
+    // Validate the request as early as possible, so that the client gets
+    // feedback before redirecting the user
+    let params = Params::from_raw(&parameters).map_err(RouteError::InvalidParameters)?;
+    client.resolve_redirect_uri(&params.auth.redirect_uri)?;
+    if let Some(unsupported) = params
+        .auth
+        .authorization_details
+        .as_ref()
+        .and_then(|details| unsupported_authorization_details_type(&site_config, details))
+    {
+        return Err(RouteError::UnsupportedAuthorizationDetailsType(
+            unsupported.to_owned(),
+        ));
+    }
+    if let Some(resource) = &params.auth.resource
+        && !is_known_resource(&site_config, resource)
+    {
+        return Err(RouteError::UnknownResource(resource.clone()));
+    }
+
+    let reference = Alphanumeric.sample_string(&mut rng, 32);
+
+    let pushed_request = repo
+        .oauth2_pushed_authorization_request()
+        .add(&mut rng, &clock, &client, reference, parameters, EXPIRES_IN)
+        .await?;
+
+    repo.save().await?;
+
+    let response = PushedAuthorizationResponse {
+        request_uri: pushed_request.request_uri(),
+        expires_in: EXPIRES_IN,
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        TypedHeader(CacheControl::new().with_no_store()),
+        TypedHeader(Pragma::no_cache()),
+        Json(response),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode, header::LOCATION};
+    use mas_router::SimpleRoute;
+    use oauth2_types::{
+        errors::{ClientError, ClientErrorCode},
+        registration::ClientRegistrationResponse,
+        requests::PushedAuthorizationResponse,
+    };
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_pushed_authorization_request(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision a client which requires pushed authorization requests
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "token_endpoint_auth_method": "client_secret_post",
+                "require_pushed_authorization_requests": true,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+        let client_secret = response.client_secret.unwrap();
+
+        // Wrong credentials are rejected
+        let request = Request::post(mas_router::OAuth2PushedAuthorizationRequestEndpoint::PATH)
+            .form(serde_json::json!({
+                "client_id": client_id,
+                "client_secret": "wrong",
+                "response_type": "code",
+                "scope": "openid",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+        let error: ClientError = response.json();
+        assert_eq!(error.error, ClientErrorCode::InvalidClient);
+
+        // Pushing a request_uri is not allowed
+        let request = Request::post(mas_router::OAuth2PushedAuthorizationRequestEndpoint::PATH)
+            .form(serde_json::json!({
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "request_uri": "urn:ietf:params:oauth:request_uri:something",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let error: ClientError = response.json();
+        assert_eq!(error.error, ClientErrorCode::InvalidRequest);
+
+        // Parameters included more than once are rejected
+        let request = Request::post(mas_router::OAuth2PushedAuthorizationRequestEndpoint::PATH)
+            .form([
+                ("client_id", client_id.as_str()),
+                ("client_secret", client_secret.as_str()),
+                ("response_type", "code"),
+                ("redirect_uri", "https://example.com/callback"),
+                ("redirect_uri", "https://attacker.example.com/callback"),
+                ("scope", "openid"),
+            ]);
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let error: ClientError = response.json();
+        assert_eq!(error.error, ClientErrorCode::InvalidRequest);
+
+        // Unregistered redirect URIs are rejected early
+        let request = Request::post(mas_router::OAuth2PushedAuthorizationRequestEndpoint::PATH)
+            .form(serde_json::json!({
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "response_type": "code",
+                "redirect_uri": "https://example.com/not-registered",
+                "scope": "openid",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let error: ClientError = response.json();
+        assert_eq!(error.error, ClientErrorCode::InvalidRequest);
+
+        // The client can't use the authorization endpoint directly
+        let query = serde_urlencoded::to_string([
+            ("client_id", client_id.as_str()),
+            ("response_type", "code"),
+            ("scope", "openid"),
+        ])
+        .unwrap();
+        let request = Request::get(format!(
+            "{}?{query}",
+            mas_router::OAuth2AuthorizationEndpoint::PATH
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        let location = response.headers().get(LOCATION).unwrap().to_str().unwrap();
+        assert!(location.starts_with("https://example.com/callback?"));
+        assert!(location.contains("error=invalid_request"));
+
+        // Push a valid request
+        let request = Request::post(mas_router::OAuth2PushedAuthorizationRequestEndpoint::PATH)
+            .form(serde_json::json!({
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "response_type": "code",
+                "scope": "openid",
+                "state": "some-state",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let response: PushedAuthorizationResponse = response.json();
+        assert!(
+            response
+                .request_uri
+                .starts_with("urn:ietf:params:oauth:request_uri:")
+        );
+        let request_uri = response.request_uri;
+
+        // Using it with another client_id fails
+        let query = serde_urlencoded::to_string([
+            ("client_id", "01FSHN9AG0MZAA6S4AF7CTV32E"),
+            ("request_uri", request_uri.as_str()),
+        ])
+        .unwrap();
+        let request = Request::get(format!(
+            "{}?{query}",
+            mas_router::OAuth2AuthorizationEndpoint::PATH
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // Using it with the right client_id starts the authorization flow
+        let query = serde_urlencoded::to_string([
+            ("client_id", client_id.as_str()),
+            ("request_uri", request_uri.as_str()),
+        ])
+        .unwrap();
+        let request = Request::get(format!(
+            "{}?{query}",
+            mas_router::OAuth2AuthorizationEndpoint::PATH
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        let location = response.headers().get(LOCATION).unwrap().to_str().unwrap();
+        assert!(location.starts_with("/login"));
+
+        // It can't be used twice
+        let request = Request::get(format!(
+            "{}?{query}",
+            mas_router::OAuth2AuthorizationEndpoint::PATH
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+}
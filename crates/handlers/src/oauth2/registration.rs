@@ -6,16 +6,35 @@
 
 use std::sync::LazyLock;
 
-use axum::{Json, extract::State, response::IntoResponse};
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
 use axum_extra::TypedHeader;
-use hyper::StatusCode;
+use headers::{Authorization, authorization::Bearer};
+use hyper::{
+    StatusCode,
+    header::{HeaderValue, WWW_AUTHENTICATE},
+};
 use mas_axum_utils::record_error;
-use mas_iana::oauth::OAuthClientAuthenticationMethod;
+use mas_data_model::{Client, JwksOrJwksUri, SiteConfig};
+use mas_http::RequestBuilderExt as _;
+use mas_iana::{
+    jose::{JsonWebEncryptionAlg, JsonWebEncryptionEnc, JsonWebSignatureAlg},
+    oauth::OAuthClientAuthenticationMethod,
+};
+use mas_jose::jwa::{SUPPORTED_CONTENT_ENCRYPTION_ALGORITHMS, SUPPORTED_KEY_MANAGEMENT_ALGORITHMS};
 use mas_keystore::Encrypter;
 use mas_policy::{EvaluationResult, Policy};
-use mas_storage::{BoxClock, BoxRepository, BoxRng, oauth2::OAuth2ClientRepository};
+use mas_router::UrlBuilder;
+use mas_storage::{
+    BoxClock, BoxRepository, BoxRng, Clock,
+    oauth2::{OAuth2ClientParams, OAuth2ClientRepository},
+};
 use oauth2_types::{
     errors::{ClientError, ClientErrorCode},
+    oidc::{BackchannelTokenDeliveryMode, SubjectType},
     registration::{
         ClientMetadata, ClientMetadataVerificationError, ClientRegistrationResponse, Localized,
         VerifiedClientMetadata,
@@ -24,13 +43,16 @@ use oauth2_types::{
 use opentelemetry::{Key, KeyValue, metrics::Counter};
 use psl::Psl;
 use rand::distributions::{Alphanumeric, DistString};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sha2::Digest as _;
 use thiserror::Error;
 use tracing::info;
 use url::Url;
 
-use crate::{BoundActivityTracker, METER, impl_from_error_for_route};
+use crate::{
+    BoundActivityTracker, METER, impl_from_error_for_route,
+    oauth2::software_statement::{self, SoftwareStatementError},
+};
 
 static REGISTRATION_COUNTER: LazyLock<Counter<u64>> = LazyLock::new(|| {
     METER
@@ -57,6 +79,51 @@ pub(crate) enum RouteError {
 
     #[error("client registration denied by the policy: {0}")]
     PolicyDenied(EvaluationResult),
+
+    #[error("unsupported subject_type")]
+    UnsupportedSubjectType,
+
+    #[error("failed to fetch the sector_identifier_uri")]
+    FetchSectorIdentifierUri(#[source] reqwest::Error),
+
+    #[error("redirect_uri {0} is not listed in the sector_identifier_uri")]
+    RedirectUriNotInSector(Url),
+
+    #[error("redirect_uris with different hosts require a sector_identifier_uri")]
+    MissingSectorIdentifierUri,
+
+    #[error("introspection responses must be signed")]
+    UnsignedIntrospectionResponse,
+
+    #[error("authorization responses must be signed")]
+    UnsignedAuthorizationResponse,
+
+    #[error("unsupported {0} encryption algorithms")]
+    UnsupportedEncryptionAlgorithm(&'static str),
+
+    #[error("{0} encryption requires a jwks or jwks_uri")]
+    MissingEncryptionKeys(&'static str),
+
+    #[error("{0} encryption with A256KW requires a client secret")]
+    MissingEncryptionSecret(&'static str),
+
+    #[error("unsupported backchannel_token_delivery_mode")]
+    UnsupportedBackchannelTokenDeliveryMode,
+
+    #[error("invalid software statement")]
+    InvalidSoftwareStatement(#[source] SoftwareStatementError),
+
+    #[error("unapproved software statement")]
+    UnapprovedSoftwareStatement(#[source] SoftwareStatementError),
+
+    #[error("invalid registration access token")]
+    InvalidRegistrationAccessToken,
+
+    #[error("client_id does not match the client being updated")]
+    ClientIdMismatch,
+
+    #[error("client_secret does not match the current client secret")]
+    ClientSecretMismatch,
 }
 
 impl_from_error_for_route!(mas_storage::RepositoryError);
@@ -65,7 +132,20 @@ impl_from_error_for_route!(mas_policy::EvaluationError);
 impl_from_error_for_route!(mas_keystore::aead::Error);
 impl_from_error_for_route!(serde_json::Error);
 
+impl From<SoftwareStatementError> for RouteError {
+    fn from(e: SoftwareStatementError) -> Self {
+        if e.is_internal() {
+            Self::Internal(Box::new(e))
+        } else if e.is_unapproved() {
+            Self::UnapprovedSoftwareStatement(e)
+        } else {
+            Self::InvalidSoftwareStatement(e)
+        }
+    }
+}
+
 impl IntoResponse for RouteError {
+    #[allow(clippy::too_many_lines)]
     fn into_response(self) -> axum::response::Response {
         let sentry_event_id = record_error!(self, Self::Internal(_));
 
@@ -140,6 +220,63 @@ impl IntoResponse for RouteError {
             )
                 .into_response(),
 
+            // Pairwise subject identifier, response signing and encryption, and
+            // backchannel authentication errors are all about the client metadata
+            Self::UnsupportedSubjectType
+            | Self::FetchSectorIdentifierUri(_)
+            | Self::RedirectUriNotInSector(_)
+            | Self::MissingSectorIdentifierUri
+            | Self::UnsignedIntrospectionResponse
+            | Self::UnsignedAuthorizationResponse
+            | Self::UnsupportedEncryptionAlgorithm(_)
+            | Self::MissingEncryptionKeys(_)
+            | Self::MissingEncryptionSecret(_)
+            | Self::UnsupportedBackchannelTokenDeliveryMode => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidClientMetadata)
+                        .with_description(self.to_string()),
+                ),
+            )
+                .into_response(),
+
+            Self::InvalidSoftwareStatement(ref e) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidSoftwareStatement)
+                        .with_description(e.to_string()),
+                ),
+            )
+                .into_response(),
+
+            Self::UnapprovedSoftwareStatement(ref e) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::UnapprovedSoftwareStatement)
+                        .with_description(e.to_string()),
+                ),
+            )
+                .into_response(),
+
+            // Unknown clients and invalid tokens are handled the same way, as per RFC 7592
+            Self::InvalidRegistrationAccessToken => (
+                StatusCode::UNAUTHORIZED,
+                [(
+                    WWW_AUTHENTICATE,
+                    HeaderValue::from_static(r#"Bearer error="invalid_token""#),
+                )],
+            )
+                .into_response(),
+
+            Self::ClientIdMismatch | Self::ClientSecretMismatch => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidRequest)
+                        .with_description(self.to_string()),
+                ),
+            )
+                .into_response(),
+
             // For policy violations, we return an `invalid_client_metadata` error with the details
             // of the violations in most cases. If a violation includes `redirect_uri` in the
             // message, we return an `invalid_redirect_uri` error instead.
@@ -212,33 +349,146 @@ fn localised_url_has_public_suffix(url: &Localized<Url>) -> bool {
     url.iter().any(|(_lang, url)| host_is_public_suffix(url))
 }
 
-#[tracing::instrument(name = "handlers.oauth2.registration.post", skip_all)]
-pub(crate) async fn post(
-    mut rng: BoxRng,
-    clock: BoxClock,
-    mut repo: BoxRepository,
-    mut policy: Policy,
-    activity_tracker: BoundActivityTracker,
-    user_agent: Option<TypedHeader<headers::UserAgent>>,
-    State(encrypter): State<Encrypter>,
-    body: Result<Json<ClientMetadata>, axum::extract::rejection::JsonRejection>,
-) -> Result<impl IntoResponse, RouteError> {
-    // Propagate any JSON extraction error
-    let Json(body) = body?;
+/// Check that the client can use the requested subject type, and that its
+/// redirect URIs all belong to the same sector if it uses pairwise subject
+/// identifiers
+async fn check_subject_type(
+    http_client: &reqwest::Client,
+    site_config: &SiteConfig,
+    metadata: &VerifiedClientMetadata,
+) -> Result<(), RouteError> {
+    match &metadata.subject_type {
+        None | Some(SubjectType::Public) => return Ok(()),
+        // Pairwise subject identifiers are derived from a secret, so we can't
+        // issue them if none is configured
+        Some(SubjectType::Pairwise) if site_config.pairwise_subject_secret.is_some() => {}
+        Some(_) => return Err(RouteError::UnsupportedSubjectType),
+    }
 
-    // Sort the properties to ensure a stable serialisation order for hashing
-    let body = body.sorted();
+    let redirect_uris = metadata.redirect_uris();
 
-    // We need to serialize the body to compute the hash, and to log it
-    let body_json = serde_json::to_string(&body)?;
+    let Some(sector_identifier_uri) = &metadata.sector_identifier_uri else {
+        // Without a sector_identifier_uri, the sector is the host of the
+        // redirect URIs, so they must all have the same one
+        let mut hosts = redirect_uris.iter().map(Url::host_str);
+        let first = hosts.next().flatten();
+        if hosts.any(|host| host != first) {
+            return Err(RouteError::MissingSectorIdentifierUri);
+        }
 
-    info!(body = body_json, "Client registration");
+        return Ok(());
+    };
 
-    let user_agent = user_agent.map(|ua| ua.to_string());
+    if host_is_public_suffix(sector_identifier_uri) {
+        return Err(RouteError::UrlIsPublicSuffix("sector_identifier_uri"));
+    }
 
-    // Validate the body
-    let metadata = body.validate()?;
+    // The sector_identifier_uri must point to a JSON array of all the
+    // redirect URIs the client may use
+    let sector_redirect_uris: Vec<Url> = http_client
+        .get(sector_identifier_uri.clone())
+        .send_traced()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(RouteError::FetchSectorIdentifierUri)?
+        .json()
+        .await
+        .map_err(RouteError::FetchSectorIdentifierUri)?;
+
+    if let Some(redirect_uri) = redirect_uris
+        .iter()
+        .find(|redirect_uri| !sector_redirect_uris.contains(redirect_uri))
+    {
+        return Err(RouteError::RedirectUriNotInSector(redirect_uri.clone()));
+    }
+
+    Ok(())
+}
+
+/// Check that we support the algorithms the client asked us to encrypt some
+/// responses with, and that it has a key for them
+fn check_encrypted_response(
+    response: &'static str,
+    algs: Option<(&JsonWebEncryptionAlg, &JsonWebEncryptionEnc)>,
+    metadata: &VerifiedClientMetadata,
+) -> Result<(), RouteError> {
+    let Some((alg, enc)) = algs else {
+        return Ok(());
+    };
+
+    if !SUPPORTED_KEY_MANAGEMENT_ALGORITHMS.contains(alg)
+        || !SUPPORTED_CONTENT_ENCRYPTION_ALGORITHMS.contains(enc)
+    {
+        return Err(RouteError::UnsupportedEncryptionAlgorithm(response));
+    }
+
+    if matches!(
+        alg,
+        JsonWebEncryptionAlg::A128Kw | JsonWebEncryptionAlg::A256Kw
+    ) {
+        // The key is derived from the client secret
+        if !matches!(
+            metadata.token_endpoint_auth_method,
+            Some(
+                OAuthClientAuthenticationMethod::ClientSecretJwt
+                    | OAuthClientAuthenticationMethod::ClientSecretPost
+                    | OAuthClientAuthenticationMethod::ClientSecretBasic,
+            )
+        ) {
+            return Err(RouteError::MissingEncryptionSecret(response));
+        }
+    } else if metadata.jwks.is_none() && metadata.jwks_uri.is_none() {
+        return Err(RouteError::MissingEncryptionKeys(response));
+    }
+
+    Ok(())
+}
+
+/// Clone the encryption algorithms of a response, splitting them in separate
+/// options to be stored
+fn cloned_algs(
+    algs: Option<(&JsonWebEncryptionAlg, &JsonWebEncryptionEnc)>,
+) -> (Option<JsonWebEncryptionAlg>, Option<JsonWebEncryptionEnc>) {
+    algs.map(|(alg, enc)| (alg.clone(), enc.clone())).unzip()
+}
+
+/// Verify the software statement sent along the client metadata, if any, and
+/// let its claims take precedence over the self-asserted metadata
+///
+/// Returns the resulting metadata, and the claims of the verified statement
+async fn apply_software_statement(
+    http_client: &reqwest::Client,
+    site_config: &SiteConfig,
+    clock: &dyn Clock,
+    metadata: ClientMetadata,
+) -> Result<
+    (
+        ClientMetadata,
+        Option<serde_json::Map<String, serde_json::Value>>,
+    ),
+    RouteError,
+> {
+    let Some(software_statement) = &metadata.software_statement else {
+        return Ok((metadata, None));
+    };
+
+    let claims =
+        software_statement::verify(http_client, site_config, clock, software_statement).await?;
+    let metadata = software_statement::apply(&metadata, &claims)?;
+
+    Ok((metadata, Some(claims)))
+}
 
+/// Run the checks on the client metadata which are not done by
+/// [`ClientMetadata::validate`], and evaluate the client registration policy
+async fn check_client_metadata(
+    http_client: &reqwest::Client,
+    site_config: &SiteConfig,
+    policy: &mut Policy,
+    requester: mas_policy::Requester,
+    metadata: &VerifiedClientMetadata,
+    software_statement: Option<&serde_json::Map<String, serde_json::Value>>,
+) -> Result<(), RouteError> {
     // Some extra validation that is hard to do in OPA and not done by the
     // `validate` method either
     if let Some(client_uri) = &metadata.client_uri {
@@ -277,19 +527,132 @@ pub(crate) async fn post(
         }
     }
 
+    for post_logout_redirect_uri in metadata.post_logout_redirect_uris.iter().flatten() {
+        if host_is_public_suffix(post_logout_redirect_uri) {
+            return Err(RouteError::UrlIsPublicSuffix("post_logout_redirect_uri"));
+        }
+    }
+
+    for request_uri in metadata.request_uris.iter().flatten() {
+        if host_is_public_suffix(request_uri) {
+            return Err(RouteError::UrlIsPublicSuffix("request_uri"));
+        }
+    }
+
+    if let Some(backchannel_logout_uri) = &metadata.backchannel_logout_uri
+        && host_is_public_suffix(backchannel_logout_uri)
+    {
+        return Err(RouteError::UrlIsPublicSuffix("backchannel_logout_uri"));
+    }
+
+    if let Some(frontchannel_logout_uri) = &metadata.frontchannel_logout_uri
+        && host_is_public_suffix(frontchannel_logout_uri)
+    {
+        return Err(RouteError::UrlIsPublicSuffix("frontchannel_logout_uri"));
+    }
+
+    if let Some(endpoint) = &metadata.backchannel_client_notification_endpoint
+        && host_is_public_suffix(endpoint)
+    {
+        return Err(RouteError::UrlIsPublicSuffix(
+            "backchannel_client_notification_endpoint",
+        ));
+    }
+
+    // We only support the `poll` and `ping` modes of client-initiated backchannel
+    // authentication
+    if !matches!(
+        metadata.backchannel_token_delivery_mode,
+        None | Some(BackchannelTokenDeliveryMode::Poll | BackchannelTokenDeliveryMode::Ping)
+    ) {
+        return Err(RouteError::UnsupportedBackchannelTokenDeliveryMode);
+    }
+
+    // JWT introspection responses (RFC 9701) can't use the `none` algorithm
+    if metadata.introspection_signed_response_alg == Some(JsonWebSignatureAlg::None) {
+        return Err(RouteError::UnsignedIntrospectionResponse);
+    }
+
+    // Same goes for JWT-secured authorization responses (JARM)
+    if metadata.authorization_signed_response_alg == Some(JsonWebSignatureAlg::None) {
+        return Err(RouteError::UnsignedAuthorizationResponse);
+    }
+
+    check_encrypted_response("id_token", metadata.id_token_encrypted_response(), metadata)?;
+    check_encrypted_response("userinfo", metadata.userinfo_encrypted_response(), metadata)?;
+    check_encrypted_response(
+        "introspection",
+        metadata.introspection_encrypted_response(),
+        metadata,
+    )?;
+    check_encrypted_response(
+        "authorization",
+        metadata.authorization_encrypted_response(),
+        metadata,
+    )?;
+
+    check_subject_type(http_client, site_config, metadata).await?;
+
     let res = policy
         .evaluate_client_registration(mas_policy::ClientRegistrationInput {
-            client_metadata: &metadata,
-            requester: mas_policy::Requester {
-                ip_address: activity_tracker.ip(),
-                user_agent,
-            },
+            client_metadata: metadata,
+            software_statement,
+            requester,
         })
         .await?;
     if !res.valid() {
         return Err(RouteError::PolicyDenied(res));
     }
 
+    Ok(())
+}
+
+#[tracing::instrument(name = "handlers.oauth2.registration.post", skip_all)]
+pub(crate) async fn post(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    mut repo: BoxRepository,
+    mut policy: Policy,
+    activity_tracker: BoundActivityTracker,
+    user_agent: Option<TypedHeader<headers::UserAgent>>,
+    State(encrypter): State<Encrypter>,
+    State(http_client): State<reqwest::Client>,
+    State(site_config): State<SiteConfig>,
+    State(url_builder): State<UrlBuilder>,
+    body: Result<Json<ClientMetadata>, axum::extract::rejection::JsonRejection>,
+) -> Result<impl IntoResponse, RouteError> {
+    // Propagate any JSON extraction error
+    let Json(body) = body?;
+
+    let (body, software_statement) =
+        apply_software_statement(&http_client, &site_config, &clock, body).await?;
+
+    // Sort the properties to ensure a stable serialisation order for hashing
+    let body = body.sorted();
+
+    // We need to serialize the body to compute the hash, and to log it
+    let body_json = serde_json::to_string(&body)?;
+
+    info!(body = body_json, "Client registration");
+
+    let user_agent = user_agent.map(|ua| ua.to_string());
+
+    // Validate the body
+    let metadata = body.validate()?;
+
+    check_client_metadata(
+        &http_client,
+        &site_config,
+        &mut policy,
+        mas_policy::Requester {
+            ip_address: activity_tracker.ip(),
+            user_agent,
+        },
+        &metadata,
+        software_statement.as_ref(),
+    )
+    .await?;
+
     let (client_secret, encrypted_client_secret) = match metadata.token_endpoint_auth_method {
         Some(
             OAuthClientAuthenticationMethod::ClientSecretJwt
@@ -304,6 +667,17 @@ pub(crate) async fn post(
         _ => (None, None),
     };
 
+    // Resolve the default content encryption algorithms, so that they are stored
+    // along the key management ones
+    let (id_token_encrypted_response_alg, id_token_encrypted_response_enc) =
+        cloned_algs(metadata.id_token_encrypted_response());
+    let (userinfo_encrypted_response_alg, userinfo_encrypted_response_enc) =
+        cloned_algs(metadata.userinfo_encrypted_response());
+    let (introspection_encrypted_response_alg, introspection_encrypted_response_enc) =
+        cloned_algs(metadata.introspection_encrypted_response());
+    let (authorization_encrypted_response_alg, authorization_encrypted_response_enc) =
+        cloned_algs(metadata.authorization_encrypted_response());
+
     // If the client doesn't have a secret, we may be able to deduplicate it. To
     // do so, we hash the client metadata, and look for it in the database
     let (digest_hash, existing_client) = if client_secret.is_none() {
@@ -321,67 +695,420 @@ pub(crate) async fn post(
         (None, None)
     };
 
-    let client = if let Some(client) = existing_client {
+    // Deduplicated clients are shared by multiple registrations, so we can't let
+    // any of them manage it. Only new clients get a registration access token.
+    let (client, registration_access_token) = if let Some(client) = existing_client {
         tracing::info!(%client.id, "Reusing existing client");
         REGISTRATION_COUNTER.add(1, &[KeyValue::new(RESULT, "reused")]);
-        client
+        (client, None)
     } else {
+        let registration_access_token = Alphanumeric.sample_string(&mut rng, 32);
         let client = repo
             .oauth2_client()
             .add(
                 &mut rng,
                 &clock,
-                metadata.redirect_uris().to_vec(),
-                digest_hash,
-                encrypted_client_secret,
-                metadata.application_type.clone(),
-                //&metadata.response_types(),
-                metadata.grant_types().to_vec(),
-                metadata
-                    .client_name
-                    .clone()
-                    .map(Localized::to_non_localized),
-                metadata.logo_uri.clone().map(Localized::to_non_localized),
-                metadata.client_uri.clone().map(Localized::to_non_localized),
-                metadata.policy_uri.clone().map(Localized::to_non_localized),
-                metadata.tos_uri.clone().map(Localized::to_non_localized),
-                metadata.jwks_uri.clone(),
-                metadata.jwks.clone(),
-                // XXX: those might not be right, should be function calls
-                metadata.id_token_signed_response_alg.clone(),
-                metadata.userinfo_signed_response_alg.clone(),
-                metadata.token_endpoint_auth_method.clone(),
-                metadata.token_endpoint_auth_signing_alg.clone(),
-                metadata.initiate_login_uri.clone(),
+                OAuth2ClientParams {
+                    redirect_uris: metadata.redirect_uris().to_vec(),
+                    metadata_digest: digest_hash,
+                    encrypted_client_secret,
+                    application_type: metadata.application_type.clone(),
+                    grant_types: metadata.grant_types().to_vec(),
+                    client_name: metadata
+                        .client_name
+                        .clone()
+                        .map(Localized::to_non_localized),
+                    logo_uri: metadata.logo_uri.clone().map(Localized::to_non_localized),
+                    client_uri: metadata.client_uri.clone().map(Localized::to_non_localized),
+                    policy_uri: metadata.policy_uri.clone().map(Localized::to_non_localized),
+                    tos_uri: metadata.tos_uri.clone().map(Localized::to_non_localized),
+                    jwks_uri: metadata.jwks_uri.clone(),
+                    jwks: metadata.jwks.clone(),
+                    // XXX: those might not be right, should be function calls
+                    id_token_signed_response_alg: metadata.id_token_signed_response_alg.clone(),
+                    userinfo_signed_response_alg: metadata.userinfo_signed_response_alg.clone(),
+                    token_endpoint_auth_method: metadata.token_endpoint_auth_method.clone(),
+                    token_endpoint_auth_signing_alg: metadata
+                        .token_endpoint_auth_signing_alg
+                        .clone(),
+                    initiate_login_uri: metadata.initiate_login_uri.clone(),
+                    require_pushed_authorization_requests: metadata
+                        .require_pushed_authorization_requests(),
+                    subject_type: metadata.subject_type.clone(),
+                    sector_identifier_uri: metadata.sector_identifier_uri.clone(),
+                    post_logout_redirect_uris: metadata
+                        .post_logout_redirect_uris
+                        .clone()
+                        .unwrap_or_default(),
+                    request_uris: metadata.request_uris.clone().unwrap_or_default(),
+                    backchannel_logout_uri: metadata.backchannel_logout_uri.clone(),
+                    frontchannel_logout_uri: metadata.frontchannel_logout_uri.clone(),
+                    frontchannel_logout_session_required: metadata
+                        .frontchannel_logout_session_required(),
+                    dpop_bound_access_tokens: metadata.dpop_bound_access_tokens(),
+                    tls_client_auth_subject_dn: metadata.tls_client_auth_subject_dn.clone(),
+                    tls_client_auth_san_dns: metadata.tls_client_auth_san_dns.clone(),
+                    introspection_signed_response_alg: metadata
+                        .introspection_signed_response_alg
+                        .clone(),
+                    backchannel_token_delivery_mode: metadata
+                        .backchannel_token_delivery_mode
+                        .clone(),
+                    backchannel_client_notification_endpoint: metadata
+                        .backchannel_client_notification_endpoint
+                        .clone(),
+                    registration_access_token_hash: Some(hash_registration_access_token(
+                        &registration_access_token,
+                    )),
+                    id_token_encrypted_response_alg,
+                    id_token_encrypted_response_enc,
+                    userinfo_encrypted_response_alg,
+                    userinfo_encrypted_response_enc,
+                    introspection_encrypted_response_alg,
+                    introspection_encrypted_response_enc,
+                    authorization_signed_response_alg: metadata
+                        .authorization_signed_response_alg
+                        .clone(),
+                    authorization_encrypted_response_alg,
+                    authorization_encrypted_response_enc,
+                },
             )
             .await?;
         tracing::info!(%client.id, "Registered new client");
         REGISTRATION_COUNTER.add(1, &[KeyValue::new(RESULT, "created")]);
-        client
+        (client, Some(registration_access_token))
     };
 
+    let response = client_response(
+        &url_builder,
+        client,
+        client_secret,
+        registration_access_token,
+    )?;
+
+    repo.save().await?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Hash a registration access token, to store it or compare it with the one
+/// stored on a client
+fn hash_registration_access_token(token: &str) -> String {
+    hex::encode(sha2::Sha256::digest(token))
+}
+
+/// Build the client information response, as returned by the registration and
+/// client configuration endpoints
+fn client_response(
+    url_builder: &UrlBuilder,
+    client: Client,
+    client_secret: Option<String>,
+    registration_access_token: Option<String>,
+) -> Result<RouteResponse, RouteError> {
+    let registration_client_uri = registration_access_token
+        .is_some()
+        .then(|| url_builder.oauth_client_configuration_endpoint(client.id));
+
     let response = ClientRegistrationResponse {
         client_id: client.client_id.clone(),
         client_secret,
         // XXX: we should have a `created_at` field on the clients
         client_id_issued_at: Some(client.id.datetime().into()),
         client_secret_expires_at: None,
+        registration_access_token,
+        registration_client_uri,
     };
 
     // We round-trip back to the metadata to output it in the response
     // This should never fail, as the client is valid
     let metadata = client.into_metadata().validate()?;
 
+    Ok(RouteResponse { response, metadata })
+}
+
+/// Load the client targeted by a request to the client configuration endpoint,
+/// making sure the request has the right registration access token
+async fn load_managed_client(
+    repo: &mut BoxRepository,
+    client_id: &str,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<(Client, String), RouteError> {
+    let TypedHeader(Authorization(bearer)) =
+        authorization.ok_or(RouteError::InvalidRegistrationAccessToken)?;
+    let token = bearer.token();
+
+    // Unknown clients are handled the same way as invalid tokens, as per RFC 7592
+    let client = repo
+        .oauth2_client()
+        .find_by_client_id(client_id)
+        .await?
+        .ok_or(RouteError::InvalidRegistrationAccessToken)?;
+
+    let Some(expected_hash) = &client.registration_access_token_hash else {
+        return Err(RouteError::InvalidRegistrationAccessToken);
+    };
+
+    if hash_registration_access_token(token) != *expected_hash {
+        return Err(RouteError::InvalidRegistrationAccessToken);
+    }
+
+    Ok((client, token.to_owned()))
+}
+
+/// Decrypt the secret of a client, to include it in client information
+/// responses
+fn decrypt_client_secret(
+    encrypter: &Encrypter,
+    client: &Client,
+) -> Result<Option<String>, RouteError> {
+    let Some(encrypted_client_secret) = &client.encrypted_client_secret else {
+        return Ok(None);
+    };
+
+    let client_secret = encrypter
+        .decrypt_string(encrypted_client_secret)
+        .map_err(|e| RouteError::Internal(e.into()))?;
+    let client_secret =
+        String::from_utf8(client_secret).map_err(|e| RouteError::Internal(e.into()))?;
+    Ok(Some(client_secret))
+}
+
+/// The body of a client update request, as per RFC 7592
+#[derive(Deserialize)]
+pub(crate) struct ClientUpdateRequest {
+    client_id: String,
+
+    #[serde(default)]
+    client_secret: Option<String>,
+
+    #[serde(flatten)]
+    metadata: ClientMetadata,
+}
+
+#[tracing::instrument(
+    name = "handlers.oauth2.registration.get",
+    fields(client.id = client_id),
+    skip_all,
+)]
+pub(crate) async fn get(
+    mut repo: BoxRepository,
+    State(encrypter): State<Encrypter>,
+    State(url_builder): State<UrlBuilder>,
+    Path(client_id): Path<String>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<impl IntoResponse, RouteError> {
+    let (client, registration_access_token) =
+        load_managed_client(&mut repo, &client_id, authorization).await?;
+
+    let client_secret = decrypt_client_secret(&encrypter, &client)?;
+
+    let response = client_response(
+        &url_builder,
+        client,
+        client_secret,
+        Some(registration_access_token),
+    )?;
+
+    repo.cancel().await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+#[tracing::instrument(
+    name = "handlers.oauth2.registration.put",
+    fields(client.id = client_id),
+    skip_all,
+)]
+pub(crate) async fn put(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    mut repo: BoxRepository,
+    mut policy: Policy,
+    activity_tracker: BoundActivityTracker,
+    user_agent: Option<TypedHeader<headers::UserAgent>>,
+    State(encrypter): State<Encrypter>,
+    State(http_client): State<reqwest::Client>,
+    State(site_config): State<SiteConfig>,
+    State(url_builder): State<UrlBuilder>,
+    Path(client_id): Path<String>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    body: Result<Json<ClientUpdateRequest>, axum::extract::rejection::JsonRejection>,
+) -> Result<impl IntoResponse, RouteError> {
+    let (client, registration_access_token) =
+        load_managed_client(&mut repo, &client_id, authorization).await?;
+
+    // Propagate any JSON extraction error
+    let Json(body) = body?;
+
+    // The client ID must match the one of the client being updated, and if
+    // the client secret is provided, it must match the current one
+    if body.client_id != client.client_id {
+        return Err(RouteError::ClientIdMismatch);
+    }
+
+    let current_client_secret = decrypt_client_secret(&encrypter, &client)?;
+    if body.client_secret.is_some() && body.client_secret != current_client_secret {
+        return Err(RouteError::ClientSecretMismatch);
+    }
+
+    let (body, software_statement) =
+        apply_software_statement(&http_client, &site_config, &clock, body.metadata).await?;
+    let body = body.sorted();
+    info!(
+        body = serde_json::to_string(&body)?,
+        "Client registration update"
+    );
+
+    let metadata = body.validate()?;
+
+    check_client_metadata(
+        &http_client,
+        &site_config,
+        &mut policy,
+        mas_policy::Requester {
+            ip_address: activity_tracker.ip(),
+            user_agent: user_agent.map(|ua| ua.to_string()),
+        },
+        &metadata,
+        software_statement.as_ref(),
+    )
+    .await?;
+
+    // Keep the current client secret if the client still needs one, or
+    // generate a new one if it didn't have one before
+    let (client_secret, encrypted_client_secret) = match metadata.token_endpoint_auth_method {
+        Some(
+            OAuthClientAuthenticationMethod::ClientSecretJwt
+            | OAuthClientAuthenticationMethod::ClientSecretPost
+            | OAuthClientAuthenticationMethod::ClientSecretBasic,
+        ) => {
+            if let Some(client_secret) = current_client_secret {
+                (Some(client_secret), client.encrypted_client_secret.clone())
+            } else {
+                let client_secret = Alphanumeric.sample_string(&mut rng, 20);
+                let encrypted_client_secret =
+                    encrypter.encrypt_to_string(client_secret.as_bytes())?;
+                (Some(client_secret), Some(encrypted_client_secret))
+            }
+        }
+        _ => (None, None),
+    };
+
+    // Resolve the default content encryption algorithms, so that they are stored
+    // along the key management ones
+    let (id_token_encrypted_response_alg, id_token_encrypted_response_enc) =
+        cloned_algs(metadata.id_token_encrypted_response());
+    let (userinfo_encrypted_response_alg, userinfo_encrypted_response_enc) =
+        cloned_algs(metadata.userinfo_encrypted_response());
+    let (introspection_encrypted_response_alg, introspection_encrypted_response_enc) =
+        cloned_algs(metadata.introspection_encrypted_response());
+    let (authorization_encrypted_response_alg, authorization_encrypted_response_enc) =
+        cloned_algs(metadata.authorization_encrypted_response());
+
+    let jwks = match (metadata.jwks.clone(), metadata.jwks_uri.clone()) {
+        (Some(jwks), _) => Some(JwksOrJwksUri::Jwks(jwks)),
+        (None, Some(jwks_uri)) => Some(JwksOrJwksUri::JwksUri(jwks_uri)),
+        (None, None) => None,
+    };
+
+    let client = repo
+        .oauth2_client()
+        .update(Client {
+            // The client isn't a candidate for deduplication anymore
+            metadata_digest: None,
+            encrypted_client_secret,
+            application_type: metadata.application_type.clone(),
+            redirect_uris: metadata.redirect_uris().to_vec(),
+            grant_types: metadata.grant_types().to_vec(),
+            client_name: metadata
+                .client_name
+                .clone()
+                .map(Localized::to_non_localized),
+            logo_uri: metadata.logo_uri.clone().map(Localized::to_non_localized),
+            client_uri: metadata.client_uri.clone().map(Localized::to_non_localized),
+            policy_uri: metadata.policy_uri.clone().map(Localized::to_non_localized),
+            tos_uri: metadata.tos_uri.clone().map(Localized::to_non_localized),
+            jwks,
+            id_token_signed_response_alg: metadata.id_token_signed_response_alg.clone(),
+            userinfo_signed_response_alg: metadata.userinfo_signed_response_alg.clone(),
+            introspection_signed_response_alg: metadata.introspection_signed_response_alg.clone(),
+            token_endpoint_auth_method: metadata.token_endpoint_auth_method.clone(),
+            token_endpoint_auth_signing_alg: metadata.token_endpoint_auth_signing_alg.clone(),
+            initiate_login_uri: metadata.initiate_login_uri.clone(),
+            require_pushed_authorization_requests: metadata.require_pushed_authorization_requests(),
+            subject_type: metadata.subject_type.clone(),
+            sector_identifier_uri: metadata.sector_identifier_uri.clone(),
+            post_logout_redirect_uris: metadata
+                .post_logout_redirect_uris
+                .clone()
+                .unwrap_or_default(),
+            request_uris: metadata.request_uris.clone().unwrap_or_default(),
+            backchannel_logout_uri: metadata.backchannel_logout_uri.clone(),
+            frontchannel_logout_uri: metadata.frontchannel_logout_uri.clone(),
+            frontchannel_logout_session_required: metadata.frontchannel_logout_session_required(),
+            dpop_bound_access_tokens: metadata.dpop_bound_access_tokens(),
+            tls_client_auth_subject_dn: metadata.tls_client_auth_subject_dn.clone(),
+            tls_client_auth_san_dns: metadata.tls_client_auth_san_dns.clone(),
+            backchannel_token_delivery_mode: metadata.backchannel_token_delivery_mode.clone(),
+            backchannel_client_notification_endpoint: metadata
+                .backchannel_client_notification_endpoint
+                .clone(),
+            id_token_encrypted_response_alg,
+            id_token_encrypted_response_enc,
+            userinfo_encrypted_response_alg,
+            userinfo_encrypted_response_enc,
+            introspection_encrypted_response_alg,
+            introspection_encrypted_response_enc,
+            authorization_signed_response_alg: metadata.authorization_signed_response_alg.clone(),
+            authorization_encrypted_response_alg,
+            authorization_encrypted_response_enc,
+            ..client
+        })
+        .await?;
+    tracing::info!(%client.id, "Updated client");
+    REGISTRATION_COUNTER.add(1, &[KeyValue::new(RESULT, "updated")]);
+
+    let response = client_response(
+        &url_builder,
+        client,
+        client_secret,
+        Some(registration_access_token),
+    )?;
+
     repo.save().await?;
 
-    let response = RouteResponse { response, metadata };
+    Ok((StatusCode::OK, Json(response)))
+}
 
-    Ok((StatusCode::CREATED, Json(response)))
+#[tracing::instrument(
+    name = "handlers.oauth2.registration.delete",
+    fields(client.id = client_id),
+    skip_all,
+)]
+pub(crate) async fn delete(
+    mut repo: BoxRepository,
+    Path(client_id): Path<String>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<impl IntoResponse, RouteError> {
+    let (client, _) = load_managed_client(&mut repo, &client_id, authorization).await?;
+
+    tracing::info!(%client.id, "Deleting client");
+    repo.oauth2_client().delete(client).await?;
+    REGISTRATION_COUNTER.add(1, &[KeyValue::new(RESULT, "deleted")]);
+
+    repo.save().await?;
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use hyper::{Request, StatusCode};
+    use mas_data_model::{JwksOrJwksUri, SoftwareStatementPublisher};
+    use mas_iana::jose::JsonWebSignatureAlg;
+    use mas_jose::jwt::{JsonWebSignatureHeader, Jwt};
     use mas_router::SimpleRoute;
     use oauth2_types::{
         errors::{ClientError, ClientErrorCode},
@@ -506,6 +1233,46 @@ mod tests {
             response.error_description.unwrap(),
             "client_uri is not using a valid domain"
         );
+
+        // Using an unsupported encryption algorithm
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/"],
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "token_endpoint_auth_method": "client_secret_basic",
+                "id_token_encrypted_response_alg": "RSA1_5",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidClientMetadata);
+        assert_eq!(
+            response.error_description.unwrap(),
+            "unsupported id_token encryption algorithms"
+        );
+
+        // Using a symmetric encryption algorithm without a client secret
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/"],
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "token_endpoint_auth_method": "none",
+                "userinfo_encrypted_response_alg": "A256KW",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidClientMetadata);
+        assert_eq!(
+            response.error_description.unwrap(),
+            "userinfo encryption with A256KW requires a client secret"
+        );
     }
 
     #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
@@ -567,12 +1334,16 @@ mod tests {
         let response = state.request(request.clone()).await;
         response.assert_status(StatusCode::CREATED);
         let response: ClientRegistrationResponse = response.json();
+        assert!(response.registration_access_token.is_some());
         let client_id = response.client_id;
 
         let response = state.request(request).await;
         response.assert_status(StatusCode::CREATED);
         let response: ClientRegistrationResponse = response.json();
         assert_eq!(response.client_id, client_id);
+        // The deduplicated client can't be managed by the second registration
+        assert!(response.registration_access_token.is_none());
+        assert!(response.registration_client_uri.is_none());
 
         // Check that the order of some properties doesn't matter
         let request =
@@ -615,4 +1386,253 @@ mod tests {
         let response: ClientRegistrationResponse = response.json();
         assert_ne!(response.client_id, client_id);
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_registration_pairwise(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Pairwise clients with redirect URIs on different hosts need a
+        // sector_identifier_uri
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/", "https://example.org/"],
+                "subject_type": "pairwise",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidClientMetadata);
+
+        // Unknown subject types are rejected
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/"],
+                "subject_type": "something",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidClientMetadata);
+
+        // Pairwise clients with all their redirect URIs on the same host are fine
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/", "https://example.com/callback"],
+                "subject_type": "pairwise",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let response: serde_json::Value = response.json();
+        assert_eq!(response["subject_type"], "pairwise");
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_registration_management(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/"],
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "token_endpoint_auth_method": "client_secret_basic",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+        let client_secret = response.client_secret.unwrap();
+        let token = response.registration_access_token.unwrap();
+        let registration_client_uri = response.registration_client_uri.unwrap();
+        assert_eq!(
+            registration_client_uri.path(),
+            format!("/oauth2/registration/{client_id}")
+        );
+        let path = registration_client_uri.path().to_owned();
+
+        // Reading the registration without a token or with the wrong token fails
+        let response = state.request(Request::get(&path).empty()).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+
+        let response = state
+            .request(Request::get(&path).bearer("wrong-token").empty())
+            .await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+
+        // Reading it with the right token works
+        let response = state
+            .request(Request::get(&path).bearer(&token).empty())
+            .await;
+        response.assert_status(StatusCode::OK);
+        let response: serde_json::Value = response.json();
+        assert_eq!(response["client_id"], client_id);
+        assert_eq!(response["client_secret"], client_secret);
+        assert_eq!(
+            response["redirect_uris"],
+            serde_json::json!(["https://example.com/"])
+        );
+
+        // Updating the client with a different client_id fails
+        let response = state
+            .request(Request::put(&path).bearer(&token).json(serde_json::json!({
+                "client_id": "some-other-client",
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "token_endpoint_auth_method": "client_secret_basic",
+            })))
+            .await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidRequest);
+
+        // Updating the client with invalid metadata fails
+        let response = state
+            .request(Request::put(&path).bearer(&token).json(serde_json::json!({
+                "client_id": client_id,
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://github.io/"],
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "token_endpoint_auth_method": "client_secret_basic",
+            })))
+            .await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidRedirectUri);
+
+        // Updating the redirect URIs keeps the client ID and secret
+        let response = state
+            .request(Request::put(&path).bearer(&token).json(serde_json::json!({
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "client_uri": "https://example.com/",
+                "logo_uri": "https://example.com/logo.png",
+                "redirect_uris": ["https://example.com/callback"],
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "token_endpoint_auth_method": "client_secret_basic",
+            })))
+            .await;
+        response.assert_status(StatusCode::OK);
+        let response: serde_json::Value = response.json();
+        assert_eq!(response["client_id"], client_id);
+        assert_eq!(response["client_secret"], client_secret);
+        assert_eq!(response["registration_access_token"], token);
+        assert_eq!(response["logo_uri"], "https://example.com/logo.png");
+        assert_eq!(
+            response["redirect_uris"],
+            serde_json::json!(["https://example.com/callback"])
+        );
+
+        // Deleting the client works, after which the token is no longer valid
+        let response = state
+            .request(Request::delete(&path).bearer(&token).empty())
+            .await;
+        response.assert_status(StatusCode::NO_CONTENT);
+
+        let response = state
+            .request(Request::get(&path).bearer(&token).empty())
+            .await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_registration_software_statement(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool.clone()).await.unwrap();
+
+        // Trust a software publisher, which signs its statements with the test key
+        let mut site_config = state.site_config.clone();
+        site_config.software_statement_publishers = vec![SoftwareStatementPublisher {
+            issuer: "https://publisher.example.com/".to_owned(),
+            jwks: JwksOrJwksUri::Jwks(state.key_store.public_jwks()),
+        }];
+        let key_store = state.key_store.clone();
+        let state = TestState::from_pool_with_site_config(pool, site_config)
+            .await
+            .unwrap();
+
+        let sign = |issuer: &str| {
+            let alg = JsonWebSignatureAlg::Rs256;
+            let key = key_store.signing_key_for_algorithm(&alg).unwrap();
+            let signer = key.params().signing_key_for_alg(&alg).unwrap();
+            let header = JsonWebSignatureHeader::new(alg).with_kid("test-rsa");
+
+            let claims: HashMap<String, serde_json::Value> =
+                serde_json::from_value(serde_json::json!({
+                    "iss": issuer,
+                    "software_id": "example-app",
+                    "client_name": "Official app",
+                    "client_uri": "https://example.com/",
+                    "redirect_uris": ["https://example.com/callback"],
+                }))
+                .unwrap();
+
+            Jwt::<HashMap<String, serde_json::Value>>::sign(header, claims, &signer)
+                .unwrap()
+                .into_string()
+        };
+
+        // The claims of the statement take precedence over the self-asserted
+        // metadata
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_name": "Unofficial app",
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/other-callback"],
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "token_endpoint_auth_method": "none",
+                "software_statement": sign("https://publisher.example.com/"),
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let response: serde_json::Value = response.json();
+        assert_eq!(response["client_name"], "Official app");
+        assert_eq!(
+            response["redirect_uris"],
+            serde_json::json!(["https://example.com/callback"])
+        );
+
+        // Statements from unknown publishers are not approved
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "token_endpoint_auth_method": "none",
+                "software_statement": sign("https://untrusted.example.com/"),
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::UnapprovedSoftwareStatement);
+
+        // Malformed statements are rejected
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "token_endpoint_auth_method": "none",
+                "software_statement": "not-a-jwt",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidSoftwareStatement);
+    }
 }
@@ -0,0 +1,265 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Support for JWT-secured authorization requests, as defined in RFC 9101
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use mas_data_model::{Client, JwksOrJwksUri};
+use mas_http::RequestBuilderExt as _;
+use mas_iana::jose::JsonWebSignatureAlg;
+use mas_jose::{
+    claims::{self, ClaimError, TimeOptions},
+    jwt::{Jwt, JwtDecodeError},
+};
+use mas_router::UrlBuilder;
+use mas_storage::{BoxRepository, Clock};
+use serde_json::Value;
+use thiserror::Error;
+use ulid::Ulid;
+use url::Url;
+
+use crate::impl_from_error_for_route;
+
+/// Claims which are about the request object itself, and are not
+/// authorization request parameters
+const REQUEST_OBJECT_CLAIMS: [&str; 6] = ["iss", "aud", "exp", "iat", "nbf", "jti"];
+
+/// How long to wait for a request object passed by reference to be fetched
+const REQUEST_URI_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum size of a request object passed by reference
+const MAX_REQUEST_OBJECT_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Error)]
+pub(crate) enum RequestObjectError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("both the request and request_uri parameters were provided")]
+    BothRequestAndRequestUri,
+
+    #[error("the request_uri parameter must be an https URL")]
+    InvalidRequestUri,
+
+    #[error("the request_uri was not registered by the client")]
+    UnregisteredRequestUri,
+
+    #[error("failed to fetch the request object from the request_uri")]
+    FetchRequestUri(#[source] reqwest::Error),
+
+    #[error("the request object fetched from the request_uri is too large")]
+    RequestObjectTooLarge,
+
+    #[error("failed to decode the request object")]
+    Decode(#[from] JwtDecodeError),
+
+    #[error("the request object is not signed")]
+    Unsigned,
+
+    #[error("client {0} has no keys to verify request objects with")]
+    NoClientKeys(Ulid),
+
+    #[error("failed to fetch the JWKS of client {client_id}")]
+    FetchJwks {
+        client_id: Ulid,
+        #[source]
+        source: mas_oidc_client::error::JwksError,
+    },
+
+    #[error("invalid request object signature")]
+    InvalidSignature,
+
+    #[error("invalid claims in request object")]
+    InvalidClaims(#[from] ClaimError),
+
+    #[error("the client_id in the request object does not match the client")]
+    ClientIdMismatch,
+
+    #[error("the request object was already used")]
+    Replayed,
+}
+
+impl_from_error_for_route!(RequestObjectError: mas_storage::RepositoryError);
+
+impl RequestObjectError {
+    /// Returns true if the error is an internal error, not caused by the client
+    pub(crate) fn is_internal(&self) -> bool {
+        matches!(self, Self::Internal(_) | Self::FetchJwks { .. })
+    }
+
+    /// Returns true if the error is about the `request_uri` parameter, rather
+    /// than the request object itself
+    pub(crate) fn is_request_uri_error(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidRequestUri
+                | Self::UnregisteredRequestUri
+                | Self::FetchRequestUri(_)
+                | Self::RequestObjectTooLarge
+        )
+    }
+}
+
+/// Resolve the authorization request parameters, taking into account the
+/// request object passed through the `request` or `request_uri` parameters, if
+/// any.
+///
+/// As per RFC 9101 section 6.3, when a request object is used, only the
+/// parameters it contains are used, apart from the `client_id` which is needed
+/// to find the client it is signed by.
+///
+/// # Errors
+///
+/// Returns an error if the request object could not be fetched, is not signed
+/// by the client, is invalid or was already used.
+pub(crate) async fn resolve_parameters(
+    http_client: &reqwest::Client,
+    url_builder: &UrlBuilder,
+    clock: &dyn Clock,
+    repo: &mut BoxRepository,
+    client: &Client,
+    mut parameters: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, RequestObjectError> {
+    let request = parameters.remove("request");
+    let request_uri = parameters.remove("request_uri");
+
+    let request = match (request, request_uri) {
+        (None, None) => return Ok(parameters),
+        (Some(_), Some(_)) => return Err(RequestObjectError::BothRequestAndRequestUri),
+        (Some(request), None) => request,
+        (None, Some(request_uri)) => {
+            fetch_request_object(http_client, client, &request_uri).await?
+        }
+    };
+
+    let mut parameters: BTreeMap<String, String> =
+        parameters.remove_entry("client_id").into_iter().collect();
+
+    let jwt: Jwt<'static, HashMap<String, Value>> = Jwt::try_from(request)?;
+
+    if jwt.header().alg() == &JsonWebSignatureAlg::None {
+        return Err(RequestObjectError::Unsigned);
+    }
+
+    let jwks = match client.jwks.as_ref() {
+        Some(JwksOrJwksUri::Jwks(jwks)) => jwks.clone(),
+        Some(JwksOrJwksUri::JwksUri(jwks_uri)) => {
+            mas_oidc_client::requests::jose::fetch_jwks(http_client, jwks_uri)
+                .await
+                .map_err(|source| RequestObjectError::FetchJwks {
+                    client_id: client.id,
+                    source,
+                })?
+        }
+        None => return Err(RequestObjectError::NoClientKeys(client.id)),
+    };
+
+    jwt.verify_with_jwks(&jwks)
+        .map_err(|_| RequestObjectError::InvalidSignature)?;
+
+    let (_header, mut claims) = jwt.into_parts();
+
+    // The request object must be issued by the client and intended for us
+    let issuer = url_builder.oidc_issuer().to_string();
+    let time_options = TimeOptions::new(clock.now());
+    claims::ISS.extract_optional_with_options(&mut claims, client.client_id.as_str())?;
+    claims::AUD.extract_optional_with_options(&mut claims, &issuer)?;
+    claims::NBF.extract_optional_with_options(&mut claims, &time_options)?;
+
+    // The expiration and the JWT ID are required to detect replays
+    let exp = claims::EXP.extract_required_with_options(&mut claims, &time_options)?;
+    let jti = claims::JTI.extract_required(&mut claims)?;
+
+    if claims
+        .get("client_id")
+        .is_some_and(|client_id| client_id.as_str() != Some(client.client_id.as_str()))
+    {
+        return Err(RequestObjectError::ClientIdMismatch);
+    }
+
+    let first_use = repo
+        .oauth2_used_jwt_id()
+        .mark_used(clock, &client.client_id, &jti, *exp)
+        .await?;
+    if !first_use {
+        return Err(RequestObjectError::Replayed);
+    }
+
+    for (name, value) in claims {
+        if REQUEST_OBJECT_CLAIMS.contains(&name.as_str())
+            || name == "request"
+            || name == "request_uri"
+        {
+            continue;
+        }
+
+        // Parameters which are not strings, like the `claims` parameter, are
+        // passed as JSON-encoded strings, like they would be in a plain request
+        let value = match value {
+            Value::Null => continue,
+            Value::String(value) => value,
+            value => value.to_string(),
+        };
+
+        parameters.insert(name, value);
+    }
+
+    Ok(parameters)
+}
+
+/// Fetch a request object passed by reference
+///
+/// Only URIs pre-registered by the client are fetched, so that the server
+/// can't be used to make arbitrary requests.
+async fn fetch_request_object(
+    http_client: &reqwest::Client,
+    client: &Client,
+    request_uri: &str,
+) -> Result<String, RequestObjectError> {
+    let request_uri = Url::parse(request_uri).map_err(|_| RequestObjectError::InvalidRequestUri)?;
+    if request_uri.scheme() != "https" {
+        return Err(RequestObjectError::InvalidRequestUri);
+    }
+
+    if !client.has_request_uri(&request_uri) {
+        return Err(RequestObjectError::UnregisteredRequestUri);
+    }
+
+    let mut response = http_client
+        .get(request_uri)
+        .header(reqwest::header::ACCEPT, "application/oauth-authz-req+jwt")
+        .timeout(REQUEST_URI_TIMEOUT)
+        .send_traced()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(RequestObjectError::FetchRequestUri)?;
+
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_REQUEST_OBJECT_SIZE as u64)
+    {
+        return Err(RequestObjectError::RequestObjectTooLarge);
+    }
+
+    // The Content-Length header may be missing or lie, so also check the size
+    // as the body is read
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(RequestObjectError::FetchRequestUri)?
+    {
+        if body.len() + chunk.len() > MAX_REQUEST_OBJECT_SIZE {
+            return Err(RequestObjectError::RequestObjectTooLarge);
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(String::from_utf8_lossy(&body).trim().to_owned())
+}
@@ -0,0 +1,94 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Encryption of the ID tokens, userinfo and introspection responses sent to
+//! clients which registered an encryption algorithm for them
+
+use mas_data_model::{Client, JwksOrJwksUri};
+use mas_iana::jose::JsonWebEncryptionAlg;
+use mas_jose::jwe::{JsonWebEncryptionHeader, Jwe, JweEncryptionError};
+use mas_keystore::Encrypter;
+use sha2::{Digest as _, Sha256};
+use thiserror::Error;
+use ulid::Ulid;
+
+#[derive(Debug, Error)]
+pub(crate) enum ResponseEncryptionError {
+    #[error("client {0} has no keys to encrypt responses with")]
+    NoClientKeys(Ulid),
+
+    #[error("client {0} has no secret to encrypt responses with")]
+    NoClientSecret(Ulid),
+
+    #[error("failed to decrypt the secret of client {0}")]
+    DecryptClientSecret(Ulid),
+
+    #[error("failed to fetch the JWKS of client {client_id}")]
+    FetchJwks {
+        client_id: Ulid,
+        #[source]
+        source: mas_oidc_client::error::JwksError,
+    },
+
+    #[error("failed to encrypt the response")]
+    Encryption(#[from] JweEncryptionError),
+}
+
+/// Encrypt a response payload for the given client, using the algorithms set
+/// in the given header.
+///
+/// Asymmetric algorithms use a key from the client JWKS, while the symmetric
+/// `A128KW` and `A256KW` algorithms use a key derived from the client secret,
+/// as per section 10.2 of OpenID Connect Core.
+///
+/// # Errors
+///
+/// Returns an error if the client has no suitable key, if its JWKS could not
+/// be fetched or if the encryption failed.
+pub(crate) async fn encrypt_for_client(
+    rng: &mut (impl rand::RngCore + rand::CryptoRng),
+    http_client: &reqwest::Client,
+    encrypter: &Encrypter,
+    client: &Client,
+    header: JsonWebEncryptionHeader,
+    payload: &[u8],
+) -> Result<String, ResponseEncryptionError> {
+    let key_len = match header.alg() {
+        JsonWebEncryptionAlg::A128Kw => Some(16),
+        JsonWebEncryptionAlg::A256Kw => Some(32),
+        _ => None,
+    };
+
+    let jwe = if let Some(key_len) = key_len {
+        let encrypted_client_secret = client
+            .encrypted_client_secret
+            .as_ref()
+            .ok_or(ResponseEncryptionError::NoClientSecret(client.id))?;
+        let client_secret = encrypter
+            .decrypt_string(encrypted_client_secret)
+            .map_err(|_| ResponseEncryptionError::DecryptClientSecret(client.id))?;
+
+        // The key is the left-most bits of the SHA-256 hash of the secret
+        let key = Sha256::digest(client_secret);
+        Jwe::encrypt_with_shared_secret(rng, header, payload, &key[..key_len])?
+    } else {
+        let jwks = match client.jwks.as_ref() {
+            Some(JwksOrJwksUri::Jwks(jwks)) => jwks.clone(),
+            Some(JwksOrJwksUri::JwksUri(jwks_uri)) => {
+                mas_oidc_client::requests::jose::fetch_jwks(http_client, jwks_uri)
+                    .await
+                    .map_err(|source| ResponseEncryptionError::FetchJwks {
+                        client_id: client.id,
+                        source,
+                    })?
+            }
+            None => return Err(ResponseEncryptionError::NoClientKeys(client.id)),
+        };
+
+        Jwe::encrypt_with_jwks(rng, header, payload, &jwks)?
+    };
+
+    Ok(jwe.into_string())
+}
@@ -263,7 +263,7 @@ mod tests {
 
     use super::*;
     use crate::{
-        oauth2::generate_token_pair,
+        oauth2::{GeneratedAccessToken, generate_token_pair},
         test_utils::{RequestBuilderExt, ResponseExt, TestState, setup},
     };
 
@@ -331,6 +331,7 @@ mod tests {
                 &state.clock,
                 &mut repo,
                 &session,
+                GeneratedAccessToken::opaque(&mut state.rng(), &state.clock),
                 Duration::microseconds(5 * 60 * 1000 * 1000),
             )
             .await
@@ -398,6 +399,7 @@ mod tests {
                 &state.clock,
                 &mut repo,
                 &session,
+                GeneratedAccessToken::opaque(&mut state.rng(), &state.clock),
                 Duration::microseconds(5 * 60 * 1000 * 1000),
             )
             .await
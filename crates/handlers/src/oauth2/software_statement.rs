@@ -0,0 +1,144 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Support for software statements in dynamic client registration, as defined
+//! in RFC 7591
+
+use std::collections::HashMap;
+
+use mas_data_model::{JwksOrJwksUri, SiteConfig};
+use mas_iana::jose::JsonWebSignatureAlg;
+use mas_jose::{
+    claims::{self, ClaimError, TimeOptions},
+    jwt::{Jwt, JwtDecodeError},
+};
+use mas_storage::Clock;
+use oauth2_types::registration::ClientMetadata;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// Registered JWT claims which are not client metadata, and are therefore not
+/// merged into the metadata of the client
+const REGISTERED_CLAIMS: [&str; 7] = ["iss", "sub", "aud", "exp", "nbf", "iat", "jti"];
+
+#[derive(Debug, Error)]
+pub(crate) enum SoftwareStatementError {
+    #[error("failed to decode the software statement")]
+    Decode(#[from] JwtDecodeError),
+
+    #[error("the software statement is not signed")]
+    Unsigned,
+
+    #[error("the software statement was issued by an untrusted publisher {0:?}")]
+    UntrustedIssuer(Option<String>),
+
+    #[error("failed to fetch the JWKS of software publisher {issuer:?}")]
+    FetchJwks {
+        issuer: String,
+        #[source]
+        source: mas_oidc_client::error::JwksError,
+    },
+
+    #[error("invalid software statement signature")]
+    InvalidSignature,
+
+    #[error("invalid claims in software statement")]
+    InvalidClaims(#[from] ClaimError),
+
+    #[error("the claims of the software statement are not valid client metadata")]
+    InvalidMetadata(#[source] serde_json::Error),
+}
+
+impl SoftwareStatementError {
+    /// Returns true if the error is an internal error, not caused by the client
+    pub(crate) fn is_internal(&self) -> bool {
+        matches!(self, Self::FetchJwks { .. })
+    }
+
+    /// Returns true if the statement is well-formed, but not issued by a
+    /// publisher we trust
+    pub(crate) fn is_unapproved(&self) -> bool {
+        matches!(self, Self::UntrustedIssuer(_))
+    }
+}
+
+/// Verify a software statement against the software publishers trusted in the
+/// site configuration, and return its claims.
+///
+/// # Errors
+///
+/// Returns an error if the statement is malformed, not signed by a trusted
+/// publisher, or expired.
+pub(crate) async fn verify(
+    http_client: &reqwest::Client,
+    site_config: &SiteConfig,
+    clock: &dyn Clock,
+    software_statement: &str,
+) -> Result<Map<String, Value>, SoftwareStatementError> {
+    let jwt: Jwt<'_, HashMap<String, Value>> = Jwt::try_from(software_statement)?;
+
+    if jwt.header().alg() == &JsonWebSignatureAlg::None {
+        return Err(SoftwareStatementError::Unsigned);
+    }
+
+    // Find out which keys to use from the (not yet verified) issuer
+    let issuer = jwt.payload().get("iss").and_then(Value::as_str);
+    let publisher = site_config
+        .software_statement_publishers
+        .iter()
+        .find(|publisher| Some(publisher.issuer.as_str()) == issuer)
+        .ok_or_else(|| SoftwareStatementError::UntrustedIssuer(issuer.map(ToOwned::to_owned)))?;
+
+    let jwks = match &publisher.jwks {
+        JwksOrJwksUri::Jwks(jwks) => jwks.clone(),
+        JwksOrJwksUri::JwksUri(jwks_uri) => {
+            mas_oidc_client::requests::jose::fetch_jwks(http_client, jwks_uri)
+                .await
+                .map_err(|source| SoftwareStatementError::FetchJwks {
+                    issuer: publisher.issuer.clone(),
+                    source,
+                })?
+        }
+    };
+
+    jwt.verify_with_jwks(&jwks)
+        .map_err(|_| SoftwareStatementError::InvalidSignature)?;
+
+    let (_header, mut claims) = jwt.into_parts();
+
+    // Software statements are usually long-lived, so the expiration is
+    // optional, but enforced if present
+    let time_options = TimeOptions::new(clock.now());
+    claims::EXP.extract_optional_with_options(&mut claims, &time_options)?;
+    claims::NBF.extract_optional_with_options(&mut claims, &time_options)?;
+
+    Ok(claims.into_iter().collect())
+}
+
+/// Merge the claims of a verified software statement into the client metadata.
+///
+/// The claims of the statement take precedence over the values sent by the
+/// client in plain text.
+///
+/// # Errors
+///
+/// Returns an error if the claims don't form valid client metadata.
+pub(crate) fn apply(
+    metadata: &ClientMetadata,
+    claims: &Map<String, Value>,
+) -> Result<ClientMetadata, SoftwareStatementError> {
+    let mut merged =
+        serde_json::to_value(metadata).map_err(SoftwareStatementError::InvalidMetadata)?;
+
+    if let Value::Object(merged) = &mut merged {
+        for (key, value) in claims {
+            if !REGISTERED_CLAIMS.contains(&key.as_str()) {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    serde_json::from_value(merged).map_err(SoftwareStatementError::InvalidMetadata)
+}
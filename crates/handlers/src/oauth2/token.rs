@@ -4,9 +4,12 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use std::sync::{Arc, LazyLock};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock},
+};
 
-use axum::{Json, extract::State, response::IntoResponse};
+use axum::{Json, extract::State, http::Method, response::IntoResponse};
 use axum_extra::typed_header::TypedHeader;
 use chrono::Duration;
 use headers::{CacheControl, HeaderMap, HeaderMapExt, Pragma};
@@ -16,9 +19,11 @@ use mas_axum_utils::{
     record_error,
 };
 use mas_data_model::{
-    AuthorizationGrantStage, Client, Device, DeviceCodeGrantState, SiteConfig, TokenType,
+    AccessToken, AuthorizationGrantStage, Client, Device, DeviceCodeGrantState, RefreshToken,
+    Session, SiteConfig, TokenType,
 };
 use mas_i18n::DataLocale;
+use mas_iana::oauth::{OAuthAccessTokenType, OAuthClientAuthenticationMethod};
 use mas_keystore::{Encrypter, Keystore};
 use mas_matrix::HomeserverConnection;
 use mas_oidc_client::types::scope::ScopeToken;
@@ -27,18 +32,20 @@ use mas_router::UrlBuilder;
 use mas_storage::{
     BoxClock, BoxRepository, BoxRng, Clock, RepositoryAccess,
     oauth2::{
-        OAuth2AccessTokenRepository, OAuth2AuthorizationGrantRepository,
+        OAuth2AccessTokenRepository, OAuth2AuthorizationGrantRepository, OAuth2ClientRepository,
         OAuth2RefreshTokenRepository, OAuth2SessionRepository,
     },
-    user::BrowserSessionRepository,
+    queue::{QueueJobRepositoryExt as _, SendBackchannelLogoutJob, SyncDevicesJob},
+    user::{BrowserSessionRepository, UserRepository},
 };
 use mas_templates::{DeviceNameContext, TemplateContext, Templates};
 use oauth2_types::{
     errors::{ClientError, ClientErrorCode},
     pkce::CodeChallengeError,
     requests::{
-        AccessTokenRequest, AccessTokenResponse, AuthorizationCodeGrant, ClientCredentialsGrant,
-        DeviceCodeGrant, GrantType, RefreshTokenGrant,
+        AccessTokenRequest, AccessTokenResponse, ActorClaim, AuthorizationCodeGrant,
+        ClientCredentialsGrant, GrantType, JwtBearerGrant, RefreshTokenGrant, TokenExchangeGrant,
+        TokenTypeIdentifier,
     },
     scope,
 };
@@ -46,8 +53,16 @@ use opentelemetry::{Key, KeyValue, metrics::Counter};
 use thiserror::Error;
 use tracing::{debug, info, warn};
 use ulid::Ulid;
-
-use super::{generate_id_token, generate_token_pair};
+use url::Url;
+
+use super::{
+    AccessTokenGenerationError,
+    dpop::{self, DpopError},
+    encrypt_id_token, generate_access_token, generate_id_token, generate_token_pair,
+    is_known_resource,
+    jwt_bearer::{self, JwtBearerError},
+    load_requested_claims, unsupported_authorization_details_type,
+};
 use crate::{BoundActivityTracker, METER, impl_from_error_for_route};
 
 static TOKEN_REQUEST_COUNTER: LazyLock<Counter<u64>> = LazyLock::new(|| {
@@ -127,6 +142,9 @@ pub(crate) enum RouteError {
     #[error("failed to load oauth session {0}")]
     NoSuchOAuthSession(Ulid),
 
+    #[error("failed to load user {0}")]
+    NoSuchUser(Ulid),
+
     #[error(
         "failed to load the next refresh token ({next:?}) from the previous one ({previous:?})"
     )]
@@ -157,6 +175,50 @@ pub(crate) enum RouteError {
 
     #[error("failed to provision device")]
     ProvisionDeviceFailed(#[source] anyhow::Error),
+
+    #[error("invalid DPoP proof")]
+    InvalidDpopProof(#[source] DpopError),
+
+    #[error("the client certificate does not match the one session {0} is bound to")]
+    CertificateMismatch(Ulid),
+
+    #[error("the {0} token to exchange is invalid")]
+    InvalidExchangedToken(&'static str),
+
+    #[error("unsupported token type {0}")]
+    UnsupportedTokenType(TokenTypeIdentifier),
+
+    #[error("the requested scope is not allowed")]
+    InvalidScope,
+
+    #[error("the requested audience {0:?} is not allowed")]
+    InvalidTarget(String),
+
+    #[error("invalid assertion")]
+    InvalidAssertion(#[source] JwtBearerError),
+
+    #[error("unsupported authorization details type {0:?}")]
+    UnsupportedAuthorizationDetailsType(String),
+}
+
+impl From<DpopError> for RouteError {
+    fn from(e: DpopError) -> Self {
+        if e.is_internal() {
+            Self::Internal(Box::new(e))
+        } else {
+            Self::InvalidDpopProof(e)
+        }
+    }
+}
+
+impl From<JwtBearerError> for RouteError {
+    fn from(e: JwtBearerError) -> Self {
+        if e.is_internal() {
+            Self::Internal(Box::new(e))
+        } else {
+            Self::InvalidAssertion(e)
+        }
+    }
 }
 
 impl IntoResponse for RouteError {
@@ -167,6 +229,7 @@ impl IntoResponse for RouteError {
                 | Self::ClientCredentialsVerification { .. }
                 | Self::NoSuchBrowserSession(_)
                 | Self::NoSuchOAuthSession(_)
+                | Self::NoSuchUser(_)
                 | Self::ProvisionDeviceFailed(_)
                 | Self::NoSuchNextRefreshToken { .. }
                 | Self::NoSuchNextAccessToken { .. }
@@ -180,6 +243,7 @@ impl IntoResponse for RouteError {
             | Self::ClientCredentialsVerification { .. }
             | Self::NoSuchBrowserSession(_)
             | Self::NoSuchOAuthSession(_)
+            | Self::NoSuchUser(_)
             | Self::ProvisionDeviceFailed(_)
             | Self::NoSuchNextRefreshToken { .. }
             | Self::NoSuchNextAccessToken { .. }
@@ -193,6 +257,32 @@ impl IntoResponse for RouteError {
                 Json(ClientError::from(ClientErrorCode::InvalidRequest)),
             ),
 
+            Self::InvalidExchangedToken(_) | Self::UnsupportedTokenType(_) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidRequest)
+                        .with_description(self.to_string()),
+                ),
+            ),
+
+            Self::InvalidScope => (
+                StatusCode::BAD_REQUEST,
+                Json(ClientError::from(ClientErrorCode::InvalidScope)),
+            ),
+
+            Self::InvalidTarget(_) => (
+                StatusCode::BAD_REQUEST,
+                Json(ClientError::from(ClientErrorCode::InvalidTarget)),
+            ),
+
+            Self::UnsupportedAuthorizationDetailsType(_) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidAuthorizationDetails)
+                        .with_description(self.to_string()),
+                ),
+            ),
+
             Self::PkceVerification(err) => (
                 StatusCode::BAD_REQUEST,
                 Json(
@@ -248,15 +338,32 @@ impl IntoResponse for RouteError {
             | Self::RefreshTokenInvalid(_)
             | Self::SessionInvalid(_)
             | Self::ClientIDMismatch { .. }
+            | Self::CertificateMismatch(_)
             | Self::GrantNotFound => (
                 StatusCode::BAD_REQUEST,
                 Json(ClientError::from(ClientErrorCode::InvalidGrant)),
             ),
 
+            Self::InvalidAssertion(err) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidGrant)
+                        .with_description(err.to_string()),
+                ),
+            ),
+
             Self::UnsupportedGrantType => (
                 StatusCode::BAD_REQUEST,
                 Json(ClientError::from(ClientErrorCode::UnsupportedGrantType)),
             ),
+
+            Self::InvalidDpopProof(err) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidDpopProof)
+                        .with_description(err.to_string()),
+                ),
+            ),
         };
 
         (sentry_event_id, response).into_response()
@@ -268,6 +375,8 @@ impl_from_error_for_route!(mas_templates::TemplateError);
 impl_from_error_for_route!(mas_storage::RepositoryError);
 impl_from_error_for_route!(mas_policy::EvaluationError);
 impl_from_error_for_route!(super::IdTokenSignatureError);
+impl_from_error_for_route!(super::ResponseEncryptionError);
+impl_from_error_for_route!(AccessTokenGenerationError);
 
 #[tracing::instrument(
     name = "handlers.oauth2.token.post",
@@ -288,6 +397,7 @@ pub(crate) async fn post(
     State(templates): State<Templates>,
     policy: Policy,
     user_agent: Option<TypedHeader<headers::UserAgent>>,
+    request_headers: HeaderMap,
     client_authorization: ClientAuthorization<AccessTokenRequest>,
 ) -> Result<impl IntoResponse, RouteError> {
     let user_agent = user_agent.map(|ua| ua.as_str().to_owned());
@@ -326,7 +436,27 @@ pub(crate) async fn post(
 
     let grant_type = form.grant_type();
 
-    let (reply, repo) = match form {
+    // Check the DPoP proof, which binds the issued tokens to the client key
+    let dpop_jkt = dpop::verify_proof(
+        &clock,
+        &mut repo,
+        &request_headers,
+        &Method::POST,
+        &url_builder.oauth_token_endpoint(),
+    )
+    .await?;
+
+    if client.dpop_bound_access_tokens && dpop_jkt.is_none() {
+        return Err(DpopError::Required.into());
+    }
+    let dpop_jkt = dpop_jkt.as_deref();
+
+    // Tokens issued to clients which presented a TLS client certificate are bound
+    // to it
+    let x5t_s256 = client_authorization.credentials.certificate_thumbprint();
+    let x5t_s256 = x5t_s256.as_deref();
+
+    let (mut reply, repo) = match form {
         AccessTokenRequest::AuthorizationCode(grant) => {
             authorization_code_grant(
                 &mut rng,
@@ -341,6 +471,8 @@ pub(crate) async fn post(
                 &homeserver,
                 &templates,
                 user_agent,
+                dpop_jkt,
+                x5t_s256,
             )
             .await?
         }
@@ -351,9 +483,13 @@ pub(crate) async fn post(
                 &activity_tracker,
                 &grant,
                 &client,
+                &key_store,
+                &url_builder,
                 &site_config,
                 repo,
                 user_agent,
+                dpop_jkt,
+                x5t_s256,
             )
             .await?
         }
@@ -364,26 +500,90 @@ pub(crate) async fn post(
                 &activity_tracker,
                 &grant,
                 &client,
+                &key_store,
+                &url_builder,
                 &site_config,
                 repo,
                 policy,
                 user_agent,
+                dpop_jkt,
+                x5t_s256,
             )
             .await?
         }
         AccessTokenRequest::DeviceCode(grant) => {
             device_code_grant(
+                &mut rng,
+                &clock,
+                &activity_tracker,
+                &grant.device_code,
+                &GrantType::DeviceCode,
+                &client,
+                &key_store,
+                &url_builder,
+                &site_config,
+                repo,
+                &homeserver,
+                user_agent,
+                dpop_jkt,
+                x5t_s256,
+            )
+            .await?
+        }
+        AccessTokenRequest::BackchannelAuthentication(grant) => {
+            device_code_grant(
+                &mut rng,
+                &clock,
+                &activity_tracker,
+                &grant.auth_req_id,
+                &GrantType::ClientInitiatedBackchannelAuthentication,
+                &client,
+                &key_store,
+                &url_builder,
+                &site_config,
+                repo,
+                &homeserver,
+                user_agent,
+                dpop_jkt,
+                x5t_s256,
+            )
+            .await?
+        }
+        AccessTokenRequest::TokenExchange(grant) => {
+            token_exchange_grant(
+                &mut rng,
+                &clock,
+                &activity_tracker,
+                &grant,
+                &client,
+                &key_store,
+                &url_builder,
+                &site_config,
+                repo,
+                policy,
+                user_agent,
+                dpop_jkt,
+                x5t_s256,
+            )
+            .await?
+        }
+        AccessTokenRequest::JwtBearer(grant) => {
+            jwt_bearer_grant(
                 &mut rng,
                 &clock,
                 &activity_tracker,
                 &grant,
                 &client,
+                &http_client,
                 &key_store,
                 &url_builder,
                 &site_config,
                 repo,
                 &homeserver,
+                policy,
                 user_agent,
+                dpop_jkt,
+                x5t_s256,
             )
             .await?
         }
@@ -392,6 +592,20 @@ pub(crate) async fn post(
         }
     };
 
+    // Encrypt the ID token if the client registered an encryption algorithm for
+    // them
+    if let Some(id_token) = reply.id_token.take() {
+        reply.id_token =
+            Some(encrypt_id_token(&mut rng, &http_client, &encrypter, &client, id_token).await?);
+    }
+
+    // Tokens bound to a DPoP key are not bearer tokens
+    let reply = if dpop_jkt.is_some() {
+        reply.with_token_type(OAuthAccessTokenType::DPoP)
+    } else {
+        reply
+    };
+
     repo.save().await?;
 
     TOKEN_REQUEST_COUNTER.add(
@@ -409,6 +623,60 @@ pub(crate) async fn post(
     Ok((headers, Json(reply)))
 }
 
+/// Bind the tokens of a session to the TLS client certificate presented by the
+/// client, as per RFC 8705
+///
+/// Clients which authenticate with their certificate may rotate it, but other
+/// clients must keep presenting the certificate the session is bound to.
+async fn bind_certificate(
+    repo: &mut BoxRepository,
+    client: &Client,
+    session: Session,
+    x5t_s256: Option<&str>,
+) -> Result<Session, RouteError> {
+    let authenticated_with_certificate = matches!(
+        client.token_endpoint_auth_method,
+        Some(
+            OAuthClientAuthenticationMethod::TlsClientAuth
+                | OAuthClientAuthenticationMethod::SelfSignedTlsClientAuth
+        )
+    );
+
+    match (session.x5t_s256.as_deref(), x5t_s256) {
+        (None, None) => Ok(session),
+        (Some(bound), Some(x5t_s256)) if bound == x5t_s256 => Ok(session),
+        (Some(_), None) => Err(RouteError::CertificateMismatch(session.id)),
+        (Some(_), Some(_)) if !authenticated_with_certificate => {
+            Err(RouteError::CertificateMismatch(session.id))
+        }
+        (_, Some(x5t_s256)) => Ok(repo
+            .oauth2_session()
+            .set_x5t_s256(session, x5t_s256.to_owned())
+            .await?),
+    }
+}
+
+/// Check that the resource server a client asked a token for through the
+/// `resource` parameter is one the tokens of the session are intended for.
+///
+/// The audience of a session is fixed when it starts, so the `resource`
+/// parameter can only repeat it and not change it.
+fn check_session_resource(session: &Session, resource: Option<&Url>) -> Result<(), RouteError> {
+    let Some(resource) = resource else {
+        return Ok(());
+    };
+
+    if session
+        .audience
+        .iter()
+        .any(|audience| audience == resource.as_str())
+    {
+        Ok(())
+    } else {
+        Err(RouteError::InvalidTarget(resource.to_string()))
+    }
+}
+
 #[allow(clippy::too_many_lines)] // TODO: refactor some parts out
 async fn authorization_code_grant(
     mut rng: &mut BoxRng,
@@ -423,6 +691,8 @@ async fn authorization_code_grant(
     homeserver: &Arc<dyn HomeserverConnection>,
     templates: &Templates,
     user_agent: Option<String>,
+    dpop_jkt: Option<&str>,
+    x5t_s256: Option<&str>,
 ) -> Result<(AccessTokenResponse, BoxRepository), RouteError> {
     // Check that the client is allowed to use this grant type
     if !client.grant_types.contains(&GrantType::AuthorizationCode) {
@@ -459,6 +729,9 @@ async fn authorization_code_grant(
                     .ok_or(RouteError::NoSuchOAuthSession(session_id))?;
 
                 //if !session.is_finished() {
+                repo.queue_job()
+                    .schedule_job(&mut rng, clock, SendBackchannelLogoutJob::new(&session))
+                    .await?;
                 repo.oauth2_session().finish(clock, session).await?;
                 repo.save().await?;
                 //}
@@ -514,6 +787,11 @@ async fn authorization_code_grant(
         });
     }
 
+    let session = dpop::bind_session(&mut repo, session, dpop_jkt).await?;
+    let session = bind_certificate(&mut repo, client, session, x5t_s256).await?;
+
+    check_session_resource(&session, grant.resource.as_ref())?;
+
     match (code.pkce.as_ref(), grant.code_verifier.as_ref()) {
         (None, None) => {}
         // We have a challenge but no verifier (or vice-versa)? Bad request.
@@ -541,20 +819,53 @@ async fn authorization_code_grant(
         .await?;
 
     let ttl = site_config.access_token_ttl;
-    let (access_token, refresh_token) =
-        generate_token_pair(&mut rng, clock, &mut repo, &session, ttl).await?;
+    let generated_access_token = generate_access_token(
+        &mut rng,
+        clock,
+        &mut repo,
+        url_builder,
+        key_store,
+        site_config,
+        client,
+        &session,
+        ttl,
+    )
+    .await?;
+    let (access_token, refresh_token) = generate_token_pair(
+        &mut rng,
+        clock,
+        &mut repo,
+        &session,
+        generated_access_token,
+        ttl,
+    )
+    .await?;
 
     let id_token = if session.scope.contains(&scope::OPENID) {
+        let user_claims = load_requested_claims(
+            &mut repo,
+            client,
+            &browser_session.user,
+            &session.scope,
+            authz_grant
+                .requested_claims
+                .as_ref()
+                .and_then(|claims| claims.id_token.as_ref()),
+        )
+        .await?;
+
         Some(generate_id_token(
             &mut rng,
             clock,
             url_builder,
             key_store,
+            site_config,
             client,
             Some(&authz_grant),
             &browser_session,
             Some(&access_token),
             last_authentication.as_ref(),
+            user_claims,
         )?)
     } else {
         None
@@ -569,6 +880,10 @@ async fn authorization_code_grant(
         params = params.with_id_token(id_token);
     }
 
+    if let Some(authorization_details) = session.authorization_details.clone() {
+        params = params.with_authorization_details(authorization_details);
+    }
+
     // Lock the user sync to make sure we don't get into a race condition
     repo.user()
         .acquire_lock_for_sync(&browser_session.user)
@@ -609,9 +924,13 @@ async fn refresh_token_grant(
     activity_tracker: &BoundActivityTracker,
     grant: &RefreshTokenGrant,
     client: &Client,
+    key_store: &Keystore,
+    url_builder: &UrlBuilder,
     site_config: &SiteConfig,
     mut repo: BoxRepository,
     user_agent: Option<String>,
+    dpop_jkt: Option<&str>,
+    x5t_s256: Option<&str>,
 ) -> Result<(AccessTokenResponse, BoxRepository), RouteError> {
     // Check that the client is allowed to use this grant type
     if !client.grant_types.contains(&GrantType::RefreshToken) {
@@ -632,7 +951,7 @@ async fn refresh_token_grant(
 
     // Let's for now record the user agent on each refresh, that should be
     // responsive enough and not too much of a burden on the database.
-    if let Some(user_agent) = user_agent {
+    if let Some(user_agent) = user_agent.clone() {
         session = repo
             .oauth2_session()
             .record_user_agent(session, user_agent)
@@ -651,6 +970,9 @@ async fn refresh_token_grant(
         });
     }
 
+    // Reuse detection runs before the sender-constraint checks: otherwise, whoever
+    // refreshed first with a leaked token could lock the legitimate client out of
+    // the detection by binding the session to their own key
     if !refresh_token.is_valid() {
         // We're seing a refresh token that already has been consumed, this might be a
         // double-refresh or a replay attack
@@ -675,7 +997,18 @@ async fn refresh_token_grant(
 
         // Check if the next refresh token was already consumed or not
         if !next_refresh_token.is_valid() {
-            // XXX: This is a replay, we *may* want to invalidate the session
+            // This is a replay
+            revoke_session_on_reuse(
+                rng,
+                clock,
+                activity_tracker,
+                site_config,
+                repo,
+                session,
+                &refresh_token,
+                user_agent,
+            )
+            .await?;
             return Err(RouteError::RefreshTokenInvalid(next_refresh_token.id));
         }
 
@@ -699,7 +1032,18 @@ async fn refresh_token_grant(
             })?;
 
         if next_access_token.is_used() {
-            // XXX: This is a replay, we *may* want to invalidate the session
+            // This is a replay
+            revoke_session_on_reuse(
+                rng,
+                clock,
+                activity_tracker,
+                site_config,
+                repo,
+                session,
+                &refresh_token,
+                user_agent,
+            )
+            .await?;
             return Err(RouteError::RefreshTokenInvalid(next_refresh_token.id));
         }
 
@@ -722,13 +1066,31 @@ async fn refresh_token_grant(
             .await?;
     }
 
+    // Refreshing tokens bound to a DPoP key requires a proof with the same key
+    dpop::check_session(&session, dpop_jkt)?;
+    let session = bind_certificate(&mut repo, client, session, x5t_s256).await?;
+
+    check_session_resource(&session, grant.resource.as_ref())?;
+
     activity_tracker
         .record_oauth2_session(clock, &session)
         .await;
 
     let ttl = site_config.access_token_ttl;
+    let generated_access_token = generate_access_token(
+        rng,
+        clock,
+        &mut repo,
+        url_builder,
+        key_store,
+        site_config,
+        client,
+        &session,
+        ttl,
+    )
+    .await?;
     let (new_access_token, new_refresh_token) =
-        generate_token_pair(rng, clock, &mut repo, &session, ttl).await?;
+        generate_token_pair(rng, clock, &mut repo, &session, generated_access_token, ttl).await?;
 
     let refresh_token = repo
         .oauth2_refresh_token()
@@ -747,24 +1109,103 @@ async fn refresh_token_grant(
         }
     }
 
-    let params = AccessTokenResponse::new(new_access_token.access_token)
+    let mut params = AccessTokenResponse::new(new_access_token.access_token)
         .with_expires_in(ttl)
         .with_refresh_token(new_refresh_token.refresh_token)
         .with_scope(session.scope);
 
+    if let Some(authorization_details) = session.authorization_details {
+        params = params.with_authorization_details(authorization_details);
+    }
+
     Ok((params, repo))
 }
 
+/// Handle a consumed refresh token being presented again after its
+/// replacement was used.
+///
+/// Within the grace period, this is most likely a client racing with itself,
+/// and nothing is done. Outside of it, the refresh token probably leaked: the
+/// session is ended, all its tokens are revoked and the reuse is recorded.
+#[allow(clippy::too_many_arguments)]
+async fn revoke_session_on_reuse(
+    rng: &mut BoxRng,
+    clock: &impl Clock,
+    activity_tracker: &BoundActivityTracker,
+    site_config: &SiteConfig,
+    mut repo: BoxRepository,
+    session: Session,
+    refresh_token: &RefreshToken,
+    user_agent: Option<String>,
+) -> Result<(), RouteError> {
+    if let Some(consumed_at) = refresh_token.consumed_at()
+        && clock.now() - consumed_at <= site_config.refresh_token_reuse_grace_period
+    {
+        info!(
+            oauth2_session.id = %session.id,
+            %refresh_token.id,
+            %consumed_at,
+            "Refresh token reused within the grace period"
+        );
+        return Ok(());
+    }
+
+    warn!(
+        oauth2_session.id = %session.id,
+        oauth2_client.id = %session.client_id,
+        %refresh_token.id,
+        "Refresh token reused, revoking the potentially compromised session"
+    );
+
+    repo.oauth2_refresh_token()
+        .record_reuse(rng, clock, refresh_token, activity_tracker.ip(), user_agent)
+        .await?;
+
+    if let Some(user_id) = session.user_id {
+        let user = repo
+            .user()
+            .lookup(user_id)
+            .await?
+            .ok_or(RouteError::NoSuchUser(user_id))?;
+
+        // Schedule a job to sync the devices of the user with the homeserver
+        repo.queue_job()
+            .schedule_job(rng, clock, SyncDevicesJob::new(&user))
+            .await?;
+    }
+
+    // Let the client know that the session ended
+    repo.queue_job()
+        .schedule_job(rng, clock, SendBackchannelLogoutJob::new(&session))
+        .await?;
+
+    repo.oauth2_access_token()
+        .revoke_for_session(clock, &session)
+        .await?;
+    repo.oauth2_refresh_token()
+        .revoke_for_session(clock, &session)
+        .await?;
+    repo.oauth2_session().finish(clock, session).await?;
+
+    repo.save().await?;
+
+    Ok(())
+}
+
 async fn client_credentials_grant(
     rng: &mut BoxRng,
     clock: &impl Clock,
     activity_tracker: &BoundActivityTracker,
     grant: &ClientCredentialsGrant,
     client: &Client,
+    key_store: &Keystore,
+    url_builder: &UrlBuilder,
     site_config: &SiteConfig,
     mut repo: BoxRepository,
     mut policy: Policy,
     user_agent: Option<String>,
+    dpop_jkt: Option<&str>,
+    x5t_s256: Option<&str>,
 ) -> Result<(AccessTokenResponse, BoxRepository), RouteError> {
     // Check that the client is allowed to use this grant type
     if !client.grant_types.contains(&GrantType::ClientCredentials) {
@@ -777,6 +1218,24 @@ async fn client_credentials_grant(
         .clone()
         .unwrap_or_else(|| std::iter::empty::<ScopeToken>().collect());
 
+    // Check that we know about all the requested types of authorization details
+    if let Some(unsupported) = grant
+        .authorization_details
+        .as_ref()
+        .and_then(|details| unsupported_authorization_details_type(site_config, details))
+    {
+        return Err(RouteError::UnsupportedAuthorizationDetailsType(
+            unsupported.to_owned(),
+        ));
+    }
+
+    // Check that the requested resource server is one we know about
+    if let Some(resource) = &grant.resource
+        && !is_known_resource(site_config, resource)
+    {
+        return Err(RouteError::InvalidTarget(resource.to_string()));
+    }
+
     // Make the request go through the policy engine
     let res = policy
         .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
@@ -784,6 +1243,8 @@ async fn client_credentials_grant(
             client,
             scope: &scope,
             grant_type: mas_policy::GrantType::ClientCredentials,
+            authorization_details: grant.authorization_details.as_deref().unwrap_or_default(),
+            acr: None,
             requester: mas_policy::Requester {
                 ip_address: activity_tracker.ip(),
                 user_agent: user_agent.clone(),
@@ -807,12 +1268,47 @@ async fn client_credentials_grant(
             .await?;
     }
 
+    if grant.authorization_details.is_some() {
+        session = repo
+            .oauth2_session()
+            .set_authorization_details(session, grant.authorization_details.clone())
+            .await?;
+    }
+
+    // Restrict the token to the resource server the client asked for
+    if let Some(resource) = &grant.resource {
+        session = repo
+            .oauth2_session()
+            .set_audience(session, vec![resource.to_string()])
+            .await?;
+    }
+
+    let session = dpop::bind_session(&mut repo, session, dpop_jkt).await?;
+    let session = bind_certificate(&mut repo, client, session, x5t_s256).await?;
+
     let ttl = site_config.access_token_ttl;
-    let access_token_str = TokenType::AccessToken.generate(rng);
+    let generated_access_token = generate_access_token(
+        rng,
+        clock,
+        &mut repo,
+        url_builder,
+        key_store,
+        site_config,
+        client,
+        &session,
+        ttl,
+    )
+    .await?;
 
     let access_token = repo
         .oauth2_access_token()
-        .add(rng, clock, &session, access_token_str, Some(ttl))
+        .add_with_id(
+            clock,
+            generated_access_token.id,
+            &session,
+            generated_access_token.token,
+            Some(ttl),
+        )
         .await?;
 
     let mut params = AccessTokenResponse::new(access_token.access_token).with_expires_in(ttl);
@@ -829,14 +1325,24 @@ async fn client_credentials_grant(
         params = params.with_scope(session.scope);
     }
 
+    if let Some(authorization_details) = session.authorization_details {
+        params = params.with_authorization_details(authorization_details);
+    }
+
     Ok((params, repo))
 }
 
+/// Exchange a fulfilled device code grant for tokens.
+///
+/// This handles both the device authorization grant and client-initiated
+/// backchannel authentication requests, which share the same storage and
+/// state machine. `device_code` is the `auth_req_id` in the latter case.
 async fn device_code_grant(
     rng: &mut BoxRng,
     clock: &impl Clock,
     activity_tracker: &BoundActivityTracker,
-    grant: &DeviceCodeGrant,
+    device_code: &str,
+    grant_type: &GrantType,
     client: &Client,
     key_store: &Keystore,
     url_builder: &UrlBuilder,
@@ -844,16 +1350,22 @@ async fn device_code_grant(
     mut repo: BoxRepository,
     homeserver: &Arc<dyn HomeserverConnection>,
     user_agent: Option<String>,
+    dpop_jkt: Option<&str>,
+    x5t_s256: Option<&str>,
 ) -> Result<(AccessTokenResponse, BoxRepository), RouteError> {
     // Check that the client is allowed to use this grant type
-    if !client.grant_types.contains(&GrantType::DeviceCode) {
+    if !client.grant_types.contains(grant_type) {
         return Err(RouteError::UnauthorizedClient(client.id));
     }
 
+    let is_backchannel_authentication =
+        *grant_type == GrantType::ClientInitiatedBackchannelAuthentication;
     let grant = repo
         .oauth2_device_code_grant()
-        .find_by_device_code(&grant.device_code)
+        .find_by_device_code(device_code)
         .await?
+        // A device code can't be used as an `auth_req_id`, and vice versa
+        .filter(|grant| grant.is_backchannel_authentication() == is_backchannel_authentication)
         .ok_or(RouteError::GrantNotFound)?;
 
     // Check that the client match
@@ -907,12 +1419,32 @@ async fn device_code_grant(
             .await?;
     }
 
+    let session = dpop::bind_session(&mut repo, session, dpop_jkt).await?;
+    let session = bind_certificate(&mut repo, client, session, x5t_s256).await?;
+
     let ttl = site_config.access_token_ttl;
-    let access_token_str = TokenType::AccessToken.generate(rng);
+    let generated_access_token = generate_access_token(
+        rng,
+        clock,
+        &mut repo,
+        url_builder,
+        key_store,
+        site_config,
+        client,
+        &session,
+        ttl,
+    )
+    .await?;
 
     let access_token = repo
         .oauth2_access_token()
-        .add(rng, clock, &session, access_token_str, Some(ttl))
+        .add_with_id(
+            clock,
+            generated_access_token.id,
+            &session,
+            generated_access_token.token,
+            Some(ttl),
+        )
         .await?;
 
     let mut params =
@@ -938,11 +1470,13 @@ async fn device_code_grant(
             clock,
             url_builder,
             key_store,
+            site_config,
             client,
             None,
             &browser_session,
             Some(&access_token),
             None,
+            HashMap::new(),
         )?;
 
         params = params.with_id_token(id_token);
@@ -978,40 +1512,498 @@ async fn device_code_grant(
     Ok((params, repo))
 }
 
-#[cfg(test)]
-mod tests {
-    use hyper::Request;
-    use mas_data_model::{AccessToken, AuthorizationCode, RefreshToken};
-    use mas_router::SimpleRoute;
-    use oauth2_types::{
-        registration::ClientRegistrationResponse,
-        requests::{DeviceAuthorizationResponse, ResponseMode},
-        scope::{OPENID, Scope},
-    };
-    use sqlx::PgPool;
-
-    use super::*;
-    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+#[allow(clippy::too_many_lines)]
+async fn jwt_bearer_grant(
+    rng: &mut BoxRng,
+    clock: &impl Clock,
+    activity_tracker: &BoundActivityTracker,
+    grant: &JwtBearerGrant,
+    client: &Client,
+    http_client: &reqwest::Client,
+    key_store: &Keystore,
+    url_builder: &UrlBuilder,
+    site_config: &SiteConfig,
+    mut repo: BoxRepository,
+    homeserver: &Arc<dyn HomeserverConnection>,
+    mut policy: Policy,
+    user_agent: Option<String>,
+    dpop_jkt: Option<&str>,
+    x5t_s256: Option<&str>,
+) -> Result<(AccessTokenResponse, BoxRepository), RouteError> {
+    // Check that the client is allowed to use this grant type
+    if !client.grant_types.contains(&GrantType::JwtBearer) {
+        return Err(RouteError::UnauthorizedClient(client.id));
+    }
 
-    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
-    async fn test_auth_code_grant(pool: PgPool) {
-        setup();
-        let state = TestState::from_pool(pool).await.unwrap();
+    let user = jwt_bearer::verify_assertion(
+        http_client,
+        url_builder,
+        site_config,
+        clock,
+        &mut repo,
+        &grant.assertion,
+    )
+    .await?;
 
-        // Provision a client
-        let request =
-            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
-                "client_uri": "https://example.com/",
-                "redirect_uris": ["https://example.com/callback"],
-                "token_endpoint_auth_method": "none",
-                "response_types": ["code"],
-                "grant_types": ["authorization_code"],
-            }));
+    // Default to an empty scope if none is provided
+    let scope = grant
+        .scope
+        .clone()
+        .unwrap_or_else(|| std::iter::empty::<ScopeToken>().collect());
 
-        let response = state.request(request).await;
-        response.assert_status(StatusCode::CREATED);
+    // Make the request go through the policy engine
+    let res = policy
+        .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
+            user: Some(&user),
+            client,
+            scope: &scope,
+            grant_type: mas_policy::GrantType::JwtBearer,
+            authorization_details: &[],
+            acr: None,
+            requester: mas_policy::Requester {
+                ip_address: activity_tracker.ip(),
+                user_agent: user_agent.clone(),
+            },
+        })
+        .await?;
+    if !res.valid() {
+        return Err(RouteError::DeniedByPolicy(res));
+    }
 
-        let ClientRegistrationResponse { client_id, .. } = response.json();
+    // Start the session. There is no browser session, as the user is not
+    // present.
+    let mut session = repo
+        .oauth2_session()
+        .add(rng, clock, client, Some(&user), None, scope)
+        .await?;
+
+    if let Some(user_agent) = user_agent {
+        session = repo
+            .oauth2_session()
+            .record_user_agent(session, user_agent)
+            .await?;
+    }
+
+    let session = dpop::bind_session(&mut repo, session, dpop_jkt).await?;
+    let session = bind_certificate(&mut repo, client, session, x5t_s256).await?;
+
+    // The assertion can be presented again to get a new access token, so we don't
+    // issue refresh tokens
+    let ttl = site_config.access_token_ttl;
+    let generated_access_token = generate_access_token(
+        rng,
+        clock,
+        &mut repo,
+        url_builder,
+        key_store,
+        site_config,
+        client,
+        &session,
+        ttl,
+    )
+    .await?;
+
+    let access_token = repo
+        .oauth2_access_token()
+        .add_with_id(
+            clock,
+            generated_access_token.id,
+            &session,
+            generated_access_token.token,
+            Some(ttl),
+        )
+        .await?;
+
+    let mut params = AccessTokenResponse::new(access_token.access_token).with_expires_in(ttl);
+
+    // Lock the user sync to make sure we don't get into a race condition
+    repo.user().acquire_lock_for_sync(&user).await?;
+
+    // Look for device to provision
+    for scope in &*session.scope {
+        if let Some(device) = Device::from_scope_token(scope) {
+            homeserver
+                .upsert_device(&user.username, device.as_str(), None)
+                .await
+                .map_err(RouteError::ProvisionDeviceFailed)?;
+        }
+    }
+
+    // XXX: there is a potential (but unlikely) race here, where the activity for
+    // the session is recorded before the transaction is committed. We would have to
+    // save the repository here to fix that.
+    activity_tracker
+        .record_oauth2_session(clock, &session)
+        .await;
+
+    if !session.scope.is_empty() {
+        // We only return the scope if it's not empty
+        params = params.with_scope(session.scope);
+    }
+
+    Ok((params, repo))
+}
+
+/// Load an access token presented in a token exchange, along with the session
+/// and the client it was issued to
+///
+/// Sender-constrained tokens can only be exchanged in a request made with the
+/// `DPoP` key or the client certificate they are bound to.
+async fn load_exchanged_token(
+    clock: &impl Clock,
+    repo: &mut BoxRepository,
+    kind: &'static str,
+    token: &str,
+    token_type: &TokenTypeIdentifier,
+    dpop_jkt: Option<&str>,
+    x5t_s256: Option<&str>,
+) -> Result<(AccessToken, Session, Client), RouteError> {
+    // We can only exchange our own access tokens
+    if *token_type != TokenTypeIdentifier::AccessToken {
+        return Err(RouteError::UnsupportedTokenType(token_type.clone()));
+    }
+
+    let access_token = repo
+        .oauth2_access_token()
+        .find_by_token(token)
+        .await?
+        .filter(|access_token| access_token.is_valid(clock.now()))
+        .ok_or(RouteError::InvalidExchangedToken(kind))?;
+
+    let session = repo
+        .oauth2_session()
+        .lookup(access_token.session_id)
+        .await?
+        .ok_or(RouteError::NoSuchOAuthSession(access_token.session_id))?;
+
+    if !session.is_valid() {
+        return Err(RouteError::InvalidExchangedToken(kind));
+    }
+
+    match (session.dpop_jkt.as_deref(), dpop_jkt) {
+        (Some(_), None) => return Err(DpopError::Required.into()),
+        (Some(bound), Some(jkt)) if bound != jkt => return Err(DpopError::KeyMismatch.into()),
+        _ => {}
+    }
+
+    if session.x5t_s256.is_some() && session.x5t_s256.as_deref() != x5t_s256 {
+        return Err(RouteError::CertificateMismatch(session.id));
+    }
+
+    let client = repo
+        .oauth2_client()
+        .lookup(session.client_id)
+        .await?
+        .ok_or(RouteError::InvalidExchangedToken(kind))?;
+
+    Ok((access_token, session, client))
+}
+
+#[allow(clippy::too_many_lines)]
+async fn token_exchange_grant(
+    rng: &mut BoxRng,
+    clock: &impl Clock,
+    activity_tracker: &BoundActivityTracker,
+    grant: &TokenExchangeGrant,
+    client: &Client,
+    key_store: &Keystore,
+    url_builder: &UrlBuilder,
+    site_config: &SiteConfig,
+    mut repo: BoxRepository,
+    mut policy: Policy,
+    user_agent: Option<String>,
+    dpop_jkt: Option<&str>,
+    x5t_s256: Option<&str>,
+) -> Result<(AccessTokenResponse, BoxRepository), RouteError> {
+    // Check that the client is allowed to use this grant type
+    if !client.grant_types.contains(&GrantType::TokenExchange) {
+        return Err(RouteError::UnauthorizedClient(client.id));
+    }
+
+    // We only ever issue access tokens
+    if let Some(requested_token_type) = &grant.requested_token_type
+        && *requested_token_type != TokenTypeIdentifier::AccessToken
+    {
+        return Err(RouteError::UnsupportedTokenType(
+            requested_token_type.clone(),
+        ));
+    }
+
+    let (subject_token, subject_session, subject_client) = load_exchanged_token(
+        clock,
+        &mut repo,
+        "subject",
+        &grant.subject_token,
+        &grant.subject_token_type,
+        dpop_jkt,
+        x5t_s256,
+    )
+    .await?;
+
+    let actor = match (&grant.actor_token, &grant.actor_token_type) {
+        (None, None) => None,
+        (Some(actor_token), Some(actor_token_type)) => {
+            let (_, actor_session, actor_client) = load_exchanged_token(
+                clock,
+                &mut repo,
+                "actor",
+                actor_token,
+                actor_token_type,
+                dpop_jkt,
+                x5t_s256,
+            )
+            .await?;
+            Some((actor_session, actor_client))
+        }
+        _ => return Err(RouteError::BadRequest),
+    };
+
+    // The new token can't have more privileges than the subject token
+    let scope = match &grant.scope {
+        Some(scope)
+            if scope
+                .iter()
+                .all(|token| subject_session.scope.contains(token)) =>
+        {
+            scope.clone()
+        }
+        Some(_) => return Err(RouteError::InvalidScope),
+        None => subject_session.scope.clone(),
+    };
+
+    // The audience can be either one of the configured resource servers, or the
+    // ID of one of our clients
+    let mut audience = Vec::new();
+    if let Some(resource) = &grant.resource {
+        if !is_known_resource(site_config, resource) {
+            return Err(RouteError::InvalidTarget(resource.to_string()));
+        }
+        audience.push(resource.to_string());
+    }
+
+    if let Some(target) = &grant.audience {
+        let target_client = repo
+            .oauth2_client()
+            .find_by_client_id(target)
+            .await?
+            .ok_or_else(|| RouteError::InvalidTarget(target.clone()))?;
+        audience.push(target_client.client_id);
+    }
+
+    // The new token can't be intended for more targets than the subject token
+    if !subject_session.audience.is_empty() {
+        if let Some(target) = audience
+            .iter()
+            .find(|target| !subject_session.audience.contains(target))
+        {
+            return Err(RouteError::InvalidTarget(target.clone()));
+        }
+
+        if audience.is_empty() {
+            audience.clone_from(&subject_session.audience);
+        }
+    }
+
+    let user = if let Some(user_id) = subject_session.user_id {
+        let user = repo
+            .user()
+            .lookup(user_id)
+            .await?
+            .filter(mas_data_model::User::is_valid)
+            .ok_or(RouteError::InvalidExchangedToken("subject"))?;
+        Some(user)
+    } else {
+        None
+    };
+
+    let browser_session = if let Some(user_session_id) = subject_session.user_session_id {
+        let browser_session = repo
+            .browser_session()
+            .lookup(user_session_id)
+            .await?
+            .ok_or(RouteError::NoSuchBrowserSession(user_session_id))?;
+        Some(browser_session)
+    } else {
+        None
+    };
+
+    // Make the request go through the policy engine
+    let res = policy
+        .evaluate_token_exchange(mas_policy::TokenExchangeInput {
+            user: user.as_ref(),
+            client,
+            subject_client: &subject_client,
+            actor_client: actor.as_ref().map(|(_, actor_client)| actor_client),
+            scope: &scope,
+            audience: &audience,
+            requester: mas_policy::Requester {
+                ip_address: activity_tracker.ip(),
+                user_agent: user_agent.clone(),
+            },
+        })
+        .await?;
+    if !res.valid() {
+        return Err(RouteError::DeniedByPolicy(res));
+    }
+
+    // The actor is recorded on top of the delegation chain of the subject token
+    let act = match actor {
+        Some((actor_session, actor_client)) => {
+            let sub = if let Some(user_id) = actor_session.user_id {
+                let actor_user = repo
+                    .user()
+                    .lookup(user_id)
+                    .await?
+                    .ok_or(RouteError::InvalidExchangedToken("actor"))?;
+                Some(actor_user.sub)
+            } else {
+                None
+            };
+
+            Some(ActorClaim {
+                sub,
+                client_id: Some(actor_client.client_id),
+                act: subject_session.act.clone().map(Box::new),
+            })
+        }
+        None => subject_session.act.clone(),
+    };
+
+    // Start the session
+    let mut session = repo
+        .oauth2_session()
+        .add(
+            rng,
+            clock,
+            client,
+            user.as_ref(),
+            browser_session.as_ref(),
+            scope,
+        )
+        .await?;
+
+    if let Some(user_agent) = user_agent {
+        session = repo
+            .oauth2_session()
+            .record_user_agent(session, user_agent)
+            .await?;
+    }
+
+    if !audience.is_empty() {
+        session = repo
+            .oauth2_session()
+            .set_audience(session, audience)
+            .await?;
+    }
+
+    if let Some(act) = act {
+        session = repo.oauth2_session().set_act(session, act).await?;
+    }
+
+    let session = dpop::bind_session(&mut repo, session, dpop_jkt).await?;
+    let session = bind_certificate(&mut repo, client, session, x5t_s256).await?;
+
+    // The new token doesn't outlive the subject token
+    let mut ttl = site_config.access_token_ttl;
+    if let Some(expires_at) = subject_token.expires_at {
+        ttl = ttl.min(expires_at - clock.now());
+    }
+
+    let generated_access_token = generate_access_token(
+        rng,
+        clock,
+        &mut repo,
+        url_builder,
+        key_store,
+        site_config,
+        client,
+        &session,
+        ttl,
+    )
+    .await?;
+    let access_token = repo
+        .oauth2_access_token()
+        .add_with_id(
+            clock,
+            generated_access_token.id,
+            &session,
+            generated_access_token.token,
+            Some(ttl),
+        )
+        .await?;
+
+    let mut params = AccessTokenResponse::new(access_token.access_token)
+        .with_expires_in(ttl)
+        .with_issued_token_type(TokenTypeIdentifier::AccessToken);
+
+    // XXX: there is a potential (but unlikely) race here, where the activity for
+    // the session is recorded before the transaction is committed. We would have to
+    // save the repository here to fix that.
+    activity_tracker
+        .record_oauth2_session(clock, &session)
+        .await;
+
+    if !session.scope.is_empty() {
+        // We only return the scope if it's not empty
+        params = params.with_scope(session.scope);
+    }
+
+    Ok((params, repo))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::Request;
+    use hyper::header::CONTENT_TYPE;
+    use mas_data_model::{
+        AuthorizationCode, JwksOrJwksUri, JwtAccessTokenConfig, JwtBearerIssuer,
+        UpstreamOAuthProviderClaimsImports, UpstreamOAuthProviderDiscoveryMode,
+        UpstreamOAuthProviderOnBackchannelLogout, UpstreamOAuthProviderPkceMode,
+        UpstreamOAuthProviderTokenAuthMethod,
+    };
+    use mas_iana::jose::{JsonWebEncryptionAlg, JsonWebEncryptionEnc, JsonWebSignatureAlg};
+    use mas_jose::{
+        claims,
+        jwe::Jwe,
+        jwt::{JsonWebSignatureHeader, Jwt},
+    };
+    use mas_router::SimpleRoute;
+    use mas_storage::{
+        oauth2::OAuth2AuthorizationGrantParams, upstream_oauth2::UpstreamOAuthProviderParams,
+    };
+    use oauth2_types::{
+        registration::ClientRegistrationResponse,
+        requests::{DeviceAuthorizationResponse, ResponseMode},
+        scope::{OPENID, Scope},
+    };
+    use sha2::{Digest as _, Sha256};
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        oauth2::GeneratedAccessToken,
+        test_utils::{RequestBuilderExt, ResponseExt, TestState, setup, test_site_config},
+    };
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_auth_code_grant(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision a client
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "token_endpoint_auth_method": "none",
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let ClientRegistrationResponse { client_id, .. } = response.json();
 
         // Let's provision a user and create a session for them. This part is hard to
         // test with just HTTP requests, so we'll use the repository directly.
@@ -1044,19 +2036,27 @@ mod tests {
             .add(
                 &mut state.rng(),
                 &state.clock,
-                &client,
-                "https://example.com/redirect".parse().unwrap(),
-                Scope::from_iter([OPENID]),
-                Some(AuthorizationCode {
-                    code: code.to_owned(),
-                    pkce: None,
-                }),
-                Some("state".to_owned()),
-                Some("nonce".to_owned()),
-                ResponseMode::Query,
-                false,
-                None,
-                None,
+                OAuth2AuthorizationGrantParams {
+                    client: &client,
+                    redirect_uri: "https://example.com/redirect".parse().unwrap(),
+                    scope: Scope::from_iter([OPENID]),
+                    code: Some(AuthorizationCode {
+                        code: code.to_owned(),
+                        pkce: None,
+                    }),
+                    state: Some("state".to_owned()),
+                    nonce: Some("nonce".to_owned()),
+                    response_mode: ResponseMode::Query,
+                    response_type_id_token: false,
+                    login_hint: None,
+                    locale: None,
+                    requested_claims: None,
+                    authorization_details: None,
+                    resource: None,
+                    max_age: None,
+                    acr_values: Vec::new(),
+                    requires_consent: false,
+                },
             )
             .await
             .unwrap();
@@ -1144,19 +2144,27 @@ mod tests {
             .add(
                 &mut state.rng(),
                 &state.clock,
-                &client,
-                "https://example.com/redirect".parse().unwrap(),
-                Scope::from_iter([OPENID]),
-                Some(AuthorizationCode {
-                    code: code.to_owned(),
-                    pkce: None,
-                }),
-                Some("state".to_owned()),
-                Some("nonce".to_owned()),
-                ResponseMode::Query,
-                false,
-                None,
-                None,
+                OAuth2AuthorizationGrantParams {
+                    client: &client,
+                    redirect_uri: "https://example.com/redirect".parse().unwrap(),
+                    scope: Scope::from_iter([OPENID]),
+                    code: Some(AuthorizationCode {
+                        code: code.to_owned(),
+                        pkce: None,
+                    }),
+                    state: Some("state".to_owned()),
+                    nonce: Some("nonce".to_owned()),
+                    response_mode: ResponseMode::Query,
+                    response_type_id_token: false,
+                    login_hint: None,
+                    locale: None,
+                    requested_claims: None,
+                    authorization_details: None,
+                    resource: None,
+                    max_age: None,
+                    acr_values: Vec::new(),
+                    requires_consent: false,
+                },
             )
             .await
             .unwrap();
@@ -1203,27 +2211,36 @@ mod tests {
     }
 
     #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
-    async fn test_refresh_token_grant(pool: PgPool) {
+    async fn test_encrypted_id_token_and_userinfo(pool: PgPool) {
         setup();
         let state = TestState::from_pool(pool).await.unwrap();
 
-        // Provision a client
+        // Provision a client which gets encrypted ID tokens and signed then encrypted
+        // userinfo responses, using keys derived from its secret
         let request =
             Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
                 "client_uri": "https://example.com/",
                 "redirect_uris": ["https://example.com/callback"],
-                "token_endpoint_auth_method": "none",
+                "token_endpoint_auth_method": "client_secret_post",
                 "response_types": ["code"],
-                "grant_types": ["authorization_code", "refresh_token"],
+                "grant_types": ["authorization_code"],
+                "id_token_encrypted_response_alg": "A256KW",
+                "userinfo_signed_response_alg": "RS256",
+                "userinfo_encrypted_response_alg": "A256KW",
+                "userinfo_encrypted_response_enc": "A256GCM",
             }));
 
         let response = state.request(request).await;
         response.assert_status(StatusCode::CREATED);
 
-        let ClientRegistrationResponse { client_id, .. } = response.json();
+        let ClientRegistrationResponse {
+            client_id,
+            client_secret,
+            ..
+        } = response.json();
+        let client_secret = client_secret.unwrap();
+        let encryption_key = Sha256::digest(&client_secret);
 
-        // Let's provision a user and create a session for them. This part is hard to
-        // test with just HTTP requests, so we'll use the repository directly.
         let mut repo = state.repository().await.unwrap();
 
         let user = repo
@@ -1238,7 +2255,6 @@ mod tests {
             .await
             .unwrap();
 
-        // Lookup the client in the database.
         let client = repo
             .oauth2_client()
             .find_by_client_id(&client_id)
@@ -1246,7 +2262,37 @@ mod tests {
             .unwrap()
             .unwrap();
 
-        // Get a token pair
+        let code = "thisisaverysecurecode";
+        let grant = repo
+            .oauth2_authorization_grant()
+            .add(
+                &mut state.rng(),
+                &state.clock,
+                OAuth2AuthorizationGrantParams {
+                    client: &client,
+                    redirect_uri: "https://example.com/redirect".parse().unwrap(),
+                    scope: Scope::from_iter([OPENID]),
+                    code: Some(AuthorizationCode {
+                        code: code.to_owned(),
+                        pkce: None,
+                    }),
+                    state: Some("state".to_owned()),
+                    nonce: Some("nonce".to_owned()),
+                    response_mode: ResponseMode::Query,
+                    response_type_id_token: false,
+                    login_hint: None,
+                    locale: None,
+                    requested_claims: None,
+                    authorization_details: None,
+                    resource: None,
+                    max_age: None,
+                    acr_values: Vec::new(),
+                    requires_consent: false,
+                },
+            )
+            .await
+            .unwrap();
+
         let session = repo
             .oauth2_session()
             .add_from_browser_session(
@@ -1254,7 +2300,131 @@ mod tests {
                 &state.clock,
                 &client,
                 &browser_session,
-                Scope::from_iter([OPENID]),
+                grant.scope.clone(),
+            )
+            .await
+            .unwrap();
+
+        let grant = repo
+            .oauth2_authorization_grant()
+            .fulfill(&state.clock, &session, grant)
+            .await
+            .unwrap();
+
+        repo.save().await.unwrap();
+
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "authorization_code",
+                "code": code,
+                "redirect_uri": grant.redirect_uri,
+                "client_id": client_id,
+                "client_secret": client_secret,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let AccessTokenResponse {
+            access_token,
+            id_token,
+            ..
+        } = response.json();
+
+        // The ID token is a signed JWT, encrypted with the default content
+        // encryption algorithm
+        let id_token = Jwe::try_from(id_token.unwrap()).unwrap();
+        assert_eq!(*id_token.header().alg(), JsonWebEncryptionAlg::A256Kw);
+        assert_eq!(*id_token.header().enc(), JsonWebEncryptionEnc::A128CbcHs256);
+        assert_eq!(id_token.header().cty(), Some("JWT"));
+
+        let id_token = id_token
+            .decrypt_with_shared_secret(&encryption_key)
+            .unwrap();
+        let id_token: Jwt<'_, HashMap<String, serde_json::Value>> =
+            Jwt::try_from(std::str::from_utf8(&id_token).unwrap()).unwrap();
+        id_token
+            .verify_with_jwks(&state.key_store.public_jwks())
+            .unwrap();
+        assert_eq!(id_token.payload()["aud"], client_id);
+        assert_eq!(id_token.payload()["nonce"], "nonce");
+
+        // The userinfo response is also signed then encrypted
+        let request = Request::get(mas_router::OidcUserinfo::PATH)
+            .bearer(&access_token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        response.assert_header_value(CONTENT_TYPE, "application/jwt");
+
+        let user_info = Jwe::try_from(response.body().as_str()).unwrap();
+        assert_eq!(*user_info.header().enc(), JsonWebEncryptionEnc::A256Gcm);
+
+        let user_info = user_info
+            .decrypt_with_shared_secret(&encryption_key)
+            .unwrap();
+        let user_info: Jwt<'_, HashMap<String, serde_json::Value>> =
+            Jwt::try_from(std::str::from_utf8(&user_info).unwrap()).unwrap();
+        user_info
+            .verify_with_jwks(&state.key_store.public_jwks())
+            .unwrap();
+        assert_eq!(user_info.payload()["aud"], client_id);
+        assert_eq!(user_info.payload()["username"], "alice");
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_refresh_token_grant(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision a client
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "token_endpoint_auth_method": "none",
+                "response_types": ["code"],
+                "grant_types": ["authorization_code", "refresh_token"],
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let ClientRegistrationResponse { client_id, .. } = response.json();
+
+        // Let's provision a user and create a session for them. This part is hard to
+        // test with just HTTP requests, so we'll use the repository directly.
+        let mut repo = state.repository().await.unwrap();
+
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+
+        let browser_session = repo
+            .browser_session()
+            .add(&mut state.rng(), &state.clock, &user, None)
+            .await
+            .unwrap();
+
+        // Lookup the client in the database.
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Get a token pair
+        let session = repo
+            .oauth2_session()
+            .add_from_browser_session(
+                &mut state.rng(),
+                &state.clock,
+                &client,
+                &browser_session,
+                Scope::from_iter([OPENID]),
             )
             .await
             .unwrap();
@@ -1265,6 +2435,7 @@ mod tests {
                 &state.clock,
                 &mut repo,
                 &session,
+                GeneratedAccessToken::opaque(&mut state.rng(), &state.clock),
                 Duration::microseconds(5 * 60 * 1000 * 1000),
             )
             .await
@@ -1387,6 +2558,7 @@ mod tests {
                 &state.clock,
                 &mut repo,
                 &session,
+                GeneratedAccessToken::opaque(&mut state.rng(), &state.clock),
                 Duration::microseconds(5 * 60 * 1000 * 1000),
             )
             .await
@@ -1494,7 +2666,7 @@ mod tests {
     }
 
     #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
-    async fn test_client_credentials(pool: PgPool) {
+    async fn test_refresh_token_reuse(pool: PgPool) {
         setup();
         let state = TestState::from_pool(pool).await.unwrap();
 
@@ -1502,128 +2674,418 @@ mod tests {
         let request =
             Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
                 "client_uri": "https://example.com/",
-                "token_endpoint_auth_method": "client_secret_post",
-                "grant_types": ["client_credentials"],
+                "redirect_uris": ["https://example.com/callback"],
+                "token_endpoint_auth_method": "none",
+                "response_types": ["code"],
+                "grant_types": ["authorization_code", "refresh_token"],
             }));
 
         let response = state.request(request).await;
         response.assert_status(StatusCode::CREATED);
 
-        let response: ClientRegistrationResponse = response.json();
-        let client_id = response.client_id;
-        let client_secret = response.client_secret.expect("to have a client secret");
+        let ClientRegistrationResponse { client_id, .. } = response.json();
 
-        // Call the token endpoint with an empty scope
-        let request =
-            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
-                "grant_type": "client_credentials",
-                "client_id": client_id,
-                "client_secret": client_secret,
-            }));
+        // Provision a user and a session with a token pair
+        let mut repo = state.repository().await.unwrap();
 
-        let response = state.request(request).await;
-        response.assert_status(StatusCode::OK);
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
 
-        let response: AccessTokenResponse = response.json();
-        assert!(response.refresh_token.is_none());
-        assert!(response.expires_in.is_some());
-        assert!(response.scope.is_none());
+        let browser_session = repo
+            .browser_session()
+            .add(&mut state.rng(), &state.clock, &user, None)
+            .await
+            .unwrap();
 
-        // Revoke the token
-        let request = Request::post(mas_router::OAuth2Revocation::PATH).form(serde_json::json!({
-            "token": response.access_token,
-            "client_id": client_id,
-            "client_secret": client_secret,
-        }));
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await
+            .unwrap()
+            .unwrap();
 
-        let response = state.request(request).await;
-        response.assert_status(StatusCode::OK);
+        let session = repo
+            .oauth2_session()
+            .add_from_browser_session(
+                &mut state.rng(),
+                &state.clock,
+                &client,
+                &browser_session,
+                Scope::from_iter([OPENID]),
+            )
+            .await
+            .unwrap();
 
-        // We should be allowed to ask for the GraphQL API scope
-        let request =
-            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
-                "grant_type": "client_credentials",
-                "client_id": client_id,
-                "client_secret": client_secret,
-                "scope": "urn:mas:graphql:*"
-            }));
+        let (_, RefreshToken { refresh_token, .. }) = generate_token_pair(
+            &mut state.rng(),
+            &state.clock,
+            &mut repo,
+            &session,
+            GeneratedAccessToken::opaque(&mut state.rng(), &state.clock),
+            Duration::microseconds(5 * 60 * 1000 * 1000),
+        )
+        .await
+        .unwrap();
 
-        let response = state.request(request).await;
-        response.assert_status(StatusCode::OK);
+        repo.save().await.unwrap();
 
-        let response: AccessTokenResponse = response.json();
-        assert!(response.refresh_token.is_none());
-        assert!(response.expires_in.is_some());
-        assert_eq!(response.scope, Some("urn:mas:graphql:*".parse().unwrap()));
+        let refresh = |refresh_token: &str| {
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "refresh_token",
+                "refresh_token": refresh_token,
+                "client_id": client.client_id,
+            }))
+        };
 
-        // Revoke the token
-        let request = Request::post(mas_router::OAuth2Revocation::PATH).form(serde_json::json!({
-            "token": response.access_token,
-            "client_id": client_id,
-            "client_secret": client_secret,
-        }));
+        // Refresh twice, so that the first refresh token is consumed, and its
+        // replacement as well
+        let response = state.request(refresh(&refresh_token)).await;
+        response.assert_status(StatusCode::OK);
+        let first_response: AccessTokenResponse = response.json();
 
-        let response = state.request(request).await;
+        let response = state
+            .request(refresh(first_response.refresh_token.as_deref().unwrap()))
+            .await;
         response.assert_status(StatusCode::OK);
+        let second_response: AccessTokenResponse = response.json();
 
-        // We should be NOT allowed to ask for the MAS admin scope
-        let request =
-            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
-                "grant_type": "client_credentials",
-                "client_id": client_id,
-                "client_secret": client_secret,
-                "scope": "urn:mas:admin"
-            }));
+        // Replaying the first refresh token within the grace period fails, but
+        // leaves the session alone
+        let response = state.request(refresh(&refresh_token)).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidGrant);
 
-        let response = state.request(request).await;
-        response.assert_status(StatusCode::FORBIDDEN);
+        assert!(
+            state
+                .is_access_token_valid(&second_response.access_token)
+                .await
+        );
 
+        // Replaying it outside of the grace period revokes the whole session
+        state.clock.advance(Duration::try_minutes(1).unwrap());
+
+        let response = state.request(refresh(&refresh_token)).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
         let ClientError { error, .. } = response.json();
-        assert_eq!(error, ClientErrorCode::InvalidScope);
+        assert_eq!(error, ClientErrorCode::InvalidGrant);
 
-        // Now, if we add the client to the admin list in the policy, it should work
-        let state = {
-            let mut state = state;
-            state.policy_factory = crate::test_utils::policy_factory(
-                "example.com",
-                serde_json::json!({
-                    "admin_clients": [client_id]
-                }),
-            )
+        assert!(
+            !state
+                .is_access_token_valid(&second_response.access_token)
+                .await
+        );
+
+        let mut repo = state.repository().await.unwrap();
+        let session = repo
+            .oauth2_session()
+            .lookup(session.id)
             .await
+            .unwrap()
             .unwrap();
-            state
-        };
-
-        let request =
-            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
-                "grant_type": "client_credentials",
-                "client_id": client_id,
-                "client_secret": client_secret,
-                "scope": "urn:mas:admin"
-            }));
+        assert!(!session.is_valid());
 
-        let response = state.request(request).await;
-        response.assert_status(StatusCode::OK);
+        let latest_refresh_token = repo
+            .oauth2_refresh_token()
+            .find_by_token(second_response.refresh_token.as_deref().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!latest_refresh_token.is_valid());
+        repo.save().await.unwrap();
 
-        let response: AccessTokenResponse = response.json();
-        assert!(response.refresh_token.is_none());
-        assert!(response.expires_in.is_some());
-        assert_eq!(response.scope, Some("urn:mas:admin".parse().unwrap()));
+        // The latest refresh token can't be used anymore
+        let response = state
+            .request(refresh(second_response.refresh_token.as_deref().unwrap()))
+            .await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
 
-        // Revoke the token
-        let request = Request::post(mas_router::OAuth2Revocation::PATH).form(serde_json::json!({
-            "token": response.access_token,
-            "client_id": client_id,
-            "client_secret": client_secret,
-        }));
+    /// Build a `DPoP` proof for the token endpoint, signed with the test key
+    fn dpop_proof(state: &TestState, jti: &str) -> String {
+        let alg = JsonWebSignatureAlg::Rs256;
+        let key = state.key_store.signing_key_for_algorithm(&alg).unwrap();
+        let signer = key.params().signing_key_for_alg(&alg).unwrap();
+        let jwk = state
+            .key_store
+            .public_jwks()
+            .signing_key_for_algorithm(&alg)
+            .unwrap()
+            .clone();
+        let header = JsonWebSignatureHeader::new(alg)
+            .with_typ("dpop+jwt".to_owned())
+            .with_jwk(jwk);
+
+        let mut claims = HashMap::new();
+        claims::IAT.insert(&mut claims, state.clock.now()).unwrap();
+        claims::JTI.insert(&mut claims, jti.to_owned()).unwrap();
+        claims.insert("htm".to_owned(), serde_json::json!("POST"));
+        claims.insert(
+            "htu".to_owned(),
+            serde_json::json!(state.url_builder.oauth_token_endpoint()),
+        );
 
-        let response = state.request(request).await;
-        response.assert_status(StatusCode::OK);
+        Jwt::<HashMap<String, serde_json::Value>>::sign(header, claims, &signer)
+            .unwrap()
+            .into_string()
     }
 
     #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
-    async fn test_device_code_grant(pool: PgPool) {
+    async fn test_refresh_token_reuse_with_dpop_proof(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision a client
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "token_endpoint_auth_method": "none",
+                "response_types": ["code"],
+                "grant_types": ["authorization_code", "refresh_token"],
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let ClientRegistrationResponse { client_id, .. } = response.json();
+
+        // Provision a user and a session with a bearer token pair
+        let mut repo = state.repository().await.unwrap();
+
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+
+        let browser_session = repo
+            .browser_session()
+            .add(&mut state.rng(), &state.clock, &user, None)
+            .await
+            .unwrap();
+
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let session = repo
+            .oauth2_session()
+            .add_from_browser_session(
+                &mut state.rng(),
+                &state.clock,
+                &client,
+                &browser_session,
+                Scope::from_iter([OPENID]),
+            )
+            .await
+            .unwrap();
+
+        let (_, RefreshToken { refresh_token, .. }) = generate_token_pair(
+            &mut state.rng(),
+            &state.clock,
+            &mut repo,
+            &session,
+            GeneratedAccessToken::opaque(&mut state.rng(), &state.clock),
+            Duration::microseconds(5 * 60 * 1000 * 1000),
+        )
+        .await
+        .unwrap();
+
+        repo.save().await.unwrap();
+
+        let refresh = |refresh_token: &str, proof: Option<String>| {
+            let mut request = Request::post(mas_router::OAuth2TokenEndpoint::PATH);
+            if let Some(proof) = proof {
+                request = request.header("DPoP", proof);
+            }
+            request.form(serde_json::json!({
+                "grant_type": "refresh_token",
+                "refresh_token": refresh_token,
+                "client_id": client.client_id,
+            }))
+        };
+
+        // An attacker with the leaked refresh token can't bind the session to
+        // their own DPoP key
+        let request = refresh(&refresh_token, Some(dpop_proof(&state, "proof-1")));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidDpopProof);
+
+        // They refresh twice as a bearer instead
+        let response = state.request(refresh(&refresh_token, None)).await;
+        response.assert_status(StatusCode::OK);
+        let first_response: AccessTokenResponse = response.json();
+
+        let response = state
+            .request(refresh(
+                first_response.refresh_token.as_deref().unwrap(),
+                None,
+            ))
+            .await;
+        response.assert_status(StatusCode::OK);
+
+        // The legitimate client then presents the consumed refresh token with a
+        // proof the session isn't bound to. This is detected as a reuse before
+        // the proof gets rejected
+        state.clock.advance(Duration::try_minutes(1).unwrap());
+
+        let request = refresh(&refresh_token, Some(dpop_proof(&state, "proof-2")));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidGrant);
+
+        let mut repo = state.repository().await.unwrap();
+        let session = repo
+            .oauth2_session()
+            .lookup(session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!session.is_valid());
+        repo.save().await.unwrap();
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_client_credentials(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision a client
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "token_endpoint_auth_method": "client_secret_post",
+                "grant_types": ["client_credentials"],
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+        let client_secret = response.client_secret.expect("to have a client secret");
+
+        // Call the token endpoint with an empty scope
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let response: AccessTokenResponse = response.json();
+        assert!(response.refresh_token.is_none());
+        assert!(response.expires_in.is_some());
+        assert!(response.scope.is_none());
+
+        // Revoke the token
+        let request = Request::post(mas_router::OAuth2Revocation::PATH).form(serde_json::json!({
+            "token": response.access_token,
+            "client_id": client_id,
+            "client_secret": client_secret,
+        }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        // We should be allowed to ask for the GraphQL API scope
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "scope": "urn:mas:graphql:*"
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let response: AccessTokenResponse = response.json();
+        assert!(response.refresh_token.is_none());
+        assert!(response.expires_in.is_some());
+        assert_eq!(response.scope, Some("urn:mas:graphql:*".parse().unwrap()));
+
+        // Revoke the token
+        let request = Request::post(mas_router::OAuth2Revocation::PATH).form(serde_json::json!({
+            "token": response.access_token,
+            "client_id": client_id,
+            "client_secret": client_secret,
+        }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        // We should be NOT allowed to ask for the MAS admin scope
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "scope": "urn:mas:admin"
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidScope);
+
+        // Now, if we add the client to the admin list in the policy, it should work
+        let state = {
+            let mut state = state;
+            state.policy_factory = crate::test_utils::policy_factory(
+                "example.com",
+                serde_json::json!({
+                    "admin_clients": [client_id]
+                }),
+            )
+            .await
+            .unwrap();
+            state
+        };
+
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "scope": "urn:mas:admin"
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let response: AccessTokenResponse = response.json();
+        assert!(response.refresh_token.is_none());
+        assert!(response.expires_in.is_some());
+        assert_eq!(response.scope, Some("urn:mas:admin".parse().unwrap()));
+
+        // Revoke the token
+        let request = Request::post(mas_router::OAuth2Revocation::PATH).form(serde_json::json!({
+            "token": response.access_token,
+            "client_id": client_id,
+            "client_secret": client_secret,
+        }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_device_code_grant(pool: PgPool) {
         setup();
         let state = TestState::from_pool(pool).await.unwrap();
 
@@ -1820,6 +3282,728 @@ mod tests {
         assert_eq!(error, ClientErrorCode::AccessDenied);
     }
 
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_token_exchange(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision two clients which can exchange tokens
+        let mut clients = Vec::new();
+        for _ in 0..2 {
+            let request = Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(
+                serde_json::json!({
+                    "client_uri": "https://example.com/",
+                    "token_endpoint_auth_method": "client_secret_post",
+                    "grant_types": ["urn:ietf:params:oauth:grant-type:token-exchange"],
+                }),
+            );
+
+            let response = state.request(request).await;
+            response.assert_status(StatusCode::CREATED);
+
+            let response: ClientRegistrationResponse = response.json();
+            let client_secret = response.client_secret.expect("to have a client secret");
+            clients.push((response.client_id, client_secret));
+        }
+        let (client_id, client_secret) = clients[0].clone();
+        let (backend_id, backend_secret) = clients[1].clone();
+
+        // Let's provision a user and give them a session with the first client
+        let mut repo = state.repository().await.unwrap();
+
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+
+        let browser_session = repo
+            .browser_session()
+            .add(&mut state.rng(), &state.clock, &user, None)
+            .await
+            .unwrap();
+
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let session = repo
+            .oauth2_session()
+            .add_from_browser_session(
+                &mut state.rng(),
+                &state.clock,
+                &client,
+                &browser_session,
+                "openid urn:mas:graphql:*".parse().unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let access_token_str = TokenType::AccessToken.generate(&mut state.rng());
+        let access_token = repo
+            .oauth2_access_token()
+            .add(
+                &mut state.rng(),
+                &state.clock,
+                &session,
+                access_token_str,
+                None,
+            )
+            .await
+            .unwrap();
+
+        repo.save().await.unwrap();
+
+        // Exchange the token for a narrower one
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "subject_token": access_token.access_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+                "audience": backend_id,
+                "scope": "urn:mas:graphql:*",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let response: AccessTokenResponse = response.json();
+        assert!(response.refresh_token.is_none());
+        assert_eq!(
+            response.issued_token_type,
+            Some(TokenTypeIdentifier::AccessToken)
+        );
+        assert_eq!(response.scope, Some("urn:mas:graphql:*".parse().unwrap()));
+
+        let mut repo = state.repository().await.unwrap();
+        let exchanged = repo
+            .oauth2_access_token()
+            .find_by_token(&response.access_token)
+            .await
+            .unwrap()
+            .unwrap();
+        let exchanged_session = repo
+            .oauth2_session()
+            .lookup(exchanged.session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        repo.cancel().await.unwrap();
+        assert_eq!(exchanged_session.user_id, Some(user.id));
+        assert_eq!(exchanged_session.audience, vec![backend_id.clone()]);
+        assert_eq!(exchanged_session.act, None);
+
+        // Exchanging the restricted token again keeps its audience by default
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "subject_token": exchanged.access_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let reexchanged: AccessTokenResponse = response.json();
+
+        let mut repo = state.repository().await.unwrap();
+        let reexchanged = repo
+            .oauth2_access_token()
+            .find_by_token(&reexchanged.access_token)
+            .await
+            .unwrap()
+            .unwrap();
+        let reexchanged_session = repo
+            .oauth2_session()
+            .lookup(reexchanged.session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        repo.cancel().await.unwrap();
+        assert_eq!(reexchanged_session.audience, vec![backend_id.clone()]);
+
+        // ...and can't widen it to another target
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "subject_token": exchanged.access_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+                "audience": client_id,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidTarget);
+
+        // Asking for a scope the subject token doesn't have should fail
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "subject_token": access_token.access_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+                "scope": "urn:mas:admin",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidScope);
+
+        // The other client is not allowed to exchange the token by default
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": backend_id,
+                "client_secret": backend_secret,
+                "subject_token": access_token.access_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        // Once trusted by the policy, it can act on behalf of the user
+        let state = {
+            let mut state = state;
+            state.policy_factory = crate::test_utils::policy_factory(
+                "example.com",
+                serde_json::json!({
+                    "token_exchange_clients": [backend_id]
+                }),
+            )
+            .await
+            .unwrap();
+            state
+        };
+
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": backend_id,
+                "client_secret": backend_secret,
+                "subject_token": access_token.access_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+                "actor_token": access_token.access_token,
+                "actor_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let response: AccessTokenResponse = response.json();
+        assert_eq!(response.scope, Some(session.scope.clone()));
+
+        let mut repo = state.repository().await.unwrap();
+        let exchanged = repo
+            .oauth2_access_token()
+            .find_by_token(&response.access_token)
+            .await
+            .unwrap()
+            .unwrap();
+        let exchanged_session = repo
+            .oauth2_session()
+            .lookup(exchanged.session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        repo.cancel().await.unwrap();
+        let act = exchanged_session.act.expect("to have an actor");
+        assert_eq!(act.sub, Some(user.sub));
+        assert_eq!(act.client_id, Some(client_id));
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_token_exchange_dpop_bound_subject_token(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision a client which can exchange tokens
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "token_endpoint_auth_method": "client_secret_post",
+                "grant_types": ["urn:ietf:params:oauth:grant-type:token-exchange"],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+        let client_secret = response.client_secret.expect("to have a client secret");
+
+        // Provision a user with a session bound to the key used by `dpop_proof`
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let browser_session = repo
+            .browser_session()
+            .add(&mut state.rng(), &state.clock, &user, None)
+            .await
+            .unwrap();
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let session = repo
+            .oauth2_session()
+            .add_from_browser_session(
+                &mut state.rng(),
+                &state.clock,
+                &client,
+                &browser_session,
+                Scope::from_iter([OPENID]),
+            )
+            .await
+            .unwrap();
+        let jkt = state
+            .key_store
+            .public_jwks()
+            .signing_key_for_algorithm(&JsonWebSignatureAlg::Rs256)
+            .unwrap()
+            .params()
+            .thumbprint_sha256();
+        let session = repo
+            .oauth2_session()
+            .set_dpop_jkt(session, jkt.clone())
+            .await
+            .unwrap();
+        let access_token_str = TokenType::AccessToken.generate(&mut state.rng());
+        repo.oauth2_access_token()
+            .add(
+                &mut state.rng(),
+                &state.clock,
+                &session,
+                access_token_str.clone(),
+                None,
+            )
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        let exchange = |proof: Option<String>| {
+            let mut request = Request::post(mas_router::OAuth2TokenEndpoint::PATH);
+            if let Some(proof) = proof {
+                request = request.header("DPoP", proof);
+            }
+            request.form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "subject_token": access_token_str,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            }))
+        };
+
+        // A stolen bound token can't be exchanged for a bearer token
+        let response = state.request(exchange(None)).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidDpopProof);
+
+        // With a proof from the key it is bound to, the new token is bound too
+        let response = state
+            .request(exchange(Some(dpop_proof(&state, "exchange"))))
+            .await;
+        response.assert_status(StatusCode::OK);
+        let response: AccessTokenResponse = response.json();
+
+        let mut repo = state.repository().await.unwrap();
+        let exchanged = repo
+            .oauth2_access_token()
+            .find_by_token(&response.access_token)
+            .await
+            .unwrap()
+            .unwrap();
+        let exchanged_session = repo
+            .oauth2_session()
+            .lookup(exchanged.session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        repo.cancel().await.unwrap();
+        assert_eq!(exchanged_session.dpop_jkt, Some(jkt));
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_jwt_access_tokens(pool: PgPool) {
+        setup();
+        let mut site_config = test_site_config();
+        site_config.jwt_access_tokens = Some(JwtAccessTokenConfig {
+            clients: Vec::new(),
+            scopes: vec!["urn:mas:graphql:*".to_owned()],
+            signing_alg: JsonWebSignatureAlg::Rs256,
+        });
+        let state = TestState::from_pool_with_site_config(pool, site_config)
+            .await
+            .unwrap();
+
+        // Provision a client
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "token_endpoint_auth_method": "client_secret_post",
+                "grant_types": ["client_credentials"],
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+        let client_secret = response.client_secret.expect("to have a client secret");
+
+        // Without the configured scope, the access token is opaque
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: AccessTokenResponse = response.json();
+        assert_eq!(
+            TokenType::check(&response.access_token).unwrap(),
+            TokenType::AccessToken
+        );
+        assert!(response.access_token.starts_with("mat_"));
+
+        // With it, the access token is a JWT signed by the server
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "scope": "urn:mas:graphql:*",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: AccessTokenResponse = response.json();
+
+        let jwt: Jwt<'_, HashMap<String, serde_json::Value>> =
+            Jwt::try_from(response.access_token.as_str()).unwrap();
+        jwt.verify_with_jwks(&state.key_store.public_jwks())
+            .unwrap();
+        assert_eq!(jwt.header().typ(), Some("at+jwt"));
+
+        let (_, mut claims) = jwt.into_parts();
+        let issuer = state.url_builder.oidc_issuer().to_string();
+        claims::ISS
+            .extract_required_with_options(&mut claims, issuer.as_str())
+            .unwrap();
+        assert_eq!(
+            claims::SUB.extract_required(&mut claims).unwrap(),
+            client_id
+        );
+        assert_eq!(claims["client_id"], client_id);
+        assert_eq!(claims["scope"], "urn:mas:graphql:*");
+
+        // It is stored under its `jti`, so that introspection reports the same
+        // one, and can still be used and revoked
+        let mut repo = state.repository().await.unwrap();
+        let access_token = repo
+            .oauth2_access_token()
+            .find_by_token(&response.access_token)
+            .await
+            .unwrap()
+            .unwrap();
+        repo.cancel().await.unwrap();
+        assert_eq!(claims["jti"], access_token.jti());
+        assert!(state.is_access_token_valid(&response.access_token).await);
+
+        let request = Request::post(mas_router::OAuth2Revocation::PATH).form(serde_json::json!({
+            "token": response.access_token,
+            "client_id": client_id,
+            "client_secret": client_secret,
+        }));
+        let revoke_response = state.request(request).await;
+        revoke_response.assert_status(StatusCode::OK);
+
+        assert!(!state.is_access_token_valid(&response.access_token).await);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_client_credentials_authorization_details(pool: PgPool) {
+        setup();
+        let mut site_config = test_site_config();
+        site_config.authorization_details_types = vec!["room_access".to_owned()];
+        let state = TestState::from_pool_with_site_config(pool, site_config)
+            .await
+            .unwrap();
+
+        // Provision a client
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "token_endpoint_auth_method": "client_secret_post",
+                "grant_types": ["client_credentials"],
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+        let client_secret = response.client_secret.expect("to have a client secret");
+
+        // Unknown types of authorization details are rejected
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "authorization_details": r#"[{"type":"payment_initiation"}]"#,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let error: ClientError = response.json();
+        assert_eq!(error.error, ClientErrorCode::InvalidAuthorizationDetails);
+
+        // Known ones are granted, and returned in the response
+        let authorization_details =
+            r#"[{"type":"room_access","identifier":"!room:example.com","actions":["read"]}]"#;
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "authorization_details": authorization_details,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: AccessTokenResponse = response.json();
+        assert_eq!(
+            response.authorization_details,
+            Some(authorization_details.parse().unwrap())
+        );
+
+        // They are also recorded on the session
+        let mut repo = state.repository().await.unwrap();
+        let access_token = repo
+            .oauth2_access_token()
+            .find_by_token(&response.access_token)
+            .await
+            .unwrap()
+            .unwrap();
+        let session = repo
+            .oauth2_session()
+            .lookup(access_token.session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            session.authorization_details,
+            response.authorization_details
+        );
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_jwt_bearer(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool.clone()).await.unwrap();
+        let mut rng = state.rng();
+
+        // Provision an upstream provider, and a user linked to a workload
+        // identity on it
+        let mut repo = state.repository().await.unwrap();
+        let provider = repo
+            .upstream_oauth_provider()
+            .add(
+                &mut rng,
+                &state.clock,
+                UpstreamOAuthProviderParams {
+                    issuer: Some("https://workload.example.com/".to_owned()),
+                    human_name: None,
+                    brand_name: None,
+                    scope: Scope::from_iter([OPENID]),
+                    token_endpoint_auth_method: UpstreamOAuthProviderTokenAuthMethod::None,
+                    token_endpoint_signing_alg: None,
+                    id_token_signed_response_alg: JsonWebSignatureAlg::Rs256,
+                    client_id: "client".to_owned(),
+                    encrypted_client_secret: None,
+                    claims_imports: UpstreamOAuthProviderClaimsImports::default(),
+                    authorization_endpoint_override: None,
+                    token_endpoint_override: None,
+                    userinfo_endpoint_override: None,
+                    fetch_userinfo: false,
+                    userinfo_signed_response_alg: None,
+                    jwks_uri_override: None,
+                    discovery_mode: UpstreamOAuthProviderDiscoveryMode::Disabled,
+                    pkce_mode: UpstreamOAuthProviderPkceMode::Auto,
+                    response_mode: None,
+                    additional_authorization_parameters: Vec::new(),
+                    forward_login_hint: false,
+                    ui_order: 0,
+                    on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                },
+            )
+            .await
+            .unwrap();
+
+        let user = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+
+        let link = repo
+            .upstream_oauth_link()
+            .add(
+                &mut rng,
+                &state.clock,
+                &provider,
+                "workload-1".to_owned(),
+                None,
+            )
+            .await
+            .unwrap();
+        repo.upstream_oauth_link()
+            .associate_to_user(&link, &user)
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // Trust the workload issuer, which signs its assertions with the test key
+        let mut site_config = state.site_config.clone();
+        site_config.jwt_bearer_issuers = vec![JwtBearerIssuer {
+            issuer: "https://workload.example.com/".to_owned(),
+            jwks: JwksOrJwksUri::Jwks(state.key_store.public_jwks()),
+            upstream_provider_id: provider.id,
+        }];
+        let key_store = state.key_store.clone();
+        let state = TestState::from_pool_with_site_config(pool, site_config)
+            .await
+            .unwrap();
+
+        // Provision a client which can use the JWT bearer grant
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "token_endpoint_auth_method": "client_secret_post",
+                "grant_types": ["urn:ietf:params:oauth:grant-type:jwt-bearer"],
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+        let client_secret = response.client_secret.expect("to have a client secret");
+
+        let sign = |subject: &str, jti: &str| {
+            let alg = JsonWebSignatureAlg::Rs256;
+            let key = key_store.signing_key_for_algorithm(&alg).unwrap();
+            let signer = key.params().signing_key_for_alg(&alg).unwrap();
+            let header = JsonWebSignatureHeader::new(alg).with_kid("test-rsa");
+
+            let mut claims = HashMap::new();
+            claims::ISS
+                .insert(&mut claims, "https://workload.example.com/".to_owned())
+                .unwrap();
+            claims::SUB.insert(&mut claims, subject.to_owned()).unwrap();
+            claims::AUD
+                .insert(
+                    &mut claims,
+                    state.url_builder.oauth_token_endpoint().to_string(),
+                )
+                .unwrap();
+            claims::EXP
+                .insert(&mut claims, state.clock.now() + Duration::minutes(5))
+                .unwrap();
+            claims::JTI.insert(&mut claims, jti.to_owned()).unwrap();
+
+            Jwt::<HashMap<String, serde_json::Value>>::sign(header, claims, &signer)
+                .unwrap()
+                .into_string()
+        };
+
+        // Exchange the assertion for an access token
+        let device_id = "AABBCCDDEE";
+        let assertion = sign("workload-1", "assertion-1");
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:jwt-bearer",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "assertion": assertion,
+                "scope": format!("urn:matrix:org.matrix.msc2967.client:api:* urn:matrix:org.matrix.msc2967.client:device:{device_id}"),
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let AccessTokenResponse {
+            access_token,
+            refresh_token,
+            ..
+        } = response.json();
+        assert!(refresh_token.is_none());
+        assert!(state.is_access_token_valid(&access_token).await);
+
+        // The session belongs to the linked user
+        let mut repo = state.repository().await.unwrap();
+        let token = repo
+            .oauth2_access_token()
+            .find_by_token(&access_token)
+            .await
+            .unwrap()
+            .unwrap();
+        let session = repo
+            .oauth2_session()
+            .lookup(token.session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        repo.cancel().await.unwrap();
+        assert_eq!(session.user_id, Some(user.id));
+
+        // The assertion can't be replayed
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:jwt-bearer",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "assertion": assertion,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidGrant);
+
+        // Subjects which aren't linked to a user are rejected
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:jwt-bearer",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "assertion": sign("workload-2", "assertion-2"),
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidGrant);
+    }
+
     #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
     async fn test_unsupported_grant(pool: PgPool) {
         setup();
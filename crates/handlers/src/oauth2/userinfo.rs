@@ -4,36 +4,59 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
+use std::{collections::HashMap, sync::LazyLock};
+
 use axum::{
     Json,
     extract::State,
+    http::{HeaderMap, Method},
     response::{IntoResponse, Response},
 };
+use axum_extra::typed_header::TypedHeader;
+use headers::ContentType;
 use hyper::StatusCode;
 use mas_axum_utils::{
     jwt::JwtResponse,
     record_error,
     user_authorization::{AuthorizationVerificationError, UserAuthorization},
 };
+use mas_data_model::{Client, SiteConfig};
+use mas_iana::jose::JsonWebSignatureAlg;
 use mas_jose::{
     constraints::Constrainable,
+    jwe::JsonWebEncryptionHeader,
     jwt::{JsonWebSignatureHeader, Jwt},
 };
-use mas_keystore::Keystore;
+use mas_keystore::{Encrypter, Keystore};
 use mas_router::UrlBuilder;
 use mas_storage::{BoxClock, BoxRepository, BoxRng, oauth2::OAuth2ClientRepository};
+use mime::Mime;
 use serde::Serialize;
+use serde_json::Value;
 use serde_with::skip_serializing_none;
 use thiserror::Error;
 use ulid::Ulid;
 
-use crate::{BoundActivityTracker, impl_from_error_for_route};
+use super::{
+    dpop::{self, DpopError},
+    response_encryption::{ResponseEncryptionError, encrypt_for_client},
+};
+use crate::{BoundActivityTracker, impl_from_error_for_route, oauth2::load_requested_claims};
+
+static APPLICATION_JWT: LazyLock<Mime> =
+    LazyLock::new(|| "application/jwt".parse().expect("valid media type"));
 
 #[skip_serializing_none]
 #[derive(Serialize)]
 struct UserInfo {
     sub: String,
-    username: String,
+
+    /// The username, omitted for clients with pairwise subject identifiers
+    username: Option<String>,
+
+    /// Additional claims requested through the `claims` parameter
+    #[serde(flatten)]
+    claims: HashMap<String, Value>,
 }
 
 #[derive(Serialize)]
@@ -54,6 +77,9 @@ pub enum RouteError {
         #[from] AuthorizationVerificationError<mas_storage::RepositoryError>,
     ),
 
+    #[error("invalid DPoP proof")]
+    InvalidDpopProof(#[source] DpopError),
+
     #[error("session is not allowed to access the userinfo endpoint")]
     Unauthorized,
 
@@ -67,9 +93,22 @@ pub enum RouteError {
     NoSuchUser(Ulid),
 }
 
+impl From<DpopError> for RouteError {
+    fn from(e: DpopError) -> Self {
+        if e.is_internal() {
+            Self::Internal(Box::new(e))
+        } else {
+            Self::InvalidDpopProof(e)
+        }
+    }
+}
+
 impl_from_error_for_route!(mas_storage::RepositoryError);
 impl_from_error_for_route!(mas_keystore::WrongAlgorithmError);
 impl_from_error_for_route!(mas_jose::jwt::JwtSignatureError);
+impl_from_error_for_route!(mas_data_model::MissingPairwiseSubjectSecretError);
+impl_from_error_for_route!(ResponseEncryptionError);
+impl_from_error_for_route!(serde_json::Error);
 
 impl IntoResponse for RouteError {
     fn into_response(self) -> axum::response::Response {
@@ -87,9 +126,9 @@ impl IntoResponse for RouteError {
             | Self::NoSuchUser(_) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
             }
-            Self::AuthorizationVerificationError(_) | Self::Unauthorized => {
-                StatusCode::UNAUTHORIZED.into_response()
-            }
+            Self::AuthorizationVerificationError(_)
+            | Self::InvalidDpopProof(_)
+            | Self::Unauthorized => StatusCode::UNAUTHORIZED.into_response(),
         };
 
         (sentry_event_id, response).into_response()
@@ -104,9 +143,27 @@ pub async fn get(
     activity_tracker: BoundActivityTracker,
     mut repo: BoxRepository,
     State(key_store): State<Keystore>,
+    State(encrypter): State<Encrypter>,
+    State(http_client): State<reqwest::Client>,
+    State(site_config): State<SiteConfig>,
+    method: Method,
+    headers: HeaderMap,
     user_authorization: UserAuthorization,
 ) -> Result<Response, RouteError> {
-    let session = user_authorization.protected(&mut repo, &clock).await?;
+    // Tokens presented with the DPoP scheme must come with a proof for this request
+    let dpop_jkt = dpop::verify_resource_proof(
+        &clock,
+        &mut repo,
+        &headers,
+        &method,
+        &url_builder.oidc_userinfo_endpoint(),
+        user_authorization.dpop_token(),
+    )
+    .await?;
+
+    let session = user_authorization
+        .protected(&mut repo, &clock, dpop_jkt.as_deref())
+        .await?;
 
     // This endpoint requires the `openid` scope.
     if !session.scope.contains("openid") {
@@ -128,37 +185,423 @@ pub async fn get(
         .await?
         .ok_or(RouteError::NoSuchUser(user_id))?;
 
-    let user_info = UserInfo {
-        sub: user.sub.clone(),
-        username: user.username.clone(),
-    };
-
     let client = repo
         .oauth2_client()
         .lookup(session.client_id)
         .await?
         .ok_or(RouteError::NoSuchClient(session.client_id))?;
 
-    repo.save().await?;
+    let claims = load_requested_claims(
+        &mut repo,
+        &client,
+        &user,
+        &session.scope,
+        session
+            .requested_claims
+            .as_ref()
+            .and_then(|claims| claims.userinfo.as_ref()),
+    )
+    .await?;
 
-    if let Some(alg) = client.userinfo_signed_response_alg {
-        let key = key_store
-            .signing_key_for_algorithm(&alg)
-            .ok_or(RouteError::InvalidSigningKey)?;
+    // The username would let clients with pairwise subject identifiers
+    // correlate users across sectors
+    let username = client
+        .pairwise_sector_identifier()
+        .is_none()
+        .then(|| user.username.clone());
 
-        let signer = key.params().signing_key_for_alg(&alg)?;
-        let header = JsonWebSignatureHeader::new(alg)
-            .with_kid(key.kid().ok_or(RouteError::InvalidSigningKey)?);
+    let user_info = UserInfo {
+        sub: client.subject_for(&user, site_config.pairwise_subject_secret.as_ref())?,
+        username,
+        claims,
+    };
+
+    repo.save().await?;
 
-        let user_info = SignedUserInfo {
-            iss: url_builder.oidc_issuer().to_string(),
-            aud: client.client_id,
-            user_info,
+    let signed_response_alg = client.userinfo_signed_response_alg.clone();
+    let Some((alg, enc)) = client.userinfo_encrypted_response() else {
+        let response = if let Some(alg) = signed_response_alg {
+            let token =
+                sign_user_info(&mut rng, &key_store, &url_builder, &client, alg, user_info)?;
+            JwtResponse(token).into_response()
+        } else {
+            Json(user_info).into_response()
         };
 
-        let token = Jwt::sign_with_rng(&mut rng, header, user_info, &signer)?;
-        Ok(JwtResponse(token).into_response())
+        return Ok(response);
+    };
+
+    let header = JsonWebEncryptionHeader::new(alg.clone(), enc.clone());
+    let (header, payload) = if let Some(alg) = signed_response_alg {
+        // Signed responses are then encrypted, making them nested JWTs
+        let token = sign_user_info(&mut rng, &key_store, &url_builder, &client, alg, user_info)?;
+        (
+            header.with_cty("JWT".to_owned()),
+            token.into_string().into_bytes(),
+        )
     } else {
-        Ok(Json(user_info).into_response())
+        // Responses which are only encrypted contain the plain JSON claims
+        (header, serde_json::to_vec(&user_info)?)
+    };
+
+    let token = encrypt_for_client(
+        &mut rng,
+        &http_client,
+        &encrypter,
+        &client,
+        header,
+        &payload,
+    )
+    .await?;
+
+    let content_type = ContentType::from(APPLICATION_JWT.clone());
+    Ok((TypedHeader(content_type), token).into_response())
+}
+
+/// Sign the userinfo response with the given algorithm
+fn sign_user_info(
+    rng: &mut BoxRng,
+    key_store: &Keystore,
+    url_builder: &UrlBuilder,
+    client: &Client,
+    alg: JsonWebSignatureAlg,
+    user_info: UserInfo,
+) -> Result<Jwt<'static, SignedUserInfo>, RouteError> {
+    let key = key_store
+        .signing_key_for_algorithm(&alg)
+        .ok_or(RouteError::InvalidSigningKey)?;
+
+    let signer = key.params().signing_key_for_alg(&alg)?;
+    let header =
+        JsonWebSignatureHeader::new(alg).with_kid(key.kid().ok_or(RouteError::InvalidSigningKey)?);
+
+    let user_info = SignedUserInfo {
+        iss: url_builder.oidc_issuer().to_string(),
+        aud: client.client_id.clone(),
+        user_info,
+    };
+
+    Ok(Jwt::sign_with_rng(rng, header, user_info, &signer)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use base64ct::{Base64UrlUnpadded, Encoding};
+    use hyper::{Request, header::AUTHORIZATION};
+    use mas_data_model::TokenType;
+    use mas_jose::claims;
+    use mas_router::SimpleRoute;
+    use mas_storage::Clock;
+    use oauth2_types::{
+        registration::ClientRegistrationResponse,
+        requests::ClaimsParameter,
+        scope::{OPENID, PROFILE, Scope},
+    };
+    use sha2::{Digest, Sha256};
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    /// Build a `DPoP` proof for a GET request to the userinfo endpoint with
+    /// the given access token
+    fn dpop_proof(state: &TestState, jti: &str, access_token: &str) -> String {
+        let alg = JsonWebSignatureAlg::Rs256;
+        let key = state.key_store.signing_key_for_algorithm(&alg).unwrap();
+        let signer = key.params().signing_key_for_alg(&alg).unwrap();
+        let jwk = state
+            .key_store
+            .public_jwks()
+            .signing_key_for_algorithm(&alg)
+            .unwrap()
+            .clone();
+        let header = JsonWebSignatureHeader::new(alg)
+            .with_typ("dpop+jwt".to_owned())
+            .with_jwk(jwk);
+
+        let mut claims = HashMap::new();
+        claims::IAT.insert(&mut claims, state.clock.now()).unwrap();
+        claims::JTI.insert(&mut claims, jti.to_owned()).unwrap();
+        claims.insert("htm".to_owned(), serde_json::json!("GET"));
+        claims.insert(
+            "htu".to_owned(),
+            serde_json::json!(state.url_builder.oidc_userinfo_endpoint()),
+        );
+        claims.insert(
+            "ath".to_owned(),
+            serde_json::json!(Base64UrlUnpadded::encode_string(&Sha256::digest(
+                access_token
+            ))),
+        );
+
+        Jwt::<HashMap<String, Value>>::sign(header, claims, &signer)
+            .unwrap()
+            .into_string()
+    }
+
+    /// Start a session bound to the key used by [`dpop_proof`], and return an
+    /// access token for it
+    async fn dpop_bound_access_token(state: &TestState) -> String {
+        // Provision a client
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "token_endpoint_auth_method": "none",
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "dpop_bound_access_tokens": true,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let ClientRegistrationResponse { client_id, .. } = response.json();
+
+        // Provision a user and a session bound to the key
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let browser_session = repo
+            .browser_session()
+            .add(&mut state.rng(), &state.clock, &user, None)
+            .await
+            .unwrap();
+        let session = repo
+            .oauth2_session()
+            .add_from_browser_session(
+                &mut state.rng(),
+                &state.clock,
+                &client,
+                &browser_session,
+                Scope::from_iter([OPENID]),
+            )
+            .await
+            .unwrap();
+
+        let jkt = state
+            .key_store
+            .public_jwks()
+            .signing_key_for_algorithm(&JsonWebSignatureAlg::Rs256)
+            .unwrap()
+            .params()
+            .thumbprint_sha256();
+        let session = repo
+            .oauth2_session()
+            .set_dpop_jkt(session, jkt)
+            .await
+            .unwrap();
+
+        let access_token = TokenType::AccessToken.generate(&mut state.rng());
+        repo.oauth2_access_token()
+            .add(
+                &mut state.rng(),
+                &state.clock,
+                &session,
+                access_token.clone(),
+                None,
+            )
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        access_token
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_dpop_bound_token_used_as_bearer(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let access_token = dpop_bound_access_token(&state).await;
+
+        // Presenting the token as a bearer token should fail
+        let request = Request::get(mas_router::OidcUserinfo::PATH)
+            .bearer(&access_token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+
+        // Even if a valid proof is sent along
+        let request = Request::get(mas_router::OidcUserinfo::PATH)
+            .header("DPoP", dpop_proof(&state, "bearer", &access_token))
+            .bearer(&access_token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+
+        // Presenting it with the DPoP scheme but no proof should also fail
+        let request = Request::get(mas_router::OidcUserinfo::PATH)
+            .header(AUTHORIZATION, format!("DPoP {access_token}"))
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_dpop_bound_token_with_proof(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let access_token = dpop_bound_access_token(&state).await;
+
+        // A proof bound to another access token should fail
+        let request = Request::get(mas_router::OidcUserinfo::PATH)
+            .header(AUTHORIZATION, format!("DPoP {access_token}"))
+            .header("DPoP", dpop_proof(&state, "other", "another-token"))
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+
+        // A valid proof should succeed
+        let proof = dpop_proof(&state, "valid", &access_token);
+        let request = Request::get(mas_router::OidcUserinfo::PATH)
+            .header(AUTHORIZATION, format!("DPoP {access_token}"))
+            .header("DPoP", &proof)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let user_info: serde_json::Value = response.json();
+        assert_eq!(user_info["username"], "alice");
+
+        // Replaying the proof should fail
+        let request = Request::get(mas_router::OidcUserinfo::PATH)
+            .header(AUTHORIZATION, format!("DPoP {access_token}"))
+            .header("DPoP", &proof)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    /// Start a session for the given user on a new client with pairwise
+    /// subject identifiers in the given sector, asking for the
+    /// `preferred_username` claim, and return an access token for it
+    async fn pairwise_access_token(
+        state: &TestState,
+        user: &mas_data_model::User,
+        sector: &str,
+        scope: Scope,
+    ) -> String {
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": format!("https://{sector}/"),
+                "redirect_uris": [format!("https://{sector}/callback")],
+                "token_endpoint_auth_method": "none",
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "subject_type": "pairwise",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let ClientRegistrationResponse { client_id, .. } = response.json();
+
+        let mut repo = state.repository().await.unwrap();
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let browser_session = repo
+            .browser_session()
+            .add(&mut state.rng(), &state.clock, user, None)
+            .await
+            .unwrap();
+        let session = repo
+            .oauth2_session()
+            .add_from_browser_session(
+                &mut state.rng(),
+                &state.clock,
+                &client,
+                &browser_session,
+                scope,
+            )
+            .await
+            .unwrap();
+        let requested_claims: ClaimsParameter = serde_json::from_value(serde_json::json!({
+            "userinfo": { "preferred_username": null },
+        }))
+        .unwrap();
+        let session = repo
+            .oauth2_session()
+            .set_requested_claims(session, Some(requested_claims))
+            .await
+            .unwrap();
+
+        let access_token = TokenType::AccessToken.generate(&mut state.rng());
+        repo.oauth2_access_token()
+            .add(
+                &mut state.rng(),
+                &state.clock,
+                &session,
+                access_token.clone(),
+                None,
+            )
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        access_token
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_pairwise_clients_get_no_shared_identifier(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        let scope = Scope::from_iter([OPENID]);
+        let first = pairwise_access_token(&state, &user, "first.example.com", scope.clone()).await;
+        let second = pairwise_access_token(&state, &user, "second.example.com", scope).await;
+
+        let mut responses = Vec::new();
+        for access_token in [first, second] {
+            let request = Request::get(mas_router::OidcUserinfo::PATH)
+                .bearer(&access_token)
+                .empty();
+            let response = state.request(request).await;
+            response.assert_status(StatusCode::OK);
+            let user_info: serde_json::Value = response.json();
+            responses.push(user_info);
+        }
+
+        // The clients only get their own subject identifier, and neither the
+        // username nor the preferred_username they asked for
+        for user_info in &responses {
+            let user_info = user_info.as_object().unwrap();
+            assert_eq!(user_info.keys().collect::<Vec<_>>(), ["sub"]);
+            assert_ne!(user_info["sub"], user.sub);
+        }
+        assert_ne!(responses[0]["sub"], responses[1]["sub"]);
+
+        // Once the user consented to the profile scope, the preferred_username
+        // is released
+        let access_token = pairwise_access_token(
+            &state,
+            &user,
+            "third.example.com",
+            Scope::from_iter([OPENID, PROFILE]),
+        )
+        .await;
+        let request = Request::get(mas_router::OidcUserinfo::PATH)
+            .bearer(&access_token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let user_info: serde_json::Value = response.json();
+        assert_eq!(user_info["preferred_username"], "alice");
+        assert!(user_info.get("username").is_none());
     }
 }
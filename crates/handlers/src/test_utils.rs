@@ -85,6 +85,7 @@ pub(crate) async fn policy_factory(
         register: "register/violation".to_owned(),
         client_registration: "client_registration/violation".to_owned(),
         authorization_grant: "authorization_grant/violation".to_owned(),
+        token_exchange: "token_exchange/violation".to_owned(),
         email: "email/violation".to_owned(),
     };
 
@@ -133,6 +134,7 @@ pub fn test_site_config() -> SiteConfig {
     SiteConfig {
         access_token_ttl: Duration::try_minutes(5).unwrap(),
         compat_token_ttl: Duration::try_minutes(5).unwrap(),
+        refresh_token_reuse_grace_period: Duration::try_seconds(30).unwrap(),
         server_name: "example.com".to_owned(),
         policy_uri: Some("https://example.com/policy".parse().unwrap()),
         tos_uri: Some("https://example.com/tos".parse().unwrap()),
@@ -150,6 +152,13 @@ pub fn test_site_config() -> SiteConfig {
         session_expiration: None,
         login_with_email_allowed: true,
         plan_management_iframe_uri: None,
+        pairwise_subject_secret: Some([0x42; 32]),
+        jwt_bearer_issuers: Vec::new(),
+        software_statement_publishers: Vec::new(),
+        jwt_access_tokens: None,
+        authorization_details_types: Vec::new(),
+        acr_levels: Vec::new(),
+        resource_servers: Vec::new(),
     }
 }
 
@@ -252,6 +261,8 @@ impl TestState {
             homeserver_connection.clone(),
             url_builder.clone(),
             &site_config,
+            &key_store,
+            http_client.clone(),
             shutdown_token.child_token(),
         )
         .await
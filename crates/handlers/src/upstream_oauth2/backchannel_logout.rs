@@ -22,10 +22,10 @@ use mas_oidc_client::{
     requests::jose::{JwtVerificationData, verify_signed_jwt},
 };
 use mas_storage::{
-    BoxClock, BoxRepository, BoxRng, Pagination,
+    BoxClock, BoxRepository, BoxRng, Clock, Pagination, RepositoryError,
     compat::CompatSessionFilter,
     oauth2::OAuth2SessionFilter,
-    queue::{QueueJobRepositoryExt as _, SyncDevicesJob},
+    queue::{BackchannelLogoutJob, QueueJobRepositoryExt as _, SyncDevicesJob},
     upstream_oauth2::UpstreamOAuthSessionFilter,
     user::BrowserSessionFilter,
 };
@@ -249,6 +249,7 @@ pub(crate) async fn post(
             let filter = BrowserSessionFilter::new()
                 .authenticated_by_upstream_sessions_only(auth_session_filter)
                 .active_only();
+            schedule_backchannel_logouts(&mut rng, &clock, &mut repo, filter).await?;
             let affected = repo.browser_session().finish_bulk(&clock, filter).await?;
             tracing::info!("Finished {affected} browser sessions");
         }
@@ -275,6 +276,14 @@ pub(crate) async fn post(
                 }
             }
 
+            schedule_backchannel_logouts(
+                &mut rng,
+                &clock,
+                &mut repo,
+                browser_session_filter.active_only(),
+            )
+            .await?;
+
             let browser_sessions_affected = repo
                 .browser_session()
                 .finish_bulk(&clock, browser_session_filter.active_only())
@@ -314,3 +323,31 @@ pub(crate) async fn post(
 
     Ok(())
 }
+
+/// Let the clients of the browser sessions matching the filter know that the
+/// user logged out, through back-channel logout
+///
+/// This must be called before finishing the browser sessions.
+async fn schedule_backchannel_logouts(
+    rng: &mut BoxRng,
+    clock: &BoxClock,
+    repo: &mut BoxRepository,
+    filter: BrowserSessionFilter<'_>,
+) -> Result<(), RepositoryError> {
+    let logged_out_at = clock.now();
+    let mut cursor = Pagination::first(1000);
+    loop {
+        let browser_sessions = repo.browser_session().list(filter, cursor).await?;
+        for browser_session in browser_sessions.edges {
+            let job = BackchannelLogoutJob::for_browser_session(&browser_session, logged_out_at);
+            repo.queue_job().schedule_job(rng, clock, job).await?;
+            cursor = cursor.after(browser_session.id);
+        }
+
+        if !browser_sessions.has_next_page {
+            break;
+        }
+    }
+
+    Ok(())
+}
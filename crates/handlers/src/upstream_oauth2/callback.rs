@@ -303,6 +303,7 @@ pub(crate) async fn handler(
             code: code.clone(),
             redirect_uri: Some(redirect_uri),
             code_verifier: session.code_challenge_verifier.clone(),
+            resource: None,
         }),
         clock.now(),
         &mut rng,
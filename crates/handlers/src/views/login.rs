@@ -93,8 +93,16 @@ pub(crate) async fn get(
             .record_browser_session(&clock, &session)
             .await;
 
-        let reply = query.go_next(&url_builder);
-        return Ok((cookie_jar, reply).into_response());
+        // Unless the client asked for a more recent or a stronger
+        // authentication, there is no need to log in again
+        if !query
+            .requires_reauthentication(&mut repo, &clock, &site_config, &session)
+            .await
+            .map_err(InternalError::from_anyhow)?
+        {
+            let reply = query.go_next(&url_builder);
+            return Ok((cookie_jar, reply).into_response());
+        }
     }
 
     let providers = repo.upstream_oauth_provider().all_enabled().await?;
@@ -4,33 +4,47 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
+use std::collections::BTreeSet;
+
 use axum::{
     extract::{Form, State},
-    response::IntoResponse,
+    response::{Html, IntoResponse, Redirect, Response},
 };
 use mas_axum_utils::{
     InternalError, SessionInfoExt,
     cookies::CookieJar,
     csrf::{CsrfExt, ProtectedForm},
 };
+use mas_data_model::BrowserSession;
 use mas_router::{PostAuthAction, UrlBuilder};
-use mas_storage::{BoxClock, BoxRepository, user::BrowserSessionRepository};
+use mas_storage::{
+    BoxClock, BoxRepository, BoxRng, Clock, Pagination, RepositoryError,
+    oauth2::{OAuth2ClientRepository, OAuth2SessionFilter, OAuth2SessionRepository},
+    queue::{BackchannelLogoutJob, QueueJobRepositoryExt as _},
+    user::BrowserSessionRepository,
+};
+use mas_templates::{FrontchannelLogoutContext, TemplateContext, Templates};
+use url::Url;
 
-use crate::BoundActivityTracker;
+use crate::{BoundActivityTracker, PreferredLanguage};
 
 #[tracing::instrument(name = "handlers.views.logout.post", skip_all)]
 pub(crate) async fn post(
+    mut rng: BoxRng,
     clock: BoxClock,
     mut repo: BoxRepository,
     cookie_jar: CookieJar,
+    PreferredLanguage(locale): PreferredLanguage,
+    State(templates): State<Templates>,
     State(url_builder): State<UrlBuilder>,
     activity_tracker: BoundActivityTracker,
     Form(form): Form<ProtectedForm<Option<PostAuthAction>>>,
-) -> Result<impl IntoResponse, InternalError> {
+) -> Result<Response, InternalError> {
     let form = cookie_jar.verify_form(&clock, form)?;
 
     let (session_info, cookie_jar) = cookie_jar.session_info();
 
+    let mut logout_uris = Vec::new();
     if let Some(session_id) = session_info.current_session_id() {
         let maybe_session = repo.browser_session().lookup(session_id).await?;
         if let Some(session) = maybe_session {
@@ -39,6 +53,17 @@ pub(crate) async fn post(
                     .record_browser_session(&clock, &session)
                     .await;
 
+                // Let the clients know that the user logged out
+                repo.queue_job()
+                    .schedule_job(
+                        &mut rng,
+                        &clock,
+                        BackchannelLogoutJob::for_browser_session(&session, clock.now()),
+                    )
+                    .await?;
+
+                logout_uris = frontchannel_logout_uris(&url_builder, &mut repo, &session).await?;
+
                 repo.browser_session().finish(&clock, session).await?;
             }
         }
@@ -51,10 +76,228 @@ pub(crate) async fn post(
     let cookie_jar = cookie_jar.update_session_info(&session_info.mark_session_ended());
 
     let destination = if let Some(action) = form {
-        action.go_next(&url_builder)
+        action.relative_url(&url_builder)
     } else {
-        url_builder.redirect(&mas_router::Login::default())
+        url_builder.relative_url_for(&mas_router::Login::default())
+    };
+
+    if logout_uris.is_empty() {
+        return Ok((cookie_jar, Redirect::to(&destination)).into_response());
+    }
+
+    let ctx = FrontchannelLogoutContext::new(logout_uris, destination).with_language(locale);
+    let content = templates.render_frontchannel_logout(&ctx)?;
+
+    Ok((cookie_jar, Html(content)).into_response())
+}
+
+/// Collect the [front-channel logout] URIs of the clients which have an active
+/// session started from the given browser session.
+///
+/// This must be called before the browser session is finished, as it ends all
+/// the sessions which were started from it.
+///
+/// [front-channel logout]: https://openid.net/specs/openid-connect-frontchannel-1_0.html
+pub(crate) async fn frontchannel_logout_uris(
+    url_builder: &UrlBuilder,
+    repo: &mut BoxRepository,
+    browser_session: &BrowserSession,
+) -> Result<Vec<Url>, RepositoryError> {
+    let filter = OAuth2SessionFilter::new()
+        .for_browser_session(browser_session)
+        .active_only();
+
+    let mut client_ids = BTreeSet::new();
+    let mut pagination = Pagination::first(100);
+    loop {
+        let page = repo.oauth2_session().list(filter, pagination).await?;
+        for session in &page.edges {
+            client_ids.insert(session.client_id);
+            pagination = pagination.after(session.id);
+        }
+
+        if !page.has_next_page {
+            break;
+        }
+    }
+
+    let clients = repo.oauth2_client().load_batch(client_ids).await?;
+    let issuer = url_builder.oidc_issuer();
+    let logout_uris = clients
+        .values()
+        .filter_map(|client| client.frontchannel_logout_uri_for(&issuer, browser_session.id))
+        .collect();
+
+    Ok(logout_uris)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode, header::LOCATION};
+    use mas_axum_utils::{SessionInfoExt, csrf::CsrfExt};
+    use mas_data_model::{BrowserSession, Client};
+    use mas_iana::oauth::OAuthClientAuthenticationMethod;
+    use mas_router::SimpleRoute;
+    use mas_storage::{
+        Clock, RepositoryAccess,
+        oauth2::{OAuth2ClientRepository, OAuth2SessionRepository, OAuth2StaticClientParams},
+        user::{BrowserSessionRepository, UserRepository},
     };
+    use oauth2_types::scope::{OPENID, Scope};
+    use sqlx::PgPool;
+    use ulid::Ulid;
+    use url::Url;
 
-    Ok((cookie_jar, destination))
+    use crate::test_utils::{CookieHelper, RequestBuilderExt, ResponseExt, TestState, setup};
+
+    /// Provision a client with the given front-channel logout URI
+    async fn client(
+        state: &TestState,
+        frontchannel_logout_uri: Option<&str>,
+        frontchannel_logout_session_required: bool,
+    ) -> Client {
+        let mut rng = state.rng();
+        let mut repo = state.repository().await.unwrap();
+        let client = repo
+            .oauth2_client()
+            .upsert_static(OAuth2StaticClientParams {
+                client_id: Ulid::from_datetime_with_source(state.clock.now().into(), &mut rng),
+                client_name: None,
+                client_auth_method: OAuthClientAuthenticationMethod::None,
+                encrypted_client_secret: None,
+                jwks: None,
+                jwks_uri: None,
+                redirect_uris: vec!["https://example.com/callback".parse().unwrap()],
+                post_logout_redirect_uris: Vec::new(),
+                request_uris: Vec::new(),
+                backchannel_logout_uri: None,
+                frontchannel_logout_uri: frontchannel_logout_uri.map(|uri| uri.parse().unwrap()),
+                frontchannel_logout_session_required,
+                dpop_bound_access_tokens: false,
+                tls_client_auth_subject_dn: None,
+                tls_client_auth_san_dns: None,
+                require_pushed_authorization_requests: false,
+            })
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+        client
+    }
+
+    /// Log a user in, with a session on each of the given clients
+    async fn logged_in(state: &TestState, clients: &[&Client]) -> BrowserSession {
+        let mut rng = state.rng();
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let browser_session = repo
+            .browser_session()
+            .add(&mut rng, &state.clock, &user, None)
+            .await
+            .unwrap();
+        for client in clients {
+            repo.oauth2_session()
+                .add_from_browser_session(
+                    &mut rng,
+                    &state.clock,
+                    client,
+                    &browser_session,
+                    Scope::from_iter([OPENID]),
+                )
+                .await
+                .unwrap();
+        }
+        repo.save().await.unwrap();
+        browser_session
+    }
+
+    /// Submit the logout form with the given browser session
+    async fn logout(
+        state: &TestState,
+        browser_session: &BrowserSession,
+    ) -> hyper::Response<String> {
+        let cookies = CookieHelper::new();
+        let (csrf_token, cookie_jar) = state
+            .cookie_jar()
+            .set_session(browser_session)
+            .csrf_token(&state.clock, state.rng());
+        cookies.import(cookie_jar);
+
+        let request = Request::post(mas_router::Logout::PATH).form(serde_json::json!({
+            "csrf": csrf_token.form_value(),
+        }));
+        let request = cookies.with_cookies(request);
+        state.request(request).await
+    }
+
+    /// Get the URIs of the iframes on the front-channel logout page
+    fn iframe_uris(body: &str) -> Vec<Url> {
+        body.split("<iframe hidden src=\"")
+            .skip(1)
+            .map(|rest| {
+                let uri = rest.split('"').next().unwrap();
+                let uri = uri.replace("&#x2f;", "/").replace("&amp;", "&");
+                uri.parse().unwrap()
+            })
+            .collect()
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_frontchannel_logout(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let with_session = client(&state, Some("https://one.example.com/logout"), true).await;
+        let without_session = client(&state, Some("https://two.example.com/logout"), false).await;
+        let browser_session = logged_in(&state, &[&with_session, &without_session]).await;
+
+        let response = logout(&state, &browser_session).await;
+        response.assert_status(StatusCode::OK);
+
+        let mut uris = iframe_uris(response.body());
+        uris.sort();
+        let issuer = state.url_builder.oidc_issuer();
+        let expected: Url = Url::parse_with_params(
+            "https://one.example.com/logout",
+            &[
+                ("iss", issuer.as_str()),
+                ("sid", &browser_session.id.to_string()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            uris,
+            vec![expected, "https://two.example.com/logout".parse().unwrap()]
+        );
+
+        // The page still sends the user to the login page once done
+        assert!(response.body().contains("id=\"continue\""));
+        assert!(response.body().contains("&#x2f;login"));
+
+        // The browser session ended
+        let mut repo = state.repository().await.unwrap();
+        let browser_session = repo
+            .browser_session()
+            .lookup(browser_session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(browser_session.finished_at.is_some());
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_logout_without_frontchannel_clients(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let client = client(&state, None, false).await;
+        let browser_session = logged_in(&state, &[&client]).await;
+
+        // Without any client registered for front-channel logout, the user is
+        // redirected right away
+        let response = logout(&state, &browser_session).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        response.assert_header_value(LOCATION, "/login");
+    }
 }
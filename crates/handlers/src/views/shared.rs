@@ -5,12 +5,14 @@
 // Please see LICENSE files in the repository root for full details.
 
 use anyhow::Context;
+use mas_data_model::{AuthenticationCheck, BrowserSession, SiteConfig};
 use mas_router::{PostAuthAction, Route, UrlBuilder};
 use mas_storage::{
-    RepositoryAccess,
+    Clock, RepositoryAccess,
     compat::CompatSsoLoginRepository,
     oauth2::OAuth2AuthorizationGrantRepository,
     upstream_oauth2::{UpstreamOAuthLinkRepository, UpstreamOAuthProviderRepository},
+    user::BrowserSessionRepository,
 };
 use mas_templates::{PostAuthContext, PostAuthContextInner};
 use serde::{Deserialize, Serialize};
@@ -43,6 +45,41 @@ impl OptionalPostAuthAction {
         self.go_next_or_default(url_builder, &mas_router::Index)
     }
 
+    /// Whether the user has to log in again before going on with the action,
+    /// even though they already have a session, because the authorization
+    /// grant it continues asks for a more recent or a stronger authentication
+    pub async fn requires_reauthentication(
+        &self,
+        repo: &mut impl RepositoryAccess,
+        clock: &impl Clock,
+        site_config: &SiteConfig,
+        session: &BrowserSession,
+    ) -> anyhow::Result<bool> {
+        let Some(PostAuthAction::ContinueAuthorizationGrant { id }) = &self.post_auth_action else {
+            return Ok(false);
+        };
+
+        let grant = repo
+            .oauth2_authorization_grant()
+            .lookup(*id)
+            .await?
+            .context("Failed to load authorization grant")?;
+
+        let last_authentication = repo
+            .browser_session()
+            .get_last_authentication(session)
+            .await?;
+
+        let check = grant.check_authentication(
+            last_authentication.as_ref(),
+            &site_config.acr_levels,
+            clock.now(),
+        );
+
+        // If logging in again doesn't help, let the grant fail instead
+        Ok(check == AuthenticationCheck::Reauthenticate)
+    }
+
     pub async fn load_context<'a>(
         &'a self,
         repo: &'a mut impl RepositoryAccess,
@@ -6,9 +6,9 @@
 
 use std::collections::HashSet;
 
-use mas_iana::jose::{JsonWebKeyType, JsonWebKeyUse, JsonWebSignatureAlg};
+use mas_iana::jose::{JsonWebEncryptionAlg, JsonWebKeyType, JsonWebKeyUse, JsonWebSignatureAlg};
 
-use crate::jwt::JsonWebSignatureHeader;
+use crate::{jwe::JsonWebEncryptionHeader, jwt::JsonWebSignatureHeader};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Constraint<'a> {
@@ -20,6 +20,10 @@ pub enum Constraint<'a> {
         constraint_algs: &'a [JsonWebSignatureAlg],
     },
 
+    EncryptionAlg {
+        constraint_alg: &'a JsonWebEncryptionAlg,
+    },
+
     Kid {
         constraint_kid: &'a str,
     },
@@ -44,6 +48,11 @@ impl<'a> Constraint<'a> {
         Constraint::Algs { constraint_algs }
     }
 
+    #[must_use]
+    pub fn encryption_alg(constraint_alg: &'a JsonWebEncryptionAlg) -> Self {
+        Constraint::EncryptionAlg { constraint_alg }
+    }
+
     #[must_use]
     pub fn kid(constraint_kid: &'a str) -> Self {
         Constraint::Kid { constraint_kid }
@@ -77,6 +86,16 @@ pub trait Constrainable {
         &[]
     }
 
+    /// Key management algorithm this key is restricted to, if any
+    fn encryption_alg(&self) -> Option<JsonWebEncryptionAlg> {
+        None
+    }
+
+    /// List of available key management algorithms for this key
+    fn encryption_algs(&self) -> &[JsonWebEncryptionAlg] {
+        &[]
+    }
+
     /// Key ID (`kid`) of this key
     fn kid(&self) -> Option<&str> {
         None
@@ -127,6 +146,23 @@ impl Constraint<'_> {
                     ConstraintDecision::Negative
                 }
             }
+            Constraint::EncryptionAlg { constraint_alg } => {
+                if let Some(alg) = constrainable.encryption_alg() {
+                    if alg == **constraint_alg {
+                        ConstraintDecision::Positive
+                    } else {
+                        ConstraintDecision::Negative
+                    }
+                // Keys restricted to a signature algorithm can't be used for
+                // encryption
+                } else if constrainable.alg().is_none()
+                    && constrainable.encryption_algs().contains(constraint_alg)
+                {
+                    ConstraintDecision::Neutral
+                } else {
+                    ConstraintDecision::Negative
+                }
+            }
             Constraint::Kid { constraint_kid } => {
                 if let Some(kid) = constrainable.kid() {
                     if kid == *constraint_kid {
@@ -220,6 +256,13 @@ impl<'a> ConstraintSet<'a> {
         self
     }
 
+    #[must_use]
+    pub fn encryption_alg(mut self, constraint_alg: &'a JsonWebEncryptionAlg) -> Self {
+        self.constraints
+            .insert(Constraint::encryption_alg(constraint_alg));
+        self
+    }
+
     #[must_use]
     pub fn kid(mut self, constraint_kid: &'a str) -> Self {
         self.constraints.insert(Constraint::kid(constraint_kid));
@@ -250,3 +293,17 @@ impl<'a> From<&'a JsonWebSignatureHeader> for ConstraintSet<'a> {
         constraints
     }
 }
+
+impl<'a> From<&'a JsonWebEncryptionHeader> for ConstraintSet<'a> {
+    fn from(header: &'a JsonWebEncryptionHeader) -> Self {
+        let mut constraints = Self::default()
+            .encryption_alg(header.alg())
+            .use_(&JsonWebKeyUse::Enc);
+
+        if let Some(kid) = header.kid() {
+            constraints = constraints.kid(kid);
+        }
+
+        constraints
+    }
+}
@@ -0,0 +1,551 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Key management and content encryption algorithms used by JWE
+//!
+//! Ref: <https://www.rfc-editor.org/rfc/rfc7518.html#section-4>
+
+use aes_gcm::{
+    Aes256Gcm,
+    aead::{AeadInPlace, KeyInit},
+};
+use aes_kw::{KekAes128, KekAes256};
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit, block_padding::Pkcs7};
+use elliptic_curve::{
+    AffinePoint, CurveArithmetic, PublicKey, SecretKey,
+    sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+};
+use generic_array::GenericArray;
+use hmac::{Hmac, Mac};
+use mas_iana::jose::{JsonWebEncryptionAlg, JsonWebEncryptionEnc, JsonWebKeyEcEllipticCurve};
+use rsa::{Oaep, RsaPrivateKey, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use signature::rand_core::CryptoRngCore;
+use thiserror::Error;
+
+use crate::jwk::{
+    JsonWebKeyPrivateParameters, JsonWebKeyPublicParameters, JwkEcCurve,
+    public_parameters::EcPublicParameters,
+};
+
+#[derive(Debug, Error)]
+pub enum EncryptionAlgorithmError {
+    #[error("Unsupported key management algorithm {alg}")]
+    UnsupportedAlgorithm { alg: JsonWebEncryptionAlg },
+
+    #[error("Unsupported content encryption algorithm {enc}")]
+    UnsupportedEncryption { enc: JsonWebEncryptionEnc },
+
+    #[error("Key not suitable for algorithm {alg}")]
+    KeyNotSuitable { alg: JsonWebEncryptionAlg },
+
+    #[error("Invalid RSA parameters")]
+    Rsa {
+        #[from]
+        inner: rsa::errors::Error,
+    },
+
+    #[error("Invalid Elliptic Curve parameters")]
+    EllipticCurve {
+        #[from]
+        inner: elliptic_curve::Error,
+    },
+
+    #[error("AES key wrapping failed")]
+    KeyWrap {
+        #[from]
+        inner: aes_kw::Error,
+    },
+
+    #[error("Missing ephemeral public key")]
+    MissingEphemeralKey,
+
+    #[error("Decryption failed")]
+    Decryption,
+}
+
+/// The output of a key management algorithm on the encryption side
+pub(crate) struct WrappedKey {
+    /// The content encryption key
+    pub cek: Vec<u8>,
+
+    /// The encrypted content encryption key, empty for direct key agreement
+    pub encrypted_key: Vec<u8>,
+
+    /// The ephemeral public key, for ECDH-ES based algorithms
+    pub epk: Option<JsonWebKeyPublicParameters>,
+}
+
+/// The output of a content encryption algorithm
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct EncryptedContent {
+    pub iv: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub tag: Vec<u8>,
+}
+
+/// The size in bytes of the content encryption key for the given algorithm
+fn content_key_len(enc: &JsonWebEncryptionEnc) -> Result<usize, EncryptionAlgorithmError> {
+    match enc {
+        JsonWebEncryptionEnc::A128CbcHs256 | JsonWebEncryptionEnc::A256Gcm => Ok(32),
+        enc => Err(EncryptionAlgorithmError::UnsupportedEncryption { enc: enc.clone() }),
+    }
+}
+
+fn random_content_key(
+    rng: &mut impl CryptoRngCore,
+    enc: &JsonWebEncryptionEnc,
+) -> Result<Vec<u8>, EncryptionAlgorithmError> {
+    let mut cek = vec![0; content_key_len(enc)?];
+    rng.fill_bytes(&mut cek);
+    Ok(cek)
+}
+
+/// The OAEP padding used by the given RSA key management algorithm
+fn rsa_oaep_padding(alg: &JsonWebEncryptionAlg) -> Oaep {
+    if *alg == JsonWebEncryptionAlg::RsaOaep {
+        Oaep::new::<Sha1>()
+    } else {
+        Oaep::new::<Sha256>()
+    }
+}
+
+/// Wrap the content encryption key with AES Key Wrap, using the key size of
+/// the given algorithm
+fn aes_kw_wrap(
+    alg: &JsonWebEncryptionAlg,
+    secret: &[u8],
+    cek: &[u8],
+) -> Result<Vec<u8>, EncryptionAlgorithmError> {
+    match alg {
+        JsonWebEncryptionAlg::A128Kw => Ok(KekAes128::try_from(secret)?.wrap_vec(cek)?),
+        JsonWebEncryptionAlg::A256Kw => Ok(KekAes256::try_from(secret)?.wrap_vec(cek)?),
+        alg => Err(EncryptionAlgorithmError::KeyNotSuitable { alg: alg.clone() }),
+    }
+}
+
+/// Unwrap the content encryption key with AES Key Wrap, using the key size of
+/// the given algorithm
+fn aes_kw_unwrap(
+    alg: &JsonWebEncryptionAlg,
+    secret: &[u8],
+    encrypted_key: &[u8],
+) -> Result<Vec<u8>, EncryptionAlgorithmError> {
+    let cek = match alg {
+        JsonWebEncryptionAlg::A128Kw => KekAes128::try_from(secret)?.unwrap_vec(encrypted_key),
+        JsonWebEncryptionAlg::A256Kw => KekAes256::try_from(secret)?.unwrap_vec(encrypted_key),
+        alg => return Err(EncryptionAlgorithmError::KeyNotSuitable { alg: alg.clone() }),
+    };
+
+    cek.map_err(|_| EncryptionAlgorithmError::Decryption)
+}
+
+/// Key derivation function used by ECDH-ES, as defined in section 5.8.1 of
+/// NIST.800-56A
+#[allow(clippy::cast_possible_truncation)]
+fn concat_kdf(z: &[u8], algorithm_id: &[u8], apu: &[u8], apv: &[u8], key_len: usize) -> Vec<u8> {
+    let mut other_info = Vec::new();
+    for data in [algorithm_id, apu, apv] {
+        other_info.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        other_info.extend_from_slice(data);
+    }
+    other_info.extend_from_slice(&((key_len * 8) as u32).to_be_bytes());
+
+    let mut derived = Vec::with_capacity(key_len);
+    let mut counter: u32 = 1;
+    while derived.len() < key_len {
+        let digest = Sha256::new()
+            .chain_update(counter.to_be_bytes())
+            .chain_update(z)
+            .chain_update(&other_info)
+            .finalize();
+        derived.extend_from_slice(&digest);
+        counter += 1;
+    }
+
+    derived.truncate(key_len);
+    derived
+}
+
+/// Derive the key out of an ECDH-ES shared secret. For direct key agreement,
+/// this is the content encryption key, else it is the key used to wrap it.
+fn ecdh_es_derive_key(
+    alg: &JsonWebEncryptionAlg,
+    enc: &JsonWebEncryptionEnc,
+    z: &[u8],
+    apu: &[u8],
+    apv: &[u8],
+) -> Result<Vec<u8>, EncryptionAlgorithmError> {
+    let (algorithm_id, key_len) = match alg {
+        JsonWebEncryptionAlg::EcdhEs => (enc.to_string(), content_key_len(enc)?),
+        JsonWebEncryptionAlg::EcdhEsA256Kw => (alg.to_string(), 32),
+        alg => return Err(EncryptionAlgorithmError::UnsupportedAlgorithm { alg: alg.clone() }),
+    };
+
+    Ok(concat_kdf(z, algorithm_id.as_bytes(), apu, apv, key_len))
+}
+
+fn ecdh_es_wrap<C>(
+    rng: &mut impl CryptoRngCore,
+    alg: &JsonWebEncryptionAlg,
+    enc: &JsonWebEncryptionEnc,
+    params: &EcPublicParameters,
+    apu: &[u8],
+    apv: &[u8],
+) -> Result<WrappedKey, EncryptionAlgorithmError>
+where
+    C: CurveArithmetic + JwkEcCurve,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    C::FieldBytesSize: ModulusSize,
+{
+    let public_key = PublicKey::<C>::try_from(params)?;
+    let ephemeral = elliptic_curve::ecdh::EphemeralSecret::<C>::random(rng);
+    let shared_secret = ephemeral.diffie_hellman(&public_key);
+    let epk = JsonWebKeyPublicParameters::from(ephemeral.public_key());
+
+    let derived = ecdh_es_derive_key(alg, enc, shared_secret.raw_secret_bytes(), apu, apv)?;
+    let (cek, encrypted_key) = if *alg == JsonWebEncryptionAlg::EcdhEs {
+        (derived, Vec::new())
+    } else {
+        let cek = random_content_key(rng, enc)?;
+        let encrypted_key = KekAes256::try_from(derived.as_slice())?.wrap_vec(&cek)?;
+        (cek, encrypted_key)
+    };
+
+    Ok(WrappedKey {
+        cek,
+        encrypted_key,
+        epk: Some(epk),
+    })
+}
+
+fn ecdh_es_unwrap<C>(
+    alg: &JsonWebEncryptionAlg,
+    enc: &JsonWebEncryptionEnc,
+    secret_key: &SecretKey<C>,
+    epk: &EcPublicParameters,
+    encrypted_key: &[u8],
+    apu: &[u8],
+    apv: &[u8],
+) -> Result<Vec<u8>, EncryptionAlgorithmError>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    C::FieldBytesSize: ModulusSize,
+{
+    let epk = PublicKey::<C>::try_from(epk)?;
+    let shared_secret =
+        elliptic_curve::ecdh::diffie_hellman(secret_key.to_nonzero_scalar(), epk.as_affine());
+
+    let derived = ecdh_es_derive_key(alg, enc, shared_secret.raw_secret_bytes(), apu, apv)?;
+    if *alg == JsonWebEncryptionAlg::EcdhEs {
+        if !encrypted_key.is_empty() {
+            return Err(EncryptionAlgorithmError::Decryption);
+        }
+
+        Ok(derived)
+    } else {
+        KekAes256::try_from(derived.as_slice())?
+            .unwrap_vec(encrypted_key)
+            .map_err(|_| EncryptionAlgorithmError::Decryption)
+    }
+}
+
+/// Generate a content encryption key and wrap it for the given public key.
+///
+/// `apu` and `apv` are the agreement party info, only used by ECDH-ES based
+/// algorithms.
+pub(crate) fn wrap_key(
+    rng: &mut impl CryptoRngCore,
+    alg: &JsonWebEncryptionAlg,
+    enc: &JsonWebEncryptionEnc,
+    key: &JsonWebKeyPublicParameters,
+    apu: &[u8],
+    apv: &[u8],
+) -> Result<WrappedKey, EncryptionAlgorithmError> {
+    match (key, alg) {
+        (
+            JsonWebKeyPublicParameters::Rsa(params),
+            JsonWebEncryptionAlg::RsaOaep | JsonWebEncryptionAlg::RsaOaep256,
+        ) => {
+            let public_key: RsaPublicKey = params.try_into()?;
+            let cek = random_content_key(rng, enc)?;
+            let encrypted_key = public_key.encrypt(rng, rsa_oaep_padding(alg), &cek)?;
+            Ok(WrappedKey {
+                cek,
+                encrypted_key,
+                epk: None,
+            })
+        }
+
+        (
+            JsonWebKeyPublicParameters::Ec(params),
+            JsonWebEncryptionAlg::EcdhEs | JsonWebEncryptionAlg::EcdhEsA256Kw,
+        ) => match params.crv {
+            JsonWebKeyEcEllipticCurve::P256 => {
+                ecdh_es_wrap::<p256::NistP256>(rng, alg, enc, params, apu, apv)
+            }
+            JsonWebKeyEcEllipticCurve::P384 => {
+                ecdh_es_wrap::<p384::NistP384>(rng, alg, enc, params, apu, apv)
+            }
+            _ => Err(EncryptionAlgorithmError::KeyNotSuitable { alg: alg.clone() }),
+        },
+
+        (_, alg) if super::SUPPORTED_KEY_MANAGEMENT_ALGORITHMS.contains(alg) => {
+            Err(EncryptionAlgorithmError::KeyNotSuitable { alg: alg.clone() })
+        }
+
+        (_, alg) => Err(EncryptionAlgorithmError::UnsupportedAlgorithm { alg: alg.clone() }),
+    }
+}
+
+/// Generate a content encryption key and wrap it with the given shared secret.
+pub(crate) fn wrap_key_with_shared_secret(
+    rng: &mut impl CryptoRngCore,
+    alg: &JsonWebEncryptionAlg,
+    enc: &JsonWebEncryptionEnc,
+    secret: &[u8],
+) -> Result<WrappedKey, EncryptionAlgorithmError> {
+    let cek = random_content_key(rng, enc)?;
+    let encrypted_key = aes_kw_wrap(alg, secret, &cek)?;
+    Ok(WrappedKey {
+        cek,
+        encrypted_key,
+        epk: None,
+    })
+}
+
+/// Recover the content encryption key using the given private key.
+pub(crate) fn unwrap_key(
+    alg: &JsonWebEncryptionAlg,
+    enc: &JsonWebEncryptionEnc,
+    key: &JsonWebKeyPrivateParameters,
+    encrypted_key: &[u8],
+    epk: Option<&JsonWebKeyPublicParameters>,
+    apu: &[u8],
+    apv: &[u8],
+) -> Result<Vec<u8>, EncryptionAlgorithmError> {
+    let cek = match (key, alg) {
+        (
+            JsonWebKeyPrivateParameters::Rsa(params),
+            JsonWebEncryptionAlg::RsaOaep | JsonWebEncryptionAlg::RsaOaep256,
+        ) => {
+            let private_key: RsaPrivateKey = params.try_into()?;
+            private_key
+                .decrypt(rsa_oaep_padding(alg), encrypted_key)
+                .map_err(|_| EncryptionAlgorithmError::Decryption)?
+        }
+
+        (
+            JsonWebKeyPrivateParameters::Ec(params),
+            JsonWebEncryptionAlg::EcdhEs | JsonWebEncryptionAlg::EcdhEsA256Kw,
+        ) => {
+            let epk = epk
+                .and_then(JsonWebKeyPublicParameters::ec)
+                .ok_or(EncryptionAlgorithmError::MissingEphemeralKey)?;
+
+            if epk.crv != params.crv {
+                return Err(EncryptionAlgorithmError::KeyNotSuitable { alg: alg.clone() });
+            }
+
+            match params.crv {
+                JsonWebKeyEcEllipticCurve::P256 => ecdh_es_unwrap::<p256::NistP256>(
+                    alg,
+                    enc,
+                    &params.try_into()?,
+                    epk,
+                    encrypted_key,
+                    apu,
+                    apv,
+                )?,
+                JsonWebKeyEcEllipticCurve::P384 => ecdh_es_unwrap::<p384::NistP384>(
+                    alg,
+                    enc,
+                    &params.try_into()?,
+                    epk,
+                    encrypted_key,
+                    apu,
+                    apv,
+                )?,
+                _ => return Err(EncryptionAlgorithmError::KeyNotSuitable { alg: alg.clone() }),
+            }
+        }
+
+        (
+            JsonWebKeyPrivateParameters::Oct(params),
+            JsonWebEncryptionAlg::A128Kw | JsonWebEncryptionAlg::A256Kw,
+        ) => {
+            return unwrap_key_with_shared_secret(alg, enc, params.k.as_bytes(), encrypted_key);
+        }
+
+        (_, alg) if super::SUPPORTED_KEY_MANAGEMENT_ALGORITHMS.contains(alg) => {
+            return Err(EncryptionAlgorithmError::KeyNotSuitable { alg: alg.clone() });
+        }
+
+        (_, alg) => {
+            return Err(EncryptionAlgorithmError::UnsupportedAlgorithm { alg: alg.clone() });
+        }
+    };
+
+    if cek.len() != content_key_len(enc)? {
+        return Err(EncryptionAlgorithmError::Decryption);
+    }
+
+    Ok(cek)
+}
+
+/// Recover the content encryption key using the given shared secret.
+pub(crate) fn unwrap_key_with_shared_secret(
+    alg: &JsonWebEncryptionAlg,
+    enc: &JsonWebEncryptionEnc,
+    secret: &[u8],
+    encrypted_key: &[u8],
+) -> Result<Vec<u8>, EncryptionAlgorithmError> {
+    let cek = aes_kw_unwrap(alg, secret, encrypted_key)?;
+
+    if cek.len() != content_key_len(enc)? {
+        return Err(EncryptionAlgorithmError::Decryption);
+    }
+
+    Ok(cek)
+}
+
+/// Compute the authentication tag of the `AES_CBC_HMAC_SHA2` algorithms
+fn cbc_hmac_sha256(mac_key: &[u8], aad: &[u8], iv: &[u8], ciphertext: &[u8]) -> Hmac<Sha256> {
+    let aad_len = (aad.len() as u64) * 8;
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(mac_key).expect("HMAC accepts keys of any size");
+    mac.update(aad);
+    mac.update(iv);
+    mac.update(ciphertext);
+    mac.update(&aad_len.to_be_bytes());
+    mac
+}
+
+/// Encrypt the plaintext with the given content encryption key, using `aad`
+/// as additional authenticated data.
+pub(crate) fn encrypt_content(
+    rng: &mut impl CryptoRngCore,
+    enc: &JsonWebEncryptionEnc,
+    cek: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<EncryptedContent, EncryptionAlgorithmError> {
+    match enc {
+        JsonWebEncryptionEnc::A256Gcm => {
+            let cipher = Aes256Gcm::new_from_slice(cek).map_err(|_| {
+                EncryptionAlgorithmError::UnsupportedEncryption { enc: enc.clone() }
+            })?;
+            let mut iv = vec![0; 12];
+            rng.fill_bytes(&mut iv);
+
+            let mut ciphertext = plaintext.to_vec();
+            let tag = cipher
+                .encrypt_in_place_detached(GenericArray::from_slice(&iv), aad, &mut ciphertext)
+                .map_err(|_| EncryptionAlgorithmError::UnsupportedEncryption {
+                    enc: enc.clone(),
+                })?;
+
+            Ok(EncryptedContent {
+                iv,
+                ciphertext,
+                tag: tag.to_vec(),
+            })
+        }
+
+        JsonWebEncryptionEnc::A128CbcHs256 => {
+            let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+            let mut iv = vec![0; 16];
+            rng.fill_bytes(&mut iv);
+
+            let ciphertext = cbc::Encryptor::<aes::Aes128>::new_from_slices(enc_key, &iv)
+                .map_err(|_| EncryptionAlgorithmError::UnsupportedEncryption { enc: enc.clone() })?
+                .encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+
+            let mac = cbc_hmac_sha256(mac_key, aad, &iv, &ciphertext).finalize();
+            let tag = mac.into_bytes()[..16].to_vec();
+
+            Ok(EncryptedContent {
+                iv,
+                ciphertext,
+                tag,
+            })
+        }
+
+        enc => Err(EncryptionAlgorithmError::UnsupportedEncryption { enc: enc.clone() }),
+    }
+}
+
+/// Decrypt and authenticate the ciphertext with the given content encryption
+/// key.
+pub(crate) fn decrypt_content(
+    enc: &JsonWebEncryptionEnc,
+    cek: &[u8],
+    aad: &[u8],
+    content: &EncryptedContent,
+) -> Result<Vec<u8>, EncryptionAlgorithmError> {
+    match enc {
+        JsonWebEncryptionEnc::A256Gcm => {
+            if content.iv.len() != 12 || content.tag.len() != 16 {
+                return Err(EncryptionAlgorithmError::Decryption);
+            }
+
+            let cipher =
+                Aes256Gcm::new_from_slice(cek).map_err(|_| EncryptionAlgorithmError::Decryption)?;
+            let mut plaintext = content.ciphertext.clone();
+            cipher
+                .decrypt_in_place_detached(
+                    GenericArray::from_slice(&content.iv),
+                    aad,
+                    &mut plaintext,
+                    GenericArray::from_slice(&content.tag),
+                )
+                .map_err(|_| EncryptionAlgorithmError::Decryption)?;
+
+            Ok(plaintext)
+        }
+
+        JsonWebEncryptionEnc::A128CbcHs256 => {
+            if content.tag.len() != 16 {
+                return Err(EncryptionAlgorithmError::Decryption);
+            }
+
+            let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+            cbc_hmac_sha256(mac_key, aad, &content.iv, &content.ciphertext)
+                .verify_truncated_left(&content.tag)
+                .map_err(|_| EncryptionAlgorithmError::Decryption)?;
+
+            cbc::Decryptor::<aes::Aes128>::new_from_slices(enc_key, &content.iv)
+                .map_err(|_| EncryptionAlgorithmError::Decryption)?
+                .decrypt_padded_vec_mut::<Pkcs7>(&content.ciphertext)
+                .map_err(|_| EncryptionAlgorithmError::Decryption)
+        }
+
+        enc => Err(EncryptionAlgorithmError::UnsupportedEncryption { enc: enc.clone() }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64ct::{Base64UrlUnpadded, Encoding};
+
+    use super::*;
+
+    #[test]
+    fn test_concat_kdf() {
+        // Test vector from RFC 7518, Appendix C
+        let z = [
+            158, 86, 217, 29, 129, 113, 53, 211, 114, 131, 66, 131, 191, 132, 38, 156, 251, 49,
+            110, 163, 218, 128, 106, 72, 246, 218, 167, 121, 140, 254, 144, 196,
+        ];
+
+        let derived = concat_kdf(&z, b"A128GCM", b"Alice", b"Bob", 16);
+        assert_eq!(
+            Base64UrlUnpadded::encode_string(&derived),
+            "VqqN6vgjbSBcIijNcacQGg"
+        );
+    }
+}
@@ -4,16 +4,18 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use mas_iana::jose::JsonWebSignatureAlg;
+use mas_iana::jose::{JsonWebEncryptionAlg, JsonWebEncryptionEnc, JsonWebSignatureAlg};
 use sha2::{Sha256, Sha384, Sha512};
 
 mod asymmetric;
+pub(crate) mod encryption;
 pub(crate) mod hmac;
 mod signature;
 mod symmetric;
 
 pub use self::{
     asymmetric::{AsymmetricKeyFromJwkError, AsymmetricSigningKey, AsymmetricVerifyingKey},
+    encryption::EncryptionAlgorithmError,
     symmetric::{InvalidAlgorithm, SymmetricKey},
 };
 
@@ -57,3 +59,19 @@ pub const SUPPORTED_SIGNING_ALGORITHMS: [JsonWebSignatureAlg; 12] = [
     JsonWebSignatureAlg::Es384,
     JsonWebSignatureAlg::Es256K,
 ];
+
+/// All the key management algorithms supported by this crate.
+pub const SUPPORTED_KEY_MANAGEMENT_ALGORITHMS: [JsonWebEncryptionAlg; 6] = [
+    JsonWebEncryptionAlg::RsaOaep,
+    JsonWebEncryptionAlg::RsaOaep256,
+    JsonWebEncryptionAlg::EcdhEs,
+    JsonWebEncryptionAlg::EcdhEsA256Kw,
+    JsonWebEncryptionAlg::A128Kw,
+    JsonWebEncryptionAlg::A256Kw,
+];
+
+/// All the content encryption algorithms supported by this crate.
+pub const SUPPORTED_CONTENT_ENCRYPTION_ALGORITHMS: [JsonWebEncryptionEnc; 2] = [
+    JsonWebEncryptionEnc::A128CbcHs256,
+    JsonWebEncryptionEnc::A256Gcm,
+];
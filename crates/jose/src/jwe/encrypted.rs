@@ -0,0 +1,358 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use signature::rand_core::CryptoRngCore;
+use thiserror::Error;
+
+use super::header::JsonWebEncryptionHeader;
+use crate::{
+    constraints::{Constrainable, ConstraintSet},
+    jwa::{
+        EncryptionAlgorithmError,
+        encryption::{self, EncryptedContent, WrappedKey},
+    },
+    jwk::{
+        JsonWebKey, JsonWebKeyPrivateParameters, JsonWebKeyPublicParameters, PrivateJsonWebKeySet,
+        PublicJsonWebKey, PublicJsonWebKeySet,
+    },
+    jwt::NoKeyWorked,
+};
+
+/// A JSON Web Encryption object, in its compact serialization
+#[derive(Clone, PartialEq, Eq)]
+pub struct Jwe {
+    raw: String,
+    header: JsonWebEncryptionHeader,
+    encrypted_key: Vec<u8>,
+    content: EncryptedContent,
+}
+
+impl std::fmt::Display for Jwe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl std::fmt::Debug for Jwe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Jwe")
+            .field("raw", &"...")
+            .field("header", &self.header)
+            .field("encrypted_key", &"...")
+            .field("content", &"...")
+            .finish()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum JweDecodeError {
+    #[error("JWE must have exactly five parts")]
+    InvalidPartCount,
+
+    #[error("failed to decode JWE header")]
+    DecodeHeader {
+        #[source]
+        inner: base64ct::Error,
+    },
+
+    #[error("failed to deserialize JWE header")]
+    DeserializeHeader {
+        #[source]
+        inner: serde_json::Error,
+    },
+
+    #[error("failed to decode JWE {part}")]
+    DecodePart {
+        part: &'static str,
+        #[source]
+        inner: base64ct::Error,
+    },
+}
+
+impl JweDecodeError {
+    fn decode_header(inner: base64ct::Error) -> Self {
+        Self::DecodeHeader { inner }
+    }
+
+    fn deserialize_header(inner: serde_json::Error) -> Self {
+        Self::DeserializeHeader { inner }
+    }
+}
+
+impl TryFrom<String> for Jwe {
+    type Error = JweDecodeError;
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        let decode_part = |part: &'static str, value: &str| {
+            Base64UrlUnpadded::decode_vec(value)
+                .map_err(|inner| JweDecodeError::DecodePart { part, inner })
+        };
+
+        let parts: Vec<&str> = raw.split('.').collect();
+        let [header, encrypted_key, iv, ciphertext, tag] = parts[..] else {
+            return Err(JweDecodeError::InvalidPartCount);
+        };
+
+        let header =
+            Base64UrlUnpadded::decode_vec(header).map_err(JweDecodeError::decode_header)?;
+        let header = serde_json::from_slice(&header).map_err(JweDecodeError::deserialize_header)?;
+
+        let encrypted_key = decode_part("encrypted key", encrypted_key)?;
+        let content = EncryptedContent {
+            iv: decode_part("initialization vector", iv)?,
+            ciphertext: decode_part("ciphertext", ciphertext)?,
+            tag: decode_part("authentication tag", tag)?,
+        };
+
+        Ok(Self {
+            raw,
+            header,
+            encrypted_key,
+            content,
+        })
+    }
+}
+
+impl TryFrom<&str> for Jwe {
+    type Error = JweDecodeError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::try_from(value.to_owned())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum JweEncryptionError {
+    #[error("failed to serialize header")]
+    EncodeHeader {
+        #[source]
+        inner: serde_json::Error,
+    },
+
+    #[error("no key matches the header constraints")]
+    NoSuitableKey,
+
+    #[error(transparent)]
+    Algorithm {
+        #[from]
+        inner: EncryptionAlgorithmError,
+    },
+}
+
+impl JweEncryptionError {
+    fn encode_header(inner: serde_json::Error) -> Self {
+        Self::EncodeHeader { inner }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum JweDecryptionError {
+    #[error("compressed payloads are not supported")]
+    UnsupportedCompression,
+
+    #[error(transparent)]
+    Algorithm {
+        #[from]
+        inner: EncryptionAlgorithmError,
+    },
+}
+
+impl Jwe {
+    /// Get the JWE header
+    #[must_use]
+    pub fn header(&self) -> &JsonWebEncryptionHeader {
+        &self.header
+    }
+
+    /// Get the raw JWE string as a borrowed [`str`]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// Get the raw JWE string as an owned [`String`]
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.raw
+    }
+
+    /// Encrypt the given plaintext for the given public key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the algorithms in the header are not supported, or
+    /// if the key is not suitable for them.
+    pub fn encrypt(
+        rng: &mut impl CryptoRngCore,
+        header: JsonWebEncryptionHeader,
+        plaintext: &[u8],
+        key: &JsonWebKeyPublicParameters,
+    ) -> Result<Self, JweEncryptionError> {
+        let wrapped = encryption::wrap_key(
+            rng,
+            header.alg(),
+            header.enc(),
+            key,
+            header.apu().unwrap_or_default(),
+            header.apv().unwrap_or_default(),
+        )?;
+
+        Self::seal(rng, header, plaintext, wrapped)
+    }
+
+    /// Encrypt the given plaintext with the given shared secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the algorithms in the header are not supported, or
+    /// if the secret is not suitable for them.
+    pub fn encrypt_with_shared_secret(
+        rng: &mut impl CryptoRngCore,
+        header: JsonWebEncryptionHeader,
+        plaintext: &[u8],
+        secret: &[u8],
+    ) -> Result<Self, JweEncryptionError> {
+        let wrapped =
+            encryption::wrap_key_with_shared_secret(rng, header.alg(), header.enc(), secret)?;
+
+        Self::seal(rng, header, plaintext, wrapped)
+    }
+
+    /// Encrypt the given plaintext for the best key of the given JWKS. The
+    /// `kid` of the selected key is added to the header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no key matches the constraints, or if the
+    /// algorithms in the header are not supported.
+    pub fn encrypt_with_jwks(
+        rng: &mut impl CryptoRngCore,
+        header: JsonWebEncryptionHeader,
+        plaintext: &[u8],
+        jwks: &PublicJsonWebKeySet,
+    ) -> Result<Self, JweEncryptionError> {
+        let key = {
+            let constraints = ConstraintSet::from(&header);
+            jwks.find_key(&constraints)
+                .ok_or(JweEncryptionError::NoSuitableKey)?
+        };
+
+        let header = match key.kid() {
+            Some(kid) => header.with_kid(kid),
+            None => header,
+        };
+
+        Self::encrypt(rng, header, plaintext, key.params())
+    }
+
+    fn seal(
+        rng: &mut impl CryptoRngCore,
+        mut header: JsonWebEncryptionHeader,
+        plaintext: &[u8],
+        wrapped: WrappedKey,
+    ) -> Result<Self, JweEncryptionError> {
+        if let Some(epk) = wrapped.epk {
+            header = header.with_epk(PublicJsonWebKey::new(epk));
+        }
+
+        let header_ = serde_json::to_vec(&header).map_err(JweEncryptionError::encode_header)?;
+        let header_ = Base64UrlUnpadded::encode_string(&header_);
+
+        // The encoded protected header is the additional authenticated data
+        let content = encryption::encrypt_content(
+            rng,
+            header.enc(),
+            &wrapped.cek,
+            header_.as_bytes(),
+            plaintext,
+        )?;
+
+        let raw = [
+            header_,
+            Base64UrlUnpadded::encode_string(&wrapped.encrypted_key),
+            Base64UrlUnpadded::encode_string(&content.iv),
+            Base64UrlUnpadded::encode_string(&content.ciphertext),
+            Base64UrlUnpadded::encode_string(&content.tag),
+        ]
+        .join(".");
+
+        Ok(Self {
+            raw,
+            header,
+            encrypted_key: wrapped.encrypted_key,
+            content,
+        })
+    }
+
+    /// Decrypt this JWE using the given private key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not suitable for the algorithms in the
+    /// header, or if the decryption failed.
+    pub fn decrypt(
+        &self,
+        key: &JsonWebKeyPrivateParameters,
+    ) -> Result<Vec<u8>, JweDecryptionError> {
+        let cek = encryption::unwrap_key(
+            self.header.alg(),
+            self.header.enc(),
+            key,
+            &self.encrypted_key,
+            self.header.epk().map(JsonWebKey::params),
+            self.header.apu().unwrap_or_default(),
+            self.header.apv().unwrap_or_default(),
+        )?;
+
+        self.open(&cek)
+    }
+
+    /// Decrypt this JWE using the given shared secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the secret is not suitable for the algorithms in
+    /// the header, or if the decryption failed.
+    pub fn decrypt_with_shared_secret(&self, secret: &[u8]) -> Result<Vec<u8>, JweDecryptionError> {
+        let cek = encryption::unwrap_key_with_shared_secret(
+            self.header.alg(),
+            self.header.enc(),
+            secret,
+            &self.encrypted_key,
+        )?;
+
+        self.open(&cek)
+    }
+
+    /// Decrypt this JWE using the given JWKS.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no key matches the constraints, or if none of the
+    /// candidates could decrypt it.
+    pub fn decrypt_with_jwks(&self, jwks: &PrivateJsonWebKeySet) -> Result<Vec<u8>, NoKeyWorked> {
+        let constraints = ConstraintSet::from(self.header());
+        let candidates = constraints.filter(&**jwks);
+
+        for candidate in candidates {
+            if let Ok(plaintext) = self.decrypt(candidate.params()) {
+                return Ok(plaintext);
+            }
+        }
+
+        Err(NoKeyWorked::default())
+    }
+
+    fn open(&self, cek: &[u8]) -> Result<Vec<u8>, JweDecryptionError> {
+        if self.header.zip().is_some() {
+            return Err(JweDecryptionError::UnsupportedCompression);
+        }
+
+        let (header_, _) = self.raw.split_once('.').unwrap_or_default();
+        let plaintext =
+            encryption::decrypt_content(self.header.enc(), cek, header_.as_bytes(), &self.content)?;
+
+        Ok(plaintext)
+    }
+}
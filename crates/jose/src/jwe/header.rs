@@ -0,0 +1,211 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use mas_iana::jose::{
+    JsonWebEncryptionAlg, JsonWebEncryptionCompressionAlgorithm, JsonWebEncryptionEnc,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use url::Url;
+
+use crate::{Base64, base64::Base64UrlNoPad, jwk::PublicJsonWebKey};
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct JsonWebEncryptionHeader {
+    alg: JsonWebEncryptionAlg,
+
+    enc: JsonWebEncryptionEnc,
+
+    #[serde(default)]
+    zip: Option<JsonWebEncryptionCompressionAlgorithm>,
+
+    #[serde(default)]
+    jku: Option<Url>,
+
+    #[serde(default)]
+    jwk: Option<Box<PublicJsonWebKey>>,
+
+    #[serde(default)]
+    kid: Option<String>,
+
+    #[serde(default)]
+    x5u: Option<Url>,
+
+    #[serde(default)]
+    x5c: Option<Vec<Base64>>,
+
+    #[serde(default)]
+    x5t: Option<Base64UrlNoPad>,
+
+    #[serde(default, rename = "x5t#S256")]
+    x5t_s256: Option<Base64UrlNoPad>,
+
+    #[serde(default)]
+    typ: Option<String>,
+
+    #[serde(default)]
+    cty: Option<String>,
+
+    #[serde(default)]
+    crit: Option<Vec<String>>,
+
+    #[serde(default)]
+    epk: Option<Box<PublicJsonWebKey>>,
+
+    #[serde(default)]
+    apu: Option<Base64UrlNoPad>,
+
+    #[serde(default)]
+    apv: Option<Base64UrlNoPad>,
+}
+
+impl JsonWebEncryptionHeader {
+    #[must_use]
+    pub fn new(alg: JsonWebEncryptionAlg, enc: JsonWebEncryptionEnc) -> Self {
+        Self {
+            alg,
+            enc,
+            zip: None,
+            jku: None,
+            jwk: None,
+            kid: None,
+            x5u: None,
+            x5c: None,
+            x5t: None,
+            x5t_s256: None,
+            typ: None,
+            cty: None,
+            crit: None,
+            epk: None,
+            apu: None,
+            apv: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn alg(&self) -> &JsonWebEncryptionAlg {
+        &self.alg
+    }
+
+    #[must_use]
+    pub const fn enc(&self) -> &JsonWebEncryptionEnc {
+        &self.enc
+    }
+
+    #[must_use]
+    pub const fn zip(&self) -> Option<&JsonWebEncryptionCompressionAlgorithm> {
+        self.zip.as_ref()
+    }
+
+    #[must_use]
+    pub const fn jku(&self) -> Option<&Url> {
+        self.jku.as_ref()
+    }
+
+    #[must_use]
+    pub fn with_jku(mut self, jku: Url) -> Self {
+        self.jku = Some(jku);
+        self
+    }
+
+    #[must_use]
+    pub const fn jwk(&self) -> Option<&PublicJsonWebKey> {
+        // Can't use as_deref because it's not a const fn
+        match &self.jwk {
+            Some(jwk) => Some(jwk),
+            None => None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_jwk(mut self, jwk: PublicJsonWebKey) -> Self {
+        self.jwk = Some(Box::new(jwk));
+        self
+    }
+
+    #[must_use]
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    #[must_use]
+    pub fn with_kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    #[must_use]
+    pub fn typ(&self) -> Option<&str> {
+        self.typ.as_deref()
+    }
+
+    #[must_use]
+    pub fn with_typ(mut self, typ: String) -> Self {
+        self.typ = Some(typ);
+        self
+    }
+
+    #[must_use]
+    pub fn cty(&self) -> Option<&str> {
+        self.cty.as_deref()
+    }
+
+    #[must_use]
+    pub fn with_cty(mut self, cty: String) -> Self {
+        self.cty = Some(cty);
+        self
+    }
+
+    #[must_use]
+    pub fn crit(&self) -> Option<&[String]> {
+        self.crit.as_deref()
+    }
+
+    #[must_use]
+    pub fn with_crit(mut self, crit: Vec<String>) -> Self {
+        self.crit = Some(crit);
+        self
+    }
+
+    /// Get the ephemeral public key used by ECDH-ES based algorithms
+    #[must_use]
+    pub const fn epk(&self) -> Option<&PublicJsonWebKey> {
+        match &self.epk {
+            Some(epk) => Some(epk),
+            None => None,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn with_epk(mut self, epk: PublicJsonWebKey) -> Self {
+        self.epk = Some(Box::new(epk));
+        self
+    }
+
+    /// Get the agreement `PartyUInfo` used by ECDH-ES based algorithms
+    #[must_use]
+    pub fn apu(&self) -> Option<&[u8]> {
+        self.apu.as_ref().map(Base64UrlNoPad::as_bytes)
+    }
+
+    #[must_use]
+    pub fn with_apu(mut self, apu: Vec<u8>) -> Self {
+        self.apu = Some(Base64UrlNoPad::new(apu));
+        self
+    }
+
+    /// Get the agreement `PartyVInfo` used by ECDH-ES based algorithms
+    #[must_use]
+    pub fn apv(&self) -> Option<&[u8]> {
+        self.apv.as_ref().map(Base64UrlNoPad::as_bytes)
+    }
+
+    #[must_use]
+    pub fn with_apv(mut self, apv: Vec<u8>) -> Self {
+        self.apv = Some(Base64UrlNoPad::new(apv));
+        self
+    }
+}
@@ -7,8 +7,8 @@
 //! Ref: <https://www.rfc-editor.org/rfc/rfc7517.html>
 
 use mas_iana::jose::{
-    JsonWebKeyEcEllipticCurve, JsonWebKeyOperation, JsonWebKeyType, JsonWebKeyUse,
-    JsonWebSignatureAlg,
+    JsonWebEncryptionAlg, JsonWebKeyEcEllipticCurve, JsonWebKeyOperation, JsonWebKeyType,
+    JsonWebKeyUse, JsonWebSignatureAlg,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -31,11 +31,12 @@ pub use self::{
 pub trait ParametersInfo {
     fn kty(&self) -> JsonWebKeyType;
     fn possible_algs(&self) -> &[JsonWebSignatureAlg];
+    fn possible_encryption_algs(&self) -> &[JsonWebEncryptionAlg];
 }
 
 /// An utilitary trait to figure out the [`JsonWebKeyEcEllipticCurve`] value for
 /// elliptic curves
-trait JwkEcCurve {
+pub(crate) trait JwkEcCurve {
     const CRV: JsonWebKeyEcEllipticCurve;
 }
 
@@ -237,6 +238,12 @@ impl<P> JsonWebKey<P> {
     pub const fn params(&self) -> &P {
         &self.parameters
     }
+
+    /// Get the `x5c` certificate chain of this [`JsonWebKey`], if set.
+    #[must_use]
+    pub fn x5c(&self) -> Option<&[Base64]> {
+        self.x5c.as_deref()
+    }
 }
 
 impl<P> Constrainable for JsonWebKey<P>
@@ -262,6 +269,19 @@ where
     fn use_(&self) -> Option<&JsonWebKeyUse> {
         self.r#use.as_ref()
     }
+
+    fn encryption_algs(&self) -> &[JsonWebEncryptionAlg] {
+        self.parameters.possible_encryption_algs()
+    }
+
+    fn encryption_alg(&self) -> Option<JsonWebEncryptionAlg> {
+        // Key management algorithms don't have a variant in the signature
+        // algorithms enum, so they get parsed as unknown signature algorithms
+        match &self.alg {
+            Some(JsonWebSignatureAlg::Unknown(alg)) => alg.parse().ok(),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -548,4 +568,22 @@ mod tests {
         // 8th is P-521, but we don't support it yet
         keys.next().unwrap().params().ec().unwrap();
     }
+
+    #[test]
+    fn rsa_thumbprint() {
+        // Example from RFC 7638, section 3.1
+        let jwk = serde_json::json!({
+            "kty": "RSA",
+            "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+            "e": "AQAB",
+            "alg": "RS256",
+            "kid": "2011-04-29"
+        });
+
+        let jwk: PublicJsonWebKey = serde_json::from_value(jwk).unwrap();
+        assert_eq!(
+            jwk.params().thumbprint_sha256(),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+    }
 }
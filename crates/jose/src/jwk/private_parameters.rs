@@ -5,7 +5,8 @@
 // Please see LICENSE files in the repository root for full details.
 
 use mas_iana::jose::{
-    JsonWebKeyEcEllipticCurve, JsonWebKeyOkpEllipticCurve, JsonWebKeyType, JsonWebSignatureAlg,
+    JsonWebEncryptionAlg, JsonWebKeyEcEllipticCurve, JsonWebKeyOkpEllipticCurve, JsonWebKeyType,
+    JsonWebSignatureAlg,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -82,6 +83,15 @@ impl ParametersInfo for JsonWebKeyPrivateParameters {
             JsonWebKeyPrivateParameters::Okp(p) => p.possible_algs(),
         }
     }
+
+    fn possible_encryption_algs(&self) -> &[JsonWebEncryptionAlg] {
+        match self {
+            JsonWebKeyPrivateParameters::Oct(p) => p.possible_encryption_algs(),
+            JsonWebKeyPrivateParameters::Rsa(p) => p.possible_encryption_algs(),
+            JsonWebKeyPrivateParameters::Ec(p) => p.possible_encryption_algs(),
+            JsonWebKeyPrivateParameters::Okp(p) => p.possible_encryption_algs(),
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -105,7 +115,7 @@ impl TryFrom<JsonWebKeyPrivateParameters> for JsonWebKeyPublicParameters {
 pub struct OctPrivateParameters {
     /// Key Value
     #[schemars(with = "String")]
-    k: Base64UrlNoPad,
+    pub(crate) k: Base64UrlNoPad,
 }
 
 impl ParametersInfo for OctPrivateParameters {
@@ -120,6 +130,10 @@ impl ParametersInfo for OctPrivateParameters {
             JsonWebSignatureAlg::Hs512,
         ]
     }
+
+    fn possible_encryption_algs(&self) -> &[JsonWebEncryptionAlg] {
+        &[JsonWebEncryptionAlg::A128Kw, JsonWebEncryptionAlg::A256Kw]
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -176,6 +190,13 @@ impl ParametersInfo for RsaPrivateParameters {
             JsonWebSignatureAlg::Ps512,
         ]
     }
+
+    fn possible_encryption_algs(&self) -> &[JsonWebEncryptionAlg] {
+        &[
+            JsonWebEncryptionAlg::RsaOaep,
+            JsonWebEncryptionAlg::RsaOaep256,
+        ]
+    }
 }
 
 impl From<RsaPrivateParameters> for super::public_parameters::RsaPublicParameters {
@@ -263,6 +284,16 @@ impl ParametersInfo for EcPrivateParameters {
             _ => &[],
         }
     }
+
+    fn possible_encryption_algs(&self) -> &[JsonWebEncryptionAlg] {
+        match self.crv {
+            JsonWebKeyEcEllipticCurve::P256 | JsonWebKeyEcEllipticCurve::P384 => &[
+                JsonWebEncryptionAlg::EcdhEs,
+                JsonWebEncryptionAlg::EcdhEsA256Kw,
+            ],
+            _ => &[],
+        }
+    }
 }
 
 impl From<EcPrivateParameters> for super::public_parameters::EcPublicParameters {
@@ -350,6 +381,10 @@ impl ParametersInfo for OkpPrivateParameters {
     fn possible_algs(&self) -> &[JsonWebSignatureAlg] {
         &[JsonWebSignatureAlg::EdDsa]
     }
+
+    fn possible_encryption_algs(&self) -> &[JsonWebEncryptionAlg] {
+        &[]
+    }
 }
 
 impl From<OkpPrivateParameters> for super::public_parameters::OkpPublicParameters {
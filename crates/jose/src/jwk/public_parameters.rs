@@ -5,10 +5,12 @@
 // Please see LICENSE files in the repository root for full details.
 
 use mas_iana::jose::{
-    JsonWebKeyEcEllipticCurve, JsonWebKeyOkpEllipticCurve, JsonWebKeyType, JsonWebSignatureAlg,
+    JsonWebEncryptionAlg, JsonWebKeyEcEllipticCurve, JsonWebKeyOkpEllipticCurve, JsonWebKeyType,
+    JsonWebSignatureAlg,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use super::ParametersInfo;
 use crate::base64::Base64UrlNoPad;
@@ -50,6 +52,37 @@ impl JsonWebKeyPublicParameters {
             _ => None,
         }
     }
+
+    /// Compute the [JWK thumbprint] of this key, using SHA-256, encoded in
+    /// base64url.
+    ///
+    /// [JWK thumbprint]: https://www.rfc-editor.org/rfc/rfc7638
+    #[must_use]
+    pub fn thumbprint_sha256(&self) -> String {
+        // The thumbprint is computed over the required members of the key, in
+        // lexicographic order and without any whitespace
+        let members = match self {
+            Self::Rsa(params) => format!(
+                r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#,
+                params.e.encode(),
+                params.n.encode(),
+            ),
+            Self::Ec(params) => format!(
+                r#"{{"crv":"{}","kty":"EC","x":"{}","y":"{}"}}"#,
+                params.crv,
+                params.x.encode(),
+                params.y.encode(),
+            ),
+            Self::Okp(params) => format!(
+                r#"{{"crv":"{}","kty":"OKP","x":"{}"}}"#,
+                params.crv,
+                params.x.encode(),
+            ),
+        };
+
+        let digest = Sha256::digest(members.as_bytes());
+        Base64UrlNoPad::new(digest.to_vec()).encode()
+    }
 }
 
 impl ParametersInfo for JsonWebKeyPublicParameters {
@@ -68,6 +101,14 @@ impl ParametersInfo for JsonWebKeyPublicParameters {
             JsonWebKeyPublicParameters::Okp(p) => p.possible_algs(),
         }
     }
+
+    fn possible_encryption_algs(&self) -> &[JsonWebEncryptionAlg] {
+        match self {
+            JsonWebKeyPublicParameters::Rsa(p) => p.possible_encryption_algs(),
+            JsonWebKeyPublicParameters::Ec(p) => p.possible_encryption_algs(),
+            JsonWebKeyPublicParameters::Okp(p) => p.possible_encryption_algs(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -94,6 +135,13 @@ impl ParametersInfo for RsaPublicParameters {
             JsonWebSignatureAlg::Ps512,
         ]
     }
+
+    fn possible_encryption_algs(&self) -> &[JsonWebEncryptionAlg] {
+        &[
+            JsonWebEncryptionAlg::RsaOaep,
+            JsonWebEncryptionAlg::RsaOaep256,
+        ]
+    }
 }
 
 impl RsaPublicParameters {
@@ -133,6 +181,16 @@ impl ParametersInfo for EcPublicParameters {
             _ => &[],
         }
     }
+
+    fn possible_encryption_algs(&self) -> &[JsonWebEncryptionAlg] {
+        match &self.crv {
+            JsonWebKeyEcEllipticCurve::P256 | JsonWebKeyEcEllipticCurve::P384 => &[
+                JsonWebEncryptionAlg::EcdhEs,
+                JsonWebEncryptionAlg::EcdhEsA256Kw,
+            ],
+            _ => &[],
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -151,6 +209,10 @@ impl ParametersInfo for OkpPublicParameters {
     fn possible_algs(&self) -> &[JsonWebSignatureAlg] {
         &[JsonWebSignatureAlg::EdDsa]
     }
+
+    fn possible_encryption_algs(&self) -> &[JsonWebEncryptionAlg] {
+        &[]
+    }
 }
 
 impl OkpPublicParameters {
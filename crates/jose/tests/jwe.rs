@@ -0,0 +1,189 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use mas_iana::jose::{JsonWebEncryptionAlg, JsonWebEncryptionEnc};
+use mas_jose::jwe::{JsonWebEncryptionHeader, Jwe};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+static PLAINTEXT: &[u8] = b"The true sign of intelligence is not knowledge but imagination.";
+
+fn public_jwks() -> mas_jose::jwk::PublicJsonWebKeySet {
+    serde_json::from_str(include_str!("./keys/jwks.pub.json")).unwrap()
+}
+
+fn private_jwks() -> mas_jose::jwk::PrivateJsonWebKeySet {
+    serde_json::from_str(include_str!("./keys/jwks.priv.json")).unwrap()
+}
+
+macro_rules! asymmetric_jwe_test {
+    ($test_name:ident, $alg:ident, $enc:ident) => {
+        mod $test_name {
+            use super::*;
+
+            #[test]
+            fn encrypt_and_decrypt_jwe() {
+                let mut rng = ChaCha8Rng::seed_from_u64(42);
+                let header = JsonWebEncryptionHeader::new(
+                    JsonWebEncryptionAlg::$alg,
+                    JsonWebEncryptionEnc::$enc,
+                );
+
+                let jwe =
+                    Jwe::encrypt_with_jwks(&mut rng, header, PLAINTEXT, &public_jwks()).unwrap();
+                assert!(jwe.header().kid().is_some());
+
+                let jwe = Jwe::try_from(jwe.as_str()).unwrap();
+                assert_eq!(*jwe.header().alg(), JsonWebEncryptionAlg::$alg);
+                assert_eq!(*jwe.header().enc(), JsonWebEncryptionEnc::$enc);
+
+                let plaintext = jwe.decrypt_with_jwks(&private_jwks()).unwrap();
+                assert_eq!(plaintext, PLAINTEXT);
+            }
+
+            #[test]
+            fn reject_tampered_jwe() {
+                let mut rng = ChaCha8Rng::seed_from_u64(42);
+                let header = JsonWebEncryptionHeader::new(
+                    JsonWebEncryptionAlg::$alg,
+                    JsonWebEncryptionEnc::$enc,
+                );
+
+                let jwe =
+                    Jwe::encrypt_with_jwks(&mut rng, header, PLAINTEXT, &public_jwks()).unwrap();
+
+                // Flip a character in the ciphertext
+                let mut parts: Vec<String> = jwe.as_str().split('.').map(str::to_owned).collect();
+                let replacement = if parts[3].starts_with('A') { "B" } else { "A" };
+                parts[3].replace_range(..1, replacement);
+                let jwe = Jwe::try_from(parts.join(".")).unwrap();
+
+                jwe.decrypt_with_jwks(&private_jwks()).unwrap_err();
+            }
+        }
+    };
+}
+
+asymmetric_jwe_test!(rsa_oaep_a256gcm, RsaOaep, A256Gcm);
+asymmetric_jwe_test!(rsa_oaep_256_a256gcm, RsaOaep256, A256Gcm);
+asymmetric_jwe_test!(rsa_oaep_256_a128cbc_hs256, RsaOaep256, A128CbcHs256);
+asymmetric_jwe_test!(ecdh_es_a256gcm, EcdhEs, A256Gcm);
+asymmetric_jwe_test!(ecdh_es_a128cbc_hs256, EcdhEs, A128CbcHs256);
+asymmetric_jwe_test!(ecdh_es_a256kw_a256gcm, EcdhEsA256Kw, A256Gcm);
+asymmetric_jwe_test!(ecdh_es_a256kw_a128cbc_hs256, EcdhEsA256Kw, A128CbcHs256);
+
+#[test]
+fn encrypt_and_decrypt_with_shared_secret() {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+    for (alg, secret) in [
+        (JsonWebEncryptionAlg::A128Kw, &[42; 16][..]),
+        (JsonWebEncryptionAlg::A256Kw, &[42; 32][..]),
+    ] {
+        for enc in mas_jose::jwa::SUPPORTED_CONTENT_ENCRYPTION_ALGORITHMS {
+            let header = JsonWebEncryptionHeader::new(alg.clone(), enc);
+            let jwe = Jwe::encrypt_with_shared_secret(&mut rng, header, PLAINTEXT, secret).unwrap();
+            let jwe = Jwe::try_from(jwe.into_string()).unwrap();
+
+            let plaintext = jwe.decrypt_with_shared_secret(secret).unwrap();
+            assert_eq!(plaintext, PLAINTEXT);
+
+            jwe.decrypt_with_shared_secret(&vec![21; secret.len()])
+                .unwrap_err();
+        }
+    }
+}
+
+/// Example JWE using RSAES-OAEP and AES GCM, from RFC 7516, Appendix A.1.
+///
+/// The RFC doesn't ship its RSA key in a reusable form, so the content
+/// encryption key of the example is wrapped for the RSA key of the test JWKS
+/// instead. The encrypted key isn't part of the additional authenticated
+/// data, so the protected header, IV, ciphertext and tag are the ones from
+/// the RFC.
+#[test]
+fn rfc7516_appendix_a1() {
+    let jwe = [
+        "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkEyNTZHQ00ifQ",
+        "QO97o3kTGzRGtM7NNINaVX45GQdljQJM-ZspXxnRVKQLmOwIb56snfVesFZIFH6F5duikmXpAWbbusYwIycAvdWycp\
+         o1r7Mu3Z-MC7niq3DfbvQI10-17GAV0JFSMYJPHW1wSDxxPN1Bly63UYjMZGU-Fnul21YKKxDugejw0pd9pCEZDWDa\
+         ghgeINfRxfhyeJO03y7iTRi7TFJ6TrAG_vShTkUCAkaZzskPZK9St2H2RWrTx04lmH7EksvBIb3SP46J9ewZZfVUTo\
+         IOZRq2pamkW3yMg5XK0JU7wmDb3Q5GA_K_lU8Hjg_SF1EyP9l_3AMYaahL32m2vBOP1DTgHQ",
+        "48V1_ALb6US04U3b",
+        "5eym8TW_c8SuK0ltJ3rpYIzOeDQz7TALvtu6UG9oMo4vpzs9tX_EFShS8iB7j6jiSdiwkIr3ajwQzaBtQD_A",
+        "XFBoMYUZodetZdvTiFvSkQ",
+    ]
+    .join(".");
+
+    let jwe = Jwe::try_from(jwe).unwrap();
+    assert_eq!(*jwe.header().alg(), JsonWebEncryptionAlg::RsaOaep);
+    assert_eq!(*jwe.header().enc(), JsonWebEncryptionEnc::A256Gcm);
+
+    let plaintext = jwe.decrypt_with_jwks(&private_jwks()).unwrap();
+    assert_eq!(plaintext, PLAINTEXT);
+}
+
+/// Example JWE using AES Key Wrap and `AES_128_CBC_HMAC_SHA_256`, from RFC
+/// 7516, Appendix A.3
+#[test]
+fn rfc7516_appendix_a3() {
+    let jwe = [
+        "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0",
+        "6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ",
+        "AxY8DCtDaGlsbGljb3RoZQ",
+        "KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY",
+        "U0m_YmjN04DJvceFICbCVQ",
+    ]
+    .join(".");
+    let secret = Base64UrlUnpadded::decode_vec("GawgguFyGrWKav7AX4VKUg").unwrap();
+
+    let jwe = Jwe::try_from(jwe).unwrap();
+    assert_eq!(*jwe.header().alg(), JsonWebEncryptionAlg::A128Kw);
+    assert_eq!(*jwe.header().enc(), JsonWebEncryptionEnc::A128CbcHs256);
+
+    let plaintext = jwe.decrypt_with_shared_secret(&secret).unwrap();
+    assert_eq!(plaintext, b"Live long and prosper.");
+
+    // Tampering with the authentication tag must be detected
+    let tampered = jwe
+        .as_str()
+        .replace("U0m_YmjN04DJvceFICbCVQ", "V0m_YmjN04DJvceFICbCVQ");
+    let tampered = Jwe::try_from(tampered).unwrap();
+    tampered.decrypt_with_shared_secret(&secret).unwrap_err();
+}
+
+#[test]
+fn ecdh_es_agreement_party_info() {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let header =
+        JsonWebEncryptionHeader::new(JsonWebEncryptionAlg::EcdhEs, JsonWebEncryptionEnc::A256Gcm)
+            .with_apu(b"Alice".to_vec())
+            .with_apv(b"Bob".to_vec());
+
+    let jwe = Jwe::encrypt_with_jwks(&mut rng, header, PLAINTEXT, &public_jwks()).unwrap();
+    assert!(jwe.header().epk().is_some());
+    assert!(jwe.as_str().split('.').nth(1).unwrap().is_empty());
+
+    let jwe = Jwe::try_from(jwe.as_str()).unwrap();
+    assert_eq!(jwe.header().apu(), Some(&b"Alice"[..]));
+    assert_eq!(jwe.decrypt_with_jwks(&private_jwks()).unwrap(), PLAINTEXT);
+}
+
+#[test]
+fn unsupported_algorithms() {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+    // No key in the JWKS supports RSA1_5
+    let header =
+        JsonWebEncryptionHeader::new(JsonWebEncryptionAlg::Rsa15, JsonWebEncryptionEnc::A256Gcm);
+    Jwe::encrypt_with_jwks(&mut rng, header, PLAINTEXT, &public_jwks()).unwrap_err();
+
+    let header = JsonWebEncryptionHeader::new(
+        JsonWebEncryptionAlg::RsaOaep256,
+        JsonWebEncryptionEnc::A192Gcm,
+    );
+    Jwe::encrypt_with_jwks(&mut rng, header, PLAINTEXT, &public_jwks()).unwrap_err();
+}
@@ -10,7 +10,7 @@ use std::{ops::Deref, sync::Arc};
 
 use der::{Decode, Encode, EncodePem, zeroize::Zeroizing};
 use elliptic_curve::{pkcs8::EncodePrivateKey, sec1::ToEncodedPoint};
-use mas_iana::jose::{JsonWebKeyType, JsonWebSignatureAlg};
+use mas_iana::jose::{JsonWebEncryptionAlg, JsonWebKeyType, JsonWebSignatureAlg};
 pub use mas_jose::jwk::{JsonWebKey, JsonWebKeySet};
 use mas_jose::{
     jwa::{AsymmetricSigningKey, AsymmetricVerifyingKey},
@@ -597,6 +597,17 @@ impl ParametersInfo for PrivateKey {
             PrivateKey::EcK256(_) => &[JsonWebSignatureAlg::Es256K],
         }
     }
+
+    fn possible_encryption_algs(&self) -> &'static [JsonWebEncryptionAlg] {
+        match self {
+            PrivateKey::Rsa(_) => &[JsonWebEncryptionAlg::RsaOaep256],
+            PrivateKey::EcP256(_) | PrivateKey::EcP384(_) => &[
+                JsonWebEncryptionAlg::EcdhEs,
+                JsonWebEncryptionAlg::EcdhEsA256Kw,
+            ],
+            PrivateKey::EcK256(_) => &[],
+        }
+    }
 }
 
 /// A structure to store a list of [`PrivateKey`]. The keys are held in an
@@ -165,6 +165,14 @@ pub enum ClientErrorCode {
     /// From [OpenID Connect Core 1.0](https://openid.net/specs/openid-connect-core-1_0.html#AuthError).
     ConsentRequired,
 
+    /// `unmet_authentication_requirements`
+    ///
+    /// The authorization server is unable to meet the requirements of the
+    /// relying party for the authentication of the end-user.
+    ///
+    /// From [OpenID Connect Core Unmet Authentication Requirements 1.0](https://openid.net/specs/openid-connect-unmet-authentication-requirements-1_0.html).
+    UnmetAuthenticationRequirements,
+
     /// `invalid_request_uri`
     ///
     /// The `request_uri` in the authorization request returns an error or
@@ -219,6 +227,21 @@ pub enum ClientErrorCode {
     /// From [RFC7591](https://www.rfc-editor.org/rfc/rfc7591#section-3.2.2).
     InvalidClientMetadata,
 
+    /// `invalid_software_statement`
+    ///
+    /// The software statement presented is invalid.
+    ///
+    /// From [RFC7591](https://www.rfc-editor.org/rfc/rfc7591#section-3.2.2).
+    InvalidSoftwareStatement,
+
+    /// `unapproved_software_statement`
+    ///
+    /// The software statement presented is not approved for use by this
+    /// authorization server.
+    ///
+    /// From [RFC7591](https://www.rfc-editor.org/rfc/rfc7591#section-3.2.2).
+    UnapprovedSoftwareStatement,
+
     /// `authorization_pending`
     ///
     /// The authorization request is still pending as the end user hasn't yet
@@ -265,6 +288,45 @@ pub enum ClientErrorCode {
     /// From [RFC7009](https://www.rfc-editor.org/rfc/rfc7009#section-2.2.1).
     UnsupportedTokenType,
 
+    /// `invalid_dpop_proof`
+    ///
+    /// The `DPoP` proof sent with the request is missing or invalid.
+    ///
+    /// From [RFC9449](https://www.rfc-editor.org/rfc/rfc9449#section-5).
+    InvalidDpopProof,
+
+    /// `invalid_target`
+    ///
+    /// The requested resource or audience is invalid, unknown, or malformed.
+    ///
+    /// From [RFC8693](https://www.rfc-editor.org/rfc/rfc8693#section-2.2.2).
+    InvalidTarget,
+
+    /// `invalid_authorization_details`
+    ///
+    /// The requested authorization details are invalid, unknown, or
+    /// malformed.
+    ///
+    /// From [RFC9396](https://www.rfc-editor.org/rfc/rfc9396#section-5).
+    InvalidAuthorizationDetails,
+
+    /// `unknown_user_id`
+    ///
+    /// The authorization server is not able to identify which end-user the
+    /// client wishes to be authenticated by means of the hint provided in the
+    /// request.
+    ///
+    /// From [OpenID Connect Client-Initiated Backchannel Authentication Flow](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.13).
+    UnknownUserId,
+
+    /// `invalid_binding_message`
+    ///
+    /// The binding message is invalid or unacceptable for use in the context
+    /// of the given request.
+    ///
+    /// From [OpenID Connect Client-Initiated Backchannel Authentication Flow](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.13).
+    InvalidBindingMessage,
+
     /// Another error code.
     Unknown(String),
 }
@@ -286,6 +348,9 @@ impl core::fmt::Display for ClientErrorCode {
             ClientErrorCode::LoginRequired => f.write_str("login_required"),
             ClientErrorCode::AccountSelectionRequired => f.write_str("account_selection_required"),
             ClientErrorCode::ConsentRequired => f.write_str("consent_required"),
+            ClientErrorCode::UnmetAuthenticationRequirements => {
+                f.write_str("unmet_authentication_requirements")
+            }
             ClientErrorCode::InvalidRequestUri => f.write_str("invalid_request_uri"),
             ClientErrorCode::InvalidRequestObject => f.write_str("invalid_request_object"),
             ClientErrorCode::RequestNotSupported => f.write_str("request_not_supported"),
@@ -293,10 +358,21 @@ impl core::fmt::Display for ClientErrorCode {
             ClientErrorCode::RegistrationNotSupported => f.write_str("registration_not_supported"),
             ClientErrorCode::InvalidRedirectUri => f.write_str("invalid_redirect_uri"),
             ClientErrorCode::InvalidClientMetadata => f.write_str("invalid_client_metadata"),
+            ClientErrorCode::InvalidSoftwareStatement => f.write_str("invalid_software_statement"),
+            ClientErrorCode::UnapprovedSoftwareStatement => {
+                f.write_str("unapproved_software_statement")
+            }
             ClientErrorCode::AuthorizationPending => f.write_str("authorization_pending"),
             ClientErrorCode::SlowDown => f.write_str("slow_down"),
             ClientErrorCode::ExpiredToken => f.write_str("expired_token"),
             ClientErrorCode::UnsupportedTokenType => f.write_str("unsupported_token_type"),
+            ClientErrorCode::InvalidDpopProof => f.write_str("invalid_dpop_proof"),
+            ClientErrorCode::InvalidTarget => f.write_str("invalid_target"),
+            ClientErrorCode::InvalidAuthorizationDetails => {
+                f.write_str("invalid_authorization_details")
+            }
+            ClientErrorCode::UnknownUserId => f.write_str("unknown_user_id"),
+            ClientErrorCode::InvalidBindingMessage => f.write_str("invalid_binding_message"),
             ClientErrorCode::Unknown(value) => f.write_str(value),
         }
     }
@@ -321,6 +397,9 @@ impl core::str::FromStr for ClientErrorCode {
             "login_required" => Ok(ClientErrorCode::LoginRequired),
             "account_selection_required" => Ok(ClientErrorCode::AccountSelectionRequired),
             "consent_required" => Ok(ClientErrorCode::ConsentRequired),
+            "unmet_authentication_requirements" => {
+                Ok(ClientErrorCode::UnmetAuthenticationRequirements)
+            }
             "invalid_request_uri" => Ok(ClientErrorCode::InvalidRequestUri),
             "invalid_request_object" => Ok(ClientErrorCode::InvalidRequestObject),
             "request_not_supported" => Ok(ClientErrorCode::RequestNotSupported),
@@ -328,10 +407,17 @@ impl core::str::FromStr for ClientErrorCode {
             "registration_not_supported" => Ok(ClientErrorCode::RegistrationNotSupported),
             "invalid_redirect_uri" => Ok(ClientErrorCode::InvalidRedirectUri),
             "invalid_client_metadata" => Ok(ClientErrorCode::InvalidClientMetadata),
+            "invalid_software_statement" => Ok(ClientErrorCode::InvalidSoftwareStatement),
+            "unapproved_software_statement" => Ok(ClientErrorCode::UnapprovedSoftwareStatement),
             "authorization_pending" => Ok(ClientErrorCode::AuthorizationPending),
             "slow_down" => Ok(ClientErrorCode::SlowDown),
             "expired_token" => Ok(ClientErrorCode::ExpiredToken),
             "unsupported_token_type" => Ok(ClientErrorCode::UnsupportedTokenType),
+            "invalid_dpop_proof" => Ok(ClientErrorCode::InvalidDpopProof),
+            "invalid_target" => Ok(ClientErrorCode::InvalidTarget),
+            "invalid_authorization_details" => Ok(ClientErrorCode::InvalidAuthorizationDetails),
+            "unknown_user_id" => Ok(ClientErrorCode::UnknownUserId),
+            "invalid_binding_message" => Ok(ClientErrorCode::InvalidBindingMessage),
             _ => Ok(ClientErrorCode::Unknown(s.to_owned())),
         }
     }
@@ -342,6 +428,7 @@ impl ClientErrorCode {
     ///
     /// Note that [`ClientErrorCode::Unknown`] returns an empty string.
     #[must_use]
+    #[allow(clippy::too_many_lines)]
     pub fn default_description(&self) -> &'static str {
         match self {
             ClientErrorCode::InvalidRequest => {
@@ -389,6 +476,10 @@ impl ClientErrorCode {
             ClientErrorCode::ConsentRequired => {
                 "The Authorization Server requires End-User consent."
             }
+            ClientErrorCode::UnmetAuthenticationRequirements => {
+                "The Authorization Server is unable to meet the requirements of the \
+                Relying Party for the authentication of the End-User."
+            }
             ClientErrorCode::InvalidRequestUri => {
                 "The request_uri in the Authorization Request returns an error \
                 or contains invalid data."
@@ -411,6 +502,12 @@ impl ClientErrorCode {
             ClientErrorCode::InvalidClientMetadata => {
                 "The value of one of the client metadata fields is invalid"
             }
+            ClientErrorCode::InvalidSoftwareStatement => {
+                "The software statement presented is invalid"
+            }
+            ClientErrorCode::UnapprovedSoftwareStatement => {
+                "The software statement presented is not approved by this server"
+            }
             ClientErrorCode::AuthorizationPending => "The authorization request is still pending",
             ClientErrorCode::SlowDown => {
                 "The interval must be increased by 5 seconds for this and all subsequent requests"
@@ -421,6 +518,19 @@ impl ClientErrorCode {
             ClientErrorCode::UnsupportedTokenType => {
                 "The authorization server does not support the revocation of the presented token type."
             }
+            ClientErrorCode::InvalidDpopProof => "The DPoP proof is missing or invalid.",
+            ClientErrorCode::InvalidTarget => {
+                "The requested resource or audience is invalid, unknown, or malformed."
+            }
+            ClientErrorCode::InvalidAuthorizationDetails => {
+                "The requested authorization details are invalid, unknown, or malformed."
+            }
+            ClientErrorCode::UnknownUserId => {
+                "The authorization server is not able to identify which end-user the client wishes to be authenticated."
+            }
+            ClientErrorCode::InvalidBindingMessage => {
+                "The binding message is invalid or unacceptable for use in the context of the given request."
+            }
             ClientErrorCode::Unknown(_) => "",
         }
     }
@@ -488,6 +598,10 @@ mod tests {
             serde_json::to_string(&ClientErrorCode::ConsentRequired).unwrap(),
             "\"consent_required\""
         );
+        assert_eq!(
+            serde_json::to_string(&ClientErrorCode::UnmetAuthenticationRequirements).unwrap(),
+            "\"unmet_authentication_requirements\""
+        );
         assert_eq!(
             serde_json::to_string(&ClientErrorCode::InvalidRequestUri).unwrap(),
             "\"invalid_request_uri\""
@@ -516,6 +630,34 @@ mod tests {
             serde_json::to_string(&ClientErrorCode::InvalidClientMetadata).unwrap(),
             "\"invalid_client_metadata\""
         );
+        assert_eq!(
+            serde_json::to_string(&ClientErrorCode::InvalidSoftwareStatement).unwrap(),
+            "\"invalid_software_statement\""
+        );
+        assert_eq!(
+            serde_json::to_string(&ClientErrorCode::UnapprovedSoftwareStatement).unwrap(),
+            "\"unapproved_software_statement\""
+        );
+        assert_eq!(
+            serde_json::to_string(&ClientErrorCode::InvalidDpopProof).unwrap(),
+            "\"invalid_dpop_proof\""
+        );
+        assert_eq!(
+            serde_json::to_string(&ClientErrorCode::InvalidTarget).unwrap(),
+            "\"invalid_target\""
+        );
+        assert_eq!(
+            serde_json::to_string(&ClientErrorCode::InvalidAuthorizationDetails).unwrap(),
+            "\"invalid_authorization_details\""
+        );
+        assert_eq!(
+            serde_json::to_string(&ClientErrorCode::UnknownUserId).unwrap(),
+            "\"unknown_user_id\""
+        );
+        assert_eq!(
+            serde_json::to_string(&ClientErrorCode::InvalidBindingMessage).unwrap(),
+            "\"invalid_binding_message\""
+        );
 
         assert_eq!(
             serde_json::to_string(&ClientErrorCode::Unknown("unknown_error_code".to_owned()))
@@ -582,6 +724,11 @@ mod tests {
             serde_json::from_str::<ClientErrorCode>("\"consent_required\"").unwrap(),
             ClientErrorCode::ConsentRequired
         );
+        assert_eq!(
+            serde_json::from_str::<ClientErrorCode>("\"unmet_authentication_requirements\"")
+                .unwrap(),
+            ClientErrorCode::UnmetAuthenticationRequirements
+        );
         assert_eq!(
             serde_json::from_str::<ClientErrorCode>("\"invalid_request_uri\"").unwrap(),
             ClientErrorCode::InvalidRequestUri
@@ -610,6 +757,34 @@ mod tests {
             serde_json::from_str::<ClientErrorCode>("\"invalid_client_metadata\"").unwrap(),
             ClientErrorCode::InvalidClientMetadata
         );
+        assert_eq!(
+            serde_json::from_str::<ClientErrorCode>("\"invalid_software_statement\"").unwrap(),
+            ClientErrorCode::InvalidSoftwareStatement
+        );
+        assert_eq!(
+            serde_json::from_str::<ClientErrorCode>("\"unapproved_software_statement\"").unwrap(),
+            ClientErrorCode::UnapprovedSoftwareStatement
+        );
+        assert_eq!(
+            serde_json::from_str::<ClientErrorCode>("\"invalid_dpop_proof\"").unwrap(),
+            ClientErrorCode::InvalidDpopProof
+        );
+        assert_eq!(
+            serde_json::from_str::<ClientErrorCode>("\"invalid_target\"").unwrap(),
+            ClientErrorCode::InvalidTarget
+        );
+        assert_eq!(
+            serde_json::from_str::<ClientErrorCode>("\"invalid_authorization_details\"").unwrap(),
+            ClientErrorCode::InvalidAuthorizationDetails
+        );
+        assert_eq!(
+            serde_json::from_str::<ClientErrorCode>("\"unknown_user_id\"").unwrap(),
+            ClientErrorCode::UnknownUserId
+        );
+        assert_eq!(
+            serde_json::from_str::<ClientErrorCode>("\"invalid_binding_message\"").unwrap(),
+            ClientErrorCode::InvalidBindingMessage
+        );
 
         assert_eq!(
             serde_json::from_str::<ClientErrorCode>("\"unknown_error_code\"").unwrap(),
@@ -184,6 +184,51 @@ impl core::str::FromStr for SubjectType {
     }
 }
 
+/// Token delivery modes of the [Client-Initiated Backchannel Authentication]
+/// flow.
+///
+/// [Client-Initiated Backchannel Authentication]: https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html
+#[derive(SerializeDisplay, DeserializeFromStr, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum BackchannelTokenDeliveryMode {
+    /// The client polls the token endpoint to get the result of the
+    /// authentication.
+    Poll,
+
+    /// The provider notifies the client when the result of the authentication
+    /// is available, and the client then gets it from the token endpoint.
+    Ping,
+
+    /// The provider sends the tokens directly to the client.
+    Push,
+
+    /// An unknown value.
+    Unknown(String),
+}
+
+impl core::fmt::Display for BackchannelTokenDeliveryMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Poll => f.write_str("poll"),
+            Self::Ping => f.write_str("ping"),
+            Self::Push => f.write_str("push"),
+            Self::Unknown(s) => f.write_str(s),
+        }
+    }
+}
+
+impl core::str::FromStr for BackchannelTokenDeliveryMode {
+    type Err = core::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "poll" => Ok(Self::Poll),
+            "ping" => Ok(Self::Ping),
+            "push" => Ok(Self::Push),
+            s => Ok(Self::Unknown(s.to_owned())),
+        }
+    }
+}
+
 /// Claim types.
 #[derive(SerializeDisplay, DeserializeFromStr, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum ClaimType {
@@ -475,6 +520,46 @@ pub struct ProviderMetadata {
     /// [`OAuthClientAuthenticationMethod::ClientSecretJwt`].
     pub introspection_endpoint_auth_signing_alg_values_supported: Option<Vec<JsonWebSignatureAlg>>,
 
+    /// JSON array containing a list of the JWS algorithms supported by the
+    /// introspection endpoint to sign [JWT introspection responses].
+    ///
+    /// [JWT introspection responses]: https://www.rfc-editor.org/rfc/rfc9701
+    pub introspection_signing_alg_values_supported: Option<Vec<JsonWebSignatureAlg>>,
+
+    /// JSON array containing a list of the JWE encryption algorithms (`alg`
+    /// values) supported by the introspection endpoint to encrypt [JWT
+    /// introspection responses].
+    ///
+    /// [JWT introspection responses]: https://www.rfc-editor.org/rfc/rfc9701
+    pub introspection_encryption_alg_values_supported: Option<Vec<JsonWebEncryptionAlg>>,
+
+    /// JSON array containing a list of the JWE encryption algorithms (`enc`
+    /// values) supported by the introspection endpoint to encrypt [JWT
+    /// introspection responses].
+    ///
+    /// [JWT introspection responses]: https://www.rfc-editor.org/rfc/rfc9701
+    pub introspection_encryption_enc_values_supported: Option<Vec<JsonWebEncryptionEnc>>,
+
+    /// JSON array containing a list of the JWS algorithms supported by the
+    /// authorization endpoint to sign [JWT-secured authorization responses].
+    ///
+    /// [JWT-secured authorization responses]: https://openid.net/specs/oauth-v2-jarm.html
+    pub authorization_signing_alg_values_supported: Option<Vec<JsonWebSignatureAlg>>,
+
+    /// JSON array containing a list of the JWE encryption algorithms (`alg`
+    /// values) supported by the authorization endpoint to encrypt [JWT-secured
+    /// authorization responses].
+    ///
+    /// [JWT-secured authorization responses]: https://openid.net/specs/oauth-v2-jarm.html
+    pub authorization_encryption_alg_values_supported: Option<Vec<JsonWebEncryptionAlg>>,
+
+    /// JSON array containing a list of the JWE encryption algorithms (`enc`
+    /// values) supported by the authorization endpoint to encrypt [JWT-secured
+    /// authorization responses].
+    ///
+    /// [JWT-secured authorization responses]: https://openid.net/specs/oauth-v2-jarm.html
+    pub authorization_encryption_enc_values_supported: Option<Vec<JsonWebEncryptionEnc>>,
+
     /// [PKCE code challenge methods] supported by this authorization server.
     /// If omitted, the authorization server does not support PKCE.
     ///
@@ -609,11 +694,79 @@ pub struct ProviderMetadata {
     /// [device authorization endpoint]: https://www.rfc-editor.org/rfc/rfc8628
     pub device_authorization_endpoint: Option<Url>,
 
+    /// URL of the authorization server's [backchannel authentication
+    /// endpoint].
+    ///
+    /// [backchannel authentication endpoint]: https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7
+    pub backchannel_authentication_endpoint: Option<Url>,
+
+    /// JSON array containing the [backchannel token delivery modes] supported
+    /// by the provider.
+    ///
+    /// [backchannel token delivery modes]: https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.4
+    pub backchannel_token_delivery_modes_supported: Option<Vec<BackchannelTokenDeliveryMode>>,
+
+    /// Indicates whether the provider supports the `user_code` parameter in
+    /// [backchannel authentication requests].
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [backchannel authentication requests]: https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7.1
+    pub backchannel_user_code_parameter_supported: Option<bool>,
+
     /// URL of the authorization server's [RP-Initiated Logout endpoint].
     ///
     /// [RP-Initiated Logout endpoint]: https://openid.net/specs/openid-connect-rpinitiated-1_0.html
     pub end_session_endpoint: Option<Url>,
 
+    /// Indicates whether the OP supports [back-channel logout].
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [back-channel logout]: https://openid.net/specs/openid-connect-backchannel-1_0.html
+    pub backchannel_logout_supported: Option<bool>,
+
+    /// Indicates whether the OP includes the `sid` claim in the logout tokens
+    /// sent through [back-channel logout].
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [back-channel logout]: https://openid.net/specs/openid-connect-backchannel-1_0.html
+    pub backchannel_logout_session_supported: Option<bool>,
+
+    /// Indicates whether the OP supports [front-channel logout].
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [front-channel logout]: https://openid.net/specs/openid-connect-frontchannel-1_0.html
+    pub frontchannel_logout_supported: Option<bool>,
+
+    /// Indicates whether the OP can pass the `iss` and `sid` query parameters
+    /// to the clients' [front-channel logout] URIs.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [front-channel logout]: https://openid.net/specs/openid-connect-frontchannel-1_0.html
+    pub frontchannel_logout_session_supported: Option<bool>,
+
+    /// JSON array containing a list of the JWS algorithms supported for [DPoP]
+    /// proof JWTs.
+    ///
+    /// [DPoP]: https://www.rfc-editor.org/rfc/rfc9449
+    pub dpop_signing_alg_values_supported: Option<Vec<JsonWebSignatureAlg>>,
+
+    /// Whether the provider supports [mutual-TLS certificate-bound access
+    /// tokens].
+    ///
+    /// [mutual-TLS certificate-bound access tokens]: https://www.rfc-editor.org/rfc/rfc8705#section-3
+    pub tls_client_certificate_bound_access_tokens: Option<bool>,
+
+    /// JSON array containing the [authorization details] types supported by
+    /// the provider.
+    ///
+    /// [authorization details]: https://www.rfc-editor.org/rfc/rfc9396#section-10
+    pub authorization_details_types_supported: Option<Vec<String>>,
+
     /// URL where the user is able to access the account management capabilities
     /// of this OP.
     ///
@@ -919,6 +1072,48 @@ impl ProviderMetadata {
     pub fn require_pushed_authorization_requests(&self) -> bool {
         self.require_pushed_authorization_requests.unwrap_or(false)
     }
+
+    /// Indicates whether the OP supports [back-channel logout].
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [back-channel logout]: https://openid.net/specs/openid-connect-backchannel-1_0.html
+    #[must_use]
+    pub fn backchannel_logout_supported(&self) -> bool {
+        self.backchannel_logout_supported.unwrap_or(false)
+    }
+
+    /// Indicates whether the OP includes the `sid` claim in the logout tokens
+    /// sent through [back-channel logout].
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [back-channel logout]: https://openid.net/specs/openid-connect-backchannel-1_0.html
+    #[must_use]
+    pub fn backchannel_logout_session_supported(&self) -> bool {
+        self.backchannel_logout_session_supported.unwrap_or(false)
+    }
+
+    /// Indicates whether the OP supports [front-channel logout].
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [front-channel logout]: https://openid.net/specs/openid-connect-frontchannel-1_0.html
+    #[must_use]
+    pub fn frontchannel_logout_supported(&self) -> bool {
+        self.frontchannel_logout_supported.unwrap_or(false)
+    }
+
+    /// Indicates whether the OP can pass the `iss` and `sid` query parameters
+    /// to the clients' [front-channel logout] URIs.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [front-channel logout]: https://openid.net/specs/openid-connect-frontchannel-1_0.html
+    #[must_use]
+    pub fn frontchannel_logout_session_supported(&self) -> bool {
+        self.frontchannel_logout_session_supported.unwrap_or(false)
+    }
 }
 
 /// The verified authorization server metadata.
@@ -25,7 +25,7 @@ use url::Url;
 
 use super::{ClientMetadata, Localized, VerifiedClientMetadata};
 use crate::{
-    oidc::{ApplicationType, SubjectType},
+    oidc::{ApplicationType, BackchannelTokenDeliveryMode, SubjectType},
     requests::GrantType,
     response_type::ResponseType,
 };
@@ -102,6 +102,7 @@ pub struct ClientMetadataSerdeHelper {
     jwks: Option<PublicJsonWebKeySet>,
     software_id: Option<String>,
     software_version: Option<String>,
+    software_statement: Option<String>,
     sector_identifier_uri: Option<Url>,
     subject_type: Option<SubjectType>,
     token_endpoint_auth_method: Option<OAuthClientAuthenticationMethod>,
@@ -126,7 +127,18 @@ pub struct ClientMetadataSerdeHelper {
     introspection_signed_response_alg: Option<JsonWebSignatureAlg>,
     introspection_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
     introspection_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
+    authorization_signed_response_alg: Option<JsonWebSignatureAlg>,
+    authorization_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
+    authorization_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
     post_logout_redirect_uris: Option<Vec<Url>>,
+    backchannel_logout_uri: Option<Url>,
+    frontchannel_logout_uri: Option<Url>,
+    frontchannel_logout_session_required: Option<bool>,
+    dpop_bound_access_tokens: Option<bool>,
+    tls_client_auth_subject_dn: Option<String>,
+    tls_client_auth_san_dns: Option<String>,
+    backchannel_token_delivery_mode: Option<BackchannelTokenDeliveryMode>,
+    backchannel_client_notification_endpoint: Option<Url>,
     #[serde(flatten)]
     extra: ClientMetadataLocalizedFields,
 }
@@ -138,6 +150,7 @@ impl From<VerifiedClientMetadata> for ClientMetadataSerdeHelper {
 }
 
 impl From<ClientMetadata> for ClientMetadataSerdeHelper {
+    #[allow(clippy::too_many_lines)]
     fn from(metadata: ClientMetadata) -> Self {
         let ClientMetadata {
             redirect_uris,
@@ -154,6 +167,7 @@ impl From<ClientMetadata> for ClientMetadataSerdeHelper {
             jwks,
             software_id,
             software_version,
+            software_statement,
             sector_identifier_uri,
             subject_type,
             token_endpoint_auth_method,
@@ -177,7 +191,18 @@ impl From<ClientMetadata> for ClientMetadataSerdeHelper {
             introspection_signed_response_alg,
             introspection_encrypted_response_alg,
             introspection_encrypted_response_enc,
+            authorization_signed_response_alg,
+            authorization_encrypted_response_alg,
+            authorization_encrypted_response_enc,
             post_logout_redirect_uris,
+            backchannel_logout_uri,
+            frontchannel_logout_uri,
+            frontchannel_logout_session_required,
+            dpop_bound_access_tokens,
+            tls_client_auth_subject_dn,
+            tls_client_auth_san_dns,
+            backchannel_token_delivery_mode,
+            backchannel_client_notification_endpoint,
         } = metadata;
 
         ClientMetadataSerdeHelper {
@@ -190,6 +215,7 @@ impl From<ClientMetadata> for ClientMetadataSerdeHelper {
             jwks,
             software_id,
             software_version,
+            software_statement,
             sector_identifier_uri,
             subject_type,
             token_endpoint_auth_method,
@@ -213,7 +239,18 @@ impl From<ClientMetadata> for ClientMetadataSerdeHelper {
             introspection_signed_response_alg,
             introspection_encrypted_response_alg,
             introspection_encrypted_response_enc,
+            authorization_signed_response_alg,
+            authorization_encrypted_response_alg,
+            authorization_encrypted_response_enc,
             post_logout_redirect_uris,
+            backchannel_logout_uri,
+            frontchannel_logout_uri,
+            frontchannel_logout_session_required,
+            dpop_bound_access_tokens,
+            tls_client_auth_subject_dn,
+            tls_client_auth_san_dns,
+            backchannel_token_delivery_mode,
+            backchannel_client_notification_endpoint,
             extra: ClientMetadataLocalizedFields {
                 client_name,
                 logo_uri,
@@ -226,6 +263,7 @@ impl From<ClientMetadata> for ClientMetadataSerdeHelper {
 }
 
 impl From<ClientMetadataSerdeHelper> for ClientMetadata {
+    #[allow(clippy::too_many_lines)]
     fn from(metadata: ClientMetadataSerdeHelper) -> Self {
         let ClientMetadataSerdeHelper {
             redirect_uris,
@@ -237,6 +275,7 @@ impl From<ClientMetadataSerdeHelper> for ClientMetadata {
             jwks,
             software_id,
             software_version,
+            software_statement,
             sector_identifier_uri,
             subject_type,
             token_endpoint_auth_method,
@@ -260,7 +299,18 @@ impl From<ClientMetadataSerdeHelper> for ClientMetadata {
             introspection_signed_response_alg,
             introspection_encrypted_response_alg,
             introspection_encrypted_response_enc,
+            authorization_signed_response_alg,
+            authorization_encrypted_response_alg,
+            authorization_encrypted_response_enc,
             post_logout_redirect_uris,
+            backchannel_logout_uri,
+            frontchannel_logout_uri,
+            frontchannel_logout_session_required,
+            dpop_bound_access_tokens,
+            tls_client_auth_subject_dn,
+            tls_client_auth_san_dns,
+            backchannel_token_delivery_mode,
+            backchannel_client_notification_endpoint,
             extra:
                 ClientMetadataLocalizedFields {
                     client_name,
@@ -286,6 +336,7 @@ impl From<ClientMetadataSerdeHelper> for ClientMetadata {
             jwks,
             software_id,
             software_version,
+            software_statement,
             sector_identifier_uri,
             subject_type,
             token_endpoint_auth_method,
@@ -309,7 +360,18 @@ impl From<ClientMetadataSerdeHelper> for ClientMetadata {
             introspection_signed_response_alg,
             introspection_encrypted_response_alg,
             introspection_encrypted_response_enc,
+            authorization_signed_response_alg,
+            authorization_encrypted_response_alg,
+            authorization_encrypted_response_enc,
             post_logout_redirect_uris,
+            backchannel_logout_uri,
+            frontchannel_logout_uri,
+            frontchannel_logout_session_required,
+            dpop_bound_access_tokens,
+            tls_client_auth_subject_dn,
+            tls_client_auth_san_dns,
+            backchannel_token_delivery_mode,
+            backchannel_client_notification_endpoint,
         }
     }
 }
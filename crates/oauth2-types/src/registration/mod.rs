@@ -24,7 +24,7 @@ use thiserror::Error;
 use url::Url;
 
 use crate::{
-    oidc::{ApplicationType, SubjectType},
+    oidc::{ApplicationType, BackchannelTokenDeliveryMode, SubjectType},
     requests::GrantType,
     response_type::ResponseType,
 };
@@ -220,6 +220,13 @@ pub struct ClientMetadata {
     /// `software_id`.
     pub software_version: Option<String>,
 
+    /// A software statement containing client metadata values about the client
+    /// software as claims, as a JWT signed by a software publisher.
+    ///
+    /// Once verified, the claims of the statement take precedence over the
+    /// corresponding metadata values sent in plain text.
+    pub software_statement: Option<String>,
+
     /// URL to be used in calculating pseudonymous identifiers by the OpenID
     /// Connect provider when [pairwise subject identifiers] are used.
     ///
@@ -235,8 +242,13 @@ pub struct ClientMetadata {
 
     /// Requested client authentication method for the [token endpoint].
     ///
-    /// If this is set to [`OAuthClientAuthenticationMethod::PrivateKeyJwt`],
-    /// one of the `jwks_uri` or `jwks` fields is required.
+    /// If this is set to [`OAuthClientAuthenticationMethod::PrivateKeyJwt`] or
+    /// [`OAuthClientAuthenticationMethod::SelfSignedTlsClientAuth`], one of the
+    /// `jwks_uri` or `jwks` fields is required.
+    ///
+    /// If this is set to [`OAuthClientAuthenticationMethod::TlsClientAuth`],
+    /// exactly one of the `tls_client_auth_subject_dn` or
+    /// `tls_client_auth_san_dns` fields is required.
     ///
     /// Defaults to [`DEFAULT_TOKEN_AUTH_METHOD`].
     ///
@@ -424,11 +436,106 @@ pub struct ClientMetadata {
     /// [introspection endpoint]: https://www.rfc-editor.org/info/rfc7662
     pub introspection_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
 
+    /// [JWS] `alg` algorithm for signing [JWT-secured authorization responses].
+    ///
+    /// Defaults to [`DEFAULT_SIGNING_ALGORITHM`].
+    ///
+    /// [JWS]: http://tools.ietf.org/html/draft-ietf-jose-json-web-signature
+    /// [JWT-secured authorization responses]: https://openid.net/specs/oauth-v2-jarm.html
+    pub authorization_signed_response_alg: Option<JsonWebSignatureAlg>,
+
+    /// [JWE] `alg` algorithm for encrypting [JWT-secured authorization
+    /// responses].
+    ///
+    /// This field is required if `authorization_encrypted_response_enc` is
+    /// provided.
+    ///
+    /// [JWE]: http://tools.ietf.org/html/draft-ietf-jose-json-web-encryption
+    /// [JWT-secured authorization responses]: https://openid.net/specs/oauth-v2-jarm.html
+    pub authorization_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
+
+    /// [JWE] `enc` algorithm for encrypting [JWT-secured authorization
+    /// responses].
+    ///
+    /// Defaults to [`DEFAULT_ENCRYPTION_ENC_ALGORITHM`] if
+    /// `authorization_encrypted_response_alg` is provided.
+    ///
+    /// [JWE]: http://tools.ietf.org/html/draft-ietf-jose-json-web-encryption
+    /// [JWT-secured authorization responses]: https://openid.net/specs/oauth-v2-jarm.html
+    pub authorization_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
+
     /// `post_logout_redirect_uri` values that are pre-registered by the client
     /// for use at the provider's [RP-Initiated Logout endpoint].
     ///
     /// [RP-Initiated Logout endpoint]: https://openid.net/specs/openid-connect-rpinitiated-1_0.html
     pub post_logout_redirect_uris: Option<Vec<Url>>,
+
+    /// URL that the provider calls to log out the user from the client, as
+    /// described in [OpenID Connect Back-Channel Logout].
+    ///
+    /// This must not contain a fragment.
+    ///
+    /// [OpenID Connect Back-Channel Logout]: https://openid.net/specs/openid-connect-backchannel-1_0.html
+    pub backchannel_logout_uri: Option<Url>,
+
+    /// URL that the provider loads in an `iframe` to log out the user from the
+    /// client, as described in [OpenID Connect Front-Channel Logout].
+    ///
+    /// This must not contain a fragment.
+    ///
+    /// [OpenID Connect Front-Channel Logout]: https://openid.net/specs/openid-connect-frontchannel-1_0.html
+    pub frontchannel_logout_uri: Option<Url>,
+
+    /// Whether the client requires the `iss` and `sid` query parameters to be
+    /// added to the `frontchannel_logout_uri`.
+    ///
+    /// Defaults to `false`.
+    pub frontchannel_logout_session_required: Option<bool>,
+
+    /// Whether the client always uses [DPoP] to get sender-constrained access
+    /// tokens.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [DPoP]: https://www.rfc-editor.org/rfc/rfc9449
+    pub dpop_bound_access_tokens: Option<bool>,
+
+    /// Expected subject distinguished name of the certificate the client uses
+    /// for [mutual-TLS client authentication], in the format of [RFC 4514].
+    ///
+    /// If `token_endpoint_auth_method` is
+    /// [`OAuthClientAuthenticationMethod::TlsClientAuth`], exactly one of this
+    /// field or `tls_client_auth_san_dns` is required.
+    ///
+    /// [mutual-TLS client authentication]: https://www.rfc-editor.org/rfc/rfc8705#section-2.1.2
+    /// [RFC 4514]: https://www.rfc-editor.org/rfc/rfc4514
+    pub tls_client_auth_subject_dn: Option<String>,
+
+    /// Expected DNS name in the subject alternative names of the certificate
+    /// the client uses for [mutual-TLS client authentication].
+    ///
+    /// If `token_endpoint_auth_method` is
+    /// [`OAuthClientAuthenticationMethod::TlsClientAuth`], exactly one of this
+    /// field or `tls_client_auth_subject_dn` is required.
+    ///
+    /// [mutual-TLS client authentication]: https://www.rfc-editor.org/rfc/rfc8705#section-2.1.2
+    pub tls_client_auth_san_dns: Option<String>,
+
+    /// The mode the client wants to use to receive the result of a
+    /// [client-initiated backchannel authentication] request.
+    ///
+    /// Required if `grant_types` contains
+    /// [`GrantType::ClientInitiatedBackchannelAuthentication`].
+    ///
+    /// [client-initiated backchannel authentication]: https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html
+    pub backchannel_token_delivery_mode: Option<BackchannelTokenDeliveryMode>,
+
+    /// URL where the provider notifies the client about the result of a
+    /// client-initiated backchannel authentication request.
+    ///
+    /// Required if `backchannel_token_delivery_mode` is `ping` or `push`. Must
+    /// use the `https` scheme.
+    pub backchannel_client_notification_endpoint: Option<Url>,
 }
 
 impl ClientMetadata {
@@ -497,13 +604,44 @@ impl ClientMetadata {
             ));
         }
 
-        if *self.token_endpoint_auth_method() == OAuthClientAuthenticationMethod::PrivateKeyJwt
-            && self.jwks_uri.is_none()
+        if let Some(url) = self
+            .backchannel_logout_uri
+            .as_ref()
+            .filter(|url| url.fragment().is_some())
+        {
+            return Err(ClientMetadataVerificationError::UrlWithFragment(
+                "backchannel_logout_uri",
+                url.clone(),
+            ));
+        }
+
+        if let Some(url) = self
+            .frontchannel_logout_uri
+            .as_ref()
+            .filter(|url| url.fragment().is_some())
+        {
+            return Err(ClientMetadataVerificationError::UrlWithFragment(
+                "frontchannel_logout_uri",
+                url.clone(),
+            ));
+        }
+
+        if matches!(
+            self.token_endpoint_auth_method(),
+            OAuthClientAuthenticationMethod::PrivateKeyJwt
+                | OAuthClientAuthenticationMethod::SelfSignedTlsClientAuth
+        ) && self.jwks_uri.is_none()
             && self.jwks.is_none()
         {
             return Err(ClientMetadataVerificationError::MissingJwksForTokenMethod);
         }
 
+        if *self.token_endpoint_auth_method() == OAuthClientAuthenticationMethod::TlsClientAuth
+            && self.tls_client_auth_subject_dn.is_some() == self.tls_client_auth_san_dns.is_some()
+        {
+            return Err(ClientMetadataVerificationError::InvalidTlsClientAuthSubject);
+        }
+
         if let Some(alg) = &self.token_endpoint_auth_signing_alg {
             if *alg == JsonWebSignatureAlg::None {
                 return Err(ClientMetadataVerificationError::UnauthorizedSigningAlgNone(
@@ -561,6 +699,41 @@ impl ClientMetadata {
             )?;
         }
 
+        if self.authorization_encrypted_response_enc.is_some() {
+            self.authorization_encrypted_response_alg.as_ref().ok_or(
+                ClientMetadataVerificationError::MissingEncryptionAlg("authorization"),
+            )?;
+        }
+
+        if grant_types.contains(&GrantType::ClientInitiatedBackchannelAuthentication)
+            && self.backchannel_token_delivery_mode.is_none()
+        {
+            return Err(ClientMetadataVerificationError::MissingBackchannelTokenDeliveryMode);
+        }
+
+        if let Some(url) = &self.backchannel_client_notification_endpoint {
+            if url.scheme() != "https" {
+                return Err(ClientMetadataVerificationError::UrlNonHttpsScheme(
+                    "backchannel_client_notification_endpoint",
+                    url.clone(),
+                ));
+            }
+
+            if url.fragment().is_some() {
+                return Err(ClientMetadataVerificationError::UrlWithFragment(
+                    "backchannel_client_notification_endpoint",
+                    url.clone(),
+                ));
+            }
+        } else if matches!(
+            self.backchannel_token_delivery_mode,
+            Some(BackchannelTokenDeliveryMode::Ping | BackchannelTokenDeliveryMode::Push)
+        ) {
+            return Err(
+                ClientMetadataVerificationError::MissingBackchannelClientNotificationEndpoint,
+            );
+        }
+
         Ok(VerifiedClientMetadata { inner: self })
     }
 
@@ -782,6 +955,29 @@ impl ClientMetadata {
             .unwrap_or_default()
     }
 
+    /// Whether the client requires the `iss` and `sid` query parameters to be
+    /// added to its [front-channel logout URI].
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [front-channel logout URI]: https://openid.net/specs/openid-connect-frontchannel-1_0.html
+    #[must_use]
+    pub fn frontchannel_logout_session_required(&self) -> bool {
+        self.frontchannel_logout_session_required
+            .unwrap_or_default()
+    }
+
+    /// Whether the client always uses [DPoP] to get sender-constrained access
+    /// tokens.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [DPoP]: https://www.rfc-editor.org/rfc/rfc9449
+    #[must_use]
+    pub fn dpop_bound_access_tokens(&self) -> bool {
+        self.dpop_bound_access_tokens.unwrap_or_default()
+    }
+
     /// [JWE] `alg` and `enc` algorithms for encrypting responses of the
     /// [introspection endpoint].
     ///
@@ -806,6 +1002,44 @@ impl ClientMetadata {
                 )
             })
     }
+
+    /// [JWS] `alg` algorithm for signing [JWT-secured authorization responses].
+    ///
+    /// Defaults to [`DEFAULT_SIGNING_ALGORITHM`].
+    ///
+    /// [JWS]: http://tools.ietf.org/html/draft-ietf-jose-json-web-signature
+    /// [JWT-secured authorization responses]: https://openid.net/specs/oauth-v2-jarm.html
+    #[must_use]
+    pub fn authorization_signed_response_alg(&self) -> &JsonWebSignatureAlg {
+        self.authorization_signed_response_alg
+            .as_ref()
+            .unwrap_or(DEFAULT_SIGNING_ALGORITHM)
+    }
+
+    /// [JWE] `alg` and `enc` algorithms for encrypting [JWT-secured
+    /// authorization responses].
+    ///
+    /// Always returns `Some` if `authorization_encrypted_response_alg` is
+    /// provided, using the default of [`DEFAULT_ENCRYPTION_ENC_ALGORITHM`] for
+    /// the `enc` value if needed.
+    ///
+    /// [JWE]: http://tools.ietf.org/html/draft-ietf-jose-json-web-encryption
+    /// [JWT-secured authorization responses]: https://openid.net/specs/oauth-v2-jarm.html
+    #[must_use]
+    pub fn authorization_encrypted_response(
+        &self,
+    ) -> Option<(&JsonWebEncryptionAlg, &JsonWebEncryptionEnc)> {
+        self.authorization_encrypted_response_alg
+            .as_ref()
+            .map(|alg| {
+                (
+                    alg,
+                    self.authorization_encrypted_response_enc
+                        .as_ref()
+                        .unwrap_or(DEFAULT_ENCRYPTION_ENC_ALGORITHM),
+                )
+            })
+    }
 }
 
 /// The verified client metadata.
@@ -871,6 +1105,10 @@ pub enum ClientMetadataVerificationError {
     #[error("{0}'s URL doesn't use a https scheme: {1}")]
     UrlNonHttpsScheme(&'static str, Url),
 
+    /// The URL of the given field has a fragment, which is not allowed.
+    #[error("{0}'s URL has a fragment: {1}")]
+    UrlWithFragment(&'static str, Url),
+
     /// No JWK Set was provided but one is required for the token auth method.
     #[error("missing JWK Set for token auth method")]
     MissingJwksForTokenMethod,
@@ -893,6 +1131,21 @@ pub enum ClientMetadataVerificationError {
     /// The given encryption field has an `enc` value but not `alg` value.
     #[error("{0} missing encryption alg value")]
     MissingEncryptionAlg(&'static str),
+
+    /// The `tls_client_auth` authentication method is used, but not exactly
+    /// one of the expected certificate subject fields is set.
+    #[error("exactly one of tls_client_auth_subject_dn or tls_client_auth_san_dns is required")]
+    InvalidTlsClientAuthSubject,
+
+    /// The client-initiated backchannel authentication grant type is used, but
+    /// no token delivery mode is set.
+    #[error("missing backchannel_token_delivery_mode for the CIBA grant type")]
+    MissingBackchannelTokenDeliveryMode,
+
+    /// The `ping` or `push` token delivery mode is used, but no client
+    /// notification endpoint is set.
+    #[error("missing backchannel_client_notification_endpoint for the ping or push modes")]
+    MissingBackchannelClientNotificationEndpoint,
 }
 
 /// The issuer response to dynamic client registration.
@@ -918,6 +1171,16 @@ pub struct ClientRegistrationResponse {
     #[serde(default)]
     #[serde_as(as = "Option<TimestampSeconds<i64>>")]
     pub client_secret_expires_at: Option<DateTime<Utc>>,
+
+    /// An access token that can be used at the client configuration endpoint
+    /// to read, update or delete the registration, as per RFC 7592.
+    #[serde(default)]
+    pub registration_access_token: Option<String>,
+
+    /// The URL of the client configuration endpoint of this client, as per RFC
+    /// 7592.
+    #[serde(default)]
+    pub registration_client_uri: Option<Url>,
 }
 
 #[cfg(test)]
@@ -931,7 +1194,9 @@ mod tests {
     use url::Url;
 
     use super::{ClientMetadata, ClientMetadataVerificationError};
-    use crate::{requests::GrantType, response_type::ResponseType};
+    use crate::{
+        oidc::BackchannelTokenDeliveryMode, requests::GrantType, response_type::ResponseType,
+    };
 
     fn valid_client_metadata() -> ClientMetadata {
         ClientMetadata {
@@ -1228,6 +1493,85 @@ mod tests {
         metadata.validate().unwrap();
     }
 
+    #[test]
+    fn validate_backchannel_logout_uri() {
+        let mut metadata = valid_client_metadata();
+
+        // Err - URL with a fragment
+        let logout_uri = Url::parse("https://localhost/logout#fragment").unwrap();
+        metadata.backchannel_logout_uri = Some(logout_uri.clone());
+        let (field, url) = assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::UrlWithFragment(field, url)) => (field, url)
+        );
+        assert_eq!(field, "backchannel_logout_uri");
+        assert_eq!(url, logout_uri);
+
+        // Ok - URL without a fragment
+        metadata.backchannel_logout_uri = Some(Url::parse("https://localhost/logout").unwrap());
+        metadata.validate().unwrap();
+    }
+
+    #[test]
+    fn validate_backchannel_authentication() {
+        let mut metadata = valid_client_metadata();
+        metadata.grant_types = Some(vec![
+            GrantType::AuthorizationCode,
+            GrantType::ClientInitiatedBackchannelAuthentication,
+        ]);
+
+        // Err - No token delivery mode
+        assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::MissingBackchannelTokenDeliveryMode)
+        );
+
+        // Ok - Poll mode
+        metadata.backchannel_token_delivery_mode = Some(BackchannelTokenDeliveryMode::Poll);
+        metadata.clone().validate().unwrap();
+
+        // Err - Ping mode without a notification endpoint
+        metadata.backchannel_token_delivery_mode = Some(BackchannelTokenDeliveryMode::Ping);
+        assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::MissingBackchannelClientNotificationEndpoint)
+        );
+
+        // Err - Notification endpoint without https
+        let endpoint = Url::parse("http://localhost/ciba").unwrap();
+        metadata.backchannel_client_notification_endpoint = Some(endpoint.clone());
+        let (field, url) = assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::UrlNonHttpsScheme(field, url)) => (field, url)
+        );
+        assert_eq!(field, "backchannel_client_notification_endpoint");
+        assert_eq!(url, endpoint);
+
+        // Ok - Ping mode with a notification endpoint
+        metadata.backchannel_client_notification_endpoint =
+            Some(Url::parse("https://localhost/ciba").unwrap());
+        metadata.validate().unwrap();
+    }
+
+    #[test]
+    fn validate_frontchannel_logout_uri() {
+        let mut metadata = valid_client_metadata();
+
+        // Err - URL with a fragment
+        let logout_uri = Url::parse("https://localhost/logout#fragment").unwrap();
+        metadata.frontchannel_logout_uri = Some(logout_uri.clone());
+        let (field, url) = assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::UrlWithFragment(field, url)) => (field, url)
+        );
+        assert_eq!(field, "frontchannel_logout_uri");
+        assert_eq!(url, logout_uri);
+
+        // Ok - URL without a fragment
+        metadata.frontchannel_logout_uri = Some(Url::parse("https://localhost/logout").unwrap());
+        metadata.validate().unwrap();
+    }
+
     #[test]
     fn validate_token_endpoint_auth_method() {
         let mut metadata = valid_client_metadata();
@@ -1281,6 +1625,43 @@ mod tests {
 
         // Ok - Has token_endpoint_auth_signing_alg
         metadata.token_endpoint_auth_signing_alg = Some(JsonWebSignatureAlg::Rs256);
+        metadata.clone().validate().unwrap();
+
+        // self_signed_tls_client_auth
+        metadata.token_endpoint_auth_method =
+            Some(OAuthClientAuthenticationMethod::SelfSignedTlsClientAuth);
+        metadata.token_endpoint_auth_signing_alg = None;
+
+        // Err - No JWKS
+        assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::MissingJwksForTokenMethod)
+        );
+
+        // Ok - jwks
+        metadata.jwks = Some(jwks());
+        metadata.clone().validate().unwrap();
+
+        // tls_client_auth
+        metadata.token_endpoint_auth_method = Some(OAuthClientAuthenticationMethod::TlsClientAuth);
+        metadata.jwks = None;
+
+        // Err - No expected subject
+        assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::InvalidTlsClientAuthSubject)
+        );
+
+        // Err - Both expected subjects
+        metadata.tls_client_auth_subject_dn = Some("CN=client,O=Example".to_owned());
+        metadata.tls_client_auth_san_dns = Some("client.example.com".to_owned());
+        assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::InvalidTlsClientAuthSubject)
+        );
+
+        // Ok - Only the subject DN
+        metadata.tls_client_auth_san_dns = None;
         metadata.validate().unwrap();
     }
 
@@ -1421,4 +1802,21 @@ mod tests {
         metadata.introspection_encrypted_response_alg = Some(JsonWebEncryptionAlg::RsaOaep);
         metadata.validate().unwrap();
     }
+
+    #[test]
+    fn validate_authorization_encrypted_response() {
+        let mut metadata = valid_client_metadata();
+        metadata.authorization_encrypted_response_enc = Some(JsonWebEncryptionEnc::A128CbcHs256);
+
+        // Err - No authorization_encrypted_response_alg
+        let field = assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::MissingEncryptionAlg(field)) => field
+        );
+        assert_eq!(field, "authorization");
+
+        // Ok - Has authorization_encrypted_response_alg
+        metadata.authorization_encrypted_response_alg = Some(JsonWebEncryptionAlg::RsaOaep);
+        metadata.validate().unwrap();
+    }
 }
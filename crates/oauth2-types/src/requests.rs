@@ -8,15 +8,22 @@
 //!
 //! [OAuth 2.0]: https://oauth.net/2/
 
-use std::{collections::HashSet, fmt, hash::Hash, num::NonZeroU32};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    hash::Hash,
+    num::NonZeroU32,
+};
 
 use chrono::{DateTime, Duration, Utc};
 use language_tags::LanguageTag;
 use mas_iana::oauth::{OAuthAccessTokenType, OAuthTokenTypeHint};
 use serde::{Deserialize, Serialize};
 use serde_with::{
-    DeserializeFromStr, DisplayFromStr, DurationSeconds, SerializeDisplay, StringWithSeparator,
-    TimestampSeconds, formats::SpaceSeparator, serde_as, skip_serializing_none,
+    DeserializeFromStr, DisplayFromStr, DurationSeconds, OneOrMany, SerializeDisplay,
+    StringWithSeparator, TimestampSeconds,
+    formats::{PreferOne, SpaceSeparator},
+    serde_as, skip_serializing_none,
 };
 use url::Url;
 
@@ -50,6 +57,31 @@ pub enum ResponseMode {
     /// Defined in [OAuth 2.0 Form Post Response Mode](https://openid.net/specs/oauth-v2-form-post-response-mode-1_0.html).
     FormPost,
 
+    /// Authorization Response parameters are wrapped in a JWT, using the
+    /// default encoding of the response type: `query.jwt` for the `code`
+    /// response type, `fragment.jwt` otherwise.
+    ///
+    /// Defined in [JWT Secured Authorization Response Mode for OAuth 2.0](https://openid.net/specs/oauth-v2-jarm.html).
+    Jwt,
+
+    /// Authorization Response parameters are wrapped in a JWT, which is
+    /// encoded in the query string added to the `redirect_uri`.
+    ///
+    /// Defined in [JWT Secured Authorization Response Mode for OAuth 2.0](https://openid.net/specs/oauth-v2-jarm.html).
+    QueryJwt,
+
+    /// Authorization Response parameters are wrapped in a JWT, which is
+    /// encoded in the fragment added to the `redirect_uri`.
+    ///
+    /// Defined in [JWT Secured Authorization Response Mode for OAuth 2.0](https://openid.net/specs/oauth-v2-jarm.html).
+    FragmentJwt,
+
+    /// Authorization Response parameters are wrapped in a JWT, which is
+    /// transmitted as an HTML form value auto-submitted in the User Agent.
+    ///
+    /// Defined in [JWT Secured Authorization Response Mode for OAuth 2.0](https://openid.net/specs/oauth-v2-jarm.html).
+    FormPostJwt,
+
     /// An unknown value.
     Unknown(String),
 }
@@ -60,6 +92,10 @@ impl core::fmt::Display for ResponseMode {
             ResponseMode::Query => f.write_str("query"),
             ResponseMode::Fragment => f.write_str("fragment"),
             ResponseMode::FormPost => f.write_str("form_post"),
+            ResponseMode::Jwt => f.write_str("jwt"),
+            ResponseMode::QueryJwt => f.write_str("query.jwt"),
+            ResponseMode::FragmentJwt => f.write_str("fragment.jwt"),
+            ResponseMode::FormPostJwt => f.write_str("form_post.jwt"),
             ResponseMode::Unknown(s) => f.write_str(s),
         }
     }
@@ -73,6 +109,10 @@ impl core::str::FromStr for ResponseMode {
             "query" => Ok(ResponseMode::Query),
             "fragment" => Ok(ResponseMode::Fragment),
             "form_post" => Ok(ResponseMode::FormPost),
+            "jwt" => Ok(ResponseMode::Jwt),
+            "query.jwt" => Ok(ResponseMode::QueryJwt),
+            "fragment.jwt" => Ok(ResponseMode::FragmentJwt),
+            "form_post.jwt" => Ok(ResponseMode::FormPostJwt),
             s => Ok(ResponseMode::Unknown(s.to_owned())),
         }
     }
@@ -208,6 +248,182 @@ impl core::str::FromStr for Prompt {
     }
 }
 
+/// How an individual claim is requested in the [`claims` parameter].
+///
+/// [`claims` parameter]: https://openid.net/specs/openid-connect-core-1_0.html#IndividualClaimsRequests
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct IndividualClaimRequest {
+    /// Whether the claim is an Essential Claim, as opposed to a Voluntary
+    /// Claim.
+    pub essential: Option<bool>,
+
+    /// The value the claim is requested to have.
+    pub value: Option<serde_json::Value>,
+
+    /// A set of values the claim is requested to have one of.
+    pub values: Option<Vec<serde_json::Value>>,
+}
+
+impl IndividualClaimRequest {
+    /// Whether the claim is an Essential Claim.
+    #[must_use]
+    pub fn is_essential(&self) -> bool {
+        self.essential.unwrap_or(false)
+    }
+}
+
+/// Individual claims requested in the ID Token or from the `UserInfo` Endpoint.
+///
+/// The claim names map to how they are requested. A `null` value means the
+/// claim is requested as a Voluntary Claim, without any other constraint.
+pub type RequestedClaims = BTreeMap<String, Option<IndividualClaimRequest>>;
+
+/// The value of the [`claims` parameter] of an authorization request.
+///
+/// In authorization requests, it is passed as a JSON-encoded string.
+///
+/// [`claims` parameter]: https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ClaimsParameter {
+    /// Claims requested to be returned from the `UserInfo` Endpoint.
+    pub userinfo: Option<RequestedClaims>,
+
+    /// Claims requested to be returned in the ID Token.
+    pub id_token: Option<RequestedClaims>,
+}
+
+impl ClaimsParameter {
+    /// Get the names of all the requested claims, with whether they are
+    /// essential either in the ID Token or from the `UserInfo` Endpoint.
+    #[must_use]
+    pub fn all_claims(&self) -> BTreeMap<&str, bool> {
+        let mut claims = BTreeMap::new();
+        for requested in [&self.userinfo, &self.id_token].into_iter().flatten() {
+            for (name, request) in requested {
+                let essential = request
+                    .as_ref()
+                    .is_some_and(IndividualClaimRequest::is_essential);
+                let entry = claims.entry(name.as_str()).or_insert(false);
+                *entry |= essential;
+            }
+        }
+        claims
+    }
+}
+
+impl core::str::FromStr for ClaimsParameter {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+impl fmt::Display for ClaimsParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&s)
+    }
+}
+
+/// A single entry of the [`authorization_details` parameter], describing
+/// fine-grained authorization data for a specific kind of resource.
+///
+/// [`authorization_details` parameter]: https://www.rfc-editor.org/rfc/rfc9396#section-2
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationDetail {
+    /// The type of authorization data, which determines the other fields
+    /// allowed in this entry.
+    pub r#type: String,
+
+    /// The locations of the resource or resource server the authorization
+    /// applies to.
+    pub locations: Option<Vec<String>>,
+
+    /// The kinds of actions to be taken at the resource.
+    pub actions: Option<Vec<String>>,
+
+    /// The kinds of data being requested from the resource.
+    pub datatypes: Option<Vec<String>>,
+
+    /// A specific resource available at the API.
+    pub identifier: Option<String>,
+
+    /// The types or levels of privilege being requested at the resource.
+    pub privileges: Option<Vec<String>>,
+
+    /// Other fields, specific to the type of authorization data.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+/// The value of the [`authorization_details` parameter] of authorization and
+/// token requests.
+///
+/// In requests, it is passed as a JSON-encoded string. In responses, it is a
+/// plain JSON array.
+///
+/// [`authorization_details` parameter]: https://www.rfc-editor.org/rfc/rfc9396#section-2
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct AuthorizationDetails(Vec<AuthorizationDetail>);
+
+impl AuthorizationDetails {
+    /// Returns `true` if there are no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns `true` if every entry of `other` is also in these
+    /// authorization details.
+    #[must_use]
+    pub fn contains_all(&self, other: &Self) -> bool {
+        other.iter().all(|detail| self.0.contains(detail))
+    }
+}
+
+impl std::ops::Deref for AuthorizationDetails {
+    type Target = [AuthorizationDetail];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromIterator<AuthorizationDetail> for AuthorizationDetails {
+    fn from_iter<T: IntoIterator<Item = AuthorizationDetail>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for AuthorizationDetails {
+    type Item = AuthorizationDetail;
+    type IntoIter = std::vec::IntoIter<AuthorizationDetail>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl core::str::FromStr for AuthorizationDetails {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+impl fmt::Display for AuthorizationDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&s)
+    }
+}
+
 /// The body of a request to the [Authorization Endpoint].
 ///
 /// [Authorization Endpoint]: https://www.rfc-editor.org/rfc/rfc6749.html#section-3.1
@@ -288,6 +504,25 @@ pub struct AuthorizationRequest {
     #[serde(default)]
     pub acr_values: Option<HashSet<String>>,
 
+    /// Individual claims requested to be returned in the ID Token or from the
+    /// `UserInfo` Endpoint.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub claims: Option<ClaimsParameter>,
+
+    /// Fine-grained authorization data requested, as per [RFC 9396].
+    ///
+    /// [RFC 9396]: https://www.rfc-editor.org/rfc/rfc9396
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub authorization_details: Option<AuthorizationDetails>,
+
+    /// The URI of the resource server where the client intends to use the
+    /// requested token, as per [RFC 8707].
+    ///
+    /// [RFC 8707]: https://www.rfc-editor.org/rfc/rfc8707
+    pub resource: Option<Url>,
+
     /// A JWT that contains the request's parameter values, called a [Request
     /// Object].
     ///
@@ -327,6 +562,9 @@ impl AuthorizationRequest {
             id_token_hint: None,
             login_hint: None,
             acr_values: None,
+            claims: None,
+            authorization_details: None,
+            resource: None,
             request: None,
             request_uri: None,
             registration: None,
@@ -347,6 +585,9 @@ impl fmt::Debug for AuthorizationRequest {
             .field("ui_locales", &self.ui_locales)
             .field("login_hint", &self.login_hint)
             .field("acr_values", &self.acr_values)
+            .field("claims", &self.claims)
+            .field("authorization_details", &self.authorization_details)
+            .field("resource", &self.resource)
             .field("request", &self.request)
             .field("request_uri", &self.request_uri)
             .field("registration", &self.registration)
@@ -395,6 +636,17 @@ impl fmt::Debug for AuthorizationResponse {
 pub struct DeviceAuthorizationRequest {
     /// The scope of the access request.
     pub scope: Option<Scope>,
+
+    /// A JWT that contains the request's parameter values, called a [Request
+    /// Object].
+    ///
+    /// [Request Object]: https://www.rfc-editor.org/rfc/rfc9101
+    pub request: Option<String>,
+
+    /// A URI referencing a [Request Object].
+    ///
+    /// [Request Object]: https://www.rfc-editor.org/rfc/rfc9101
+    pub request_uri: Option<Url>,
 }
 
 /// The default value of the `interval` between polling requests, if it is not
@@ -459,6 +711,83 @@ impl fmt::Debug for DeviceAuthorizationResponse {
     }
 }
 
+/// A request to the [Backchannel Authentication Endpoint].
+///
+/// [Backchannel Authentication Endpoint]: https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#auth_request
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BackchannelAuthenticationRequest {
+    /// The scope of the access request.
+    ///
+    /// Must contain the `openid` scope.
+    pub scope: Scope,
+
+    /// A bearer token the provider uses to authenticate its notification to
+    /// the client, in the `ping` and `push` modes.
+    pub client_notification_token: Option<String>,
+
+    /// A hint identifying the end-user for whom authentication is being
+    /// requested.
+    pub login_hint: Option<String>,
+
+    /// A token containing information identifying the end-user for whom
+    /// authentication is being requested.
+    pub login_hint_token: Option<String>,
+
+    /// An ID Token previously issued to the client, identifying the end-user
+    /// for whom authentication is being requested.
+    pub id_token_hint: Option<String>,
+
+    /// A human-readable message displayed on both the consumption device and
+    /// the authentication device, to tie them together.
+    pub binding_message: Option<String>,
+
+    /// The requested lifetime of the `auth_req_id`, in seconds.
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub requested_expiry: Option<NonZeroU32>,
+}
+
+impl fmt::Debug for BackchannelAuthenticationRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackchannelAuthenticationRequest")
+            .field("scope", &self.scope)
+            .field("binding_message", &self.binding_message)
+            .field("requested_expiry", &self.requested_expiry)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A successful response from the [Backchannel Authentication Endpoint].
+///
+/// [Backchannel Authentication Endpoint]: https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#auth_response
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BackchannelAuthenticationResponse {
+    /// The identifier of the authentication request.
+    pub auth_req_id: String,
+
+    /// The lifetime of the `auth_req_id`.
+    #[serde_as(as = "DurationSeconds<i64>")]
+    pub expires_in: Duration,
+
+    /// The minimum amount of time in seconds that the client should wait
+    /// between polling requests to the token endpoint.
+    #[serde_as(as = "Option<DurationSeconds<i64>>")]
+    pub interval: Option<Duration>,
+}
+
+impl fmt::Debug for BackchannelAuthenticationResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackchannelAuthenticationResponse")
+            .field("expires_in", &self.expires_in)
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
 /// A request to the [Token Endpoint] for the [Authorization Code] grant type.
 ///
 /// [Token Endpoint]: https://www.rfc-editor.org/rfc/rfc6749#section-3.2
@@ -480,12 +809,19 @@ pub struct AuthorizationCodeGrant {
     /// authorization endpoint.
     // TODO: move this somehow in the pkce module
     pub code_verifier: Option<String>,
+
+    /// The URI of the resource server where the client intends to use the
+    /// requested token, as per [RFC 8707].
+    ///
+    /// [RFC 8707]: https://www.rfc-editor.org/rfc/rfc8707
+    pub resource: Option<Url>,
 }
 
 impl fmt::Debug for AuthorizationCodeGrant {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("AuthorizationCodeGrant")
             .field("redirect_uri", &self.redirect_uri)
+            .field("resource", &self.resource)
             .finish_non_exhaustive()
     }
 }
@@ -506,12 +842,19 @@ pub struct RefreshTokenGrant {
     /// the resource owner, and if omitted is treated as equal to the scope
     /// originally granted by the resource owner.
     pub scope: Option<Scope>,
+
+    /// The URI of the resource server where the client intends to use the
+    /// requested token, as per [RFC 8707].
+    ///
+    /// [RFC 8707]: https://www.rfc-editor.org/rfc/rfc8707
+    pub resource: Option<Url>,
 }
 
 impl fmt::Debug for RefreshTokenGrant {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RefreshTokenGrant")
             .field("scope", &self.scope)
+            .field("resource", &self.resource)
             .finish_non_exhaustive()
     }
 }
@@ -520,10 +863,25 @@ impl fmt::Debug for RefreshTokenGrant {
 ///
 /// [Token Endpoint]: https://www.rfc-editor.org/rfc/rfc6749#section-3.2
 /// [Client Credentials]: https://www.rfc-editor.org/rfc/rfc6749#section-4.4
+#[serde_as]
+#[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ClientCredentialsGrant {
     /// The scope of the access request.
     pub scope: Option<Scope>,
+
+    /// Fine-grained authorization data requested, as per [RFC 9396].
+    ///
+    /// [RFC 9396]: https://www.rfc-editor.org/rfc/rfc9396
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub authorization_details: Option<AuthorizationDetails>,
+
+    /// The URI of the resource server where the client intends to use the
+    /// requested token, as per [RFC 8707].
+    ///
+    /// [RFC 8707]: https://www.rfc-editor.org/rfc/rfc8707
+    pub resource: Option<Url>,
 }
 
 /// A request to the [Token Endpoint] for the [Device Authorization] grant type.
@@ -542,6 +900,168 @@ impl fmt::Debug for DeviceCodeGrant {
     }
 }
 
+/// A request to the [Token Endpoint] for the [Client-Initiated Backchannel
+/// Authentication] grant type.
+///
+/// [Token Endpoint]: https://www.rfc-editor.org/rfc/rfc6749#section-3.2
+/// [Client-Initiated Backchannel Authentication]: https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#token_request
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BackchannelAuthenticationGrant {
+    /// The identifier of the authentication request, from the backchannel
+    /// authentication response.
+    pub auth_req_id: String,
+}
+
+impl fmt::Debug for BackchannelAuthenticationGrant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackchannelAuthenticationGrant")
+            .finish_non_exhaustive()
+    }
+}
+
+/// A request to the [Token Endpoint] using a [JWT as an authorization grant].
+///
+/// [Token Endpoint]: https://www.rfc-editor.org/rfc/rfc6749#section-3.2
+/// [JWT as an authorization grant]: https://www.rfc-editor.org/rfc/rfc7523#section-2.1
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct JwtBearerGrant {
+    /// The signed JWT used as an authorization grant.
+    pub assertion: String,
+
+    /// The scope of the access request.
+    pub scope: Option<Scope>,
+}
+
+impl fmt::Debug for JwtBearerGrant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtBearerGrant")
+            .field("scope", &self.scope)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A request to the [Token Endpoint] for the [Token Exchange] grant type.
+///
+/// [Token Endpoint]: https://www.rfc-editor.org/rfc/rfc6749#section-3.2
+/// [Token Exchange]: https://www.rfc-editor.org/rfc/rfc8693
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TokenExchangeGrant {
+    /// The token that represents the identity of the party on behalf of whom
+    /// the request is being made.
+    pub subject_token: String,
+
+    /// The type of the `subject_token`.
+    pub subject_token_type: TokenTypeIdentifier,
+
+    /// The token that represents the identity of the acting party.
+    pub actor_token: Option<String>,
+
+    /// The type of the `actor_token`.
+    ///
+    /// Required if `actor_token` is present.
+    pub actor_token_type: Option<TokenTypeIdentifier>,
+
+    /// The type of the requested security token.
+    pub requested_token_type: Option<TokenTypeIdentifier>,
+
+    /// The logical name of the target service where the client intends to use
+    /// the requested token.
+    pub audience: Option<String>,
+
+    /// The URI of the target service where the client intends to use the
+    /// requested token.
+    pub resource: Option<Url>,
+
+    /// The scope of the requested token.
+    ///
+    /// It must not include any scope not granted to the subject token, and if
+    /// omitted is treated as equal to the scope of the subject token.
+    pub scope: Option<Scope>,
+}
+
+impl fmt::Debug for TokenExchangeGrant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenExchangeGrant")
+            .field("subject_token_type", &self.subject_token_type)
+            .field("actor_token_type", &self.actor_token_type)
+            .field("requested_token_type", &self.requested_token_type)
+            .field("audience", &self.audience)
+            .field("resource", &self.resource)
+            .field("scope", &self.scope)
+            .finish_non_exhaustive()
+    }
+}
+
+/// All possible values for the [token type identifiers] used in token
+/// exchanges.
+///
+/// [token type identifiers]: https://www.rfc-editor.org/rfc/rfc8693#section-3
+#[derive(
+    Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, SerializeDisplay, DeserializeFromStr,
+)]
+pub enum TokenTypeIdentifier {
+    /// `urn:ietf:params:oauth:token-type:access_token`
+    AccessToken,
+
+    /// `urn:ietf:params:oauth:token-type:refresh_token`
+    RefreshToken,
+
+    /// `urn:ietf:params:oauth:token-type:id_token`
+    IdToken,
+
+    /// `urn:ietf:params:oauth:token-type:saml1`
+    Saml1,
+
+    /// `urn:ietf:params:oauth:token-type:saml2`
+    Saml2,
+
+    /// `urn:ietf:params:oauth:token-type:jwt`
+    Jwt,
+
+    /// An unknown value.
+    Unknown(String),
+}
+
+impl core::fmt::Display for TokenTypeIdentifier {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TokenTypeIdentifier::AccessToken => {
+                f.write_str("urn:ietf:params:oauth:token-type:access_token")
+            }
+            TokenTypeIdentifier::RefreshToken => {
+                f.write_str("urn:ietf:params:oauth:token-type:refresh_token")
+            }
+            TokenTypeIdentifier::IdToken => {
+                f.write_str("urn:ietf:params:oauth:token-type:id_token")
+            }
+            TokenTypeIdentifier::Saml1 => f.write_str("urn:ietf:params:oauth:token-type:saml1"),
+            TokenTypeIdentifier::Saml2 => f.write_str("urn:ietf:params:oauth:token-type:saml2"),
+            TokenTypeIdentifier::Jwt => f.write_str("urn:ietf:params:oauth:token-type:jwt"),
+            TokenTypeIdentifier::Unknown(s) => f.write_str(s),
+        }
+    }
+}
+
+impl core::str::FromStr for TokenTypeIdentifier {
+    type Err = core::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "urn:ietf:params:oauth:token-type:access_token" => Ok(TokenTypeIdentifier::AccessToken),
+            "urn:ietf:params:oauth:token-type:refresh_token" => {
+                Ok(TokenTypeIdentifier::RefreshToken)
+            }
+            "urn:ietf:params:oauth:token-type:id_token" => Ok(TokenTypeIdentifier::IdToken),
+            "urn:ietf:params:oauth:token-type:saml1" => Ok(TokenTypeIdentifier::Saml1),
+            "urn:ietf:params:oauth:token-type:saml2" => Ok(TokenTypeIdentifier::Saml2),
+            "urn:ietf:params:oauth:token-type:jwt" => Ok(TokenTypeIdentifier::Jwt),
+            s => Ok(TokenTypeIdentifier::Unknown(s.to_owned())),
+        }
+    }
+}
+
 /// All possible values for the `grant_type` parameter.
 #[derive(
     Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, SerializeDisplay, DeserializeFromStr,
@@ -571,6 +1091,9 @@ pub enum GrantType {
     /// [`urn:openid:params:grant-type:ciba`](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html)
     ClientInitiatedBackchannelAuthentication,
 
+    /// [`urn:ietf:params:oauth:grant-type:token-exchange`](https://www.rfc-editor.org/rfc/rfc8693)
+    TokenExchange,
+
     /// An unknown value.
     Unknown(String),
 }
@@ -588,6 +1111,9 @@ impl core::fmt::Display for GrantType {
             GrantType::ClientInitiatedBackchannelAuthentication => {
                 f.write_str("urn:openid:params:grant-type:ciba")
             }
+            GrantType::TokenExchange => {
+                f.write_str("urn:ietf:params:oauth:grant-type:token-exchange")
+            }
             GrantType::Unknown(s) => f.write_str(s),
         }
     }
@@ -608,6 +1134,7 @@ impl core::str::FromStr for GrantType {
             "urn:openid:params:grant-type:ciba" => {
                 Ok(GrantType::ClientInitiatedBackchannelAuthentication)
             }
+            "urn:ietf:params:oauth:grant-type:token-exchange" => Ok(GrantType::TokenExchange),
             s => Ok(GrantType::Unknown(s.to_owned())),
         }
     }
@@ -633,6 +1160,18 @@ pub enum AccessTokenRequest {
     #[serde(rename = "urn:ietf:params:oauth:grant-type:device_code")]
     DeviceCode(DeviceCodeGrant),
 
+    /// A request in the Client-Initiated Backchannel Authentication flow.
+    #[serde(rename = "urn:openid:params:grant-type:ciba")]
+    BackchannelAuthentication(BackchannelAuthenticationGrant),
+
+    /// A request to exchange a token for another one.
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:token-exchange")]
+    TokenExchange(TokenExchangeGrant),
+
+    /// A request using a JWT as an authorization grant.
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:jwt-bearer")]
+    JwtBearer(JwtBearerGrant),
+
     /// An unsupported request.
     #[serde(skip_serializing, other)]
     Unsupported,
@@ -647,6 +1186,9 @@ impl AccessTokenRequest {
             Self::RefreshToken(_) => "refresh_token",
             Self::ClientCredentials(_) => "client_credentials",
             Self::DeviceCode(_) => "urn:ietf:params:oauth:grant-type:device_code",
+            Self::BackchannelAuthentication(_) => "urn:openid:params:grant-type:ciba",
+            Self::TokenExchange(_) => "urn:ietf:params:oauth:grant-type:token-exchange",
+            Self::JwtBearer(_) => "urn:ietf:params:oauth:grant-type:jwt-bearer",
             Self::Unsupported => "unsupported",
         }
     }
@@ -678,6 +1220,12 @@ pub struct AccessTokenResponse {
 
     /// The scope of the access token.
     pub scope: Option<Scope>,
+
+    /// The type of the issued token, in response to a token exchange.
+    pub issued_token_type: Option<TokenTypeIdentifier>,
+
+    /// The fine-grained authorization data granted to the access token.
+    pub authorization_details: Option<AuthorizationDetails>,
 }
 
 impl AccessTokenResponse {
@@ -691,6 +1239,8 @@ impl AccessTokenResponse {
             token_type: OAuthAccessTokenType::Bearer,
             expires_in: None,
             scope: None,
+            issued_token_type: None,
+            authorization_details: None,
         }
     }
 
@@ -721,6 +1271,30 @@ impl AccessTokenResponse {
         self.expires_in = Some(expires_in);
         self
     }
+
+    /// Sets the type of the access token of an `AccessTokenResponse`.
+    #[must_use]
+    pub fn with_token_type(mut self, token_type: OAuthAccessTokenType) -> Self {
+        self.token_type = token_type;
+        self
+    }
+
+    /// Sets the type of the issued token of an `AccessTokenResponse`.
+    #[must_use]
+    pub fn with_issued_token_type(mut self, issued_token_type: TokenTypeIdentifier) -> Self {
+        self.issued_token_type = Some(issued_token_type);
+        self
+    }
+
+    /// Adds authorization details to an `AccessTokenResponse`.
+    #[must_use]
+    pub fn with_authorization_details(
+        mut self,
+        authorization_details: AuthorizationDetails,
+    ) -> Self {
+        self.authorization_details = Some(authorization_details);
+        self
+    }
 }
 
 impl fmt::Debug for AccessTokenResponse {
@@ -729,6 +1303,8 @@ impl fmt::Debug for AccessTokenResponse {
             .field("token_type", &self.token_type)
             .field("expires_in", &self.expires_in)
             .field("scope", &self.scope)
+            .field("issued_token_type", &self.issued_token_type)
+            .field("authorization_details", &self.authorization_details)
             .finish_non_exhaustive()
     }
 }
@@ -798,7 +1374,9 @@ pub struct IntrospectionResponse {
     pub sub: Option<String>,
 
     /// Intended audience of the token.
-    pub aud: Option<String>,
+    #[serde_as(as = "Option<OneOrMany<_, PreferOne>>")]
+    #[serde(default)]
+    pub aud: Option<Vec<String>>,
 
     /// Issuer of the token.
     pub iss: Option<String>,
@@ -808,6 +1386,62 @@ pub struct IntrospectionResponse {
 
     /// MAS extension: explicit device ID
     pub device_id: Option<String>,
+
+    /// The key the token is bound to, if it is sender-constrained.
+    pub cnf: Option<Confirmation>,
+
+    /// The party acting on behalf of the subject, if the token was obtained
+    /// through a token exchange.
+    pub act: Option<ActorClaim>,
+
+    /// The fine-grained authorization data granted to the token.
+    pub authorization_details: Option<AuthorizationDetails>,
+
+    /// The authentication context class reference satisfied by the user
+    /// authentication the token was obtained with.
+    pub acr: Option<String>,
+
+    /// Timestamp indicating when the user authentication the token was
+    /// obtained with occurred.
+    #[serde_as(as = "Option<TimestampSeconds>")]
+    pub auth_time: Option<DateTime<Utc>>,
+}
+
+/// The [actor] claim of a token obtained through a token exchange, which
+/// identifies the party acting on behalf of the subject.
+///
+/// [actor]: https://www.rfc-editor.org/rfc/rfc8693#section-4.1
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ActorClaim {
+    /// The subject of the acting party.
+    pub sub: Option<String>,
+
+    /// The client identifier of the acting party.
+    pub client_id: Option<String>,
+
+    /// The prior actor, if the delegation is chained.
+    pub act: Option<Box<ActorClaim>>,
+}
+
+/// The [confirmation] claim of a sender-constrained token, which identifies the
+/// key the client has to prove possession of to use the token.
+///
+/// [confirmation]: https://www.rfc-editor.org/rfc/rfc7800#section-3.1
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Confirmation {
+    /// The SHA-256 JWK thumbprint of the key used in [DPoP] proofs.
+    ///
+    /// [DPoP]: https://www.rfc-editor.org/rfc/rfc9449#section-6
+    pub jkt: Option<String>,
+
+    /// The SHA-256 thumbprint of the certificate used for [mutual-TLS] client
+    /// authentication.
+    ///
+    /// [mutual-TLS]: https://www.rfc-editor.org/rfc/rfc8705#section-3.1
+    #[serde(rename = "x5t#S256")]
+    pub x5t_s256: Option<String>,
 }
 
 /// A request to the [Revocation Endpoint].
@@ -872,6 +1506,7 @@ mod tests {
         let req = AccessTokenRequest::RefreshToken(RefreshTokenGrant {
             refresh_token: "abcd".into(),
             scope,
+            resource: None,
         });
 
         assert_serde_json(&req, expected);
@@ -889,11 +1524,131 @@ mod tests {
             code: "abcd".into(),
             redirect_uri: Some("https://example.com/redirect".parse().unwrap()),
             code_verifier: None,
+            resource: None,
         });
 
         assert_serde_json(&req, expected);
     }
 
+    #[test]
+    fn serde_jwt_bearer_grant() {
+        let expected = json!({
+            "grant_type": "urn:ietf:params:oauth:grant-type:jwt-bearer",
+            "assertion": "eyJhbGciOiJFUzI1NiJ9.e30.c2lnbmF0dXJl",
+            "scope": "openid",
+        });
+
+        let req = AccessTokenRequest::JwtBearer(JwtBearerGrant {
+            assertion: "eyJhbGciOiJFUzI1NiJ9.e30.c2lnbmF0dXJl".into(),
+            scope: Some(vec![OPENID].into_iter().collect()),
+        });
+
+        assert_serde_json(&req, expected);
+    }
+
+    #[test]
+    fn serde_token_exchange_grant() {
+        let expected = json!({
+            "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+            "subject_token": "abcd",
+            "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            "audience": "https://example.com/",
+            "scope": "openid",
+        });
+
+        let req = AccessTokenRequest::TokenExchange(TokenExchangeGrant {
+            subject_token: "abcd".into(),
+            subject_token_type: TokenTypeIdentifier::AccessToken,
+            actor_token: None,
+            actor_token_type: None,
+            requested_token_type: None,
+            audience: Some("https://example.com/".into()),
+            resource: None,
+            scope: Some(vec![OPENID].into_iter().collect()),
+        });
+
+        assert_serde_json(&req, expected);
+    }
+
+    #[test]
+    fn parse_claims_parameter() {
+        let claims: ClaimsParameter = r#"{
+            "userinfo": {
+                "email": {"essential": true},
+                "preferred_username": null
+            },
+            "id_token": {
+                "email": null,
+                "auth_time": {"essential": true}
+            }
+        }"#
+        .parse()
+        .unwrap();
+
+        let userinfo = claims.userinfo.as_ref().unwrap();
+        assert!(userinfo["email"].as_ref().unwrap().is_essential());
+        assert!(userinfo["preferred_username"].is_none());
+
+        let id_token = claims.id_token.as_ref().unwrap();
+        assert!(id_token["email"].is_none());
+        assert!(id_token["auth_time"].as_ref().unwrap().is_essential());
+
+        let all_claims = claims.all_claims();
+        assert_eq!(
+            all_claims.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("auth_time", true),
+                ("email", true),
+                ("preferred_username", false)
+            ]
+        );
+
+        // It roundtrips through its string representation
+        assert_eq!(
+            claims.to_string().parse::<ClaimsParameter>().unwrap(),
+            claims
+        );
+    }
+
+    #[test]
+    fn parse_authorization_details() {
+        let details: AuthorizationDetails = r#"[
+            {
+                "type": "urn:example:room",
+                "actions": ["send_message"],
+                "identifier": "!room:example.com",
+                "room_version": "10"
+            }
+        ]"#
+        .parse()
+        .unwrap();
+
+        assert_eq!(details.len(), 1);
+        let detail = &details[0];
+        assert_eq!(detail.r#type, "urn:example:room");
+        assert_eq!(detail.actions, Some(vec!["send_message".to_owned()]));
+        assert_eq!(detail.identifier.as_deref(), Some("!room:example.com"));
+        assert_eq!(detail.locations, None);
+        assert_eq!(detail.extra["room_version"], json!("10"));
+
+        // It roundtrips through its string representation
+        assert_eq!(
+            details.to_string().parse::<AuthorizationDetails>().unwrap(),
+            details
+        );
+
+        // It is a subset of itself, but not of an empty list
+        assert!(details.contains_all(&details));
+        assert!(!AuthorizationDetails::default().contains_all(&details));
+
+        // The type is required
+        assert!(
+            "[{\"actions\": []}]"
+                .parse::<AuthorizationDetails>()
+                .is_err()
+        );
+    }
+
     #[test]
     fn serialize_grant_type() {
         assert_eq!(
@@ -924,6 +1679,10 @@ mod tests {
             serde_json::to_string(&GrantType::ClientInitiatedBackchannelAuthentication).unwrap(),
             "\"urn:openid:params:grant-type:ciba\""
         );
+        assert_eq!(
+            serde_json::to_string(&GrantType::TokenExchange).unwrap(),
+            "\"urn:ietf:params:oauth:grant-type:token-exchange\""
+        );
     }
 
     #[test]
@@ -957,6 +1716,13 @@ mod tests {
             serde_json::from_str::<GrantType>("\"urn:openid:params:grant-type:ciba\"").unwrap(),
             GrantType::ClientInitiatedBackchannelAuthentication
         );
+        assert_eq!(
+            serde_json::from_str::<GrantType>(
+                "\"urn:ietf:params:oauth:grant-type:token-exchange\""
+            )
+            .unwrap(),
+            GrantType::TokenExchange
+        );
     }
 
     #[test]
@@ -277,9 +277,12 @@ fn build_authorization_request(
             id_token_hint,
             login_hint,
             acr_values,
+            claims: None,
             request: None,
             request_uri: None,
             registration: None,
+            authorization_details: None,
+            resource: None,
         },
         pkce,
     };
@@ -415,6 +418,7 @@ pub async fn access_token_with_authorization_code(
             code: code.clone(),
             redirect_uri: Some(validation_data.redirect_uri),
             code_verifier: validation_data.code_challenge_verifier,
+            resource: None,
         }),
         now,
         rng,
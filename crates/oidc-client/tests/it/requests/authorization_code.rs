@@ -210,6 +210,8 @@ async fn pass_access_token_with_authorization_code() {
                 token_type: OAuthAccessTokenType::Bearer,
                 expires_in: None,
                 scope: Some([OPENID].into_iter().collect()),
+                issued_token_type: None,
+                authorization_details: None,
             }),
         )
         .mount(&mock_server)
@@ -268,6 +270,8 @@ async fn fail_access_token_with_authorization_code_wrong_nonce() {
                 token_type: OAuthAccessTokenType::Bearer,
                 expires_in: None,
                 scope: Some([OPENID].into_iter().collect()),
+                issued_token_type: None,
+                authorization_details: None,
             }),
         )
         .mount(&mock_server)
@@ -329,6 +333,8 @@ async fn fail_access_token_with_authorization_code_no_id_token() {
                 token_type: OAuthAccessTokenType::Bearer,
                 expires_in: None,
                 scope: Some([OPENID].into_iter().collect()),
+                issued_token_type: None,
+                authorization_details: None,
             }),
         )
         .mount(&mock_server)
@@ -77,6 +77,8 @@ async fn pass_access_token_with_client_credentials() {
                 token_type: OAuthAccessTokenType::Bearer,
                 expires_in: None,
                 scope: Some(scope.clone()),
+                issued_token_type: None,
+                authorization_details: None,
             }),
         )
         .mount(&mock_server)
@@ -65,6 +65,8 @@ async fn pass_refresh_access_token() {
                 token_type: OAuthAccessTokenType::Bearer,
                 expires_in: None,
                 scope: None,
+                issued_token_type: None,
+                authorization_details: None,
             }),
         )
         .mount(&mock_server)
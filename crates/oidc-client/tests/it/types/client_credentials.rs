@@ -58,6 +58,8 @@ async fn pass_none() {
                 token_type: OAuthAccessTokenType::Bearer,
                 expires_in: None,
                 scope: None,
+                issued_token_type: None,
+                authorization_details: None,
             }),
         )
         .mount(&mock_server)
@@ -100,6 +102,8 @@ async fn pass_client_secret_basic() {
                 token_type: OAuthAccessTokenType::Bearer,
                 expires_in: None,
                 scope: None,
+                issued_token_type: None,
+                authorization_details: None,
             }),
         )
         .mount(&mock_server)
@@ -157,6 +161,8 @@ async fn pass_client_secret_post() {
                 token_type: OAuthAccessTokenType::Bearer,
                 expires_in: None,
                 scope: None,
+                issued_token_type: None,
+                authorization_details: None,
             }),
         )
         .mount(&mock_server)
@@ -231,6 +237,8 @@ async fn pass_client_secret_jwt() {
                 token_type: OAuthAccessTokenType::Bearer,
                 expires_in: None,
                 scope: None,
+                issued_token_type: None,
+                authorization_details: None,
             }),
         )
         .mount(&mock_server)
@@ -307,6 +315,8 @@ async fn pass_private_key_jwt() {
                 token_type: OAuthAccessTokenType::Bearer,
                 expires_in: None,
                 scope: None,
+                issued_token_type: None,
+                authorization_details: None,
             }),
         )
         .mount(&mock_server)
@@ -19,7 +19,8 @@ use tokio::io::{AsyncRead, AsyncReadExt};
 
 pub use self::model::{
     AuthorizationGrantInput, ClientRegistrationInput, Code as ViolationCode, EmailInput,
-    EvaluationResult, GrantType, RegisterInput, RegistrationMethod, Requester, Violation,
+    EvaluationResult, GrantType, RegisterInput, RegistrationMethod, Requester, TokenExchangeInput,
+    Violation,
 };
 
 #[derive(Debug, Error)]
@@ -71,15 +72,17 @@ pub struct Entrypoints {
     pub register: String,
     pub client_registration: String,
     pub authorization_grant: String,
+    pub token_exchange: String,
     pub email: String,
 }
 
 impl Entrypoints {
-    fn all(&self) -> [&str; 4] {
+    fn all(&self) -> [&str; 5] {
         [
             self.register.as_str(),
             self.client_registration.as_str(),
             self.authorization_grant.as_str(),
+            self.token_exchange.as_str(),
             self.email.as_str(),
         ]
     }
@@ -416,6 +419,27 @@ impl Policy {
 
         Ok(res)
     }
+
+    #[tracing::instrument(
+        name = "policy.evaluate.token_exchange",
+        skip_all,
+        fields(
+            %input.scope,
+            %input.client.id,
+            %input.subject_client.id,
+        ),
+    )]
+    pub async fn evaluate_token_exchange(
+        &mut self,
+        input: TokenExchangeInput<'_>,
+    ) -> Result<EvaluationResult, EvaluationError> {
+        let [res]: [EvaluationResult; 1] = self
+            .instance
+            .evaluate(&mut self.store, &self.entrypoints.token_exchange, &input)
+            .await?;
+
+        Ok(res)
+    }
 }
 
 #[cfg(test)]
@@ -445,6 +469,7 @@ mod tests {
             register: "register/violation".to_owned(),
             client_registration: "client_registration/violation".to_owned(),
             authorization_grant: "authorization_grant/violation".to_owned(),
+            token_exchange: "token_exchange/violation".to_owned(),
             email: "email/violation".to_owned(),
         };
 
@@ -512,6 +537,7 @@ mod tests {
             register: "register/violation".to_owned(),
             client_registration: "client_registration/violation".to_owned(),
             authorization_grant: "authorization_grant/violation".to_owned(),
+            token_exchange: "token_exchange/violation".to_owned(),
             email: "email/violation".to_owned(),
         };
 
@@ -581,6 +607,7 @@ mod tests {
             register: "register/violation".to_owned(),
             client_registration: "client_registration/violation".to_owned(),
             authorization_grant: "authorization_grant/violation".to_owned(),
+            token_exchange: "token_exchange/violation".to_owned(),
             email: "email/violation".to_owned(),
         };
 
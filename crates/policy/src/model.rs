@@ -12,7 +12,9 @@
 use std::net::IpAddr;
 
 use mas_data_model::{Client, User};
-use oauth2_types::{registration::VerifiedClientMetadata, scope::Scope};
+use oauth2_types::{
+    registration::VerifiedClientMetadata, requests::AuthorizationDetail, scope::Scope,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -149,6 +151,12 @@ pub struct RegisterInput<'a> {
 pub struct ClientRegistrationInput<'a> {
     #[schemars(with = "std::collections::HashMap<String, serde_json::Value>")]
     pub client_metadata: &'a VerifiedClientMetadata,
+
+    /// The claims of the software statement sent along the registration, if it
+    /// was verified against a trusted software publisher
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub software_statement: Option<&'a serde_json::Map<String, serde_json::Value>>,
+
     pub requester: Requester,
 }
 
@@ -159,6 +167,10 @@ pub enum GrantType {
     ClientCredentials,
     #[serde(rename = "urn:ietf:params:oauth:grant-type:device_code")]
     DeviceCode,
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:jwt-bearer")]
+    JwtBearer,
+    #[serde(rename = "urn:openid:params:grant-type:ciba")]
+    ClientInitiatedBackchannelAuthentication,
 }
 
 /// Input for the authorization grant policy.
@@ -176,6 +188,43 @@ pub struct AuthorizationGrantInput<'a> {
 
     pub grant_type: GrantType,
 
+    /// The fine-grained authorization details requested, if any
+    #[schemars(with = "Vec<std::collections::HashMap<String, serde_json::Value>>")]
+    pub authorization_details: &'a [AuthorizationDetail],
+
+    /// The ACR level satisfied by the user's last authentication, if any
+    pub acr: Option<&'a str>,
+
+    pub requester: Requester,
+}
+
+/// Input for the token exchange policy.
+#[derive(Serialize, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TokenExchangeInput<'a> {
+    /// The user the subject token was issued for, if any
+    #[schemars(with = "Option<std::collections::HashMap<String, serde_json::Value>>")]
+    pub user: Option<&'a User>,
+
+    /// The client requesting the exchange
+    #[schemars(with = "std::collections::HashMap<String, serde_json::Value>")]
+    pub client: &'a Client,
+
+    /// The client the subject token was issued to
+    #[schemars(with = "std::collections::HashMap<String, serde_json::Value>")]
+    pub subject_client: &'a Client,
+
+    /// The client the actor token was issued to, if any
+    #[schemars(with = "Option<std::collections::HashMap<String, serde_json::Value>>")]
+    pub actor_client: Option<&'a Client>,
+
+    /// The scope requested for the new token
+    #[schemars(with = "String")]
+    pub scope: &'a Scope,
+
+    /// The audience requested for the new token
+    pub audience: &'a [String],
+
     pub requester: Requester,
 }
 
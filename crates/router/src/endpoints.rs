@@ -59,17 +59,26 @@ impl PostAuthAction {
     }
 
     pub fn go_next(&self, url_builder: &UrlBuilder) -> axum::response::Redirect {
+        axum::response::Redirect::to(&self.relative_url(url_builder))
+    }
+
+    /// The relative URL where the user should go next, prefixed with the base
+    /// URL
+    #[must_use]
+    pub fn relative_url(&self, url_builder: &UrlBuilder) -> String {
         match self {
-            Self::ContinueAuthorizationGrant { id } => url_builder.redirect(&Consent(*id)),
+            Self::ContinueAuthorizationGrant { id } => url_builder.relative_url_for(&Consent(*id)),
             Self::ContinueDeviceCodeGrant { id } => {
-                url_builder.redirect(&DeviceCodeConsent::new(*id))
+                url_builder.relative_url_for(&DeviceCodeConsent::new(*id))
             }
             Self::ContinueCompatSsoLogin { id } => {
-                url_builder.redirect(&CompatLoginSsoComplete::new(*id, None))
+                url_builder.relative_url_for(&CompatLoginSsoComplete::new(*id, None))
             }
-            Self::ChangePassword => url_builder.redirect(&AccountPasswordChange),
-            Self::LinkUpstream { id } => url_builder.redirect(&UpstreamOAuth2Link::new(*id)),
-            Self::ManageAccount { action } => url_builder.redirect(&Account {
+            Self::ChangePassword => url_builder.relative_url_for(&AccountPasswordChange),
+            Self::LinkUpstream { id } => {
+                url_builder.relative_url_for(&UpstreamOAuth2Link::new(*id))
+            }
+            Self::ManageAccount { action } => url_builder.relative_url_for(&Account {
                 action: action.clone(),
             }),
         }
@@ -115,6 +124,14 @@ impl SimpleRoute for OidcUserinfo {
     const PATH: &'static str = "/oauth2/userinfo";
 }
 
+/// `GET|POST /oauth2/end_session`
+#[derive(Default, Debug, Clone)]
+pub struct OidcEndSession;
+
+impl SimpleRoute for OidcEndSession {
+    const PATH: &'static str = "/oauth2/end_session";
+}
+
 /// `POST /oauth2/introspect`
 #[derive(Default, Debug, Clone)]
 pub struct OAuth2Introspection;
@@ -147,6 +164,30 @@ impl SimpleRoute for OAuth2RegistrationEndpoint {
     const PATH: &'static str = "/oauth2/registration";
 }
 
+/// `GET|PUT|DELETE /oauth2/registration/{client_id}`
+#[derive(Debug, Clone)]
+pub struct OAuth2ClientConfiguration {
+    id: Ulid,
+}
+
+impl OAuth2ClientConfiguration {
+    #[must_use]
+    pub const fn new(id: Ulid) -> Self {
+        Self { id }
+    }
+}
+
+impl Route for OAuth2ClientConfiguration {
+    type Query = ();
+    fn route() -> &'static str {
+        "/oauth2/registration/{client_id}"
+    }
+
+    fn path(&self) -> std::borrow::Cow<'static, str> {
+        format!("/oauth2/registration/{}", self.id).into()
+    }
+}
+
 /// `GET /authorize`
 #[derive(Default, Debug, Clone)]
 pub struct OAuth2AuthorizationEndpoint;
@@ -155,6 +196,14 @@ impl SimpleRoute for OAuth2AuthorizationEndpoint {
     const PATH: &'static str = "/authorize";
 }
 
+/// `POST /oauth2/par`
+#[derive(Default, Debug, Clone)]
+pub struct OAuth2PushedAuthorizationRequestEndpoint;
+
+impl SimpleRoute for OAuth2PushedAuthorizationRequestEndpoint {
+    const PATH: &'static str = "/oauth2/par";
+}
+
 /// `GET /`
 #[derive(Default, Debug, Clone)]
 pub struct Index;
@@ -558,6 +607,20 @@ impl Route for Consent {
     }
 }
 
+/// `GET|POST /select_account/{grant_id}`
+pub struct SelectAccount(pub Ulid);
+
+impl Route for SelectAccount {
+    type Query = ();
+    fn route() -> &'static str {
+        "/select_account/{grant_id}"
+    }
+
+    fn path(&self) -> std::borrow::Cow<'static, str> {
+        format!("/select_account/{}", self.0).into()
+    }
+}
+
 /// `GET|POST /_matrix/client/v3/login`
 pub struct CompatLogin;
 
@@ -817,6 +880,14 @@ impl SimpleRoute for OAuth2DeviceAuthorizationEndpoint {
     const PATH: &'static str = "/oauth2/device";
 }
 
+/// `POST /oauth2/bc-authorize`
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
+pub struct OAuth2BackchannelAuthenticationEndpoint;
+
+impl SimpleRoute for OAuth2BackchannelAuthenticationEndpoint {
+    const PATH: &'static str = "/oauth2/bc-authorize";
+}
+
 /// `GET|POST /recover`
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct AccountRecoveryStart;
@@ -166,12 +166,36 @@ impl UrlBuilder {
         self.absolute_url_for(&crate::endpoints::OAuth2RegistrationEndpoint)
     }
 
+    /// OAuth 2.0 client configuration endpoint, as per RFC 7592
+    #[must_use]
+    pub fn oauth_client_configuration_endpoint(&self, id: Ulid) -> Url {
+        self.absolute_url_for(&crate::endpoints::OAuth2ClientConfiguration::new(id))
+    }
+
+    /// OIDC end session endpoint
+    #[must_use]
+    pub fn oidc_end_session_endpoint(&self) -> Url {
+        self.absolute_url_for(&crate::endpoints::OidcEndSession)
+    }
+
+    /// OAuth 2.0 pushed authorization request endpoint
+    #[must_use]
+    pub fn oauth_pushed_authorization_request_endpoint(&self) -> Url {
+        self.absolute_url_for(&crate::endpoints::OAuth2PushedAuthorizationRequestEndpoint)
+    }
+
     /// OAuth 2.0 device authorization endpoint
     #[must_use]
     pub fn oauth_device_authorization_endpoint(&self) -> Url {
         self.absolute_url_for(&crate::endpoints::OAuth2DeviceAuthorizationEndpoint)
     }
 
+    /// OpenID Connect client-initiated backchannel authentication endpoint
+    #[must_use]
+    pub fn oauth_backchannel_authentication_endpoint(&self) -> Url {
+        self.absolute_url_for(&crate::endpoints::OAuth2BackchannelAuthenticationEndpoint)
+    }
+
     /// OAuth 2.0 device code link
     #[must_use]
     pub fn device_code_link(&self) -> Url {
@@ -0,0 +1,40 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Adds a table to store pushed authorization requests (RFC 9126)
+CREATE TABLE "oauth2_pushed_authorization_requests" (
+    "oauth2_pushed_authorization_request_id" UUID NOT NULL
+        PRIMARY KEY,
+
+    -- The client who pushed the authorization request
+    "oauth2_client_id" UUID NOT NULL
+        REFERENCES "oauth2_clients" ("oauth2_client_id")
+        ON DELETE CASCADE,
+
+    -- The random reference used in the `request_uri`
+    "reference" TEXT NOT NULL
+        UNIQUE,
+
+    -- The authorization request parameters, as a JSON object of strings
+    "parameters" JSONB NOT NULL,
+
+    -- Timestamp when the request was pushed
+    "created_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    -- Timestamp when the `request_uri` expires
+    "expires_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    -- Timestamp when the `request_uri` was used at the authorization endpoint
+    "consumed_at" TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX "oauth2_pushed_authorization_requests_client_fk"
+    ON "oauth2_pushed_authorization_requests" ("oauth2_client_id");
+
+-- Whether the client must use pushed authorization requests
+ALTER TABLE "oauth2_clients"
+  ADD COLUMN "require_pushed_authorization_requests" BOOLEAN
+    NOT NULL
+    DEFAULT FALSE;
@@ -0,0 +1,19 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Keeps track of the JWT IDs (`jti`) which were already used, to prevent
+-- replaying signed JWTs like request objects
+CREATE TABLE "oauth2_used_jwt_ids" (
+    -- Who issued the JWT, e.g. the client ID for request objects
+    "issuer" TEXT NOT NULL,
+    "jti" TEXT NOT NULL,
+    "used_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+    -- After this time, the JWT is expired anyway and the row can be removed
+    "expires_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY ("issuer", "jti")
+);
+
+CREATE INDEX "oauth2_used_jwt_ids_expires_at"
+    ON "oauth2_used_jwt_ids" ("expires_at");
@@ -0,0 +1,12 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Individual claims requested through the `claims` parameter of authorization
+-- requests, as a JSON object
+ALTER TABLE "oauth2_authorization_grants"
+    ADD COLUMN "requested_claims" JSONB;
+
+ALTER TABLE "oauth2_sessions"
+    ADD COLUMN "requested_claims" JSONB;
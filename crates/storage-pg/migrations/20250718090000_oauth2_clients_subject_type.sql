@@ -0,0 +1,10 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The kind of subject identifiers the client gets, and the URL listing the
+-- redirect URIs of the sector it belongs to, used for pairwise identifiers
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "subject_type" TEXT,
+    ADD COLUMN "sector_identifier_uri" TEXT;
@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The URIs the client may ask users to be redirected to after logging out
+-- through the end session endpoint
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "post_logout_redirect_uris" TEXT[] NOT NULL DEFAULT '{}';
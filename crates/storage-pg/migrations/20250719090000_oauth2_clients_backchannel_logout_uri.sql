@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The URI to which logout tokens are sent when a user logs out, as per the
+-- OpenID Connect Back-Channel Logout specification
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "backchannel_logout_uri" TEXT;
@@ -0,0 +1,11 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The URI loaded in an iframe when a user logs out, and whether the issuer and
+-- session ID should be passed to it, as per the OpenID Connect Front-Channel
+-- Logout specification
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "frontchannel_logout_uri" TEXT,
+    ADD COLUMN "frontchannel_logout_session_required" BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The JWK SHA-256 thumbprint of the DPoP key the tokens of the session are
+-- bound to, as per RFC 9449
+ALTER TABLE "oauth2_sessions"
+    ADD COLUMN "dpop_jkt" TEXT;
@@ -0,0 +1,8 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Whether the client must always use DPoP-bound access tokens, as per RFC 9449
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "dpop_bound_access_tokens" BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,10 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The expected subject of the certificate used by clients authenticating with
+-- the tls_client_auth method, as per RFC 8705
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "tls_client_auth_subject_dn" TEXT,
+    ADD COLUMN "tls_client_auth_san_dns" TEXT;
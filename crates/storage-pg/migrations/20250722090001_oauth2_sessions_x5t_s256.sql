@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The SHA-256 thumbprint of the client certificate the tokens of the session
+-- are bound to, as per RFC 8705
+ALTER TABLE "oauth2_sessions"
+    ADD COLUMN "x5t_s256" TEXT;
@@ -0,0 +1,11 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The audience the tokens of the session are restricted to, and the party
+-- acting on behalf of the user, for sessions obtained through a token exchange
+-- as per RFC 8693
+ALTER TABLE "oauth2_sessions"
+    ADD COLUMN "audience" TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN "act" JSONB;
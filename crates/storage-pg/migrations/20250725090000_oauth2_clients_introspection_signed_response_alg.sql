@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Add the algorithm used to sign JWT introspection responses for a client,
+-- as per RFC 9701
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "introspection_signed_response_alg" TEXT;
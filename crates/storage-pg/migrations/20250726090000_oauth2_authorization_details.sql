@@ -0,0 +1,12 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Fine-grained authorization details requested through the
+-- `authorization_details` parameter (RFC 9396), as a JSON array
+ALTER TABLE "oauth2_authorization_grants"
+    ADD COLUMN "authorization_details" JSONB;
+
+ALTER TABLE "oauth2_sessions"
+    ADD COLUMN "authorization_details" JSONB;
@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The resource server the client requested a token for through the
+-- `resource` parameter (RFC 8707)
+ALTER TABLE "oauth2_authorization_grants"
+    ADD COLUMN "resource" TEXT;
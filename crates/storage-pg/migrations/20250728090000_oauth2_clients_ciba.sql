@@ -0,0 +1,11 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Client metadata for the OpenID Connect Client-Initiated Backchannel
+-- Authentication flow
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "grant_type_ciba" BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN "backchannel_token_delivery_mode" TEXT,
+    ADD COLUMN "backchannel_client_notification_endpoint" TEXT;
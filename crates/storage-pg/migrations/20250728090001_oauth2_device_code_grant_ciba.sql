@@ -0,0 +1,12 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Client-initiated backchannel authentication requests are stored as device
+-- code grants, bound to the user for whom authentication was requested
+ALTER TABLE "oauth2_device_code_grant"
+    ADD COLUMN "user_id" UUID
+        REFERENCES "users" ("user_id") ON DELETE CASCADE,
+    ADD COLUMN "binding_message" TEXT,
+    ADD COLUMN "client_notification_token" TEXT;
@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Hash of the access token used by dynamically registered clients to manage
+-- their registration, as per RFC 7592
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "registration_access_token_hash" TEXT;
@@ -0,0 +1,14 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Algorithms used to encrypt the ID tokens, userinfo and introspection
+-- responses sent to the client
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "id_token_encrypted_response_alg" TEXT,
+    ADD COLUMN "id_token_encrypted_response_enc" TEXT,
+    ADD COLUMN "userinfo_encrypted_response_alg" TEXT,
+    ADD COLUMN "userinfo_encrypted_response_enc" TEXT,
+    ADD COLUMN "introspection_encrypted_response_alg" TEXT,
+    ADD COLUMN "introspection_encrypted_response_enc" TEXT;
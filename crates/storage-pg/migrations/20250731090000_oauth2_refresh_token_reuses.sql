@@ -0,0 +1,23 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Records every time a consumed refresh token was presented again outside of
+-- the grace period, which led to the whole session being revoked
+CREATE TABLE oauth2_refresh_token_reuses (
+  oauth2_refresh_token_reuse_id UUID NOT NULL PRIMARY KEY,
+  oauth2_session_id UUID NOT NULL
+    REFERENCES oauth2_sessions (oauth2_session_id),
+  oauth2_refresh_token_id UUID NOT NULL
+    REFERENCES oauth2_refresh_tokens (oauth2_refresh_token_id),
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  ip_address INET,
+  user_agent TEXT
+);
+
+CREATE INDEX oauth2_refresh_token_reuses_session_fk
+  ON oauth2_refresh_token_reuses (oauth2_session_id);
+
+CREATE INDEX oauth2_refresh_token_reuses_refresh_token_fk
+  ON oauth2_refresh_token_reuses (oauth2_refresh_token_id);
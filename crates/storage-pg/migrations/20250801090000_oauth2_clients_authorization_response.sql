@@ -0,0 +1,10 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Algorithms used to sign and encrypt JWT-secured authorization responses
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "authorization_signed_response_alg" TEXT,
+    ADD COLUMN "authorization_encrypted_response_alg" TEXT,
+    ADD COLUMN "authorization_encrypted_response_enc" TEXT;
@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The authentication context class references requested through the
+-- `acr_values` parameter. The `max_age` column already exists, but was unused
+ALTER TABLE oauth2_authorization_grants
+  ADD COLUMN acr_values TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,38 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Consents used to be stored as one row per scope token. Store one row per
+-- user and client instead, with the whole list of scope tokens the user
+-- consented to, so that consents can be listed and revoked as a whole.
+ALTER TABLE oauth2_consents
+  ADD COLUMN scope_list TEXT[];
+
+-- Merge the existing rows into the oldest one for each user and client
+WITH merged AS (
+  SELECT (array_agg(oauth2_consent_id ORDER BY oauth2_consent_id))[1] AS oauth2_consent_id
+       , array_agg(scope_token ORDER BY scope_token) AS scope_list
+       , MIN(created_at) AS created_at
+       , MAX(refreshed_at) AS refreshed_at
+  FROM oauth2_consents
+  GROUP BY oauth2_client_id, user_id
+)
+UPDATE oauth2_consents
+  SET scope_list = merged.scope_list
+    , created_at = merged.created_at
+    , refreshed_at = merged.refreshed_at
+  FROM merged
+  WHERE oauth2_consents.oauth2_consent_id = merged.oauth2_consent_id;
+
+DELETE FROM oauth2_consents
+  WHERE scope_list IS NULL;
+
+ALTER TABLE oauth2_consents
+  DROP CONSTRAINT oauth2_consents_unique,
+  DROP COLUMN scope_token,
+  ALTER COLUMN scope_list SET NOT NULL,
+  ADD CONSTRAINT oauth2_consents_unique UNIQUE (oauth2_client_id, user_id);
+
+-- The unique constraint covers this index
+DROP INDEX oauth2_consents_oauth2_client_id_user_id;
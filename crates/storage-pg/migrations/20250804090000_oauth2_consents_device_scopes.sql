@@ -0,0 +1,19 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Matrix device scope tokens are different for every session, and are not
+-- remembered in consents anymore
+UPDATE oauth2_consents
+  SET scope_list = ARRAY(
+    SELECT scope_token
+    FROM unnest(scope_list) AS scope_token
+    WHERE scope_token NOT LIKE 'urn:matrix:org.matrix.msc2967.client:device:%'
+    ORDER BY scope_token
+  )
+  WHERE EXISTS (
+    SELECT 1
+    FROM unnest(scope_list) AS scope_token
+    WHERE scope_token LIKE 'urn:matrix:org.matrix.msc2967.client:device:%'
+  );
@@ -0,0 +1,11 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- JWT-formatted access tokens are stored as the hex-encoded SHA-256 hash of
+-- the token, as they can be too large for the unique index on this column.
+-- Opaque access tokens never contain dots.
+UPDATE oauth2_access_tokens
+  SET access_token = encode(sha256(convert_to(access_token, 'UTF8')), 'hex')
+  WHERE access_token LIKE '%.%.%';
@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The URIs the client pre-registered to pass request objects by reference
+-- through the `request_uri` parameter
+ALTER TABLE "oauth2_clients"
+    ADD COLUMN "request_uris" TEXT[] NOT NULL DEFAULT '{}';
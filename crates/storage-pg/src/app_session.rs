@@ -193,6 +193,14 @@ impl TryFrom<AppSessionLookup> for AppSession {
                     last_active_at,
                     last_active_ip,
                     human_name,
+                    // The requested claims are only useful when issuing
+                    // tokens, so they are not loaded when listing sessions
+                    requested_claims: None,
+                    dpop_jkt: None,
+                    x5t_s256: None,
+                    audience: Vec::new(),
+                    act: None,
+                    authorization_details: None,
                 };
 
                 Ok(AppSession::OAuth2(Box::new(session)))
@@ -530,7 +538,7 @@ mod tests {
         Pagination, RepositoryAccess,
         app_session::{AppSession, AppSessionFilter},
         clock::MockClock,
-        oauth2::OAuth2SessionRepository,
+        oauth2::{OAuth2ClientParams, OAuth2SessionRepository},
     };
     use oauth2_types::{
         requests::GrantType,
@@ -630,23 +638,18 @@ mod tests {
             .add(
                 &mut rng,
                 &clock,
-                vec!["https://example.com/redirect".parse().unwrap()],
-                None,
-                None,
-                None,
-                vec![GrantType::AuthorizationCode],
-                Some("First client".to_owned()),
-                Some("https://example.com/logo.png".parse().unwrap()),
-                Some("https://example.com/".parse().unwrap()),
-                Some("https://example.com/policy".parse().unwrap()),
-                Some("https://example.com/tos".parse().unwrap()),
-                Some("https://example.com/jwks.json".parse().unwrap()),
-                None,
-                None,
-                None,
-                None,
-                None,
-                Some("https://example.com/login".parse().unwrap()),
+                OAuth2ClientParams {
+                    redirect_uris: vec!["https://example.com/redirect".parse().unwrap()],
+                    grant_types: vec![GrantType::AuthorizationCode],
+                    client_name: Some("First client".to_owned()),
+                    logo_uri: Some("https://example.com/logo.png".parse().unwrap()),
+                    client_uri: Some("https://example.com/".parse().unwrap()),
+                    policy_uri: Some("https://example.com/policy".parse().unwrap()),
+                    tos_uri: Some("https://example.com/tos".parse().unwrap()),
+                    jwks_uri: Some("https://example.com/jwks.json".parse().unwrap()),
+                    initiate_login_uri: Some("https://example.com/login".parse().unwrap()),
+                    ..Default::default()
+                },
             )
             .await
             .unwrap();
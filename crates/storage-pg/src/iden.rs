@@ -96,6 +96,12 @@ pub enum OAuth2Sessions {
     LastActiveAt,
     LastActiveIp,
     HumanName,
+    RequestedClaims,
+    DpopJkt,
+    X5tS256,
+    Audience,
+    Act,
+    AuthorizationDetails,
 }
 
 #[derive(sea_query::Iden)]
@@ -107,6 +113,20 @@ pub enum OAuth2Clients {
     IsStatic,
 }
 
+#[derive(sea_query::Iden)]
+#[iden = "oauth2_consents"]
+pub enum OAuth2Consents {
+    Table,
+    #[iden = "oauth2_consent_id"]
+    OAuth2ConsentId,
+    #[iden = "oauth2_client_id"]
+    OAuth2ClientId,
+    UserId,
+    ScopeList,
+    CreatedAt,
+    RefreshedAt,
+}
+
 #[derive(sea_query::Iden)]
 #[iden = "upstream_oauth_providers"]
 pub enum UpstreamOAuthProviders {
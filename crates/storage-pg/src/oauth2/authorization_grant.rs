@@ -7,11 +7,14 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use mas_data_model::{
-    AuthorizationCode, AuthorizationGrant, AuthorizationGrantStage, Client, Pkce, Session,
+    AuthorizationCode, AuthorizationGrant, AuthorizationGrantStage, Pkce, Session,
 };
 use mas_iana::oauth::PkceCodeChallengeMethod;
-use mas_storage::{Clock, oauth2::OAuth2AuthorizationGrantRepository};
-use oauth2_types::{requests::ResponseMode, scope::Scope};
+use mas_storage::{
+    Clock,
+    oauth2::{OAuth2AuthorizationGrantParams, OAuth2AuthorizationGrantRepository},
+};
+use oauth2_types::scope::Scope;
 use rand::RngCore;
 use sqlx::PgConnection;
 use ulid::Ulid;
@@ -53,6 +56,12 @@ struct GrantLookup {
     code_challenge_method: Option<String>,
     login_hint: Option<String>,
     locale: Option<String>,
+    requested_claims: Option<serde_json::Value>,
+    authorization_details: Option<serde_json::Value>,
+    resource: Option<String>,
+    max_age: Option<i32>,
+    acr_values: Vec<String>,
+    requires_consent: bool,
     oauth2_client_id: Uuid,
     oauth2_session_id: Option<Uuid>,
 }
@@ -150,6 +159,46 @@ impl TryFrom<GrantLookup> for AuthorizationGrant {
                 .source(e)
         })?;
 
+        let requested_claims = value
+            .requested_claims
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_authorization_grants")
+                    .column("requested_claims")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let authorization_details = value
+            .authorization_details
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_authorization_grants")
+                    .column("authorization_details")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let resource = value
+            .resource
+            .map(|resource| resource.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_authorization_grants")
+                    .column("resource")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let max_age = value.max_age.map(u32::try_from).transpose().map_err(|e| {
+            DatabaseInconsistencyError::on("oauth2_authorization_grants")
+                .column("max_age")
+                .row(id)
+                .source(e)
+        })?;
+
         Ok(AuthorizationGrant {
             id,
             stage,
@@ -164,6 +213,12 @@ impl TryFrom<GrantLookup> for AuthorizationGrant {
             response_type_id_token: value.response_type_id_token,
             login_hint: value.login_hint,
             locale: value.locale,
+            requested_claims,
+            authorization_details,
+            resource,
+            max_age,
+            acr_values: value.acr_values,
+            requires_consent: value.requires_consent,
         })
     }
 }
@@ -178,8 +233,8 @@ impl OAuth2AuthorizationGrantRepository for PgOAuth2AuthorizationGrantRepository
         fields(
             db.query.text,
             grant.id,
-            grant.scope = %scope,
-            %client.id,
+            grant.scope = %params.scope,
+            client.id = %params.client.id,
         ),
         err,
     )]
@@ -187,17 +242,27 @@ impl OAuth2AuthorizationGrantRepository for PgOAuth2AuthorizationGrantRepository
         &mut self,
         rng: &mut (dyn RngCore + Send),
         clock: &dyn Clock,
-        client: &Client,
-        redirect_uri: Url,
-        scope: Scope,
-        code: Option<AuthorizationCode>,
-        state: Option<String>,
-        nonce: Option<String>,
-        response_mode: ResponseMode,
-        response_type_id_token: bool,
-        login_hint: Option<String>,
-        locale: Option<String>,
+        params: OAuth2AuthorizationGrantParams<'_>,
     ) -> Result<AuthorizationGrant, Self::Error> {
+        let OAuth2AuthorizationGrantParams {
+            client,
+            redirect_uri,
+            scope,
+            code,
+            state,
+            nonce,
+            response_mode,
+            response_type_id_token,
+            login_hint,
+            locale,
+            requested_claims,
+            authorization_details,
+            resource,
+            max_age,
+            acr_values,
+            requires_consent,
+        } = params;
+
         let code_challenge = code
             .as_ref()
             .and_then(|c| c.pkce.as_ref())
@@ -207,6 +272,16 @@ impl OAuth2AuthorizationGrantRepository for PgOAuth2AuthorizationGrantRepository
             .and_then(|c| c.pkce.as_ref())
             .map(|p| p.challenge_method.to_string());
         let code_str = code.as_ref().map(|c| &c.code);
+        let requested_claims_json = requested_claims
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(DatabaseError::to_invalid_operation)?;
+        let authorization_details_json = authorization_details
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(DatabaseError::to_invalid_operation)?;
 
         let created_at = clock.now();
         let id = Ulid::from_datetime_with_source(created_at.into(), rng);
@@ -229,10 +304,17 @@ impl OAuth2AuthorizationGrantRepository for PgOAuth2AuthorizationGrantRepository
                      authorization_code,
                      login_hint,
                      locale,
+                     requested_claims,
+                     authorization_details,
+                     resource,
+                     max_age,
+                     acr_values,
+                     requires_consent,
                      created_at
                 )
                 VALUES
-                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17,
+                     $18, $19, $20, $21)
             "#,
             Uuid::from(id),
             Uuid::from(client.id),
@@ -248,6 +330,15 @@ impl OAuth2AuthorizationGrantRepository for PgOAuth2AuthorizationGrantRepository
             code_str,
             login_hint,
             locale,
+            requested_claims_json,
+            authorization_details_json,
+            resource.as_ref().map(Url::as_str),
+            max_age
+                .map(i32::try_from)
+                .transpose()
+                .map_err(DatabaseError::to_invalid_operation)?,
+            &acr_values,
+            requires_consent,
             created_at,
         )
         .traced()
@@ -268,6 +359,12 @@ impl OAuth2AuthorizationGrantRepository for PgOAuth2AuthorizationGrantRepository
             response_type_id_token,
             login_hint,
             locale,
+            requested_claims,
+            authorization_details,
+            resource,
+            max_age,
+            acr_values,
+            requires_consent,
         })
     }
 
@@ -302,6 +399,12 @@ impl OAuth2AuthorizationGrantRepository for PgOAuth2AuthorizationGrantRepository
                      , code_challenge_method
                      , login_hint
                      , locale
+                     , requested_claims
+                     , authorization_details
+                     , resource
+                     , max_age
+                     , acr_values
+                     , requires_consent
                      , oauth2_session_id
                 FROM
                     oauth2_authorization_grants
@@ -352,6 +455,12 @@ impl OAuth2AuthorizationGrantRepository for PgOAuth2AuthorizationGrantRepository
                      , code_challenge_method
                      , login_hint
                      , locale
+                     , requested_claims
+                     , authorization_details
+                     , resource
+                     , max_age
+                     , acr_values
+                     , requires_consent
                      , oauth2_session_id
                 FROM
                     oauth2_authorization_grants
@@ -11,10 +11,11 @@ use std::{
 
 use async_trait::async_trait;
 use mas_data_model::{Client, JwksOrJwksUri};
-use mas_iana::{jose::JsonWebSignatureAlg, oauth::OAuthClientAuthenticationMethod};
-use mas_jose::jwk::PublicJsonWebKeySet;
-use mas_storage::{Clock, oauth2::OAuth2ClientRepository};
-use oauth2_types::{oidc::ApplicationType, requests::GrantType};
+use mas_storage::{
+    Clock,
+    oauth2::{OAuth2ClientParams, OAuth2ClientRepository, OAuth2StaticClientParams},
+};
+use oauth2_types::requests::GrantType;
 use opentelemetry_semantic_conventions::attribute::DB_QUERY_TEXT;
 use rand::RngCore;
 use sqlx::PgConnection;
@@ -50,6 +51,9 @@ struct OAuth2ClientLookup {
     grant_type_refresh_token: bool,
     grant_type_client_credentials: bool,
     grant_type_device_code: bool,
+    grant_type_token_exchange: bool,
+    grant_type_jwt_bearer: bool,
+    grant_type_ciba: bool,
     client_name: Option<String>,
     logo_uri: Option<String>,
     client_uri: Option<String>,
@@ -62,6 +66,30 @@ struct OAuth2ClientLookup {
     token_endpoint_auth_method: Option<String>,
     token_endpoint_auth_signing_alg: Option<String>,
     initiate_login_uri: Option<String>,
+    require_pushed_authorization_requests: bool,
+    subject_type: Option<String>,
+    sector_identifier_uri: Option<String>,
+    post_logout_redirect_uris: Vec<String>,
+    request_uris: Vec<String>,
+    backchannel_logout_uri: Option<String>,
+    frontchannel_logout_uri: Option<String>,
+    frontchannel_logout_session_required: bool,
+    dpop_bound_access_tokens: bool,
+    tls_client_auth_subject_dn: Option<String>,
+    tls_client_auth_san_dns: Option<String>,
+    introspection_signed_response_alg: Option<String>,
+    backchannel_token_delivery_mode: Option<String>,
+    backchannel_client_notification_endpoint: Option<String>,
+    registration_access_token_hash: Option<String>,
+    id_token_encrypted_response_alg: Option<String>,
+    id_token_encrypted_response_enc: Option<String>,
+    userinfo_encrypted_response_alg: Option<String>,
+    userinfo_encrypted_response_enc: Option<String>,
+    introspection_encrypted_response_alg: Option<String>,
+    introspection_encrypted_response_enc: Option<String>,
+    authorization_signed_response_alg: Option<String>,
+    authorization_encrypted_response_alg: Option<String>,
+    authorization_encrypted_response_enc: Option<String>,
 }
 
 impl TryInto<Client> for OAuth2ClientLookup {
@@ -80,6 +108,27 @@ impl TryInto<Client> for OAuth2ClientLookup {
                 .source(e)
         })?;
 
+        let post_logout_redirect_uris: Result<Vec<Url>, _> = self
+            .post_logout_redirect_uris
+            .iter()
+            .map(|s| s.parse())
+            .collect();
+        let post_logout_redirect_uris = post_logout_redirect_uris.map_err(|e| {
+            DatabaseInconsistencyError::on("oauth2_clients")
+                .column("post_logout_redirect_uris")
+                .row(id)
+                .source(e)
+        })?;
+
+        let request_uris: Result<Vec<Url>, _> =
+            self.request_uris.iter().map(|s| s.parse()).collect();
+        let request_uris = request_uris.map_err(|e| {
+            DatabaseInconsistencyError::on("oauth2_clients")
+                .column("request_uris")
+                .row(id)
+                .source(e)
+        })?;
+
         let application_type = self
             .application_type
             .map(|s| s.parse())
@@ -104,6 +153,15 @@ impl TryInto<Client> for OAuth2ClientLookup {
         if self.grant_type_device_code {
             grant_types.push(GrantType::DeviceCode);
         }
+        if self.grant_type_token_exchange {
+            grant_types.push(GrantType::TokenExchange);
+        }
+        if self.grant_type_jwt_bearer {
+            grant_types.push(GrantType::JwtBearer);
+        }
+        if self.grant_type_ciba {
+            grant_types.push(GrantType::ClientInitiatedBackchannelAuthentication);
+        }
 
         let logo_uri = self.logo_uri.map(|s| s.parse()).transpose().map_err(|e| {
             DatabaseInconsistencyError::on("oauth2_clients")
@@ -163,6 +221,116 @@ impl TryInto<Client> for OAuth2ClientLookup {
                     .source(e)
             })?;
 
+        let introspection_signed_response_alg = self
+            .introspection_signed_response_alg
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("introspection_signed_response_alg")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let id_token_encrypted_response_alg = self
+            .id_token_encrypted_response_alg
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("id_token_encrypted_response_alg")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let id_token_encrypted_response_enc = self
+            .id_token_encrypted_response_enc
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("id_token_encrypted_response_enc")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let userinfo_encrypted_response_alg = self
+            .userinfo_encrypted_response_alg
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("userinfo_encrypted_response_alg")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let userinfo_encrypted_response_enc = self
+            .userinfo_encrypted_response_enc
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("userinfo_encrypted_response_enc")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let introspection_encrypted_response_alg = self
+            .introspection_encrypted_response_alg
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("introspection_encrypted_response_alg")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let introspection_encrypted_response_enc = self
+            .introspection_encrypted_response_enc
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("introspection_encrypted_response_enc")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let authorization_signed_response_alg = self
+            .authorization_signed_response_alg
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("authorization_signed_response_alg")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let authorization_encrypted_response_alg = self
+            .authorization_encrypted_response_alg
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("authorization_encrypted_response_alg")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let authorization_encrypted_response_enc = self
+            .authorization_encrypted_response_enc
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("authorization_encrypted_response_enc")
+                    .row(id)
+                    .source(e)
+            })?;
+
         let token_endpoint_auth_method = self
             .token_endpoint_auth_method
             .map(|s| s.parse())
@@ -196,6 +364,72 @@ impl TryInto<Client> for OAuth2ClientLookup {
                     .source(e)
             })?;
 
+        let subject_type = self
+            .subject_type
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("subject_type")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let sector_identifier_uri = self
+            .sector_identifier_uri
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("sector_identifier_uri")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let backchannel_logout_uri = self
+            .backchannel_logout_uri
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("backchannel_logout_uri")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let frontchannel_logout_uri = self
+            .frontchannel_logout_uri
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("frontchannel_logout_uri")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let backchannel_token_delivery_mode = self
+            .backchannel_token_delivery_mode
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("backchannel_token_delivery_mode")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let backchannel_client_notification_endpoint = self
+            .backchannel_client_notification_endpoint
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("backchannel_client_notification_endpoint")
+                    .row(id)
+                    .source(e)
+            })?;
+
         let jwks = match (self.jwks, self.jwks_uri) {
             (None, None) => None,
             (Some(jwks), None) => {
@@ -240,9 +474,33 @@ impl TryInto<Client> for OAuth2ClientLookup {
             jwks,
             id_token_signed_response_alg,
             userinfo_signed_response_alg,
+            introspection_signed_response_alg,
+            id_token_encrypted_response_alg,
+            id_token_encrypted_response_enc,
+            userinfo_encrypted_response_alg,
+            userinfo_encrypted_response_enc,
+            introspection_encrypted_response_alg,
+            introspection_encrypted_response_enc,
+            authorization_signed_response_alg,
+            authorization_encrypted_response_alg,
+            authorization_encrypted_response_enc,
             token_endpoint_auth_method,
             token_endpoint_auth_signing_alg,
             initiate_login_uri,
+            require_pushed_authorization_requests: self.require_pushed_authorization_requests,
+            subject_type,
+            sector_identifier_uri,
+            post_logout_redirect_uris,
+            request_uris,
+            backchannel_logout_uri,
+            frontchannel_logout_uri,
+            frontchannel_logout_session_required: self.frontchannel_logout_session_required,
+            dpop_bound_access_tokens: self.dpop_bound_access_tokens,
+            tls_client_auth_subject_dn: self.tls_client_auth_subject_dn,
+            tls_client_auth_san_dns: self.tls_client_auth_san_dns,
+            backchannel_token_delivery_mode,
+            backchannel_client_notification_endpoint,
+            registration_access_token_hash: self.registration_access_token_hash,
         })
     }
 }
@@ -273,6 +531,9 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , grant_type_refresh_token
                      , grant_type_client_credentials
                      , grant_type_device_code
+                     , grant_type_token_exchange
+                     , grant_type_jwt_bearer
+                     , grant_type_ciba
                      , client_name
                      , logo_uri
                      , client_uri
@@ -285,6 +546,30 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , token_endpoint_auth_method
                      , token_endpoint_auth_signing_alg
                      , initiate_login_uri
+                     , require_pushed_authorization_requests
+                     , subject_type
+                     , sector_identifier_uri
+                     , post_logout_redirect_uris
+                     , request_uris
+                     , backchannel_logout_uri
+                     , frontchannel_logout_uri
+                     , frontchannel_logout_session_required
+                     , dpop_bound_access_tokens
+                     , tls_client_auth_subject_dn
+                     , tls_client_auth_san_dns
+                     , introspection_signed_response_alg
+                     , backchannel_token_delivery_mode
+                     , backchannel_client_notification_endpoint
+                     , registration_access_token_hash
+                     , id_token_encrypted_response_alg
+                     , id_token_encrypted_response_enc
+                     , userinfo_encrypted_response_alg
+                     , userinfo_encrypted_response_enc
+                     , introspection_encrypted_response_alg
+                     , introspection_encrypted_response_enc
+                     , authorization_signed_response_alg
+                     , authorization_encrypted_response_alg
+                     , authorization_encrypted_response_enc
                 FROM oauth2_clients c
 
                 WHERE oauth2_client_id = $1
@@ -324,6 +609,9 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                     , grant_type_refresh_token
                     , grant_type_client_credentials
                     , grant_type_device_code
+                    , grant_type_token_exchange
+                    , grant_type_jwt_bearer
+                    , grant_type_ciba
                     , client_name
                     , logo_uri
                     , client_uri
@@ -336,6 +624,30 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                     , token_endpoint_auth_method
                     , token_endpoint_auth_signing_alg
                     , initiate_login_uri
+                    , require_pushed_authorization_requests
+                    , subject_type
+                    , sector_identifier_uri
+                    , post_logout_redirect_uris
+                    , request_uris
+                    , backchannel_logout_uri
+                    , frontchannel_logout_uri
+                    , frontchannel_logout_session_required
+                    , dpop_bound_access_tokens
+                    , tls_client_auth_subject_dn
+                    , tls_client_auth_san_dns
+                    , introspection_signed_response_alg
+                    , backchannel_token_delivery_mode
+                    , backchannel_client_notification_endpoint
+                    , registration_access_token_hash
+                    , id_token_encrypted_response_alg
+                    , id_token_encrypted_response_enc
+                    , userinfo_encrypted_response_alg
+                    , userinfo_encrypted_response_enc
+                    , introspection_encrypted_response_alg
+                    , introspection_encrypted_response_enc
+                    , authorization_signed_response_alg
+                    , authorization_encrypted_response_alg
+                    , authorization_encrypted_response_enc
                 FROM oauth2_clients
                 WHERE metadata_digest = $1
             "#,
@@ -375,6 +687,9 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , grant_type_refresh_token
                      , grant_type_client_credentials
                      , grant_type_device_code
+                     , grant_type_token_exchange
+                     , grant_type_jwt_bearer
+                     , grant_type_ciba
                      , client_name
                      , logo_uri
                      , client_uri
@@ -387,6 +702,30 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , token_endpoint_auth_method
                      , token_endpoint_auth_signing_alg
                      , initiate_login_uri
+                     , require_pushed_authorization_requests
+                     , subject_type
+                     , sector_identifier_uri
+                     , post_logout_redirect_uris
+                     , request_uris
+                     , backchannel_logout_uri
+                     , frontchannel_logout_uri
+                     , frontchannel_logout_session_required
+                     , dpop_bound_access_tokens
+                     , tls_client_auth_subject_dn
+                     , tls_client_auth_san_dns
+                     , introspection_signed_response_alg
+                     , backchannel_token_delivery_mode
+                     , backchannel_client_notification_endpoint
+                     , registration_access_token_hash
+                     , id_token_encrypted_response_alg
+                     , id_token_encrypted_response_enc
+                     , userinfo_encrypted_response_alg
+                     , userinfo_encrypted_response_enc
+                     , introspection_encrypted_response_alg
+                     , introspection_encrypted_response_enc
+                     , authorization_signed_response_alg
+                     , authorization_encrypted_response_alg
+                     , authorization_encrypted_response_enc
                 FROM oauth2_clients c
 
                 WHERE oauth2_client_id = ANY($1::uuid[])
@@ -412,7 +751,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
         fields(
             db.query.text,
             client.id,
-            client.name = client_name
+            client.name = params.client_name
         ),
         err,
     )]
@@ -421,24 +760,52 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
         &mut self,
         rng: &mut (dyn RngCore + Send),
         clock: &dyn Clock,
-        redirect_uris: Vec<Url>,
-        metadata_digest: Option<String>,
-        encrypted_client_secret: Option<String>,
-        application_type: Option<ApplicationType>,
-        grant_types: Vec<GrantType>,
-        client_name: Option<String>,
-        logo_uri: Option<Url>,
-        client_uri: Option<Url>,
-        policy_uri: Option<Url>,
-        tos_uri: Option<Url>,
-        jwks_uri: Option<Url>,
-        jwks: Option<PublicJsonWebKeySet>,
-        id_token_signed_response_alg: Option<JsonWebSignatureAlg>,
-        userinfo_signed_response_alg: Option<JsonWebSignatureAlg>,
-        token_endpoint_auth_method: Option<OAuthClientAuthenticationMethod>,
-        token_endpoint_auth_signing_alg: Option<JsonWebSignatureAlg>,
-        initiate_login_uri: Option<Url>,
+        params: OAuth2ClientParams,
     ) -> Result<Client, Self::Error> {
+        let OAuth2ClientParams {
+            redirect_uris,
+            metadata_digest,
+            encrypted_client_secret,
+            application_type,
+            grant_types,
+            client_name,
+            logo_uri,
+            client_uri,
+            policy_uri,
+            tos_uri,
+            jwks_uri,
+            jwks,
+            id_token_signed_response_alg,
+            userinfo_signed_response_alg,
+            token_endpoint_auth_method,
+            token_endpoint_auth_signing_alg,
+            initiate_login_uri,
+            require_pushed_authorization_requests,
+            subject_type,
+            sector_identifier_uri,
+            post_logout_redirect_uris,
+            request_uris,
+            backchannel_logout_uri,
+            frontchannel_logout_uri,
+            frontchannel_logout_session_required,
+            dpop_bound_access_tokens,
+            tls_client_auth_subject_dn,
+            tls_client_auth_san_dns,
+            introspection_signed_response_alg,
+            backchannel_token_delivery_mode,
+            backchannel_client_notification_endpoint,
+            registration_access_token_hash,
+            id_token_encrypted_response_alg,
+            id_token_encrypted_response_enc,
+            userinfo_encrypted_response_alg,
+            userinfo_encrypted_response_enc,
+            introspection_encrypted_response_alg,
+            introspection_encrypted_response_enc,
+            authorization_signed_response_alg,
+            authorization_encrypted_response_alg,
+            authorization_encrypted_response_enc,
+        } = params;
+
         let now = clock.now();
         let id = Ulid::from_datetime_with_source(now.into(), rng);
         tracing::Span::current().record("client.id", tracing::field::display(id));
@@ -450,6 +817,11 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             .map_err(DatabaseError::to_invalid_operation)?;
 
         let redirect_uris_array = redirect_uris.iter().map(Url::to_string).collect::<Vec<_>>();
+        let post_logout_redirect_uris_array = post_logout_redirect_uris
+            .iter()
+            .map(Url::to_string)
+            .collect::<Vec<_>>();
+        let request_uris_array = request_uris.iter().map(Url::to_string).collect::<Vec<_>>();
 
         sqlx::query!(
             r#"
@@ -463,6 +835,8 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                     , grant_type_refresh_token
                     , grant_type_client_credentials
                     , grant_type_device_code
+                    , grant_type_token_exchange
+                    , grant_type_jwt_bearer
                     , client_name
                     , logo_uri
                     , client_uri
@@ -475,11 +849,38 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                     , token_endpoint_auth_method
                     , token_endpoint_auth_signing_alg
                     , initiate_login_uri
+                    , require_pushed_authorization_requests
+                    , subject_type
+                    , sector_identifier_uri
+                    , post_logout_redirect_uris
+                    , backchannel_logout_uri
+                    , frontchannel_logout_uri
+                    , frontchannel_logout_session_required
+                    , dpop_bound_access_tokens
+                    , tls_client_auth_subject_dn
+                    , tls_client_auth_san_dns
+                    , introspection_signed_response_alg
+                    , grant_type_ciba
+                    , backchannel_token_delivery_mode
+                    , backchannel_client_notification_endpoint
+                    , registration_access_token_hash
+                    , id_token_encrypted_response_alg
+                    , id_token_encrypted_response_enc
+                    , userinfo_encrypted_response_alg
+                    , userinfo_encrypted_response_enc
+                    , introspection_encrypted_response_alg
+                    , introspection_encrypted_response_enc
+                    , authorization_signed_response_alg
+                    , authorization_encrypted_response_alg
+                    , authorization_encrypted_response_enc
+                    , request_uris
                     , is_static
                     )
                 VALUES
                     ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13,
-                    $14, $15, $16, $17, $18, $19, $20, $21, FALSE)
+                    $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26,
+                    $27, $28, $29, $30, $31, $32, $33, $34, $35, $36, $37, $38, $39,
+                    $40, $41, $42, $43, $44, $45, $46, $47, $48, FALSE)
             "#,
             Uuid::from(id),
             metadata_digest,
@@ -490,6 +891,8 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             grant_types.contains(&GrantType::RefreshToken),
             grant_types.contains(&GrantType::ClientCredentials),
             grant_types.contains(&GrantType::DeviceCode),
+            grant_types.contains(&GrantType::TokenExchange),
+            grant_types.contains(&GrantType::JwtBearer),
             client_name,
             logo_uri.as_ref().map(Url::as_str),
             client_uri.as_ref().map(Url::as_str),
@@ -508,6 +911,55 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                 .as_ref()
                 .map(ToString::to_string),
             initiate_login_uri.as_ref().map(Url::as_str),
+            require_pushed_authorization_requests,
+            subject_type.as_ref().map(ToString::to_string),
+            sector_identifier_uri.as_ref().map(Url::as_str),
+            &post_logout_redirect_uris_array,
+            backchannel_logout_uri.as_ref().map(Url::as_str),
+            frontchannel_logout_uri.as_ref().map(Url::as_str),
+            frontchannel_logout_session_required,
+            dpop_bound_access_tokens,
+            tls_client_auth_subject_dn.as_deref(),
+            tls_client_auth_san_dns.as_deref(),
+            introspection_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            grant_types.contains(&GrantType::ClientInitiatedBackchannelAuthentication),
+            backchannel_token_delivery_mode
+                .as_ref()
+                .map(ToString::to_string),
+            backchannel_client_notification_endpoint
+                .as_ref()
+                .map(Url::as_str),
+            registration_access_token_hash.as_deref(),
+            id_token_encrypted_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            id_token_encrypted_response_enc
+                .as_ref()
+                .map(ToString::to_string),
+            userinfo_encrypted_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            userinfo_encrypted_response_enc
+                .as_ref()
+                .map(ToString::to_string),
+            introspection_encrypted_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            introspection_encrypted_response_enc
+                .as_ref()
+                .map(ToString::to_string),
+            authorization_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            authorization_encrypted_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            authorization_encrypted_response_enc
+                .as_ref()
+                .map(ToString::to_string),
+            &request_uris_array,
         )
         .traced()
         .execute(&mut *self.conn)
@@ -536,31 +988,265 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             jwks,
             id_token_signed_response_alg,
             userinfo_signed_response_alg,
+            introspection_signed_response_alg,
+            id_token_encrypted_response_alg,
+            id_token_encrypted_response_enc,
+            userinfo_encrypted_response_alg,
+            userinfo_encrypted_response_enc,
+            introspection_encrypted_response_alg,
+            introspection_encrypted_response_enc,
+            authorization_signed_response_alg,
+            authorization_encrypted_response_alg,
+            authorization_encrypted_response_enc,
             token_endpoint_auth_method,
             token_endpoint_auth_signing_alg,
             initiate_login_uri,
+            require_pushed_authorization_requests,
+            subject_type,
+            sector_identifier_uri,
+            post_logout_redirect_uris,
+            request_uris,
+            backchannel_logout_uri,
+            frontchannel_logout_uri,
+            frontchannel_logout_session_required,
+            dpop_bound_access_tokens,
+            tls_client_auth_subject_dn,
+            tls_client_auth_san_dns,
+            backchannel_token_delivery_mode,
+            backchannel_client_notification_endpoint,
+            registration_access_token_hash,
         })
     }
 
+    #[tracing::instrument(
+        name = "db.oauth2_client.update",
+        skip_all,
+        fields(
+            db.query.text,
+            %client.id,
+        ),
+        err,
+    )]
+    #[allow(clippy::too_many_lines)]
+    async fn update(&mut self, client: Client) -> Result<Client, Self::Error> {
+        let (jwks, jwks_uri) = match &client.jwks {
+            None => (None, None),
+            Some(JwksOrJwksUri::Jwks(jwks)) => (
+                Some(serde_json::to_value(jwks).map_err(DatabaseError::to_invalid_operation)?),
+                None,
+            ),
+            Some(JwksOrJwksUri::JwksUri(jwks_uri)) => (None, Some(jwks_uri.as_str())),
+        };
+
+        let redirect_uris_array = client
+            .redirect_uris
+            .iter()
+            .map(Url::to_string)
+            .collect::<Vec<_>>();
+        let post_logout_redirect_uris_array = client
+            .post_logout_redirect_uris
+            .iter()
+            .map(Url::to_string)
+            .collect::<Vec<_>>();
+        let request_uris_array = client
+            .request_uris
+            .iter()
+            .map(Url::to_string)
+            .collect::<Vec<_>>();
+
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_clients
+                SET metadata_digest = $2
+                  , encrypted_client_secret = $3
+                  , application_type = $4
+                  , redirect_uris = $5
+                  , grant_type_authorization_code = $6
+                  , grant_type_refresh_token = $7
+                  , grant_type_client_credentials = $8
+                  , grant_type_device_code = $9
+                  , grant_type_token_exchange = $10
+                  , grant_type_jwt_bearer = $11
+                  , client_name = $12
+                  , logo_uri = $13
+                  , client_uri = $14
+                  , policy_uri = $15
+                  , tos_uri = $16
+                  , jwks_uri = $17
+                  , jwks = $18
+                  , id_token_signed_response_alg = $19
+                  , userinfo_signed_response_alg = $20
+                  , token_endpoint_auth_method = $21
+                  , token_endpoint_auth_signing_alg = $22
+                  , initiate_login_uri = $23
+                  , require_pushed_authorization_requests = $24
+                  , subject_type = $25
+                  , sector_identifier_uri = $26
+                  , post_logout_redirect_uris = $27
+                  , backchannel_logout_uri = $28
+                  , frontchannel_logout_uri = $29
+                  , frontchannel_logout_session_required = $30
+                  , dpop_bound_access_tokens = $31
+                  , tls_client_auth_subject_dn = $32
+                  , tls_client_auth_san_dns = $33
+                  , introspection_signed_response_alg = $34
+                  , grant_type_ciba = $35
+                  , backchannel_token_delivery_mode = $36
+                  , backchannel_client_notification_endpoint = $37
+                  , registration_access_token_hash = $38
+                  , id_token_encrypted_response_alg = $39
+                  , id_token_encrypted_response_enc = $40
+                  , userinfo_encrypted_response_alg = $41
+                  , userinfo_encrypted_response_enc = $42
+                  , introspection_encrypted_response_alg = $43
+                  , introspection_encrypted_response_enc = $44
+                  , authorization_signed_response_alg = $45
+                  , authorization_encrypted_response_alg = $46
+                  , authorization_encrypted_response_enc = $47
+                  , request_uris = $48
+                WHERE oauth2_client_id = $1
+                  AND is_static = FALSE
+            "#,
+            Uuid::from(client.id),
+            client.metadata_digest,
+            client.encrypted_client_secret,
+            client.application_type.as_ref().map(ToString::to_string),
+            &redirect_uris_array,
+            client.grant_types.contains(&GrantType::AuthorizationCode),
+            client.grant_types.contains(&GrantType::RefreshToken),
+            client.grant_types.contains(&GrantType::ClientCredentials),
+            client.grant_types.contains(&GrantType::DeviceCode),
+            client.grant_types.contains(&GrantType::TokenExchange),
+            client.grant_types.contains(&GrantType::JwtBearer),
+            client.client_name,
+            client.logo_uri.as_ref().map(Url::as_str),
+            client.client_uri.as_ref().map(Url::as_str),
+            client.policy_uri.as_ref().map(Url::as_str),
+            client.tos_uri.as_ref().map(Url::as_str),
+            jwks_uri,
+            jwks,
+            client
+                .id_token_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .userinfo_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .token_endpoint_auth_method
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .token_endpoint_auth_signing_alg
+                .as_ref()
+                .map(ToString::to_string),
+            client.initiate_login_uri.as_ref().map(Url::as_str),
+            client.require_pushed_authorization_requests,
+            client.subject_type.as_ref().map(ToString::to_string),
+            client.sector_identifier_uri.as_ref().map(Url::as_str),
+            &post_logout_redirect_uris_array,
+            client.backchannel_logout_uri.as_ref().map(Url::as_str),
+            client.frontchannel_logout_uri.as_ref().map(Url::as_str),
+            client.frontchannel_logout_session_required,
+            client.dpop_bound_access_tokens,
+            client.tls_client_auth_subject_dn.as_deref(),
+            client.tls_client_auth_san_dns.as_deref(),
+            client
+                .introspection_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .grant_types
+                .contains(&GrantType::ClientInitiatedBackchannelAuthentication),
+            client
+                .backchannel_token_delivery_mode
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .backchannel_client_notification_endpoint
+                .as_ref()
+                .map(Url::as_str),
+            client.registration_access_token_hash.as_deref(),
+            client
+                .id_token_encrypted_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .id_token_encrypted_response_enc
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .userinfo_encrypted_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .userinfo_encrypted_response_enc
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .introspection_encrypted_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .introspection_encrypted_response_enc
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .authorization_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .authorization_encrypted_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            client
+                .authorization_encrypted_response_enc
+                .as_ref()
+                .map(ToString::to_string),
+            &request_uris_array,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(client)
+    }
+
     #[tracing::instrument(
         name = "db.oauth2_client.upsert_static",
         skip_all,
         fields(
             db.query.text,
-            client.id = %client_id,
+            client.id = %params.client_id,
         ),
         err,
     )]
     async fn upsert_static(
         &mut self,
-        client_id: Ulid,
-        client_name: Option<String>,
-        client_auth_method: OAuthClientAuthenticationMethod,
-        encrypted_client_secret: Option<String>,
-        jwks: Option<PublicJsonWebKeySet>,
-        jwks_uri: Option<Url>,
-        redirect_uris: Vec<Url>,
+        params: OAuth2StaticClientParams,
     ) -> Result<Client, Self::Error> {
+        let OAuth2StaticClientParams {
+            client_id,
+            client_name,
+            client_auth_method,
+            encrypted_client_secret,
+            jwks,
+            jwks_uri,
+            redirect_uris,
+            post_logout_redirect_uris,
+            request_uris,
+            backchannel_logout_uri,
+            frontchannel_logout_uri,
+            frontchannel_logout_session_required,
+            dpop_bound_access_tokens,
+            tls_client_auth_subject_dn,
+            tls_client_auth_san_dns,
+            require_pushed_authorization_requests,
+        } = params;
+
         let jwks_json = jwks
             .as_ref()
             .map(serde_json::to_value)
@@ -569,6 +1255,11 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
 
         let client_auth_method = client_auth_method.to_string();
         let redirect_uris_array = redirect_uris.iter().map(Url::to_string).collect::<Vec<_>>();
+        let post_logout_redirect_uris_array = post_logout_redirect_uris
+            .iter()
+            .map(Url::to_string)
+            .collect::<Vec<_>>();
+        let request_uris_array = request_uris.iter().map(Url::to_string).collect::<Vec<_>>();
 
         sqlx::query!(
             r#"
@@ -580,14 +1271,26 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                     , grant_type_refresh_token
                     , grant_type_client_credentials
                     , grant_type_device_code
+                    , grant_type_token_exchange
+                    , grant_type_jwt_bearer
                     , token_endpoint_auth_method
                     , jwks
                     , client_name
                     , jwks_uri
+                    , require_pushed_authorization_requests
+                    , post_logout_redirect_uris
+                    , backchannel_logout_uri
+                    , frontchannel_logout_uri
+                    , frontchannel_logout_session_required
+                    , dpop_bound_access_tokens
+                    , tls_client_auth_subject_dn
+                    , tls_client_auth_san_dns
+                    , grant_type_ciba
+                    , request_uris
                     , is_static
                     )
                 VALUES
-                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, TRUE)
+                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, TRUE)
                 ON CONFLICT (oauth2_client_id)
                 DO
                     UPDATE SET encrypted_client_secret = EXCLUDED.encrypted_client_secret
@@ -596,10 +1299,22 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                              , grant_type_refresh_token = EXCLUDED.grant_type_refresh_token
                              , grant_type_client_credentials = EXCLUDED.grant_type_client_credentials
                              , grant_type_device_code = EXCLUDED.grant_type_device_code
+                             , grant_type_token_exchange = EXCLUDED.grant_type_token_exchange
+                             , grant_type_jwt_bearer = EXCLUDED.grant_type_jwt_bearer
                              , token_endpoint_auth_method = EXCLUDED.token_endpoint_auth_method
                              , jwks = EXCLUDED.jwks
                              , client_name = EXCLUDED.client_name
                              , jwks_uri = EXCLUDED.jwks_uri
+                             , require_pushed_authorization_requests = EXCLUDED.require_pushed_authorization_requests
+                             , post_logout_redirect_uris = EXCLUDED.post_logout_redirect_uris
+                             , backchannel_logout_uri = EXCLUDED.backchannel_logout_uri
+                             , frontchannel_logout_uri = EXCLUDED.frontchannel_logout_uri
+                             , frontchannel_logout_session_required = EXCLUDED.frontchannel_logout_session_required
+                             , dpop_bound_access_tokens = EXCLUDED.dpop_bound_access_tokens
+                             , tls_client_auth_subject_dn = EXCLUDED.tls_client_auth_subject_dn
+                             , tls_client_auth_san_dns = EXCLUDED.tls_client_auth_san_dns
+                             , grant_type_ciba = EXCLUDED.grant_type_ciba
+                             , request_uris = EXCLUDED.request_uris
                              , is_static = TRUE
             "#,
             Uuid::from(client_id),
@@ -609,10 +1324,22 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             true,
             true,
             true,
+            true,
+            true,
             client_auth_method,
             jwks_json,
             client_name,
             jwks_uri.as_ref().map(Url::as_str),
+            require_pushed_authorization_requests,
+            &post_logout_redirect_uris_array,
+            backchannel_logout_uri.as_ref().map(Url::as_str),
+            frontchannel_logout_uri.as_ref().map(Url::as_str),
+            frontchannel_logout_session_required,
+            dpop_bound_access_tokens,
+            tls_client_auth_subject_dn.as_deref(),
+            tls_client_auth_san_dns.as_deref(),
+            true,
+            &request_uris_array,
         )
         .traced()
         .execute(&mut *self.conn)
@@ -645,9 +1372,33 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             jwks,
             id_token_signed_response_alg: None,
             userinfo_signed_response_alg: None,
+            introspection_signed_response_alg: None,
             token_endpoint_auth_method: None,
             token_endpoint_auth_signing_alg: None,
             initiate_login_uri: None,
+            require_pushed_authorization_requests,
+            subject_type: None,
+            sector_identifier_uri: None,
+            post_logout_redirect_uris,
+            request_uris,
+            backchannel_logout_uri,
+            frontchannel_logout_uri,
+            frontchannel_logout_session_required,
+            dpop_bound_access_tokens,
+            tls_client_auth_subject_dn,
+            tls_client_auth_san_dns,
+            backchannel_token_delivery_mode: None,
+            backchannel_client_notification_endpoint: None,
+            registration_access_token_hash: None,
+            id_token_encrypted_response_alg: None,
+            id_token_encrypted_response_enc: None,
+            userinfo_encrypted_response_alg: None,
+            userinfo_encrypted_response_enc: None,
+            introspection_encrypted_response_alg: None,
+            introspection_encrypted_response_enc: None,
+            authorization_signed_response_alg: None,
+            authorization_encrypted_response_alg: None,
+            authorization_encrypted_response_enc: None,
         })
     }
 
@@ -672,6 +1423,9 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , grant_type_refresh_token
                      , grant_type_client_credentials
                      , grant_type_device_code
+                     , grant_type_token_exchange
+                     , grant_type_jwt_bearer
+                     , grant_type_ciba
                      , client_name
                      , logo_uri
                      , client_uri
@@ -684,6 +1438,30 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , token_endpoint_auth_method
                      , token_endpoint_auth_signing_alg
                      , initiate_login_uri
+                     , require_pushed_authorization_requests
+                     , subject_type
+                     , sector_identifier_uri
+                     , post_logout_redirect_uris
+                     , request_uris
+                     , backchannel_logout_uri
+                     , frontchannel_logout_uri
+                     , frontchannel_logout_session_required
+                     , dpop_bound_access_tokens
+                     , tls_client_auth_subject_dn
+                     , tls_client_auth_san_dns
+                     , introspection_signed_response_alg
+                     , backchannel_token_delivery_mode
+                     , backchannel_client_notification_endpoint
+                     , registration_access_token_hash
+                     , id_token_encrypted_response_alg
+                     , id_token_encrypted_response_enc
+                     , userinfo_encrypted_response_alg
+                     , userinfo_encrypted_response_enc
+                     , introspection_encrypted_response_alg
+                     , introspection_encrypted_response_enc
+                     , authorization_signed_response_alg
+                     , authorization_encrypted_response_alg
+                     , authorization_encrypted_response_enc
                 FROM oauth2_clients c
                 WHERE is_static = TRUE
             "#,
@@ -771,6 +1549,29 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             .await?;
         }
 
+        {
+            let span = info_span!(
+                "db.oauth2_client.delete_by_id.refresh_token_reuses",
+                { DB_QUERY_TEXT } = tracing::field::Empty,
+            );
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM oauth2_refresh_token_reuses
+                    WHERE oauth2_session_id IN (
+                        SELECT oauth2_session_id
+                        FROM oauth2_sessions
+                        WHERE oauth2_client_id = $1
+                    )
+                "#,
+                Uuid::from(id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span)
+            .await?;
+        }
+
         {
             let span = info_span!(
                 "db.oauth2_client.delete_by_id.refresh_tokens",
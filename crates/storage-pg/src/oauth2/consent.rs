@@ -0,0 +1,347 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mas_data_model::{Client, Consent, User};
+use mas_storage::{
+    Clock, Page, Pagination,
+    oauth2::{OAuth2ConsentFilter, OAuth2ConsentRepository},
+};
+use oauth2_types::scope::{Scope, ScopeToken};
+use rand::RngCore;
+use sea_query::{Expr, PostgresQueryBuilder, Query, enum_def};
+use sea_query_binder::SqlxBinder;
+use sqlx::PgConnection;
+use ulid::Ulid;
+use uuid::Uuid;
+
+use crate::{
+    DatabaseError, DatabaseInconsistencyError,
+    filter::{Filter, StatementExt},
+    iden::OAuth2Consents,
+    pagination::QueryBuilderExt,
+    tracing::ExecuteExt,
+};
+
+/// An implementation of [`OAuth2ConsentRepository`] for a PostgreSQL
+/// connection
+pub struct PgOAuth2ConsentRepository<'c> {
+    conn: &'c mut PgConnection,
+}
+
+impl<'c> PgOAuth2ConsentRepository<'c> {
+    /// Create a new [`PgOAuth2ConsentRepository`] from an active PostgreSQL
+    /// connection
+    pub fn new(conn: &'c mut PgConnection) -> Self {
+        Self { conn }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+#[enum_def]
+struct ConsentLookup {
+    oauth2_consent_id: Uuid,
+    user_id: Uuid,
+    oauth2_client_id: Uuid,
+    scope_list: Vec<String>,
+    created_at: DateTime<Utc>,
+    refreshed_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<ConsentLookup> for Consent {
+    type Error = DatabaseInconsistencyError;
+
+    fn try_from(value: ConsentLookup) -> Result<Self, Self::Error> {
+        let id = Ulid::from(value.oauth2_consent_id);
+        let scope: Result<Scope, _> = value
+            .scope_list
+            .iter()
+            .map(|s| s.parse::<ScopeToken>())
+            .collect();
+        let scope = scope.map_err(|e| {
+            DatabaseInconsistencyError::on("oauth2_consents")
+                .column("scope_list")
+                .row(id)
+                .source(e)
+        })?;
+
+        Ok(Consent {
+            id,
+            user_id: value.user_id.into(),
+            client_id: value.oauth2_client_id.into(),
+            scope,
+            created_at: value.created_at,
+            refreshed_at: value.refreshed_at,
+        })
+    }
+}
+
+impl Filter for OAuth2ConsentFilter<'_> {
+    fn generate_condition(&self, _has_joins: bool) -> impl sea_query::IntoCondition {
+        sea_query::Condition::all()
+            .add_option(self.user().map(|user| {
+                Expr::col((OAuth2Consents::Table, OAuth2Consents::UserId)).eq(Uuid::from(user.id))
+            }))
+            .add_option(self.client().map(|client| {
+                Expr::col((OAuth2Consents::Table, OAuth2Consents::OAuth2ClientId))
+                    .eq(Uuid::from(client.id))
+            }))
+    }
+}
+
+#[async_trait]
+impl OAuth2ConsentRepository for PgOAuth2ConsentRepository<'_> {
+    type Error = DatabaseError;
+
+    #[tracing::instrument(
+        name = "db.oauth2_consent.lookup",
+        skip_all,
+        fields(
+            db.query.text,
+            oauth2_consent.id = %id,
+        ),
+        err,
+    )]
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<Consent>, Self::Error> {
+        let res = sqlx::query_as!(
+            ConsentLookup,
+            r#"
+                SELECT oauth2_consent_id
+                     , user_id
+                     , oauth2_client_id
+                     , scope_list
+                     , created_at
+                     , refreshed_at
+                FROM oauth2_consents
+
+                WHERE oauth2_consent_id = $1
+            "#,
+            Uuid::from(id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        let Some(res) = res else { return Ok(None) };
+
+        Ok(Some(res.try_into()?))
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_consent.find",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+            %client.id,
+        ),
+        err,
+    )]
+    async fn find(&mut self, user: &User, client: &Client) -> Result<Option<Consent>, Self::Error> {
+        let res = sqlx::query_as!(
+            ConsentLookup,
+            r#"
+                SELECT oauth2_consent_id
+                     , user_id
+                     , oauth2_client_id
+                     , scope_list
+                     , created_at
+                     , refreshed_at
+                FROM oauth2_consents
+
+                WHERE user_id = $1
+                  AND oauth2_client_id = $2
+            "#,
+            Uuid::from(user.id),
+            Uuid::from(client.id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        let Some(res) = res else { return Ok(None) };
+
+        Ok(Some(res.try_into()?))
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_consent.give",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+            %client.id,
+            oauth2_consent.id,
+            oauth2_consent.scope = %scope,
+        ),
+        err,
+    )]
+    async fn give(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        client: &Client,
+        scope: &Scope,
+    ) -> Result<Consent, Self::Error> {
+        let now = clock.now();
+        let id = Ulid::from_datetime_with_source(now.into(), rng);
+        let scope_list: Vec<String> = Consent::remembered_scope(scope)
+            .iter()
+            .map(|s| s.as_str().to_owned())
+            .collect();
+
+        // If the user already consented to this client, merge the scope tokens
+        // into the existing consent
+        let res = sqlx::query_as!(
+            ConsentLookup,
+            r#"
+                INSERT INTO oauth2_consents
+                    ( oauth2_consent_id
+                    , user_id
+                    , oauth2_client_id
+                    , scope_list
+                    , created_at
+                    )
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (oauth2_client_id, user_id) DO UPDATE
+                SET scope_list = ARRAY(
+                        SELECT DISTINCT unnest(oauth2_consents.scope_list || EXCLUDED.scope_list)
+                        ORDER BY 1
+                    )
+                  , refreshed_at = EXCLUDED.created_at
+                RETURNING oauth2_consent_id
+                        , user_id
+                        , oauth2_client_id
+                        , scope_list as "scope_list!"
+                        , created_at
+                        , refreshed_at
+            "#,
+            Uuid::from(id),
+            Uuid::from(user.id),
+            Uuid::from(client.id),
+            &scope_list,
+            now,
+        )
+        .traced()
+        .fetch_one(&mut *self.conn)
+        .await?;
+
+        let consent = Consent::try_from(res)?;
+        tracing::Span::current().record("oauth2_consent.id", tracing::field::display(consent.id));
+
+        Ok(consent)
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_consent.list",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn list(
+        &mut self,
+        filter: OAuth2ConsentFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<Consent>, Self::Error> {
+        let (sql, arguments) = Query::select()
+            .expr_as(
+                Expr::col((OAuth2Consents::Table, OAuth2Consents::OAuth2ConsentId)),
+                ConsentLookupIden::Oauth2ConsentId,
+            )
+            .expr_as(
+                Expr::col((OAuth2Consents::Table, OAuth2Consents::UserId)),
+                ConsentLookupIden::UserId,
+            )
+            .expr_as(
+                Expr::col((OAuth2Consents::Table, OAuth2Consents::OAuth2ClientId)),
+                ConsentLookupIden::Oauth2ClientId,
+            )
+            .expr_as(
+                Expr::col((OAuth2Consents::Table, OAuth2Consents::ScopeList)),
+                ConsentLookupIden::ScopeList,
+            )
+            .expr_as(
+                Expr::col((OAuth2Consents::Table, OAuth2Consents::CreatedAt)),
+                ConsentLookupIden::CreatedAt,
+            )
+            .expr_as(
+                Expr::col((OAuth2Consents::Table, OAuth2Consents::RefreshedAt)),
+                ConsentLookupIden::RefreshedAt,
+            )
+            .from(OAuth2Consents::Table)
+            .apply_filter(filter)
+            .generate_pagination(
+                (OAuth2Consents::Table, OAuth2Consents::OAuth2ConsentId),
+                pagination,
+            )
+            .build_sqlx(PostgresQueryBuilder);
+
+        let edges: Vec<ConsentLookup> = sqlx::query_as_with(&sql, arguments)
+            .traced()
+            .fetch_all(&mut *self.conn)
+            .await?;
+
+        let page = pagination.process(edges).try_map(Consent::try_from)?;
+
+        Ok(page)
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_consent.count",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn count(&mut self, filter: OAuth2ConsentFilter<'_>) -> Result<usize, Self::Error> {
+        let (sql, arguments) = Query::select()
+            .expr(Expr::col((OAuth2Consents::Table, OAuth2Consents::OAuth2ConsentId)).count())
+            .from(OAuth2Consents::Table)
+            .apply_filter(filter)
+            .build_sqlx(PostgresQueryBuilder);
+
+        let count: i64 = sqlx::query_scalar_with(&sql, arguments)
+            .traced()
+            .fetch_one(&mut *self.conn)
+            .await?;
+
+        count
+            .try_into()
+            .map_err(DatabaseError::to_invalid_operation)
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_consent.revoke",
+        skip_all,
+        fields(
+            db.query.text,
+            %consent.id,
+            user.id = %consent.user_id,
+            client.id = %consent.client_id,
+        ),
+        err,
+    )]
+    async fn revoke(&mut self, consent: Consent) -> Result<(), Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM oauth2_consents
+                WHERE oauth2_consent_id = $1
+            "#,
+            Uuid::from(consent.id),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(())
+    }
+}
@@ -8,7 +8,7 @@ use std::net::IpAddr;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use mas_data_model::{BrowserSession, DeviceCodeGrant, DeviceCodeGrantState, Session};
+use mas_data_model::{BrowserSession, DeviceCodeGrant, DeviceCodeGrantState, Session, User};
 use mas_storage::{
     Clock,
     oauth2::{OAuth2DeviceCodeGrantParams, OAuth2DeviceCodeGrantRepository},
@@ -50,6 +50,9 @@ struct OAuth2DeviceGrantLookup {
     oauth2_session_id: Option<Uuid>,
     ip_address: Option<IpAddr>,
     user_agent: Option<String>,
+    user_id: Option<Uuid>,
+    binding_message: Option<String>,
+    client_notification_token: Option<String>,
 }
 
 impl TryFrom<OAuth2DeviceGrantLookup> for DeviceCodeGrant {
@@ -71,6 +74,9 @@ impl TryFrom<OAuth2DeviceGrantLookup> for DeviceCodeGrant {
             oauth2_session_id,
             ip_address,
             user_agent,
+            user_id,
+            binding_message,
+            client_notification_token,
         }: OAuth2DeviceGrantLookup,
     ) -> Result<Self, Self::Error> {
         let id = Ulid::from(oauth2_device_code_grant_id);
@@ -133,6 +139,9 @@ impl TryFrom<OAuth2DeviceGrantLookup> for DeviceCodeGrant {
             expires_at,
             ip_address,
             user_agent,
+            user_id: user_id.map(Ulid::from),
+            binding_message,
+            client_notification_token,
         })
     }
 }
@@ -178,9 +187,12 @@ impl OAuth2DeviceCodeGrantRepository for PgOAuth2DeviceCodeGrantRepository<'_> {
                     , expires_at
                     , ip_address
                     , user_agent
+                    , user_id
+                    , binding_message
+                    , client_notification_token
                     )
                 VALUES
-                    ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             "#,
             Uuid::from(id),
             Uuid::from(client_id),
@@ -191,6 +203,9 @@ impl OAuth2DeviceCodeGrantRepository for PgOAuth2DeviceCodeGrantRepository<'_> {
             expires_at,
             params.ip_address as Option<IpAddr>,
             params.user_agent.as_deref(),
+            params.user.map(|user| Uuid::from(user.id)),
+            params.binding_message.as_deref(),
+            params.client_notification_token.as_deref(),
         )
         .traced()
         .execute(&mut *self.conn)
@@ -207,6 +222,9 @@ impl OAuth2DeviceCodeGrantRepository for PgOAuth2DeviceCodeGrantRepository<'_> {
             expires_at,
             ip_address: params.ip_address,
             user_agent: params.user_agent,
+            user_id: params.user.map(|user| user.id),
+            binding_message: params.binding_message,
+            client_notification_token: params.client_notification_token,
         })
     }
 
@@ -237,6 +255,9 @@ impl OAuth2DeviceCodeGrantRepository for PgOAuth2DeviceCodeGrantRepository<'_> {
                      , oauth2_session_id
                      , ip_address as "ip_address: IpAddr"
                      , user_agent
+                     , user_id
+                     , binding_message
+                     , client_notification_token
                 FROM
                     oauth2_device_code_grant
 
@@ -283,6 +304,9 @@ impl OAuth2DeviceCodeGrantRepository for PgOAuth2DeviceCodeGrantRepository<'_> {
                      , oauth2_session_id
                      , ip_address as "ip_address: IpAddr"
                      , user_agent
+                     , user_id
+                     , binding_message
+                     , client_notification_token
                 FROM
                     oauth2_device_code_grant
 
@@ -329,6 +353,9 @@ impl OAuth2DeviceCodeGrantRepository for PgOAuth2DeviceCodeGrantRepository<'_> {
                      , oauth2_session_id
                      , ip_address as "ip_address: IpAddr"
                      , user_agent
+                     , user_id
+                     , binding_message
+                     , client_notification_token
                 FROM
                     oauth2_device_code_grant
 
@@ -345,6 +372,62 @@ impl OAuth2DeviceCodeGrantRepository for PgOAuth2DeviceCodeGrantRepository<'_> {
         Ok(Some(res.try_into()?))
     }
 
+    #[tracing::instrument(
+        name = "db.oauth2_device_code_grant.list_pending_for_user",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+        ),
+        err,
+    )]
+    async fn list_pending_for_user(
+        &mut self,
+        clock: &dyn Clock,
+        user: &User,
+    ) -> Result<Vec<DeviceCodeGrant>, Self::Error> {
+        let res = sqlx::query_as!(
+            OAuth2DeviceGrantLookup,
+            r#"
+                SELECT oauth2_device_code_grant_id
+                     , oauth2_client_id
+                     , scope
+                     , device_code
+                     , user_code
+                     , created_at
+                     , expires_at
+                     , fulfilled_at
+                     , rejected_at
+                     , exchanged_at
+                     , user_session_id
+                     , oauth2_session_id
+                     , ip_address as "ip_address: IpAddr"
+                     , user_agent
+                     , user_id
+                     , binding_message
+                     , client_notification_token
+                FROM
+                    oauth2_device_code_grant
+
+                WHERE user_id = $1
+                  AND fulfilled_at IS NULL
+                  AND rejected_at IS NULL
+                  AND expires_at > $2
+                ORDER BY created_at ASC
+            "#,
+            Uuid::from(user.id),
+            clock.now(),
+        )
+        .traced()
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        res.into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()
+            .map_err(DatabaseError::from)
+    }
+
     #[tracing::instrument(
         name = "db.oauth2_device_code_grant.fulfill",
         skip_all,
@@ -10,31 +10,41 @@
 mod access_token;
 mod authorization_grant;
 mod client;
+mod consent;
 mod device_code_grant;
+mod pushed_authorization_request;
 mod refresh_token;
 mod session;
+mod used_jwt_id;
 
 pub use self::{
     access_token::PgOAuth2AccessTokenRepository,
     authorization_grant::PgOAuth2AuthorizationGrantRepository, client::PgOAuth2ClientRepository,
-    device_code_grant::PgOAuth2DeviceCodeGrantRepository,
+    consent::PgOAuth2ConsentRepository, device_code_grant::PgOAuth2DeviceCodeGrantRepository,
+    pushed_authorization_request::PgOAuth2PushedAuthorizationRequestRepository,
     refresh_token::PgOAuth2RefreshTokenRepository, session::PgOAuth2SessionRepository,
+    used_jwt_id::PgOAuth2UsedJwtIdRepository,
 };
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use chrono::Duration;
-    use mas_data_model::AuthorizationCode;
+    use mas_data_model::{AuthorizationCode, Device};
     use mas_storage::{
         Clock, Pagination,
         clock::MockClock,
-        oauth2::{OAuth2DeviceCodeGrantParams, OAuth2SessionFilter, OAuth2SessionRepository},
+        oauth2::{
+            OAuth2AuthorizationGrantParams, OAuth2ClientParams, OAuth2ConsentFilter,
+            OAuth2DeviceCodeGrantParams, OAuth2SessionFilter, OAuth2SessionRepository,
+        },
     };
     use oauth2_types::{
-        requests::{GrantType, ResponseMode},
+        requests::{ActorClaim, AuthorizationDetails, ClaimsParameter, GrantType, ResponseMode},
         scope::{EMAIL, OPENID, PROFILE, Scope},
     };
-    use rand::SeedableRng;
+    use rand::{Rng, SeedableRng, distributions::Alphanumeric};
     use rand_chacha::ChaChaRng;
     use sqlx::PgPool;
     use ulid::Ulid;
@@ -65,23 +75,18 @@ mod tests {
             .add(
                 &mut rng,
                 &clock,
-                vec!["https://example.com/redirect".parse().unwrap()],
-                None,
-                None,
-                None,
-                vec![GrantType::AuthorizationCode],
-                Some("Test client".to_owned()),
-                Some("https://example.com/logo.png".parse().unwrap()),
-                Some("https://example.com/".parse().unwrap()),
-                Some("https://example.com/policy".parse().unwrap()),
-                Some("https://example.com/tos".parse().unwrap()),
-                Some("https://example.com/jwks.json".parse().unwrap()),
-                None,
-                None,
-                None,
-                None,
-                None,
-                Some("https://example.com/login".parse().unwrap()),
+                OAuth2ClientParams {
+                    redirect_uris: vec!["https://example.com/redirect".parse().unwrap()],
+                    grant_types: vec![GrantType::AuthorizationCode],
+                    client_name: Some("Test client".to_owned()),
+                    logo_uri: Some("https://example.com/logo.png".parse().unwrap()),
+                    client_uri: Some("https://example.com/".parse().unwrap()),
+                    policy_uri: Some("https://example.com/policy".parse().unwrap()),
+                    tos_uri: Some("https://example.com/tos".parse().unwrap()),
+                    jwks_uri: Some("https://example.com/jwks.json".parse().unwrap()),
+                    initiate_login_uri: Some("https://example.com/login".parse().unwrap()),
+                    ..Default::default()
+                },
             )
             .await
             .unwrap();
@@ -104,6 +109,27 @@ mod tests {
             .expect("client not found");
         assert_eq!(client, client_lookup);
 
+        // Update the client metadata
+        let client = repo
+            .oauth2_client()
+            .update(mas_data_model::Client {
+                client_name: Some("Renamed client".to_owned()),
+                redirect_uris: vec!["https://example.com/callback".parse().unwrap()],
+                registration_access_token_hash: Some("hash".to_owned()),
+                ..client
+            })
+            .await
+            .unwrap();
+
+        let client_lookup = repo
+            .oauth2_client()
+            .lookup(client.id)
+            .await
+            .unwrap()
+            .expect("client not found");
+        assert_eq!(client, client_lookup);
+        assert_eq!(client_lookup.client_name.as_deref(), Some("Renamed client"));
+
         // Lookup a non-existing grant
         let grant = repo
             .oauth2_authorization_grant()
@@ -120,29 +146,49 @@ mod tests {
             .unwrap();
         assert_eq!(grant, None);
 
-        // Create an authorization grant
+        // Create an authorization grant, requesting an individual claim and
+        // fine-grained authorization details
+        let requested_claims: ClaimsParameter =
+            r#"{"id_token":{"email":{"essential":true}}}"#.parse().unwrap();
+        let authorization_details: AuthorizationDetails =
+            r#"[{"type":"payment_initiation","actions":["initiate"]}]"#
+                .parse()
+                .unwrap();
         let grant = repo
             .oauth2_authorization_grant()
             .add(
                 &mut rng,
                 &clock,
-                &client,
-                "https://example.com/redirect".parse().unwrap(),
-                Scope::from_iter([OPENID]),
-                Some(AuthorizationCode {
-                    code: "code".to_owned(),
-                    pkce: None,
-                }),
-                Some("state".to_owned()),
-                Some("nonce".to_owned()),
-                ResponseMode::Query,
-                true,
-                None,
-                None,
+                OAuth2AuthorizationGrantParams {
+                    client: &client,
+                    redirect_uri: "https://example.com/redirect".parse().unwrap(),
+                    scope: Scope::from_iter([OPENID]),
+                    code: Some(AuthorizationCode {
+                        code: "code".to_owned(),
+                        pkce: None,
+                    }),
+                    state: Some("state".to_owned()),
+                    nonce: Some("nonce".to_owned()),
+                    response_mode: ResponseMode::Query,
+                    response_type_id_token: true,
+                    login_hint: None,
+                    locale: None,
+                    requested_claims: Some(requested_claims.clone()),
+                    authorization_details: Some(authorization_details.clone()),
+                    resource: None,
+                    max_age: None,
+                    acr_values: Vec::new(),
+                    requires_consent: false,
+                },
             )
             .await
             .unwrap();
         assert!(grant.is_pending());
+        assert_eq!(grant.requested_claims, Some(requested_claims.clone()));
+        assert_eq!(
+            grant.authorization_details,
+            Some(authorization_details.clone())
+        );
 
         // Lookup the same grant by id
         let grant_lookup = repo
@@ -190,6 +236,93 @@ mod tests {
             )
             .await
             .unwrap();
+        assert_eq!(session.requested_claims, None);
+
+        // Record the claims the grant requested on the session
+        let session = repo
+            .oauth2_session()
+            .set_requested_claims(session, grant.requested_claims.clone())
+            .await
+            .unwrap();
+        assert_eq!(session.requested_claims, Some(requested_claims));
+
+        // Record the authorization details the grant requested on the session
+        assert_eq!(session.authorization_details, None);
+        let session = repo
+            .oauth2_session()
+            .set_authorization_details(session, grant.authorization_details.clone())
+            .await
+            .unwrap();
+        assert_eq!(session.authorization_details, Some(authorization_details));
+
+        // Bind the session to a DPoP key
+        assert_eq!(session.dpop_jkt, None);
+        let session = repo
+            .oauth2_session()
+            .set_dpop_jkt(
+                session,
+                "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs".to_owned(),
+            )
+            .await
+            .unwrap();
+        let session = repo
+            .oauth2_session()
+            .lookup(session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            session.dpop_jkt.as_deref(),
+            Some("NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs")
+        );
+
+        // Bind the session to a client certificate
+        assert_eq!(session.x5t_s256, None);
+        let session = repo
+            .oauth2_session()
+            .set_x5t_s256(
+                session,
+                "bwcK0esc3ACC3DB2Y5_lESsXE8o9ltc05O89jdN-dg2".to_owned(),
+            )
+            .await
+            .unwrap();
+        let session = repo
+            .oauth2_session()
+            .lookup(session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            session.x5t_s256.as_deref(),
+            Some("bwcK0esc3ACC3DB2Y5_lESsXE8o9ltc05O89jdN-dg2")
+        );
+
+        // Restrict the audience and record an actor
+        assert!(session.audience.is_empty());
+        assert_eq!(session.act, None);
+        let session = repo
+            .oauth2_session()
+            .set_audience(session, vec!["https://example.com/".to_owned()])
+            .await
+            .unwrap();
+        let act = ActorClaim {
+            sub: None,
+            client_id: Some("backend".to_owned()),
+            act: None,
+        };
+        let session = repo
+            .oauth2_session()
+            .set_act(session, act.clone())
+            .await
+            .unwrap();
+        let session = repo
+            .oauth2_session()
+            .lookup(session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.audience, vec!["https://example.com/".to_owned()]);
+        assert_eq!(session.act, Some(act));
 
         // Mark the grant as fulfilled
         let grant = repo
@@ -263,6 +396,39 @@ mod tests {
             .expect("token not found");
         assert_eq!(access_token, access_token_lookup);
 
+        // JWT-formatted access tokens are stored by their hash, so they can be
+        // larger than what the unique index on tokens accepts
+        let payload: String = (&mut rng)
+            .sample_iter(Alphanumeric)
+            .take(8192)
+            .map(char::from)
+            .collect();
+        let jwt = format!("eyJhbGciOiJSUzI1NiIsInR5cCI6ImF0K2p3dCJ9.{payload}.c2lnbmF0dXJl");
+        let jwt_access_token = repo
+            .oauth2_access_token()
+            .add(
+                &mut rng,
+                &clock,
+                &session,
+                jwt.clone(),
+                Some(Duration::try_minutes(5).unwrap()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(jwt_access_token.access_token, jwt);
+
+        let jwt_access_token_lookup = repo
+            .oauth2_access_token()
+            .find_by_token(&jwt)
+            .await
+            .unwrap()
+            .expect("token not found");
+        assert_eq!(jwt_access_token.id, jwt_access_token_lookup.id);
+        repo.oauth2_access_token()
+            .revoke(&clock, jwt_access_token_lookup)
+            .await
+            .unwrap();
+
         // Lookup a non-existing refresh token
         let refresh_token = repo
             .oauth2_refresh_token()
@@ -347,6 +513,56 @@ mod tests {
             .await
             .unwrap();
         assert!(!refresh_token.is_valid());
+        assert_eq!(refresh_token.consumed_at(), Some(clock.now()));
+
+        // Record that the consumed refresh token was presented again
+        let reuse = repo
+            .oauth2_refresh_token()
+            .record_reuse(
+                &mut rng,
+                &clock,
+                &refresh_token,
+                Some("203.0.113.1".parse().unwrap()),
+                Some("Mozilla/5.0".to_owned()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reuse.session_id, session.id);
+        assert_eq!(reuse.refresh_token_id, refresh_token.id);
+
+        // Create another access token, and revoke all the tokens of the session.
+        // Only the new access token and the new refresh token are still valid.
+        repo.oauth2_access_token()
+            .add(&mut rng, &clock, &session, "gghhii".to_owned(), None)
+            .await
+            .unwrap();
+        let revoked = repo
+            .oauth2_access_token()
+            .revoke_for_session(&clock, &session)
+            .await
+            .unwrap();
+        assert_eq!(revoked, 1);
+        let revoked = repo
+            .oauth2_refresh_token()
+            .revoke_for_session(&clock, &session)
+            .await
+            .unwrap();
+        assert_eq!(revoked, 1);
+
+        let new_refresh_token = repo
+            .oauth2_refresh_token()
+            .lookup(new_refresh_token.id)
+            .await
+            .unwrap()
+            .expect("refresh token not found");
+        assert!(!new_refresh_token.is_valid());
+        let access_token = repo
+            .oauth2_access_token()
+            .find_by_token("gghhii")
+            .await
+            .unwrap()
+            .expect("token not found");
+        assert!(!access_token.is_valid(clock.now()));
 
         // Record the user-agent on the session
         assert!(session.user_agent.is_none());
@@ -409,23 +625,18 @@ mod tests {
             .add(
                 &mut rng,
                 &clock,
-                vec!["https://first.example.com/redirect".parse().unwrap()],
-                None,
-                None,
-                None,
-                vec![GrantType::AuthorizationCode],
-                Some("First client".to_owned()),
-                Some("https://first.example.com/logo.png".parse().unwrap()),
-                Some("https://first.example.com/".parse().unwrap()),
-                Some("https://first.example.com/policy".parse().unwrap()),
-                Some("https://first.example.com/tos".parse().unwrap()),
-                Some("https://first.example.com/jwks.json".parse().unwrap()),
-                None,
-                None,
-                None,
-                None,
-                None,
-                Some("https://first.example.com/login".parse().unwrap()),
+                OAuth2ClientParams {
+                    redirect_uris: vec!["https://first.example.com/redirect".parse().unwrap()],
+                    grant_types: vec![GrantType::AuthorizationCode],
+                    client_name: Some("First client".to_owned()),
+                    logo_uri: Some("https://first.example.com/logo.png".parse().unwrap()),
+                    client_uri: Some("https://first.example.com/".parse().unwrap()),
+                    policy_uri: Some("https://first.example.com/policy".parse().unwrap()),
+                    tos_uri: Some("https://first.example.com/tos".parse().unwrap()),
+                    jwks_uri: Some("https://first.example.com/jwks.json".parse().unwrap()),
+                    initiate_login_uri: Some("https://first.example.com/login".parse().unwrap()),
+                    ..Default::default()
+                },
             )
             .await
             .unwrap();
@@ -434,23 +645,18 @@ mod tests {
             .add(
                 &mut rng,
                 &clock,
-                vec!["https://second.example.com/redirect".parse().unwrap()],
-                None,
-                None,
-                None,
-                vec![GrantType::AuthorizationCode],
-                Some("Second client".to_owned()),
-                Some("https://second.example.com/logo.png".parse().unwrap()),
-                Some("https://second.example.com/".parse().unwrap()),
-                Some("https://second.example.com/policy".parse().unwrap()),
-                Some("https://second.example.com/tos".parse().unwrap()),
-                Some("https://second.example.com/jwks.json".parse().unwrap()),
-                None,
-                None,
-                None,
-                None,
-                None,
-                Some("https://second.example.com/login".parse().unwrap()),
+                OAuth2ClientParams {
+                    redirect_uris: vec!["https://second.example.com/redirect".parse().unwrap()],
+                    grant_types: vec![GrantType::AuthorizationCode],
+                    client_name: Some("Second client".to_owned()),
+                    logo_uri: Some("https://second.example.com/logo.png".parse().unwrap()),
+                    client_uri: Some("https://second.example.com/".parse().unwrap()),
+                    policy_uri: Some("https://second.example.com/policy".parse().unwrap()),
+                    tos_uri: Some("https://second.example.com/tos".parse().unwrap()),
+                    jwks_uri: Some("https://second.example.com/jwks.json".parse().unwrap()),
+                    initiate_login_uri: Some("https://second.example.com/login".parse().unwrap()),
+                    ..Default::default()
+                },
             )
             .await
             .unwrap();
@@ -734,23 +940,18 @@ mod tests {
             .add(
                 &mut rng,
                 &clock,
-                vec!["https://example.com/redirect".parse().unwrap()],
-                None,
-                None,
-                None,
-                vec![GrantType::AuthorizationCode],
-                Some("Example".to_owned()),
-                Some("https://example.com/logo.png".parse().unwrap()),
-                Some("https://example.com/".parse().unwrap()),
-                Some("https://example.com/policy".parse().unwrap()),
-                Some("https://example.com/tos".parse().unwrap()),
-                Some("https://example.com/jwks.json".parse().unwrap()),
-                None,
-                None,
-                None,
-                None,
-                None,
-                Some("https://example.com/login".parse().unwrap()),
+                OAuth2ClientParams {
+                    redirect_uris: vec!["https://example.com/redirect".parse().unwrap()],
+                    grant_types: vec![GrantType::AuthorizationCode],
+                    client_name: Some("Example".to_owned()),
+                    logo_uri: Some("https://example.com/logo.png".parse().unwrap()),
+                    client_uri: Some("https://example.com/".parse().unwrap()),
+                    policy_uri: Some("https://example.com/policy".parse().unwrap()),
+                    tos_uri: Some("https://example.com/tos".parse().unwrap()),
+                    jwks_uri: Some("https://example.com/jwks.json".parse().unwrap()),
+                    initiate_login_uri: Some("https://example.com/login".parse().unwrap()),
+                    ..Default::default()
+                },
             )
             .await
             .unwrap();
@@ -787,6 +988,9 @@ mod tests {
                     expires_in: Duration::try_minutes(5).unwrap(),
                     ip_address: None,
                     user_agent: None,
+                    user: None,
+                    binding_message: None,
+                    client_notification_token: None,
                 },
             )
             .await
@@ -892,6 +1096,9 @@ mod tests {
                     expires_in: Duration::try_minutes(5).unwrap(),
                     ip_address: None,
                     user_agent: None,
+                    user: None,
+                    binding_message: None,
+                    client_notification_token: None,
                 },
             )
             .await
@@ -945,4 +1152,355 @@ mod tests {
             .await;
         assert!(res.is_err());
     }
+
+    /// Test the [`OAuth2PushedAuthorizationRequestRepository`] implementation
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn test_pushed_authorization_request_repository(pool: PgPool) {
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let clock = MockClock::default();
+        let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+
+        // Provision a client
+        let client = repo
+            .oauth2_client()
+            .add(
+                &mut rng,
+                &clock,
+                OAuth2ClientParams {
+                    redirect_uris: vec!["https://example.com/redirect".parse().unwrap()],
+                    grant_types: vec![GrantType::AuthorizationCode],
+                    client_name: Some("Example".to_owned()),
+                    require_pushed_authorization_requests: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(client.require_pushed_authorization_requests);
+
+        // The client flag is persisted
+        let client = repo
+            .oauth2_client()
+            .lookup(client.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(client.require_pushed_authorization_requests);
+
+        // Nothing to find yet
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .find_by_reference("reference")
+            .await
+            .unwrap();
+        assert!(request.is_none());
+
+        let parameters = BTreeMap::from([
+            ("response_type".to_owned(), "code".to_owned()),
+            ("client_id".to_owned(), client.client_id.clone()),
+            ("scope".to_owned(), "openid".to_owned()),
+        ]);
+
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .add(
+                &mut rng,
+                &clock,
+                &client,
+                "reference".to_owned(),
+                parameters.clone(),
+                Duration::try_minutes(1).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(request.client_id, client.id);
+        assert_eq!(request.parameters, parameters);
+        assert!(request.is_valid(clock.now()));
+        assert_eq!(
+            request.request_uri(),
+            "urn:ietf:params:oauth:request_uri:reference"
+        );
+
+        // Lookup by ID and by reference
+        let lookup = repo
+            .oauth2_pushed_authorization_request()
+            .lookup(request.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lookup, request);
+
+        let lookup = repo
+            .oauth2_pushed_authorization_request()
+            .find_by_reference("reference")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lookup, request);
+
+        // It expires after a minute
+        clock.advance(Duration::try_minutes(2).unwrap());
+        assert!(!lookup.is_valid(clock.now()));
+
+        // Consume it
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .consume(&clock, request)
+            .await
+            .unwrap();
+        assert_eq!(request.consumed_at, Some(clock.now()));
+
+        let lookup = repo
+            .oauth2_pushed_authorization_request()
+            .lookup(request.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lookup.consumed_at, Some(clock.now()));
+
+        // It can't be consumed twice
+        let res = repo
+            .oauth2_pushed_authorization_request()
+            .consume(&clock, lookup)
+            .await;
+        assert!(res.is_err());
+
+        // Push another request, which is still valid
+        let other = repo
+            .oauth2_pushed_authorization_request()
+            .add(
+                &mut rng,
+                &clock,
+                &client,
+                "other-reference".to_owned(),
+                parameters,
+                Duration::try_minutes(1).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Only the consumed request is cleaned up
+        let count = repo
+            .oauth2_pushed_authorization_request()
+            .cleanup(&clock)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        let lookup = repo
+            .oauth2_pushed_authorization_request()
+            .lookup(request.id)
+            .await
+            .unwrap();
+        assert!(lookup.is_none());
+
+        // The other one is cleaned up once it expired
+        clock.advance(Duration::try_minutes(2).unwrap());
+        let count = repo
+            .oauth2_pushed_authorization_request()
+            .cleanup(&clock)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        let lookup = repo
+            .oauth2_pushed_authorization_request()
+            .lookup(other.id)
+            .await
+            .unwrap();
+        assert!(lookup.is_none());
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn test_used_jwt_id_repository(pool: PgPool) {
+        let clock = MockClock::default();
+        let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+
+        let expires_at = clock.now() + Duration::try_minutes(5).unwrap();
+
+        // The first use is fine
+        assert!(
+            repo.oauth2_used_jwt_id()
+                .mark_used(&clock, "client", "jti", expires_at)
+                .await
+                .unwrap()
+        );
+
+        // Using it again is a replay
+        assert!(
+            !repo
+                .oauth2_used_jwt_id()
+                .mark_used(&clock, "client", "jti", expires_at)
+                .await
+                .unwrap()
+        );
+
+        // The same JWT ID from another issuer is fine
+        assert!(
+            repo.oauth2_used_jwt_id()
+                .mark_used(&clock, "other-client", "jti", expires_at)
+                .await
+                .unwrap()
+        );
+
+        // Nothing expired yet
+        let count = repo
+            .oauth2_used_jwt_id()
+            .cleanup_expired(&clock)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+
+        // Once the JWT expired, the JWT ID can be used again
+        clock.advance(Duration::try_minutes(10).unwrap());
+        let expires_at = clock.now() + Duration::try_minutes(5).unwrap();
+        assert!(
+            repo.oauth2_used_jwt_id()
+                .mark_used(&clock, "client", "jti", expires_at)
+                .await
+                .unwrap()
+        );
+
+        // Only the JWT ID from the other client is cleaned up
+        let count = repo
+            .oauth2_used_jwt_id()
+            .cleanup_expired(&clock)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn test_consent_repository(pool: PgPool) {
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let clock = MockClock::default();
+        let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+
+        let user = repo
+            .user()
+            .add(&mut rng, &clock, "john".to_owned())
+            .await
+            .unwrap();
+
+        let client = repo
+            .oauth2_client()
+            .add(
+                &mut rng,
+                &clock,
+                OAuth2ClientParams {
+                    redirect_uris: vec!["https://example.com/redirect".parse().unwrap()],
+                    grant_types: vec![GrantType::AuthorizationCode],
+                    client_name: Some("Test client".to_owned()),
+                    logo_uri: Some("https://example.com/logo.png".parse().unwrap()),
+                    client_uri: Some("https://example.com/".parse().unwrap()),
+                    policy_uri: Some("https://example.com/policy".parse().unwrap()),
+                    tos_uri: Some("https://example.com/tos".parse().unwrap()),
+                    jwks_uri: Some("https://example.com/jwks.json".parse().unwrap()),
+                    initiate_login_uri: Some("https://example.com/login".parse().unwrap()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let filter = OAuth2ConsentFilter::new().for_user(&user);
+
+        // The user didn't consent to anything yet
+        assert!(
+            repo.oauth2_consent()
+                .find(&user, &client)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(repo.oauth2_consent().count(filter).await.unwrap(), 0);
+
+        // Give consent to a first scope
+        let consent = repo
+            .oauth2_consent()
+            .give(
+                &mut rng,
+                &clock,
+                &user,
+                &client,
+                &Scope::from_iter([OPENID, EMAIL]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(consent.user_id, user.id);
+        assert_eq!(consent.client_id, client.id);
+        assert_eq!(consent.scope, Scope::from_iter([OPENID, EMAIL]));
+        assert_eq!(consent.refreshed_at, None);
+
+        // Consenting again merges the scope into the same consent
+        clock.advance(Duration::try_minutes(1).unwrap());
+        let refreshed = repo
+            .oauth2_consent()
+            .give(
+                &mut rng,
+                &clock,
+                &user,
+                &client,
+                &Scope::from_iter([OPENID, PROFILE]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(refreshed.id, consent.id);
+        assert_eq!(refreshed.created_at, consent.created_at);
+        assert_eq!(refreshed.refreshed_at, Some(clock.now()));
+        assert_eq!(refreshed.scope, Scope::from_iter([OPENID, EMAIL, PROFILE]));
+
+        // Matrix device scope tokens are never remembered, so that logging in
+        // again with a new device doesn't need a new consent
+        let device = Device::generate(&mut rng).to_scope_token().unwrap();
+        let refreshed = repo
+            .oauth2_consent()
+            .give(
+                &mut rng,
+                &clock,
+                &user,
+                &client,
+                &Scope::from_iter([OPENID, device]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(refreshed.scope, Scope::from_iter([OPENID, EMAIL, PROFILE]));
+        let other_device = Device::generate(&mut rng).to_scope_token().unwrap();
+        assert!(refreshed.covers(&Scope::from_iter([OPENID, other_device])));
+
+        let lookup = repo
+            .oauth2_consent()
+            .find(&user, &client)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lookup, refreshed);
+        let lookup = repo
+            .oauth2_consent()
+            .lookup(consent.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lookup, refreshed);
+
+        // It shows up when listing
+        assert_eq!(repo.oauth2_consent().count(filter).await.unwrap(), 1);
+        let page = repo
+            .oauth2_consent()
+            .list(filter.for_client(&client), Pagination::first(10))
+            .await
+            .unwrap();
+        assert!(!page.has_next_page);
+        assert_eq!(page.edges.len(), 1);
+        assert_eq!(page.edges[0], refreshed);
+
+        // Revoke it
+        repo.oauth2_consent().revoke(refreshed).await.unwrap();
+        assert!(
+            repo.oauth2_consent()
+                .find(&user, &client)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(repo.oauth2_consent().count(filter).await.unwrap(), 0);
+    }
 }
@@ -4,9 +4,11 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
+use std::net::IpAddr;
+
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use mas_data_model::{AccessToken, RefreshToken, RefreshTokenState, Session};
+use mas_data_model::{AccessToken, RefreshToken, RefreshTokenReuse, RefreshTokenState, Session};
 use mas_storage::{Clock, oauth2::OAuth2RefreshTokenRepository};
 use rand::RngCore;
 use sqlx::PgConnection;
@@ -281,4 +283,89 @@ impl OAuth2RefreshTokenRepository for PgOAuth2RefreshTokenRepository<'_> {
             .revoke(revoked_at)
             .map_err(DatabaseError::to_invalid_operation)
     }
+
+    #[tracing::instrument(
+        name = "db.oauth2_refresh_token.revoke_for_session",
+        skip_all,
+        fields(
+            db.query.text,
+            %session.id,
+        ),
+        err,
+    )]
+    async fn revoke_for_session(
+        &mut self,
+        clock: &dyn Clock,
+        session: &Session,
+    ) -> Result<usize, Self::Error> {
+        let revoked_at = clock.now();
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_refresh_tokens
+                SET revoked_at = $2
+                WHERE oauth2_session_id = $1
+                  AND revoked_at IS NULL
+                  AND consumed_at IS NULL
+            "#,
+            Uuid::from(session.id),
+            revoked_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(res.rows_affected().try_into().unwrap_or(usize::MAX))
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_refresh_token.record_reuse",
+        skip_all,
+        fields(
+            db.query.text,
+            %refresh_token.id,
+            session.id = %refresh_token.session_id,
+            refresh_token_reuse.id,
+        ),
+        err,
+    )]
+    async fn record_reuse(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        refresh_token: &RefreshToken,
+        ip_address: Option<IpAddr>,
+        user_agent: Option<String>,
+    ) -> Result<RefreshTokenReuse, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("refresh_token_reuse.id", tracing::field::display(id));
+
+        sqlx::query!(
+            r#"
+                INSERT INTO oauth2_refresh_token_reuses
+                    (oauth2_refresh_token_reuse_id, oauth2_session_id,
+                     oauth2_refresh_token_id, created_at, ip_address, user_agent)
+                VALUES
+                    ($1, $2, $3, $4, $5, $6)
+            "#,
+            Uuid::from(id),
+            Uuid::from(refresh_token.session_id),
+            Uuid::from(refresh_token.id),
+            created_at,
+            ip_address as Option<IpAddr>,
+            user_agent.as_deref(),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(RefreshTokenReuse {
+            id,
+            session_id: refresh_token.session_id,
+            refresh_token_id: refresh_token.id,
+            created_at,
+            ip_address,
+            user_agent,
+        })
+    }
 }
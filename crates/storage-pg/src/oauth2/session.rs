@@ -13,7 +13,10 @@ use mas_storage::{
     Clock, Page, Pagination,
     oauth2::{OAuth2SessionFilter, OAuth2SessionRepository},
 };
-use oauth2_types::scope::{Scope, ScopeToken};
+use oauth2_types::{
+    requests::{ActorClaim, AuthorizationDetails, ClaimsParameter},
+    scope::{Scope, ScopeToken},
+};
 use rand::RngCore;
 use sea_query::{Expr, PgFunc, PostgresQueryBuilder, Query, enum_def, extension::postgres::PgExpr};
 use sea_query_binder::SqlxBinder;
@@ -56,6 +59,12 @@ struct OAuthSessionLookup {
     last_active_at: Option<DateTime<Utc>>,
     last_active_ip: Option<IpAddr>,
     human_name: Option<String>,
+    requested_claims: Option<serde_json::Value>,
+    dpop_jkt: Option<String>,
+    x5t_s256: Option<String>,
+    audience: Vec<String>,
+    act: Option<serde_json::Value>,
+    authorization_details: Option<serde_json::Value>,
 }
 
 impl TryFrom<OAuthSessionLookup> for Session {
@@ -75,6 +84,39 @@ impl TryFrom<OAuthSessionLookup> for Session {
                 .source(e)
         })?;
 
+        let requested_claims = value
+            .requested_claims
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_sessions")
+                    .column("requested_claims")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let act = value
+            .act
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_sessions")
+                    .column("act")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        let authorization_details = value
+            .authorization_details
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_sessions")
+                    .column("authorization_details")
+                    .row(id)
+                    .source(e)
+            })?;
+
         let state = match value.finished_at {
             None => SessionState::Valid,
             Some(finished_at) => SessionState::Finished { finished_at },
@@ -92,6 +134,12 @@ impl TryFrom<OAuthSessionLookup> for Session {
             last_active_at: value.last_active_at,
             last_active_ip: value.last_active_ip,
             human_name: value.human_name,
+            requested_claims,
+            dpop_jkt: value.dpop_jkt,
+            x5t_s256: value.x5t_s256,
+            audience: value.audience,
+            act,
+            authorization_details,
         })
     }
 }
@@ -210,6 +258,12 @@ impl OAuth2SessionRepository for PgOAuth2SessionRepository<'_> {
                      , last_active_at
                      , last_active_ip as "last_active_ip: IpAddr"
                      , human_name
+                     , requested_claims
+                     , dpop_jkt
+                     , x5t_s256
+                     , audience
+                     , act
+                     , authorization_details
                 FROM oauth2_sessions
 
                 WHERE oauth2_session_id = $1
@@ -286,6 +340,12 @@ impl OAuth2SessionRepository for PgOAuth2SessionRepository<'_> {
             last_active_at: None,
             last_active_ip: None,
             human_name: None,
+            requested_claims: None,
+            dpop_jkt: None,
+            x5t_s256: None,
+            audience: Vec::new(),
+            act: None,
+            authorization_details: None,
         })
     }
 
@@ -412,6 +472,30 @@ impl OAuth2SessionRepository for PgOAuth2SessionRepository<'_> {
                 Expr::col((OAuth2Sessions::Table, OAuth2Sessions::HumanName)),
                 OAuthSessionLookupIden::HumanName,
             )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::RequestedClaims)),
+                OAuthSessionLookupIden::RequestedClaims,
+            )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::DpopJkt)),
+                OAuthSessionLookupIden::DpopJkt,
+            )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::X5tS256)),
+                OAuthSessionLookupIden::X5tS256,
+            )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::Audience)),
+                OAuthSessionLookupIden::Audience,
+            )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::Act)),
+                OAuthSessionLookupIden::Act,
+            )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::AuthorizationDetails)),
+                OAuthSessionLookupIden::AuthorizationDetails,
+            )
             .from(OAuth2Sessions::Table)
             .apply_filter(filter)
             .generate_pagination(
@@ -575,4 +659,228 @@ impl OAuth2SessionRepository for PgOAuth2SessionRepository<'_> {
 
         Ok(session)
     }
+
+    #[tracing::instrument(
+        name = "repository.oauth2_session.set_requested_claims",
+        skip_all,
+        fields(
+            db.query.text,
+            %session.id,
+            client.id = %session.client_id,
+        ),
+        err,
+    )]
+    async fn set_requested_claims(
+        &mut self,
+        mut session: Session,
+        requested_claims: Option<ClaimsParameter>,
+    ) -> Result<Session, Self::Error> {
+        let requested_claims_json = requested_claims
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(DatabaseError::to_invalid_operation)?;
+
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_sessions
+                SET requested_claims = $2
+                WHERE oauth2_session_id = $1
+            "#,
+            Uuid::from(session.id),
+            requested_claims_json,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        session.requested_claims = requested_claims;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(session)
+    }
+
+    #[tracing::instrument(
+        name = "repository.oauth2_session.set_dpop_jkt",
+        skip_all,
+        fields(
+            db.query.text,
+            %session.id,
+            client.id = %session.client_id,
+        ),
+        err,
+    )]
+    async fn set_dpop_jkt(
+        &mut self,
+        mut session: Session,
+        dpop_jkt: String,
+    ) -> Result<Session, Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_sessions
+                SET dpop_jkt = $2
+                WHERE oauth2_session_id = $1
+            "#,
+            Uuid::from(session.id),
+            &dpop_jkt,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        session.dpop_jkt = Some(dpop_jkt);
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(session)
+    }
+
+    #[tracing::instrument(
+        name = "repository.oauth2_session.set_x5t_s256",
+        skip_all,
+        fields(
+            db.query.text,
+            %session.id,
+            client.id = %session.client_id,
+        ),
+        err,
+    )]
+    async fn set_x5t_s256(
+        &mut self,
+        mut session: Session,
+        x5t_s256: String,
+    ) -> Result<Session, Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_sessions
+                SET x5t_s256 = $2
+                WHERE oauth2_session_id = $1
+            "#,
+            Uuid::from(session.id),
+            &x5t_s256,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        session.x5t_s256 = Some(x5t_s256);
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(session)
+    }
+
+    #[tracing::instrument(
+        name = "repository.oauth2_session.set_audience",
+        skip_all,
+        fields(
+            db.query.text,
+            %session.id,
+            client.id = %session.client_id,
+        ),
+        err,
+    )]
+    async fn set_audience(
+        &mut self,
+        mut session: Session,
+        audience: Vec<String>,
+    ) -> Result<Session, Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_sessions
+                SET audience = $2
+                WHERE oauth2_session_id = $1
+            "#,
+            Uuid::from(session.id),
+            &audience,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        session.audience = audience;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(session)
+    }
+
+    #[tracing::instrument(
+        name = "repository.oauth2_session.set_act",
+        skip_all,
+        fields(
+            db.query.text,
+            %session.id,
+            client.id = %session.client_id,
+        ),
+        err,
+    )]
+    async fn set_act(
+        &mut self,
+        mut session: Session,
+        act: ActorClaim,
+    ) -> Result<Session, Self::Error> {
+        let act_json = serde_json::to_value(&act).map_err(DatabaseError::to_invalid_operation)?;
+
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_sessions
+                SET act = $2
+                WHERE oauth2_session_id = $1
+            "#,
+            Uuid::from(session.id),
+            act_json,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        session.act = Some(act);
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(session)
+    }
+
+    #[tracing::instrument(
+        name = "repository.oauth2_session.set_authorization_details",
+        skip_all,
+        fields(
+            db.query.text,
+            %session.id,
+            client.id = %session.client_id,
+        ),
+        err,
+    )]
+    async fn set_authorization_details(
+        &mut self,
+        mut session: Session,
+        authorization_details: Option<AuthorizationDetails>,
+    ) -> Result<Session, Self::Error> {
+        let authorization_details_json = authorization_details
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(DatabaseError::to_invalid_operation)?;
+
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_sessions
+                SET authorization_details = $2
+                WHERE oauth2_session_id = $1
+            "#,
+            Uuid::from(session.id),
+            authorization_details_json,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        session.authorization_details = authorization_details;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(session)
+    }
 }
@@ -0,0 +1,96 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mas_storage::{Clock, oauth2::OAuth2UsedJwtIdRepository};
+use sqlx::PgConnection;
+
+use crate::{DatabaseError, ExecuteExt};
+
+/// An implementation of [`OAuth2UsedJwtIdRepository`] for a PostgreSQL
+/// connection
+pub struct PgOAuth2UsedJwtIdRepository<'c> {
+    conn: &'c mut PgConnection,
+}
+
+impl<'c> PgOAuth2UsedJwtIdRepository<'c> {
+    /// Create a new [`PgOAuth2UsedJwtIdRepository`] from an active PostgreSQL
+    /// connection
+    pub fn new(conn: &'c mut PgConnection) -> Self {
+        Self { conn }
+    }
+}
+
+#[async_trait]
+impl OAuth2UsedJwtIdRepository for PgOAuth2UsedJwtIdRepository<'_> {
+    type Error = DatabaseError;
+
+    #[tracing::instrument(
+        name = "db.oauth2_used_jwt_id.mark_used",
+        skip_all,
+        fields(
+            db.query.text,
+            jwt.issuer = issuer,
+            jwt.jti = jti,
+        ),
+        err,
+    )]
+    async fn mark_used(
+        &mut self,
+        clock: &dyn Clock,
+        issuer: &str,
+        jti: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, Self::Error> {
+        let used_at = clock.now();
+
+        // If the JWT ID was already used, only overwrite it if the previous JWT
+        // has expired. No row is affected if it is being replayed.
+        let res = sqlx::query!(
+            r#"
+                INSERT INTO oauth2_used_jwt_ids
+                    (issuer, jti, used_at, expires_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (issuer, jti) DO UPDATE
+                SET used_at = EXCLUDED.used_at
+                  , expires_at = EXCLUDED.expires_at
+                WHERE oauth2_used_jwt_ids.expires_at < EXCLUDED.used_at
+            "#,
+            issuer,
+            jti,
+            used_at,
+            expires_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_used_jwt_id.cleanup_expired",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn cleanup_expired(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM oauth2_used_jwt_ids
+                WHERE expires_at < $1
+            "#,
+            clock.now(),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(res.rows_affected().try_into().unwrap_or(usize::MAX))
+    }
+}
@@ -18,7 +18,9 @@ use mas_storage::{
     },
     oauth2::{
         OAuth2AccessTokenRepository, OAuth2AuthorizationGrantRepository, OAuth2ClientRepository,
-        OAuth2DeviceCodeGrantRepository, OAuth2RefreshTokenRepository, OAuth2SessionRepository,
+        OAuth2ConsentRepository, OAuth2DeviceCodeGrantRepository,
+        OAuth2PushedAuthorizationRequestRepository, OAuth2RefreshTokenRepository,
+        OAuth2SessionRepository, OAuth2UsedJwtIdRepository,
     },
     policy_data::PolicyDataRepository,
     queue::{QueueJobRepository, QueueScheduleRepository, QueueWorkerRepository},
@@ -44,8 +46,9 @@ use crate::{
     },
     oauth2::{
         PgOAuth2AccessTokenRepository, PgOAuth2AuthorizationGrantRepository,
-        PgOAuth2ClientRepository, PgOAuth2DeviceCodeGrantRepository,
-        PgOAuth2RefreshTokenRepository, PgOAuth2SessionRepository,
+        PgOAuth2ClientRepository, PgOAuth2ConsentRepository, PgOAuth2DeviceCodeGrantRepository,
+        PgOAuth2PushedAuthorizationRequestRepository, PgOAuth2RefreshTokenRepository,
+        PgOAuth2SessionRepository, PgOAuth2UsedJwtIdRepository,
     },
     policy_data::PgPolicyDataRepository,
     queue::{
@@ -272,6 +275,12 @@ where
         Box::new(PgOAuth2ClientRepository::new(self.conn.as_mut()))
     }
 
+    fn oauth2_consent<'c>(
+        &'c mut self,
+    ) -> Box<dyn OAuth2ConsentRepository<Error = Self::Error> + 'c> {
+        Box::new(PgOAuth2ConsentRepository::new(self.conn.as_mut()))
+    }
+
     fn oauth2_authorization_grant<'c>(
         &'c mut self,
     ) -> Box<dyn OAuth2AuthorizationGrantRepository<Error = Self::Error> + 'c> {
@@ -304,6 +313,20 @@ where
         Box::new(PgOAuth2DeviceCodeGrantRepository::new(self.conn.as_mut()))
     }
 
+    fn oauth2_pushed_authorization_request<'c>(
+        &'c mut self,
+    ) -> Box<dyn OAuth2PushedAuthorizationRequestRepository<Error = Self::Error> + 'c> {
+        Box::new(PgOAuth2PushedAuthorizationRequestRepository::new(
+            self.conn.as_mut(),
+        ))
+    }
+
+    fn oauth2_used_jwt_id<'c>(
+        &'c mut self,
+    ) -> Box<dyn OAuth2UsedJwtIdRepository<Error = Self::Error> + 'c> {
+        Box::new(PgOAuth2UsedJwtIdRepository::new(self.conn.as_mut()))
+    }
+
     fn compat_session<'c>(
         &'c mut self,
     ) -> Box<dyn CompatSessionRepository<Error = Self::Error> + 'c> {
@@ -57,7 +57,8 @@ pub trait OAuth2AccessTokenRepository: Send + Sync {
     /// * `rng`: A random number generator
     /// * `clock`: The clock used to generate timestamps
     /// * `session`: The session the access token is associated with
-    /// * `access_token`: The access token to add
+    /// * `access_token`: The access token to add. It is stored under its
+    ///   [`AccessToken::storage_key`]
     /// * `expires_after`: The duration after which the access token expires. If
     ///   [`None`] the access token never expires
     ///
@@ -73,6 +74,36 @@ pub trait OAuth2AccessTokenRepository: Send + Sync {
         expires_after: Option<Duration>,
     ) -> Result<AccessToken, Self::Error>;
 
+    /// Add a new access token to the database, with an ID allocated
+    /// beforehand
+    ///
+    /// This is used for JWT-formatted access tokens, which carry their ID as
+    /// their `jti` claim.
+    ///
+    /// Returns the newly created access token
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `id`: The ID of the access token
+    /// * `session`: The session the access token is associated with
+    /// * `access_token`: The access token to add. It is stored under its
+    ///   [`AccessToken::storage_key`]
+    /// * `expires_after`: The duration after which the access token expires. If
+    ///   [`None`] the access token never expires
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn add_with_id(
+        &mut self,
+        clock: &dyn Clock,
+        id: Ulid,
+        session: &Session,
+        access_token: String,
+        expires_after: Option<Duration>,
+    ) -> Result<AccessToken, Self::Error>;
+
     /// Revoke an access token
     ///
     /// Returns the revoked access token
@@ -91,6 +122,24 @@ pub trait OAuth2AccessTokenRepository: Send + Sync {
         access_token: AccessToken,
     ) -> Result<AccessToken, Self::Error>;
 
+    /// Revoke all the valid access tokens of a session
+    ///
+    /// Returns the number of access tokens that were revoked
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `session`: The [`Session`] whose access tokens should be revoked
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn revoke_for_session(
+        &mut self,
+        clock: &dyn Clock,
+        session: &Session,
+    ) -> Result<usize, Self::Error>;
+
     /// Mark the access token as used, to track when it was first used
     ///
     /// # Parameters
@@ -138,12 +187,27 @@ repository_impl!(OAuth2AccessTokenRepository:
         expires_after: Option<Duration>,
     ) -> Result<AccessToken, Self::Error>;
 
+    async fn add_with_id(
+        &mut self,
+        clock: &dyn Clock,
+        id: Ulid,
+        session: &Session,
+        access_token: String,
+        expires_after: Option<Duration>,
+    ) -> Result<AccessToken, Self::Error>;
+
     async fn revoke(
         &mut self,
         clock: &dyn Clock,
         access_token: AccessToken,
     ) -> Result<AccessToken, Self::Error>;
 
+    async fn revoke_for_session(
+        &mut self,
+        clock: &dyn Clock,
+        session: &Session,
+    ) -> Result<usize, Self::Error>;
+
     async fn mark_used(
         &mut self,
         clock: &dyn Clock,
@@ -6,13 +6,72 @@
 
 use async_trait::async_trait;
 use mas_data_model::{AuthorizationCode, AuthorizationGrant, Client, Session};
-use oauth2_types::{requests::ResponseMode, scope::Scope};
+use oauth2_types::{
+    requests::{AuthorizationDetails, ClaimsParameter, ResponseMode},
+    scope::Scope,
+};
 use rand_core::RngCore;
 use ulid::Ulid;
 use url::Url;
 
 use crate::{Clock, repository_impl};
 
+/// Parameters used to create a new [`AuthorizationGrant`]
+pub struct OAuth2AuthorizationGrantParams<'a> {
+    /// The client that requested the authorization grant
+    pub client: &'a Client,
+
+    /// The redirect URI the client requested
+    pub redirect_uri: Url,
+
+    /// The scope the client requested
+    pub scope: Scope,
+
+    /// The authorization code used by this grant, if the `code` `response_type`
+    /// was requested
+    pub code: Option<AuthorizationCode>,
+
+    /// The state the client sent, if set
+    pub state: Option<String>,
+
+    /// The nonce the client sent, if set
+    pub nonce: Option<String>,
+
+    /// The response mode the client requested
+    pub response_mode: ResponseMode,
+
+    /// Whether the `id_token` `response_type` was requested
+    pub response_type_id_token: bool,
+
+    /// The `login_hint` the client sent, if set
+    pub login_hint: Option<String>,
+
+    /// The locale detected when the user asked for the authorization grant
+    pub locale: Option<String>,
+
+    /// The individual claims the client requested through the `claims`
+    /// parameter, if set
+    pub requested_claims: Option<ClaimsParameter>,
+
+    /// The fine-grained authorization details the client requested through the
+    /// `authorization_details` parameter, if set
+    pub authorization_details: Option<AuthorizationDetails>,
+
+    /// The resource server the client requested a token for through the
+    /// `resource` parameter, if set
+    pub resource: Option<Url>,
+
+    /// The maximum age of the user authentication, in seconds, if set through
+    /// the `max_age` or `prompt=login` parameters
+    pub max_age: Option<u32>,
+
+    /// The ACR values the client requested
+    pub acr_values: Vec<String>,
+
+    /// Whether the client asked for `prompt=consent`
+    pub requires_consent: bool,
+}
+
 /// An [`OAuth2AuthorizationGrantRepository`] helps interacting with
 /// [`AuthorizationGrant`] saved in the storage backend
 #[async_trait]
@@ -28,38 +87,17 @@ pub trait OAuth2AuthorizationGrantRepository: Send + Sync {
     ///
     /// * `rng`: A random number generator
     /// * `clock`: The clock used to generate timestamps
-    /// * `client`: The client that requested the authorization grant
-    /// * `redirect_uri`: The redirect URI the client requested
-    /// * `scope`: The scope the client requested
-    /// * `code`: The authorization code used by this grant, if the `code`
-    ///   `response_type` was requested
-    /// * `state`: The state the client sent, if set
-    /// * `nonce`: The nonce the client sent, if set
-    /// * `response_mode`: The response mode the client requested
-    /// * `response_type_id_token`: Whether the `id_token` `response_type` was
-    ///   requested
-    /// * `login_hint`: The login_hint the client sent, if set
-    /// * `locale`: The locale the detected when the user asked for the
-    ///   authorization grant
+    /// * `params`: The parameters used to create the authorization grant. See
+    ///   the fields of [`OAuth2AuthorizationGrantParams`]
     ///
     /// # Errors
     ///
     /// Returns [`Self::Error`] if the underlying repository fails
-    #[allow(clippy::too_many_arguments)]
     async fn add(
         &mut self,
         rng: &mut (dyn RngCore + Send),
         clock: &dyn Clock,
-        client: &Client,
-        redirect_uri: Url,
-        scope: Scope,
-        code: Option<AuthorizationCode>,
-        state: Option<String>,
-        nonce: Option<String>,
-        response_mode: ResponseMode,
-        response_type_id_token: bool,
-        login_hint: Option<String>,
-        locale: Option<String>,
+        params: OAuth2AuthorizationGrantParams<'_>,
     ) -> Result<AuthorizationGrant, Self::Error>;
 
     /// Lookup an authorization grant by its ID
@@ -134,16 +172,7 @@ repository_impl!(OAuth2AuthorizationGrantRepository:
         &mut self,
         rng: &mut (dyn RngCore + Send),
         clock: &dyn Clock,
-        client: &Client,
-        redirect_uri: Url,
-        scope: Scope,
-        code: Option<AuthorizationCode>,
-        state: Option<String>,
-        nonce: Option<String>,
-        response_mode: ResponseMode,
-        response_type_id_token: bool,
-        login_hint: Option<String>,
-        locale: Option<String>,
+        params: OAuth2AuthorizationGrantParams<'_>,
     ) -> Result<AuthorizationGrant, Self::Error>;
 
     async fn lookup(&mut self, id: Ulid) -> Result<Option<AuthorizationGrant>, Self::Error>;
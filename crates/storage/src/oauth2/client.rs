@@ -8,15 +8,220 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use async_trait::async_trait;
 use mas_data_model::Client;
-use mas_iana::{jose::JsonWebSignatureAlg, oauth::OAuthClientAuthenticationMethod};
+use mas_iana::{
+    jose::{JsonWebEncryptionAlg, JsonWebEncryptionEnc, JsonWebSignatureAlg},
+    oauth::OAuthClientAuthenticationMethod,
+};
 use mas_jose::jwk::PublicJsonWebKeySet;
-use oauth2_types::{oidc::ApplicationType, requests::GrantType};
+use oauth2_types::{
+    oidc::{ApplicationType, BackchannelTokenDeliveryMode, SubjectType},
+    requests::GrantType,
+};
 use rand_core::RngCore;
 use ulid::Ulid;
 use url::Url;
 
 use crate::{Clock, repository_impl};
 
+/// Parameters used to create a new [`Client`]
+#[derive(Debug, Default)]
+pub struct OAuth2ClientParams {
+    /// The list of redirect URIs used by this client
+    pub redirect_uris: Vec<Url>,
+
+    /// The hash of the client metadata, if computed
+    pub metadata_digest: Option<String>,
+
+    /// The encrypted client secret, if any
+    pub encrypted_client_secret: Option<String>,
+
+    /// The application type of this client
+    pub application_type: Option<ApplicationType>,
+
+    /// The list of grant types this client can use
+    pub grant_types: Vec<GrantType>,
+
+    /// The human-readable name of this client, if given
+    pub client_name: Option<String>,
+
+    /// The URI of the logo of this client, if given
+    pub logo_uri: Option<Url>,
+
+    /// The URI of a website of this client, if given
+    pub client_uri: Option<Url>,
+
+    /// The URI of the privacy policy of this client, if given
+    pub policy_uri: Option<Url>,
+
+    /// The URI of the terms of service of this client, if given
+    pub tos_uri: Option<Url>,
+
+    /// The URI of the JWKS of this client, if given
+    pub jwks_uri: Option<Url>,
+
+    /// The JWKS of this client, if given
+    pub jwks: Option<PublicJsonWebKeySet>,
+
+    /// The algorithm used to sign the ID token
+    pub id_token_signed_response_alg: Option<JsonWebSignatureAlg>,
+
+    /// The algorithm used to sign the user info. If none, the user info
+    /// endpoint will not sign the response
+    pub userinfo_signed_response_alg: Option<JsonWebSignatureAlg>,
+
+    /// The authentication method used by this client when calling the token
+    /// endpoint
+    pub token_endpoint_auth_method: Option<OAuthClientAuthenticationMethod>,
+
+    /// The algorithm used to sign the JWT when using the `client_secret_jwt` or
+    /// `private_key_jwt` authentication methods
+    pub token_endpoint_auth_signing_alg: Option<JsonWebSignatureAlg>,
+
+    /// The URI used to initiate a login, if given
+    pub initiate_login_uri: Option<Url>,
+
+    /// Whether this client must use pushed authorization requests
+    pub require_pushed_authorization_requests: bool,
+
+    /// The kind of subject identifiers this client gets, if given
+    pub subject_type: Option<SubjectType>,
+
+    /// The URI listing the redirect URIs of the sector this client belongs to,
+    /// if given
+    pub sector_identifier_uri: Option<Url>,
+
+    /// The list of URIs this client may redirect to after logging out
+    pub post_logout_redirect_uris: Vec<Url>,
+
+    /// The list of URIs this client may pass request objects by reference
+    /// from
+    pub request_uris: Vec<Url>,
+
+    /// The URI to send logout tokens to, if any
+    pub backchannel_logout_uri: Option<Url>,
+
+    /// The URI to load in an iframe when the user logs out, if any
+    pub frontchannel_logout_uri: Option<Url>,
+
+    /// Whether the issuer and session ID must be passed to the
+    /// `frontchannel_logout_uri`
+    pub frontchannel_logout_session_required: bool,
+
+    /// Whether this client must always use DPoP-bound access tokens
+    pub dpop_bound_access_tokens: bool,
+
+    /// The expected subject distinguished name of the client certificate, if
+    /// any
+    pub tls_client_auth_subject_dn: Option<String>,
+
+    /// The expected DNS subject alternative name of the client certificate, if
+    /// any
+    pub tls_client_auth_san_dns: Option<String>,
+
+    /// The algorithm used to sign the introspection responses requested as JWTs
+    pub introspection_signed_response_alg: Option<JsonWebSignatureAlg>,
+
+    /// The mode used to deliver the result of client-initiated backchannel
+    /// authentication requests
+    pub backchannel_token_delivery_mode: Option<BackchannelTokenDeliveryMode>,
+
+    /// The URL where the client receives notifications about client-initiated
+    /// backchannel authentication requests
+    pub backchannel_client_notification_endpoint: Option<Url>,
+
+    /// The hash of the access token the client can use to manage its
+    /// registration, if any
+    pub registration_access_token_hash: Option<String>,
+
+    /// The algorithm used to encrypt the ID token. If none, the ID token is not
+    /// encrypted
+    pub id_token_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
+
+    /// The content encryption algorithm used to encrypt the ID token
+    pub id_token_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
+
+    /// The algorithm used to encrypt the user info. If none, the user info is
+    /// not encrypted
+    pub userinfo_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
+
+    /// The content encryption algorithm used to encrypt the user info
+    pub userinfo_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
+
+    /// The algorithm used to encrypt the introspection responses requested as
+    /// JWTs
+    pub introspection_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
+
+    /// The content encryption algorithm used to encrypt the introspection
+    /// responses
+    pub introspection_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
+
+    /// The algorithm used to sign the JWT-secured authorization responses
+    pub authorization_signed_response_alg: Option<JsonWebSignatureAlg>,
+
+    /// The algorithm used to encrypt the JWT-secured authorization responses
+    pub authorization_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
+
+    /// The content encryption algorithm used to encrypt the JWT-secured
+    /// authorization responses
+    pub authorization_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
+}
+
+/// Parameters used to add or replace a static [`Client`]
+#[derive(Debug)]
+pub struct OAuth2StaticClientParams {
+    /// The client ID
+    pub client_id: Ulid,
+
+    /// The human-readable name of this client, if given
+    pub client_name: Option<String>,
+
+    /// The authentication method this client uses
+    pub client_auth_method: OAuthClientAuthenticationMethod,
+
+    /// The encrypted client secret, if any
+    pub encrypted_client_secret: Option<String>,
+
+    /// The client JWKS, if any
+    pub jwks: Option<PublicJsonWebKeySet>,
+
+    /// The client JWKS URI, if any
+    pub jwks_uri: Option<Url>,
+
+    /// The list of redirect URIs used by this client
+    pub redirect_uris: Vec<Url>,
+
+    /// The list of URIs this client may redirect to after logging out
+    pub post_logout_redirect_uris: Vec<Url>,
+
+    /// The list of URIs this client may pass request objects by reference
+    /// from
+    pub request_uris: Vec<Url>,
+
+    /// The URI to send logout tokens to, if any
+    pub backchannel_logout_uri: Option<Url>,
+
+    /// The URI to load in an iframe when the user logs out, if any
+    pub frontchannel_logout_uri: Option<Url>,
+
+    /// Whether the issuer and session ID must be passed to the
+    /// `frontchannel_logout_uri`
+    pub frontchannel_logout_session_required: bool,
+
+    /// Whether this client must always use DPoP-bound access tokens
+    pub dpop_bound_access_tokens: bool,
+
+    /// The expected subject distinguished name of the client certificate, if
+    /// any
+    pub tls_client_auth_subject_dn: Option<String>,
+
+    /// The expected DNS subject alternative name of the client certificate, if
+    /// any
+    pub tls_client_auth_san_dns: Option<String>,
+
+    /// Whether this client must use pushed authorization requests
+    pub require_pushed_authorization_requests: bool,
+}
+
 /// An [`OAuth2ClientRepository`] helps interacting with [`Client`] saved in the
 /// storage backend
 #[async_trait]
@@ -87,82 +292,49 @@ pub trait OAuth2ClientRepository: Send + Sync {
     ///
     /// * `rng`: The random number generator to use
     /// * `clock`: The clock used to generate timestamps
-    /// * `redirect_uris`: The list of redirect URIs used by this client
-    /// * `metadata_digest`: The hash of the client metadata, if computed
-    /// * `encrypted_client_secret`: The encrypted client secret, if any
-    /// * `application_type`: The application type of this client
-    /// * `grant_types`: The list of grant types this client can use
-    /// * `client_name`: The human-readable name of this client, if given
-    /// * `logo_uri`: The URI of the logo of this client, if given
-    /// * `client_uri`: The URI of a website of this client, if given
-    /// * `policy_uri`: The URI of the privacy policy of this client, if given
-    /// * `tos_uri`: The URI of the terms of service of this client, if given
-    /// * `jwks_uri`: The URI of the JWKS of this client, if given
-    /// * `jwks`: The JWKS of this client, if given
-    /// * `id_token_signed_response_alg`: The algorithm used to sign the ID
-    ///   token
-    /// * `userinfo_signed_response_alg`: The algorithm used to sign the user
-    ///   info. If none, the user info endpoint will not sign the response
-    /// * `token_endpoint_auth_method`: The authentication method used by this
-    ///   client when calling the token endpoint
-    /// * `token_endpoint_auth_signing_alg`: The algorithm used to sign the JWT
-    ///   when using the `client_secret_jwt` or `private_key_jwt` authentication
-    ///   methods
-    /// * `initiate_login_uri`: The URI used to initiate a login, if given
+    /// * `params`: The metadata of the client to add. See the fields of
+    ///   [`OAuth2ClientParams`]
     ///
     /// # Errors
     ///
     /// Returns [`Self::Error`] if the underlying repository fails
-    #[allow(clippy::too_many_arguments)]
     async fn add(
         &mut self,
         rng: &mut (dyn RngCore + Send),
         clock: &dyn Clock,
-        redirect_uris: Vec<Url>,
-        metadata_digest: Option<String>,
-        encrypted_client_secret: Option<String>,
-        application_type: Option<ApplicationType>,
-        grant_types: Vec<GrantType>,
-        client_name: Option<String>,
-        logo_uri: Option<Url>,
-        client_uri: Option<Url>,
-        policy_uri: Option<Url>,
-        tos_uri: Option<Url>,
-        jwks_uri: Option<Url>,
-        jwks: Option<PublicJsonWebKeySet>,
-        id_token_signed_response_alg: Option<JsonWebSignatureAlg>,
-        userinfo_signed_response_alg: Option<JsonWebSignatureAlg>,
-        token_endpoint_auth_method: Option<OAuthClientAuthenticationMethod>,
-        token_endpoint_auth_signing_alg: Option<JsonWebSignatureAlg>,
-        initiate_login_uri: Option<Url>,
+        params: OAuth2ClientParams,
     ) -> Result<Client, Self::Error>;
 
+    /// Update a dynamically registered client, replacing all its metadata
+    /// with the one of the given client
+    ///
+    /// Returns the updated client
+    ///
+    /// # Parameters
+    ///
+    /// * `client`: The client to update, with its new metadata
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails, or if the
+    /// client does not exist or is a static client
+    async fn update(&mut self, client: Client) -> Result<Client, Self::Error>;
+
     /// Add or replace a static client
     ///
     /// Returns the client that was added or replaced
     ///
     /// # Parameters
     ///
-    /// * `client_id`: The client ID
-    /// * `client_auth_method`: The authentication method this client uses
-    /// * `encrypted_client_secret`: The encrypted client secret, if any
-    /// * `jwks`: The client JWKS, if any
-    /// * `jwks_uri`: The client JWKS URI, if any
-    /// * `redirect_uris`: The list of redirect URIs used by this client
+    /// * `params`: The metadata of the client to add or replace. See the fields
+    ///   of [`OAuth2StaticClientParams`]
     ///
     /// # Errors
     ///
     /// Returns [`Self::Error`] if the underlying repository fails
-    #[allow(clippy::too_many_arguments)]
     async fn upsert_static(
         &mut self,
-        client_id: Ulid,
-        client_name: Option<String>,
-        client_auth_method: OAuthClientAuthenticationMethod,
-        encrypted_client_secret: Option<String>,
-        jwks: Option<PublicJsonWebKeySet>,
-        jwks_uri: Option<Url>,
-        redirect_uris: Vec<Url>,
+        params: OAuth2StaticClientParams,
     ) -> Result<Client, Self::Error>;
 
     /// List all static clients
@@ -216,34 +388,14 @@ repository_impl!(OAuth2ClientRepository:
         &mut self,
         rng: &mut (dyn RngCore + Send),
         clock: &dyn Clock,
-        redirect_uris: Vec<Url>,
-        metadata_digest: Option<String>,
-        encrypted_client_secret: Option<String>,
-        application_type: Option<ApplicationType>,
-        grant_types: Vec<GrantType>,
-        client_name: Option<String>,
-        logo_uri: Option<Url>,
-        client_uri: Option<Url>,
-        policy_uri: Option<Url>,
-        tos_uri: Option<Url>,
-        jwks_uri: Option<Url>,
-        jwks: Option<PublicJsonWebKeySet>,
-        id_token_signed_response_alg: Option<JsonWebSignatureAlg>,
-        userinfo_signed_response_alg: Option<JsonWebSignatureAlg>,
-        token_endpoint_auth_method: Option<OAuthClientAuthenticationMethod>,
-        token_endpoint_auth_signing_alg: Option<JsonWebSignatureAlg>,
-        initiate_login_uri: Option<Url>,
+        params: OAuth2ClientParams,
     ) -> Result<Client, Self::Error>;
 
+    async fn update(&mut self, client: Client) -> Result<Client, Self::Error>;
+
     async fn upsert_static(
         &mut self,
-        client_id: Ulid,
-        client_name: Option<String>,
-        client_auth_method: OAuthClientAuthenticationMethod,
-        encrypted_client_secret: Option<String>,
-        jwks: Option<PublicJsonWebKeySet>,
-        jwks_uri: Option<Url>,
-        redirect_uris: Vec<Url>,
+        params: OAuth2StaticClientParams,
     ) -> Result<Client, Self::Error>;
 
     async fn all_static(&mut self) -> Result<Vec<Client>, Self::Error>;
@@ -0,0 +1,184 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_trait::async_trait;
+use mas_data_model::{Client, Consent, User};
+use oauth2_types::scope::Scope;
+use rand_core::RngCore;
+use ulid::Ulid;
+
+use crate::{Clock, Pagination, pagination::Page, repository_impl};
+
+/// Filter parameters for listing OAuth 2.0 consents
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct OAuth2ConsentFilter<'a> {
+    user: Option<&'a User>,
+    client: Option<&'a Client>,
+}
+
+impl<'a> OAuth2ConsentFilter<'a> {
+    /// Create a new [`OAuth2ConsentFilter`] with default values
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filter for consents given by a specific user
+    #[must_use]
+    pub fn for_user(mut self, user: &'a User) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    /// Filter for consents given to a specific client
+    #[must_use]
+    pub fn for_client(mut self, client: &'a Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Get the user filter
+    ///
+    /// Returns [`None`] if no user filter is set
+    #[must_use]
+    pub fn user(&self) -> Option<&User> {
+        self.user
+    }
+
+    /// Get the client filter
+    ///
+    /// Returns [`None`] if no client filter is set
+    #[must_use]
+    pub fn client(&self) -> Option<&Client> {
+        self.client
+    }
+}
+
+/// An [`OAuth2ConsentRepository`] helps interacting with the [`Consent`]s
+/// users gave to OAuth 2.0 clients, saved in the storage backend
+#[async_trait]
+pub trait OAuth2ConsentRepository: Send + Sync {
+    /// The error type returned by the repository
+    type Error;
+
+    /// Lookup a [`Consent`] by its ID
+    ///
+    /// Returns `None` if no [`Consent`] was found
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: The ID of the [`Consent`] to lookup
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<Consent>, Self::Error>;
+
+    /// Find the [`Consent`] a [`User`] gave to a [`Client`]
+    ///
+    /// Returns `None` if the user never consented, or revoked their consent
+    ///
+    /// # Parameters
+    ///
+    /// * `user`: The [`User`] who gave their consent
+    /// * `client`: The [`Client`] the consent was given to
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn find(&mut self, user: &User, client: &Client) -> Result<Option<Consent>, Self::Error>;
+
+    /// Record that a [`User`] consented to grant a [`Scope`] to a [`Client`]
+    ///
+    /// If the user already consented to other scope tokens for this client,
+    /// those are kept. Only the [`Consent::remembered_scope`] of the given
+    /// scope is recorded.
+    ///
+    /// Returns the updated [`Consent`]
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user`: The [`User`] who gave their consent
+    /// * `client`: The [`Client`] the consent is given to
+    /// * `scope`: The [`Scope`] the user consented to
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn give(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        client: &Client,
+        scope: &Scope,
+    ) -> Result<Consent, Self::Error>;
+
+    /// List [`Consent`]s with the given filter and pagination
+    ///
+    /// # Parameters
+    ///
+    /// * `filter`: The filter parameters
+    /// * `pagination`: The pagination parameters
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn list(
+        &mut self,
+        filter: OAuth2ConsentFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<Consent>, Self::Error>;
+
+    /// Count the [`Consent`]s matching the given filter
+    ///
+    /// # Parameters
+    ///
+    /// * `filter`: The filter parameters
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn count(&mut self, filter: OAuth2ConsentFilter<'_>) -> Result<usize, Self::Error>;
+
+    /// Revoke a [`Consent`], so that the user gets asked again the next time
+    /// the client wants access
+    ///
+    /// # Parameters
+    ///
+    /// * `consent`: The [`Consent`] to revoke
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn revoke(&mut self, consent: Consent) -> Result<(), Self::Error>;
+}
+
+repository_impl!(OAuth2ConsentRepository:
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<Consent>, Self::Error>;
+
+    async fn find(&mut self, user: &User, client: &Client) -> Result<Option<Consent>, Self::Error>;
+
+    async fn give(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        client: &Client,
+        scope: &Scope,
+    ) -> Result<Consent, Self::Error>;
+
+    async fn list(
+        &mut self,
+        filter: OAuth2ConsentFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<Consent>, Self::Error>;
+
+    async fn count(&mut self, filter: OAuth2ConsentFilter<'_>) -> Result<usize, Self::Error>;
+
+    async fn revoke(&mut self, consent: Consent) -> Result<(), Self::Error>;
+);
@@ -8,7 +8,7 @@ use std::net::IpAddr;
 
 use async_trait::async_trait;
 use chrono::Duration;
-use mas_data_model::{BrowserSession, Client, DeviceCodeGrant, Session};
+use mas_data_model::{BrowserSession, Client, DeviceCodeGrant, Session, User};
 use oauth2_types::scope::Scope;
 use rand_core::RngCore;
 use ulid::Ulid;
@@ -37,6 +37,18 @@ pub struct OAuth2DeviceCodeGrantParams<'a> {
 
     /// The user agent from which the request was made
     pub user_agent: Option<String>,
+
+    /// The user for whom authentication was requested, for client-initiated
+    /// backchannel authentication requests
+    pub user: Option<&'a User>,
+
+    /// The message to display to the user to tie the request to the device
+    /// which started it
+    pub binding_message: Option<String>,
+
+    /// The bearer token used to authenticate the notification sent to the
+    /// client, in the `ping` token delivery mode
+    pub client_notification_token: Option<String>,
 }
 
 /// An [`OAuth2DeviceCodeGrantRepository`] helps interacting with
@@ -112,6 +124,25 @@ pub trait OAuth2DeviceCodeGrantRepository: Send + Sync {
         user_code: &str,
     ) -> Result<Option<DeviceCodeGrant>, Self::Error>;
 
+    /// List the pending, non-expired client-initiated backchannel
+    /// authentication requests for a user
+    ///
+    /// Returns the grants, oldest first
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to determine which grants are expired
+    /// * `user`: The user for whom authentication was requested
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn list_pending_for_user(
+        &mut self,
+        clock: &dyn Clock,
+        user: &User,
+    ) -> Result<Vec<DeviceCodeGrant>, Self::Error>;
+
     /// Mark the device code grant as fulfilled with the given browser session
     ///
     /// Returns the updated device code grant
@@ -205,6 +236,12 @@ repository_impl!(OAuth2DeviceCodeGrantRepository:
         user_code: &str,
     ) -> Result<Option<DeviceCodeGrant>, Self::Error>;
 
+    async fn list_pending_for_user(
+        &mut self,
+        clock: &dyn Clock,
+        user: &User,
+    ) -> Result<Vec<DeviceCodeGrant>, Self::Error>;
+
     async fn fulfill(
         &mut self,
         clock: &dyn Clock,
@@ -9,15 +9,21 @@
 mod access_token;
 mod authorization_grant;
 mod client;
+mod consent;
 mod device_code_grant;
+mod pushed_authorization_request;
 mod refresh_token;
 mod session;
+mod used_jwt_id;
 
 pub use self::{
     access_token::OAuth2AccessTokenRepository,
-    authorization_grant::OAuth2AuthorizationGrantRepository,
-    client::OAuth2ClientRepository,
+    authorization_grant::{OAuth2AuthorizationGrantParams, OAuth2AuthorizationGrantRepository},
+    client::{OAuth2ClientParams, OAuth2ClientRepository, OAuth2StaticClientParams},
+    consent::{OAuth2ConsentFilter, OAuth2ConsentRepository},
     device_code_grant::{OAuth2DeviceCodeGrantParams, OAuth2DeviceCodeGrantRepository},
+    pushed_authorization_request::OAuth2PushedAuthorizationRequestRepository,
     refresh_token::OAuth2RefreshTokenRepository,
     session::{OAuth2SessionFilter, OAuth2SessionRepository},
+    used_jwt_id::OAuth2UsedJwtIdRepository,
 };
@@ -4,8 +4,10 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
+use std::net::IpAddr;
+
 use async_trait::async_trait;
-use mas_data_model::{AccessToken, RefreshToken, Session};
+use mas_data_model::{AccessToken, RefreshToken, RefreshTokenReuse, Session};
 use rand_core::RngCore;
 use ulid::Ulid;
 
@@ -111,6 +113,48 @@ pub trait OAuth2RefreshTokenRepository: Send + Sync {
         clock: &dyn Clock,
         refresh_token: RefreshToken,
     ) -> Result<RefreshToken, Self::Error>;
+
+    /// Revoke all the valid refresh tokens of a session
+    ///
+    /// Returns the number of refresh tokens that were revoked
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `session`: The [`Session`] whose refresh tokens should be revoked
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn revoke_for_session(
+        &mut self,
+        clock: &dyn Clock,
+        session: &Session,
+    ) -> Result<usize, Self::Error>;
+
+    /// Record that a consumed refresh token was presented again
+    ///
+    /// Returns the newly created [`RefreshTokenReuse`]
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `refresh_token`: The consumed [`RefreshToken`] which was presented
+    /// * `ip_address`: The IP address from which it was presented, if known
+    /// * `user_agent`: The user agent which presented it, if known
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn record_reuse(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        refresh_token: &RefreshToken,
+        ip_address: Option<IpAddr>,
+        user_agent: Option<String>,
+    ) -> Result<RefreshTokenReuse, Self::Error>;
 }
 
 repository_impl!(OAuth2RefreshTokenRepository:
@@ -142,4 +186,19 @@ repository_impl!(OAuth2RefreshTokenRepository:
         clock: &dyn Clock,
         refresh_token: RefreshToken,
     ) -> Result<RefreshToken, Self::Error>;
+
+    async fn revoke_for_session(
+        &mut self,
+        clock: &dyn Clock,
+        session: &Session,
+    ) -> Result<usize, Self::Error>;
+
+    async fn record_reuse(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        refresh_token: &RefreshToken,
+        ip_address: Option<IpAddr>,
+        user_agent: Option<String>,
+    ) -> Result<RefreshTokenReuse, Self::Error>;
 );
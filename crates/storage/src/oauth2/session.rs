@@ -9,7 +9,10 @@ use std::net::IpAddr;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use mas_data_model::{BrowserSession, Client, Device, Session, User};
-use oauth2_types::scope::Scope;
+use oauth2_types::{
+    requests::{ActorClaim, AuthorizationDetails, ClaimsParameter},
+    scope::Scope,
+};
 use rand_core::RngCore;
 use ulid::Ulid;
 
@@ -461,6 +464,75 @@ pub trait OAuth2SessionRepository: Send + Sync {
         session: Session,
         human_name: Option<String>,
     ) -> Result<Session, Self::Error>;
+
+    /// Set the individual claims requested through the `claims` parameter
+    /// for a [`Session`]
+    ///
+    /// # Parameters
+    ///
+    /// * `session`: The [`Session`] to set the requested claims for
+    /// * `requested_claims`: The requested claims to set
+    async fn set_requested_claims(
+        &mut self,
+        session: Session,
+        requested_claims: Option<ClaimsParameter>,
+    ) -> Result<Session, Self::Error>;
+
+    /// Bind the tokens of a [`Session`] to a `DPoP` key
+    ///
+    /// # Parameters
+    ///
+    /// * `session`: The [`Session`] to bind
+    /// * `dpop_jkt`: The JWK SHA-256 thumbprint of the `DPoP` key
+    async fn set_dpop_jkt(
+        &mut self,
+        session: Session,
+        dpop_jkt: String,
+    ) -> Result<Session, Self::Error>;
+
+    /// Bind the tokens of a [`Session`] to a client certificate
+    ///
+    /// # Parameters
+    ///
+    /// * `session`: The [`Session`] to bind
+    /// * `x5t_s256`: The SHA-256 thumbprint of the client certificate
+    async fn set_x5t_s256(
+        &mut self,
+        session: Session,
+        x5t_s256: String,
+    ) -> Result<Session, Self::Error>;
+
+    /// Restrict the tokens of a [`Session`] to the given audience
+    ///
+    /// # Parameters
+    ///
+    /// * `session`: The [`Session`] to restrict
+    /// * `audience`: The audience the tokens are intended for
+    async fn set_audience(
+        &mut self,
+        session: Session,
+        audience: Vec<String>,
+    ) -> Result<Session, Self::Error>;
+
+    /// Record the party acting on behalf of the user of a [`Session`]
+    ///
+    /// # Parameters
+    ///
+    /// * `session`: The [`Session`] to update
+    /// * `act`: The actor claim to record
+    async fn set_act(&mut self, session: Session, act: ActorClaim) -> Result<Session, Self::Error>;
+
+    /// Set the fine-grained authorization details granted to a [`Session`]
+    ///
+    /// # Parameters
+    ///
+    /// * `session`: The [`Session`] to set the authorization details for
+    /// * `authorization_details`: The authorization details to set
+    async fn set_authorization_details(
+        &mut self,
+        session: Session,
+        authorization_details: Option<AuthorizationDetails>,
+    ) -> Result<Session, Self::Error>;
 }
 
 repository_impl!(OAuth2SessionRepository:
@@ -526,4 +598,30 @@ repository_impl!(OAuth2SessionRepository:
         session: Session,
         human_name: Option<String>,
     ) -> Result<Session, Self::Error>;
+
+    async fn set_requested_claims(
+        &mut self,
+        session: Session,
+        requested_claims: Option<ClaimsParameter>,
+    ) -> Result<Session, Self::Error>;
+
+    async fn set_dpop_jkt(&mut self, session: Session, dpop_jkt: String)
+    -> Result<Session, Self::Error>;
+
+    async fn set_x5t_s256(&mut self, session: Session, x5t_s256: String)
+    -> Result<Session, Self::Error>;
+
+    async fn set_audience(
+        &mut self,
+        session: Session,
+        audience: Vec<String>,
+    ) -> Result<Session, Self::Error>;
+
+    async fn set_act(&mut self, session: Session, act: ActorClaim) -> Result<Session, Self::Error>;
+
+    async fn set_authorization_details(
+        &mut self,
+        session: Session,
+        authorization_details: Option<AuthorizationDetails>,
+    ) -> Result<Session, Self::Error>;
 );
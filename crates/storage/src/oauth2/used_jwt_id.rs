@@ -0,0 +1,66 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{Clock, repository_impl};
+
+/// An [`OAuth2UsedJwtIdRepository`] keeps track of the JWT IDs (`jti`) which
+/// were already used, to detect replayed JWTs
+#[async_trait]
+pub trait OAuth2UsedJwtIdRepository: Send + Sync {
+    /// The error type returned by the repository
+    type Error;
+
+    /// Mark a JWT ID as used
+    ///
+    /// Returns `true` if the JWT ID was not used before, `false` if it was
+    /// already used and has not expired yet, meaning the JWT is being replayed
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `issuer`: Who issued the JWT, used to scope the JWT ID
+    /// * `jti`: The JWT ID
+    /// * `expires_at`: When the JWT expires, after which the JWT ID can be
+    ///   forgotten
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn mark_used(
+        &mut self,
+        clock: &dyn Clock,
+        issuer: &str,
+        jti: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, Self::Error>;
+
+    /// Cleanup JWT IDs of expired JWTs
+    ///
+    /// Returns the number of JWT IDs that were cleaned up
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to get the current time
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn cleanup_expired(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error>;
+}
+
+repository_impl!(OAuth2UsedJwtIdRepository:
+    async fn mark_used(
+        &mut self,
+        clock: &dyn Clock,
+        issuer: &str,
+        jti: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, Self::Error>;
+
+    async fn cleanup_expired(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error>;
+);
@@ -5,7 +5,7 @@
 
 use chrono::{DateTime, Utc};
 use mas_data_model::{
-    BrowserSession, CompatSession, Device, Session, User, UserEmailAuthentication,
+    BrowserSession, CompatSession, Device, DeviceCodeGrant, Session, User, UserEmailAuthentication,
     UserRecoverySession,
 };
 use serde::{Deserialize, Serialize};
@@ -507,6 +507,176 @@ impl InsertableJob for ExpireInactiveUserSessionsJob {
     const QUEUE_NAME: &'static str = "expire-inactive-user-sessions";
 }
 
+/// The sessions concerned by a [`BackchannelLogoutJob`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BackchannelLogoutTarget {
+    /// The OAuth 2.0 sessions started from a browser session
+    BrowserSession {
+        /// The ID of the browser session
+        browser_session_id: Ulid,
+    },
+
+    /// All the OAuth 2.0 sessions of a user
+    User {
+        /// The ID of the user
+        user_id: Ulid,
+    },
+}
+
+/// A job to notify the clients of a set of OAuth 2.0 sessions that the user
+/// logged out, through [OpenID Connect Back-Channel Logout].
+///
+/// This schedules a [`SendBackchannelLogoutJob`] for each session whose client
+/// registered a back-channel logout URI.
+///
+/// [OpenID Connect Back-Channel Logout]: https://openid.net/specs/openid-connect-backchannel-1_0.html
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackchannelLogoutJob {
+    target: BackchannelLogoutTarget,
+    logged_out_at: DateTime<Utc>,
+    after: Option<Ulid>,
+}
+
+impl BackchannelLogoutJob {
+    /// Create a new job to notify the clients of the OAuth 2.0 sessions started
+    /// from the given browser session
+    ///
+    /// # Parameters
+    ///
+    /// * `browser_session` - The browser session which ended
+    /// * `logged_out_at` - When the browser session ended
+    #[must_use]
+    pub fn for_browser_session(
+        browser_session: &BrowserSession,
+        logged_out_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            target: BackchannelLogoutTarget::BrowserSession {
+                browser_session_id: browser_session.id,
+            },
+            logged_out_at,
+            after: None,
+        }
+    }
+
+    /// Create a new job to notify the clients of all the OAuth 2.0 sessions of
+    /// the given user
+    ///
+    /// # Parameters
+    ///
+    /// * `user` - The user who got logged out
+    /// * `logged_out_at` - When the user got logged out
+    #[must_use]
+    pub fn for_user(user: &User, logged_out_at: DateTime<Utc>) -> Self {
+        Self {
+            target: BackchannelLogoutTarget::User { user_id: user.id },
+            logged_out_at,
+            after: None,
+        }
+    }
+
+    /// The sessions concerned by this job
+    #[must_use]
+    pub fn target(&self) -> BackchannelLogoutTarget {
+        self.target
+    }
+
+    /// When the user got logged out.
+    ///
+    /// Sessions which were finished before that don't need to be notified.
+    #[must_use]
+    pub fn logged_out_at(&self) -> DateTime<Utc> {
+        self.logged_out_at
+    }
+
+    /// Get the pagination cursor
+    #[must_use]
+    pub fn pagination(&self, batch_size: usize) -> Pagination {
+        let pagination = Pagination::first(batch_size);
+        if let Some(after) = self.after {
+            pagination.after(after)
+        } else {
+            pagination
+        }
+    }
+
+    /// Get the next job given the page returned by the database
+    #[must_use]
+    pub fn next(&self, page: &Page<Session>) -> Option<Self> {
+        if !page.has_next_page {
+            return None;
+        }
+
+        let last_edge = page.edges.last()?;
+        Some(Self {
+            target: self.target,
+            logged_out_at: self.logged_out_at,
+            after: Some(last_edge.id),
+        })
+    }
+}
+
+impl InsertableJob for BackchannelLogoutJob {
+    const QUEUE_NAME: &'static str = "backchannel-logout";
+}
+
+/// A job to send a logout token to the back-channel logout URI of the client of
+/// an OAuth 2.0 session
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SendBackchannelLogoutJob {
+    oauth2_session_id: Ulid,
+}
+
+impl SendBackchannelLogoutJob {
+    /// Create a new job to send a logout token to the client of the given
+    /// OAuth 2.0 session
+    #[must_use]
+    pub fn new(session: &Session) -> Self {
+        Self {
+            oauth2_session_id: session.id,
+        }
+    }
+
+    /// The ID of the OAuth 2.0 session the user got logged out of
+    #[must_use]
+    pub fn oauth2_session_id(&self) -> Ulid {
+        self.oauth2_session_id
+    }
+}
+
+impl InsertableJob for SendBackchannelLogoutJob {
+    const QUEUE_NAME: &'static str = "send-backchannel-logout";
+}
+
+/// A job to notify a client that a client-initiated backchannel authentication
+/// request was approved or rejected, in the `ping` token delivery mode
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SendBackchannelAuthenticationNotificationJob {
+    device_code_grant_id: Ulid,
+}
+
+impl SendBackchannelAuthenticationNotificationJob {
+    /// Create a new job to notify the client of the given backchannel
+    /// authentication request
+    #[must_use]
+    pub fn new(grant: &DeviceCodeGrant) -> Self {
+        Self {
+            device_code_grant_id: grant.id,
+        }
+    }
+
+    /// The ID of the device code grant backing the request
+    #[must_use]
+    pub fn device_code_grant_id(&self) -> Ulid {
+        self.device_code_grant_id
+    }
+}
+
+impl InsertableJob for SendBackchannelAuthenticationNotificationJob {
+    const QUEUE_NAME: &'static str = "send-backchannel-authentication-notification";
+}
+
 /// Prune stale policy data
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PruneStalePolicyDataJob;
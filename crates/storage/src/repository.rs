@@ -16,7 +16,9 @@ use crate::{
     },
     oauth2::{
         OAuth2AccessTokenRepository, OAuth2AuthorizationGrantRepository, OAuth2ClientRepository,
-        OAuth2DeviceCodeGrantRepository, OAuth2RefreshTokenRepository, OAuth2SessionRepository,
+        OAuth2ConsentRepository, OAuth2DeviceCodeGrantRepository,
+        OAuth2PushedAuthorizationRequestRepository, OAuth2RefreshTokenRepository,
+        OAuth2SessionRepository, OAuth2UsedJwtIdRepository,
     },
     policy_data::PolicyDataRepository,
     queue::{QueueJobRepository, QueueScheduleRepository, QueueWorkerRepository},
@@ -169,6 +171,11 @@ pub trait RepositoryAccess: Send {
     fn oauth2_client<'c>(&'c mut self)
     -> Box<dyn OAuth2ClientRepository<Error = Self::Error> + 'c>;
 
+    /// Get an [`OAuth2ConsentRepository`]
+    fn oauth2_consent<'c>(
+        &'c mut self,
+    ) -> Box<dyn OAuth2ConsentRepository<Error = Self::Error> + 'c>;
+
     /// Get an [`OAuth2AuthorizationGrantRepository`]
     fn oauth2_authorization_grant<'c>(
         &'c mut self,
@@ -194,6 +201,16 @@ pub trait RepositoryAccess: Send {
         &'c mut self,
     ) -> Box<dyn OAuth2DeviceCodeGrantRepository<Error = Self::Error> + 'c>;
 
+    /// Get an [`OAuth2PushedAuthorizationRequestRepository`]
+    fn oauth2_pushed_authorization_request<'c>(
+        &'c mut self,
+    ) -> Box<dyn OAuth2PushedAuthorizationRequestRepository<Error = Self::Error> + 'c>;
+
+    /// Get an [`OAuth2UsedJwtIdRepository`]
+    fn oauth2_used_jwt_id<'c>(
+        &'c mut self,
+    ) -> Box<dyn OAuth2UsedJwtIdRepository<Error = Self::Error> + 'c>;
+
     /// Get a [`CompatSessionRepository`]
     fn compat_session<'c>(
         &'c mut self,
@@ -244,8 +261,9 @@ mod impls {
         },
         oauth2::{
             OAuth2AccessTokenRepository, OAuth2AuthorizationGrantRepository,
-            OAuth2ClientRepository, OAuth2DeviceCodeGrantRepository, OAuth2RefreshTokenRepository,
-            OAuth2SessionRepository,
+            OAuth2ClientRepository, OAuth2ConsentRepository, OAuth2DeviceCodeGrantRepository,
+            OAuth2PushedAuthorizationRequestRepository, OAuth2RefreshTokenRepository,
+            OAuth2SessionRepository, OAuth2UsedJwtIdRepository,
         },
         policy_data::PolicyDataRepository,
         queue::{QueueJobRepository, QueueScheduleRepository, QueueWorkerRepository},
@@ -386,6 +404,12 @@ mod impls {
             Box::new(MapErr::new(self.inner.oauth2_client(), &mut self.mapper))
         }
 
+        fn oauth2_consent<'c>(
+            &'c mut self,
+        ) -> Box<dyn OAuth2ConsentRepository<Error = Self::Error> + 'c> {
+            Box::new(MapErr::new(self.inner.oauth2_consent(), &mut self.mapper))
+        }
+
         fn oauth2_authorization_grant<'c>(
             &'c mut self,
         ) -> Box<dyn OAuth2AuthorizationGrantRepository<Error = Self::Error> + 'c> {
@@ -428,6 +452,24 @@ mod impls {
             ))
         }
 
+        fn oauth2_pushed_authorization_request<'c>(
+            &'c mut self,
+        ) -> Box<dyn OAuth2PushedAuthorizationRequestRepository<Error = Self::Error> + 'c> {
+            Box::new(MapErr::new(
+                self.inner.oauth2_pushed_authorization_request(),
+                &mut self.mapper,
+            ))
+        }
+
+        fn oauth2_used_jwt_id<'c>(
+            &'c mut self,
+        ) -> Box<dyn OAuth2UsedJwtIdRepository<Error = Self::Error> + 'c> {
+            Box::new(MapErr::new(
+                self.inner.oauth2_used_jwt_id(),
+                &mut self.mapper,
+            ))
+        }
+
         fn compat_session<'c>(
             &'c mut self,
         ) -> Box<dyn CompatSessionRepository<Error = Self::Error> + 'c> {
@@ -556,6 +598,12 @@ mod impls {
             (**self).oauth2_client()
         }
 
+        fn oauth2_consent<'c>(
+            &'c mut self,
+        ) -> Box<dyn OAuth2ConsentRepository<Error = Self::Error> + 'c> {
+            (**self).oauth2_consent()
+        }
+
         fn oauth2_authorization_grant<'c>(
             &'c mut self,
         ) -> Box<dyn OAuth2AuthorizationGrantRepository<Error = Self::Error> + 'c> {
@@ -586,6 +634,18 @@ mod impls {
             (**self).oauth2_device_code_grant()
         }
 
+        fn oauth2_pushed_authorization_request<'c>(
+            &'c mut self,
+        ) -> Box<dyn OAuth2PushedAuthorizationRequestRepository<Error = Self::Error> + 'c> {
+            (**self).oauth2_pushed_authorization_request()
+        }
+
+        fn oauth2_used_jwt_id<'c>(
+            &'c mut self,
+        ) -> Box<dyn OAuth2UsedJwtIdRepository<Error = Self::Error> + 'c> {
+            (**self).oauth2_used_jwt_id()
+        }
+
         fn compat_session<'c>(
             &'c mut self,
         ) -> Box<dyn CompatSessionRepository<Error = Self::Error> + 'c> {
@@ -0,0 +1,80 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Job notifying clients about [OpenID Connect Client-Initiated Backchannel
+//! Authentication] requests in the `ping` mode
+//!
+//! [OpenID Connect Client-Initiated Backchannel Authentication]: https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#ping_callback
+
+use anyhow::Context;
+use async_trait::async_trait;
+use mas_http::RequestBuilderExt as _;
+use mas_storage::{
+    RepositoryAccess,
+    oauth2::{OAuth2ClientRepository, OAuth2DeviceCodeGrantRepository},
+    queue::SendBackchannelAuthenticationNotificationJob,
+};
+use serde_json::json;
+use tracing::info;
+
+use crate::{
+    State,
+    new_queue::{JobContext, JobError, RunnableJob},
+};
+
+#[async_trait]
+impl RunnableJob for SendBackchannelAuthenticationNotificationJob {
+    #[tracing::instrument(
+        name = "job.send_backchannel_authentication_notification",
+        fields(oauth2_device_code.id = %self.device_code_grant_id()),
+        skip_all,
+    )]
+    async fn run(&self, state: &State, _context: JobContext) -> Result<(), JobError> {
+        let mut repo = state.repository().await.map_err(JobError::retry)?;
+
+        let grant = repo
+            .oauth2_device_code_grant()
+            .lookup(self.device_code_grant_id())
+            .await
+            .map_err(JobError::retry)?
+            .context("Device code grant not found")
+            .map_err(JobError::fail)?;
+
+        let Some(client_notification_token) = grant.client_notification_token.clone() else {
+            // The client didn't ask to be notified
+            return Ok(());
+        };
+
+        let client = repo
+            .oauth2_client()
+            .lookup(grant.client_id)
+            .await
+            .map_err(JobError::retry)?
+            .context("Client not found")
+            .map_err(JobError::fail)?;
+
+        let Some(endpoint) = client.backchannel_client_notification_endpoint.clone() else {
+            // The client doesn't have a notification endpoint anymore
+            return Ok(());
+        };
+
+        // We don't need the repository anymore, and we don't want to hold the
+        // connection while calling the client
+        repo.cancel().await.map_err(JobError::retry)?;
+
+        info!(client.id = %client.id, %endpoint, "Sending backchannel authentication notification");
+        state
+            .http_client()
+            .post(endpoint)
+            .bearer_auth(client_notification_token)
+            .json(&json!({ "auth_req_id": grant.device_code }))
+            .send_traced()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(JobError::retry)?;
+
+        Ok(())
+    }
+}
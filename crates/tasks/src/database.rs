@@ -27,6 +27,18 @@ impl RunnableJob for CleanupExpiredTokensJob {
             .cleanup_revoked(clock)
             .await
             .map_err(JobError::retry)?;
+
+        let jwt_id_count = repo
+            .oauth2_used_jwt_id()
+            .cleanup_expired(clock)
+            .await
+            .map_err(JobError::retry)?;
+
+        let pushed_request_count = repo
+            .oauth2_pushed_authorization_request()
+            .cleanup(clock)
+            .await
+            .map_err(JobError::retry)?;
         repo.save().await.map_err(JobError::retry)?;
 
         if count == 0 {
@@ -35,6 +47,21 @@ impl RunnableJob for CleanupExpiredTokensJob {
             info!(count, "cleaned up revoked tokens");
         }
 
+        if jwt_id_count == 0 {
+            debug!("no used JWT ID to clean up");
+        } else {
+            info!(count = jwt_id_count, "cleaned up used JWT IDs");
+        }
+
+        if pushed_request_count == 0 {
+            debug!("no pushed authorization request to clean up");
+        } else {
+            info!(
+                count = pushed_request_count,
+                "cleaned up pushed authorization requests"
+            );
+        }
+
         Ok(())
     }
 }
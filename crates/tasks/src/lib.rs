@@ -8,6 +8,7 @@ use std::sync::{Arc, LazyLock};
 
 use mas_data_model::SiteConfig;
 use mas_email::Mailer;
+use mas_keystore::Keystore;
 use mas_matrix::HomeserverConnection;
 use mas_router::UrlBuilder;
 use mas_storage::{BoxRepository, Clock, RepositoryError, RepositoryFactory};
@@ -20,8 +21,10 @@ use tokio_util::{sync::CancellationToken, task::TaskTracker};
 
 pub use crate::new_queue::QueueWorker;
 
+mod backchannel_authentication;
 mod database;
 mod email;
+mod logout;
 mod matrix;
 mod new_queue;
 mod recovery;
@@ -45,9 +48,12 @@ struct State {
     homeserver: Arc<dyn HomeserverConnection>,
     url_builder: UrlBuilder,
     site_config: SiteConfig,
+    key_store: Keystore,
+    http_client: reqwest::Client,
 }
 
 impl State {
+    #[expect(clippy::too_many_arguments, reason = "this is fine")]
     pub fn new(
         repository_factory: PgRepositoryFactory,
         clock: impl Clock + 'static,
@@ -55,6 +61,8 @@ impl State {
         homeserver: impl HomeserverConnection + 'static,
         url_builder: UrlBuilder,
         site_config: SiteConfig,
+        key_store: Keystore,
+        http_client: reqwest::Client,
     ) -> Self {
         Self {
             repository_factory,
@@ -63,6 +71,8 @@ impl State {
             homeserver: Arc::new(homeserver),
             url_builder,
             site_config,
+            key_store,
+            http_client,
         }
     }
 
@@ -99,6 +109,14 @@ impl State {
     pub fn site_config(&self) -> &SiteConfig {
         &self.site_config
     }
+
+    pub fn key_store(&self) -> &Keystore {
+        &self.key_store
+    }
+
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
 }
 
 /// Initialise the worker, without running it.
@@ -108,6 +126,7 @@ impl State {
 /// # Errors
 ///
 /// This function can fail if the database connection fails.
+#[expect(clippy::too_many_arguments, reason = "this is fine")]
 pub async fn init(
     repository_factory: PgRepositoryFactory,
     clock: impl Clock + 'static,
@@ -115,6 +134,8 @@ pub async fn init(
     homeserver: impl HomeserverConnection + 'static,
     url_builder: UrlBuilder,
     site_config: &SiteConfig,
+    key_store: &Keystore,
+    http_client: reqwest::Client,
     cancellation_token: CancellationToken,
 ) -> Result<QueueWorker, QueueRunnerError> {
     let state = State::new(
@@ -124,10 +145,13 @@ pub async fn init(
         homeserver,
         url_builder,
         site_config.clone(),
+        key_store.clone(),
+        http_client,
     );
     let mut worker = QueueWorker::new(state, cancellation_token).await?;
 
     worker
+        .register_handler::<mas_storage::queue::BackchannelLogoutJob>()
         .register_handler::<mas_storage::queue::CleanupExpiredTokensJob>()
         .register_handler::<mas_storage::queue::DeactivateUserJob>()
         .register_handler::<mas_storage::queue::DeleteDeviceJob>()
@@ -135,6 +159,8 @@ pub async fn init(
         .register_handler::<mas_storage::queue::ProvisionUserJob>()
         .register_handler::<mas_storage::queue::ReactivateUserJob>()
         .register_handler::<mas_storage::queue::SendAccountRecoveryEmailsJob>()
+        .register_handler::<mas_storage::queue::SendBackchannelAuthenticationNotificationJob>()
+        .register_handler::<mas_storage::queue::SendBackchannelLogoutJob>()
         .register_handler::<mas_storage::queue::SendEmailAuthenticationCodeJob>()
         .register_handler::<mas_storage::queue::SyncDevicesJob>()
         .register_handler::<mas_storage::queue::VerifyEmailJob>()
@@ -177,6 +203,8 @@ pub async fn init_and_run(
     homeserver: impl HomeserverConnection + 'static,
     url_builder: UrlBuilder,
     site_config: &SiteConfig,
+    key_store: &Keystore,
+    http_client: reqwest::Client,
     cancellation_token: CancellationToken,
     task_tracker: &TaskTracker,
 ) -> Result<(), QueueRunnerError> {
@@ -187,6 +215,8 @@ pub async fn init_and_run(
         homeserver,
         url_builder,
         site_config,
+        key_store,
+        http_client,
         cancellation_token,
     )
     .await?;
@@ -0,0 +1,273 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Jobs implementing [OpenID Connect Back-Channel Logout] towards our own
+//! clients
+//!
+//! [OpenID Connect Back-Channel Logout]: https://openid.net/specs/openid-connect-backchannel-1_0.html
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::Duration;
+use mas_http::RequestBuilderExt as _;
+use mas_iana::jose::JsonWebSignatureAlg;
+use mas_jose::{
+    claims::{self, Claim},
+    constraints::Constrainable,
+    jwt::{JsonWebSignatureHeader, Jwt},
+};
+use mas_storage::{
+    RepositoryAccess,
+    oauth2::{OAuth2ClientRepository, OAuth2SessionFilter, OAuth2SessionRepository},
+    queue::{
+        BackchannelLogoutJob, BackchannelLogoutTarget, QueueJobRepositoryExt as _,
+        SendBackchannelLogoutJob,
+    },
+    user::{BrowserSessionRepository, UserRepository},
+};
+use serde_json::{Value, json};
+use tracing::info;
+use ulid::Ulid;
+
+use crate::{
+    State,
+    new_queue::{JobContext, JobError, RunnableJob},
+};
+
+/// The event URI which identifies a logout token
+const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+const EVENTS: Claim<Value> = Claim::new("events");
+
+/// How long a logout token is valid for
+const LOGOUT_TOKEN_TTL: Duration = Duration::minutes(2);
+
+#[async_trait]
+impl RunnableJob for BackchannelLogoutJob {
+    #[tracing::instrument(name = "job.backchannel_logout", skip_all)]
+    async fn run(&self, state: &State, _context: JobContext) -> Result<(), JobError> {
+        let mut repo = state.repository().await.map_err(JobError::retry)?;
+        let clock = state.clock();
+        let mut rng = state.rng();
+
+        let page = match self.target() {
+            BackchannelLogoutTarget::BrowserSession { browser_session_id } => {
+                let browser_session = repo
+                    .browser_session()
+                    .lookup(browser_session_id)
+                    .await
+                    .map_err(JobError::retry)?
+                    .context("Browser session not found")
+                    .map_err(JobError::fail)?;
+
+                let filter = OAuth2SessionFilter::new().for_browser_session(&browser_session);
+                repo.oauth2_session()
+                    .list(filter, self.pagination(100))
+                    .await
+                    .map_err(JobError::retry)?
+            }
+
+            BackchannelLogoutTarget::User { user_id } => {
+                let user = repo
+                    .user()
+                    .lookup(user_id)
+                    .await
+                    .map_err(JobError::retry)?
+                    .context("User not found")
+                    .map_err(JobError::fail)?;
+
+                let filter = OAuth2SessionFilter::new().for_user(&user);
+                repo.oauth2_session()
+                    .list(filter, self.pagination(100))
+                    .await
+                    .map_err(JobError::retry)?
+            }
+        };
+
+        if let Some(job) = self.next(&page) {
+            info!("Scheduling job to notify the next batch of sessions");
+            repo.queue_job()
+                .schedule_job(&mut rng, clock, job)
+                .await
+                .map_err(JobError::retry)?;
+        }
+
+        // Sessions which ended before the logout were already dealt with
+        let sessions: Vec<_> = page
+            .edges
+            .into_iter()
+            .filter(|session| {
+                session
+                    .finished_at()
+                    .is_none_or(|finished_at| finished_at >= self.logged_out_at())
+            })
+            .collect();
+
+        // Only notify the clients which registered a back-channel logout URI
+        let client_ids: BTreeSet<Ulid> = sessions.iter().map(|session| session.client_id).collect();
+        let clients = repo
+            .oauth2_client()
+            .load_batch(client_ids)
+            .await
+            .map_err(JobError::retry)?;
+
+        // The logout token only identifies the user and the browser session, so
+        // there is no point in notifying a client more than once for both
+        let mut notified = HashSet::new();
+        for session in sessions {
+            let Some(client) = clients.get(&session.client_id) else {
+                continue;
+            };
+
+            if client.backchannel_logout_uri.is_none() {
+                continue;
+            }
+
+            if !notified.insert((session.client_id, session.user_id, session.user_session_id)) {
+                continue;
+            }
+
+            info!(oauth2_session.id = %session.id, client.id = %client.id, "Scheduling back-channel logout");
+            repo.queue_job()
+                .schedule_job(&mut rng, clock, SendBackchannelLogoutJob::new(&session))
+                .await
+                .map_err(JobError::retry)?;
+        }
+
+        repo.save().await.map_err(JobError::retry)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RunnableJob for SendBackchannelLogoutJob {
+    #[tracing::instrument(
+        name = "job.send_backchannel_logout",
+        fields(oauth2_session.id = %self.oauth2_session_id()),
+        skip_all,
+    )]
+    async fn run(&self, state: &State, _context: JobContext) -> Result<(), JobError> {
+        let mut repo = state.repository().await.map_err(JobError::retry)?;
+        let clock = state.clock();
+        let mut rng = state.rng();
+
+        let session = repo
+            .oauth2_session()
+            .lookup(self.oauth2_session_id())
+            .await
+            .map_err(JobError::retry)?
+            .context("OAuth 2.0 session not found")
+            .map_err(JobError::fail)?;
+
+        let Some(user_id) = session.user_id else {
+            // Sessions without a user, like the ones from the client credentials
+            // grant, can't be logged out
+            return Ok(());
+        };
+
+        let client = repo
+            .oauth2_client()
+            .lookup(session.client_id)
+            .await
+            .map_err(JobError::retry)?
+            .context("Client not found")
+            .map_err(JobError::fail)?;
+
+        let Some(backchannel_logout_uri) = client.backchannel_logout_uri.clone() else {
+            // The client doesn't support back-channel logout
+            return Ok(());
+        };
+
+        let user = repo
+            .user()
+            .lookup(user_id)
+            .await
+            .map_err(JobError::retry)?
+            .context("User not found")
+            .map_err(JobError::fail)?;
+
+        // We don't need the repository anymore, and we don't want to hold the
+        // connection while calling the client
+        repo.cancel().await.map_err(JobError::retry)?;
+
+        let subject = client
+            .subject_for(&user, state.site_config().pairwise_subject_secret.as_ref())
+            .map_err(JobError::fail)?;
+
+        let now = clock.now();
+        let mut claims = HashMap::new();
+        claims::ISS
+            .insert(&mut claims, state.url_builder().oidc_issuer().to_string())
+            .map_err(JobError::fail)?;
+        claims::SUB
+            .insert(&mut claims, subject)
+            .map_err(JobError::fail)?;
+        claims::AUD
+            .insert(&mut claims, client.client_id.clone())
+            .map_err(JobError::fail)?;
+        claims::IAT
+            .insert(&mut claims, now)
+            .map_err(JobError::fail)?;
+        claims::EXP
+            .insert(&mut claims, now + LOGOUT_TOKEN_TTL)
+            .map_err(JobError::fail)?;
+        claims::JTI
+            .insert(
+                &mut claims,
+                Ulid::from_datetime_with_source(now.into(), &mut rng).to_string(),
+            )
+            .map_err(JobError::fail)?;
+        EVENTS
+            .insert(&mut claims, json!({ BACKCHANNEL_LOGOUT_EVENT: {} }))
+            .map_err(JobError::fail)?;
+
+        // The session ID matches the `sid` claim of the ID tokens, which is the
+        // ID of the browser session
+        if let Some(user_session_id) = session.user_session_id {
+            claims::SID
+                .insert(&mut claims, user_session_id.to_string())
+                .map_err(JobError::fail)?;
+        }
+
+        // Logout tokens are signed the same way as ID tokens
+        let alg = client
+            .id_token_signed_response_alg
+            .clone()
+            .unwrap_or(JsonWebSignatureAlg::Rs256);
+        let key = state
+            .key_store()
+            .signing_key_for_algorithm(&alg)
+            .context("No signing key for the logout token")
+            .map_err(JobError::fail)?;
+        let signer = key
+            .params()
+            .signing_key_for_alg(&alg)
+            .map_err(JobError::fail)?;
+        let kid = key
+            .kid()
+            .context("The signing key has no key ID")
+            .map_err(JobError::fail)?;
+        let header = JsonWebSignatureHeader::new(alg)
+            .with_kid(kid)
+            .with_typ("logout+jwt".to_owned());
+        let logout_token =
+            Jwt::sign_with_rng(&mut rng, header, claims, &signer).map_err(JobError::fail)?;
+
+        info!(client.id = %client.id, %backchannel_logout_uri, "Sending logout token");
+        state
+            .http_client()
+            .post(backchannel_logout_uri)
+            .form(&[("logout_token", logout_token.as_str())])
+            .send_traced()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(JobError::retry)?;
+
+        Ok(())
+    }
+}
@@ -11,8 +11,9 @@ use mas_storage::{
     compat::CompatSessionFilter,
     oauth2::OAuth2SessionFilter,
     queue::{
-        ExpireInactiveCompatSessionsJob, ExpireInactiveOAuthSessionsJob, ExpireInactiveSessionsJob,
-        ExpireInactiveUserSessionsJob, QueueJobRepositoryExt, SyncDevicesJob,
+        BackchannelLogoutJob, ExpireInactiveCompatSessionsJob, ExpireInactiveOAuthSessionsJob,
+        ExpireInactiveSessionsJob, ExpireInactiveUserSessionsJob, QueueJobRepositoryExt,
+        SendBackchannelLogoutJob, SyncDevicesJob,
     },
     user::BrowserSessionFilter,
 };
@@ -127,6 +128,11 @@ impl RunnableJob for ExpireInactiveOAuthSessionsJob {
                 }
             }
 
+            repo.queue_job()
+                .schedule_job(&mut rng, clock, SendBackchannelLogoutJob::new(&edge))
+                .await
+                .map_err(JobError::retry)?;
+
             repo.oauth2_session()
                 .finish(clock, edge)
                 .await
@@ -229,6 +235,15 @@ impl RunnableJob for ExpireInactiveUserSessionsJob {
         }
 
         for edge in page.edges {
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    clock,
+                    BackchannelLogoutJob::for_browser_session(&edge, clock.now()),
+                )
+                .await
+                .map_err(JobError::retry)?;
+
             repo.browser_session()
                 .finish(clock, edge)
                 .await
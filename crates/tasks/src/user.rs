@@ -10,7 +10,9 @@ use mas_storage::{
     RepositoryAccess,
     compat::CompatSessionFilter,
     oauth2::OAuth2SessionFilter,
-    queue::{DeactivateUserJob, ReactivateUserJob},
+    queue::{
+        BackchannelLogoutJob, DeactivateUserJob, QueueJobRepositoryExt as _, ReactivateUserJob,
+    },
     user::{BrowserSessionFilter, UserEmailFilter, UserRepository},
 };
 use tracing::info;
@@ -32,6 +34,7 @@ impl RunnableJob for DeactivateUserJob {
         let clock = state.clock();
         let matrix = state.matrix_connection();
         let mut repo = state.repository().await.map_err(JobError::retry)?;
+        let mut rng = state.rng();
 
         let user = repo
             .user()
@@ -49,6 +52,17 @@ impl RunnableJob for DeactivateUserJob {
             .context("Failed to deactivate user")
             .map_err(JobError::retry)?;
 
+        // Let the clients know that the user got logged out. This is scheduled
+        // before ending the sessions, so that they are taken into account
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                clock,
+                BackchannelLogoutJob::for_user(&user, clock.now()),
+            )
+            .await
+            .map_err(JobError::retry)?;
+
         // Kill all sessions for the user
         let n = repo
             .browser_session()
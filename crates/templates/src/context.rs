@@ -12,6 +12,7 @@ mod ext;
 mod features;
 
 use std::{
+    collections::BTreeMap,
     fmt::Formatter,
     net::{IpAddr, Ipv4Addr},
 };
@@ -28,7 +29,10 @@ use mas_data_model::{
 use mas_i18n::DataLocale;
 use mas_iana::jose::JsonWebSignatureAlg;
 use mas_router::{Account, GraphQL, PostAuthAction, UrlBuilder};
-use oauth2_types::scope::{OPENID, Scope};
+use oauth2_types::{
+    oidc::RpInitiatedLogoutRequest,
+    scope::{OPENID, Scope},
+};
 use rand::{
     Rng,
     distributions::{Alphanumeric, DistString},
@@ -654,6 +658,10 @@ pub struct ConsentContext {
     grant: AuthorizationGrant,
     client: Client,
     action: PostAuthAction,
+
+    /// The individual claims requested through the `claims` parameter, with
+    /// whether they are essential
+    claims: BTreeMap<String, bool>,
 }
 
 impl TemplateContext for ConsentContext {
@@ -663,16 +671,31 @@ impl TemplateContext for ConsentContext {
     {
         Client::samples(now, rng)
             .into_iter()
-            .map(|client| {
+            .enumerate()
+            .map(|(index, client)| {
                 let mut grant = AuthorizationGrant::sample(now, rng);
-                let action = PostAuthAction::continue_grant(grant.id);
                 // XXX
                 grant.client_id = client.id;
-                Self {
-                    grant,
-                    client,
-                    action,
+
+                // Have one of the samples request individual claims
+                if index == 0 {
+                    grant.requested_claims = Some(
+                        r#"{"id_token":{"email":{"essential":true}},"userinfo":{"preferred_username":null}}"#
+                            .parse()
+                            .unwrap(),
+                    );
                 }
+
+                // And another one request fine-grained authorization details
+                if index == 1 {
+                    grant.authorization_details = Some(
+                        r#"[{"type":"room_access","identifier":"!room:example.com","actions":["read","send"],"locations":["https://matrix.example.com/"]}]"#
+                            .parse()
+                            .unwrap(),
+                    );
+                }
+
+                Self::new(grant, client)
             })
             .collect()
     }
@@ -683,10 +706,67 @@ impl ConsentContext {
     #[must_use]
     pub fn new(grant: AuthorizationGrant, client: Client) -> Self {
         let action = PostAuthAction::continue_grant(grant.id);
+        let claims = grant
+            .requested_claims
+            .as_ref()
+            .map(|claims| {
+                claims
+                    .all_claims()
+                    .into_iter()
+                    .map(|(name, essential)| (name.to_owned(), essential))
+                    .collect()
+            })
+            .unwrap_or_default();
+
         Self {
             grant,
             client,
             action,
+            claims,
+        }
+    }
+}
+
+/// Context used by the `select_account.html` template
+#[derive(Serialize)]
+pub struct SelectAccountContext {
+    grant: AuthorizationGrant,
+    client: Client,
+    action: PostAuthAction,
+
+    /// The sessions signed in on this browser, to choose from
+    sessions: Vec<BrowserSession>,
+}
+
+impl TemplateContext for SelectAccountContext {
+    fn sample(now: chrono::DateTime<Utc>, rng: &mut impl Rng, _locales: &[DataLocale]) -> Vec<Self>
+    where
+        Self: Sized,
+    {
+        Client::samples(now, rng)
+            .into_iter()
+            .map(|client| {
+                let mut grant = AuthorizationGrant::sample(now, rng);
+                // XXX
+                grant.client_id = client.id;
+
+                let sessions = BrowserSession::samples(now, rng);
+                Self::new(grant, client, sessions)
+            })
+            .collect()
+    }
+}
+
+impl SelectAccountContext {
+    /// Constructs a context for the account chooser page
+    #[must_use]
+    pub fn new(grant: AuthorizationGrant, client: Client, sessions: Vec<BrowserSession>) -> Self {
+        let action = PostAuthAction::continue_grant(grant.id);
+        Self {
+            grant,
+            client,
+            action,
+            sessions,
         }
     }
 }
@@ -734,6 +814,9 @@ impl TemplateContext for PolicyViolationContext {
                         expires_at: now + Duration::try_minutes(25).unwrap(),
                         ip_address: None,
                         user_agent: None,
+                        user_id: None,
+                        binding_message: None,
+                        client_notification_token: None,
                     },
                     client,
                 );
@@ -1639,6 +1722,9 @@ impl TemplateContext for DeviceConsentContext {
                     expires_at: now + Duration::try_minutes(25).unwrap(),
                     ip_address: Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
                     user_agent: Some("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/93.0.0.0 Safari/537.36".to_owned()),
+                    user_id: None,
+                    binding_message: None,
+                    client_notification_token: None,
                 };
                 Self { grant, client }
             })
@@ -1646,6 +1732,85 @@ impl TemplateContext for DeviceConsentContext {
     }
 }
 
+/// Context used by the `pages/end_session.html` template
+#[derive(Serialize)]
+pub struct EndSessionContext {
+    client: Option<Client>,
+
+    /// The parameters of the logout request, passed along when confirming
+    params: RpInitiatedLogoutRequest,
+}
+
+impl EndSessionContext {
+    /// Constructs a context for the logout confirmation page
+    #[must_use]
+    pub fn new(client: Option<Client>, params: RpInitiatedLogoutRequest) -> Self {
+        Self { client, params }
+    }
+}
+
+impl TemplateContext for EndSessionContext {
+    fn sample(now: chrono::DateTime<Utc>, rng: &mut impl Rng, _locales: &[DataLocale]) -> Vec<Self>
+    where
+        Self: Sized,
+    {
+        let samples = Client::samples(now, rng).into_iter().map(|client| {
+            let params = RpInitiatedLogoutRequest {
+                client_id: Some(client.client_id.clone()),
+                post_logout_redirect_uri: client.post_logout_redirect_uris.first().cloned(),
+                state: Some("state".to_owned()),
+                ..RpInitiatedLogoutRequest::default()
+            };
+            Self::new(Some(client), params)
+        });
+
+        // Also have a sample for a logout request from an unknown client
+        samples
+            .chain(std::iter::once(Self::new(
+                None,
+                RpInitiatedLogoutRequest::default(),
+            )))
+            .collect()
+    }
+}
+
+/// Context used by the `pages/frontchannel_logout.html` template
+#[derive(Serialize)]
+pub struct FrontchannelLogoutContext {
+    /// The front-channel logout URIs of the clients to log out from
+    logout_uris: Vec<Url>,
+
+    /// Where to send the user once the clients were loaded
+    redirect_uri: String,
+}
+
+impl FrontchannelLogoutContext {
+    /// Constructs a context for the front-channel logout page
+    #[must_use]
+    pub fn new(logout_uris: Vec<Url>, redirect_uri: String) -> Self {
+        Self {
+            logout_uris,
+            redirect_uri,
+        }
+    }
+}
+
+impl TemplateContext for FrontchannelLogoutContext {
+    fn sample(now: chrono::DateTime<Utc>, rng: &mut impl Rng, _locales: &[DataLocale]) -> Vec<Self>
+    where
+        Self: Sized,
+    {
+        let issuer = Url::parse("https://example.com/").unwrap();
+        let session_id = Ulid::from_datetime_with_source(now.into(), rng);
+        let logout_uris = Client::samples(now, rng)
+            .iter()
+            .filter_map(|client| client.frontchannel_logout_uri_for(&issuer, session_id))
+            .collect();
+
+        vec![Self::new(logout_uris, "/login".to_owned())]
+    }
+}
+
 /// Context used by the `account/deactivated.html` and `account/locked.html`
 /// templates
 #[derive(Serialize)]
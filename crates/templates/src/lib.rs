@@ -36,17 +36,19 @@ pub use self::{
     context::{
         AccountInactiveContext, ApiDocContext, AppContext, CompatSsoContext, ConsentContext,
         DeviceConsentContext, DeviceLinkContext, DeviceLinkFormField, DeviceNameContext,
-        EmailRecoveryContext, EmailVerificationContext, EmptyContext, ErrorContext,
-        FormPostContext, IndexContext, LoginContext, LoginFormField, NotFoundContext,
-        PasswordRegisterContext, PolicyViolationContext, PostAuthContext, PostAuthContextInner,
-        RecoveryExpiredContext, RecoveryFinishContext, RecoveryFinishFormField,
-        RecoveryProgressContext, RecoveryStartContext, RecoveryStartFormField, RegisterContext,
-        RegisterFormField, RegisterStepsDisplayNameContext, RegisterStepsDisplayNameFormField,
+        EmailRecoveryContext, EmailVerificationContext, EmptyContext, EndSessionContext,
+        ErrorContext, FormPostContext, FrontchannelLogoutContext, IndexContext, LoginContext,
+        LoginFormField, NotFoundContext, PasswordRegisterContext, PolicyViolationContext,
+        PostAuthContext, PostAuthContextInner, RecoveryExpiredContext, RecoveryFinishContext,
+        RecoveryFinishFormField, RecoveryProgressContext, RecoveryStartContext,
+        RecoveryStartFormField, RegisterContext, RegisterFormField,
+        RegisterStepsDisplayNameContext, RegisterStepsDisplayNameFormField,
         RegisterStepsEmailInUseContext, RegisterStepsRegistrationTokenContext,
         RegisterStepsRegistrationTokenFormField, RegisterStepsVerifyEmailContext,
-        RegisterStepsVerifyEmailFormField, SiteBranding, SiteConfigExt, SiteFeatures,
-        TemplateContext, UpstreamExistingLinkContext, UpstreamRegister, UpstreamRegisterFormField,
-        UpstreamSuggestLink, WithCaptcha, WithCsrf, WithLanguage, WithOptionalSession, WithSession,
+        RegisterStepsVerifyEmailFormField, SelectAccountContext, SiteBranding, SiteConfigExt,
+        SiteFeatures, TemplateContext, UpstreamExistingLinkContext, UpstreamRegister,
+        UpstreamRegisterFormField, UpstreamSuggestLink, WithCaptcha, WithCsrf, WithLanguage,
+        WithOptionalSession, WithSession,
     },
     forms::{FieldError, FormError, FormField, FormState, ToFormState},
 };
@@ -416,6 +418,15 @@ register_templates! {
     /// Render the device code consent page
     pub fn render_device_consent(WithLanguage<WithCsrf<WithSession<DeviceConsentContext>>>) { "pages/device_consent.html" }
 
+    /// Render the account chooser page
+    pub fn render_select_account(WithLanguage<WithCsrf<WithOptionalSession<SelectAccountContext>>>) { "pages/select_account.html" }
+
+    /// Render the RP-initiated logout confirmation page
+    pub fn render_end_session(WithLanguage<WithCsrf<WithSession<EndSessionContext>>>) { "pages/end_session.html" }
+
+    /// Render the page loading the front-channel logout URIs of the clients
+    pub fn render_frontchannel_logout(WithLanguage<FrontchannelLogoutContext>) { "pages/frontchannel_logout.html" }
+
     /// Render the 'account deactivated' page
     pub fn render_account_deactivated(WithLanguage<WithCsrf<AccountInactiveContext>>) { "pages/account/deactivated.html" }
 
@@ -476,6 +487,9 @@ impl Templates {
         check::render_upstream_oauth2_do_register(self, now, rng)?;
         check::render_device_link(self, now, rng)?;
         check::render_device_consent(self, now, rng)?;
+        check::render_select_account(self, now, rng)?;
+        check::render_end_session(self, now, rng)?;
+        check::render_frontchannel_logout(self, now, rng)?;
         check::render_account_deactivated(self, now, rng)?;
         check::render_account_locked(self, now, rng)?;
         check::render_account_logged_out(self, now, rng)?;
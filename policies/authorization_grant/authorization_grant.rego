@@ -34,6 +34,17 @@ interactive_grant_type("authorization_code") := true
 
 interactive_grant_type("urn:ietf:params:oauth:grant-type:device_code") := true
 
+# Grants which issue tokens on behalf of a user, even if they are not present
+user_grant_type(grant_type) if {
+	interactive_grant_type(grant_type)
+}
+
+user_grant_type("urn:ietf:params:oauth:grant-type:jwt-bearer") := true
+
+# The user approves backchannel authentication requests from another device,
+# so they are not considered interactive
+user_grant_type("urn:openid:params:grant-type:ciba") := true
+
 # Special case to make empty scope work
 allowed_scope("") := true
 
@@ -68,13 +79,13 @@ allowed_scope("urn:mas:admin") if {
 
 allowed_scope(scope) if {
 	# Grant access to the C-S API only if there is a user
-	interactive_grant_type(input.grant_type)
+	user_grant_type(input.grant_type)
 	regex.match(`^urn:matrix:org.matrix.msc2967.client:device:[A-Za-z0-9._~!$&'()*+,;=:@/-]{10,}$`, scope)
 }
 
 allowed_scope("urn:matrix:org.matrix.msc2967.client:api:*") if {
 	# Grant access to the C-S API only if there is a user
-	interactive_grant_type(input.grant_type)
+	user_grant_type(input.grant_type)
 }
 
 # METADATA
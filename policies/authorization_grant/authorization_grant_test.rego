@@ -92,6 +92,40 @@ test_device_scopes if {
 		with input.scope as "urn:matrix:org.matrix.msc2967.client:device:AAbbCCdd01"
 }
 
+test_jwt_bearer_scopes if {
+	authorization_grant.allow with input.user as user
+		with input.client as client
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:jwt-bearer"
+		with input.scope as "urn:matrix:org.matrix.msc2967.client:api:* urn:matrix:org.matrix.msc2967.client:device:AAbbCCdd01"
+
+	# Admin scopes require the user to be present
+	not authorization_grant.allow with input.user as user
+		with input.client as client
+		with data.admin_users as ["john"]
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:jwt-bearer"
+		with input.scope as "urn:synapse:admin:*"
+
+	not authorization_grant.allow with input.user as user
+		with input.client as client
+		with data.admin_users as ["john"]
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:jwt-bearer"
+		with input.scope as "urn:mas:admin"
+}
+
+test_ciba_scopes if {
+	authorization_grant.allow with input.user as user
+		with input.client as client
+		with input.grant_type as "urn:openid:params:grant-type:ciba"
+		with input.scope as "openid urn:matrix:org.matrix.msc2967.client:api:* urn:matrix:org.matrix.msc2967.client:device:AAbbCCdd01"
+
+	# Admin scopes require an interactive grant
+	not authorization_grant.allow with input.user as user
+		with input.client as client
+		with data.admin_users as ["john"]
+		with input.grant_type as "urn:openid:params:grant-type:ciba"
+		with input.scope as "urn:synapse:admin:*"
+}
+
 test_synapse_admin_scopes if {
 	some grant_type in ["authorization_code", "urn:ietf:params:oauth:grant-type:device_code"]
 
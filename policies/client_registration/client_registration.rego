@@ -182,11 +182,21 @@ violation contains {"msg": "logo_uri not on the same host as the client_uri"} if
 	not host_matches_client_uri(input.client_metadata.logo_uri)
 }
 
+violation contains {"msg": "missing software statement"} if {
+	data.client_registration.require_software_statement
+	not input.software_statement
+}
+
 violation contains {"msg": "client_credentials grant_type requires some form of client authentication"} if {
 	uses_grant_type("client_credentials", input.client_metadata)
 	is_public_client
 }
 
+violation contains {"msg": "token exchange grant_type requires some form of client authentication"} if {
+	uses_grant_type("urn:ietf:params:oauth:grant-type:token-exchange", input.client_metadata)
+	is_public_client
+}
+
 violation contains {"msg": "missing redirect_uris"} if {
 	requires_redirect_uris
 	not input.client_metadata.redirect_uris
@@ -402,6 +402,22 @@ test_client_credentials_grant if {
 	}
 }
 
+test_token_exchange_grant if {
+	# Allowed for confidential clients
+	client_registration.allow with input.client_metadata as {
+		"grant_types": ["urn:ietf:params:oauth:grant-type:token-exchange"],
+		"token_endpoint_auth_method": "client_secret_basic",
+		"client_uri": "https://example.com/",
+	}
+
+	# Disallowed for public clients
+	not client_registration.allow with input.client_metadata as {
+		"grant_types": ["urn:ietf:params:oauth:grant-type:token-exchange"],
+		"token_endpoint_auth_method": "none",
+		"client_uri": "https://example.com/",
+	}
+}
+
 test_is_subdomain if {
 	client_registration.is_subdomain("example.com", "example.com")
 	client_registration.is_subdomain("example.com", "app.example.com")
@@ -425,3 +441,26 @@ test_parse_uri if {
 	client_uri_query.port == "8080"
 	client_uri_query.query == "?query=test"
 }
+
+test_software_statement if {
+	client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+	}
+
+	not client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+	}
+		with client_registration.require_software_statement as true
+
+	client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+	}
+		with input.software_statement as {
+			"iss": "https://publisher.example.com/",
+			"software_id": "example-app",
+		}
+		with client_registration.require_software_statement as true
+}
@@ -0,0 +1,48 @@
+# Copyright 2025 New Vector Ltd.
+#
+# SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+# Please see LICENSE files in the repository root for full details.
+
+# METADATA
+# schemas:
+#   - input: schema["token_exchange_input"]
+package token_exchange
+
+import rego.v1
+
+import data.common
+
+default allow := false
+
+allow if {
+	count(violation) == 0
+}
+
+# Clients can always exchange the tokens they were issued themselves
+can_exchange_token if {
+	input.client.id == input.subject_client.id
+}
+
+# Trusted clients can exchange tokens issued to other clients
+can_exchange_token if {
+	some client in data.token_exchange_clients
+	input.client.id == client
+}
+
+# METADATA
+# entrypoint: true
+violation contains {"msg": "client is not allowed to exchange this token"} if {
+	not can_exchange_token
+}
+
+violation contains {"msg": "only one device scope is allowed at a time"} if {
+	scope_list := split(input.scope, " ")
+	count({scope | some scope in scope_list; startswith(scope, "urn:matrix:org.matrix.msc2967.client:device:")}) > 1
+}
+
+violation contains {"msg": sprintf(
+	"Requester [%s] isn't allowed to do this action",
+	[common.format_requester(input.requester)],
+)} if {
+	common.requester_banned(input.requester, data.requester)
+}
@@ -0,0 +1,51 @@
+# Copyright 2025 New Vector Ltd.
+#
+# SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+# Please see LICENSE files in the repository root for full details.
+
+package token_exchange_test
+
+import data.token_exchange
+import rego.v1
+
+user := {"username": "john"}
+
+client := {"id": "client"}
+
+backend := {"id": "backend"}
+
+test_same_client if {
+	token_exchange.allow with input.user as user
+		with input.client as client
+		with input.subject_client as client
+		with input.scope as "openid"
+}
+
+test_other_client if {
+	not token_exchange.allow with input.user as user
+		with input.client as backend
+		with input.subject_client as client
+		with input.scope as "openid"
+
+	token_exchange.allow with input.user as user
+		with input.client as backend
+		with input.subject_client as client
+		with input.scope as "openid"
+		with data.token_exchange_clients as ["backend"]
+}
+
+test_device_scopes if {
+	not token_exchange.allow with input.user as user
+		with input.client as client
+		with input.subject_client as client
+		with input.scope as "urn:matrix:org.matrix.msc2967.client:device:AAbbCCdd01 urn:matrix:org.matrix.msc2967.client:device:AAbbCCdd02"
+}
+
+test_requester_banned if {
+	not token_exchange.allow with input.user as user
+		with input.client as client
+		with input.subject_client as client
+		with input.scope as "openid"
+		with input.requester as {"ip_address": "1.1.1.1"}
+		with data.requester.banned_ips as ["1.1.1.1"]
+}